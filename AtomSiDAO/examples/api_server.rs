@@ -4,8 +4,9 @@
 
 use std::sync::Arc;
 use std::net::SocketAddr;
+use std::time::Duration;
 
-use atomsidao::api::{ApiServer, ApiConfig};
+use atomsidao::api::{ApiServer, ApiConfig, api_middleware::BodyLimits, websocket::KeepaliveConfig};
 use atomsidao::DAOContext;
 use atomsidao::database::{DatabaseAdapter, PostgresAdapter, SqliteAdapter};
 use atomsidao::blockchain::{BlockchainAdapter, EthereumAdapter};
@@ -55,6 +56,17 @@ async fn main() -> anyhow::Result<()> {
         enable_cors: config.api.enable_cors,
         enable_logging: true,
         enable_docs: true,
+        ip_filter: config.api.ip_filter.clone(),
+        body_limits: BodyLimits {
+            max_body_bytes: config.api.max_body_bytes,
+            max_json_depth: config.api.max_json_depth,
+            max_json_nodes: config.api.max_json_nodes,
+        },
+        websocket_keepalive: KeepaliveConfig {
+            idle_timeout: Duration::from_secs(config.api.websocket_idle_timeout_seconds),
+            ping_interval: Duration::from_secs(config.api.websocket_ping_interval_seconds),
+        },
+        http_cache: config.api.http_cache.clone(),
     };
     
     // Create and start API server
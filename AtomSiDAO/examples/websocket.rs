@@ -47,6 +47,10 @@ async fn main() -> anyhow::Result<()> {
         enable_logging: true,
         enable_docs: true,
         enable_websockets: true,
+        ip_filter: None,
+        body_limits: Default::default(),
+        websocket_keepalive: Default::default(),
+        http_cache: Default::default(),
     };
     
     // Create API server
@@ -0,0 +1,12 @@
+-- Proposal discussion comments; deleted comments keep their row with an empty body
+CREATE TABLE IF NOT EXISTS proposal_comments (
+    id VARCHAR(100) PRIMARY KEY,
+    proposal_id VARCHAR(100) NOT NULL REFERENCES proposals(id) ON DELETE CASCADE,
+    parent_comment_id VARCHAR(100) REFERENCES proposal_comments(id),
+    author VARCHAR(100) NOT NULL,
+    body TEXT NOT NULL,
+    created_at BIGINT NOT NULL,
+    deleted_at BIGINT
+);
+
+CREATE INDEX IF NOT EXISTS idx_proposal_comments_proposal ON proposal_comments(proposal_id);
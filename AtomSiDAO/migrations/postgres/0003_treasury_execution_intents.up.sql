@@ -0,0 +1,6 @@
+-- On-chain treasury executions submitted but not yet settled
+CREATE TABLE IF NOT EXISTS treasury_execution_intents (
+    transaction_id VARCHAR(100) PRIMARY KEY REFERENCES treasury_transactions(id),
+    tx_hash VARCHAR(66),
+    created_at BIGINT NOT NULL
+);
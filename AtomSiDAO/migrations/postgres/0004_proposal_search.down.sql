@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_proposals_search;
+ALTER TABLE proposals DROP COLUMN IF EXISTS search_vector;
@@ -0,0 +1,6 @@
+-- Full-text search document; the title weighs more than the description
+ALTER TABLE proposals ADD COLUMN IF NOT EXISTS search_vector tsvector GENERATED ALWAYS AS (
+    setweight(to_tsvector('english', title), 'A') || setweight(to_tsvector('english', description), 'B')
+) STORED;
+
+CREATE INDEX IF NOT EXISTS idx_proposals_search ON proposals USING GIN (search_vector);
@@ -0,0 +1,6 @@
+ALTER TABLE treasury_transactions DROP COLUMN IF EXISTS deleted_at;
+ALTER TABLE treasury_transactions DROP COLUMN IF EXISTS updated_by;
+ALTER TABLE proposals DROP COLUMN IF EXISTS deleted_at;
+ALTER TABLE proposals DROP COLUMN IF EXISTS updated_by;
+ALTER TABLE members DROP COLUMN IF EXISTS deleted_at;
+ALTER TABLE members DROP COLUMN IF EXISTS updated_by;
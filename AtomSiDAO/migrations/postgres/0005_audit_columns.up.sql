@@ -0,0 +1,7 @@
+-- Soft deletion and last-writer audit columns on the core tables
+ALTER TABLE members ADD COLUMN IF NOT EXISTS updated_by VARCHAR(100);
+ALTER TABLE members ADD COLUMN IF NOT EXISTS deleted_at BIGINT;
+ALTER TABLE proposals ADD COLUMN IF NOT EXISTS updated_by VARCHAR(100);
+ALTER TABLE proposals ADD COLUMN IF NOT EXISTS deleted_at BIGINT;
+ALTER TABLE treasury_transactions ADD COLUMN IF NOT EXISTS updated_by VARCHAR(100);
+ALTER TABLE treasury_transactions ADD COLUMN IF NOT EXISTS deleted_at BIGINT;
@@ -0,0 +1,9 @@
+-- Single-use member invitations
+CREATE TABLE IF NOT EXISTS invitations (
+    id VARCHAR(100) PRIMARY KEY,
+    role VARCHAR(20) NOT NULL,
+    expires_at BIGINT NOT NULL,
+    created_at BIGINT NOT NULL,
+    accepted_by VARCHAR(42),
+    accepted_at BIGINT
+);
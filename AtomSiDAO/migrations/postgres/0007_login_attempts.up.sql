@@ -0,0 +1,7 @@
+-- Failed authentication attempts, for login lockout
+CREATE TABLE IF NOT EXISTS login_attempts (
+    address VARCHAR(42) PRIMARY KEY,
+    failed_attempts BIGINT NOT NULL,
+    locked_until BIGINT,
+    updated_at BIGINT NOT NULL
+);
@@ -0,0 +1,7 @@
+-- Progress of contract event pollers
+CREATE TABLE IF NOT EXISTS event_checkpoints (
+    poller TEXT PRIMARY KEY,
+    next_block BIGINT NOT NULL,
+    block_range BIGINT NOT NULL,
+    updated_at BIGINT NOT NULL
+);
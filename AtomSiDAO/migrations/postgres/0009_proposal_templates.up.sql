@@ -0,0 +1,9 @@
+-- Reusable proposal presets
+CREATE TABLE IF NOT EXISTS proposal_templates (
+    id VARCHAR(100) PRIMARY KEY,
+    name VARCHAR(100) NOT NULL UNIQUE,
+    data JSONB NOT NULL,
+    created_at BIGINT NOT NULL,
+    updated_at BIGINT NOT NULL,
+    updated_by VARCHAR(100) NOT NULL
+);
@@ -0,0 +1,11 @@
+-- Conviction voting stakes, one per proposal and voter
+CREATE TABLE IF NOT EXISTS conviction (
+    proposal_id VARCHAR(100) NOT NULL,
+    voter VARCHAR(42) NOT NULL,
+    stake BIGINT NOT NULL,
+    conviction BIGINT NOT NULL,
+    updated_at BIGINT NOT NULL,
+    PRIMARY KEY (proposal_id, voter)
+);
+
+CREATE INDEX IF NOT EXISTS idx_conviction_voter ON conviction(voter);
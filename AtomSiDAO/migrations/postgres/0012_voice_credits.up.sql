@@ -0,0 +1,19 @@
+-- Voice credits spent on quadratic votes, one per voter, epoch and proposal
+CREATE TABLE IF NOT EXISTS voice_credit_usage (
+    voter VARCHAR(42) NOT NULL,
+    epoch BIGINT NOT NULL,
+    proposal_id VARCHAR(100) NOT NULL,
+    credits BIGINT NOT NULL,
+    created_at BIGINT NOT NULL,
+    PRIMARY KEY (voter, epoch, proposal_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_voice_credit_usage_epoch ON voice_credit_usage(epoch);
+
+-- Each voter's voice credit allocation for an epoch, written with every spend
+CREATE TABLE IF NOT EXISTS voice_credit_budgets (
+    voter VARCHAR(42) NOT NULL,
+    epoch BIGINT NOT NULL,
+    credits BIGINT NOT NULL,
+    PRIMARY KEY (voter, epoch)
+);
@@ -0,0 +1,41 @@
+ALTER TABLE treasury_transactions
+    DROP COLUMN IF EXISTS updated_at,
+    DROP COLUMN IF EXISTS data,
+    ALTER COLUMN created_at DROP DEFAULT,
+    ALTER COLUMN created_at TYPE BIGINT USING EXTRACT(EPOCH FROM created_at)::BIGINT,
+    ADD COLUMN IF NOT EXISTS description TEXT NOT NULL,
+    ADD COLUMN IF NOT EXISTS recipient_address VARCHAR(42) NOT NULL,
+    ADD COLUMN IF NOT EXISTS token_symbol VARCHAR(10) NOT NULL,
+    ADD COLUMN IF NOT EXISTS amount BIGINT NOT NULL,
+    ADD COLUMN IF NOT EXISTS executed_at BIGINT,
+    ADD COLUMN IF NOT EXISTS required_approvals INTEGER NOT NULL,
+    ADD COLUMN IF NOT EXISTS current_approvals INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS related_proposal_id VARCHAR(100) REFERENCES proposals(id),
+    ADD COLUMN IF NOT EXISTS metadata JSONB;
+
+ALTER TABLE proposals
+    DROP COLUMN IF EXISTS updated_at,
+    DROP COLUMN IF EXISTS state,
+    DROP COLUMN IF EXISTS data,
+    ALTER COLUMN created_at DROP DEFAULT,
+    ALTER COLUMN created_at TYPE BIGINT USING EXTRACT(EPOCH FROM created_at)::BIGINT,
+    ADD COLUMN IF NOT EXISTS proposer_id VARCHAR(100) NOT NULL REFERENCES members(id),
+    ADD COLUMN IF NOT EXISTS proposal_type VARCHAR(50) NOT NULL,
+    ADD COLUMN IF NOT EXISTS status VARCHAR(20) NOT NULL,
+    ADD COLUMN IF NOT EXISTS voting_starts_at BIGINT,
+    ADD COLUMN IF NOT EXISTS voting_ends_at BIGINT,
+    ADD COLUMN IF NOT EXISTS executed_at BIGINT,
+    ADD COLUMN IF NOT EXISTS execution_data JSONB,
+    ADD COLUMN IF NOT EXISTS metadata JSONB;
+
+ALTER TABLE members
+    DROP COLUMN IF EXISTS updated_at,
+    DROP COLUMN IF EXISTS created_at,
+    DROP COLUMN IF EXISTS data,
+    ADD COLUMN IF NOT EXISTS name VARCHAR(100) NOT NULL,
+    ADD COLUMN IF NOT EXISTS role VARCHAR(20) NOT NULL,
+    ADD COLUMN IF NOT EXISTS status VARCHAR(20) NOT NULL,
+    ADD COLUMN IF NOT EXISTS reputation INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS joined_at BIGINT NOT NULL,
+    ADD COLUMN IF NOT EXISTS last_active_at BIGINT NOT NULL,
+    ADD COLUMN IF NOT EXISTS metadata JSONB;
@@ -0,0 +1,44 @@
+-- Store members, proposals and treasury transactions as the JSON documents
+-- the managers read and write, next to the columns they query on. The
+-- managers never wrote the column-per-field layout, so no rows carry over.
+ALTER TABLE members
+    DROP COLUMN IF EXISTS name,
+    DROP COLUMN IF EXISTS role,
+    DROP COLUMN IF EXISTS status,
+    DROP COLUMN IF EXISTS reputation,
+    DROP COLUMN IF EXISTS joined_at,
+    DROP COLUMN IF EXISTS last_active_at,
+    DROP COLUMN IF EXISTS metadata,
+    ADD COLUMN IF NOT EXISTS data JSONB NOT NULL,
+    ADD COLUMN IF NOT EXISTS created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    ADD COLUMN IF NOT EXISTS updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP;
+
+ALTER TABLE proposals
+    DROP COLUMN IF EXISTS proposer_id,
+    DROP COLUMN IF EXISTS proposal_type,
+    DROP COLUMN IF EXISTS status,
+    DROP COLUMN IF EXISTS voting_starts_at,
+    DROP COLUMN IF EXISTS voting_ends_at,
+    DROP COLUMN IF EXISTS executed_at,
+    DROP COLUMN IF EXISTS execution_data,
+    DROP COLUMN IF EXISTS metadata,
+    ADD COLUMN IF NOT EXISTS data JSONB NOT NULL,
+    ADD COLUMN IF NOT EXISTS state VARCHAR(20) NOT NULL,
+    ALTER COLUMN created_at TYPE TIMESTAMPTZ USING to_timestamp(created_at),
+    ALTER COLUMN created_at SET DEFAULT CURRENT_TIMESTAMP,
+    ADD COLUMN IF NOT EXISTS updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP;
+
+ALTER TABLE treasury_transactions
+    DROP COLUMN IF EXISTS description,
+    DROP COLUMN IF EXISTS recipient_address,
+    DROP COLUMN IF EXISTS token_symbol,
+    DROP COLUMN IF EXISTS amount,
+    DROP COLUMN IF EXISTS executed_at,
+    DROP COLUMN IF EXISTS required_approvals,
+    DROP COLUMN IF EXISTS current_approvals,
+    DROP COLUMN IF EXISTS related_proposal_id,
+    DROP COLUMN IF EXISTS metadata,
+    ADD COLUMN IF NOT EXISTS data JSONB NOT NULL,
+    ALTER COLUMN created_at TYPE TIMESTAMPTZ USING to_timestamp(created_at),
+    ALTER COLUMN created_at SET DEFAULT CURRENT_TIMESTAMP,
+    ADD COLUMN IF NOT EXISTS updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP;
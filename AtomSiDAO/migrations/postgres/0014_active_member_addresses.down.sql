@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_members_address;
+ALTER TABLE members ADD CONSTRAINT members_address_key UNIQUE (address);
@@ -0,0 +1 @@
+ALTER TABLE treasury_idempotency_keys DROP COLUMN IF EXISTS request_hash;
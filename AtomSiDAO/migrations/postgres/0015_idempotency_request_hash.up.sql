@@ -0,0 +1,3 @@
+-- Hash of the request each idempotency key was used for, so a reused key
+-- with a different body can be refused
+ALTER TABLE treasury_idempotency_keys ADD COLUMN IF NOT EXISTS request_hash VARCHAR(64) NOT NULL DEFAULT '';
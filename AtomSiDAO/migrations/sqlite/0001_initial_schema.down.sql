@@ -0,0 +1,18 @@
+DROP TABLE IF EXISTS admin_audit_log;
+DROP TABLE IF EXISTS vote_weight_overrides;
+DROP TABLE IF EXISTS delegations;
+DROP TABLE IF EXISTS notifications;
+DROP TABLE IF EXISTS blockchain_transactions;
+DROP TABLE IF EXISTS settings;
+DROP TABLE IF EXISTS sessions;
+DROP TABLE IF EXISTS token_transfers;
+DROP TABLE IF EXISTS token_balances;
+DROP TABLE IF EXISTS tokens;
+DROP TABLE IF EXISTS treasury_transaction_approvals;
+DROP TABLE IF EXISTS treasury_transactions;
+DROP TABLE IF EXISTS votes;
+DROP TABLE IF EXISTS proposal_commitments;
+DROP TABLE IF EXISTS proposal_sequence;
+DROP TABLE IF EXISTS proposals;
+DROP TABLE IF EXISTS member_activities;
+DROP TABLE IF EXISTS members;
@@ -0,0 +1,14 @@
+-- Proposal discussion comments; deleted comments keep their row with an empty body
+CREATE TABLE IF NOT EXISTS proposal_comments (
+    id TEXT PRIMARY KEY,
+    proposal_id TEXT NOT NULL,
+    parent_comment_id TEXT,
+    author TEXT NOT NULL,
+    body TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    deleted_at INTEGER,
+    FOREIGN KEY(proposal_id) REFERENCES proposals(id) ON DELETE CASCADE,
+    FOREIGN KEY(parent_comment_id) REFERENCES proposal_comments(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_proposal_comments_proposal ON proposal_comments(proposal_id);
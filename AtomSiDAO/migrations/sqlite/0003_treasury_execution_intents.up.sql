@@ -0,0 +1,7 @@
+-- On-chain treasury executions submitted but not yet settled
+CREATE TABLE IF NOT EXISTS treasury_execution_intents (
+    transaction_id TEXT PRIMARY KEY,
+    tx_hash TEXT,
+    created_at INTEGER NOT NULL,
+    FOREIGN KEY(transaction_id) REFERENCES treasury_transactions(id)
+);
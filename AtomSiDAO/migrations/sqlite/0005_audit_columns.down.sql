@@ -0,0 +1,6 @@
+ALTER TABLE treasury_transactions DROP COLUMN deleted_at;
+ALTER TABLE treasury_transactions DROP COLUMN updated_by;
+ALTER TABLE proposals DROP COLUMN deleted_at;
+ALTER TABLE proposals DROP COLUMN updated_by;
+ALTER TABLE members DROP COLUMN deleted_at;
+ALTER TABLE members DROP COLUMN updated_by;
@@ -0,0 +1,7 @@
+-- Soft deletion and last-writer audit columns on the core tables
+ALTER TABLE members ADD COLUMN updated_by TEXT;
+ALTER TABLE members ADD COLUMN deleted_at INTEGER;
+ALTER TABLE proposals ADD COLUMN updated_by TEXT;
+ALTER TABLE proposals ADD COLUMN deleted_at INTEGER;
+ALTER TABLE treasury_transactions ADD COLUMN updated_by TEXT;
+ALTER TABLE treasury_transactions ADD COLUMN deleted_at INTEGER;
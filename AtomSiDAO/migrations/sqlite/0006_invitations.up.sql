@@ -0,0 +1,9 @@
+-- Single-use member invitations
+CREATE TABLE IF NOT EXISTS invitations (
+    id TEXT PRIMARY KEY,
+    role TEXT NOT NULL,
+    expires_at INTEGER NOT NULL,
+    created_at INTEGER NOT NULL,
+    accepted_by TEXT,
+    accepted_at INTEGER
+);
@@ -0,0 +1,7 @@
+-- Failed authentication attempts, for login lockout
+CREATE TABLE IF NOT EXISTS login_attempts (
+    address TEXT PRIMARY KEY,
+    failed_attempts INTEGER NOT NULL,
+    locked_until INTEGER,
+    updated_at INTEGER NOT NULL
+);
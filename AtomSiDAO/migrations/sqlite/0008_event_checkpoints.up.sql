@@ -0,0 +1,7 @@
+-- Progress of contract event pollers
+CREATE TABLE IF NOT EXISTS event_checkpoints (
+    poller TEXT PRIMARY KEY,
+    next_block INTEGER NOT NULL,
+    block_range INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL
+);
@@ -0,0 +1,9 @@
+-- Reusable proposal presets
+CREATE TABLE IF NOT EXISTS proposal_templates (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    data TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    updated_by TEXT NOT NULL
+);
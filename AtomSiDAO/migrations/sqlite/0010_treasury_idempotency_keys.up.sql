@@ -0,0 +1,10 @@
+-- Idempotency keys of created treasury transactions
+CREATE TABLE IF NOT EXISTS treasury_idempotency_keys (
+    scope TEXT NOT NULL,
+    idempotency_key TEXT NOT NULL,
+    transaction_id TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    PRIMARY KEY (scope, idempotency_key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_treasury_idempotency_keys_created_at ON treasury_idempotency_keys(created_at);
@@ -0,0 +1,11 @@
+-- Conviction voting stakes, one per proposal and voter
+CREATE TABLE IF NOT EXISTS conviction (
+    proposal_id TEXT NOT NULL,
+    voter TEXT NOT NULL,
+    stake INTEGER NOT NULL,
+    conviction INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    PRIMARY KEY (proposal_id, voter)
+);
+
+CREATE INDEX IF NOT EXISTS idx_conviction_voter ON conviction(voter);
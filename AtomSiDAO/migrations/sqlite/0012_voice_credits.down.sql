@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS voice_credit_budgets;
+DROP TABLE IF EXISTS voice_credit_usage;
@@ -0,0 +1,62 @@
+CREATE TABLE treasury_transactions_columns (
+    id TEXT PRIMARY KEY,
+    description TEXT NOT NULL,
+    recipient_address TEXT NOT NULL,
+    token_symbol TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    status TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    executed_at INTEGER,
+    required_approvals INTEGER NOT NULL,
+    current_approvals INTEGER NOT NULL DEFAULT 0,
+    related_proposal_id TEXT,
+    metadata TEXT,
+    updated_by TEXT,
+    deleted_at INTEGER,
+    FOREIGN KEY(related_proposal_id) REFERENCES proposals(id)
+);
+
+DROP TABLE treasury_transactions;
+ALTER TABLE treasury_transactions_columns RENAME TO treasury_transactions;
+
+CREATE TABLE proposals_columns (
+    id TEXT PRIMARY KEY,
+    number INTEGER UNIQUE,
+    title TEXT NOT NULL,
+    description TEXT NOT NULL,
+    proposer_id TEXT NOT NULL,
+    proposal_type TEXT NOT NULL,
+    status TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    voting_starts_at INTEGER,
+    voting_ends_at INTEGER,
+    executed_at INTEGER,
+    execution_data TEXT,
+    metadata TEXT,
+    updated_by TEXT,
+    deleted_at INTEGER,
+    FOREIGN KEY(proposer_id) REFERENCES members(id)
+);
+
+DROP TABLE proposals;
+ALTER TABLE proposals_columns RENAME TO proposals;
+
+DROP INDEX IF EXISTS idx_members_address;
+
+CREATE TABLE members_columns (
+    id TEXT PRIMARY KEY,
+    address TEXT NOT NULL,
+    name TEXT NOT NULL,
+    role TEXT NOT NULL,
+    status TEXT NOT NULL,
+    reputation INTEGER NOT NULL DEFAULT 0,
+    joined_at INTEGER NOT NULL,
+    last_active_at INTEGER NOT NULL,
+    metadata TEXT,
+    updated_by TEXT,
+    deleted_at INTEGER,
+    UNIQUE(address)
+);
+
+DROP TABLE members;
+ALTER TABLE members_columns RENAME TO members;
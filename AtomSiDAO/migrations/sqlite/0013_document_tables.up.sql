@@ -0,0 +1,46 @@
+-- Store members, proposals and treasury transactions as the JSON documents
+-- the managers read and write, next to the columns they query on. The
+-- managers never wrote the column-per-field layout, so no rows carry over.
+-- SQLite can't drop NOT NULL columns, so each table is rebuilt.
+CREATE TABLE members_documents (
+    id TEXT PRIMARY KEY,
+    address TEXT NOT NULL,
+    data TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_by TEXT,
+    deleted_at INTEGER
+);
+
+DROP TABLE members;
+ALTER TABLE members_documents RENAME TO members;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_members_address ON members(address);
+
+CREATE TABLE proposals_documents (
+    id TEXT PRIMARY KEY,
+    number INTEGER UNIQUE, -- sequential display number, assigned on submission
+    data TEXT NOT NULL,
+    state TEXT NOT NULL,
+    title TEXT NOT NULL,
+    description TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_by TEXT,
+    deleted_at INTEGER
+);
+
+DROP TABLE proposals;
+ALTER TABLE proposals_documents RENAME TO proposals;
+
+CREATE TABLE treasury_transactions_documents (
+    id TEXT PRIMARY KEY,
+    data TEXT NOT NULL,
+    status TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_by TEXT,
+    deleted_at INTEGER
+);
+
+DROP TABLE treasury_transactions;
+ALTER TABLE treasury_transactions_documents RENAME TO treasury_transactions;
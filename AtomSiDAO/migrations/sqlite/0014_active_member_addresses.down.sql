@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_members_address;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_members_address ON members(address);
@@ -0,0 +1,4 @@
+-- A soft-deleted member keeps its row, so only live members need unique
+-- addresses; a deleted address can register again
+DROP INDEX IF EXISTS idx_members_address;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_members_address ON members(address) WHERE deleted_at IS NULL;
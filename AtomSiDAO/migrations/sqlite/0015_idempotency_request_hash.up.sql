@@ -0,0 +1,3 @@
+-- Hash of the request each idempotency key was used for, so a reused key
+-- with a different body can be refused
+ALTER TABLE treasury_idempotency_keys ADD COLUMN request_hash TEXT NOT NULL DEFAULT '';
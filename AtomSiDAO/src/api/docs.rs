@@ -29,20 +29,25 @@ pub fn create_docs_router() -> Router {
             
             // Governance routes
             crate::api::routes::governance::get_proposals,
+            crate::api::routes::governance::get_votable_proposals,
             crate::api::routes::governance::get_proposal,
             crate::api::routes::governance::create_proposal,
+            crate::api::routes::governance::sponsor_proposal,
             crate::api::routes::governance::vote_on_proposal,
+            crate::api::routes::governance::get_governance_parameters,
             
             // Treasury routes
             crate::api::routes::treasury::get_transactions,
             crate::api::routes::treasury::get_transaction,
             crate::api::routes::treasury::create_transaction,
             crate::api::routes::treasury::approve_transaction,
+            crate::api::routes::treasury::cancel_transaction,
             crate::api::routes::treasury::get_balances,
             
             // Identity routes
             crate::api::routes::identity::get_members,
             crate::api::routes::identity::get_member,
+            crate::api::routes::identity::get_voting_record,
             crate::api::routes::identity::get_activities,
             
             // Token routes
@@ -67,15 +72,24 @@ pub fn create_docs_router() -> Router {
                 // Member models
                 crate::api::models::MemberResponse,
                 crate::api::models::ActivityResponse,
+                crate::api::models::VotingRecordResponse,
+                crate::api::models::VotingRecordEntryResponse,
+                crate::api::models::VotingRecordSummaryResponse,
                 
                 // Proposal models
                 crate::api::models::CreateProposalRequest,
                 crate::api::models::ProposalResponse,
+                crate::api::models::AttachmentModel,
+                crate::api::models::SponsorResponse,
                 crate::api::models::VoteRequest,
                 crate::api::models::VoteResponse,
                 
+                // Governance models
+                crate::api::models::GovernanceParameterResponse,
+                
                 // Treasury models
                 crate::api::models::CreateTransactionRequest,
+                crate::api::models::CancelTransactionRequest,
                 crate::api::models::TransactionResponse,
                 crate::api::models::TokenBalanceResponse,
                 
@@ -0,0 +1,74 @@
+//! Request locale extraction for the AtomSi DAO API
+//!
+//! Human-readable response fields are formatted for the locale named by the
+//! `locale` query parameter, falling back to the `Accept-Language` header.
+
+use std::convert::Infallible;
+
+use axum::{
+    async_trait,
+    extract::FromRequestParts,
+    http::{header::ACCEPT_LANGUAGE, request::Parts},
+};
+
+use crate::utils::locale::Locale;
+
+/// Locale requested by the client
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RequestLocale(pub Locale);
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for RequestLocale {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let query_param = parts.uri.query().and_then(|query| {
+            query
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("locale="))
+                .and_then(Locale::parse)
+        });
+
+        let locale = query_param.unwrap_or_else(|| {
+            parts
+                .headers
+                .get(ACCEPT_LANGUAGE)
+                .and_then(|value| value.to_str().ok())
+                .map(Locale::from_accept_language)
+                .unwrap_or_default()
+        });
+
+        Ok(Self(locale))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::Request;
+
+    async fn extract(request: Request<()>) -> Locale {
+        let (mut parts, _) = request.into_parts();
+        RequestLocale::from_request_parts(&mut parts, &()).await.unwrap().0
+    }
+
+    #[tokio::test]
+    async fn test_query_parameter_overrides_header() {
+        let request = Request::builder()
+            .uri("/api/treasury/balance?locale=fr")
+            .header(ACCEPT_LANGUAGE, "de-DE")
+            .body(())
+            .unwrap();
+        assert_eq!(extract(request).await, Locale::FR);
+
+        let request = Request::builder()
+            .uri("/api/treasury/balance")
+            .header(ACCEPT_LANGUAGE, "de-DE, en;q=0.5")
+            .body(())
+            .unwrap();
+        assert_eq!(extract(request).await, Locale::DE);
+
+        let request = Request::builder().uri("/api/treasury/balance").body(()).unwrap();
+        assert_eq!(extract(request).await, Locale::EN);
+    }
+}
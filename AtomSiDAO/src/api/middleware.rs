@@ -2,22 +2,29 @@
 //!
 //! This module contains middleware components for the API server.
 
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
 use std::sync::Arc;
 
 use axum::{
-    http::{Request, StatusCode},
+    http::{header, HeaderMap, HeaderValue, Method, Request, StatusCode},
     middleware::Next,
-    response::Response,
-    extract::Extension,
-    body::Body,
+    response::{IntoResponse, Response},
+    extract::{ConnectInfo, Extension, MatchedPath, State},
+    body::{self, Body, Empty, Full},
     Json,
 };
+use hyper::body::HttpBody;
+use sha2::{Digest, Sha256};
 use tower_http::trace::{TraceLayer, DefaultMakeSpan, DefaultOnResponse};
-use tracing::Level;
+use tracing::{info_span, Instrument, Level, warn};
+use uuid::Uuid;
 
 use crate::DAOContext;
 use crate::api::models::ApiResponse;
-use crate::security::AuthManager;
+use crate::config::IpFilterConfig;
+use crate::error::{Error, Result as DaoResult};
+use crate::security::{AuthManager, AuthenticatedMember, PermissionManager};
 
 /// Create a tracing middleware layer for request logging
 pub fn create_trace_layer() -> TraceLayer<tower_http::classify::SharedClassifier<tower_http::classify::ServerErrorsAsFailures>> {
@@ -26,33 +33,265 @@ pub fn create_trace_layer() -> TraceLayer<tower_http::classify::SharedClassifier
         .on_response(DefaultOnResponse::new().level(Level::INFO))
 }
 
-/// Middleware for checking if user is authenticated
+/// Header carrying the request's correlation ID
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Longest client-supplied request ID that is kept
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+/// Correlation ID of a request, available to handlers as an extension
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(pub String);
+
+/// Middleware giving each request a correlation ID
+///
+/// An `X-Request-Id` sent by the client (or a proxy in front of the API) is
+/// kept if it is short and plain; otherwise a new UUID is generated. The ID
+/// is stored as a [`RequestId`] extension, recorded on a `request` span so
+/// every log line within the request carries it, and echoed in the response.
+pub async fn request_id<B>(mut req: Request<B>, next: Next<B>) -> Response {
+    let id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| is_valid_request_id(id))
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+    
+    req.extensions_mut().insert(RequestId(id.clone()));
+    let span = info_span!(
+        "request",
+        request_id = %id,
+        method = %req.method(),
+        path = %req.uri().path(),
+    );
+    
+    let mut response = next.run(req).instrument(span).await;
+    // Validated or generated above, so always a valid header value
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    
+    response
+}
+
+/// Whether a client-supplied request ID is safe to log and echo
+fn is_valid_request_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LENGTH
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// Cookie carrying a session ID or JWT for browser clients
+pub const SESSION_COOKIE: &str = "atomsi_session";
+
+/// Resolves request credentials to the member making the request
+#[async_trait::async_trait]
+pub trait RequestAuthenticator: Send + Sync {
+    /// Authenticate a bearer token or session cookie value
+    async fn authenticate(&self, credential: &str) -> crate::core::Result<AuthenticatedMember>;
+}
+
+#[async_trait::async_trait]
+impl RequestAuthenticator for AuthManager {
+    async fn authenticate(&self, credential: &str) -> crate::core::Result<AuthenticatedMember> {
+        self.authenticate_request(credential).await
+    }
+}
+
+/// Get the credential from `Authorization: Bearer` or the session cookie
+///
+/// The header takes precedence when both are present.
+pub(crate) fn request_credential(headers: &HeaderMap) -> Option<&str> {
+    if let Some(value) = headers.get(header::AUTHORIZATION) {
+        return value.to_str().ok()?.strip_prefix("Bearer ").map(str::trim);
+    }
+    
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|cookie| cookie.trim().split_once('='))
+        .find(|(name, _)| *name == SESSION_COOKIE)
+        .map(|(_, value)| value)
+}
+
+/// Middleware requiring an authenticated member
+///
+/// Reads a JWT or session ID from `Authorization: Bearer` or the
+/// `atomsi_session` cookie and resolves it through the authenticator. On
+/// success the [`AuthenticatedMember`] is added to the request extensions for
+/// handlers; a missing or invalid credential gets 401 Unauthorized, and a
+/// locked-out address 429 Too Many Requests with `Retry-After`. Without an
+/// authenticator every request is refused.
 pub async fn require_auth<B>(
-    Extension(context): Extension<Arc<DAOContext>>,
-    req: Request<B>,
+    State(authenticator): State<Option<Arc<dyn RequestAuthenticator>>>,
+    mut req: Request<B>,
     next: Next<B>,
-) -> Result<Response, StatusCode> {
-    // Get the authorization header
-    let auth_header = req.headers()
-        .get("Authorization")
-        .and_then(|header| header.to_str().ok())
-        .ok_or(StatusCode::UNAUTHORIZED)?;
+) -> Response {
+    let unauthorized = || {
+        (
+            StatusCode::UNAUTHORIZED,
+            Json(ApiResponse::<()>::error_with_code("Unauthorized: Authentication required", 401)),
+        )
+            .into_response()
+    };
     
-    // Check if token is valid
-    // This is a placeholder implementation
-    // In a real implementation, we would call the auth service to validate the token
+    let Some(authenticator) = authenticator else {
+        warn!("Rejected request to {}: no authenticator configured", req.uri().path());
+        return unauthorized();
+    };
+    let credential = request_credential(req.headers())
+        .filter(|credential| !credential.is_empty())
+        .map(str::to_string);
+    let Some(credential) = credential else {
+        return unauthorized();
+    };
     
-    // Extract the token from the header
-    let token = auth_header.strip_prefix("Bearer ")
-        .ok_or(StatusCode::UNAUTHORIZED)?;
+    match authenticator.authenticate(&credential).await {
+        Ok(member) => {
+            req.extensions_mut().insert(member);
+            next.run(req).await
+        }
+        Err(crate::core::DaoError::RateLimitError { retry_after_seconds }) => too_many_requests(
+            &format!("Too many failed attempts, retry after {} seconds", retry_after_seconds),
+            retry_after_seconds,
+        ),
+        Err(_) => unauthorized(),
+    }
+}
+
+/// 429 Too Many Requests with `Retry-After`
+pub fn too_many_requests(message: &str, retry_after_seconds: u64) -> Response {
+    let mut response = (
+        StatusCode::TOO_MANY_REQUESTS,
+        Json(ApiResponse::<()>::error_with_code(message, 429)),
+    )
+        .into_response();
+    response
+        .headers_mut()
+        .insert(header::RETRY_AFTER, HeaderValue::from(retry_after_seconds));
+    response
+}
+
+/// Permission a route requires beyond being signed in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoutePermission {
+    /// HTTP method
+    pub method: Method,
+    /// Route path as registered under `/api`
+    pub path: &'static str,
+    /// Resource checked with [`PermissionManager::has_permission`]
+    pub resource: &'static str,
+    /// Permission required on the resource
+    pub permission: &'static str,
+}
+
+const fn route(method: Method, path: &'static str, resource: &'static str, permission: &'static str) -> RoutePermission {
+    RoutePermission { method, path, resource, permission }
+}
+
+/// Permissions required by the protected API routes
+///
+/// Routes opt in by being listed here; a protected route that isn't listed
+/// only requires authentication.
+pub const ROUTE_PERMISSIONS: &[RoutePermission] = &[
+    route(Method::GET, "/governance/proposals", "proposal", "read"),
+    route(Method::POST, "/governance/proposals", "proposal", "create"),
+    route(Method::GET, "/governance/proposals/votable", "proposal", "read"),
+    route(Method::GET, "/governance/proposals/:id", "proposal", "read"),
+    route(Method::POST, "/governance/proposals/:id/sponsor", "proposal", "create"),
+    route(Method::POST, "/governance/proposals/:id/vote", "vote", "create"),
+    route(Method::GET, "/governance/parameters", "settings", "read"),
+    route(Method::GET, "/treasury/transactions", "treasury", "read"),
+    route(Method::POST, "/treasury/transactions", "treasury", "create"),
+    route(Method::GET, "/treasury/transactions/:id", "treasury", "read"),
+    route(Method::POST, "/treasury/transactions/:id/approve", "treasury", "update"),
+    route(Method::POST, "/treasury/transactions/:id/cancel", "treasury", "update"),
+    route(Method::GET, "/treasury/balances", "treasury", "read"),
+    route(Method::GET, "/identity/members", "member", "read"),
+    route(Method::GET, "/identity/members/:id", "member", "read"),
+    route(Method::GET, "/identity/members/:id/voting-record", "member", "read"),
+    route(Method::GET, "/identity/activities", "member", "read"),
+    route(Method::GET, "/token/tokens", "token", "read"),
+    route(Method::GET, "/token/tokens/:id", "token", "read"),
+    route(Method::GET, "/token/tokens/:id/balances", "token", "read"),
+    route(Method::POST, "/token/transfer", "token", "create"),
+];
+
+/// Route permissions checked by [`authorize`]
+#[derive(Clone)]
+pub struct RouteAuthorization {
+    /// Role permissions
+    permissions: Arc<PermissionManager>,
+    /// Permissions required per route
+    routes: &'static [RoutePermission],
+}
+
+impl RouteAuthorization {
+    /// Check the routes in [`ROUTE_PERMISSIONS`] against the given role
+    /// permissions
+    pub fn new(permissions: Arc<PermissionManager>) -> Self {
+        Self::with_routes(permissions, ROUTE_PERMISSIONS)
+    }
     
-    // Try to authenticate with the token
-    // In a real implementation, this would verify the token and get the user
-    // For now, we just pass through all requests
+    /// Check the given routes against the given role permissions
+    pub fn with_routes(permissions: Arc<PermissionManager>, routes: &'static [RoutePermission]) -> Self {
+        Self { permissions, routes }
+    }
     
-    // If the token is valid, continue to the next middleware or handler
-    let response = next.run(req).await;
-    Ok(response)
+    /// Get the permission a route requires, if any
+    ///
+    /// `path` is the matched route path, with or without the `/api` prefix.
+    pub fn required(&self, method: &Method, path: &str) -> Option<&RoutePermission> {
+        let path = path.strip_prefix("/api").unwrap_or(path);
+        self.routes
+            .iter()
+            .find(|route| route.method == *method && route.path == path)
+    }
+}
+
+/// Middleware checking the caller's role against the route's permission
+///
+/// Must run after [`require_auth`]. Callers whose role lacks the permission
+/// the route requires get 403 Forbidden.
+pub async fn authorize<B>(
+    State(authorization): State<RouteAuthorization>,
+    req: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let Some(member) = req.extensions().get::<AuthenticatedMember>() else {
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(ApiResponse::<()>::error_with_code("Unauthorized: Authentication required", 401)),
+        )
+            .into_response();
+    };
+    
+    let required = req
+        .extensions()
+        .get::<MatchedPath>()
+        .and_then(|path| authorization.required(req.method(), path.as_str()));
+    
+    if let Some(required) = required {
+        if !authorization
+            .permissions
+            .has_permission(member.role, required.resource, required.permission)
+        {
+            warn!(
+                "Refused {} {} to {} ({:?}): requires {} on {}",
+                req.method(), req.uri().path(), member.address, member.role, required.permission, required.resource
+            );
+            return (
+                StatusCode::FORBIDDEN,
+                Json(ApiResponse::<()>::error_with_code("Forbidden: Insufficient permissions", 403)),
+            )
+                .into_response();
+        }
+    }
+    
+    next.run(req).await
 }
 
 /// Middleware for checking if user has required permissions
@@ -98,4 +337,749 @@ pub async fn handle_forbidden_error() -> Json<ApiResponse<()>> {
         "Forbidden: Insufficient permissions",
         403
     ))
-} 
\ No newline at end of file
+} 
+
+/// A network block in CIDR notation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IpCidr {
+    /// Network address
+    network: IpAddr,
+    /// Prefix length in bits
+    prefix_len: u8,
+}
+
+impl IpCidr {
+    /// Check if an address falls inside this network
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        // Treat IPv4-mapped IPv6 addresses as plain IPv4
+        let ip = match ip {
+            IpAddr::V6(v6) => v6.to_ipv4_mapped().map(IpAddr::V4).unwrap_or(ip),
+            v4 => v4,
+        };
+        
+        match (self.network, ip) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix_len as u32).unwrap_or(0);
+                u32::from(network) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix_len as u32).unwrap_or(0);
+                u128::from(network) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for IpCidr {
+    type Err = Error;
+    
+    fn from_str(s: &str) -> DaoResult<Self> {
+        let s = s.trim();
+        let (addr, prefix) = match s.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (s, None),
+        };
+        
+        let network: IpAddr = addr
+            .parse()
+            .map_err(|_| Error::ConfigError(format!("Invalid IP address in CIDR: {}", s)))?;
+        
+        let max_prefix = if network.is_ipv4() { 32 } else { 128 };
+        let prefix_len = match prefix {
+            Some(prefix) => prefix
+                .parse::<u8>()
+                .ok()
+                .filter(|len| *len <= max_prefix)
+                .ok_or_else(|| Error::ConfigError(format!("Invalid prefix length in CIDR: {}", s)))?,
+            None => max_prefix,
+        };
+        
+        Ok(Self { network, prefix_len })
+    }
+}
+
+/// Source IP filter for sensitive route groups
+#[derive(Debug, Clone, Default)]
+pub struct IpFilter {
+    /// Allowed networks (empty allows everything not denied)
+    allow: Vec<IpCidr>,
+    /// Denied networks
+    deny: Vec<IpCidr>,
+    /// Proxies allowed to set `X-Forwarded-For`
+    trusted_proxies: Vec<IpCidr>,
+}
+
+impl IpFilter {
+    /// Build a filter from configuration, rejecting malformed CIDR entries
+    pub fn from_config(config: &IpFilterConfig) -> DaoResult<Self> {
+        let parse = |entries: &[String]| {
+            entries
+                .iter()
+                .map(|entry| entry.parse::<IpCidr>())
+                .collect::<DaoResult<Vec<_>>>()
+        };
+        
+        Ok(Self {
+            allow: parse(&config.allow)?,
+            deny: parse(&config.deny)?,
+            trusted_proxies: parse(&config.trusted_proxies)?,
+        })
+    }
+    
+    /// Resolve the client address for a request
+    ///
+    /// `X-Forwarded-For` is only consulted when the peer is a trusted proxy;
+    /// otherwise anyone could claim an allowed address. The header is walked
+    /// from the right, skipping trusted proxies, and the first untrusted hop
+    /// is taken as the client. Returns `None` if the header is malformed.
+    pub fn client_ip(&self, peer: IpAddr, headers: &HeaderMap) -> Option<IpAddr> {
+        if !self.is_trusted_proxy(peer) {
+            return Some(peer);
+        }
+        
+        let mut hops = Vec::new();
+        for value in headers.get_all("x-forwarded-for") {
+            let value = value.to_str().ok()?;
+            hops.extend(value.split(',').map(str::trim).filter(|hop| !hop.is_empty()));
+        }
+        
+        let mut client = peer;
+        for hop in hops.into_iter().rev() {
+            client = hop.parse().ok()?;
+            if !self.is_trusted_proxy(client) {
+                break;
+            }
+        }
+        
+        Some(client)
+    }
+    
+    /// Check if a client address may access the filtered routes
+    pub fn is_allowed(&self, ip: IpAddr) -> bool {
+        if self.deny.iter().any(|cidr| cidr.contains(ip)) {
+            return false;
+        }
+        
+        self.allow.is_empty() || self.allow.iter().any(|cidr| cidr.contains(ip))
+    }
+    
+    /// Check if an address belongs to a trusted proxy
+    fn is_trusted_proxy(&self, ip: IpAddr) -> bool {
+        self.trusted_proxies.iter().any(|cidr| cidr.contains(ip))
+    }
+}
+
+/// Limits on the size and shape of request bodies
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BodyLimits {
+    /// Maximum body size in bytes
+    pub max_body_bytes: usize,
+    /// Maximum nesting depth of a JSON body
+    pub max_json_depth: usize,
+    /// Maximum number of values in a JSON body
+    pub max_json_nodes: usize,
+}
+
+impl Default for BodyLimits {
+    fn default() -> Self {
+        Self {
+            max_body_bytes: 1024 * 1024,
+            max_json_depth: 32,
+            max_json_nodes: 10_000,
+        }
+    }
+}
+
+impl BodyLimits {
+    /// Check a parsed JSON body against the depth and node limits
+    pub fn check_json(&self, value: &serde_json::Value) -> std::result::Result<(), String> {
+        let mut nodes = 0;
+        let mut pending = vec![(value, 1)];
+        
+        while let Some((value, depth)) = pending.pop() {
+            nodes += 1;
+            if nodes > self.max_json_nodes {
+                return Err(format!("JSON body has more than {} values", self.max_json_nodes));
+            }
+            if depth > self.max_json_depth {
+                return Err(format!("JSON body is nested deeper than {} levels", self.max_json_depth));
+            }
+            
+            match value {
+                serde_json::Value::Array(items) => pending.extend(items.iter().map(|item| (item, depth + 1))),
+                serde_json::Value::Object(fields) => pending.extend(fields.values().map(|field| (field, depth + 1))),
+                _ => {}
+            }
+        }
+        
+        Ok(())
+    }
+}
+
+/// Middleware rejecting oversized bodies and pathological JSON
+///
+/// Runs before any handler deserializes the body. Bodies over
+/// `max_body_bytes` get 413 Payload Too Large; JSON bodies that are malformed
+/// or exceed the depth or node limits get 400 Bad Request.
+pub async fn limit_request_body(
+    State(limits): State<BodyLimits>,
+    req: Request<Body>,
+    next: Next<Body>,
+) -> Response {
+    let too_large = || {
+        let message = format!("Request body exceeds {} bytes", limits.max_body_bytes);
+        (
+            StatusCode::PAYLOAD_TOO_LARGE,
+            Json(ApiResponse::<()>::error_with_code(&message, 413)),
+        )
+            .into_response()
+    };
+    
+    // Reject early when the declared length is already too large
+    let declared_length = req
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<usize>().ok());
+    if declared_length.map_or(false, |length| length > limits.max_body_bytes) {
+        return too_large();
+    }
+    
+    let (parts, mut body) = req.into_parts();
+    
+    // Read at most `max_body_bytes`, whatever the declared length says
+    let mut bytes = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(_) => {
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(ApiResponse::<()>::error_with_code("Failed to read request body", 400)),
+                )
+                    .into_response()
+            }
+        };
+        
+        if bytes.len() + chunk.len() > limits.max_body_bytes {
+            return too_large();
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    
+    let is_json = parts
+        .headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map_or(false, |value| value.starts_with("application/json"));
+    
+    if is_json && !bytes.is_empty() {
+        let checked = serde_json::from_slice::<serde_json::Value>(&bytes)
+            .map_err(|e| format!("Invalid JSON body: {}", e))
+            .and_then(|value| limits.check_json(&value));
+        
+        if let Err(message) = checked {
+            warn!("Rejected request body for {}: {}", parts.uri.path(), message);
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::<()>::error_with_code(&message, 400)),
+            )
+                .into_response();
+        }
+    }
+    
+    next.run(Request::from_parts(parts, Body::from(bytes))).await
+}
+
+/// HTTP caching policy for a route group
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CachePolicy {
+    /// `Cache-Control` max-age in seconds (0 makes clients revalidate every time)
+    pub max_age_seconds: u64,
+}
+
+/// Middleware adding ETags and `Cache-Control` to successful GET responses
+///
+/// The ETag is a hash of the response body, so any mutation that changes
+/// what a route returns (including an entity's `updated_at`) changes it.
+/// A request whose `If-None-Match` matches gets 304 Not Modified.
+pub async fn etag_cache(
+    State(policy): State<CachePolicy>,
+    req: Request<Body>,
+    next: Next<Body>,
+) -> Response {
+    if req.method() != Method::GET {
+        return next.run(req).await;
+    }
+    
+    let if_none_match = req.headers().get(header::IF_NONE_MATCH).cloned();
+    let response = next.run(req).await;
+    if response.status() != StatusCode::OK {
+        return response;
+    }
+    
+    let (mut parts, response_body) = response.into_parts();
+    let bytes = match hyper::body::to_bytes(response_body).await {
+        Ok(bytes) => bytes,
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    };
+    
+    let etag = format!("\"{}\"", hex::encode(Sha256::digest(&bytes)));
+    let cache_control = format!("private, max-age={}", policy.max_age_seconds);
+    parts.headers.insert(header::ETAG, HeaderValue::from_str(&etag).unwrap());
+    parts.headers.insert(header::CACHE_CONTROL, HeaderValue::from_str(&cache_control).unwrap());
+    
+    if if_none_match.map_or(false, |value| etag_matches(&value, &etag)) {
+        parts.status = StatusCode::NOT_MODIFIED;
+        parts.headers.remove(header::CONTENT_LENGTH);
+        return Response::from_parts(parts, body::boxed(Empty::new()));
+    }
+    
+    Response::from_parts(parts, body::boxed(Full::from(bytes)))
+}
+
+/// Check an `If-None-Match` header against an ETag
+///
+/// Uses weak comparison, as RFC 9110 requires for `If-None-Match`.
+fn etag_matches(if_none_match: &HeaderValue, etag: &str) -> bool {
+    let Ok(value) = if_none_match.to_str() else {
+        return false;
+    };
+    
+    value.split(',').map(str::trim).any(|candidate| {
+        candidate == "*" || candidate.strip_prefix("W/").unwrap_or(candidate) == etag
+    })
+}
+
+/// Middleware restricting a route group to allowed source IPs
+///
+/// Requires the server to be started with connection info so the peer
+/// address is available. Disallowed sources get 403 Forbidden.
+pub async fn ip_filter<B>(
+    State(filter): State<Arc<IpFilter>>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    req: Request<B>,
+    next: Next<B>,
+) -> Result<Response, StatusCode> {
+    match filter.client_ip(peer.ip(), req.headers()) {
+        Some(client) if filter.is_allowed(client) => Ok(next.run(req).await),
+        client => {
+            warn!("Rejected request to {} from {:?} (peer {})", req.uri().path(), client, peer);
+            Err(StatusCode::FORBIDDEN)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::identity::MemberRole;
+    use axum::{middleware, routing::get, Router};
+    use tower::ServiceExt;
+    
+    fn filter(allow: &[&str], deny: &[&str], trusted_proxies: &[&str]) -> IpFilter {
+        let to_vec = |entries: &[&str]| entries.iter().map(|e| e.to_string()).collect();
+        IpFilter::from_config(&IpFilterConfig {
+            allow: to_vec(allow),
+            deny: to_vec(deny),
+            trusted_proxies: to_vec(trusted_proxies),
+        })
+        .unwrap()
+    }
+    
+    fn forwarded_for(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", HeaderValue::from_str(value).unwrap());
+        headers
+    }
+    
+    #[test]
+    fn test_cidr_parsing() {
+        let cidr: IpCidr = "10.0.0.0/8".parse().unwrap();
+        assert!(cidr.contains("10.20.30.40".parse().unwrap()));
+        assert!(!cidr.contains("11.0.0.1".parse().unwrap()));
+        
+        let single: IpCidr = "192.168.1.5".parse().unwrap();
+        assert!(single.contains("192.168.1.5".parse().unwrap()));
+        assert!(!single.contains("192.168.1.6".parse().unwrap()));
+        
+        let v6: IpCidr = "2001:db8::/32".parse().unwrap();
+        assert!(v6.contains("2001:db8:1::1".parse().unwrap()));
+        
+        assert!("10.0.0.0/33".parse::<IpCidr>().is_err());
+        assert!("not-an-ip/8".parse::<IpCidr>().is_err());
+    }
+    
+    #[test]
+    fn test_malformed_config_rejected() {
+        let config = IpFilterConfig {
+            allow: vec!["10.0.0.0/8".to_string(), "10.0.0.0/abc".to_string()],
+            ..Default::default()
+        };
+        
+        assert!(matches!(IpFilter::from_config(&config), Err(Error::ConfigError(_))));
+    }
+    
+    #[test]
+    fn test_allowed_ip_passes() {
+        let filter = filter(&["10.0.0.0/8"], &["10.0.0.99"], &[]);
+        
+        let client = filter.client_ip("10.1.2.3".parse().unwrap(), &HeaderMap::new()).unwrap();
+        assert!(filter.is_allowed(client));
+        
+        assert!(!filter.is_allowed("192.168.1.1".parse().unwrap()));
+        assert!(!filter.is_allowed("10.0.0.99".parse().unwrap()));
+    }
+    
+    #[test]
+    fn test_spoofed_forwarded_header_ignored_from_untrusted_peer() {
+        let filter = filter(&["203.0.113.0/24"], &[], &["10.0.0.1"]);
+        let peer: IpAddr = "198.51.100.7".parse().unwrap();
+        
+        let client = filter.client_ip(peer, &forwarded_for("203.0.113.5")).unwrap();
+        assert_eq!(client, peer);
+        assert!(!filter.is_allowed(client));
+    }
+    
+    #[test]
+    fn test_forwarded_header_honored_from_trusted_proxy() {
+        let filter = filter(&["203.0.113.0/24"], &[], &["10.0.0.0/24"]);
+        let proxy: IpAddr = "10.0.0.1".parse().unwrap();
+        
+        // Left-most entry is client-supplied and must not be trusted
+        let client = filter
+            .client_ip(proxy, &forwarded_for("203.0.113.9, 198.51.100.7, 10.0.0.2"))
+            .unwrap();
+        assert_eq!(client, "198.51.100.7".parse::<IpAddr>().unwrap());
+        assert!(!filter.is_allowed(client));
+        
+        let client = filter.client_ip(proxy, &forwarded_for("203.0.113.5")).unwrap();
+        assert!(filter.is_allowed(client));
+        
+        assert_eq!(filter.client_ip(proxy, &forwarded_for("garbage")), None);
+    }
+    
+    fn limited_app(limits: BodyLimits) -> Router {
+        Router::new()
+            .route("/proposals", axum::routing::post(|Json(value): Json<serde_json::Value>| async move {
+                Json(value)
+            }))
+            .layer(middleware::from_fn_with_state(limits, limit_request_body))
+    }
+    
+    fn json_request(body: String) -> Request<Body> {
+        Request::builder()
+            .method("POST")
+            .uri("/proposals")
+            .header("content-type", "application/json")
+            .body(Body::from(body))
+            .unwrap()
+    }
+    
+    #[tokio::test]
+    async fn test_oversized_body_rejected() {
+        let limits = BodyLimits {
+            max_body_bytes: 64,
+            ..Default::default()
+        };
+        
+        let small = format!("{{\"metadata\":\"{}\"}}", "a".repeat(16));
+        let response = limited_app(limits).oneshot(json_request(small)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        
+        let large = format!("{{\"metadata\":\"{}\"}}", "a".repeat(128));
+        let response = limited_app(limits).oneshot(json_request(large)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+    
+    #[tokio::test]
+    async fn test_deep_json_rejected() {
+        let limits = BodyLimits {
+            max_json_depth: 8,
+            ..Default::default()
+        };
+        let nested = |depth: usize| format!("{}1{}", "[".repeat(depth), "]".repeat(depth));
+        
+        let response = limited_app(limits).oneshot(json_request(nested(7))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        
+        let response = limited_app(limits).oneshot(json_request(nested(20))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+    
+    #[test]
+    fn test_json_node_limit() {
+        let limits = BodyLimits {
+            max_json_nodes: 100,
+            ..Default::default()
+        };
+        
+        assert!(limits.check_json(&serde_json::json!({ "items": vec![1; 50] })).is_ok());
+        assert!(limits.check_json(&serde_json::json!({ "items": vec![1; 500] })).is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_matching_etag_returns_not_modified() {
+        let proposal = Arc::new(std::sync::Mutex::new(serde_json::json!({ "id": "AIP-42", "yes_votes": 10 })));
+        let state = proposal.clone();
+        let app = Router::new()
+            .route("/proposals/AIP-42", get(move || {
+                let proposal = state.lock().unwrap().clone();
+                async move { Json(proposal) }
+            }))
+            .route_layer(middleware::from_fn_with_state(
+                CachePolicy { max_age_seconds: 30 },
+                etag_cache,
+            ));
+        
+        let request = |etag: Option<&HeaderValue>| {
+            let mut request = Request::builder().uri("/proposals/AIP-42");
+            if let Some(etag) = etag {
+                request = request.header(header::IF_NONE_MATCH, etag);
+            }
+            request.body(Body::empty()).unwrap()
+        };
+        
+        let response = app.clone().oneshot(request(None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CACHE_CONTROL], "private, max-age=30");
+        let etag = response.headers()[header::ETAG].clone();
+        
+        let response = app.clone().oneshot(request(Some(&etag))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers()[header::ETAG], etag);
+        assert!(hyper::body::to_bytes(response.into_body()).await.unwrap().is_empty());
+        
+        // A vote changes the proposal, so the old ETag no longer matches
+        proposal.lock().unwrap()["yes_votes"] = serde_json::json!(11);
+        let response = app.oneshot(request(Some(&etag))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_ne!(response.headers()[header::ETAG], etag);
+    }
+    
+    #[test]
+    fn test_if_none_match_parsing() {
+        let etag = "\"abc\"";
+        let matches = |value: &str| etag_matches(&HeaderValue::from_str(value).unwrap(), etag);
+        
+        assert!(matches("\"abc\""));
+        assert!(matches("W/\"abc\""));
+        assert!(matches("\"xyz\", \"abc\""));
+        assert!(matches("*"));
+        assert!(!matches("\"xyz\""));
+        assert!(!matches("abc"));
+    }
+    
+    /// Accepts a single fixed token
+    struct StaticToken;
+    
+    #[async_trait::async_trait]
+    impl RequestAuthenticator for StaticToken {
+        async fn authenticate(&self, credential: &str) -> crate::core::Result<AuthenticatedMember> {
+            match credential {
+                "valid-token" => Ok(AuthenticatedMember {
+                    address: "0x0000000000000000000000000000000000000001".to_string(),
+                    role: MemberRole::Member,
+                    session_id: Some(credential.to_string()),
+                }),
+                _ => Err(crate::core::DaoError::Unauthorized),
+            }
+        }
+    }
+    
+    fn protected_app(authenticator: Option<Arc<dyn RequestAuthenticator>>) -> Router {
+        Router::new()
+            .route("/proposals", get(|Extension(member): Extension<AuthenticatedMember>| async move {
+                member.address
+            }))
+            .route_layer(middleware::from_fn_with_state(authenticator, require_auth))
+    }
+    
+    fn authorized_request(header_name: header::HeaderName, value: &str) -> Request<Body> {
+        Request::builder()
+            .uri("/proposals")
+            .header(header_name, value)
+            .body(Body::empty())
+            .unwrap()
+    }
+    
+    /// Log output captured by a test subscriber
+    #[derive(Clone, Default)]
+    struct LogBuffer(Arc<std::sync::Mutex<Vec<u8>>>);
+    
+    impl std::io::Write for LogBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+        
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_request_id_round_trips_into_logs() {
+        let logs = LogBuffer::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .json()
+            .with_current_span(true)
+            .with_span_list(true)
+            .with_max_level(Level::INFO)
+            .with_writer(move || writer.clone())
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+        
+        let app = Router::new()
+            .route("/proposals", get(|Extension(RequestId(id)): Extension<RequestId>| async move {
+                tracing::info!("listing proposals");
+                tracing::info!("listed proposals");
+                id
+            }))
+            .layer(middleware::from_fn(request_id));
+        let request = |id: Option<&str>| {
+            let mut request = Request::builder().uri("/proposals");
+            if let Some(id) = id {
+                request = request.header(REQUEST_ID_HEADER, id);
+            }
+            request.body(Body::empty()).unwrap()
+        };
+        
+        // A client-supplied ID reaches the handler and is echoed back
+        let response = app.clone().oneshot(request(Some("dash-7f3a"))).await.unwrap();
+        assert_eq!(response.headers()[REQUEST_ID_HEADER], "dash-7f3a");
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(&body[..], b"dash-7f3a");
+        
+        // Every line logged within the request carries it
+        let output = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<serde_json::Value> = output.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|line| line["span"]["request_id"] == "dash-7f3a"));
+        
+        // Missing or unsafe IDs are replaced by a generated one
+        let too_long = "a".repeat(MAX_REQUEST_ID_LENGTH + 1);
+        for id in [None, Some("dash 7f3a"), Some(too_long.as_str())] {
+            let response = app.clone().oneshot(request(id)).await.unwrap();
+            let echoed = response.headers()[REQUEST_ID_HEADER].to_str().unwrap().to_string();
+            assert!(Uuid::parse_str(&echoed).is_ok());
+            let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+            assert_eq!(&body[..], echoed.as_bytes());
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_protected_route_requires_token() {
+        let app = protected_app(Some(Arc::new(StaticToken)));
+        
+        let request = Request::builder().uri("/proposals").body(Body::empty()).unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["success"], false);
+        
+        let response = app
+            .clone()
+            .oneshot(authorized_request(header::AUTHORIZATION, "Bearer forged-token"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        
+        // No authenticator means nothing gets through
+        let response = protected_app(None)
+            .oneshot(authorized_request(header::AUTHORIZATION, "Bearer valid-token"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+    
+    #[tokio::test]
+    async fn test_protected_route_with_valid_token() {
+        let app = protected_app(Some(Arc::new(StaticToken)));
+        
+        let response = app
+            .clone()
+            .oneshot(authorized_request(header::AUTHORIZATION, "Bearer valid-token"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(&body[..], b"0x0000000000000000000000000000000000000001");
+        
+        // Browser clients send the session cookie instead
+        let response = app
+            .oneshot(authorized_request(header::COOKIE, "theme=dark; atomsi_session=valid-token"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+    
+    #[tokio::test]
+    async fn test_member_can_read_proposals_but_not_approve_transactions() {
+        let authorization = RouteAuthorization::new(Arc::new(PermissionManager::new()));
+        let app = Router::new()
+            .route("/governance/proposals", get(|| async { "proposals" }))
+            .route("/treasury/transactions/:id/approve", axum::routing::post(|| async { "approved" }))
+            .route_layer(middleware::from_fn_with_state(authorization, authorize))
+            .route_layer(middleware::from_fn_with_state(
+                Some(Arc::new(StaticToken) as Arc<dyn RequestAuthenticator>),
+                require_auth,
+            ));
+        
+        let response = app
+            .clone()
+            .oneshot(authorized_request(header::AUTHORIZATION, "Bearer valid-token"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri("/treasury/transactions/tx-1/approve")
+            .header(header::AUTHORIZATION, "Bearer valid-token")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+    
+    #[test]
+    fn test_route_permissions_lookup() {
+        let manager = Arc::new(PermissionManager::new());
+        let authorization = RouteAuthorization::new(manager.clone());
+        
+        let approve = authorization
+            .required(&Method::POST, "/api/treasury/transactions/:id/approve")
+            .unwrap();
+        assert!(manager.has_permission(MemberRole::Council, approve.resource, approve.permission));
+        assert!(!manager.has_permission(MemberRole::Delegate, approve.resource, approve.permission));
+        
+        assert!(authorization.required(&Method::DELETE, "/governance/proposals").is_none());
+    }
+    
+    #[tokio::test]
+    async fn test_ip_filter_middleware() {
+        let filter = Arc::new(filter(&["10.0.0.0/8"], &[], &[]));
+        let app = Router::new()
+            .route("/admin", get(|| async { "ok" }))
+            .route_layer(middleware::from_fn_with_state(filter, ip_filter));
+        
+        let request = |peer: &str| {
+            Request::builder()
+                .uri("/admin")
+                .header("x-forwarded-for", "10.0.0.5")
+                .extension(ConnectInfo(peer.parse::<SocketAddr>().unwrap()))
+                .body(Body::empty())
+                .unwrap()
+        };
+        
+        let response = app.clone().oneshot(request("10.1.1.1:4000")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        
+        let response = app.oneshot(request("192.168.1.1:4000")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+}
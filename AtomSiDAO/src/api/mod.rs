@@ -18,11 +18,16 @@ use tracing::info;
 
 use crate::DAOContext;
 use crate::api::models::ApiResponse;
+use crate::config::{FeaturesConfig, HttpCacheConfig, IpFilterConfig};
+use crate::security::PermissionManager;
 
+pub mod locale;
 pub mod models;
 pub mod routes;
 pub mod middleware as api_middleware;
 pub mod docs;
+pub mod rate_limit;
+pub mod validation;
 pub mod websocket;
 
 /// API server configuration
@@ -38,6 +43,14 @@ pub struct ApiConfig {
     pub enable_docs: bool,
     /// Whether to enable WebSockets
     pub enable_websockets: bool,
+    /// Source IP restrictions for the treasury (execution) routes
+    pub ip_filter: Option<IpFilterConfig>,
+    /// Request body size and JSON shape limits for the API routes
+    pub body_limits: api_middleware::BodyLimits,
+    /// Ping interval and idle timeout for WebSocket connections
+    pub websocket_keepalive: websocket::KeepaliveConfig,
+    /// ETag and `Cache-Control` settings for GET routes
+    pub http_cache: HttpCacheConfig,
 }
 
 impl Default for ApiConfig {
@@ -48,6 +61,10 @@ impl Default for ApiConfig {
             enable_logging: true,
             enable_docs: true,
             enable_websockets: true,
+            ip_filter: None,
+            body_limits: api_middleware::BodyLimits::default(),
+            websocket_keepalive: websocket::KeepaliveConfig::default(),
+            http_cache: HttpCacheConfig::default(),
         }
     }
 }
@@ -60,13 +77,21 @@ pub struct ApiServer {
     context: Arc<DAOContext>,
     /// WebSocket manager
     ws_manager: Option<Arc<websocket::WebSocketManager>>,
+    /// Resolves credentials on protected routes (all refused when unset)
+    authenticator: Option<Arc<dyn api_middleware::RequestAuthenticator>>,
+    /// Role permissions checked against `api_middleware::ROUTE_PERMISSIONS`
+    permission_manager: Arc<PermissionManager>,
 }
 
 impl ApiServer {
     /// Create a new API server
     pub fn new(config: ApiConfig, context: Arc<DAOContext>) -> Self {
-        let ws_manager = if config.enable_websockets {
-            Some(Arc::new(websocket::WebSocketManager::new(context.clone())))
+        let features = context.config_manager.get_config().features;
+        let ws_manager = if config.enable_websockets && features.websockets_enabled {
+            Some(Arc::new(websocket::WebSocketManager::new(
+                context.clone(),
+                config.websocket_keepalive,
+            )))
         } else {
             None
         };
@@ -75,9 +100,25 @@ impl ApiServer {
             config,
             context,
             ws_manager,
+            authenticator: None,
+            permission_manager: Arc::new(PermissionManager::new()),
         }
     }
     
+    /// Authenticate requests to protected routes with the given authenticator,
+    /// usually an `AuthManager`
+    pub fn with_authenticator(mut self, authenticator: Arc<dyn api_middleware::RequestAuthenticator>) -> Self {
+        self.authenticator = Some(authenticator);
+        self
+    }
+    
+    /// Check route permissions against the given role permissions instead of
+    /// the defaults
+    pub fn with_permission_manager(mut self, permission_manager: Arc<PermissionManager>) -> Self {
+        self.permission_manager = permission_manager;
+        self
+    }
+    
     /// Get a reference to the WebSocket manager
     pub fn get_ws_manager(&self) -> Option<Arc<websocket::WebSocketManager>> {
         self.ws_manager.clone()
@@ -85,6 +126,24 @@ impl ApiServer {
     
     /// Start the API server
     pub async fn start(&self) -> anyhow::Result<()> {
+        // Parse the IP filter up front so malformed CIDRs fail startup
+        let ip_filter = match &self.config.ip_filter {
+            Some(config) => Some(Arc::new(api_middleware::IpFilter::from_config(config)?)),
+            None => None,
+        };
+        
+        // Limit requests per client if enabled in the API config
+        let api_config = self.context.config_manager.get_config().api;
+        let rate_limiter = match (api_config.enable_rate_limiting, api_config.rate_limit_per_minute) {
+            (true, Some(per_minute)) => {
+                let client_ips = ip_filter.as_deref().cloned().unwrap_or_default();
+                let limiter = Arc::new(rate_limit::RateLimiter::new(per_minute).with_ip_filter(client_ips));
+                limiter.spawn_cleanup();
+                Some(limiter)
+            }
+            _ => None,
+        };
+        
         // Build the CORS layer if enabled
         let cors_layer = if self.config.enable_cors {
             CorsLayer::new()
@@ -109,37 +168,50 @@ impl ApiServer {
         
         // Add WebSocket routes if enabled
         if let Some(ws_manager) = &self.ws_manager {
+            ws_manager.spawn_keepalive();
             app = app
                 .route("/ws", get(websocket::handle_ws_upgrade))
                 .route("/api/ws/info", get(websocket::get_ws_info))
-                .layer(Extension(ws_manager.clone()));
+                .layer(Extension(ws_manager.clone()))
+                .layer(Extension(self.authenticator.clone()));
         }
         
         // Add the API routes
-        app = app.nest("/api", self.create_api_router());
+        app = app.nest("/api", self.create_api_router(ip_filter, rate_limiter));
         
         // Add the documentation routes if enabled
         if self.config.enable_docs {
             app = app.nest("/docs", docs::create_docs_router());
         }
         
+        // Tag every request with a correlation ID; added last so it wraps
+        // all routes and its span encloses every other layer's logs
+        app = app.layer(middleware::from_fn(api_middleware::request_id));
+        
         // Start the server
         info!("Starting API server on {}", self.config.bind_address);
         axum::Server::bind(&self.config.bind_address)
-            .serve(app.into_make_service())
+            .serve(app.into_make_service_with_connect_info::<SocketAddr>())
             .await?;
         
         Ok(())
     }
     
     /// Create the API router
-    fn create_api_router(&self) -> Router {
+    fn create_api_router(
+        &self,
+        ip_filter: Option<Arc<api_middleware::IpFilter>>,
+        rate_limiter: Option<Arc<rate_limit::RateLimiter>>,
+    ) -> Router {
         // Governance routes
         let governance_routes = Router::new()
             .route("/proposals", get(routes::governance::get_proposals)
                                    .post(routes::governance::create_proposal))
+            .route("/proposals/votable", get(routes::governance::get_votable_proposals))
             .route("/proposals/:id", get(routes::governance::get_proposal))
-            .route("/proposals/:id/vote", post(routes::governance::vote_on_proposal));
+            .route("/proposals/:id/sponsor", post(routes::governance::sponsor_proposal))
+            .route("/proposals/:id/vote", post(routes::governance::vote_on_proposal))
+            .route("/parameters", get(routes::governance::get_governance_parameters));
         
         // Treasury routes
         let treasury_routes = Router::new()
@@ -147,12 +219,24 @@ impl ApiServer {
                                       .post(routes::treasury::create_transaction))
             .route("/transactions/:id", get(routes::treasury::get_transaction))
             .route("/transactions/:id/approve", post(routes::treasury::approve_transaction))
+            .route("/transactions/:id/cancel", post(routes::treasury::cancel_transaction))
             .route("/balances", get(routes::treasury::get_balances));
         
+        // Restrict treasury execution routes to allowed source IPs if configured
+        let treasury_routes = match ip_filter {
+            Some(filter) => treasury_routes.route_layer(middleware::from_fn_with_state(
+                filter,
+                api_middleware::ip_filter,
+            )),
+            None => treasury_routes,
+        };
+        
         // Identity routes
         let identity_routes = Router::new()
             .route("/members", get(routes::identity::get_members))
             .route("/members/:id", get(routes::identity::get_member))
+            // Takes a member address; the segment is named `:id` to match the route above
+            .route("/members/:id/voting-record", get(routes::identity::get_voting_record))
             .route("/activities", get(routes::identity::get_activities));
         
         // Token routes
@@ -162,6 +246,14 @@ impl ApiServer {
             .route("/tokens/:id/balances", get(routes::token::get_token_balances))
             .route("/transfer", post(routes::token::transfer_tokens));
         
+        // Reads get ETags and a per-group max-age, so polling clients can
+        // revalidate instead of re-downloading unchanged payloads
+        let http_cache = &self.config.http_cache;
+        let governance_routes = with_http_cache(governance_routes, http_cache, http_cache.governance_max_age_seconds);
+        let treasury_routes = with_http_cache(treasury_routes, http_cache, http_cache.treasury_max_age_seconds);
+        let identity_routes = with_http_cache(identity_routes, http_cache, http_cache.identity_max_age_seconds);
+        let token_routes = with_http_cache(token_routes, http_cache, http_cache.token_max_age_seconds);
+        
         // Auth routes
         let auth_routes = Router::new()
             .route("/login", post(routes::auth::login))
@@ -169,28 +261,40 @@ impl ApiServer {
             .route("/check-session", get(routes::auth::check_session));
         
         // Combine all routes into the API router
-        // Protected routes require authentication
+        // Protected routes require authentication, then the caller's role must
+        // have the permission the route requires (the outer layer runs first).
+        // The rate limit runs before authentication, so requests with invalid
+        // tokens are throttled like any other.
+        let features = self.context.config_manager.get_config().features;
         let protected_routes = Router::new()
             .nest("/governance", governance_routes)
-            .nest("/treasury", treasury_routes)
             .nest("/identity", identity_routes)
-            .nest("/token", token_routes)
+            .nest("/token", token_routes);
+        let protected_routes = nest_feature_routes(protected_routes, &features, treasury_routes);
+        let protected_routes = protected_routes
+            .route_layer(middleware::from_fn_with_state(
+                api_middleware::RouteAuthorization::new(self.permission_manager.clone()),
+                api_middleware::authorize,
+            ))
             .route_layer(middleware::from_fn_with_state(
-                self.context.clone(),
-                |context, req, next| async move {
-                    api_middleware::require_auth(context, req, next).await
-                },
+                self.authenticator.clone(),
+                api_middleware::require_auth,
             ));
+        let protected_routes = with_rate_limit(protected_routes, rate_limiter.clone());
         
         // Public routes don't require authentication
-        let public_routes = Router::new()
-            .nest("/auth", auth_routes);
+        let public_routes = with_rate_limit(Router::new().nest("/auth", auth_routes), rate_limiter);
         
         // Combine protected and public routes
+        // Body limits are checked before any handler deserializes the body
         Router::new()
             .merge(protected_routes)
             .merge(public_routes)
             .fallback(handle_not_found)
+            .layer(middleware::from_fn_with_state(
+                self.config.body_limits,
+                api_middleware::limit_request_body,
+            ))
     }
     
     /// Broadcast a WebSocket event
@@ -208,6 +312,39 @@ impl ApiServer {
     }
 }
 
+/// Mount the routes of optional feature modules that are enabled
+///
+/// Routes of disabled modules are left out, so requests fall through to the
+/// 404 handler.
+fn nest_feature_routes(router: Router, features: &FeaturesConfig, treasury_routes: Router) -> Router {
+    if features.treasury_enabled {
+        router.nest("/treasury", treasury_routes)
+    } else {
+        router
+    }
+}
+
+/// Add ETags and `Cache-Control` to a router's GET routes, if HTTP caching
+/// is enabled
+fn with_http_cache(router: Router, config: &HttpCacheConfig, max_age_seconds: u64) -> Router {
+    if !config.enabled {
+        return router;
+    }
+    
+    router.route_layer(middleware::from_fn_with_state(
+        api_middleware::CachePolicy { max_age_seconds },
+        api_middleware::etag_cache,
+    ))
+}
+
+/// Apply the rate limiter to a router's routes, if rate limiting is enabled
+fn with_rate_limit(router: Router, rate_limiter: Option<Arc<rate_limit::RateLimiter>>) -> Router {
+    match rate_limiter {
+        Some(limiter) => router.route_layer(middleware::from_fn_with_state(limiter, rate_limit::rate_limit)),
+        None => router,
+    }
+}
+
 /// Health check handler
 async fn health_check() -> impl IntoResponse {
     let response = ApiResponse::success(serde_json::json!({
@@ -222,4 +359,96 @@ async fn health_check() -> impl IntoResponse {
 async fn handle_not_found() -> impl IntoResponse {
     let response = ApiResponse::<()>::error_with_code("Not Found", 404);
     (StatusCode::NOT_FOUND, axum::Json(response))
-} 
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::{header, Request}};
+    use tower::ServiceExt;
+
+    fn treasury_routes() -> Router {
+        Router::new().route("/balances", get(|| async { "balances" }))
+    }
+
+    async fn status(router: Router, uri: &str) -> StatusCode {
+        let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        router.oneshot(request).await.unwrap().status()
+    }
+
+    fn conditional_get(uri: &str, etag: Option<&HeaderValue>) -> Request<Body> {
+        let mut request = Request::builder().uri(uri);
+        if let Some(etag) = etag {
+            request = request.header(header::IF_NONE_MATCH, etag);
+        }
+        request.body(Body::empty()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_disabled_treasury_routes_are_absent() {
+        let mut features = FeaturesConfig::default();
+        let router = nest_feature_routes(Router::new(), &features, treasury_routes());
+        assert_eq!(status(router, "/treasury/balances").await, StatusCode::OK);
+
+        features.treasury_enabled = false;
+        let router = nest_feature_routes(Router::new(), &features, treasury_routes())
+            .fallback(handle_not_found);
+        assert_eq!(status(router, "/treasury/balances").await, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_refetch_not_modified_until_changed() {
+        let config = HttpCacheConfig::default();
+        let cases = [
+            ("/proposals/p-1", config.governance_max_age_seconds),
+            ("/treasury/transactions/t-1", config.treasury_max_age_seconds),
+            ("/members/m-1", config.identity_max_age_seconds),
+            ("/tokens/ATOM", config.token_max_age_seconds),
+        ];
+
+        for (uri, max_age_seconds) in cases {
+            let resource = Arc::new(std::sync::Mutex::new(serde_json::json!({ "uri": uri, "version": 1 })));
+            let state = resource.clone();
+            let routes = Router::new().route(uri, get(move || {
+                let resource = state.lock().unwrap().clone();
+                async move { axum::Json(ApiResponse::success(resource)) }
+            }));
+            let app = with_http_cache(routes, &config, max_age_seconds);
+
+            let response = app.clone().oneshot(conditional_get(uri, None)).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK, "{}", uri);
+            assert_eq!(
+                response.headers()[header::CACHE_CONTROL],
+                format!("private, max-age={}", max_age_seconds).as_str(),
+                "{}",
+                uri
+            );
+            let etag = response.headers()[header::ETAG].clone();
+
+            for _ in 0..2 {
+                let response = app.clone().oneshot(conditional_get(uri, Some(&etag))).await.unwrap();
+                assert_eq!(response.status(), StatusCode::NOT_MODIFIED, "{}", uri);
+            }
+
+            // A change alters the body, so the old ETag no longer matches
+            resource.lock().unwrap()["version"] = serde_json::json!(2);
+            let response = app.oneshot(conditional_get(uri, Some(&etag))).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK, "{}", uri);
+            assert_ne!(response.headers()[header::ETAG], etag, "{}", uri);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_http_cache_disabled_sends_no_etag() {
+        let routes = Router::new().route("/transactions", get(|| async { "[]" }));
+        let config = HttpCacheConfig {
+            enabled: false,
+            ..Default::default()
+        };
+        let app = with_http_cache(routes, &config, config.treasury_max_age_seconds);
+
+        let response = app.oneshot(conditional_get("/transactions", None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().get(header::ETAG).is_none());
+    }
+}
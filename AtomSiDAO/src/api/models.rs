@@ -1,10 +1,21 @@
 //! API models for AtomSi DAO
 //!
 //! This module contains request and response models for API endpoints.
+//! Timestamps are serialized as RFC3339 strings. Monetary amounts and vote
+//! weights are decimal strings in base units (see [`crate::utils::amount`]);
+//! `formatted_*` fields follow the request locale (see
+//! [`crate::api::locale::RequestLocale`]).
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use crate::core::{Cursor, Page, PageRequest};
+use crate::utils::amount::{base_units, ApiAmount};
+use crate::utils::locale::{format_token_amount_localized, Locale};
+use crate::utils::time::rfc3339;
+use crate::utils::validation::ValidationError;
+
 // General models
 
 /// API response wrapper
@@ -18,6 +29,9 @@ pub struct ApiResponse<T> {
     /// Error message (if success is false)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
+    /// Per-field errors (if the request failed validation)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub errors: Option<Vec<ValidationError>>,
 }
 
 impl<T> ApiResponse<T> {
@@ -27,6 +41,7 @@ impl<T> ApiResponse<T> {
             success: true,
             data: Some(data),
             error: None,
+            errors: None,
         }
     }
     
@@ -36,11 +51,27 @@ impl<T> ApiResponse<T> {
             success: false,
             data: None,
             error: Some(message.to_string()),
+            errors: None,
+        }
+    }
+    
+    /// Create an error API response for a request that failed validation
+    pub fn validation_errors(errors: Vec<ValidationError>) -> Self {
+        Self {
+            success: false,
+            data: None,
+            error: Some("Validation failed".to_string()),
+            errors: Some(errors),
         }
     }
 }
 
 /// Pagination parameters for list requests
+///
+/// Lists are paged by offset (`page`) unless `cursor` is given, which
+/// selects cursor paging: an empty `cursor` requests the newest items, and
+/// the `next_cursor`/`prev_cursor` of a response request its neighbours.
+/// Cursor pages don't drift when items are created between requests.
 #[derive(Deserialize)]
 pub struct PaginationParams {
     /// Page number (1-based)
@@ -49,6 +80,28 @@ pub struct PaginationParams {
     /// Items per page
     #[serde(default = "default_limit")]
     pub limit: usize,
+    /// Cursor from a previous response (selects cursor paging)
+    #[serde(default)]
+    pub cursor: Option<String>,
+}
+
+impl PaginationParams {
+    /// The cursor page requested, or `None` for offset paging
+    pub fn page_request(&self) -> crate::core::Result<Option<PageRequest>> {
+        let Some(token) = &self.cursor else {
+            return Ok(None);
+        };
+        let cursor = if token.is_empty() {
+            None
+        } else {
+            Some(Cursor::decode(token)?)
+        };
+        
+        Ok(Some(PageRequest {
+            cursor,
+            limit: self.limit,
+        }))
+    }
 }
 
 fn default_page() -> usize {
@@ -70,6 +123,12 @@ pub struct PaginationMeta {
     pub total: usize,
     /// Total pages
     pub total_pages: usize,
+    /// Cursor to the next page (cursor paging only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+    /// Cursor to the previous page (cursor paging only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prev_cursor: Option<String>,
 }
 
 /// Paginated response
@@ -81,6 +140,28 @@ pub struct PaginatedResponse<T> {
     pub meta: PaginationMeta,
 }
 
+impl<T> PaginatedResponse<T> {
+    /// Response for a cursor page, converting its items
+    ///
+    /// Cursor pages have no page number or total, so those are reported as 0.
+    pub fn from_page<U>(page: Page<U>, limit: usize) -> Self
+    where
+        T: From<U>,
+    {
+        Self {
+            items: page.items.into_iter().map(T::from).collect(),
+            meta: PaginationMeta {
+                page: 0,
+                limit,
+                total: 0,
+                total_pages: 0,
+                next_cursor: page.next_cursor.map(|cursor| cursor.encode()),
+                prev_cursor: page.prev_cursor.map(|cursor| cursor.encode()),
+            },
+        }
+    }
+}
+
 // Auth models
 
 /// Login request
@@ -90,7 +171,7 @@ pub struct LoginRequest {
     pub address: String,
     /// Signature of the message
     pub signature: String,
-    /// Message that was signed
+    /// Message that was signed; must contain an `Issued At: <RFC 3339>` line
     pub message: String,
 }
 
@@ -121,9 +202,11 @@ pub struct MemberResponse {
     /// Reputation
     pub reputation: i32,
     /// Joined timestamp
-    pub joined_at: u64,
+    #[serde(with = "rfc3339")]
+    pub joined_at: DateTime<Utc>,
     /// Last active timestamp
-    pub last_active_at: u64,
+    #[serde(with = "rfc3339")]
+    pub last_active_at: DateTime<Utc>,
     /// Additional metadata
     pub metadata: Option<serde_json::Value>,
 }
@@ -140,7 +223,8 @@ pub struct ActivityResponse {
     /// Related object ID
     pub related_id: Option<String>,
     /// Timestamp
-    pub timestamp: u64,
+    #[serde(with = "rfc3339")]
+    pub timestamp: DateTime<Utc>,
     /// Description
     pub description: Option<String>,
     /// Reputation change
@@ -149,6 +233,42 @@ pub struct ActivityResponse {
     pub metadata: Option<serde_json::Value>,
 }
 
+// Governance models
+
+/// Effective governance parameter response
+#[derive(Serialize)]
+pub struct GovernanceParameterResponse {
+    /// Parameter name
+    pub name: String,
+    /// Effective value
+    pub value: serde_json::Value,
+    /// Value source (default, proposal)
+    pub source: String,
+    /// Proposal that amended the value (if amended)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proposal_id: Option<String>,
+    /// Last change timestamp (if amended)
+    #[serde(default, with = "rfc3339::option")]
+    pub changed_at: Option<DateTime<Utc>>,
+}
+
+impl From<crate::governance::GovernanceParameter> for GovernanceParameterResponse {
+    fn from(parameter: crate::governance::GovernanceParameter) -> Self {
+        let (source, proposal_id) = match parameter.source {
+            crate::governance::ParameterSource::Default => ("default", None),
+            crate::governance::ParameterSource::Proposal { proposal_id } => ("proposal", Some(proposal_id)),
+        };
+        
+        Self {
+            name: parameter.name,
+            value: parameter.value,
+            source: source.to_string(),
+            proposal_id,
+            changed_at: parameter.changed_at,
+        }
+    }
+}
+
 // Proposal models
 
 /// Create proposal request
@@ -161,13 +281,17 @@ pub struct CreateProposalRequest {
     /// Proposal type
     pub proposal_type: String,
     /// Voting start time (optional, immediate if not provided)
-    pub voting_starts_at: Option<u64>,
+    #[serde(default, with = "rfc3339::option")]
+    pub voting_starts_at: Option<DateTime<Utc>>,
     /// Voting duration in seconds
     pub voting_duration: u64,
     /// Execution data for on-chain actions
     pub execution_data: Option<serde_json::Value>,
     /// Additional metadata
     pub metadata: Option<serde_json::Value>,
+    /// Referenced documents
+    #[serde(default)]
+    pub attachments: Vec<AttachmentModel>,
 }
 
 /// Proposal response
@@ -175,6 +299,10 @@ pub struct CreateProposalRequest {
 pub struct ProposalResponse {
     /// Proposal ID
     pub id: String,
+    /// Sequential proposal number (absent until submitted)
+    pub number: Option<u64>,
+    /// Display id such as `AIP-42` (absent until submitted)
+    pub display_id: Option<String>,
     /// Proposal title
     pub title: String,
     /// Proposal description
@@ -188,32 +316,111 @@ pub struct ProposalResponse {
     /// Proposal status
     pub status: String,
     /// Creation timestamp
-    pub created_at: u64,
+    #[serde(with = "rfc3339")]
+    pub created_at: DateTime<Utc>,
     /// Voting start timestamp
-    pub voting_starts_at: Option<u64>,
+    #[serde(default, with = "rfc3339::option")]
+    pub voting_starts_at: Option<DateTime<Utc>>,
     /// Voting end timestamp
-    pub voting_ends_at: Option<u64>,
+    #[serde(default, with = "rfc3339::option")]
+    pub voting_ends_at: Option<DateTime<Utc>>,
+    /// Block at which voting power is measured
+    #[serde(default)]
+    pub snapshot_block: Option<u64>,
     /// Execution timestamp
-    pub executed_at: Option<u64>,
+    #[serde(default, with = "rfc3339::option")]
+    pub executed_at: Option<DateTime<Utc>>,
+    /// Earliest execution time of an approved proposal (end of its timelock)
+    #[serde(default, with = "rfc3339::option")]
+    pub execution_available_at: Option<DateTime<Utc>>,
     /// Execution data
     pub execution_data: Option<serde_json::Value>,
     /// Additional metadata
     pub metadata: Option<serde_json::Value>,
     /// Vote counts
     pub vote_counts: VoteCounts,
+    /// Sponsor endorsements
+    pub sponsors: Vec<SponsorResponse>,
+    /// Referenced documents
+    pub attachments: Vec<AttachmentModel>,
+}
+
+/// Document referenced by a proposal
+#[derive(Serialize, Deserialize)]
+pub struct AttachmentModel {
+    /// `https://` or `ipfs://` URI
+    pub uri: String,
+    /// Hex-encoded SHA-256 of the content
+    pub content_hash: String,
+    /// MIME type
+    pub mime_type: String,
+}
+
+impl From<crate::proposals::Attachment> for AttachmentModel {
+    fn from(attachment: crate::proposals::Attachment) -> Self {
+        Self {
+            uri: attachment.uri,
+            content_hash: attachment.content_hash,
+            mime_type: attachment.mime_type,
+        }
+    }
+}
+
+impl From<AttachmentModel> for crate::proposals::Attachment {
+    fn from(attachment: AttachmentModel) -> Self {
+        Self {
+            uri: attachment.uri,
+            content_hash: attachment.content_hash,
+            mime_type: attachment.mime_type,
+        }
+    }
+}
+
+/// Sponsor endorsement
+#[derive(Serialize)]
+pub struct SponsorResponse {
+    /// Sponsor address
+    pub member: String,
+    /// Sponsor voting weight at the time of sponsorship
+    #[serde(with = "base_units")]
+    pub weight: ApiAmount,
+    /// Sponsorship timestamp
+    #[serde(with = "rfc3339")]
+    pub sponsored_at: DateTime<Utc>,
+}
+
+impl From<crate::proposals::Sponsor> for SponsorResponse {
+    fn from(sponsor: crate::proposals::Sponsor) -> Self {
+        Self {
+            member: sponsor.member,
+            weight: sponsor.weight.into(),
+            sponsored_at: sponsor.timestamp,
+        }
+    }
 }
 
 /// Vote counts
 #[derive(Serialize)]
 pub struct VoteCounts {
     /// For votes
-    pub for_votes: u64,
+    #[serde(with = "base_units")]
+    pub for_votes: ApiAmount,
     /// Against votes
-    pub against_votes: u64,
+    #[serde(with = "base_units")]
+    pub against_votes: ApiAmount,
     /// Abstain votes
-    pub abstain_votes: u64,
+    #[serde(with = "base_units")]
+    pub abstain_votes: ApiAmount,
+    /// Veto votes
+    #[serde(with = "base_units")]
+    pub veto_votes: ApiAmount,
     /// Total votes
-    pub total: u64,
+    #[serde(with = "base_units")]
+    pub total: ApiAmount,
+    /// Share of the eligible supply that voted, abstentions included (set once finalized)
+    pub participation_rate: Option<f64>,
+    /// Share of the eligible supply that abstained (set once finalized)
+    pub abstain_rate: Option<f64>,
 }
 
 /// Vote request
@@ -222,9 +429,13 @@ pub struct VoteRequest {
     /// Vote choice
     pub choice: String,
     /// Vote weight
-    pub weight: Option<u64>,
+    #[serde(default, with = "base_units::option")]
+    pub weight: Option<ApiAmount>,
     /// Additional metadata
     pub metadata: Option<serde_json::Value>,
+    /// The voter's signature over the vote message, for a vote receipt
+    #[serde(default)]
+    pub signature: Option<crate::proposals::VoteSignature>,
 }
 
 /// Vote response
@@ -241,13 +452,101 @@ pub struct VoteResponse {
     /// Vote choice
     pub choice: String,
     /// Vote weight
-    pub weight: u64,
+    #[serde(with = "base_units")]
+    pub weight: ApiAmount,
     /// Vote timestamp
-    pub voted_at: u64,
+    #[serde(with = "rfc3339")]
+    pub voted_at: DateTime<Utc>,
     /// Additional metadata
     pub metadata: Option<serde_json::Value>,
 }
 
+/// Member voting record response
+#[derive(Serialize)]
+pub struct VotingRecordResponse {
+    /// Voter address
+    pub address: String,
+    /// Proposals voted on, most recent vote first
+    pub items: Vec<VotingRecordEntryResponse>,
+    /// Pagination metadata
+    pub meta: PaginationMeta,
+    /// Stats over the whole period, not just this page
+    pub summary: VotingRecordSummaryResponse,
+}
+
+/// Proposal in a voting record
+#[derive(Serialize)]
+pub struct VotingRecordEntryResponse {
+    /// Proposal ID
+    pub proposal_id: String,
+    /// Sequential proposal number (if assigned)
+    pub proposal_number: Option<u64>,
+    /// Proposal title
+    pub title: String,
+    /// Vote choice
+    pub choice: String,
+    /// Vote weight
+    #[serde(with = "base_units")]
+    pub weight: ApiAmount,
+    /// Vote timestamp
+    #[serde(with = "rfc3339")]
+    pub voted_at: DateTime<Utc>,
+    /// Proposal outcome (pending, approved, rejected, cancelled)
+    pub outcome: String,
+    /// Whether the vote sided with the outcome (null while pending, if cancelled or abstained)
+    pub with_majority: Option<bool>,
+}
+
+/// Voting record summary
+#[derive(Serialize)]
+pub struct VotingRecordSummaryResponse {
+    /// Votes cast in the period
+    pub votes_cast: usize,
+    /// Proposals open for voting in the period
+    pub eligible_proposals: usize,
+    /// Share of eligible proposals voted on
+    pub participation_rate: Option<f64>,
+    /// Share of decided, non-abstain votes that sided with the outcome
+    pub with_majority_rate: Option<f64>,
+}
+
+impl VotingRecordResponse {
+    /// Build the response for a page of a voting record
+    pub fn new(record: crate::governance::VotingRecord, page: usize, limit: usize) -> Self {
+        Self {
+            address: record.address,
+            items: record
+                .entries
+                .into_iter()
+                .map(|entry| VotingRecordEntryResponse {
+                    proposal_id: entry.proposal_id,
+                    proposal_number: entry.proposal_number,
+                    title: entry.title,
+                    choice: format!("{:?}", entry.choice).to_lowercase(),
+                    weight: entry.weight as ApiAmount,
+                    voted_at: entry.voted_at,
+                    outcome: format!("{:?}", entry.outcome).to_lowercase(),
+                    with_majority: entry.with_majority,
+                })
+                .collect(),
+            meta: PaginationMeta {
+                page,
+                limit,
+                total: record.total,
+                total_pages: (record.total + limit.max(1) - 1) / limit.max(1),
+                next_cursor: None,
+                prev_cursor: None,
+            },
+            summary: VotingRecordSummaryResponse {
+                votes_cast: record.summary.votes_cast,
+                eligible_proposals: record.summary.eligible_proposals,
+                participation_rate: record.summary.participation_rate,
+                with_majority_rate: record.summary.with_majority_rate,
+            },
+        }
+    }
+}
+
 // Treasury models
 
 /// Create transaction request
@@ -260,11 +559,23 @@ pub struct CreateTransactionRequest {
     /// Token symbol
     pub token_symbol: String,
     /// Amount
-    pub amount: String,
+    #[serde(with = "base_units")]
+    pub amount: ApiAmount,
     /// Related proposal ID (optional)
     pub related_proposal_id: Option<String>,
     /// Additional metadata
     pub metadata: Option<serde_json::Value>,
+    /// Key identifying this request across retries; a retry with the same
+    /// key returns the transaction the first request created
+    #[serde(default)]
+    pub idempotency_key: Option<String>,
+}
+
+/// Cancel transaction request
+#[derive(Deserialize)]
+pub struct CancelTransactionRequest {
+    /// Reason for the cancellation
+    pub reason: String,
 }
 
 /// Transaction response
@@ -279,13 +590,21 @@ pub struct TransactionResponse {
     /// Token symbol
     pub token_symbol: String,
     /// Amount
-    pub amount: String,
+    #[serde(with = "base_units")]
+    pub amount: ApiAmount,
+    /// Formatted amount with symbol, localized to the request locale
+    pub formatted_amount: String,
     /// Transaction status
     pub status: String,
     /// Creation timestamp
-    pub created_at: u64,
+    #[serde(with = "rfc3339")]
+    pub created_at: DateTime<Utc>,
     /// Execution timestamp
-    pub executed_at: Option<u64>,
+    #[serde(default, with = "rfc3339::option")]
+    pub executed_at: Option<DateTime<Utc>>,
+    /// Time after which a pending transaction can no longer be approved
+    #[serde(default, with = "rfc3339::option")]
+    pub expires_at: Option<DateTime<Utc>>,
     /// Required approvals
     pub required_approvals: u32,
     /// Current approvals
@@ -306,13 +625,42 @@ pub struct TreasuryBalanceResponse {
     /// Token name
     pub token_name: String,
     /// Balance
-    pub balance: String,
-    /// Formatted balance with symbol
+    #[serde(with = "base_units")]
+    pub balance: ApiAmount,
+    /// Formatted balance with symbol, localized to the request locale
     pub formatted_balance: String,
+    /// Value in USD as a decimal string (null if no usable price)
+    pub usd_value: Option<String>,
+    /// Price source (available, stale, unavailable)
+    pub oracle_status: String,
+    /// When the price was fetched from the oracle (null if no usable price)
+    #[serde(default, with = "rfc3339::option")]
+    pub priced_at: Option<DateTime<Utc>>,
     /// Token details
     pub token: Option<TokenResponse>,
 }
 
+impl TreasuryBalanceResponse {
+    /// Build the response for a valued balance, formatted for `locale`
+    pub fn new(balance: crate::treasury::ValuedBalance, token: &crate::token::Token, locale: Locale) -> Self {
+        Self {
+            formatted_balance: format_token_amount_localized(
+                balance.balance as ApiAmount,
+                &balance.symbol,
+                token.decimals,
+                locale,
+            ),
+            token_symbol: balance.symbol,
+            token_name: token.name.clone(),
+            balance: balance.balance as ApiAmount,
+            usd_value: balance.usd_value.map(|value| value.to_string()),
+            oracle_status: format!("{:?}", balance.oracle_status).to_lowercase(),
+            priced_at: balance.priced_at,
+            token: None,
+        }
+    }
+}
+
 // Token models
 
 /// Token response
@@ -329,13 +677,17 @@ pub struct TokenResponse {
     /// Decimals
     pub decimals: u8,
     /// Total supply
-    pub total_supply: Option<String>,
+    #[serde(default, with = "base_units::option")]
+    pub total_supply: Option<ApiAmount>,
+    /// Formatted total supply with symbol, localized to the request locale
+    pub formatted_total_supply: Option<String>,
     /// Contract address
     pub contract_address: Option<String>,
     /// Chain ID
     pub chain_id: Option<u64>,
     /// Creation timestamp
-    pub created_at: u64,
+    #[serde(with = "rfc3339")]
+    pub created_at: DateTime<Utc>,
     /// Additional metadata
     pub metadata: Option<serde_json::Value>,
 }
@@ -354,11 +706,13 @@ pub struct TokenBalanceResponse {
     /// Member information
     pub member: Option<MemberResponse>,
     /// Balance
-    pub balance: String,
-    /// Formatted balance with symbol
+    #[serde(with = "base_units")]
+    pub balance: ApiAmount,
+    /// Formatted balance with symbol, localized to the request locale
     pub formatted_balance: String,
     /// Last update timestamp
-    pub last_updated: u64,
+    #[serde(with = "rfc3339")]
+    pub last_updated: DateTime<Utc>,
 }
 
 /// Token transfer request
@@ -369,7 +723,8 @@ pub struct TokenTransferRequest {
     /// Recipient ID or address
     pub to: String,
     /// Amount
-    pub amount: String,
+    #[serde(with = "base_units")]
+    pub amount: ApiAmount,
     /// Description
     pub description: Option<String>,
     /// Additional metadata
@@ -394,11 +749,13 @@ pub struct TokenTransferResponse {
     /// Recipient information
     pub to_member: Option<MemberResponse>,
     /// Amount
-    pub amount: String,
-    /// Formatted amount with symbol
+    #[serde(with = "base_units")]
+    pub amount: ApiAmount,
+    /// Formatted amount with symbol, localized to the request locale
     pub formatted_amount: String,
     /// Transfer timestamp
-    pub timestamp: u64,
+    #[serde(with = "rfc3339")]
+    pub timestamp: DateTime<Utc>,
     /// Transaction hash
     pub transaction_hash: Option<String>,
     /// Description
@@ -0,0 +1,261 @@
+//! Request rate limiting for the AtomSi DAO API
+//!
+//! Each client gets a token bucket holding `api.rate_limit_per_minute`
+//! requests that refills continuously over a minute. Clients are keyed by
+//! source IP, resolved through trusted proxies the same way as the IP filter,
+//! and the limiter runs before authentication so rejected requests count too.
+//! Buckets live in a sharded in-memory map, and full buckets that have been
+//! idle for a while are dropped by a background task.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{ConnectInfo, State},
+    http::Request,
+    middleware::Next,
+    response::Response,
+};
+use tokio::task::JoinHandle;
+
+use crate::api::api_middleware::{too_many_requests, IpFilter};
+
+/// Number of independently locked bucket maps
+const SHARDS: usize = 16;
+
+/// How often idle buckets are cleaned up
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A client's remaining budget
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    /// Requests that can be made right now
+    tokens: f64,
+    /// When `tokens` was last brought up to date
+    updated_at: Instant,
+}
+
+/// Token-bucket rate limiter keyed by client
+pub struct RateLimiter {
+    /// Requests allowed per minute, which is also the burst size
+    per_minute: u32,
+    /// Buckets, sharded by key hash
+    shards: Vec<Mutex<HashMap<String, Bucket>>>,
+    /// Resolves the client address behind trusted proxies
+    client_ips: IpFilter,
+}
+
+impl RateLimiter {
+    /// Create a limiter allowing `per_minute` requests per client per minute
+    pub fn new(per_minute: u32) -> Self {
+        Self {
+            per_minute: per_minute.max(1),
+            shards: (0..SHARDS).map(|_| Mutex::new(HashMap::new())).collect(),
+            client_ips: IpFilter::default(),
+        }
+    }
+
+    /// Take client addresses from `X-Forwarded-For` when the peer is one of
+    /// `filter`'s trusted proxies
+    pub fn with_ip_filter(mut self, filter: IpFilter) -> Self {
+        self.client_ips = filter;
+        self
+    }
+
+    /// Take one request from `key`'s budget at `now`
+    ///
+    /// Returns how long to wait before retrying when the budget is spent.
+    pub fn check(&self, key: &str, now: Instant) -> Result<(), Duration> {
+        let capacity = self.per_minute as f64;
+
+        let mut shard = self.shard(key).lock().unwrap();
+        let bucket = shard.entry(key.to_string()).or_insert(Bucket {
+            tokens: capacity,
+            updated_at: now,
+        });
+
+        let elapsed = now.saturating_duration_since(bucket.updated_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * capacity / 60.0).min(capacity);
+        bucket.updated_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) * 60.0 / capacity))
+        }
+    }
+
+    /// Drop buckets that have refilled completely by `now`
+    ///
+    /// A full bucket behaves exactly like a missing one, so this never
+    /// changes what a client is allowed.
+    pub fn cleanup(&self, now: Instant) {
+        let refill = Duration::from_secs(60);
+        for shard in &self.shards {
+            shard
+                .lock()
+                .unwrap()
+                .retain(|_, bucket| now.saturating_duration_since(bucket.updated_at) < refill);
+        }
+    }
+
+    /// Number of clients currently tracked
+    pub fn tracked_clients(&self) -> usize {
+        self.shards.iter().map(|shard| shard.lock().unwrap().len()).sum()
+    }
+
+    /// Spawn the background task dropping idle buckets
+    pub fn spawn_cleanup(self: &Arc<Self>) -> JoinHandle<()> {
+        let limiter = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(CLEANUP_INTERVAL);
+            loop {
+                interval.tick().await;
+                limiter.cleanup(Instant::now());
+            }
+        })
+    }
+
+    /// Rate-limit key for a request: the client IP behind any trusted
+    /// proxies, or the peer address if the forwarding header is malformed
+    fn client_key<B>(&self, req: &Request<B>) -> String {
+        match req.extensions().get::<ConnectInfo<SocketAddr>>() {
+            Some(ConnectInfo(peer)) => {
+                let client = self.client_ips.client_ip(peer.ip(), req.headers()).unwrap_or(peer.ip());
+                format!("ip:{}", client)
+            }
+            None => "ip:unknown".to_string(),
+        }
+    }
+
+    fn shard(&self, key: &str) -> &Mutex<HashMap<String, Bucket>> {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        &self.shards[hasher.finish() as usize % self.shards.len()]
+    }
+}
+
+/// Middleware refusing requests over the client's per-minute budget
+///
+/// Refused requests get 429 Too Many Requests with `Retry-After`.
+pub async fn rate_limit<B>(
+    State(limiter): State<Arc<RateLimiter>>,
+    req: Request<B>,
+    next: Next<B>,
+) -> Response {
+    match limiter.check(&limiter.client_key(&req), Instant::now()) {
+        Ok(()) => next.run(req).await,
+        Err(retry_after) => too_many_requests(
+            "Rate limit exceeded",
+            retry_after.as_secs_f64().ceil() as u64,
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::IpFilterConfig;
+    use axum::{body::Body, http::StatusCode, middleware, response::IntoResponse, routing::get, Router};
+    use tower::ServiceExt;
+
+    fn request(peer: &str, forwarded_for: Option<&str>) -> Request<Body> {
+        let mut request = Request::builder()
+            .uri("/proposals")
+            .extension(ConnectInfo(peer.parse::<SocketAddr>().unwrap()));
+        if let Some(forwarded_for) = forwarded_for {
+            request = request.header("x-forwarded-for", forwarded_for);
+        }
+        request.body(Body::empty()).unwrap()
+    }
+
+    #[test]
+    fn test_budget_recovers_over_the_window() {
+        let limiter = RateLimiter::new(2);
+        let start = Instant::now();
+
+        assert!(limiter.check("ip:10.0.0.1", start).is_ok());
+        assert!(limiter.check("ip:10.0.0.1", start).is_ok());
+        let retry_after = limiter.check("ip:10.0.0.1", start).unwrap_err();
+        assert_eq!(retry_after.as_secs(), 30);
+
+        // Other clients have their own budget
+        assert!(limiter.check("ip:10.0.0.2", start).is_ok());
+
+        // One request's worth refills every 30 seconds
+        assert!(limiter.check("ip:10.0.0.1", start + Duration::from_secs(20)).is_err());
+        assert!(limiter.check("ip:10.0.0.1", start + Duration::from_secs(31)).is_ok());
+        assert!(limiter.check("ip:10.0.0.1", start + Duration::from_secs(31)).is_err());
+
+        // After a full window the burst is available again
+        let later = start + Duration::from_secs(120);
+        assert!(limiter.check("ip:10.0.0.1", later).is_ok());
+        assert!(limiter.check("ip:10.0.0.1", later).is_ok());
+    }
+
+    #[test]
+    fn test_cleanup_drops_idle_buckets() {
+        let limiter = RateLimiter::new(10);
+        let start = Instant::now();
+        limiter.check("ip:10.0.0.1", start).unwrap();
+        limiter.check("ip:10.0.0.2", start + Duration::from_secs(50)).unwrap();
+
+        limiter.cleanup(start + Duration::from_secs(70));
+        assert_eq!(limiter.tracked_clients(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_requests_past_limit_get_429() {
+        let limiter = Arc::new(RateLimiter::new(2));
+        let app = Router::new()
+            .route("/proposals", get(|| async { "ok" }))
+            .route_layer(middleware::from_fn_with_state(limiter, rate_limit));
+
+        for _ in 0..2 {
+            let response = app.clone().oneshot(request("10.0.0.1:4000", None)).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        let response = app.oneshot(request("10.0.0.1:4000", None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()["retry-after"], "30");
+    }
+
+    #[test]
+    fn test_clients_behind_trusted_proxy_keyed_separately() {
+        let filter = IpFilter::from_config(&IpFilterConfig {
+            trusted_proxies: vec!["10.0.0.0/8".to_string()],
+            ..Default::default()
+        })
+        .unwrap();
+        let limiter = RateLimiter::new(1).with_ip_filter(filter);
+
+        assert_eq!(limiter.client_key(&request("10.0.0.5:4000", Some("203.0.113.7"))), "ip:203.0.113.7");
+        assert_eq!(limiter.client_key(&request("10.0.0.5:4000", Some("203.0.113.8"))), "ip:203.0.113.8");
+
+        // Untrusted peers can't pick their own key
+        assert_eq!(limiter.client_key(&request("198.51.100.1:4000", Some("203.0.113.7"))), "ip:198.51.100.1");
+    }
+
+    #[tokio::test]
+    async fn test_rejected_requests_count_against_budget() {
+        let limiter = Arc::new(RateLimiter::new(1));
+        let app = Router::new()
+            .route("/proposals", get(|| async { "ok" }))
+            .route_layer(middleware::from_fn(|_req: Request<Body>, _next: Next<Body>| async {
+                StatusCode::UNAUTHORIZED.into_response()
+            }))
+            .route_layer(middleware::from_fn_with_state(limiter, rate_limit));
+
+        // Requests with bad tokens are throttled before authentication
+        let response = app.clone().oneshot(request("10.0.0.1:4000", None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        let response = app.oneshot(request("10.0.0.1:4000", None)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+}
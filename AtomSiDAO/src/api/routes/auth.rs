@@ -40,6 +40,9 @@ pub async fn logout(
 }
 
 /// Check if current session is valid
+///
+/// Always reads from the primary, since a session issued moments ago may not
+/// have been replicated yet.
 pub async fn check_session(
     Extension(context): Extension<Arc<DAOContext>>,
 ) -> Json<ApiResponse<bool>> {
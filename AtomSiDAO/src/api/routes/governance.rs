@@ -6,22 +6,35 @@ use axum::{
     extract::{Path, Query, Extension},
     Json,
 };
+use serde::Deserialize;
 use std::sync::Arc;
 
 use crate::api::models::{
     ApiResponse, PaginationParams, PaginatedResponse, 
-    ProposalResponse, CreateProposalRequest, VoteRequest, VoteResponse
+    ProposalResponse, CreateProposalRequest, VoteRequest, VoteResponse,
+    GovernanceParameterResponse, SponsorResponse
 };
+use crate::api::validation::ValidatedJson;
+use crate::core::Address;
+use crate::security::AuthenticatedMember;
 use crate::DAOContext;
 use crate::error::Result;
 
 /// Get all proposals
+///
+/// Read-replica safe: listings may lag the primary by the replication delay.
 pub async fn get_proposals(
     pagination: Query<PaginationParams>,
     Extension(context): Extension<Arc<DAOContext>>,
 ) -> Json<ApiResponse<PaginatedResponse<ProposalResponse>>> {
     // This is a placeholder implementation
-    // In a real implementation, we would call the proposal service to get proposals
+    // In a real implementation, we would call the proposal service to get proposals,
+    // using `get_proposals_page` when `page_request` selects cursor paging
+    
+    // An invalid cursor is reported rather than restarting from the top
+    if let Err(e) = pagination.page_request() {
+        return Json(ApiResponse::error(&e.to_string()));
+    }
     
     let proposals = Vec::new();
     let meta = crate::api::models::PaginationMeta {
@@ -29,6 +42,8 @@ pub async fn get_proposals(
         limit: pagination.limit,
         total: 0,
         total_pages: 0,
+        next_cursor: None,
+        prev_cursor: None,
     };
     
     let response = PaginatedResponse {
@@ -39,7 +54,50 @@ pub async fn get_proposals(
     Json(ApiResponse::success(response))
 }
 
+/// Query parameters for the votable proposals listing
+#[derive(Deserialize)]
+pub struct VotableParams {
+    /// Address of the member to list proposals for
+    pub address: String,
+}
+
+/// Get open proposals the member can still vote on, soonest deadline first
+///
+/// Read-replica safe: a vote cast moments ago may not have replicated yet, so
+/// the proposal can briefly still be listed; voting on it again is rejected.
+pub async fn get_votable_proposals(
+    Query(params): Query<VotableParams>,
+    Extension(context): Extension<Arc<DAOContext>>,
+) -> Json<ApiResponse<Vec<ProposalResponse>>> {
+    if params.address.trim().is_empty() {
+        return Json(ApiResponse::error("Member address is required"));
+    }
+    
+    // This is a placeholder implementation
+    // In a real implementation, we would call `ProposalManager::votable_by`
+    
+    Json(ApiResponse::success(Vec::new()))
+}
+
+/// Get the effective governance parameters
+///
+/// Each value reports whether it comes from the config or was amended by an
+/// executed proposal.
+pub async fn get_governance_parameters(
+    Extension(context): Extension<Arc<DAOContext>>,
+) -> Json<ApiResponse<Vec<GovernanceParameterResponse>>> {
+    // This is a placeholder implementation
+    // In a real implementation, we would call `GovernanceEngine::parameters`
+    // so that amendments from the settings store are included; reporting the
+    // config alone would show amended values as defaults
+    
+    // Return an error for now
+    Json(ApiResponse::error("Not implemented"))
+}
+
 /// Get a proposal by ID
+///
+/// Read-replica safe: vote tallies may briefly trail votes that were just cast.
 pub async fn get_proposal(
     Path(id): Path<String>,
     Extension(context): Extension<Arc<DAOContext>>,
@@ -53,8 +111,8 @@ pub async fn get_proposal(
 
 /// Create a new proposal
 pub async fn create_proposal(
-    Json(request): Json<CreateProposalRequest>,
     Extension(context): Extension<Arc<DAOContext>>,
+    ValidatedJson(request): ValidatedJson<CreateProposalRequest>,
 ) -> Json<ApiResponse<ProposalResponse>> {
     // This is a placeholder implementation
     // In a real implementation, we would call the proposal service to create a proposal
@@ -63,6 +121,32 @@ pub async fn create_proposal(
     Json(ApiResponse::error("Not implemented"))
 }
 
+/// Sponsor a draft proposal as the authenticated member
+///
+/// Returns the proposal's sponsors, including the new endorsement.
+pub async fn sponsor_proposal(
+    Path(id): Path<String>,
+    Extension(context): Extension<Arc<DAOContext>>,
+    Extension(member): Extension<AuthenticatedMember>,
+) -> Json<ApiResponse<Vec<SponsorResponse>>> {
+    let sponsor = match Address::parse(&member.address) {
+        Ok(sponsor) => sponsor,
+        Err(e) => return Json(ApiResponse::error(&e.to_string())),
+    };
+    
+    let manager = context.proposal_manager();
+    if let Err(e) = manager.sponsor(&id, &sponsor).await {
+        return Json(ApiResponse::error(&e.to_string()));
+    }
+    
+    match manager.get_proposal(&id).await {
+        Ok(proposal) => Json(ApiResponse::success(
+            proposal.sponsors.into_iter().map(SponsorResponse::from).collect(),
+        )),
+        Err(e) => Json(ApiResponse::error(&e.to_string())),
+    }
+}
+
 /// Vote on a proposal
 pub async fn vote_on_proposal(
     Path(id): Path<String>,
@@ -6,22 +6,34 @@ use axum::{
     extract::{Path, Query, Extension},
     Json,
 };
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
 use std::sync::Arc;
 
 use crate::api::models::{
     ApiResponse, PaginationParams, PaginatedResponse, 
-    MemberResponse, ActivityResponse
+    MemberResponse, ActivityResponse, VotingRecordResponse
 };
+use crate::governance::{VotingRecord, VotingRecordQuery, VotingRecordSummary};
+use crate::utils::time::rfc3339;
 use crate::DAOContext;
 use crate::error::Result;
 
 /// Get all members
+///
+/// Read-replica safe: newly registered members may take a moment to appear.
 pub async fn get_members(
     pagination: Query<PaginationParams>,
     Extension(context): Extension<Arc<DAOContext>>,
 ) -> Json<ApiResponse<PaginatedResponse<MemberResponse>>> {
     // This is a placeholder implementation
-    // In a real implementation, we would call the identity service to get members
+    // In a real implementation, we would call the identity service to get members,
+    // using `get_members_page` when `page_request` selects cursor paging
+    
+    // An invalid cursor is reported rather than restarting from the top
+    if let Err(e) = pagination.page_request() {
+        return Json(ApiResponse::error(&e.to_string()));
+    }
     
     let members = Vec::new();
     let meta = crate::api::models::PaginationMeta {
@@ -29,6 +41,8 @@ pub async fn get_members(
         limit: pagination.limit,
         total: 0,
         total_pages: 0,
+        next_cursor: None,
+        prev_cursor: None,
     };
     
     let response = PaginatedResponse {
@@ -40,6 +54,8 @@ pub async fn get_members(
 }
 
 /// Get a member by ID
+///
+/// Read-replica safe: role and status changes may lag the primary briefly.
 pub async fn get_member(
     Path(id): Path<String>,
     Extension(context): Extension<Arc<DAOContext>>,
@@ -51,7 +67,71 @@ pub async fn get_member(
     Json(ApiResponse::error(&format!("Member not found: {}", id)))
 }
 
+/// Query parameters for a member's voting record
+#[derive(Deserialize)]
+pub struct VotingRecordParams {
+    /// Only include votes cast at or after this time
+    #[serde(default, with = "rfc3339::option")]
+    pub from: Option<DateTime<Utc>>,
+    /// Only include votes cast at or before this time
+    #[serde(default, with = "rfc3339::option")]
+    pub to: Option<DateTime<Utc>>,
+    /// Page number (1-based)
+    #[serde(default = "default_page")]
+    pub page: usize,
+    /// Items per page
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+}
+
+fn default_page() -> usize {
+    1
+}
+
+fn default_limit() -> usize {
+    20
+}
+
+/// Get the proposals a member voted on, with outcomes and summary stats
+///
+/// Read-replica safe: a vote cast moments ago may not be listed yet.
+pub async fn get_voting_record(
+    Path(address): Path<String>,
+    Query(params): Query<VotingRecordParams>,
+    Extension(context): Extension<Arc<DAOContext>>,
+) -> Json<ApiResponse<VotingRecordResponse>> {
+    let query = VotingRecordQuery {
+        from: params.from,
+        to: params.to,
+        page: params.page,
+        limit: params.limit,
+    };
+    
+    if query.page == 0 || query.limit == 0 {
+        return Json(ApiResponse::error("Page and limit must be at least 1"));
+    }
+    
+    // This is a placeholder implementation
+    // In a real implementation, we would call `GovernanceEngine::voting_record`
+    
+    let record = VotingRecord {
+        address,
+        entries: Vec::new(),
+        total: 0,
+        summary: VotingRecordSummary {
+            votes_cast: 0,
+            eligible_proposals: 0,
+            participation_rate: None,
+            with_majority_rate: None,
+        },
+    };
+    
+    Json(ApiResponse::success(VotingRecordResponse::new(record, query.page, query.limit)))
+}
+
 /// Get all member activities
+///
+/// Read-replica safe: the most recent activities may not be listed yet.
 pub async fn get_activities(
     pagination: Query<PaginationParams>,
     Extension(context): Extension<Arc<DAOContext>>,
@@ -65,6 +145,8 @@ pub async fn get_activities(
         limit: pagination.limit,
         total: 0,
         total_pages: 0,
+        next_cursor: None,
+        prev_cursor: None,
     };
     
     let response = PaginatedResponse {
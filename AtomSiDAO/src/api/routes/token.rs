@@ -12,10 +12,13 @@ use crate::api::models::{
     ApiResponse, PaginationParams, PaginatedResponse, 
     TokenResponse, TokenBalanceResponse, TokenTransferRequest, TokenTransferResponse
 };
+use crate::api::validation::ValidatedJson;
 use crate::DAOContext;
 use crate::error::Result;
 
 /// Get all tokens
+///
+/// Read-replica safe: token metadata rarely changes, so replication lag is harmless.
 pub async fn get_tokens(
     pagination: Query<PaginationParams>,
     Extension(context): Extension<Arc<DAOContext>>,
@@ -29,6 +32,8 @@ pub async fn get_tokens(
         limit: pagination.limit,
         total: 0,
         total_pages: 0,
+        next_cursor: None,
+        prev_cursor: None,
     };
     
     let response = PaginatedResponse {
@@ -40,6 +45,8 @@ pub async fn get_tokens(
 }
 
 /// Get a token by ID
+///
+/// Read-replica safe.
 pub async fn get_token(
     Path(id): Path<String>,
     Extension(context): Extension<Arc<DAOContext>>,
@@ -52,6 +59,8 @@ pub async fn get_token(
 }
 
 /// Get token balances for a specific token
+///
+/// Read-replica safe: balances may trail transfers by the replication delay.
 pub async fn get_token_balances(
     Path(id): Path<String>,
     pagination: Query<PaginationParams>,
@@ -66,6 +75,8 @@ pub async fn get_token_balances(
         limit: pagination.limit,
         total: 0,
         total_pages: 0,
+        next_cursor: None,
+        prev_cursor: None,
     };
     
     let response = PaginatedResponse {
@@ -78,8 +89,8 @@ pub async fn get_token_balances(
 
 /// Transfer tokens
 pub async fn transfer_tokens(
-    Json(request): Json<TokenTransferRequest>,
     Extension(context): Extension<Arc<DAOContext>>,
+    ValidatedJson(request): ValidatedJson<TokenTransferRequest>,
 ) -> Json<ApiResponse<TokenTransferResponse>> {
     // This is a placeholder implementation
     // In a real implementation, we would call the token service to transfer tokens
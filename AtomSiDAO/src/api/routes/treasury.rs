@@ -10,18 +10,30 @@ use std::sync::Arc;
 
 use crate::api::models::{
     ApiResponse, PaginationParams, PaginatedResponse, 
-    TransactionResponse, CreateTransactionRequest, TreasuryBalanceResponse
+    TransactionResponse, CreateTransactionRequest, CancelTransactionRequest,
+    TreasuryBalanceResponse
 };
+use crate::api::locale::RequestLocale;
+use crate::api::validation::ValidatedJson;
+use crate::core::Symbol;
 use crate::DAOContext;
 use crate::error::Result;
 
 /// Get all treasury transactions
+///
+/// Read-replica safe: newly created transactions may take a moment to appear.
 pub async fn get_transactions(
     pagination: Query<PaginationParams>,
     Extension(context): Extension<Arc<DAOContext>>,
 ) -> Json<ApiResponse<PaginatedResponse<TransactionResponse>>> {
     // This is a placeholder implementation
-    // In a real implementation, we would call the treasury service to get transactions
+    // In a real implementation, we would call the treasury service to get transactions,
+    // using `get_transactions_page` when `page_request` selects cursor paging
+    
+    // An invalid cursor is reported rather than restarting from the top
+    if let Err(e) = pagination.page_request() {
+        return Json(ApiResponse::error(&e.to_string()));
+    }
     
     let transactions = Vec::new();
     let meta = crate::api::models::PaginationMeta {
@@ -29,6 +41,8 @@ pub async fn get_transactions(
         limit: pagination.limit,
         total: 0,
         total_pages: 0,
+        next_cursor: None,
+        prev_cursor: None,
     };
     
     let response = PaginatedResponse {
@@ -40,6 +54,8 @@ pub async fn get_transactions(
 }
 
 /// Get a transaction by ID
+///
+/// Read-replica safe: the approval count may trail approvals that were just submitted.
 pub async fn get_transaction(
     Path(id): Path<String>,
     Extension(context): Extension<Arc<DAOContext>>,
@@ -53,8 +69,8 @@ pub async fn get_transaction(
 
 /// Create a new treasury transaction
 pub async fn create_transaction(
-    Json(request): Json<CreateTransactionRequest>,
     Extension(context): Extension<Arc<DAOContext>>,
+    ValidatedJson(request): ValidatedJson<CreateTransactionRequest>,
 ) -> Json<ApiResponse<TransactionResponse>> {
     // This is a placeholder implementation
     // In a real implementation, we would call the treasury service to create a transaction
@@ -75,14 +91,49 @@ pub async fn approve_transaction(
     Json(ApiResponse::error(&format!("Not implemented: approving transaction {}", id)))
 }
 
+/// Cancel an approved treasury transaction before it is executed
+pub async fn cancel_transaction(
+    Path(id): Path<String>,
+    Extension(context): Extension<Arc<DAOContext>>,
+    Json(request): Json<CancelTransactionRequest>,
+) -> Json<ApiResponse<TransactionResponse>> {
+    if request.reason.trim().is_empty() {
+        return Json(ApiResponse::error("Cancellation reason is required"));
+    }
+    
+    // This is a placeholder implementation
+    // In a real implementation, we would call the treasury service to cancel the transaction
+    
+    // Return an error for now
+    Json(ApiResponse::error(&format!("Not implemented: cancelling transaction {}", id)))
+}
+
 /// Get treasury balances
+///
+/// Read-replica safe: balances are informational and may lag executed transfers.
+/// Formatted balances follow the request locale.
 pub async fn get_balances(
+    RequestLocale(locale): RequestLocale,
     Extension(context): Extension<Arc<DAOContext>>,
 ) -> Json<ApiResponse<Vec<TreasuryBalanceResponse>>> {
-    // This is a placeholder implementation
-    // In a real implementation, we would call the treasury service to get balances
+    let valued = match context.treasury_manager().get_valued_balances().await {
+        Ok(valued) => valued,
+        Err(e) => return Json(ApiResponse::error(&e.to_string())),
+    };
+    
+    let token_manager = context.token_manager();
+    let mut balances = Vec::new();
     
-    let balances = Vec::new();
+    for balance in valued {
+        let token = match Symbol::parse(&balance.symbol) {
+            Ok(symbol) => token_manager.get_token(&symbol).await,
+            Err(e) => Err(e),
+        };
+        match token {
+            Ok(token) => balances.push(TreasuryBalanceResponse::new(balance, &token, locale)),
+            Err(e) => return Json(ApiResponse::error(&e.to_string())),
+        }
+    }
     
     Json(ApiResponse::success(balances))
 } 
\ No newline at end of file
@@ -0,0 +1,278 @@
+//! Request validation for the AtomSi DAO API
+//!
+//! Create handlers take their body as [`ValidatedJson`], which deserializes
+//! it and runs the request's [`Validate`] checks before the handler sees it.
+//! Invalid requests get 422 Unprocessable Entity with one entry per failed
+//! field.
+
+use axum::{
+    async_trait,
+    body::HttpBody,
+    extract::FromRequest,
+    http::{Request, StatusCode},
+    response::{IntoResponse, Response},
+    BoxError, Json,
+};
+use serde::de::DeserializeOwned;
+
+use crate::api::models::{ApiResponse, CreateProposalRequest, CreateTransactionRequest, TokenTransferRequest};
+use crate::utils::amount::ApiAmount;
+use crate::utils::validation::{
+    validate_address, validate_numeric_range, validate_string_length, validate_token_amount,
+    validate_token_symbol, ValidationError, ValidationResult,
+};
+
+/// Maximum length of titles and other short text fields
+const MAX_TITLE_LENGTH: usize = 200;
+
+/// Maximum length of descriptions
+const MAX_DESCRIPTION_LENGTH: usize = 50_000;
+
+/// Longest voting period a proposal may request, in seconds (one year)
+const MAX_VOTING_DURATION: u64 = 365 * 24 * 60 * 60;
+
+/// Execution data fields holding addresses
+const EXECUTION_ADDRESS_FIELDS: &[&str] = &["recipient", "contract", "target"];
+
+/// Field checks for a request body
+pub trait Validate {
+    /// Check every field, returning all failures
+    fn validate(&self) -> ValidationResult;
+}
+
+/// JSON body that passed its [`Validate`] checks
+#[derive(Debug, Clone)]
+pub struct ValidatedJson<T>(pub T);
+
+#[async_trait]
+impl<T, S, B> FromRequest<S, B> for ValidatedJson<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+    B: HttpBody + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request<B>, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(req, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+
+        value.validate().map_err(|errors| {
+            (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(ApiResponse::<()>::validation_errors(errors)),
+            )
+                .into_response()
+        })?;
+
+        Ok(Self(value))
+    }
+}
+
+/// Collects failures from the `utils::validation` helpers under request
+/// field names
+#[derive(Default)]
+struct Errors(Vec<ValidationError>);
+
+impl Errors {
+    /// Record the failures of a check against `field`
+    fn check(&mut self, field: &str, result: ValidationResult) {
+        if let Err(errors) = result {
+            self.0.extend(errors.into_iter().map(|error| ValidationError {
+                field: field.to_string(),
+                message: error.message,
+            }));
+        }
+    }
+
+    fn finish(self) -> ValidationResult {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(self.0)
+        }
+    }
+}
+
+/// Check that an amount is positive
+fn validate_positive_amount(amount: ApiAmount) -> ValidationResult {
+    // Anything past `u64::MAX` is positive
+    validate_token_amount(u64::try_from(amount).unwrap_or(u64::MAX), 0)
+}
+
+impl Validate for CreateProposalRequest {
+    fn validate(&self) -> ValidationResult {
+        let mut errors = Errors::default();
+
+        errors.check("title", validate_string_length(self.title.trim(), 1, MAX_TITLE_LENGTH));
+        errors.check(
+            "description",
+            validate_string_length(self.description.trim(), 1, MAX_DESCRIPTION_LENGTH),
+        );
+        errors.check("proposal_type", validate_string_length(&self.proposal_type, 1, MAX_TITLE_LENGTH));
+        errors.check(
+            "voting_duration",
+            validate_numeric_range(self.voting_duration, 1, MAX_VOTING_DURATION),
+        );
+
+        if let Some(execution_data) = &self.execution_data {
+            for field in EXECUTION_ADDRESS_FIELDS {
+                match execution_data.get(field) {
+                    Some(serde_json::Value::String(address)) => {
+                        errors.check(&format!("execution_data.{}", field), validate_address(address));
+                    }
+                    Some(_) => errors.check(
+                        &format!("execution_data.{}", field),
+                        Err(vec![ValidationError::new("address", "Address must be a string")]),
+                    ),
+                    None => {}
+                }
+            }
+        }
+
+        for (index, attachment) in self.attachments.iter().enumerate() {
+            errors.check(
+                &format!("attachments[{}].uri", index),
+                validate_string_length(&attachment.uri, 1, 2048),
+            );
+        }
+
+        errors.finish()
+    }
+}
+
+impl Validate for CreateTransactionRequest {
+    fn validate(&self) -> ValidationResult {
+        let mut errors = Errors::default();
+
+        errors.check(
+            "description",
+            validate_string_length(self.description.trim(), 1, MAX_DESCRIPTION_LENGTH),
+        );
+        errors.check("recipient_address", validate_address(&self.recipient_address));
+        errors.check("token_symbol", validate_token_symbol(&self.token_symbol));
+        errors.check("amount", validate_positive_amount(self.amount));
+        if let Some(key) = &self.idempotency_key {
+            errors.check(
+                "idempotency_key",
+                validate_string_length(key.trim(), 1, crate::treasury::MAX_IDEMPOTENCY_KEY_LENGTH),
+            );
+        }
+
+        errors.finish()
+    }
+}
+
+impl Validate for TokenTransferRequest {
+    fn validate(&self) -> ValidationResult {
+        let mut errors = Errors::default();
+
+        errors.check("token_id", validate_string_length(&self.token_id, 1, MAX_TITLE_LENGTH));
+        // `to` is a member ID or an address; only addresses have a format
+        if self.to.starts_with("0x") {
+            errors.check("to", validate_address(&self.to));
+        } else {
+            errors.check("to", validate_string_length(&self.to, 1, MAX_TITLE_LENGTH));
+        }
+        errors.check("amount", validate_positive_amount(self.amount));
+        if let Some(description) = &self.description {
+            errors.check("description", validate_string_length(description, 0, MAX_DESCRIPTION_LENGTH));
+        }
+
+        errors.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::api_middleware::{limit_request_body, BodyLimits};
+    use axum::{body::Body, middleware, routing::post, Router};
+    use tower::ServiceExt;
+
+    fn app(limits: BodyLimits) -> Router {
+        Router::new()
+            .route(
+                "/proposals",
+                post(|ValidatedJson(request): ValidatedJson<CreateProposalRequest>| async move { request.title }),
+            )
+            .layer(middleware::from_fn_with_state(limits, limit_request_body))
+    }
+
+    fn proposal(recipient: &str, description: &str) -> Request<Body> {
+        let body = serde_json::json!({
+            "title": "Fund the grants round",
+            "description": description,
+            "proposal_type": "Treasury",
+            "voting_duration": 604800,
+            "execution_data": { "recipient": recipient },
+        });
+
+        Request::builder()
+            .method("POST")
+            .uri("/proposals")
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    async fn json_body(response: Response) -> serde_json::Value {
+        let bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_valid_proposal_accepted() {
+        let request = proposal("0x1234567890123456789012345678901234567890", "Pays out the Q3 grants");
+        let response = app(BodyLimits::default()).oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_address_proposal_lists_field_errors() {
+        let request = proposal("0xnot-an-address", " ");
+        let response = app(BodyLimits::default()).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let body = json_body(response).await;
+        assert_eq!(body["success"], false);
+        let fields: Vec<&str> = body["errors"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|error| error["field"].as_str().unwrap())
+            .collect();
+        assert_eq!(fields, ["description", "execution_data.recipient"]);
+    }
+
+    #[tokio::test]
+    async fn test_over_limit_body_rejected() {
+        let limits = BodyLimits {
+            max_body_bytes: 256,
+            ..Default::default()
+        };
+        let request = proposal("0x1234567890123456789012345678901234567890", &"a".repeat(1024));
+
+        let response = app(limits).oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[test]
+    fn test_transaction_request_checks() {
+        let request: CreateTransactionRequest = serde_json::from_value(serde_json::json!({
+            "description": "Audit retainer",
+            "recipient_address": "0x1234567890123456789012345678901234567890",
+            "token_symbol": "US-DC",
+            "amount": "0",
+        }))
+        .unwrap();
+
+        let errors = request.validate().unwrap_err();
+        let fields: Vec<&str> = errors.iter().map(|error| error.field.as_str()).collect();
+        assert_eq!(fields, ["token_symbol", "amount"]);
+    }
+}
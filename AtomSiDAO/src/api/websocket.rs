@@ -1,31 +1,52 @@
 //! WebSocket module for AtomSi DAO
 //!
 //! This module provides WebSocket functionality for real-time updates.
+//!
+//! Clients authenticate with a session ID or JWT in the `token` query
+//! parameter (or the usual header or cookie); connections without one are
+//! anonymous, and ones with a token that doesn't resolve are closed straight
+//! after the upgrade. Events are only sent to clients whose role may see
+//! them, e.g. treasury events to Council and above.
+//!
+//! Clients start subscribed to the event types in the `events` query
+//! parameter (all of them if unset) and can change that by sending
+//! `{"action": "subscribe", "events": [...]}` or `"unsubscribe"`. Each such
+//! message is answered with the resulting subscriptions, or with an error
+//! that leaves them unchanged.
 
 use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::collections::HashMap;
 use std::time::{Duration, Instant};
 
 use axum::{
-    extract::{ws::{WebSocket, Message}, WebSocketUpgrade, Extension, Path, Query},
+    extract::{ws::{CloseFrame, WebSocket, Message}, WebSocketUpgrade, Extension, Path, Query},
+    http::HeaderMap,
     response::IntoResponse,
 };
 use serde::{Deserialize, Serialize};
-use tokio::sync::broadcast;
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinHandle;
 use futures::{stream::StreamExt, SinkExt};
-use tracing::{info, error, debug};
+use tracing::{info, error, debug, warn};
 
 use crate::DAOContext;
+use crate::api::api_middleware::{request_credential, RequestAuthenticator};
 use crate::error::Result;
+use crate::identity::MemberRole;
+use crate::security::AuthenticatedMember;
 
 /// Maximum number of messages to buffer in broadcast channel
-const MAX_BROADCAST_BUFFER: usize = 1000;
+pub(crate) const MAX_BROADCAST_BUFFER: usize = 1000;
 
 /// WebSocket close timeout (seconds)
 const WS_CLOSE_TIMEOUT: u64 = 5;
 
+/// Close code for connections whose token is rejected (policy violation)
+const CLOSE_POLICY_VIOLATION: u16 = 1008;
+
 /// Event types that can be broadcast through WebSockets
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum EventType {
     /// Proposal events
@@ -37,6 +58,9 @@ pub enum EventType {
     TransactionCreated,
     TransactionApproved,
     TransactionExecuted,
+    TransactionCancelled,
+    /// Warning that the price oracle is down and USD values are stale or missing
+    OracleUnavailable,
     
     /// Member events
     MemberRegistered,
@@ -46,6 +70,43 @@ pub enum EventType {
     ActivityRecorded,
 }
 
+impl EventType {
+    /// Every event type
+    pub const ALL: [EventType; 11] = [
+        EventType::ProposalCreated,
+        EventType::ProposalUpdated,
+        EventType::ProposalVoted,
+        EventType::TransactionCreated,
+        EventType::TransactionApproved,
+        EventType::TransactionExecuted,
+        EventType::TransactionCancelled,
+        EventType::OracleUnavailable,
+        EventType::MemberRegistered,
+        EventType::MemberUpdated,
+        EventType::ActivityRecorded,
+    ];
+    
+    /// Parse an event type from its wire name, e.g. `proposal_created`
+    pub fn from_name(name: &str) -> Option<EventType> {
+        serde_json::from_value(serde_json::Value::String(name.to_string())).ok()
+    }
+    
+    /// Lowest role that may receive the event, or `None` if anyone may
+    pub fn required_role(&self) -> Option<MemberRole> {
+        match self {
+            EventType::ProposalCreated | EventType::ProposalUpdated | EventType::ProposalVoted => None,
+            EventType::TransactionCreated
+            | EventType::TransactionApproved
+            | EventType::TransactionExecuted
+            | EventType::TransactionCancelled
+            | EventType::OracleUnavailable => Some(MemberRole::Council),
+            EventType::MemberRegistered | EventType::MemberUpdated | EventType::ActivityRecorded => {
+                Some(MemberRole::Member)
+            }
+        }
+    }
+}
+
 /// WebSocket event message
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WebSocketEvent {
@@ -66,6 +127,50 @@ pub struct WebSocketParams {
     events: Option<String>,
 }
 
+/// Ping and idle-timeout settings for WebSocket connections
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeepaliveConfig {
+    /// Close connections with no inbound traffic for this long (zero disables reaping)
+    pub idle_timeout: Duration,
+    /// Interval between pings and idle checks
+    pub ping_interval: Duration,
+}
+
+impl Default for KeepaliveConfig {
+    fn default() -> Self {
+        Self {
+            idle_timeout: Duration::from_secs(90),
+            ping_interval: Duration::from_secs(30),
+        }
+    }
+}
+
+impl KeepaliveConfig {
+    /// Whether idle connections are reaped
+    pub fn is_enabled(&self) -> bool {
+        !self.idle_timeout.is_zero() && !self.ping_interval.is_zero()
+    }
+}
+
+/// Subscription change sent by a client
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "lowercase")]
+enum SubscriptionRequest {
+    /// Start receiving the given event types
+    Subscribe { events: Vec<String> },
+    /// Stop receiving the given event types
+    Unsubscribe { events: Vec<String> },
+}
+
+/// Instruction for a client's writer task
+#[derive(Debug)]
+enum ClientCommand {
+    /// Send a message to the client
+    Send(Message),
+    /// Close the connection
+    Close,
+}
+
 /// Client information
 struct Client {
     /// Client ID
@@ -76,8 +181,25 @@ struct Client {
     last_active: Instant,
     /// Member ID if authenticated
     member_id: Option<String>,
-    /// Subscribed event types
+    /// Member role if authenticated
+    role: Option<MemberRole>,
+    /// Subscribed event types (may be changed after connecting)
     subscribed_events: Vec<EventType>,
+    /// Channel to the client's writer task
+    control: mpsc::UnboundedSender<ClientCommand>,
+}
+
+impl Client {
+    /// Whether the client should be sent an event: its role must allow the
+    /// event, and it must have subscribed to the event type
+    fn receives(&self, event_type: &EventType) -> bool {
+        let allowed = match event_type.required_role() {
+            None => true,
+            Some(required) => self.role.map_or(false, |role| role.is_at_least(required)),
+        };
+        
+        allowed && self.subscribed_events.contains(event_type)
+    }
 }
 
 /// WebSocket manager for handling connections and broadcasts
@@ -88,19 +210,56 @@ pub struct WebSocketManager {
     clients: Arc<Mutex<HashMap<String, Client>>>,
     /// DAO context
     context: Arc<DAOContext>,
+    /// Ping and idle-timeout settings
+    keepalive: KeepaliveConfig,
+    /// Number of connections closed for being idle
+    reaped_connections: AtomicU64,
 }
 
 impl WebSocketManager {
     /// Create a new WebSocket manager
-    pub fn new(context: Arc<DAOContext>) -> Self {
-        let (tx, _) = broadcast::channel(MAX_BROADCAST_BUFFER);
+    ///
+    /// Relays the events the context's managers broadcast.
+    pub fn new(context: Arc<DAOContext>, keepalive: KeepaliveConfig) -> Self {
         Self {
-            event_sender: tx,
+            event_sender: context.event_sender.clone(),
             clients: Arc::new(Mutex::new(HashMap::new())),
             context,
+            keepalive,
+            reaped_connections: AtomicU64::new(0),
         }
     }
     
+    /// Start pinging clients and reaping idle connections
+    ///
+    /// Returns `None` when reaping is disabled.
+    pub fn spawn_keepalive(self: &Arc<Self>) -> Option<JoinHandle<()>> {
+        if !self.keepalive.is_enabled() {
+            return None;
+        }
+        
+        let manager = self.clone();
+        Some(tokio::spawn(async move {
+            let mut interval = tokio::time::interval(manager.keepalive.ping_interval);
+            loop {
+                interval.tick().await;
+                
+                let reaped = reap_idle_clients(&manager.clients, Instant::now(), manager.keepalive.idle_timeout);
+                if reaped > 0 {
+                    manager.reaped_connections.fetch_add(reaped as u64, Ordering::Relaxed);
+                    info!("Closed {} idle WebSocket connections", reaped);
+                }
+                
+                ping_clients(&manager.clients);
+            }
+        }))
+    }
+    
+    /// Number of connections closed for being idle since startup
+    pub fn reaped_connections(&self) -> u64 {
+        self.reaped_connections.load(Ordering::Relaxed)
+    }
+    
     /// Get a new broadcast sender
     pub fn get_sender(&self) -> broadcast::Sender<WebSocketEvent> {
         self.event_sender.clone()
@@ -126,14 +285,22 @@ impl WebSocketManager {
     }
     
     /// Register a new client
-    fn register_client(&self, client_id: String, subscribed_events: Vec<EventType>, member_id: Option<String>) {
+    fn register_client(
+        &self,
+        client_id: String,
+        subscribed_events: Vec<EventType>,
+        member: Option<AuthenticatedMember>,
+        control: mpsc::UnboundedSender<ClientCommand>,
+    ) {
         let now = Instant::now();
         let client = Client {
             id: client_id.clone(),
             connected_at: now,
             last_active: now,
-            member_id,
+            member_id: member.as_ref().map(|member| member.address.clone()),
+            role: member.map(|member| member.role),
             subscribed_events,
+            control,
         };
         
         let mut clients = self.clients.lock().unwrap();
@@ -150,24 +317,18 @@ impl WebSocketManager {
     
     /// Update client's last activity time
     fn update_client_activity(&self, client_id: &str) {
-        let mut clients = self.clients.lock().unwrap();
-        if let Some(client) = clients.get_mut(client_id) {
-            client.last_active = Instant::now();
-        }
+        record_activity(&self.clients, client_id, Instant::now());
+    }
+    
+    /// Apply a control message from a client and return the reply
+    fn handle_client_message(&self, client_id: &str, text: &str) -> serde_json::Value {
+        apply_client_message(&self.clients, client_id, text)
     }
     
-    /// Check if event is subscribed by client
-    fn is_event_subscribed(&self, client_id: &str, event_type: &EventType) -> bool {
+    /// Check if an event should be sent to a client
+    fn is_event_delivered(&self, client_id: &str, event_type: &EventType) -> bool {
         let clients = self.clients.lock().unwrap();
-        if let Some(client) = clients.get(client_id) {
-            if client.subscribed_events.is_empty() {
-                // If no specific subscriptions, subscribe to all events
-                return true;
-            }
-            client.subscribed_events.iter().any(|e| std::mem::discriminant(e) == std::mem::discriminant(event_type))
-        } else {
-            false
-        }
+        clients.get(client_id).map_or(false, |client| client.receives(event_type))
     }
     
     /// Get connection stats
@@ -187,46 +348,160 @@ impl WebSocketManager {
     }
 }
 
+/// Ping every client; the pong counts as activity
+fn ping_clients(clients: &Mutex<HashMap<String, Client>>) {
+    let clients = clients.lock().unwrap();
+    for client in clients.values() {
+        let _ = client.control.send(ClientCommand::Send(Message::Ping(Vec::new())));
+    }
+}
+
+/// Record inbound traffic from a client at `now`
+fn record_activity(clients: &Mutex<HashMap<String, Client>>, client_id: &str, now: Instant) {
+    let mut clients = clients.lock().unwrap();
+    if let Some(client) = clients.get_mut(client_id) {
+        client.last_active = now;
+    }
+}
+
+/// Apply a subscription change sent by a client
+///
+/// Returns the reply to send: the client's subscriptions after the change,
+/// or an error if the message is malformed or names an unknown event type,
+/// in which case nothing changes.
+fn apply_client_message(clients: &Mutex<HashMap<String, Client>>, client_id: &str, text: &str) -> serde_json::Value {
+    let error = |message: String| serde_json::json!({ "type": "error", "message": message });
+    
+    let request: SubscriptionRequest = match serde_json::from_str(text) {
+        Ok(request) => request,
+        Err(e) => return error(format!("Invalid message: {}", e)),
+    };
+    let (action, names, subscribe) = match &request {
+        SubscriptionRequest::Subscribe { events } => ("subscribe", events, true),
+        SubscriptionRequest::Unsubscribe { events } => ("unsubscribe", events, false),
+    };
+    
+    let mut events = Vec::with_capacity(names.len());
+    for name in names {
+        match EventType::from_name(name) {
+            Some(event_type) => events.push(event_type),
+            None => return error(format!("Unknown event: {}", name)),
+        }
+    }
+    
+    let mut clients = clients.lock().unwrap();
+    let Some(client) = clients.get_mut(client_id) else {
+        return error("Not connected".to_string());
+    };
+    if subscribe {
+        for event_type in events {
+            if !client.subscribed_events.contains(&event_type) {
+                client.subscribed_events.push(event_type);
+            }
+        }
+    } else {
+        client.subscribed_events.retain(|event_type| !events.contains(event_type));
+    }
+    
+    serde_json::json!({
+        "type": "subscriptions",
+        "action": action,
+        "events": client.subscribed_events,
+    })
+}
+
+/// Remove clients idle for longer than `idle_timeout` and tell them to close
+///
+/// Returns the number of clients removed.
+fn reap_idle_clients(clients: &Mutex<HashMap<String, Client>>, now: Instant, idle_timeout: Duration) -> usize {
+    let mut clients = clients.lock().unwrap();
+    let idle: Vec<String> = clients
+        .values()
+        .filter(|client| now.saturating_duration_since(client.last_active) > idle_timeout)
+        .map(|client| client.id.clone())
+        .collect();
+    
+    for client_id in &idle {
+        if let Some(client) = clients.remove(client_id) {
+            debug!("Reaping idle client: {}", client_id);
+            let _ = client.control.send(ClientCommand::Close);
+        }
+    }
+    
+    idle.len()
+}
+
+/// Resolve the member behind a connection's token
+///
+/// Connections without a token are anonymous. A token that doesn't
+/// resolve, or any token when no authenticator is configured, is rejected
+/// with the reason to send in the close frame.
+async fn authenticate_client(
+    authenticator: Option<&dyn RequestAuthenticator>,
+    credential: Option<&str>,
+) -> std::result::Result<Option<AuthenticatedMember>, &'static str> {
+    let Some(credential) = credential else {
+        return Ok(None);
+    };
+    let Some(authenticator) = authenticator else {
+        return Err("Authentication is not available");
+    };
+    
+    authenticator
+        .authenticate(credential)
+        .await
+        .map(Some)
+        .map_err(|_| "Invalid token")
+}
+
+/// Close a connection whose token was rejected
+async fn reject_socket(mut socket: WebSocket, reason: &'static str) {
+    let frame = CloseFrame {
+        code: CLOSE_POLICY_VIOLATION,
+        reason: reason.into(),
+    };
+    let _ = socket.send(Message::Close(Some(frame))).await;
+}
+
 /// Handle WebSocket connection upgrade
 pub async fn handle_ws_upgrade(
     ws: WebSocketUpgrade,
     Extension(ws_manager): Extension<Arc<WebSocketManager>>,
+    Extension(authenticator): Extension<Option<Arc<dyn RequestAuthenticator>>>,
+    headers: HeaderMap,
     Query(params): Query<WebSocketParams>,
 ) -> impl IntoResponse {
-    // Authenticate the token if provided
-    let member_id = if let Some(token) = params.token {
-        // In a real implementation, validate the token and get member ID
-        // For now, we'll just use the token as the member ID
-        Some(token)
-    } else {
-        None
+    // Browsers can't set headers on the handshake, hence the query parameter
+    let credential = params
+        .token
+        .or_else(|| request_credential(&headers).map(str::to_string));
+    let member = match authenticate_client(authenticator.as_deref(), credential.as_deref()).await {
+        Ok(member) => member,
+        Err(reason) => {
+            warn!("Rejected WebSocket connection: {}", reason);
+            return ws.on_upgrade(move |socket| reject_socket(socket, reason));
+        }
     };
     
-    // Parse event subscriptions if provided
-    let subscribed_events = if let Some(events) = params.events {
-        events.split(',')
-            .filter_map(|e| match e.trim() {
-                "proposal_created" => Some(EventType::ProposalCreated),
-                "proposal_updated" => Some(EventType::ProposalUpdated),
-                "proposal_voted" => Some(EventType::ProposalVoted),
-                "transaction_created" => Some(EventType::TransactionCreated),
-                "transaction_approved" => Some(EventType::TransactionApproved),
-                "transaction_executed" => Some(EventType::TransactionExecuted),
-                "member_registered" => Some(EventType::MemberRegistered),
-                "member_updated" => Some(EventType::MemberUpdated),
-                "activity_recorded" => Some(EventType::ActivityRecorded),
-                _ => None,
-            })
-            .collect()
+    // Parse event subscriptions if provided; unknown names are skipped, and
+    // if none are recognized the client gets all events
+    let subscribed_events: Vec<EventType> = params
+        .events
+        .iter()
+        .flat_map(|events| events.split(','))
+        .filter_map(|name| EventType::from_name(name.trim()))
+        .collect();
+    let subscribed_events = if subscribed_events.is_empty() {
+        EventType::ALL.to_vec()
     } else {
-        Vec::new() // Empty means subscribe to all events
+        subscribed_events
     };
     
     // Log the connection
     info!("WebSocket connection requested");
     
     // Upgrade the connection
-    ws.on_upgrade(move |socket| handle_socket(socket, ws_manager, subscribed_events, member_id))
+    ws.on_upgrade(move |socket| handle_socket(socket, ws_manager, subscribed_events, member))
 }
 
 /// Handle WebSocket connection
@@ -234,7 +509,7 @@ async fn handle_socket(
     socket: WebSocket,
     ws_manager: Arc<WebSocketManager>,
     subscribed_events: Vec<EventType>,
-    member_id: Option<String>,
+    member: Option<AuthenticatedMember>,
 ) {
     // Split the socket into sender and receiver
     let (mut sender, mut receiver) = socket.split();
@@ -242,8 +517,10 @@ async fn handle_socket(
     // Generate a unique client ID
     let client_id = uuid::Uuid::new_v4().to_string();
     
-    // Register the client
-    ws_manager.register_client(client_id.clone(), subscribed_events, member_id.clone());
+    // Register the client with a channel to its writer task
+    let (control_tx, mut control_rx) = mpsc::unbounded_channel();
+    let role = member.as_ref().map(|member| member.role);
+    ws_manager.register_client(client_id.clone(), subscribed_events, member, control_tx.clone());
     info!("WebSocket client connected: {}", client_id);
     
     // Subscribe to events
@@ -254,36 +531,51 @@ async fn handle_socket(
         "type": "welcome",
         "client_id": client_id,
         "message": "Connected to AtomSi DAO WebSocket",
-        "authenticated": member_id.is_some(),
+        "authenticated": role.is_some(),
+        "role": role,
     });
     
     if let Err(e) = sender.send(Message::Text(welcome_msg.to_string())).await {
         error!("Error sending welcome message: {}", e);
+        ws_manager.remove_client(&client_id);
         return;
     }
     
-    // Spawn a task to forward events to the client
+    // Spawn a task that owns the sink: it forwards events, replies and pings,
+    // and closes the connection when the reaper asks it to
     let client_id_clone = client_id.clone();
     let ws_manager_clone = ws_manager.clone();
     let mut send_task = tokio::spawn(async move {
-        while let Ok(event) = event_rx.recv().await {
-            // Check if client is subscribed to this event
-            if !ws_manager_clone.is_event_subscribed(&client_id_clone, &event.event_type) {
-                continue;
-            }
-            
-            // Serialize the event
-            let event_json = serde_json::to_string(&event).unwrap_or_else(|_| "{}".to_string());
+        loop {
+            let message = tokio::select! {
+                event = event_rx.recv() => {
+                    let Ok(event) = event else { break };
+                    
+                    // Check if client is subscribed to and allowed this event
+                    if !ws_manager_clone.is_event_delivered(&client_id_clone, &event.event_type) {
+                        continue;
+                    }
+                    
+                    // Serialize the event
+                    Message::Text(serde_json::to_string(&event).unwrap_or_else(|_| "{}".to_string()))
+                }
+                command = control_rx.recv() => match command {
+                    Some(ClientCommand::Send(message)) => message,
+                    Some(ClientCommand::Close) | None => break,
+                },
+            };
             
-            // Send the event
-            if let Err(e) = sender.send(Message::Text(event_json)).await {
-                error!("Error sending event to client {}: {}", client_id_clone, e);
+            // Outbound traffic is not activity: a dead client still accepts
+            // writes until the TCP buffers fill up
+            if let Err(e) = sender.send(message).await {
+                error!("Error sending to client {}: {}", client_id_clone, e);
                 break;
             }
-            
-            // Update client activity
-            ws_manager_clone.update_client_activity(&client_id_clone);
         }
+        
+        // Gracefully close the connection
+        let close_timeout = Duration::from_secs(WS_CLOSE_TIMEOUT);
+        let _ = tokio::time::timeout(close_timeout, sender.close()).await;
     });
     
     // Handle messages from the client
@@ -291,33 +583,35 @@ async fn handle_socket(
     let ws_manager_clone = ws_manager.clone();
     let mut recv_task = tokio::spawn(async move {
         while let Some(Ok(msg)) = receiver.next().await {
-            match msg {
+            let reply = match msg {
                 Message::Text(text) => {
                     debug!("Received text message from {}: {}", client_id_clone, text);
-                    // Handle client message if needed
-                    // For now, we'll just echo it back
-                    if let Err(e) = sender.send(Message::Text(format!("Echo: {}", text))).await {
-                        error!("Error echoing message: {}", e);
-                        break;
-                    }
+                    // Subscription changes; errors are replied, not fatal
+                    let reply = ws_manager_clone.handle_client_message(&client_id_clone, &text);
+                    Some(Message::Text(reply.to_string()))
                 },
                 Message::Binary(_) => {
                     // Ignore binary messages
+                    None
                 },
                 Message::Ping(ping) => {
                     // Respond to ping with pong
-                    if let Err(e) = sender.send(Message::Pong(ping)).await {
-                        error!("Error sending pong: {}", e);
-                        break;
-                    }
+                    Some(Message::Pong(ping))
                 },
                 Message::Pong(_) => {
-                    // Ignore pong messages
+                    // Answer to our keepalive ping; recorded as activity below
+                    None
                 },
                 Message::Close(_) => {
                     info!("Client requested close: {}", client_id_clone);
                     break;
                 },
+            };
+            
+            if let Some(reply) = reply {
+                if control_tx.send(ClientCommand::Send(reply)).is_err() {
+                    break;
+                }
             }
             
             // Update client activity
@@ -325,19 +619,18 @@ async fn handle_socket(
         }
     });
     
-    // Wait for either task to complete
+    // Wait for either task to complete; the writer closes the connection
     tokio::select! {
         _ = &mut send_task => {
             recv_task.abort();
         }
         _ = &mut recv_task => {
-            send_task.abort();
+            // Dropping the last control sender stops the writer
+            ws_manager.remove_client(&client_id);
+            let _ = send_task.await;
         }
     }
     
-    // Gracefully close the connection
-    let _ = sender.close().await;
-    
     // Remove the client
     ws_manager.remove_client(&client_id);
     info!("WebSocket client disconnected: {}", client_id);
@@ -349,6 +642,7 @@ struct WebSocketInfoResponse {
     total_connections: usize,
     connections_by_member: HashMap<String, usize>,
     broadcast_channel_capacity: usize,
+    reaped_connections: u64,
 }
 
 /// Get WebSocket connection information
@@ -361,7 +655,221 @@ pub async fn get_ws_info(
         total_connections: total,
         connections_by_member: by_member,
         broadcast_channel_capacity: MAX_BROADCAST_BUFFER,
+        reaped_connections: ws_manager.reaped_connections(),
     };
     
     axum::Json(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn client(id: &str, last_active: Instant) -> (Client, mpsc::UnboundedReceiver<ClientCommand>) {
+        let (control, control_rx) = mpsc::unbounded_channel();
+        let client = Client {
+            id: id.to_string(),
+            connected_at: last_active,
+            last_active,
+            member_id: None,
+            role: None,
+            subscribed_events: EventType::ALL.to_vec(),
+            control,
+        };
+        (client, control_rx)
+    }
+    
+    /// Resolves `member-token` to a Member and `council-token` to a Council member
+    struct StaticTokens;
+    
+    #[async_trait::async_trait]
+    impl RequestAuthenticator for StaticTokens {
+        async fn authenticate(&self, credential: &str) -> crate::core::Result<AuthenticatedMember> {
+            let role = match credential {
+                "member-token" => MemberRole::Member,
+                "council-token" => MemberRole::Council,
+                _ => return Err(crate::core::DaoError::Unauthorized),
+            };
+            
+            Ok(AuthenticatedMember {
+                address: "0x0000000000000000000000000000000000000001".to_string(),
+                role,
+                session_id: None,
+            })
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_invalid_token_rejected() {
+        let authenticator: &dyn RequestAuthenticator = &StaticTokens;
+        
+        assert_eq!(authenticate_client(Some(authenticator), Some("forged-token")).await, Err("Invalid token"));
+        assert!(authenticate_client(None, Some("member-token")).await.is_err());
+        
+        // No token connects anonymously
+        assert_eq!(authenticate_client(Some(authenticator), None).await, Ok(None));
+        
+        let member = authenticate_client(Some(authenticator), Some("member-token")).await.unwrap().unwrap();
+        assert_eq!(member.role, MemberRole::Member);
+    }
+    
+    #[tokio::test]
+    async fn test_member_does_not_receive_treasury_events() {
+        let authenticator: &dyn RequestAuthenticator = &StaticTokens;
+        let connect = |token| async move {
+            let member = authenticate_client(Some(authenticator), token).await.unwrap();
+            let (mut client, _) = client("client", Instant::now());
+            client.role = member.map(|member| member.role);
+            client
+        };
+        
+        let member = connect(Some("member-token")).await;
+        assert!(!member.receives(&EventType::TransactionCreated));
+        assert!(!member.receives(&EventType::OracleUnavailable));
+        assert!(member.receives(&EventType::ProposalVoted));
+        assert!(member.receives(&EventType::MemberUpdated));
+        
+        let council = connect(Some("council-token")).await;
+        assert!(council.receives(&EventType::TransactionCreated));
+        
+        // Anonymous clients only get public events
+        let anonymous = connect(None).await;
+        assert!(anonymous.receives(&EventType::ProposalCreated));
+        assert!(!anonymous.receives(&EventType::MemberRegistered));
+        
+        // Subscriptions narrow what a role allows, never widen it
+        let mut member = member;
+        member.subscribed_events = vec![EventType::TransactionCreated, EventType::ProposalCreated];
+        assert!(!member.receives(&EventType::TransactionCreated));
+        assert!(member.receives(&EventType::ProposalCreated));
+        assert!(!member.receives(&EventType::ProposalVoted));
+    }
+    
+    #[test]
+    fn test_idle_client_reaped_active_client_survives() {
+        let idle_timeout = Duration::from_secs(90);
+        let start = Instant::now();
+        
+        let (idle, mut idle_rx) = client("idle", start);
+        let (active, mut active_rx) = client("active", start + Duration::from_secs(60));
+        let clients = Mutex::new(HashMap::from([
+            ("idle".to_string(), idle),
+            ("active".to_string(), active),
+        ]));
+        
+        // Nobody has been idle for the full timeout yet
+        assert_eq!(reap_idle_clients(&clients, start + idle_timeout, idle_timeout), 0);
+        
+        // The idle client passes the timeout; the active one ponged 60s later
+        let now = start + idle_timeout + Duration::from_secs(1);
+        assert_eq!(reap_idle_clients(&clients, now, idle_timeout), 1);
+        
+        let clients = clients.lock().unwrap();
+        assert!(!clients.contains_key("idle"));
+        assert!(clients.contains_key("active"));
+        assert!(matches!(idle_rx.try_recv(), Ok(ClientCommand::Close)));
+        assert!(active_rx.try_recv().is_err());
+    }
+    
+    /// Answer pending pings with pongs, as a live socket's reader would;
+    /// returns whether the writer was told to close
+    fn answer_pings(
+        clients: &Mutex<HashMap<String, Client>>,
+        client_id: &str,
+        control_rx: &mut mpsc::UnboundedReceiver<ClientCommand>,
+        now: Instant,
+        responsive: bool,
+    ) -> bool {
+        while let Ok(command) = control_rx.try_recv() {
+            match command {
+                ClientCommand::Send(Message::Ping(_)) if responsive => record_activity(clients, client_id, now),
+                ClientCommand::Send(_) => {}
+                ClientCommand::Close => return true,
+            }
+        }
+        false
+    }
+    
+    #[test]
+    fn test_unresponsive_client_cleaned_up() {
+        let keepalive = KeepaliveConfig::default();
+        let start = Instant::now();
+        
+        let (live, mut live_rx) = client("live", start);
+        let (dead, mut dead_rx) = client("dead", start);
+        let clients = Mutex::new(HashMap::from([
+            ("live".to_string(), live),
+            ("dead".to_string(), dead),
+        ]));
+        
+        // Run the keepalive loop: reap, then ping. The dead socket answers
+        // the first ping and then stops responding.
+        let mut dead_closed_at = None;
+        for tick in 1..=6u32 {
+            let now = start + keepalive.ping_interval * tick;
+            reap_idle_clients(&clients, now, keepalive.idle_timeout);
+            ping_clients(&clients);
+            
+            assert!(!answer_pings(&clients, "live", &mut live_rx, now, true));
+            if answer_pings(&clients, "dead", &mut dead_rx, now, tick == 1) && dead_closed_at.is_none() {
+                dead_closed_at = Some(tick);
+            }
+        }
+        
+        // Last pong at 30s, so idle past 90s by the 150s sweep
+        assert_eq!(dead_closed_at, Some(5));
+        let clients = clients.lock().unwrap();
+        assert!(!clients.contains_key("dead"));
+        assert!(clients.contains_key("live"));
+    }
+    
+    #[test]
+    fn test_subscriptions_change_after_connecting() {
+        let (mut connected, _control_rx) = client("client", Instant::now());
+        connected.role = Some(MemberRole::Member);
+        let clients = Mutex::new(HashMap::from([("client".to_string(), connected)]));
+        let receives = |event_type| clients.lock().unwrap()["client"].receives(&event_type);
+        
+        // Narrow everything down to votes
+        let all: Vec<String> = EventType::ALL
+            .iter()
+            .map(|event_type| serde_json::to_value(event_type).unwrap().as_str().unwrap().to_string())
+            .collect();
+        let reply = apply_client_message(
+            &clients,
+            "client",
+            &serde_json::json!({ "action": "unsubscribe", "events": all }).to_string(),
+        );
+        assert_eq!(reply["events"], serde_json::json!([]));
+        let reply = apply_client_message(&clients, "client", r#"{"action":"subscribe","events":["proposal_voted"]}"#);
+        assert_eq!(reply["type"], "subscriptions");
+        assert_eq!(reply["events"], serde_json::json!(["proposal_voted"]));
+        assert!(receives(EventType::ProposalVoted));
+        assert!(!receives(EventType::ProposalCreated));
+        
+        // An unknown name is an error, and changes nothing
+        let reply = apply_client_message(
+            &clients,
+            "client",
+            r#"{"action":"subscribe","events":["proposal_created","proposal_deleted"]}"#,
+        );
+        assert_eq!(reply["type"], "error");
+        assert_eq!(reply["message"], "Unknown event: proposal_deleted");
+        assert!(!receives(EventType::ProposalCreated));
+        assert_eq!(apply_client_message(&clients, "client", "Echo?")["type"], "error");
+        
+        let reply = apply_client_message(&clients, "client", r#"{"action":"unsubscribe","events":["proposal_voted"]}"#);
+        assert_eq!(reply["events"], serde_json::json!([]));
+        assert!(!receives(EventType::ProposalVoted));
+    }
+    
+    #[test]
+    fn test_zero_timeout_disables_keepalive() {
+        assert!(KeepaliveConfig::default().is_enabled());
+        assert!(!KeepaliveConfig {
+            idle_timeout: Duration::ZERO,
+            ..Default::default()
+        }
+        .is_enabled());
+    }
 } 
\ No newline at end of file
@@ -2,9 +2,13 @@
 //!
 //! This binary provides a command-line interface for interacting with an AtomSi DAO.
 
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
+use serde_json::{json, Value};
 use std::path::PathBuf;
-use atomsi_dao::{self, DAOContext, Result};
+use std::process;
+use tracing::info;
+use atomsi_dao::{self, DAOContext, Error, Result};
+use atomsi_dao::core::{Dao, DaoError, Database};
 
 #[derive(Debug, Parser)]
 #[clap(name = "atomsi", version = atomsi_dao::VERSION, author = atomsi_dao::AUTHORS)]
@@ -14,11 +18,26 @@ struct Cli {
     #[clap(short, long, default_value = "config.json")]
     config: PathBuf,
 
+    /// Output format for results and errors
+    #[clap(long, global = true, value_enum, default_value_t = OutputFormat::Table)]
+    output: OutputFormat,
+
     /// Command to execute
     #[clap(subcommand)]
     command: Command,
 }
 
+/// Output format for command results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    /// Human-readable text
+    Table,
+    /// Pretty-printed JSON
+    Json,
+    /// YAML
+    Yaml,
+}
+
 #[derive(Debug, Subcommand)]
 enum Command {
     /// Initialize a new DAO
@@ -56,6 +75,22 @@ enum Command {
     /// Token management commands
     #[clap(subcommand)]
     Token(TokenCommand),
+
+    /// Check the configuration, database, and RPC endpoints without changing anything
+    #[clap(name = "doctor")]
+    Doctor,
+
+    /// Populate the database with sample data for development
+    #[clap(name = "seed")]
+    Seed {
+        /// Confirm that sample data should be inserted
+        #[clap(long)]
+        confirm: bool,
+
+        /// Seed even if the database is non-empty or marked as production
+        #[clap(long)]
+        force: bool,
+    },
 }
 
 #[derive(Debug, Subcommand)]
@@ -238,199 +273,425 @@ enum TokenCommand {
     },
 }
 
+/// Exit code for errors caused by the input: bad arguments, missing records,
+/// invalid configuration, unsupported operations
+const EXIT_USER_ERROR: i32 = 2;
+
+/// Exit code for errors in the environment: database, RPC, I/O
+const EXIT_SYSTEM_ERROR: i32 = 1;
+
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() {
     // Initialize logging
-    atomsi_dao::init_logging();
+    atomsi_dao::init_logging(&atomsi_dao::config::LoggingConfig::default());
 
     // Parse command-line arguments
     let cli = Cli::parse();
 
-    // Handle based on command
-    match &cli.command {
-        Command::Init { name, description, token } => {
-            println!("Initializing DAO: {}", name);
-            println!("Description: {}", description);
-            println!("Token symbol: {}", token);
+    match run(&cli).await {
+        Ok(CommandOutput { payload, success }) => {
+            println!("{}", render(cli.output, &payload));
 
-            // TODO: Implement DAO initialization
-            println!("DAO initialization is not yet implemented");
+            // Commands can complete and still report a failure (e.g. doctor)
+            if !success {
+                process::exit(EXIT_SYSTEM_ERROR);
+            }
         }
-        Command::Info => {
-            println!("Getting DAO information");
+        Err(e) => {
+            match cli.output {
+                OutputFormat::Table => eprintln!("Error: {}", e),
+                format => eprintln!("{}", render(format, &error_payload(&e))),
+            }
+            process::exit(exit_code(&e));
+        }
+    }
+}
 
+/// Result of a command, rendered in the requested output format
+struct CommandOutput {
+    /// Serializable result
+    payload: Value,
+    /// Whether the command succeeded; `false` exits non-zero after printing
+    success: bool,
+}
+
+impl From<Value> for CommandOutput {
+    fn from(payload: Value) -> Self {
+        Self { payload, success: true }
+    }
+}
+
+/// Run the parsed command
+async fn run(cli: &Cli) -> Result<CommandOutput> {
+    match &cli.command {
+        Command::Init { name, .. } => Err(not_implemented(&format!("Initializing DAO '{}'", name))),
+        Command::Info => {
             // Initialize the DAO context
-            let context = init_dao_context(&cli.config).await?;
+            let _context = init_dao_context(&cli.config).await?;
 
-            // TODO: Implement DAO info retrieval
-            println!("DAO info retrieval is not yet implemented");
+            Err(not_implemented("DAO info retrieval"))
         }
-        Command::Member(cmd) => handle_member_command(cmd, &cli.config).await?,
-        Command::Proposal(cmd) => handle_proposal_command(cmd, &cli.config).await?,
-        Command::Treasury(cmd) => handle_treasury_command(cmd, &cli.config).await?,
-        Command::Token(cmd) => handle_token_command(cmd, &cli.config).await?,
+        Command::Member(cmd) => handle_member_command(cmd, &cli.config).await.map(Into::into),
+        Command::Proposal(cmd) => handle_proposal_command(cmd, &cli.config).await.map(Into::into),
+        Command::Treasury(cmd) => handle_treasury_command(cmd, &cli.config).await.map(Into::into),
+        Command::Token(cmd) => handle_token_command(cmd, &cli.config).await.map(Into::into),
+        Command::Seed { confirm, force } => handle_seed_command(*confirm, *force, &cli.config).await.map(Into::into),
+        Command::Doctor => handle_doctor_command(&cli.config).await,
     }
+}
 
-    Ok(())
+/// Error for a command that has no implementation yet
+fn not_implemented(what: &str) -> Error {
+    Error::NotSupported(format!("{} is not yet implemented", what))
+}
+
+/// Map a seeding failure to a CLI error of the same kind
+///
+/// Only the guard against seeding a production or non-empty database is a
+/// validation error; failures while writing the data stay system errors.
+fn seed_error(error: DaoError) -> Error {
+    match error {
+        DaoError::NotSupported(message) => Error::ValidationError(message),
+        DaoError::DatabaseError(message) => Error::DatabaseError(message),
+        DaoError::BlockchainError(message) => Error::BlockchainError(message),
+        DaoError::ExternalServiceError(message) => Error::ExternalServiceError(message),
+        other => Error::InternalError(other.to_string()),
+    }
 }
 
 /// Initialize the DAO context
 async fn init_dao_context(config_path: &PathBuf) -> Result<DAOContext> {
     let config_path_str = config_path.to_string_lossy();
-    println!("Using config file: {}", config_path_str);
+    info!("Using config file: {}", config_path_str);
 
     atomsi_dao::init(&config_path_str).await
 }
 
-/// Handle member commands
-async fn handle_member_command(cmd: &MemberCommand, config_path: &PathBuf) -> Result<()> {
-    // Initialize the DAO context
-    let context = init_dao_context(config_path).await?;
-    let identity_manager = context.identity_manager();
-
-    match cmd {
-        MemberCommand::Register { address, name, role } => {
-            println!("Registering member: {}", name);
-            println!("Address: {}", address);
-            println!("Role: {}", role);
-
-            // TODO: Implement member registration
-            println!("Member registration is not yet implemented");
+/// Handle the doctor command
+///
+/// Completes unsuccessfully if any critical check fails.
+async fn handle_doctor_command(config_path: &PathBuf) -> Result<CommandOutput> {
+    let config_path_str = config_path.to_string_lossy();
+    info!("Using config file: {}", config_path_str);
+
+    // Without a readable config there is nothing else to check
+    let config = atomsi_dao::config::ConfigManager::new(&config_path_str)?.get_config();
+
+    let database = atomsi_dao::database::DatabaseManager::new(&config.database).await;
+    let chains = atomsi_dao::blockchain::BlockchainAdapter::new(&config.blockchain).and_then(|adapter| {
+        let mut chain_ids = vec![config.blockchain.chain_id];
+        for chain in config.blockchain.supported_chains.values() {
+            if !chain_ids.contains(&chain.chain_id) {
+                chain_ids.push(chain.chain_id);
+            }
         }
-        MemberCommand::List => {
-            println!("Listing all members");
 
-            // TODO: Implement member listing
-            println!("Member listing is not yet implemented");
-        }
-        MemberCommand::Get { id } => {
-            println!("Getting member details for: {}", id);
+        chain_ids
+            .into_iter()
+            .map(|chain_id| Ok((chain_id, adapter.get_adapter(chain_id)?)))
+            .collect()
+    });
+
+    let report = atomsi_dao::doctor::diagnose(&config, &database, &chains).await;
+    let checks: Vec<Value> = report
+        .checks()
+        .iter()
+        .map(|check| {
+            json!({
+                "name": check.name,
+                "status": format!("{:?}", check.status).to_lowercase(),
+                "detail": check.detail,
+            })
+        })
+        .collect();
+
+    Ok(CommandOutput {
+        payload: json!({ "healthy": report.is_healthy(), "checks": checks }),
+        success: report.is_healthy(),
+    })
+}
 
-            // TODO: Implement member retrieval
-            println!("Member retrieval is not yet implemented");
-        }
+/// Handle the seed command
+async fn handle_seed_command(confirm: bool, force: bool, config_path: &PathBuf) -> Result<Value> {
+    if !confirm {
+        return Ok(json!({
+            "seeded": false,
+            "message": "Seeding inserts sample members, proposals, and transactions into the configured database. Re-run with --confirm to continue",
+        }));
     }
 
-    Ok(())
+    // Initialize the DAO context
+    let context = init_dao_context(config_path).await?;
+    let config = context.config_manager.get_config();
+
+    let database = Database::connect(&config.database)
+        .await
+        .map_err(|e| Error::DatabaseError(e.to_string()))?;
+    let dao = Dao::new(config, context.blockchain, database)
+        .map_err(|e| Error::InternalError(e.to_string()))?;
+
+    let summary = dao
+        .seed_sample_data(force)
+        .await
+        .map_err(seed_error)?;
+
+    Ok(json!({
+        "seeded": true,
+        "members": summary.members,
+        "tokens": summary.tokens,
+        "balances": summary.balances,
+        "proposals": summary.proposals,
+        "transactions": summary.transactions,
+    }))
 }
 
-/// Handle proposal commands
-async fn handle_proposal_command(cmd: &ProposalCommand, config_path: &PathBuf) -> Result<()> {
+/// Handle member commands
+async fn handle_member_command(cmd: &MemberCommand, config_path: &PathBuf) -> Result<Value> {
     // Initialize the DAO context
     let context = init_dao_context(config_path).await?;
-    let proposal_manager = context.proposal_manager();
-
-    match cmd {
-        ProposalCommand::Create { title, description, proposal_type, proposer } => {
-            println!("Creating proposal: {}", title);
-            println!("Description: {}", description);
-            println!("Type: {}", proposal_type);
-            println!("Proposer: {}", proposer);
-
-            // TODO: Implement proposal creation
-            println!("Proposal creation is not yet implemented");
-        }
-        ProposalCommand::List => {
-            println!("Listing all proposals");
-
-            // TODO: Implement proposal listing
-            println!("Proposal listing is not yet implemented");
-        }
-        ProposalCommand::Get { id } => {
-            println!("Getting proposal details for: {}", id);
-
-            // TODO: Implement proposal retrieval
-            println!("Proposal retrieval is not yet implemented");
-        }
-        ProposalCommand::Vote { id, voter, vote } => {
-            println!("Voting on proposal: {}", id);
-            println!("Voter: {}", voter);
-            println!("Vote: {}", vote);
-
-            // TODO: Implement voting
-            println!("Voting is not yet implemented");
-        }
-    }
+    let _identity_manager = context.identity_manager();
+
+    // TODO: Implement member registration, listing and retrieval
+    Err(match cmd {
+        MemberCommand::Register { .. } => not_implemented("Member registration"),
+        MemberCommand::List => not_implemented("Member listing"),
+        MemberCommand::Get { .. } => not_implemented("Member retrieval"),
+    })
+}
 
-    Ok(())
+/// Handle proposal commands
+async fn handle_proposal_command(cmd: &ProposalCommand, config_path: &PathBuf) -> Result<Value> {
+    // Initialize the DAO context
+    let context = init_dao_context(config_path).await?;
+    let _proposal_manager = context.proposal_manager();
+
+    // TODO: Implement proposal creation, listing, retrieval and voting
+    Err(match cmd {
+        ProposalCommand::Create { .. } => not_implemented("Proposal creation"),
+        ProposalCommand::List => not_implemented("Proposal listing"),
+        ProposalCommand::Get { .. } => not_implemented("Proposal retrieval"),
+        ProposalCommand::Vote { .. } => not_implemented("Voting"),
+    })
 }
 
 /// Handle treasury commands
-async fn handle_treasury_command(cmd: &TreasuryCommand, config_path: &PathBuf) -> Result<()> {
+async fn handle_treasury_command(cmd: &TreasuryCommand, config_path: &PathBuf) -> Result<Value> {
     // Initialize the DAO context
     let context = init_dao_context(config_path).await?;
-    let treasury_manager = context.treasury_manager();
+    let _treasury_manager = context.treasury_manager();
+
+    // TODO: Implement the treasury transaction commands
+    Err(match cmd {
+        TreasuryCommand::CreateTransaction { .. } => not_implemented("Transaction creation"),
+        TreasuryCommand::ListTransactions => not_implemented("Transaction listing"),
+        TreasuryCommand::GetTransaction { .. } => not_implemented("Transaction retrieval"),
+        TreasuryCommand::ApproveTransaction { .. } => not_implemented("Transaction approval"),
+        TreasuryCommand::ExecuteTransaction { .. } => not_implemented("Transaction execution"),
+    })
+}
 
-    match cmd {
-        TreasuryCommand::CreateTransaction { description, recipient, token, amount } => {
-            println!("Creating transaction: {}", description);
-            println!("Recipient: {}", recipient);
-            println!("Token: {}", token);
-            println!("Amount: {}", amount);
+/// Handle token commands
+async fn handle_token_command(cmd: &TokenCommand, config_path: &PathBuf) -> Result<Value> {
+    // Initialize the DAO context
+    let context = init_dao_context(config_path).await?;
+    let _token_manager = context.token_manager();
+
+    // TODO: Implement token info, balance and transfer
+    Err(match cmd {
+        TokenCommand::Info { .. } => not_implemented("Token info retrieval"),
+        TokenCommand::Balance { .. } => not_implemented("Balance retrieval"),
+        TokenCommand::Transfer { .. } => not_implemented("Token transfer"),
+    })
+}
 
-            // TODO: Implement transaction creation
-            println!("Transaction creation is not yet implemented");
-        }
-        TreasuryCommand::ListTransactions => {
-            println!("Listing all transactions");
+/// Exit code for an error
+fn exit_code(error: &Error) -> i32 {
+    match error {
+        Error::ConfigError(_)
+        | Error::ValidationError(_)
+        | Error::NotFoundError(_)
+        | Error::ResourceNotFoundError(_)
+        | Error::AlreadyExistsError(_)
+        | Error::AuthError(_)
+        | Error::AuthorizationError(_)
+        | Error::NotSupported(_) => EXIT_USER_ERROR,
+        _ => EXIT_SYSTEM_ERROR,
+    }
+}
 
-            // TODO: Implement transaction listing
-            println!("Transaction listing is not yet implemented");
-        }
-        TreasuryCommand::GetTransaction { id } => {
-            println!("Getting transaction details for: {}", id);
+/// Short machine-readable name for an error
+fn error_kind(error: &Error) -> &'static str {
+    match error {
+        Error::ConfigError(_) => "config",
+        Error::ValidationError(_) => "validation",
+        Error::NotFoundError(_) | Error::ResourceNotFoundError(_) => "not_found",
+        Error::AlreadyExistsError(_) => "already_exists",
+        Error::AuthError(_) | Error::AuthorizationError(_) => "unauthorized",
+        Error::NotSupported(_) => "not_supported",
+        Error::DatabaseError(_) => "database",
+        Error::BlockchainError(_) | Error::ExecutionError(_) => "blockchain",
+        Error::NetworkError(_) | Error::ExternalServiceError(_) | Error::RateLimitError(_) => "network",
+        Error::IoError(_) => "io",
+        _ => "internal",
+    }
+}
 
-            // TODO: Implement transaction retrieval
-            println!("Transaction retrieval is not yet implemented");
+/// Structured error emitted on stderr in JSON and YAML modes
+fn error_payload(error: &Error) -> Value {
+    json!({
+        "error": {
+            "kind": error_kind(error),
+            "message": error.to_string(),
+            "exit_code": exit_code(error),
         }
-        TreasuryCommand::ApproveTransaction { id, approver } => {
-            println!("Approving transaction: {}", id);
-            println!("Approver: {}", approver);
+    })
+}
 
-            // TODO: Implement transaction approval
-            println!("Transaction approval is not yet implemented");
-        }
-        TreasuryCommand::ExecuteTransaction { id } => {
-            println!("Executing transaction: {}", id);
+/// Render a command result in the requested format
+fn render(format: OutputFormat, value: &Value) -> String {
+    match format {
+        OutputFormat::Json => serde_json::to_string_pretty(value).unwrap_or_default(),
+        OutputFormat::Yaml => serde_yaml::to_string(value).unwrap_or_default(),
+        OutputFormat::Table => render_table(value),
+    }
+}
 
-            // TODO: Implement transaction execution
-            println!("Transaction execution is not yet implemented");
+/// Render a value for humans
+///
+/// Arrays of objects become aligned columns; objects become `key: value`
+/// lines with nested arrays of objects rendered as tables below them.
+fn render_table(value: &Value) -> String {
+    match value {
+        Value::Array(rows) if rows.iter().all(Value::is_object) && !rows.is_empty() => {
+            let columns: Vec<&String> = rows[0].as_object().map(|row| row.keys().collect()).unwrap_or_default();
+            let cells: Vec<Vec<String>> = rows
+                .iter()
+                .map(|row| columns.iter().map(|column| cell(&row[column.as_str()])).collect())
+                .collect();
+
+            let widths: Vec<usize> = columns
+                .iter()
+                .enumerate()
+                .map(|(i, column)| cells.iter().map(|row| row[i].len()).chain([column.len()]).max().unwrap_or(0))
+                .collect();
+
+            let line = |values: Vec<String>| {
+                values
+                    .iter()
+                    .zip(&widths)
+                    .map(|(value, width)| format!("{:<width$}", value, width = width))
+                    .collect::<Vec<_>>()
+                    .join("  ")
+                    .trim_end()
+                    .to_string()
+            };
+
+            let mut lines = vec![line(columns.iter().map(|column| column.to_uppercase()).collect())];
+            lines.extend(cells.into_iter().map(line));
+            lines.join("\n")
         }
+        Value::Object(fields) => {
+            let mut lines = Vec::new();
+            let mut tables = Vec::new();
+
+            for (key, value) in fields {
+                match value {
+                    Value::Array(rows) if rows.iter().any(Value::is_object) => {
+                        tables.push(format!("{}:\n{}", key, render_table(value)))
+                    }
+                    _ => lines.push(format!("{}: {}", key, cell(value))),
+                }
+            }
+
+            lines.extend(tables);
+            lines.join("\n")
+        }
+        _ => cell(value),
     }
+}
 
-    Ok(())
+/// Render a single value without JSON quoting for strings
+fn cell(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
 }
 
-/// Handle token commands
-async fn handle_token_command(cmd: &TokenCommand, config_path: &PathBuf) -> Result<()> {
-    // Initialize the DAO context
-    let context = init_dao_context(config_path).await?;
-    let token_manager = context.token_manager();
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    match cmd {
-        TokenCommand::Info { symbol } => {
-            println!("Getting token info for: {}", symbol);
+    #[tokio::test]
+    async fn test_json_payload_shape() {
+        let cli = Cli::try_parse_from(["atomsi", "--output", "json", "seed"]).unwrap();
+        assert_eq!(cli.output, OutputFormat::Json);
 
-            // TODO: Implement token info retrieval
-            println!("Token info retrieval is not yet implemented");
-        }
-        TokenCommand::Balance { symbol, holder } => {
-            println!("Getting token balance for: {}", holder);
-            println!("Token: {}", symbol);
+        let output = run(&cli).await.unwrap();
+        assert!(output.success);
 
-            // TODO: Implement balance retrieval
-            println!("Balance retrieval is not yet implemented");
-        }
-        TokenCommand::Transfer { symbol, from, to, amount } => {
-            println!("Transferring tokens: {}", symbol);
-            println!("From: {}", from);
-            println!("To: {}", to);
-            println!("Amount: {}", amount);
-
-            // TODO: Implement token transfer
-            println!("Token transfer is not yet implemented");
-        }
+        let rendered: Value = serde_json::from_str(&render(cli.output, &output.payload)).unwrap();
+        assert_eq!(rendered["seeded"], json!(false));
+        assert!(rendered["message"].as_str().unwrap().contains("--confirm"));
+    }
+
+    #[test]
+    fn test_seed_guard_is_user_error_and_write_failures_are_system_errors() {
+        let refused = seed_error(DaoError::NotSupported("Refusing to seed a non-empty database".to_string()));
+        assert!(matches!(refused, Error::ValidationError(_)));
+        assert_eq!(exit_code(&refused), EXIT_USER_ERROR);
+
+        let failed = seed_error(DaoError::DatabaseError("connection reset".to_string()));
+        assert!(matches!(failed, Error::DatabaseError(_)));
+        assert_eq!(exit_code(&failed), EXIT_SYSTEM_ERROR);
+        assert_eq!(exit_code(&seed_error(DaoError::InvalidParameter("bad symbol".to_string()))), EXIT_SYSTEM_ERROR);
+    }
+
+    #[tokio::test]
+    async fn test_missing_config_is_user_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("missing.json");
+        let cli = Cli::try_parse_from([
+            "atomsi",
+            "--config",
+            missing.to_str().unwrap(),
+            "doctor",
+            "--output",
+            "json",
+        ])
+        .unwrap();
+
+        let error = run(&cli).await.err().unwrap();
+        assert_eq!(exit_code(&error), EXIT_USER_ERROR);
+
+        let payload: Value = serde_json::from_str(&render(cli.output, &error_payload(&error))).unwrap();
+        assert_eq!(payload["error"]["kind"], json!("config"));
+        assert_eq!(payload["error"]["exit_code"], json!(EXIT_USER_ERROR));
+        assert!(payload["error"]["message"].as_str().unwrap().contains("not found"));
+    }
+
+    #[test]
+    fn test_system_errors_have_distinct_exit_code() {
+        let error = Error::DatabaseError("connection refused".to_string());
+        assert_eq!(exit_code(&error), EXIT_SYSTEM_ERROR);
+        assert_ne!(EXIT_SYSTEM_ERROR, EXIT_USER_ERROR);
+        assert_eq!(error_payload(&error)["error"]["kind"], json!("database"));
     }
 
-    Ok(())
-} 
\ No newline at end of file
+    #[test]
+    fn test_table_rendering() {
+        let value = json!({
+            "healthy": true,
+            "checks": [
+                { "name": "config", "status": "pass" },
+                { "name": "database", "status": "fail" },
+            ],
+        });
+
+        let table = render_table(&value);
+        assert!(table.starts_with("healthy: true"));
+        assert!(table.contains("NAME      STATUS"));
+        assert!(table.contains("database  fail"));
+    }
+}
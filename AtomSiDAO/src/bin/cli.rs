@@ -9,7 +9,7 @@ use std::process;
 #[tokio::main]
 async fn main() {
     // Initialize logging
-    env_logger::init();
+    atomsi_dao::init_logging(&atomsi_dao::config::LoggingConfig::default());
     
     // Parse command line arguments
     let args: Vec<String> = env::args().collect();
@@ -0,0 +1,120 @@
+//! Chain capabilities
+//!
+//! The higher layers were written against EVM semantics: hex addresses,
+//! ABI-encoded contract calls, EIP-1271 contract signatures. Adapters report
+//! what their chain actually supports so callers can fail with a clear
+//! "not supported" error instead of sending malformed calls.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+/// Address format used by a chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AddressKind {
+    /// `0x`-prefixed 20-byte hex (Ethereum and other EVM chains)
+    Evm,
+    /// Base58-encoded 32-byte public key (Solana)
+    Base58,
+    /// SS58-encoded account ID (Polkadot and Substrate chains)
+    Ss58,
+}
+
+impl AddressKind {
+    /// Check that an address has this format
+    ///
+    /// Only the shape is checked; checksums are left to the adapter.
+    pub fn is_valid(&self, address: &str) -> bool {
+        let is_base58 = |s: &str| {
+            s.chars()
+                .all(|c| c.is_ascii_alphanumeric() && !matches!(c, '0' | 'O' | 'I' | 'l'))
+        };
+
+        match self {
+            AddressKind::Evm => address
+                .strip_prefix("0x")
+                .map_or(false, |hex| hex.len() == 40 && hex.chars().all(|c| c.is_ascii_hexdigit())),
+            AddressKind::Base58 => (32..=44).contains(&address.len()) && is_base58(address),
+            AddressKind::Ss58 => (46..=48).contains(&address.len()) && is_base58(address),
+        }
+    }
+}
+
+/// What an adapter's chain supports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainCapabilities {
+    /// ABI-encoded contract calls and code hashes
+    pub supports_contract_calls: bool,
+    /// EIP-1271 signature validation for contract wallets
+    pub supports_eip1271: bool,
+    /// Decimals of the chain's native currency
+    pub native_decimals: u8,
+    /// Address format
+    pub address_kind: AddressKind,
+}
+
+impl ChainCapabilities {
+    /// Capabilities of an EVM chain
+    pub const EVM: Self = Self {
+        supports_contract_calls: true,
+        supports_eip1271: true,
+        native_decimals: 18,
+        address_kind: AddressKind::Evm,
+    };
+
+    /// Fail with `NotSupported` unless the chain supports contract calls
+    pub fn require_contract_calls(&self, chain_id: u64, operation: &str) -> Result<()> {
+        if self.supports_contract_calls {
+            return Ok(());
+        }
+
+        Err(Error::NotSupported(format!(
+            "{} requires EVM contract calls, which chain {} ({:?} addresses) does not support",
+            operation, chain_id, self.address_kind
+        )))
+    }
+
+    /// Fail with `NotSupported` unless the chain validates contract signatures
+    pub fn require_eip1271(&self, chain_id: u64) -> Result<()> {
+        if self.supports_eip1271 {
+            return Ok(());
+        }
+
+        Err(Error::NotSupported(format!(
+            "Contract wallet signatures (EIP-1271) are not supported on chain {}",
+            chain_id
+        )))
+    }
+
+    /// Fail with `ValidationError` unless the address has the chain's format
+    pub fn require_address(&self, chain_id: u64, address: &str) -> Result<()> {
+        if self.address_kind.is_valid(address) {
+            return Ok(());
+        }
+
+        Err(Error::ValidationError(format!(
+            "'{}' is not a valid {:?} address for chain {}",
+            address, self.address_kind, chain_id
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_address_kinds() {
+        assert!(AddressKind::Evm.is_valid("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"));
+        assert!(!AddressKind::Evm.is_valid("5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"));
+        assert!(!AddressKind::Evm.is_valid("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeA"));
+
+        let solana = "4Nd1mBQtrMJVYVfKf2PJy9NZUZdTAsp7D4xWLs4gDB4T";
+        assert!(AddressKind::Base58.is_valid(solana));
+        assert!(!AddressKind::Evm.is_valid(solana));
+        assert!(!AddressKind::Base58.is_valid("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"));
+
+        assert!(AddressKind::Ss58.is_valid("15oF4uVJwmo4TdGW7VfQxNLavjCXviqxT9S1MgbjMNHr6Sp5"));
+    }
+}
@@ -4,29 +4,135 @@
 
 use async_trait::async_trait;
 use ethers::prelude::{
-    Address, abi::parse_abi, ContractCall, Http, LocalWallet, Middleware, Provider, 
-    SignerMiddleware, TransactionRequest, H160, H256, U256, Wallet, abigen
+    Address, abi::parse_abi, BlockNumber, ContractCall, Eip1559TransactionRequest, Filter, Http, LocalWallet,
+    Middleware, Provider, SignerMiddleware, TransactionRequest, H160, H256, U256, Wallet, abigen
 };
+use ethers::abi::{ParamType, Token};
+use ethers::providers::MiddlewareError;
+use ethers::types::transaction::eip2718::TypedTransaction;
 use ethers::utils::hex;
 use ethers::signers::{Signer, Signature, LocalAccount};
 use std::str::FromStr;
 use std::sync::Arc;
 
+use crate::config::{BlockchainConfig, RpcRetryConfig};
 use crate::error::{Error, Result};
-use crate::blockchain::{BlockchainInterface, RawTransaction, TransactionData};
+use crate::blockchain::{BlockchainInterface, Log, LogFilter, RawTransaction, TransactionData};
+use crate::blockchain::failover::{EndpointPool, EndpointStatus};
+
+/// How transaction fees are priced when the caller doesn't set them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GasStrategy {
+    /// Fixed price in wei: the legacy gas price, or the EIP-1559 fee cap
+    Static(U256),
+    /// The node's fee estimate
+    Standard,
+    /// The node's fee estimate with a doubled priority fee, for faster inclusion
+    Fast,
+}
+
+impl Default for GasStrategy {
+    fn default() -> Self {
+        GasStrategy::Standard
+    }
+}
+
+impl GasStrategy {
+    /// Read the strategy from `gas_price_strategy` and `static_gas_price_gwei`
+    pub fn from_config(config: &BlockchainConfig) -> Result<Self> {
+        match config.gas_price_strategy.as_str() {
+            "static" => config
+                .static_gas_price_gwei
+                .map(|gwei| GasStrategy::Static(U256::from(gwei) * U256::exp10(9)))
+                .ok_or_else(|| {
+                    Error::ConfigError("The static gas price strategy requires static_gas_price_gwei".to_string())
+                }),
+            "standard" => Ok(GasStrategy::Standard),
+            "fast" => Ok(GasStrategy::Fast),
+            other => Err(Error::ConfigError(format!("Unknown gas price strategy: {}", other))),
+        }
+    }
+    
+    /// Price a transaction from the node's estimates
+    ///
+    /// `eip1559` holds the estimated max fee and priority fee, and is `None`
+    /// on chains without a base fee, which are priced with `legacy_gas_price`.
+    fn price(self, eip1559: Option<(U256, U256)>, legacy_gas_price: U256) -> GasFees {
+        match (self, eip1559) {
+            (GasStrategy::Static(price), Some((_, priority))) => GasFees::Eip1559 {
+                max_fee_per_gas: price,
+                max_priority_fee_per_gas: priority.min(price),
+            },
+            (GasStrategy::Static(price), None) => GasFees::Legacy { gas_price: price },
+            (GasStrategy::Standard, Some((max_fee, priority))) => GasFees::Eip1559 {
+                max_fee_per_gas: max_fee,
+                max_priority_fee_per_gas: priority,
+            },
+            (GasStrategy::Standard, None) => GasFees::Legacy { gas_price: legacy_gas_price },
+            (GasStrategy::Fast, Some((max_fee, priority))) => GasFees::Eip1559 {
+                max_fee_per_gas: max_fee.saturating_add(priority),
+                max_priority_fee_per_gas: priority.saturating_mul(U256::from(2)),
+            },
+            (GasStrategy::Fast, None) => GasFees::Legacy {
+                gas_price: legacy_gas_price.saturating_mul(U256::from(5)) / 4,
+            },
+        }
+    }
+}
+
+/// Fees of a transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GasFees {
+    /// EIP-1559 fee cap and tip, in wei
+    Eip1559 {
+        max_fee_per_gas: U256,
+        max_priority_fee_per_gas: U256,
+    },
+    /// Legacy gas price, in wei
+    Legacy { gas_price: U256 },
+}
 
 /// Ethereum blockchain adapter for interacting with Ethereum and EVM-compatible chains
 pub struct EthereumAdapter {
-    provider: Provider<Http>,
+    providers: EndpointPool<Provider<Http>>,
+    gas_strategy: GasStrategy,
 }
 
 impl EthereumAdapter {
     /// Create a new Ethereum adapter
     pub fn new(rpc_url: &str) -> Result<Self> {
-        let provider = Provider::<Http>::try_from(rpc_url)
-            .map_err(|e| Error::BlockchainError(format!("Failed to connect to Ethereum node: {}", e)))?;
+        Self::with_urls(&[rpc_url.to_string()], &RpcRetryConfig::default())
+    }
+    
+    /// Create a new Ethereum adapter with fallback endpoints
+    ///
+    /// The first URL is the primary. Reads fail over to the others on error;
+    /// sends stay on the first healthy endpoint. Reads that fail everywhere
+    /// are retried according to `retry`.
+    pub fn with_urls(rpc_urls: &[String], retry: &RpcRetryConfig) -> Result<Self> {
+        let mut endpoints = Vec::new();
+        
+        for url in rpc_urls {
+            let provider = Provider::<Http>::try_from(url.as_str())
+                .map_err(|e| Error::BlockchainError(format!("Failed to connect to Ethereum node: {}", e)))?;
+            endpoints.push((url.clone(), provider));
+        }
         
-        Ok(Self { provider })
+        Ok(Self {
+            providers: EndpointPool::new(endpoints)?.with_retry(retry.clone()),
+            gas_strategy: GasStrategy::default(),
+        })
+    }
+    
+    /// Price transactions with the given strategy
+    pub fn with_gas_strategy(mut self, gas_strategy: GasStrategy) -> Self {
+        self.gas_strategy = gas_strategy;
+        self
+    }
+    
+    /// Get the health of each configured RPC endpoint
+    pub fn endpoint_status(&self) -> Vec<EndpointStatus> {
+        self.providers.status()
     }
     
     /// Create a provider with signer
@@ -34,15 +140,29 @@ impl EthereumAdapter {
         let wallet = LocalWallet::from_str(private_key)
             .map_err(|e| Error::BlockchainError(format!("Invalid private key: {}", e)))?;
         
-        let chain_id = self
-            .provider
+        // Sends stick to one endpoint to keep nonces consistent
+        let provider = self.providers.send_client();
+        
+        let chain_id = provider
             .get_chainid()
             .blocking_send()
             .map_err(|e| Error::BlockchainError(format!("Failed to get chain ID: {}", e)))?;
         
         let signer = wallet.with_chain_id(chain_id.as_u64());
         
-        Ok(SignerMiddleware::new(self.provider.clone(), signer))
+        Ok(SignerMiddleware::new(provider, signer))
+    }
+    
+    /// Map an RPC failure to an error
+    ///
+    /// Reverts are reported by the node as JSON-RPC error responses and fail
+    /// the same way on every endpoint, so they become `ExecutionError`
+    /// rather than `BlockchainError`.
+    fn rpc_error<E: MiddlewareError>(context: &str, error: E) -> Error {
+        match error.as_error_response() {
+            Some(response) if response.is_revert() => Error::ExecutionError(format!("{}: {}", context, response)),
+            _ => Error::BlockchainError(format!("{}: {}", context, error)),
+        }
     }
     
     /// Parse an Ethereum address
@@ -61,6 +181,187 @@ impl EthereumAdapter {
     fn format_u256(value: U256) -> String {
         value.to_string()
     }
+    
+    /// Parse a decimal wei amount
+    fn parse_wei(value: &str, field: &str) -> Result<U256> {
+        U256::from_dec_str(value).map_err(|e| Error::BlockchainError(format!("Invalid {}: {}", field, e)))
+    }
+    
+    /// Estimate fees with the configured strategy
+    ///
+    /// EIP-1559 fees are used when the latest block has a base fee; other
+    /// chains fall back to a legacy gas price.
+    pub async fn estimate_fees(&self) -> Result<GasFees> {
+        let provider = self.providers.send_client();
+        
+        let latest = provider
+            .get_block(BlockNumber::Latest)
+            .await
+            .map_err(|e| Error::BlockchainError(format!("Failed to get latest block: {}", e)))?
+            .ok_or_else(|| Error::BlockchainError("Latest block not found".to_string()))?;
+        
+        if latest.base_fee_per_gas.is_some() {
+            let estimate = provider
+                .estimate_eip1559_fees(None)
+                .await
+                .map_err(|e| Error::BlockchainError(format!("Failed to estimate EIP-1559 fees: {}", e)))?;
+            return Ok(self.gas_strategy.price(Some(estimate), U256::zero()));
+        }
+        
+        let gas_price = match self.gas_strategy {
+            GasStrategy::Static(_) => U256::zero(),
+            _ => provider
+                .get_gas_price()
+                .await
+                .map_err(|e| Error::BlockchainError(format!("Failed to get gas price: {}", e)))?,
+        };
+        Ok(self.gas_strategy.price(None, gas_price))
+    }
+    
+    /// Build a transaction from `from`, filling in the gas limit and fees
+    /// the caller left unset
+    ///
+    /// A caller-set `gas_price`, or both EIP-1559 fee fields, take
+    /// precedence over the estimate.
+    pub async fn prepare_transaction(&self, transaction: &RawTransaction, from: Address) -> Result<TypedTransaction> {
+        let to = Self::parse_address(&transaction.to)?;
+        let value = Self::parse_wei(&transaction.value, "value")?;
+        
+        let fees = match (
+            &transaction.gas_price,
+            &transaction.max_fee_per_gas,
+            &transaction.max_priority_fee_per_gas,
+        ) {
+            (Some(gas_price), _, _) => GasFees::Legacy {
+                gas_price: Self::parse_wei(gas_price, "gas price")?,
+            },
+            (None, Some(max_fee), Some(priority)) => GasFees::Eip1559 {
+                max_fee_per_gas: Self::parse_wei(max_fee, "max fee per gas")?,
+                max_priority_fee_per_gas: Self::parse_wei(priority, "max priority fee per gas")?,
+            },
+            _ => self.estimate_fees().await?,
+        };
+        
+        let mut request: TypedTransaction = match fees {
+            GasFees::Eip1559 {
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+            } => Eip1559TransactionRequest::new()
+                .from(from)
+                .to(to)
+                .value(value)
+                .max_fee_per_gas(max_fee_per_gas)
+                .max_priority_fee_per_gas(max_priority_fee_per_gas)
+                .into(),
+            GasFees::Legacy { gas_price } => TransactionRequest::new()
+                .from(from)
+                .to(to)
+                .value(value)
+                .gas_price(gas_price)
+                .into(),
+        };
+        
+        if let Some(data) = &transaction.data {
+            let data = hex::decode(data.trim_start_matches("0x"))
+                .map_err(|e| Error::BlockchainError(format!("Invalid data: {}", e)))?;
+            request.set_data(data.into());
+        }
+        
+        if let Some(nonce) = transaction.nonce {
+            request.set_nonce(nonce);
+        }
+        
+        let gas_limit = match transaction.gas_limit {
+            Some(gas_limit) => U256::from(gas_limit),
+            None => self
+                .providers
+                .send_client()
+                .estimate_gas(&request, None)
+                .await
+                .map_err(|e| Self::rpc_error("Failed to estimate gas", e))?,
+        };
+        request.set_gas(gas_limit);
+        
+        Ok(request)
+    }
+    
+    /// Build calldata for a call: the function selector followed by the
+    /// ABI-encoded arguments
+    ///
+    /// Each argument is parsed according to its parameter type in
+    /// `method_signature`, e.g. `"function transfer(address,uint256)"`.
+    pub(crate) fn encode_call(method_signature: &str, args: &[String]) -> Result<Vec<u8>> {
+        let function = parse_abi(&[method_signature])
+            .map_err(|e| Error::BlockchainError(format!("Invalid method signature: {}", e)))?
+            .functions
+            .values()
+            .next()
+            .and_then(|overloads| overloads.first())
+            .cloned()
+            .ok_or_else(|| Error::BlockchainError("No functions found in ABI".to_string()))?;
+        
+        if args.len() != function.inputs.len() {
+            return Err(Error::ValidationError(format!(
+                "{} takes {} arguments, {} given",
+                function.name,
+                function.inputs.len(),
+                args.len()
+            )));
+        }
+        
+        let tokens = function
+            .inputs
+            .iter()
+            .zip(args)
+            .map(|(input, arg)| {
+                Self::parse_token(arg, &input.kind).map_err(|reason| {
+                    Error::ValidationError(format!(
+                        "Argument {} of {}: cannot parse {:?} as {}: {}",
+                        input.name, function.name, arg, input.kind, reason
+                    ))
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        
+        function
+            .encode_input(&tokens)
+            .map_err(|e| Error::BlockchainError(format!("Failed to encode call: {}", e)))
+    }
+    
+    /// Parse a string argument into an ABI token of the given type
+    fn parse_token(value: &str, kind: &ParamType) -> std::result::Result<Token, String> {
+        let value = value.trim();
+        
+        match kind {
+            ParamType::Address => Address::from_str(value)
+                .map(Token::Address)
+                .map_err(|e| e.to_string()),
+            ParamType::Uint(_) => {
+                let parsed = match value.strip_prefix("0x") {
+                    Some(hex) => U256::from_str_radix(hex, 16).map_err(|e| e.to_string()),
+                    None => U256::from_dec_str(value).map_err(|e| e.to_string()),
+                };
+                parsed.map(Token::Uint)
+            }
+            ParamType::Bool => match value {
+                "true" => Ok(Token::Bool(true)),
+                "false" => Ok(Token::Bool(false)),
+                _ => Err("expected true or false".to_string()),
+            },
+            ParamType::String => Ok(Token::String(value.to_string())),
+            ParamType::Bytes => hex::decode(value.trim_start_matches("0x"))
+                .map(Token::Bytes)
+                .map_err(|e| e.to_string()),
+            ParamType::FixedBytes(size) => {
+                let bytes = hex::decode(value.trim_start_matches("0x")).map_err(|e| e.to_string())?;
+                if bytes.len() != *size {
+                    return Err(format!("expected {} bytes, got {}", size, bytes.len()));
+                }
+                Ok(Token::FixedBytes(bytes))
+            }
+            other => Err(format!("{} arguments are not supported", other)),
+        }
+    }
 }
 
 #[async_trait]
@@ -69,10 +370,53 @@ impl BlockchainInterface for EthereumAdapter {
         let address = Self::parse_address(address)?;
         
         let balance = self
-            .provider
-            .get_balance(address, None)
-            .await
-            .map_err(|e| Error::BlockchainError(format!("Failed to get balance: {}", e)))?;
+            .providers
+            .read(|provider| async move {
+                provider
+                    .get_balance(address, None)
+                    .await
+                    .map_err(|e| Error::BlockchainError(format!("Failed to get balance: {}", e)))
+            })
+            .await?;
+        
+        Ok(Self::format_u256(balance))
+    }
+    
+    async fn balance_at_block(&self, address: &str, block: u64) -> Result<String> {
+        let address = Self::parse_address(address)?;
+        
+        let balance = self
+            .providers
+            .read(|provider| async move {
+                provider
+                    .get_balance(address, Some(block.into()))
+                    .await
+                    .map_err(|e| Error::BlockchainError(format!("Failed to get balance at block {}: {}", block, e)))
+            })
+            .await?;
+        
+        Ok(Self::format_u256(balance))
+    }
+    
+    async fn erc20_balance(&self, token_contract: &str, holder: &str) -> Result<String> {
+        let result = self
+            .call_contract(
+                token_contract,
+                "function balanceOf(address) view returns (uint256)",
+                &[holder.to_string()],
+            )
+            .await?;
+        
+        let word = result.trim_start_matches("0x");
+        if word.is_empty() {
+            return Err(Error::BlockchainError(format!(
+                "{} returned no data for balanceOf; is it an ERC-20 contract?",
+                token_contract
+            )));
+        }
+        
+        let balance = U256::from_str_radix(word, 16)
+            .map_err(|e| Error::BlockchainError(format!("Invalid balanceOf result: {}", e)))?;
         
         Ok(Self::format_u256(balance))
     }
@@ -82,10 +426,14 @@ impl BlockchainInterface for EthereumAdapter {
         
         // Get transaction details
         let tx = self
-            .provider
-            .get_transaction(hash)
-            .await
-            .map_err(|e| Error::BlockchainError(format!("Failed to get transaction: {}", e)))?;
+            .providers
+            .read(|provider| async move {
+                provider
+                    .get_transaction(hash)
+                    .await
+                    .map_err(|e| Error::BlockchainError(format!("Failed to get transaction: {}", e)))
+            })
+            .await?;
         
         let tx = match tx {
             Some(tx) => tx,
@@ -99,10 +447,14 @@ impl BlockchainInterface for EthereumAdapter {
         
         // Get transaction receipt for additional details
         let receipt = self
-            .provider
-            .get_transaction_receipt(hash)
-            .await
-            .map_err(|e| Error::BlockchainError(format!("Failed to get transaction receipt: {}", e)))?;
+            .providers
+            .read(|provider| async move {
+                provider
+                    .get_transaction_receipt(hash)
+                    .await
+                    .map_err(|e| Error::BlockchainError(format!("Failed to get transaction receipt: {}", e)))
+            })
+            .await?;
         
         let (status, gas_used) = match receipt {
             Some(receipt) => (receipt.status, receipt.gas_used),
@@ -130,40 +482,14 @@ impl BlockchainInterface for EthereumAdapter {
             // Assume it's a private key
             let signer = self.with_signer(&transaction.from)?;
             
-            let to_address = Self::parse_address(&transaction.to)?;
-            let value = U256::from_dec_str(&transaction.value)
-                .map_err(|e| Error::BlockchainError(format!("Invalid value: {}", e)))?;
-            
-            let mut tx_request = TransactionRequest::new()
-                .to(to_address)
-                .value(value);
-            
-            // Add optional fields
-            if let Some(data) = &transaction.data {
-                let data = hex::decode(&data.trim_start_matches("0x"))
-                    .map_err(|e| Error::BlockchainError(format!("Invalid data: {}", e)))?;
-                tx_request = tx_request.data(data);
-            }
-            
-            if let Some(gas_limit) = transaction.gas_limit {
-                tx_request = tx_request.gas(gas_limit);
-            }
-            
-            if let Some(gas_price) = &transaction.gas_price {
-                let gas_price = U256::from_dec_str(gas_price)
-                    .map_err(|e| Error::BlockchainError(format!("Invalid gas price: {}", e)))?;
-                tx_request = tx_request.gas_price(gas_price);
-            }
-            
-            if let Some(nonce) = transaction.nonce {
-                tx_request = tx_request.nonce(nonce);
-            }
+            // Estimate the gas limit and fees the caller left unset
+            let tx_request = self.prepare_transaction(transaction, signer.address()).await?;
             
             // Send the transaction
             let pending_tx = signer
                 .send_transaction(tx_request, None)
                 .await
-                .map_err(|e| Error::BlockchainError(format!("Failed to send transaction: {}", e)))?;
+                .map_err(|e| Self::rpc_error("Failed to send transaction", e))?;
             
             Ok(format!("{:#x}", pending_tx.tx_hash()))
         } else {
@@ -202,48 +528,25 @@ impl BlockchainInterface for EthereumAdapter {
     async fn call_contract(&self, contract_address: &str, method_signature: &str, args: &[String]) -> Result<String> {
         let address = Self::parse_address(contract_address)?;
         
-        // Parse the method signature to get the function selector
-        let function = parse_abi(&[method_signature])
-            .map_err(|e| Error::BlockchainError(format!("Invalid method signature: {}", e)))?
-            .functions
-            .values()
-            .next()
-            .ok_or_else(|| Error::BlockchainError("No functions found in ABI".to_string()))?
-            .clone();
-        
-        // Parse the arguments
-        let mut encoded_args = Vec::new();
-        
-        for (i, arg) in args.iter().enumerate() {
-            if i >= function.inputs.len() {
-                return Err(Error::BlockchainError(format!("Too many arguments provided")));
-            }
-            
-            let param_type = &function.inputs[i].kind;
-            
-            // This is a simplified version - in a real implementation, you would need to
-            // properly encode each argument based on its type
-            encoded_args.push(arg.clone());
-        }
-        
-        // Create a call data string (simplified version)
-        let data = format!(
-            "0x{}{}",
-            function.short_signature(),
-            encoded_args.join("")
-        );
+        let data = Self::encode_call(method_signature, args)?;
         
         // Call the contract
+        let request = TransactionRequest::new()
+            .to(address)
+            .data(data);
+        
         let result = self
-            .provider
-            .call(
-                &TransactionRequest::new()
-                    .to(address)
-                    .data(data),
-                None,
-            )
-            .await
-            .map_err(|e| Error::BlockchainError(format!("Contract call failed: {}", e)))?;
+            .providers
+            .read(|provider| {
+                let request = request.clone();
+                async move {
+                    provider
+                        .call(&request.into(), None)
+                        .await
+                        .map_err(|e| Self::rpc_error("Contract call failed", e))
+                }
+            })
+            .await?;
         
         Ok(format!("0x{}", hex::encode(result.as_ref())))
     }
@@ -258,36 +561,7 @@ impl BlockchainInterface for EthereumAdapter {
         let signer = self.with_signer(private_key)?;
         let address = Self::parse_address(contract_address)?;
         
-        // Parse the method signature to get the function selector
-        let function = parse_abi(&[method_signature])
-            .map_err(|e| Error::BlockchainError(format!("Invalid method signature: {}", e)))?
-            .functions
-            .values()
-            .next()
-            .ok_or_else(|| Error::BlockchainError("No functions found in ABI".to_string()))?
-            .clone();
-        
-        // Parse the arguments
-        let mut encoded_args = Vec::new();
-        
-        for (i, arg) in args.iter().enumerate() {
-            if i >= function.inputs.len() {
-                return Err(Error::BlockchainError(format!("Too many arguments provided")));
-            }
-            
-            let param_type = &function.inputs[i].kind;
-            
-            // This is a simplified version - in a real implementation, you would need to
-            // properly encode each argument based on its type
-            encoded_args.push(arg.clone());
-        }
-        
-        // Create a call data string (simplified version)
-        let data = format!(
-            "0x{}{}",
-            function.short_signature(),
-            encoded_args.join("")
-        );
+        let data = Self::encode_call(method_signature, args)?;
         
         // Send the transaction
         let pending_tx = signer
@@ -298,30 +572,92 @@ impl BlockchainInterface for EthereumAdapter {
                 None,
             )
             .await
-            .map_err(|e| Error::BlockchainError(format!("Failed to send transaction: {}", e)))?;
+            .map_err(|e| Self::rpc_error("Failed to send transaction", e))?;
         
         Ok(format!("{:#x}", pending_tx.tx_hash()))
     }
     
     async fn get_block_number(&self) -> Result<u64> {
         let block_number = self
-            .provider
-            .get_block_number()
-            .await
-            .map_err(|e| Error::BlockchainError(format!("Failed to get block number: {}", e)))?;
+            .providers
+            .read(|provider| async move {
+                provider
+                    .get_block_number()
+                    .await
+                    .map_err(|e| Error::BlockchainError(format!("Failed to get block number: {}", e)))
+            })
+            .await?;
         
         Ok(block_number.as_u64())
     }
     
     async fn get_chain_id(&self) -> Result<u64> {
         let chain_id = self
-            .provider
-            .get_chainid()
-            .await
-            .map_err(|e| Error::BlockchainError(format!("Failed to get chain ID: {}", e)))?;
+            .providers
+            .read(|provider| async move {
+                provider
+                    .get_chainid()
+                    .await
+                    .map_err(|e| Error::BlockchainError(format!("Failed to get chain ID: {}", e)))
+            })
+            .await?;
         
         Ok(chain_id.as_u64())
     }
+    
+    async fn get_code_hash(&self, address: &str) -> Result<String> {
+        let address = Self::parse_address(address)?;
+        
+        let code = self
+            .providers
+            .read(|provider| async move {
+                provider
+                    .get_code(address, None)
+                    .await
+                    .map_err(|e| Error::BlockchainError(format!("Failed to get contract code: {}", e)))
+            })
+            .await?;
+        
+        Ok(format!("0x{}", hex::encode(ethers::utils::keccak256(&code))))
+    }
+    
+    async fn get_logs(&self, filter: &LogFilter) -> Result<Vec<Log>> {
+        let address = Self::parse_address(&filter.address)?;
+        let mut query = Filter::new()
+            .address(address)
+            .from_block(filter.from_block)
+            .to_block(filter.to_block);
+        if let Some(topic0) = &filter.topic0 {
+            let topic0 = H256::from_str(topic0)
+                .map_err(|e| Error::BlockchainError(format!("Invalid event topic: {}", e)))?;
+            query = query.topic0(topic0);
+        }
+        
+        let logs = self
+            .providers
+            .read(|provider| {
+                let query = query.clone();
+                async move {
+                    provider
+                        .get_logs(&query)
+                        .await
+                        .map_err(|e| Error::BlockchainError(format!("Failed to get logs: {}", e)))
+                }
+            })
+            .await?;
+        
+        Ok(logs
+            .into_iter()
+            .map(|log| Log {
+                address: format!("{:?}", log.address),
+                topics: log.topics.iter().map(|topic| format!("{:?}", topic)).collect(),
+                data: format!("0x{}", hex::encode(&log.data)),
+                block_number: log.block_number.map(|block| block.as_u64()),
+                transaction_hash: log.transaction_hash.map(|hash| format!("{:?}", hash)),
+                log_index: log.log_index.map(|index| index.as_u64()),
+            })
+            .collect())
+    }
 }
 
 #[cfg(test)]
@@ -330,6 +666,57 @@ mod tests {
     use ethers::utils::Anvil;
     use std::time::Duration;
     
+    #[test]
+    fn test_encode_transfer_call() {
+        let recipient = "0x70997970C51812dc3A010C7d01b50e0d17dc79C8";
+        let data = EthereumAdapter::encode_call(
+            "function transfer(address to, uint256 amount) returns (bool)",
+            &[recipient.to_string(), "1000000000000000000".to_string()],
+        )
+        .unwrap();
+        
+        // Selector, then one word per argument
+        assert_eq!(hex::encode(&data[..4]), "a9059cbb");
+        assert_eq!(data.len(), 4 + 2 * 32);
+        
+        let function = parse_abi(&["function transfer(address,uint256)"]).unwrap().function("transfer").unwrap().clone();
+        let decoded = function.decode_input(&data[4..]).unwrap();
+        assert_eq!(decoded[0], Token::Address(Address::from_str(recipient).unwrap()));
+        assert_eq!(decoded[1], Token::Uint(U256::exp10(18)));
+        
+        // Hex amounts encode the same way
+        let hex_amount = EthereumAdapter::encode_call(
+            "function transfer(address,uint256)",
+            &[recipient.to_string(), "0xde0b6b3a7640000".to_string()],
+        )
+        .unwrap();
+        assert_eq!(hex_amount, data);
+    }
+    
+    #[test]
+    fn test_encode_call_rejects_unparseable_arguments() {
+        let signature = "function transfer(address,uint256)";
+        
+        for args in [
+            vec!["0xnot-an-address".to_string(), "1".to_string()],
+            vec!["0x70997970C51812dc3A010C7d01b50e0d17dc79C8".to_string(), "-5".to_string()],
+            vec!["0x70997970C51812dc3A010C7d01b50e0d17dc79C8".to_string()],
+        ] {
+            assert!(matches!(
+                EthereumAdapter::encode_call(signature, &args),
+                Err(Error::ValidationError(_))
+            ));
+        }
+        
+        let data = EthereumAdapter::encode_call(
+            "function setConfig(bool enabled, string label, bytes payload)",
+            &["true".to_string(), "grants".to_string(), "0xdeadbeef".to_string()],
+        )
+        .unwrap();
+        assert_eq!(hex::encode(&data[..4]), hex::encode(&ethers::utils::id("setConfig(bool,string,bytes)")[..4]));
+        assert!(EthereumAdapter::encode_call("function setPaused(bool)", &["yes".to_string()]).is_err());
+    }
+    
     async fn setup_test_environment() -> (EthereumAdapter, LocalWallet) {
         // Start an Anvil instance (local Ethereum node for testing)
         let anvil = Anvil::new().spawn();
@@ -378,6 +765,190 @@ mod tests {
         assert!(!is_valid);
     }
     
+    /// Mock ERC-20 whose `balanceOf(holder)` returns the holder's low 16 bits
+    /// and which reverts on any other selector
+    const MOCK_ERC20_BYTECODE: &str = concat!(
+        "602380600b6000396000f3",             // constructor: return the runtime code
+        "60003560e01c6370a08231146013576000", // selector == balanceOf?
+        "80fd5b",                             // else revert
+        "60043561ffff1660005260206000f3",     // return holder & 0xffff
+    );
+    
+    #[tokio::test]
+    async fn test_erc20_balance() {
+        let anvil = Anvil::new().spawn();
+        let adapter = EthereumAdapter::new(&anvil.endpoint()).unwrap();
+        
+        // Deploy the mock token
+        let wallet: LocalWallet = anvil.keys()[0].clone().into();
+        let provider = Provider::<Http>::try_from(anvil.endpoint()).unwrap();
+        let client = SignerMiddleware::new(provider, wallet.with_chain_id(anvil.chain_id()));
+        let deploy = TransactionRequest::new().data(hex::decode(MOCK_ERC20_BYTECODE).unwrap());
+        let receipt = client
+            .send_transaction(deploy, None)
+            .await
+            .unwrap()
+            .await
+            .unwrap()
+            .unwrap();
+        let token = format!("{:#x}", receipt.contract_address.unwrap());
+        
+        let holder = anvil.addresses()[1];
+        let expected = u16::from_be_bytes([holder.as_bytes()[18], holder.as_bytes()[19]]);
+        
+        let balance = adapter.erc20_balance(&token, &format!("{:#x}", holder)).await.unwrap();
+        assert_eq!(balance, expected.to_string());
+        
+        // The native balance is unrelated to the token balance
+        assert_eq!(adapter.get_balance(&format!("{:#x}", holder)).await.unwrap(), "10000000000000000000000");
+        
+        // An account without code is not a token
+        assert!(adapter.erc20_balance(&format!("{:#x}", anvil.addresses()[2]), &format!("{:#x}", holder)).await.is_err());
+    }
+    
+    #[test]
+    fn test_gas_strategies() {
+        let gwei = U256::exp10(9);
+        let estimate = Some((U256::from(30) * gwei, U256::from(2) * gwei));
+        
+        assert_eq!(
+            GasStrategy::Standard.price(estimate, U256::zero()),
+            GasFees::Eip1559 {
+                max_fee_per_gas: U256::from(30) * gwei,
+                max_priority_fee_per_gas: U256::from(2) * gwei,
+            }
+        );
+        assert_eq!(
+            GasStrategy::Fast.price(estimate, U256::zero()),
+            GasFees::Eip1559 {
+                max_fee_per_gas: U256::from(32) * gwei,
+                max_priority_fee_per_gas: U256::from(4) * gwei,
+            }
+        );
+        assert_eq!(
+            GasStrategy::Static(U256::from(50) * gwei).price(estimate, U256::zero()),
+            GasFees::Eip1559 {
+                max_fee_per_gas: U256::from(50) * gwei,
+                max_priority_fee_per_gas: U256::from(2) * gwei,
+            }
+        );
+        
+        // Chains without a base fee use legacy pricing
+        assert_eq!(
+            GasStrategy::Fast.price(None, U256::from(20) * gwei),
+            GasFees::Legacy { gas_price: U256::from(25) * gwei }
+        );
+        assert_eq!(
+            GasStrategy::Static(U256::from(50) * gwei).price(None, U256::zero()),
+            GasFees::Legacy { gas_price: U256::from(50) * gwei }
+        );
+        
+        let mut config = crate::config::default().blockchain;
+        config.gas_price_strategy = "static".to_string();
+        config.static_gas_price_gwei = Some(50);
+        assert_eq!(GasStrategy::from_config(&config).unwrap(), GasStrategy::Static(U256::from(50) * gwei));
+        config.static_gas_price_gwei = None;
+        assert!(GasStrategy::from_config(&config).is_err());
+        config.gas_price_strategy = "cheapest".to_string();
+        assert!(GasStrategy::from_config(&config).is_err());
+    }
+    
+    fn transfer(to: Address) -> RawTransaction {
+        RawTransaction {
+            from: String::new(),
+            to: format!("{:#x}", to),
+            value: "1000".to_string(),
+            data: None,
+            gas_limit: None,
+            gas_price: None,
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+            nonce: None,
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_prepare_transaction_estimates_eip1559_fees() {
+        let anvil = Anvil::new().spawn();
+        let adapter = EthereumAdapter::new(&anvil.endpoint()).unwrap();
+        let (from, to) = (anvil.addresses()[0], anvil.addresses()[1]);
+        
+        let prepared = adapter.prepare_transaction(&transfer(to), from).await.unwrap();
+        let TypedTransaction::Eip1559(request) = &prepared else {
+            panic!("expected an EIP-1559 transaction, got {:?}", prepared);
+        };
+        
+        // A plain transfer costs 21000 gas
+        assert_eq!(request.gas, Some(U256::from(21_000)));
+        let max_fee = request.max_fee_per_gas.unwrap();
+        let priority = request.max_priority_fee_per_gas.unwrap();
+        assert!(priority > U256::zero());
+        assert!(max_fee >= priority);
+        
+        // The fast strategy bids a higher tip
+        let fast = EthereumAdapter::new(&anvil.endpoint()).unwrap().with_gas_strategy(GasStrategy::Fast);
+        let TypedTransaction::Eip1559(fast_request) = fast.prepare_transaction(&transfer(to), from).await.unwrap() else {
+            panic!("expected an EIP-1559 transaction");
+        };
+        assert!(fast_request.max_priority_fee_per_gas.unwrap() > priority);
+        
+        // The estimated transaction is accepted by the node
+        let wallet: LocalWallet = anvil.keys()[0].clone().into();
+        let mut raw = transfer(to);
+        raw.from = hex::encode(wallet.signer().to_bytes());
+        let tx_hash = adapter.send_transaction(&raw).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(adapter.get_transaction(&tx_hash).await.unwrap().status, Some(true));
+    }
+    
+    #[tokio::test]
+    async fn test_prepare_transaction_falls_back_to_legacy_pricing() {
+        // Berlin predates EIP-1559, so blocks carry no base fee
+        let anvil = Anvil::new().args(["--hardfork", "berlin"]).spawn();
+        let adapter = EthereumAdapter::new(&anvil.endpoint()).unwrap();
+        let (from, to) = (anvil.addresses()[0], anvil.addresses()[1]);
+        
+        let prepared = adapter.prepare_transaction(&transfer(to), from).await.unwrap();
+        assert!(matches!(prepared, TypedTransaction::Legacy(_)));
+        assert!(prepared.gas_price().unwrap() > U256::zero());
+        assert_eq!(prepared.gas(), Some(&U256::from(21_000)));
+        
+        // Caller-set fees are kept as given
+        let mut priced = transfer(to);
+        priced.gas_price = Some("7".to_string());
+        priced.gas_limit = Some(30_000);
+        let prepared = adapter.prepare_transaction(&priced, from).await.unwrap();
+        assert_eq!(prepared.gas_price(), Some(U256::from(7)));
+        assert_eq!(prepared.gas(), Some(&U256::from(30_000)));
+    }
+    
+    #[tokio::test]
+    async fn test_wait_for_confirmation() {
+        let anvil = Anvil::new().spawn();
+        let adapter = EthereumAdapter::new(&anvil.endpoint()).unwrap();
+        let provider = Provider::<Http>::try_from(anvil.endpoint()).unwrap();
+        
+        let wallet: LocalWallet = anvil.keys()[0].clone().into();
+        let mut raw = transfer(anvil.addresses()[1]);
+        raw.from = hex::encode(wallet.signer().to_bytes());
+        let tx_hash = adapter.send_transaction(&raw).await.unwrap();
+        
+        // Anvil mines the transaction into its own block: one confirmation
+        assert!(adapter.wait_for_confirmation(&tx_hash, 1, Duration::from_secs(5)).await.unwrap());
+        
+        // Three confirmations need two more blocks on top
+        assert!(adapter.wait_for_confirmation(&tx_hash, 3, Duration::from_secs(1)).await.is_err());
+        
+        let mine = async {
+            for _ in 0..2 {
+                tokio::time::sleep(Duration::from_millis(300)).await;
+                provider.request::<_, String>("evm_mine", ()).await.unwrap();
+            }
+        };
+        let (confirmed, _) = tokio::join!(adapter.wait_for_confirmation(&tx_hash, 3, Duration::from_secs(10)), mine);
+        assert!(confirmed.unwrap());
+    }
+    
     #[tokio::test]
     async fn test_get_chain_id() {
         let (adapter, _) = setup_test_environment().await;
@@ -0,0 +1,396 @@
+//! Contract event polling
+//!
+//! An [`EventPoller`] follows one event of one contract. Each poll fetches
+//! the event's logs with `eth_getLogs` from the first block not yet
+//! delivered up to the chain head (less `confirmations`), decodes their
+//! topics and data against the event's ABI, and sends them down a channel.
+//!
+//! Progress is kept in `event_checkpoints`, keyed by chain, contract and
+//! event, and saved after each scanned chunk has been delivered, so a
+//! restarted poller continues where the last one stopped. A crash between
+//! delivering a chunk and saving its checkpoint redelivers that chunk;
+//! consumers that must not see a log twice can key on its transaction hash
+//! and log index.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use ethers::abi::{parse_abi, Event, RawLog, Token};
+use ethers::types::{H256, I256};
+use ethers::utils::hex;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+use crate::blockchain::{BlockchainInterface, Log, LogFilter, LogScanner, ScanCheckpoint};
+use crate::core::Database;
+use crate::error::{Error, Result};
+
+/// How often a spawned poller polls by default (about one Ethereum block)
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(12);
+
+/// A decoded contract event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContractEvent {
+    /// Event name
+    pub name: String,
+    /// Contract address
+    pub address: String,
+    /// Block number where the event was emitted
+    pub block_number: u64,
+    /// Hash of the transaction that emitted the event
+    pub transaction_hash: Option<String>,
+    /// Position of the log in its block
+    pub log_index: Option<u64>,
+    /// Event parameters by name; integers are decimal strings, addresses
+    /// and bytes are 0x-prefixed hex
+    pub params: HashMap<String, serde_json::Value>,
+}
+
+/// Polls a contract for one event and sends the decoded events to a channel
+pub struct EventPoller {
+    chain: Arc<dyn BlockchainInterface>,
+    scanner: LogScanner,
+    database: Database,
+    address: String,
+    event: Event,
+    start_block: u64,
+    confirmations: u64,
+    poll_interval: Duration,
+}
+
+impl EventPoller {
+    /// Create a poller for an event of a contract
+    ///
+    /// `event_signature` is the event's human-readable ABI, such as
+    /// `Transfer(address indexed from, address indexed to, uint256 value)`.
+    pub fn new(
+        chain: Arc<dyn BlockchainInterface>,
+        scanner: LogScanner,
+        database: Database,
+        address: &str,
+        event_signature: &str,
+    ) -> Result<Self> {
+        Ok(Self {
+            chain,
+            scanner,
+            database,
+            address: address.to_lowercase(),
+            event: parse_event(event_signature)?,
+            start_block: 0,
+            confirmations: 0,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+        })
+    }
+
+    /// Block to start from when there is no saved checkpoint
+    pub fn with_start_block(mut self, start_block: u64) -> Self {
+        self.start_block = start_block;
+        self
+    }
+
+    /// Only deliver logs this many blocks below the head, so reorgs don't
+    /// deliver logs that are later dropped
+    pub fn with_confirmations(mut self, confirmations: u64) -> Self {
+        self.confirmations = confirmations;
+        self
+    }
+
+    /// Set how often a spawned poller polls
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Signature hash of the event (topic 0)
+    pub fn topic0(&self) -> String {
+        format!("{:?}", self.event.signature())
+    }
+
+    /// Deliver the logs emitted since the last poll
+    ///
+    /// Returns the number of events sent. Logs that match the event's
+    /// signature but don't decode against its ABI are skipped with a warning.
+    pub async fn poll(&self, sender: &mpsc::Sender<ContractEvent>) -> Result<usize> {
+        let chain_id = self.chain.get_chain_id().await?;
+        let key = format!("{}:{}:{}", chain_id, self.address, self.topic0());
+        let head = self.chain.get_block_number().await?.saturating_sub(self.confirmations);
+
+        let resume = self.load_checkpoint(&key).await?;
+        if resume.map_or(self.start_block, |checkpoint| checkpoint.next_block) > head {
+            return Ok(0);
+        }
+
+        let filter = LogFilter::new(&self.address, self.start_block, head).event(&self.topic0());
+        let key = key.as_str();
+        let mut delivered = 0;
+
+        self.scanner
+            .scan(
+                filter.from_block,
+                filter.to_block,
+                resume,
+                |start, end| {
+                    let window = filter.blocks(start, end);
+                    async move { self.chain.get_logs(&window).await }
+                },
+                |logs, checkpoint| {
+                    let events: Vec<ContractEvent> = logs.iter().filter_map(|log| self.decode(log)).collect();
+                    delivered += events.len();
+                    async move {
+                        for event in events {
+                            sender
+                                .send(event)
+                                .await
+                                .map_err(|_| Error::InternalError("Event receiver closed".to_string()))?;
+                        }
+                        self.save_checkpoint(key, checkpoint).await
+                    }
+                },
+            )
+            .await?;
+
+        Ok(delivered)
+    }
+
+    /// Poll every `poll_interval` until the receiver is dropped
+    pub fn spawn(self, sender: mpsc::Sender<ContractEvent>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.poll_interval);
+            while !sender.is_closed() {
+                interval.tick().await;
+                if let Err(e) = self.poll(&sender).await {
+                    warn!(contract = %self.address, event = %self.event.name, "Event poll failed: {}", e);
+                }
+            }
+        })
+    }
+
+    /// Decode a log against the event's ABI
+    fn decode(&self, log: &Log) -> Option<ContractEvent> {
+        match decode_log(&self.event, log) {
+            Ok(event) => Some(event),
+            Err(e) => {
+                warn!(
+                    contract = %self.address,
+                    transaction = ?log.transaction_hash,
+                    "Skipping undecodable {} log: {}",
+                    self.event.name,
+                    e
+                );
+                None
+            }
+        }
+    }
+
+    async fn load_checkpoint(&self, key: &str) -> Result<Option<ScanCheckpoint>> {
+        let row = self
+            .database
+            .query_opt_any(
+                "SELECT next_block, block_range FROM event_checkpoints WHERE poller = $1",
+                &[key.into()],
+            )
+            .await
+            .map_err(|e| Error::DatabaseError(e.to_string()))?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let column = |name: &str| {
+            row.int(name)
+                .map(|value| value as u64)
+                .map_err(|e| Error::DatabaseError(e.to_string()))
+        };
+        Ok(Some(ScanCheckpoint {
+            next_block: column("next_block")?,
+            block_range: column("block_range")?,
+        }))
+    }
+
+    async fn save_checkpoint(&self, key: &str, checkpoint: ScanCheckpoint) -> Result<()> {
+        self.database
+            .execute_any(
+                "INSERT INTO event_checkpoints (poller, next_block, block_range, updated_at) VALUES ($1, $2, $3, $4) \
+                 ON CONFLICT (poller) DO UPDATE SET next_block = excluded.next_block, \
+                 block_range = excluded.block_range, updated_at = excluded.updated_at",
+                &[
+                    key.into(),
+                    (checkpoint.next_block as i64).into(),
+                    (checkpoint.block_range as i64).into(),
+                    Utc::now().timestamp().into(),
+                ],
+            )
+            .await
+            .map_err(|e| Error::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+}
+
+/// Parse a human-readable event signature, with or without `event`
+fn parse_event(signature: &str) -> Result<Event> {
+    let signature = signature.trim();
+    let declaration = if signature.starts_with("event ") {
+        signature.to_string()
+    } else {
+        format!("event {}", signature)
+    };
+
+    parse_abi(&[declaration.as_str()])
+        .ok()
+        .and_then(|abi| abi.events().next().cloned())
+        .ok_or_else(|| Error::ValidationError(format!("Invalid event signature: {}", signature)))
+}
+
+/// Decode a log's topics and data against an event's ABI
+fn decode_log(event: &Event, log: &Log) -> Result<ContractEvent> {
+    let topics = log
+        .topics
+        .iter()
+        .map(|topic| H256::from_str(topic))
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|e| Error::BlockchainError(format!("Invalid log topic: {}", e)))?;
+    let data = hex::decode(log.data.trim_start_matches("0x"))
+        .map_err(|e| Error::BlockchainError(format!("Invalid log data: {}", e)))?;
+
+    let decoded = event
+        .parse_log(RawLog { topics, data })
+        .map_err(|e| Error::BlockchainError(format!("Failed to decode {} log: {}", event.name, e)))?;
+
+    Ok(ContractEvent {
+        name: event.name.clone(),
+        address: log.address.clone(),
+        block_number: log.block_number.unwrap_or_default(),
+        transaction_hash: log.transaction_hash.clone(),
+        log_index: log.log_index,
+        params: decoded
+            .params
+            .into_iter()
+            .map(|param| (param.name, token_to_json(param.value)))
+            .collect(),
+    })
+}
+
+/// Convert a decoded ABI value to JSON
+///
+/// Integers become decimal strings, since they don't fit JSON numbers.
+/// Indexed strings, bytes and arrays are only available as their hash.
+fn token_to_json(token: Token) -> serde_json::Value {
+    match token {
+        Token::Address(address) => format!("{:?}", address).into(),
+        Token::Uint(value) => value.to_string().into(),
+        Token::Int(value) => I256::from_raw(value).to_string().into(),
+        Token::Bool(value) => value.into(),
+        Token::String(value) => value.into(),
+        Token::Bytes(bytes) | Token::FixedBytes(bytes) => format!("0x{}", hex::encode(bytes)).into(),
+        Token::Array(tokens) | Token::FixedArray(tokens) | Token::Tuple(tokens) => {
+            tokens.into_iter().map(token_to_json).collect::<Vec<_>>().into()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::ethereum::EthereumAdapter;
+    use crate::config::LogScanConfig;
+    use ethers::prelude::{Http, LocalWallet, Middleware, Provider, Signer, SignerMiddleware, TransactionRequest};
+    use ethers::types::Address;
+    use ethers::utils::Anvil;
+
+    const TRANSFER: &str = "Transfer(address indexed from, address indexed to, uint256 value)";
+
+    /// Contract that emits `Transfer(msg.sender, to, amount)` for any call
+    /// taking `(address to, uint256 amount)`
+    const EMITTER_BYTECODE: &str = concat!(
+        "603180600b6000396000f3", // constructor: return the runtime code
+        "602435600052",           // mstore(0, amount)
+        "60043533",               // topics: to, msg.sender
+        "7fddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef", // Transfer topic
+        "60206000a300",           // log3(0, 32, ...)
+    );
+
+    #[test]
+    fn test_parse_event_signature() {
+        let with_keyword = parse_event(&format!("event {}", TRANSFER)).unwrap();
+        let without = parse_event(TRANSFER).unwrap();
+        assert_eq!(with_keyword, without);
+        assert_eq!(
+            format!("{:?}", without.signature()),
+            "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"
+        );
+
+        assert!(parse_event("Transfer(address indexed").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_poller_decodes_emitted_event_once() {
+        let anvil = Anvil::new().spawn();
+        let adapter: Arc<dyn BlockchainInterface> = Arc::new(EthereumAdapter::new(&anvil.endpoint()).unwrap());
+        let database = Database::migrated_sqlite().await;
+
+        let wallet: LocalWallet = anvil.keys()[0].clone().into();
+        let provider = Provider::<Http>::try_from(anvil.endpoint()).unwrap();
+        let client = SignerMiddleware::new(provider, wallet.with_chain_id(anvil.chain_id()));
+        let deploy = TransactionRequest::new().data(hex::decode(EMITTER_BYTECODE).unwrap());
+        let receipt = client.send_transaction(deploy, None).await.unwrap().await.unwrap().unwrap();
+        let emitter = receipt.contract_address.unwrap();
+
+        let emit = |to: Address, amount: u64| {
+            let data = EthereumAdapter::encode_call(
+                "function transfer(address to, uint256 amount)",
+                &[format!("{:#x}", to), amount.to_string()],
+            )
+            .unwrap();
+            let client = &client;
+            async move {
+                client
+                    .send_transaction(TransactionRequest::new().to(emitter).data(data), None)
+                    .await
+                    .unwrap()
+                    .await
+                    .unwrap()
+                    .unwrap()
+            }
+        };
+        let poller = || {
+            EventPoller::new(
+                adapter.clone(),
+                LogScanner::new(LogScanConfig::default()),
+                database.clone(),
+                &format!("{:#x}", emitter),
+                TRANSFER,
+            )
+            .unwrap()
+        };
+
+        let first = emit(anvil.addresses()[1], 1_000).await;
+        let (sender, mut receiver) = mpsc::channel(16);
+        assert_eq!(poller().poll(&sender).await.unwrap(), 1);
+
+        let event = receiver.try_recv().unwrap();
+        assert_eq!(event.name, "Transfer");
+        assert_eq!(event.block_number, first.block_number.unwrap().as_u64());
+        assert_eq!(event.transaction_hash, Some(format!("{:?}", first.transaction_hash)));
+        assert_eq!(event.params["from"], format!("{:?}", anvil.addresses()[0]));
+        assert_eq!(event.params["to"], format!("{:?}", anvil.addresses()[1]));
+        assert_eq!(event.params["value"], "1000");
+
+        // A restarted poller resumes from the saved block: only the new log
+        // is delivered
+        emit(anvil.addresses()[2], 5).await;
+        let restarted = poller();
+        assert_eq!(restarted.poll(&sender).await.unwrap(), 1);
+        let event = receiver.try_recv().unwrap();
+        assert_eq!(event.params["to"], format!("{:?}", anvil.addresses()[2]));
+        assert_eq!(event.params["value"], "5");
+
+        assert_eq!(restarted.poll(&sender).await.unwrap(), 0);
+        assert!(receiver.try_recv().is_err());
+    }
+}
@@ -0,0 +1,413 @@
+//! RPC endpoint failover for AtomSi DAO
+//!
+//! This module keeps a list of RPC endpoints for one chain, tracks their
+//! health, and routes calls so that a single provider outage doesn't take
+//! the DAO offline. Reads that fail on every endpoint with a transient error
+//! are retried with exponential backoff.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tracing::warn;
+
+use crate::config::RpcRetryConfig;
+use crate::error::{Error, Result};
+
+/// Consecutive failures after which an endpoint's circuit opens
+const FAILURE_THRESHOLD: u32 = 3;
+
+/// How long an endpoint with an open circuit is skipped
+const OPEN_CIRCUIT_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// Health snapshot of a single endpoint
+#[derive(Debug, Clone, Serialize)]
+pub struct EndpointStatus {
+    /// Endpoint URL
+    pub url: String,
+    /// Whether the endpoint is currently considered healthy
+    pub healthy: bool,
+    /// Number of consecutive failed calls
+    pub consecutive_failures: u32,
+    /// Total number of failed calls
+    pub total_failures: u64,
+}
+
+/// Mutable health state of an endpoint
+#[derive(Debug, Default)]
+struct Health {
+    consecutive_failures: u32,
+    total_failures: u64,
+    open_until: Option<Instant>,
+}
+
+/// An RPC endpoint and its client
+struct Endpoint<C> {
+    url: String,
+    client: C,
+    health: Mutex<Health>,
+}
+
+impl<C> Endpoint<C> {
+    fn is_healthy(&self) -> bool {
+        let health = self.health.lock().unwrap();
+        match health.open_until {
+            Some(until) => Instant::now() >= until,
+            None => true,
+        }
+    }
+    
+    fn record_success(&self) {
+        let mut health = self.health.lock().unwrap();
+        health.consecutive_failures = 0;
+        health.open_until = None;
+    }
+    
+    fn record_failure(&self) {
+        let mut health = self.health.lock().unwrap();
+        health.consecutive_failures += 1;
+        health.total_failures += 1;
+        if health.consecutive_failures >= FAILURE_THRESHOLD {
+            health.open_until = Some(Instant::now() + OPEN_CIRCUIT_COOLDOWN);
+        }
+    }
+}
+
+/// Whether an error may go away if the call is repeated
+///
+/// Validation failures, unsupported calls and reverts fail the same way on
+/// every endpoint, so they are returned without failing over or counting
+/// against the endpoint's health.
+fn is_transient(error: &Error) -> bool {
+    matches!(
+        error,
+        Error::BlockchainError(_)
+            | Error::NetworkError(_)
+            | Error::RateLimitError(_)
+            | Error::ExternalServiceError(_)
+    )
+}
+
+/// Delay before the given retry (1-based), doubling from the base delay up
+/// to the maximum
+fn retry_delay(config: &RpcRetryConfig, retry: u32) -> Duration {
+    let factor = 1u64.checked_shl(retry.saturating_sub(1)).unwrap_or(u64::MAX);
+    Duration::from_millis(config.base_delay_ms.saturating_mul(factor).min(config.max_delay_ms))
+}
+
+/// Ordered list of RPC endpoints with health tracking
+///
+/// Reads are spread round-robin over healthy endpoints and fail over to the
+/// next endpoint on a transient error. When every endpoint fails, the read
+/// is retried up to the configured number of times with exponential
+/// backoff. Sends always go to the first healthy endpoint in
+/// configuration order and are never retried elsewhere, so a transaction is
+/// not broadcast to two mempools with conflicting nonces.
+pub struct EndpointPool<C> {
+    endpoints: Vec<Endpoint<C>>,
+    next_read: AtomicUsize,
+    retry: RpcRetryConfig,
+}
+
+impl<C: Clone> EndpointPool<C> {
+    /// Create a pool from `(url, client)` pairs, primary first
+    ///
+    /// Reads are not retried once every endpoint has failed; see
+    /// [`EndpointPool::with_retry`].
+    pub fn new(endpoints: Vec<(String, C)>) -> Result<Self> {
+        if endpoints.is_empty() {
+            return Err(Error::ConfigError("At least one RPC endpoint is required".to_string()));
+        }
+        
+        Ok(Self {
+            endpoints: endpoints
+                .into_iter()
+                .map(|(url, client)| Endpoint {
+                    url,
+                    client,
+                    health: Mutex::new(Health::default()),
+                })
+                .collect(),
+            next_read: AtomicUsize::new(0),
+            retry: RpcRetryConfig {
+                max_retries: 0,
+                ..RpcRetryConfig::default()
+            },
+        })
+    }
+    
+    /// Set how reads are retried once every endpoint has failed
+    pub fn with_retry(mut self, retry: RpcRetryConfig) -> Self {
+        self.retry = retry;
+        self
+    }
+    
+    /// Run a read-only call, failing over to other endpoints on transient
+    /// errors and retrying with backoff when all of them fail
+    pub async fn read<T, F, Fut>(&self, call: F) -> Result<T>
+    where
+        F: Fn(C) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut last_error = None;
+        
+        for retry in 0..=self.retry.max_retries {
+            if retry > 0 {
+                let delay = retry_delay(&self.retry, retry);
+                warn!("All RPC endpoints failed, retrying in {:?} ({}/{})", delay, retry, self.retry.max_retries);
+                tokio::time::sleep(delay).await;
+            }
+            
+            for index in self.read_order() {
+                let endpoint = &self.endpoints[index];
+                match call(endpoint.client.clone()).await {
+                    Ok(value) => {
+                        endpoint.record_success();
+                        return Ok(value);
+                    }
+                    Err(e) if is_transient(&e) => {
+                        warn!("RPC endpoint {} failed, trying next: {}", endpoint.url, e);
+                        endpoint.record_failure();
+                        last_error = Some(e);
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+        
+        Err(last_error.unwrap_or_else(|| Error::BlockchainError("No RPC endpoints available".to_string())))
+    }
+    
+    /// Run a state-changing call on the preferred endpoint without failover
+    pub async fn send<T, F, Fut>(&self, call: F) -> Result<T>
+    where
+        F: FnOnce(C) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let endpoint = self.send_endpoint();
+        let result = call(endpoint.client.clone()).await;
+        
+        match &result {
+            Ok(_) => endpoint.record_success(),
+            Err(e) if is_transient(e) => endpoint.record_failure(),
+            Err(_) => {}
+        }
+        
+        result
+    }
+    
+    /// Get the client that sends are routed to
+    pub fn send_client(&self) -> C {
+        self.send_endpoint().client.clone()
+    }
+    
+    /// Get the health of every endpoint
+    pub fn status(&self) -> Vec<EndpointStatus> {
+        self.endpoints
+            .iter()
+            .map(|endpoint| {
+                let healthy = endpoint.is_healthy();
+                let health = endpoint.health.lock().unwrap();
+                EndpointStatus {
+                    url: endpoint.url.clone(),
+                    healthy,
+                    consecutive_failures: health.consecutive_failures,
+                    total_failures: health.total_failures,
+                }
+            })
+            .collect()
+    }
+    
+    /// First healthy endpoint in configuration order, or the primary if none are
+    fn send_endpoint(&self) -> &Endpoint<C> {
+        self.endpoints
+            .iter()
+            .find(|endpoint| endpoint.is_healthy())
+            .unwrap_or(&self.endpoints[0])
+    }
+    
+    /// Endpoint indices to try for a read: healthy ones round-robin, then the rest
+    fn read_order(&self) -> Vec<usize> {
+        let count = self.endpoints.len();
+        let start = self.next_read.fetch_add(1, Ordering::Relaxed) % count;
+        
+        let (healthy, unhealthy): (Vec<usize>, Vec<usize>) = (0..count)
+            .map(|offset| (start + offset) % count)
+            .partition(|index| self.endpoints[*index].is_healthy());
+        
+        healthy.into_iter().chain(unhealthy).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[derive(Clone)]
+    struct FakeClient {
+        name: &'static str,
+        fails: bool,
+    }
+    
+    fn pool(clients: &[(&'static str, bool)]) -> EndpointPool<FakeClient> {
+        EndpointPool::new(
+            clients
+                .iter()
+                .map(|(name, fails)| (name.to_string(), FakeClient { name, fails: *fails }))
+                .collect(),
+        )
+        .unwrap()
+    }
+    
+    async fn call(client: FakeClient) -> Result<&'static str> {
+        if client.fails {
+            Err(Error::BlockchainError(format!("{} is down", client.name)))
+        } else {
+            Ok(client.name)
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_read_fails_over_to_secondary() {
+        let pool = pool(&[("primary", true), ("secondary", false)]);
+        
+        // Whichever endpoint the round-robin starts on, the read succeeds
+        for _ in 0..4 {
+            assert_eq!(pool.read(call).await.unwrap(), "secondary");
+        }
+        
+        let status = pool.status();
+        assert!(status[0].total_failures > 0);
+        assert_eq!(status[1].total_failures, 0);
+        assert!(status[1].healthy);
+    }
+    
+    #[tokio::test]
+    async fn test_failing_endpoint_circuit_opens() {
+        let pool = pool(&[("primary", true), ("secondary", false)]);
+        
+        for _ in 0..(FAILURE_THRESHOLD * 2) {
+            pool.read(call).await.unwrap();
+        }
+        
+        assert!(!pool.status()[0].healthy);
+        
+        // Sends move to the next healthy endpoint once the primary is down
+        assert_eq!(pool.send(call).await.unwrap(), "secondary");
+    }
+    
+    #[tokio::test]
+    async fn test_reads_round_robin_and_sends_stick() {
+        let pool = pool(&[("primary", false), ("secondary", false)]);
+        
+        let first = pool.read(call).await.unwrap();
+        let second = pool.read(call).await.unwrap();
+        assert_ne!(first, second);
+        
+        for _ in 0..3 {
+            assert_eq!(pool.send(call).await.unwrap(), "primary");
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_all_endpoints_failing_returns_error() {
+        let pool = pool(&[("primary", true), ("secondary", true)]);
+        
+        assert!(pool.read(call).await.is_err());
+        assert!(EndpointPool::<FakeClient>::new(Vec::new()).is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_read_retries_with_backoff_until_an_endpoint_recovers() {
+        let retrying = pool(&[("primary", false), ("secondary", false)]).with_retry(RpcRetryConfig {
+            max_retries: 3,
+            base_delay_ms: 1,
+            max_delay_ms: 10,
+        });
+        let calls = AtomicUsize::new(0);
+        
+        // Both endpoints fail the first pass and the first retry
+        let flaky = |client: FakeClient| {
+            let attempt = calls.fetch_add(1, Ordering::SeqCst);
+            async move {
+                if attempt < 4 {
+                    Err(Error::NetworkError(format!("{} timed out", client.name)))
+                } else {
+                    Ok(client.name)
+                }
+            }
+        };
+        
+        assert!(retrying.read(flaky).await.is_ok());
+        assert_eq!(calls.load(Ordering::SeqCst), 5);
+        
+        // Without retries the same outage is returned to the caller
+        let single_pass = pool(&[("primary", false), ("secondary", false)]);
+        calls.store(0, Ordering::SeqCst);
+        assert!(single_pass.read(flaky).await.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+    
+    #[tokio::test]
+    async fn test_permanent_errors_are_not_retried() {
+        let pool = pool(&[("primary", false), ("secondary", false)]).with_retry(RpcRetryConfig {
+            max_retries: 3,
+            base_delay_ms: 1,
+            max_delay_ms: 10,
+        });
+        let calls = AtomicUsize::new(0);
+        
+        let invalid = |_client: FakeClient| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            async { Err::<(), _>(Error::ValidationError("bad address".to_string())) }
+        };
+        
+        assert!(matches!(pool.read(invalid).await, Err(Error::ValidationError(_))));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(pool.status()[0].total_failures + pool.status()[1].total_failures, 0);
+    }
+    
+    #[tokio::test]
+    async fn test_reverts_are_not_retried_or_held_against_endpoints() {
+        let pool = pool(&[("primary", false), ("secondary", false)]).with_retry(RpcRetryConfig {
+            max_retries: 3,
+            base_delay_ms: 1,
+            max_delay_ms: 10,
+        });
+        let calls = AtomicUsize::new(0);
+        
+        let reverting = |_client: FakeClient| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            async { Err::<(), _>(Error::ExecutionError("execution reverted".to_string())) }
+        };
+        
+        for _ in 0..FAILURE_THRESHOLD {
+            assert!(matches!(pool.read(reverting).await, Err(Error::ExecutionError(_))));
+            assert!(matches!(pool.send(reverting).await, Err(Error::ExecutionError(_))));
+        }
+        
+        assert_eq!(calls.load(Ordering::SeqCst), FAILURE_THRESHOLD as usize * 2);
+        for status in pool.status() {
+            assert!(status.healthy);
+            assert_eq!(status.total_failures, 0);
+        }
+    }
+    
+    #[test]
+    fn test_retry_delay_doubles_up_to_max() {
+        let config = RpcRetryConfig {
+            base_delay_ms: 250,
+            max_delay_ms: 3_000,
+            ..Default::default()
+        };
+        assert_eq!(retry_delay(&config, 1), Duration::from_millis(250));
+        assert_eq!(retry_delay(&config, 2), Duration::from_millis(500));
+        assert_eq!(retry_delay(&config, 4), Duration::from_millis(2000));
+        
+        // Later retries wait the maximum
+        assert_eq!(retry_delay(&config, 5), Duration::from_millis(3_000));
+        assert_eq!(retry_delay(&config, 100), Duration::from_millis(3_000));
+    }
+}
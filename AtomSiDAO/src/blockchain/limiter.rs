@@ -0,0 +1,130 @@
+//! Concurrency limiting for on-chain executions
+//!
+//! Bounds how many on-chain sends run in parallel so bursts of proposal and
+//! treasury executions don't flood the executor account or the RPC endpoint.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use tokio::sync::Semaphore;
+
+use crate::config::BlockchainConfig;
+
+/// Shared limiter for on-chain executions
+///
+/// Clones share the same permits, so a single limiter handed to every
+/// executing component enforces one global cap. Callers beyond the cap wait
+/// in FIFO order for a permit.
+#[derive(Debug, Clone)]
+pub struct ExecutionLimiter {
+    /// Permits for concurrent executions
+    semaphore: Arc<Semaphore>,
+    /// Number of executions currently running
+    in_flight: Arc<AtomicUsize>,
+    /// Configured concurrency cap
+    max_concurrent: usize,
+}
+
+impl ExecutionLimiter {
+    /// Create a limiter allowing `max_concurrent` executions at once
+    ///
+    /// A cap of 0 is treated as 1, i.e. fully serialized execution.
+    pub fn new(max_concurrent: usize) -> Self {
+        let max_concurrent = max_concurrent.max(1);
+        
+        Self {
+            semaphore: Arc::new(Semaphore::new(max_concurrent)),
+            in_flight: Arc::new(AtomicUsize::new(0)),
+            max_concurrent,
+        }
+    }
+    
+    /// Create a limiter from the blockchain configuration
+    pub fn from_config(config: &BlockchainConfig) -> Self {
+        Self::new(config.max_concurrent_executions)
+    }
+    
+    /// Run an on-chain execution once a permit is available
+    pub async fn run<F, T>(&self, execution: F) -> T
+    where
+        F: Future<Output = T>,
+    {
+        // The semaphore is never closed, so acquiring cannot fail
+        let _permit = self
+            .semaphore
+            .acquire()
+            .await
+            .expect("execution semaphore closed");
+        
+        let _guard = InFlightGuard::new(&self.in_flight);
+        execution.await
+    }
+    
+    /// Number of executions currently running
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+    
+    /// Configured concurrency cap
+    pub fn max_concurrent(&self) -> usize {
+        self.max_concurrent
+    }
+}
+
+/// Keeps the in-flight gauge accurate even if an execution is cancelled
+struct InFlightGuard<'a> {
+    counter: &'a AtomicUsize,
+}
+
+impl<'a> InFlightGuard<'a> {
+    fn new(counter: &'a AtomicUsize) -> Self {
+        counter.fetch_add(1, Ordering::SeqCst);
+        Self { counter }
+    }
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        self.counter.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    
+    #[tokio::test]
+    async fn test_burst_respects_concurrency_cap() {
+        let limiter = ExecutionLimiter::new(3);
+        let peak = Arc::new(AtomicUsize::new(0));
+        
+        let tasks: Vec<_> = (0..20)
+            .map(|_| {
+                let limiter = limiter.clone();
+                let peak = peak.clone();
+                tokio::spawn(async move {
+                    limiter
+                        .run(async {
+                            peak.fetch_max(limiter.in_flight(), Ordering::SeqCst);
+                            tokio::time::sleep(Duration::from_millis(10)).await;
+                        })
+                        .await
+                })
+            })
+            .collect();
+        
+        for task in tasks {
+            task.await.unwrap();
+        }
+        
+        assert_eq!(peak.load(Ordering::SeqCst), 3);
+        assert_eq!(limiter.in_flight(), 0);
+    }
+    
+    #[test]
+    fn test_zero_cap_serializes() {
+        assert_eq!(ExecutionLimiter::new(0).max_concurrent(), 1);
+    }
+}
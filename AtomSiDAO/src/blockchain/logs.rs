@@ -0,0 +1,278 @@
+//! Chunked log scanning
+//!
+//! Providers reject or time out on large `eth_getLogs` ranges ("query
+//! returned more than 10000 results"). The scanner splits a range into
+//! windows that adapt to those limits: a rejected window is halved and
+//! retried, and successful waves grow it back towards the configured maximum.
+//! Each completed wave is handed to the caller with a checkpoint, so a scan
+//! that fails part-way can resume instead of restarting.
+
+use std::future::Future;
+
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{BlockchainConfig, LogScanConfig};
+use crate::error::{Error, Result};
+
+/// Provider error fragments that mean the requested range was too large
+const RANGE_LIMIT_ERRORS: &[&str] = &[
+    "query returned more than",
+    "more than 10000 results",
+    "block range",
+    "range too large",
+    "too many results",
+    "limit exceeded",
+    "response size exceeded",
+];
+
+/// Progress of a log scan
+///
+/// Persist it alongside the logs handed to the commit callback and pass it
+/// back to [`LogScanner::scan`] to resume.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScanCheckpoint {
+    /// First block that has not been scanned yet
+    pub next_block: u64,
+    /// Window size to use for the next request
+    pub block_range: u64,
+}
+
+/// Adaptive, bounded-concurrency `eth_getLogs` scanner
+#[derive(Debug, Clone)]
+pub struct LogScanner {
+    config: LogScanConfig,
+}
+
+impl LogScanner {
+    /// Create a scanner with the given limits
+    pub fn new(config: LogScanConfig) -> Self {
+        Self { config }
+    }
+
+    /// Create a scanner from the blockchain configuration
+    pub fn from_config(config: &BlockchainConfig) -> Self {
+        Self::new(config.log_scan.clone())
+    }
+
+    /// Scan `from_block..=to_block`, or resume from a checkpoint
+    ///
+    /// Up to `max_concurrent_requests` consecutive windows are fetched at
+    /// once. Logs are committed in block order, one wave at a time, together
+    /// with the checkpoint after that wave. On a non-limit error the
+    /// completed prefix is committed before the error is returned.
+    pub async fn scan<T, F, Fut, C, CFut>(
+        &self,
+        from_block: u64,
+        to_block: u64,
+        resume: Option<ScanCheckpoint>,
+        fetch: F,
+        mut commit: C,
+    ) -> Result<ScanCheckpoint>
+    where
+        F: Fn(u64, u64) -> Fut,
+        Fut: Future<Output = Result<Vec<T>>>,
+        C: FnMut(Vec<T>, ScanCheckpoint) -> CFut,
+        CFut: Future<Output = Result<()>>,
+    {
+        let min_range = self.config.min_block_range.max(1);
+        let max_range = self.config.max_block_range.max(min_range);
+        let concurrency = self.config.max_concurrent_requests.max(1);
+
+        let mut checkpoint = resume.unwrap_or(ScanCheckpoint {
+            next_block: from_block,
+            block_range: self.config.initial_block_range,
+        });
+        checkpoint.block_range = checkpoint.block_range.clamp(min_range, max_range);
+
+        while checkpoint.next_block <= to_block {
+            let windows = plan_windows(checkpoint.next_block, to_block, checkpoint.block_range, concurrency);
+            let results = join_all(windows.iter().map(|&(start, end)| fetch(start, end))).await;
+
+            let mut logs = Vec::new();
+            let mut limited = false;
+            let mut failure = None;
+            let mut reached_end = false;
+            let started_at = checkpoint.next_block;
+
+            // Keep the completed prefix; later windows are refetched
+            for (&(_, end), result) in windows.iter().zip(results) {
+                match result {
+                    Ok(mut batch) => {
+                        logs.append(&mut batch);
+                        checkpoint.next_block = end.saturating_add(1);
+                        reached_end = end == to_block;
+                    }
+                    Err(e) if is_range_limit_error(&e) => {
+                        limited = true;
+                        break;
+                    }
+                    Err(e) => {
+                        failure = Some(e);
+                        break;
+                    }
+                }
+            }
+
+            if limited {
+                if checkpoint.block_range == min_range && checkpoint.next_block == started_at {
+                    return Err(Error::BlockchainError(format!(
+                        "Provider rejected the minimum log range of {} blocks at block {}",
+                        min_range, checkpoint.next_block
+                    )));
+                }
+                checkpoint.block_range = (checkpoint.block_range / 2).max(min_range);
+            } else if failure.is_none() {
+                checkpoint.block_range = checkpoint.block_range.saturating_mul(2).min(max_range);
+            }
+
+            if checkpoint.next_block > started_at {
+                commit(logs, checkpoint).await?;
+            }
+
+            if let Some(e) = failure {
+                return Err(e);
+            }
+
+            // `next_block` saturates when scanning up to `u64::MAX`
+            if reached_end {
+                break;
+            }
+        }
+
+        Ok(checkpoint)
+    }
+}
+
+/// Split the start of a range into up to `count` consecutive windows
+fn plan_windows(start: u64, to_block: u64, block_range: u64, count: usize) -> Vec<(u64, u64)> {
+    let mut windows = Vec::with_capacity(count);
+    let mut next = start;
+
+    while windows.len() < count && next <= to_block {
+        let end = next.saturating_add(block_range - 1).min(to_block);
+        windows.push((next, end));
+
+        if end == u64::MAX {
+            break;
+        }
+        next = end + 1;
+    }
+
+    windows
+}
+
+/// Whether an error means the requested block range was too large
+fn is_range_limit_error(error: &Error) -> bool {
+    let message = error.to_string().to_lowercase();
+    RANGE_LIMIT_ERRORS.iter().any(|fragment| message.contains(fragment))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Provider that rejects ranges wider than `limit` blocks
+    async fn fetch_limited(start: u64, end: u64, limit: u64) -> Result<Vec<u64>> {
+        if end - start + 1 > limit {
+            return Err(Error::BlockchainError(
+                "query returned more than 10000 results".to_string(),
+            ));
+        }
+        Ok((start..=end).collect())
+    }
+
+    fn scanner(initial: u64, concurrency: usize) -> LogScanner {
+        LogScanner::new(LogScanConfig {
+            initial_block_range: initial,
+            min_block_range: 1,
+            max_block_range: 10_000,
+            max_concurrent_requests: concurrency,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_backs_off_on_range_limit() {
+        let committed = Mutex::new(Vec::new());
+
+        let checkpoint = scanner(5_000, 3)
+            .scan(
+                1,
+                2_000,
+                None,
+                |start, end| fetch_limited(start, end, 300),
+                |logs, _| {
+                    committed.lock().unwrap().extend(logs);
+                    async { Ok(()) }
+                },
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(checkpoint.next_block, 2_001);
+        assert!(checkpoint.block_range <= 600);
+        assert_eq!(*committed.lock().unwrap(), (1..=2_000).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn test_resumes_from_checkpoint() {
+        let failed = Mutex::new(false);
+        let committed = Mutex::new(Vec::new());
+        let last = Mutex::new(None);
+        let scan = scanner(100, 2);
+
+        // Fail once, past the first wave
+        let result = scan
+            .scan(
+                0,
+                999,
+                None,
+                |start, end| {
+                    let fail = start >= 400 && !std::mem::replace(&mut *failed.lock().unwrap(), true);
+                    async move {
+                        if fail {
+                            Err(Error::BlockchainError("connection reset".to_string()))
+                        } else {
+                            fetch_limited(start, end, 10_000).await
+                        }
+                    }
+                },
+                |logs, checkpoint| {
+                    committed.lock().unwrap().extend(logs);
+                    *last.lock().unwrap() = Some(checkpoint);
+                    async { Ok(()) }
+                },
+            )
+            .await;
+        assert!(result.is_err());
+
+        let resume = last.lock().unwrap().unwrap();
+        assert!(resume.next_block >= 400);
+
+        scan.scan(
+            0,
+            999,
+            Some(resume),
+            |start, end| fetch_limited(start, end, 10_000),
+            |logs, _| {
+                committed.lock().unwrap().extend(logs);
+                async { Ok(()) }
+            },
+        )
+        .await
+        .unwrap();
+
+        // Nothing is scanned twice or skipped
+        assert_eq!(*committed.lock().unwrap(), (0..=999).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_at_minimum_range() {
+        let result = scanner(10, 1)
+            .scan(0, 100, None, |start, end| fetch_limited(start, end, 0), |_: Vec<u64>, _| async { Ok(()) })
+            .await;
+
+        assert!(result.is_err());
+    }
+}
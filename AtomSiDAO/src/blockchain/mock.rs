@@ -0,0 +1,475 @@
+//! In-memory blockchain for tests
+//!
+//! `MockBlockchain` implements `BlockchainInterface` without a node. Tests
+//! preload native and ERC-20 balances, make individual methods fail, and
+//! inspect the transactions and contract calls that were submitted. Sends
+//! move native balances, mine a block and produce a successful receipt, so
+//! code that waits on receipts can be exercised too.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+use ethers::prelude::LocalWallet;
+use ethers::signers::Signer;
+use ethers::utils::hash_message;
+
+use crate::blockchain::{BlockchainInterface, ChainCapabilities, Log, LogFilter, RawTransaction, TransactionData};
+use crate::error::{Error, Result};
+
+/// A contract call recorded by the mock
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContractCall {
+    /// Contract address
+    pub contract_address: String,
+    /// Method signature, e.g. `transfer(address,uint256)`
+    pub method_signature: String,
+    /// Call arguments
+    pub args: Vec<String>,
+    /// Whether the call was sent as a transaction rather than a read
+    pub sent: bool,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    block_number: u64,
+    balances: HashMap<String, u128>,
+    erc20_balances: HashMap<(String, String), u128>,
+    code_hashes: HashMap<String, String>,
+    contract_results: HashMap<String, String>,
+    logs: Vec<(u64, Log)>,
+    receipts: HashMap<String, TransactionData>,
+    sent_transactions: Vec<RawTransaction>,
+    contract_calls: Vec<ContractCall>,
+    failures: HashMap<String, String>,
+}
+
+/// In-memory `BlockchainInterface` with settable state
+///
+/// Addresses are compared case-insensitively. Reads of unknown addresses
+/// return a zero balance.
+pub struct MockBlockchain {
+    chain_id: u64,
+    capabilities: ChainCapabilities,
+    state: Mutex<MockState>,
+}
+
+impl Default for MockBlockchain {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockBlockchain {
+    /// Create an empty EVM chain with ID 1 at block 1
+    pub fn new() -> Self {
+        Self {
+            chain_id: 1,
+            capabilities: ChainCapabilities::EVM,
+            state: Mutex::new(MockState {
+                block_number: 1,
+                ..MockState::default()
+            }),
+        }
+    }
+
+    /// Use a different chain ID
+    pub fn with_chain_id(mut self, chain_id: u64) -> Self {
+        self.chain_id = chain_id;
+        self
+    }
+
+    /// Report different chain capabilities
+    pub fn with_capabilities(mut self, capabilities: ChainCapabilities) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// Set the native balance of an address, in wei
+    pub fn set_balance(&self, address: &str, amount: u128) {
+        self.state().balances.insert(key(address), amount);
+    }
+
+    /// Set an address's balance of an ERC-20 token, in base units
+    pub fn set_erc20_balance(&self, token_contract: &str, holder: &str, amount: u128) {
+        self.state()
+            .erc20_balances
+            .insert((key(token_contract), key(holder)), amount);
+    }
+
+    /// Set the code hash reported for an address
+    pub fn set_code_hash(&self, address: &str, code_hash: &str) {
+        self.state().code_hashes.insert(key(address), code_hash.to_string());
+    }
+
+    /// Set the result returned by read-only calls to a contract method
+    pub fn set_contract_result(&self, method_signature: &str, result: &str) {
+        self.state()
+            .contract_results
+            .insert(method_signature.to_string(), result.to_string());
+    }
+
+    /// Add a log emitted at a block
+    pub fn push_log(&self, block: u64, log: Log) {
+        self.state().logs.push((block, log));
+    }
+
+    /// Set the current block number
+    pub fn set_block_number(&self, block_number: u64) {
+        self.state().block_number = block_number;
+    }
+
+    /// Set the receipt status of a transaction; `None` means not yet mined
+    pub fn set_receipt_status(&self, tx_hash: &str, status: Option<bool>) {
+        let mut state = self.state();
+        let block_number = state.block_number;
+        let receipt = state.receipts.entry(tx_hash.to_string()).or_insert_with(|| TransactionData {
+            hash: tx_hash.to_string(),
+            from: String::new(),
+            to: None,
+            value: "0".to_string(),
+            gas_used: None,
+            gas_price: None,
+            block_number: None,
+            status: None,
+            data: None,
+        });
+        receipt.status = status;
+        receipt.block_number = status.map(|_| block_number);
+    }
+
+    /// Make a trait method, e.g. `"send_transaction"`, fail with a
+    /// `BlockchainError` until the failure is cleared
+    pub fn fail(&self, method: &str, message: &str) {
+        self.state().failures.insert(method.to_string(), message.to_string());
+    }
+
+    /// Let a failing method succeed again
+    pub fn clear_failure(&self, method: &str) {
+        self.state().failures.remove(method);
+    }
+
+    /// Native balance of an address, in wei
+    pub fn balance(&self, address: &str) -> u128 {
+        self.state().balances.get(&key(address)).copied().unwrap_or(0)
+    }
+
+    /// Transactions submitted with `send_transaction`, oldest first
+    pub fn sent_transactions(&self) -> Vec<RawTransaction> {
+        self.state().sent_transactions.clone()
+    }
+
+    /// Contract calls and contract transactions, oldest first
+    pub fn contract_calls(&self) -> Vec<ContractCall> {
+        self.state().contract_calls.clone()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, MockState> {
+        self.state.lock().unwrap()
+    }
+
+    /// Fail if the method was configured to fail
+    fn check(&self, method: &str) -> Result<()> {
+        match self.state().failures.get(method) {
+            Some(message) => Err(Error::BlockchainError(message.clone())),
+            None => Ok(()),
+        }
+    }
+
+    /// Mine a block for a sent transaction and return its hash
+    fn mine(state: &mut MockState, transaction: TransactionData) -> String {
+        state.block_number += 1;
+        let hash = transaction.hash.clone();
+        state.receipts.insert(
+            hash.clone(),
+            TransactionData {
+                block_number: Some(state.block_number),
+                status: Some(true),
+                ..transaction
+            },
+        );
+        hash
+    }
+
+    /// Deterministic hash of the nth submitted transaction
+    fn next_hash(state: &MockState) -> String {
+        format!("0x{:064x}", state.receipts.len() + 1)
+    }
+}
+
+fn key(address: &str) -> String {
+    address.trim().to_lowercase()
+}
+
+#[async_trait]
+impl BlockchainInterface for MockBlockchain {
+    async fn get_balance(&self, address: &str) -> Result<String> {
+        self.check("get_balance")?;
+        Ok(self.balance(address).to_string())
+    }
+
+    async fn balance_at_block(&self, address: &str, block: u64) -> Result<String> {
+        self.check("balance_at_block")?;
+
+        // Balance history is not kept; past blocks see the current balance
+        if block > self.state().block_number {
+            return Err(Error::BlockchainError(format!("Block {} has not been mined", block)));
+        }
+        Ok(self.balance(address).to_string())
+    }
+
+    async fn erc20_balance(&self, token_contract: &str, holder: &str) -> Result<String> {
+        self.check("erc20_balance")?;
+
+        let state = self.state();
+        let balance = state
+            .erc20_balances
+            .get(&(key(token_contract), key(holder)))
+            .copied()
+            .unwrap_or(0);
+        Ok(balance.to_string())
+    }
+
+    async fn get_transaction(&self, tx_hash: &str) -> Result<TransactionData> {
+        self.check("get_transaction")?;
+
+        self.state()
+            .receipts
+            .get(tx_hash)
+            .cloned()
+            .ok_or_else(|| Error::NotFoundError(format!("Transaction {} not found", tx_hash)))
+    }
+
+    async fn send_transaction(&self, transaction: &RawTransaction) -> Result<String> {
+        self.check("send_transaction")?;
+
+        let value: u128 = transaction
+            .value
+            .parse()
+            .map_err(|_| Error::ValidationError(format!("Invalid value: {}", transaction.value)))?;
+
+        let mut state = self.state();
+        let from_balance = state.balances.get(&key(&transaction.from)).copied().unwrap_or(0);
+        if from_balance < value {
+            return Err(Error::BlockchainError(format!(
+                "Insufficient funds: {} has {} wei, needs {}",
+                transaction.from, from_balance, value
+            )));
+        }
+
+        state.balances.insert(key(&transaction.from), from_balance - value);
+        *state.balances.entry(key(&transaction.to)).or_insert(0) += value;
+        state.sent_transactions.push(transaction.clone());
+
+        let hash = Self::next_hash(&state);
+        Ok(Self::mine(
+            &mut state,
+            TransactionData {
+                hash,
+                from: transaction.from.clone(),
+                to: Some(transaction.to.clone()),
+                value: transaction.value.clone(),
+                gas_used: Some(21_000),
+                gas_price: transaction.gas_price.clone(),
+                block_number: None,
+                status: None,
+                data: transaction.data.clone(),
+            },
+        ))
+    }
+
+    fn sign_message(&self, message: &str, private_key: &str) -> Result<String> {
+        self.check("sign_message")?;
+
+        let wallet = LocalWallet::from_str(private_key)
+            .map_err(|e| Error::BlockchainError(format!("Invalid private key: {}", e)))?;
+        let signature = wallet
+            .sign_hash(hash_message(message))
+            .map_err(|e| Error::BlockchainError(format!("Failed to sign message: {}", e)))?;
+
+        Ok(format!("0x{}", signature))
+    }
+
+    fn verify_signature(&self, message: &str, signature: &str, address: &str) -> Result<bool> {
+        self.check("verify_signature")?;
+
+        crate::crypto::verify_signature(address, message, signature)
+            .map_err(|e| Error::BlockchainError(format!("Invalid signature: {}", e)))
+    }
+
+    async fn call_contract(&self, contract_address: &str, method_signature: &str, args: &[String]) -> Result<String> {
+        self.check("call_contract")?;
+
+        let mut state = self.state();
+        state.contract_calls.push(ContractCall {
+            contract_address: contract_address.to_string(),
+            method_signature: method_signature.to_string(),
+            args: args.to_vec(),
+            sent: false,
+        });
+        Ok(state.contract_results.get(method_signature).cloned().unwrap_or_default())
+    }
+
+    async fn execute_contract_transaction(
+        &self,
+        contract_address: &str,
+        method_signature: &str,
+        args: &[String],
+        private_key: &str,
+    ) -> Result<String> {
+        self.check("execute_contract_transaction")?;
+
+        let from = LocalWallet::from_str(private_key)
+            .map_err(|e| Error::BlockchainError(format!("Invalid private key: {}", e)))?
+            .address();
+
+        let mut state = self.state();
+        state.contract_calls.push(ContractCall {
+            contract_address: contract_address.to_string(),
+            method_signature: method_signature.to_string(),
+            args: args.to_vec(),
+            sent: true,
+        });
+
+        let hash = Self::next_hash(&state);
+        Ok(Self::mine(
+            &mut state,
+            TransactionData {
+                hash,
+                from: format!("{:?}", from),
+                to: Some(contract_address.to_string()),
+                value: "0".to_string(),
+                gas_used: None,
+                gas_price: None,
+                block_number: None,
+                status: None,
+                data: None,
+            },
+        ))
+    }
+
+    async fn get_block_number(&self) -> Result<u64> {
+        self.check("get_block_number")?;
+        Ok(self.state().block_number)
+    }
+
+    async fn get_chain_id(&self) -> Result<u64> {
+        self.check("get_chain_id")?;
+        Ok(self.chain_id)
+    }
+
+    async fn get_code_hash(&self, address: &str) -> Result<String> {
+        self.check("get_code_hash")?;
+        Ok(self.state().code_hashes.get(&key(address)).cloned().unwrap_or_default())
+    }
+
+    async fn get_logs(&self, filter: &LogFilter) -> Result<Vec<Log>> {
+        self.check("get_logs")?;
+
+        Ok(self
+            .state()
+            .logs
+            .iter()
+            .filter(|(block, log)| {
+                (filter.from_block..=filter.to_block).contains(block)
+                    && log.address.eq_ignore_ascii_case(&filter.address)
+                    && filter.topic0.as_ref().map_or(true, |topic0| {
+                        log.topics.first().map_or(false, |topic| topic.eq_ignore_ascii_case(topic0))
+                    })
+            })
+            .map(|(block, log)| Log {
+                block_number: Some(*block),
+                ..log.clone()
+            })
+            .collect())
+    }
+
+    fn capabilities(&self) -> ChainCapabilities {
+        self.capabilities
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALICE: &str = "0x1111111111111111111111111111111111111111";
+    const BOB: &str = "0x2222222222222222222222222222222222222222";
+
+    fn transfer(from: &str, to: &str, value: u128) -> RawTransaction {
+        RawTransaction {
+            from: from.to_string(),
+            to: to.to_string(),
+            value: value.to_string(),
+            data: None,
+            gas_limit: None,
+            gas_price: None,
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+            nonce: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_moves_balances_and_is_recorded() {
+        let chain = MockBlockchain::new();
+        chain.set_balance(ALICE, 1_000);
+
+        let hash = chain.send_transaction(&transfer(ALICE, BOB, 400)).await.unwrap();
+
+        assert_eq!(chain.get_balance(ALICE).await.unwrap(), "600");
+        assert_eq!(chain.get_balance(BOB).await.unwrap(), "400");
+        assert_eq!(chain.sent_transactions().len(), 1);
+        assert_eq!(chain.get_transaction(&hash).await.unwrap().status, Some(true));
+        assert_eq!(chain.get_block_number().await.unwrap(), 2);
+
+        // Overdrafts are rejected without being recorded
+        assert!(chain.send_transaction(&transfer(ALICE, BOB, 601)).await.is_err());
+        assert_eq!(chain.sent_transactions().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_confirmation_depth() {
+        let chain = MockBlockchain::new();
+        chain.set_balance(ALICE, 1_000);
+        let hash = chain.send_transaction(&transfer(ALICE, BOB, 1)).await.unwrap();
+        let timeout = std::time::Duration::from_millis(10);
+
+        assert!(chain.wait_for_confirmation(&hash, 1, timeout).await.unwrap());
+        assert!(chain.wait_for_confirmation(&hash, 3, timeout).await.is_err());
+
+        chain.set_block_number(4);
+        assert!(chain.wait_for_confirmation(&hash, 3, timeout).await.unwrap());
+
+        // A reverted transaction confirms with a failed status
+        chain.set_receipt_status(&hash, Some(false));
+        assert!(!chain.wait_for_confirmation(&hash, 1, timeout).await.unwrap());
+        assert!(chain.wait_for_confirmation("0xunknown", 1, timeout).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_configured_failures() {
+        let chain = MockBlockchain::new();
+        chain.fail("get_block_number", "node unreachable");
+
+        assert!(matches!(chain.get_block_number().await, Err(Error::BlockchainError(_))));
+
+        chain.clear_failure("get_block_number");
+        assert_eq!(chain.get_block_number().await.unwrap(), 1);
+    }
+
+    #[test]
+    fn test_signatures_round_trip() {
+        let chain = MockBlockchain::new();
+        let key = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+        let signature = chain.sign_message("hello", key).unwrap();
+        assert!(chain
+            .verify_signature("hello", &signature, "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266")
+            .unwrap());
+        assert!(!chain
+            .verify_signature("goodbye", &signature, "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266")
+            .unwrap());
+    }
+}
@@ -4,6 +4,7 @@
 
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
 use async_trait::async_trait;
 use ethers::prelude::{Address, TransactionReceipt, H256, U256};
@@ -13,8 +14,28 @@ use crate::config::BlockchainConfig;
 use crate::crypto::CryptoError;
 use crate::error::{Error, Result};
 
+pub mod capabilities;
 pub mod ethereum;
+pub mod events;
+pub mod failover;
+pub mod limiter;
+pub mod logs;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod mock;
 pub mod providers;
+#[cfg(feature = "solana")]
+pub mod solana;
+
+pub use capabilities::{AddressKind, ChainCapabilities};
+pub use events::{ContractEvent, EventPoller};
+pub use failover::{EndpointPool, EndpointStatus};
+pub use limiter::ExecutionLimiter;
+pub use logs::{LogScanner, ScanCheckpoint};
+#[cfg(any(test, feature = "test-utils"))]
+pub use mock::MockBlockchain;
+
+/// How often `wait_for_confirmation` polls for the receipt
+const CONFIRMATION_POLL_INTERVAL: Duration = Duration::from_millis(500);
 
 /// Transaction data for blockchain transactions
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -53,6 +74,12 @@ pub trait BlockchainInterface: Send + Sync {
     /// Get the balance of an address
     async fn get_balance(&self, address: &str) -> Result<String>;
     
+    /// Get the balance of an address as of a past block
+    async fn balance_at_block(&self, address: &str, block: u64) -> Result<String>;
+    
+    /// Get an address's balance of an ERC-20 token, in the token's base units
+    async fn erc20_balance(&self, token_contract: &str, holder: &str) -> Result<String>;
+    
     /// Get transaction details
     async fn get_transaction(&self, tx_hash: &str) -> Result<TransactionData>;
     
@@ -82,6 +109,56 @@ pub trait BlockchainInterface: Send + Sync {
     
     /// Get the chain ID
     async fn get_chain_id(&self) -> Result<u64>;
+    
+    /// Get the keccak256 hash of the code deployed at an address
+    async fn get_code_hash(&self, address: &str) -> Result<String>;
+    
+    /// Get the logs matching a filter, in block order
+    async fn get_logs(&self, filter: &LogFilter) -> Result<Vec<Log>>;
+    
+    /// Wait until a transaction is `confirmations` blocks deep, counting the
+    /// block that includes it, and return its receipt status
+    ///
+    /// Polls the receipt until the depth is reached or `timeout` passes.
+    /// Lookup errors, such as a node that hasn't seen the transaction yet,
+    /// are retried until the timeout.
+    async fn wait_for_confirmation(&self, tx_hash: &str, confirmations: u64, timeout: Duration) -> Result<bool> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        let confirmations = confirmations.max(1);
+        
+        loop {
+            let progress = match self.get_transaction(tx_hash).await {
+                Ok(TransactionData {
+                    block_number: Some(mined),
+                    status: Some(status),
+                    ..
+                }) => {
+                    let depth = confirmation_depth(mined, self.get_block_number().await?);
+                    if depth >= confirmations {
+                        return Ok(status);
+                    }
+                    format!("{} of {} confirmations", depth, confirmations)
+                }
+                Ok(_) => "not mined yet".to_string(),
+                Err(e) => e.to_string(),
+            };
+            
+            if tokio::time::Instant::now() >= deadline {
+                return Err(Error::BlockchainError(format!(
+                    "Transaction {} not confirmed within {:?}: {}",
+                    tx_hash, timeout, progress
+                )));
+            }
+            tokio::time::sleep(CONFIRMATION_POLL_INTERVAL).await;
+        }
+    }
+    
+    /// Get what this adapter's chain supports
+    ///
+    /// Defaults to EVM semantics; adapters for other chains must override it.
+    fn capabilities(&self) -> ChainCapabilities {
+        ChainCapabilities::EVM
+    }
 }
 
 /// Raw transaction data
@@ -102,9 +179,17 @@ pub struct RawTransaction {
     /// Gas limit
     pub gas_limit: Option<u64>,
     
-    /// Gas price (in wei)
+    /// Legacy gas price (in wei); estimated when no fees are set
     pub gas_price: Option<String>,
     
+    /// EIP-1559 fee cap (in wei)
+    #[serde(default)]
+    pub max_fee_per_gas: Option<String>,
+    
+    /// EIP-1559 priority fee (in wei)
+    #[serde(default)]
+    pub max_priority_fee_per_gas: Option<String>,
+    
     /// Nonce
     pub nonce: Option<u64>,
 }
@@ -139,6 +224,80 @@ pub struct Log {
     
     /// Data (non-indexed fields)
     pub data: String,
+    
+    /// Block number where the log was emitted
+    #[serde(default)]
+    pub block_number: Option<u64>,
+    
+    /// Hash of the transaction that emitted the log
+    #[serde(default)]
+    pub transaction_hash: Option<String>,
+    
+    /// Position of the log in its block
+    #[serde(default)]
+    pub log_index: Option<u64>,
+}
+
+/// Selects the logs returned by `get_logs`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogFilter {
+    /// Contract address
+    pub address: String,
+    
+    /// Event signature hash (topic 0) to match, or any event when unset
+    pub topic0: Option<String>,
+    
+    /// First block, inclusive
+    pub from_block: u64,
+    
+    /// Last block, inclusive
+    pub to_block: u64,
+}
+
+impl LogFilter {
+    /// Match every log of a contract in an inclusive block range
+    pub fn new(address: &str, from_block: u64, to_block: u64) -> Self {
+        Self {
+            address: address.to_string(),
+            topic0: None,
+            from_block,
+            to_block,
+        }
+    }
+    
+    /// Only match one event, by its signature hash
+    pub fn event(mut self, topic0: &str) -> Self {
+        self.topic0 = Some(topic0.to_string());
+        self
+    }
+    
+    /// The same filter over another block range
+    pub fn blocks(&self, from_block: u64, to_block: u64) -> Self {
+        Self {
+            from_block,
+            to_block,
+            ..self.clone()
+        }
+    }
+}
+
+/// How many blocks deep a transaction mined in `mined_block` is, counting
+/// the block that includes it
+pub fn confirmation_depth(mined_block: u64, current_block: u64) -> u64 {
+    current_block.saturating_sub(mined_block) + 1
+}
+
+/// Build the ordered, de-duplicated endpoint list for a chain
+fn endpoint_urls(primary: &str, fallbacks: &[String]) -> Vec<String> {
+    let mut urls = vec![primary.to_string()];
+    
+    for url in fallbacks {
+        if !urls.contains(url) {
+            urls.push(url.clone());
+        }
+    }
+    
+    urls
 }
 
 /// Blockchain adapter for connecting to different chains
@@ -155,13 +314,16 @@ impl BlockchainAdapter {
         let mut adapters = HashMap::new();
         
         // Create adapter for the main chain
-        let adapter = ethereum::EthereumAdapter::new(&config.rpc_url)?;
+        let gas_strategy = ethereum::GasStrategy::from_config(config)?;
+        let adapter = ethereum::EthereumAdapter::with_urls(&endpoint_urls(&config.rpc_url, &config.rpc_urls), &config.retry)?
+            .with_gas_strategy(gas_strategy);
         adapters.insert(config.chain_id, Arc::new(adapter));
         
         // Create adapters for supported chains
         for (_, chain_config) in &config.supported_chains {
             if !adapters.contains_key(&chain_config.chain_id) {
-                let adapter = ethereum::EthereumAdapter::new(&chain_config.rpc_url)?;
+                let urls = endpoint_urls(&chain_config.rpc_url, &chain_config.rpc_urls);
+                let adapter = ethereum::EthereumAdapter::with_urls(&urls, &config.retry)?.with_gas_strategy(gas_strategy);
                 adapters.insert(chain_config.chain_id, Arc::new(adapter));
             }
         }
@@ -173,6 +335,21 @@ impl BlockchainAdapter {
         })
     }
     
+    /// Create an adapter whose default chain is an in-memory mock
+    ///
+    /// Lets manager tests drive on-chain paths without a node.
+    #[cfg(any(test, feature = "test-utils"))]
+    pub fn with_mock(config: &BlockchainConfig, mock: Arc<MockBlockchain>) -> Self {
+        let mut adapters: HashMap<u64, Arc<dyn BlockchainInterface>> = HashMap::new();
+        adapters.insert(config.chain_id, mock);
+        
+        Self {
+            config: config.clone(),
+            adapters,
+            default_chain_id: config.chain_id,
+        }
+    }
+    
     /// Get adapter for a specific chain
     pub fn get_adapter(&self, chain_id: u64) -> Result<Arc<dyn BlockchainInterface>> {
         self.adapters
@@ -186,13 +363,39 @@ impl BlockchainAdapter {
         self.get_adapter(self.default_chain_id)
     }
     
+    /// Get what a specific chain supports
+    ///
+    /// Higher layers should branch on this instead of assuming EVM semantics.
+    pub fn capabilities(&self, chain_id: Option<u64>) -> Result<ChainCapabilities> {
+        let chain_id = chain_id.unwrap_or(self.default_chain_id);
+        Ok(self.get_adapter(chain_id)?.capabilities())
+    }
+    
     /// Get the balance of an address on a specific chain
     pub async fn get_balance(&self, address: &str, chain_id: Option<u64>) -> Result<String> {
         let chain_id = chain_id.unwrap_or(self.default_chain_id);
         let adapter = self.get_adapter(chain_id)?;
+        adapter.capabilities().require_address(chain_id, address)?;
         adapter.get_balance(address).await
     }
     
+    /// Get the balance of an address as of a past block on a specific chain
+    pub async fn balance_at_block(&self, address: &str, block: u64, chain_id: Option<u64>) -> Result<String> {
+        let chain_id = chain_id.unwrap_or(self.default_chain_id);
+        let adapter = self.get_adapter(chain_id)?;
+        adapter.capabilities().require_address(chain_id, address)?;
+        adapter.balance_at_block(address, block).await
+    }
+    
+    /// Get an address's balance of an ERC-20 token on a specific chain
+    pub async fn erc20_balance(&self, token_contract: &str, holder: &str, chain_id: Option<u64>) -> Result<String> {
+        let chain_id = chain_id.unwrap_or(self.default_chain_id);
+        let adapter = self.get_adapter(chain_id)?;
+        adapter.capabilities().require_contract_calls(chain_id, "Reading an ERC-20 balance")?;
+        adapter.capabilities().require_address(chain_id, holder)?;
+        adapter.erc20_balance(token_contract, holder).await
+    }
+    
     /// Get transaction details from a specific chain
     pub async fn get_transaction(&self, tx_hash: &str, chain_id: Option<u64>) -> Result<TransactionData> {
         let chain_id = chain_id.unwrap_or(self.default_chain_id);
@@ -204,9 +407,23 @@ impl BlockchainAdapter {
     pub async fn send_transaction(&self, transaction: &RawTransaction, chain_id: Option<u64>) -> Result<String> {
         let chain_id = chain_id.unwrap_or(self.default_chain_id);
         let adapter = self.get_adapter(chain_id)?;
+        adapter.capabilities().require_address(chain_id, &transaction.to)?;
         adapter.send_transaction(transaction).await
     }
     
+    /// Wait for a transaction on a specific chain to reach a confirmation depth
+    pub async fn wait_for_confirmation(
+        &self,
+        tx_hash: &str,
+        confirmations: u64,
+        timeout: Duration,
+        chain_id: Option<u64>,
+    ) -> Result<bool> {
+        let chain_id = chain_id.unwrap_or(self.default_chain_id);
+        let adapter = self.get_adapter(chain_id)?;
+        adapter.wait_for_confirmation(tx_hash, confirmations, timeout).await
+    }
+    
     /// Sign a message with a private key
     pub fn sign_message(&self, message: &str, private_key: &str, chain_id: Option<u64>) -> Result<String> {
         let chain_id = chain_id.unwrap_or(self.default_chain_id);
@@ -231,6 +448,7 @@ impl BlockchainAdapter {
     ) -> Result<String> {
         let chain_id = chain_id.unwrap_or(self.default_chain_id);
         let adapter = self.get_adapter(chain_id)?;
+        adapter.capabilities().require_contract_calls(chain_id, "Calling a contract")?;
         adapter.call_contract(contract_address, method_signature, args).await
     }
     
@@ -245,6 +463,7 @@ impl BlockchainAdapter {
     ) -> Result<String> {
         let chain_id = chain_id.unwrap_or(self.default_chain_id);
         let adapter = self.get_adapter(chain_id)?;
+        adapter.capabilities().require_contract_calls(chain_id, "Executing a contract transaction")?;
         adapter
             .execute_contract_transaction(contract_address, method_signature, args, private_key)
             .await
@@ -263,4 +482,299 @@ impl BlockchainAdapter {
         let adapter = self.get_adapter(chain_id)?;
         adapter.get_chain_id().await
     }
-} 
\ No newline at end of file
+    
+    /// Get the code hash of a contract on a specific chain
+    pub async fn get_code_hash(&self, address: &str, chain_id: Option<u64>) -> Result<String> {
+        let chain_id = chain_id.unwrap_or(self.default_chain_id);
+        let adapter = self.get_adapter(chain_id)?;
+        adapter.capabilities().require_contract_calls(chain_id, "Reading contract code")?;
+        adapter.get_code_hash(address).await
+    }
+    
+    /// Scan the logs matching a filter in adaptive chunks on a specific chain
+    ///
+    /// Each completed chunk is passed to `commit` with a checkpoint that can
+    /// be passed back as `resume` after a failure.
+    pub async fn scan_logs<C, CFut>(
+        &self,
+        filter: &LogFilter,
+        resume: Option<ScanCheckpoint>,
+        chain_id: Option<u64>,
+        commit: C,
+    ) -> Result<ScanCheckpoint>
+    where
+        C: FnMut(Vec<Log>, ScanCheckpoint) -> CFut,
+        CFut: std::future::Future<Output = Result<()>>,
+    {
+        let chain_id = chain_id.unwrap_or(self.default_chain_id);
+        let adapter = self.get_adapter(chain_id)?;
+        adapter.capabilities().require_contract_calls(chain_id, "Scanning contract logs")?;
+        
+        LogScanner::from_config(&self.config)
+            .scan(
+                filter.from_block,
+                filter.to_block,
+                resume,
+                |start, end| {
+                    let adapter = adapter.clone();
+                    let window = filter.blocks(start, end);
+                    async move { adapter.get_logs(&window).await }
+                },
+                commit,
+            )
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ConfigManager;
+    
+    /// Adapter for a Solana-like chain without EVM contract support
+    struct NonEvmChain;
+    
+    #[async_trait]
+    impl BlockchainInterface for NonEvmChain {
+        async fn get_balance(&self, _address: &str) -> Result<String> {
+            Ok("1000000000".to_string())
+        }
+        
+        async fn balance_at_block(&self, _address: &str, _block: u64) -> Result<String> {
+            Ok("1000000000".to_string())
+        }
+        
+        async fn erc20_balance(&self, _token_contract: &str, _holder: &str) -> Result<String> {
+            panic!("EVM calldata must not reach a non-EVM adapter")
+        }
+        
+        async fn get_transaction(&self, _tx_hash: &str) -> Result<TransactionData> {
+            Err(Error::NotFoundError("transaction".to_string()))
+        }
+        
+        async fn send_transaction(&self, _transaction: &RawTransaction) -> Result<String> {
+            Ok("signature".to_string())
+        }
+        
+        fn sign_message(&self, _message: &str, _private_key: &str) -> Result<String> {
+            Ok(String::new())
+        }
+        
+        fn verify_signature(&self, _message: &str, _signature: &str, _address: &str) -> Result<bool> {
+            Ok(false)
+        }
+        
+        async fn call_contract(&self, _contract: &str, _method: &str, _args: &[String]) -> Result<String> {
+            panic!("EVM calldata must not reach a non-EVM adapter")
+        }
+        
+        async fn execute_contract_transaction(
+            &self,
+            _contract: &str,
+            _method: &str,
+            _args: &[String],
+            _private_key: &str,
+        ) -> Result<String> {
+            panic!("EVM calldata must not reach a non-EVM adapter")
+        }
+        
+        async fn get_block_number(&self) -> Result<u64> {
+            Ok(250_000_000)
+        }
+        
+        async fn get_chain_id(&self) -> Result<u64> {
+            Ok(101)
+        }
+        
+        async fn get_code_hash(&self, _address: &str) -> Result<String> {
+            panic!("EVM code hashes must not be requested from a non-EVM adapter")
+        }
+        
+        async fn get_logs(&self, _filter: &LogFilter) -> Result<Vec<Log>> {
+            Ok(Vec::new())
+        }
+        
+        fn capabilities(&self) -> ChainCapabilities {
+            ChainCapabilities {
+                supports_contract_calls: false,
+                supports_eip1271: false,
+                native_decimals: 9,
+                address_kind: AddressKind::Base58,
+            }
+        }
+    }
+    
+    /// EVM chain whose balances change block by block
+    struct TransferringChain {
+        /// Balances after each block, oldest first
+        history: std::sync::Mutex<Vec<(u64, HashMap<String, u64>)>>,
+    }
+    
+    impl TransferringChain {
+        fn new(block: u64, balances: &[(&str, u64)]) -> Self {
+            let balances = balances.iter().map(|&(address, amount)| (address.to_string(), amount)).collect();
+            Self {
+                history: std::sync::Mutex::new(vec![(block, balances)]),
+            }
+        }
+        
+        /// Mine a block containing a single transfer
+        fn transfer(&self, from: &str, to: &str, amount: u64) {
+            let mut history = self.history.lock().unwrap();
+            let (block, mut balances) = history.last().cloned().unwrap();
+            *balances.get_mut(from).unwrap() -= amount;
+            *balances.entry(to.to_string()).or_insert(0) += amount;
+            history.push((block + 1, balances));
+        }
+    }
+    
+    #[async_trait]
+    impl BlockchainInterface for TransferringChain {
+        async fn get_balance(&self, address: &str) -> Result<String> {
+            let block = self.get_block_number().await?;
+            self.balance_at_block(address, block).await
+        }
+        
+        async fn balance_at_block(&self, address: &str, block: u64) -> Result<String> {
+            let history = self.history.lock().unwrap();
+            let (_, balances) = history
+                .iter()
+                .rev()
+                .find(|(mined, _)| *mined <= block)
+                .ok_or_else(|| Error::BlockchainError(format!("Block {} predates the chain", block)))?;
+            Ok(balances.get(address).copied().unwrap_or(0).to_string())
+        }
+        
+        async fn erc20_balance(&self, _token_contract: &str, _holder: &str) -> Result<String> {
+            Ok("0".to_string())
+        }
+        
+        async fn get_transaction(&self, _tx_hash: &str) -> Result<TransactionData> {
+            Err(Error::NotFoundError("transaction".to_string()))
+        }
+        
+        async fn send_transaction(&self, _transaction: &RawTransaction) -> Result<String> {
+            Ok("0xhash".to_string())
+        }
+        
+        fn sign_message(&self, _message: &str, _private_key: &str) -> Result<String> {
+            Ok(String::new())
+        }
+        
+        fn verify_signature(&self, _message: &str, _signature: &str, _address: &str) -> Result<bool> {
+            Ok(false)
+        }
+        
+        async fn call_contract(&self, _contract: &str, _method: &str, _args: &[String]) -> Result<String> {
+            Ok(String::new())
+        }
+        
+        async fn execute_contract_transaction(
+            &self,
+            _contract: &str,
+            _method: &str,
+            _args: &[String],
+            _private_key: &str,
+        ) -> Result<String> {
+            Ok("0xhash".to_string())
+        }
+        
+        async fn get_block_number(&self) -> Result<u64> {
+            Ok(self.history.lock().unwrap().last().unwrap().0)
+        }
+        
+        async fn get_chain_id(&self) -> Result<u64> {
+            Ok(1)
+        }
+        
+        async fn get_code_hash(&self, _address: &str) -> Result<String> {
+            Ok(String::new())
+        }
+        
+        async fn get_logs(&self, _filter: &LogFilter) -> Result<Vec<Log>> {
+            Ok(Vec::new())
+        }
+    }
+    
+    fn non_evm_adapter() -> BlockchainAdapter {
+        let mut adapters: HashMap<u64, Arc<dyn BlockchainInterface>> = HashMap::new();
+        adapters.insert(101, Arc::new(NonEvmChain));
+        
+        BlockchainAdapter {
+            config: ConfigManager::with_defaults("blockchain_test.json").get_config().blockchain,
+            adapters,
+            default_chain_id: 101,
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_evm_only_call_on_non_evm_chain_is_not_supported() {
+        let adapter = non_evm_adapter();
+        let program = "4Nd1mBQtrMJVYVfKf2PJy9NZUZdTAsp7D4xWLs4gDB4T";
+        
+        let error = adapter
+            .call_contract(program, "balanceOf(address)", &[program.to_string()], None)
+            .await
+            .unwrap_err();
+        match error {
+            Error::NotSupported(message) => {
+                assert!(message.contains("EVM contract calls"), "{}", message);
+                assert!(message.contains("chain 101"), "{}", message);
+            }
+            other => panic!("expected NotSupported, got {:?}", other),
+        }
+        
+        assert!(matches!(
+            adapter.get_code_hash(program, None).await,
+            Err(Error::NotSupported(_))
+        ));
+        assert!(adapter.capabilities(None).unwrap().require_eip1271(101).is_err());
+        assert!(matches!(
+            adapter.erc20_balance(program, program, None).await,
+            Err(Error::NotSupported(_))
+        ));
+    }
+    
+    #[tokio::test]
+    async fn test_address_format_checked_before_call() {
+        let adapter = non_evm_adapter();
+        
+        assert!(adapter
+            .get_balance("4Nd1mBQtrMJVYVfKf2PJy9NZUZdTAsp7D4xWLs4gDB4T", None)
+            .await
+            .is_ok());
+        assert!(matches!(
+            adapter
+                .get_balance("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed", None)
+                .await,
+            Err(Error::ValidationError(_))
+        ));
+    }
+    
+    #[tokio::test]
+    async fn test_post_snapshot_transfer_does_not_change_snapshot_balance() {
+        let alice = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+        let bob = "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359";
+        let chain = Arc::new(TransferringChain::new(100, &[(alice, 500)]));
+        
+        let mut adapters: HashMap<u64, Arc<dyn BlockchainInterface>> = HashMap::new();
+        adapters.insert(1, chain.clone());
+        let adapter = BlockchainAdapter {
+            config: ConfigManager::with_defaults("blockchain_test.json").get_config().blockchain,
+            adapters,
+            default_chain_id: 1,
+        };
+        
+        // Voting starts: the snapshot is the current block
+        let snapshot = adapter.get_block_number(None).await.unwrap();
+        
+        // Alice moves her tokens to a second address after the snapshot
+        chain.transfer(alice, bob, 500);
+        assert_eq!(adapter.get_balance(bob, None).await.unwrap(), "500");
+        
+        // At the snapshot the tokens are still Alice's, so they count once
+        assert_eq!(adapter.balance_at_block(alice, snapshot, None).await.unwrap(), "500");
+        assert_eq!(adapter.balance_at_block(bob, snapshot, None).await.unwrap(), "0");
+        assert_eq!(adapter.get_balance(alice, None).await.unwrap(), "0");
+    }
+}
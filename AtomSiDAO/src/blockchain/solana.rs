@@ -0,0 +1,290 @@
+//! Solana blockchain adapter for AtomSi DAO
+//!
+//! Balances and transfers are in lamports, block numbers are slots, and
+//! keys and signatures are base58-encoded ed25519. Solana has no numeric
+//! chain ID, so one is derived from the cluster's genesis hash. Contract
+//! calls, code hashes and logs are EVM concepts and are not supported.
+
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::hash::Hash;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use solana_sdk::transaction::Transaction;
+use solana_system_interface::instruction as system_instruction;
+
+use crate::blockchain::{AddressKind, BlockchainInterface, ChainCapabilities, Log, LogFilter, RawTransaction, TransactionData};
+use crate::config::BlockchainConfig;
+use crate::error::{Error, Result};
+
+/// Capabilities of a Solana cluster
+const SOLANA_CAPABILITIES: ChainCapabilities = ChainCapabilities {
+    supports_contract_calls: false,
+    supports_eip1271: false,
+    native_decimals: 9,
+    address_kind: AddressKind::Base58,
+};
+
+/// Derive a numeric chain ID from a cluster's genesis hash
+///
+/// Uses the first eight bytes of the hash, big-endian, so every cluster
+/// (mainnet-beta, devnet, a local validator) gets a stable, distinct ID.
+pub fn chain_id_from_genesis(genesis_hash: &Hash) -> u64 {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&genesis_hash.to_bytes()[..8]);
+    u64::from_be_bytes(bytes)
+}
+
+/// Solana blockchain adapter
+pub struct SolanaAdapter {
+    client: RpcClient,
+    chain_id: u64,
+}
+
+impl SolanaAdapter {
+    /// Connect to the cluster at `config.rpc_url`
+    ///
+    /// Fetches the genesis hash to derive the chain ID.
+    pub async fn new(config: &BlockchainConfig) -> Result<Self> {
+        let client = RpcClient::new(config.rpc_url.clone());
+
+        let genesis_hash = client
+            .get_genesis_hash()
+            .await
+            .map_err(|e| Error::BlockchainError(format!("Failed to connect to Solana node: {}", e)))?;
+
+        Ok(Self::from_parts(client, chain_id_from_genesis(&genesis_hash)))
+    }
+
+    /// Create an adapter from a client and a known chain ID
+    pub fn from_parts(client: RpcClient, chain_id: u64) -> Self {
+        Self { client, chain_id }
+    }
+
+    /// Parse a base58 public key
+    fn parse_pubkey(address: &str) -> Result<Pubkey> {
+        Pubkey::from_str(address).map_err(|e| Error::ValidationError(format!("Invalid Solana address: {}", e)))
+    }
+
+    /// Parse a base58-encoded 64-byte keypair
+    fn parse_keypair(private_key: &str) -> Result<Keypair> {
+        let bytes = bs58::decode(private_key.trim())
+            .into_vec()
+            .map_err(|e| Error::BlockchainError(format!("Invalid private key: {}", e)))?;
+
+        Keypair::from_bytes(&bytes).map_err(|e| Error::BlockchainError(format!("Invalid private key: {}", e)))
+    }
+
+    fn not_supported(operation: &str) -> Error {
+        Error::NotSupported(format!("{} is not supported on Solana", operation))
+    }
+}
+
+#[async_trait]
+impl BlockchainInterface for SolanaAdapter {
+    async fn get_balance(&self, address: &str) -> Result<String> {
+        let pubkey = Self::parse_pubkey(address)?;
+
+        let lamports = self
+            .client
+            .get_balance(&pubkey)
+            .await
+            .map_err(|e| Error::BlockchainError(format!("Failed to get balance: {}", e)))?;
+
+        Ok(lamports.to_string())
+    }
+
+    async fn balance_at_block(&self, _address: &str, _block: u64) -> Result<String> {
+        // The RPC API only serves current balances
+        Err(Self::not_supported("Reading balances at a past slot"))
+    }
+
+    async fn erc20_balance(&self, _token_contract: &str, _holder: &str) -> Result<String> {
+        Err(Self::not_supported("ERC-20 balances"))
+    }
+
+    async fn get_transaction(&self, tx_hash: &str) -> Result<TransactionData> {
+        let signature = Signature::from_str(tx_hash)
+            .map_err(|e| Error::ValidationError(format!("Invalid transaction signature: {}", e)))?;
+
+        let status = self
+            .client
+            .get_signature_statuses_with_history(&[signature])
+            .await
+            .map_err(|e| Error::BlockchainError(format!("Failed to get transaction: {}", e)))?
+            .value
+            .into_iter()
+            .next()
+            .flatten()
+            .ok_or_else(|| Error::BlockchainError(format!("Transaction not found: {}", tx_hash)))?;
+
+        Ok(TransactionData {
+            hash: tx_hash.to_string(),
+            from: String::new(),
+            to: None,
+            value: "0".to_string(),
+            gas_used: None,
+            gas_price: None,
+            block_number: Some(status.slot),
+            status: Some(status.err.is_none()),
+            data: None,
+        })
+    }
+
+    async fn send_transaction(&self, transaction: &RawTransaction) -> Result<String> {
+        // As with the Ethereum adapter, `from` carries the sender's private key
+        let payer = Self::parse_keypair(&transaction.from)?;
+        let to = Self::parse_pubkey(&transaction.to)?;
+        let lamports: u64 = transaction
+            .value
+            .parse()
+            .map_err(|e| Error::ValidationError(format!("Invalid lamport amount: {}", e)))?;
+
+        let blockhash = self
+            .client
+            .get_latest_blockhash()
+            .await
+            .map_err(|e| Error::BlockchainError(format!("Failed to get recent blockhash: {}", e)))?;
+
+        let transfer = system_instruction::transfer(&payer.pubkey(), &to, lamports);
+        let signed = Transaction::new_signed_with_payer(&[transfer], Some(&payer.pubkey()), &[&payer], blockhash);
+
+        let signature = self
+            .client
+            .send_transaction(&signed)
+            .await
+            .map_err(|e| Error::BlockchainError(format!("Failed to send transaction: {}", e)))?;
+
+        Ok(signature.to_string())
+    }
+
+    fn sign_message(&self, message: &str, private_key: &str) -> Result<String> {
+        let keypair = Self::parse_keypair(private_key)?;
+        Ok(keypair.sign_message(message.as_bytes()).to_string())
+    }
+
+    fn verify_signature(&self, message: &str, signature: &str, address: &str) -> Result<bool> {
+        let signature = Signature::from_str(signature)
+            .map_err(|e| Error::BlockchainError(format!("Invalid signature: {}", e)))?;
+        let pubkey = Self::parse_pubkey(address)?;
+
+        Ok(signature.verify(pubkey.as_ref(), message.as_bytes()))
+    }
+
+    async fn call_contract(&self, _contract_address: &str, _method_signature: &str, _args: &[String]) -> Result<String> {
+        Err(Self::not_supported("EVM contract calls"))
+    }
+
+    async fn execute_contract_transaction(
+        &self,
+        _contract_address: &str,
+        _method_signature: &str,
+        _args: &[String],
+        _private_key: &str,
+    ) -> Result<String> {
+        Err(Self::not_supported("EVM contract calls"))
+    }
+
+    async fn get_block_number(&self) -> Result<u64> {
+        self.client
+            .get_slot()
+            .await
+            .map_err(|e| Error::BlockchainError(format!("Failed to get slot: {}", e)))
+    }
+
+    async fn get_chain_id(&self) -> Result<u64> {
+        Ok(self.chain_id)
+    }
+
+    async fn get_code_hash(&self, _address: &str) -> Result<String> {
+        Err(Self::not_supported("Contract code hashes"))
+    }
+
+    async fn get_logs(&self, _filter: &LogFilter) -> Result<Vec<Log>> {
+        Err(Self::not_supported("Contract logs"))
+    }
+
+    fn capabilities(&self) -> ChainCapabilities {
+        SOLANA_CAPABILITIES
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn offline_adapter() -> SolanaAdapter {
+        SolanaAdapter::from_parts(RpcClient::new("http://127.0.0.1:8899".to_string()), 1)
+    }
+
+    #[test]
+    fn test_sign_and_verify_message() {
+        let adapter = offline_adapter();
+        let keypair = Keypair::new();
+        let private_key = keypair.to_base58_string();
+        let address = keypair.pubkey().to_string();
+
+        let signature = adapter.sign_message("Hello, AtomSi DAO!", &private_key).unwrap();
+        assert!(adapter.verify_signature("Hello, AtomSi DAO!", &signature, &address).unwrap());
+        assert!(!adapter.verify_signature("Goodbye", &signature, &address).unwrap());
+        assert!(!adapter
+            .verify_signature("Hello, AtomSi DAO!", &signature, &Keypair::new().pubkey().to_string())
+            .unwrap());
+
+        assert!(adapter.sign_message("Hello", "not-a-key").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_evm_operations_not_supported() {
+        let adapter = offline_adapter();
+
+        assert!(matches!(
+            adapter.call_contract("11111111111111111111111111111111", "balanceOf(address)", &[]).await,
+            Err(Error::NotSupported(_))
+        ));
+        assert!(!adapter.capabilities().supports_contract_calls);
+        assert!(adapter.capabilities().address_kind.is_valid(&Keypair::new().pubkey().to_string()));
+    }
+
+    #[test]
+    fn test_chain_id_from_genesis_hash() {
+        // mainnet-beta
+        let mainnet = Hash::from_str("5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d").unwrap();
+        // devnet
+        let devnet = Hash::from_str("EtWTRABZaYq6iMfeYKouRu166VU2xqa1wcaWoxPkrZBG").unwrap();
+
+        assert_eq!(chain_id_from_genesis(&mainnet), chain_id_from_genesis(&mainnet));
+        assert_ne!(chain_id_from_genesis(&mainnet), chain_id_from_genesis(&devnet));
+    }
+
+    /// Runs against the validator in `ATOMSI_TEST_SOLANA_URL`, e.g. a local
+    /// `solana-test-validator` at http://127.0.0.1:8899, and is skipped when
+    /// it is not set
+    #[tokio::test]
+    async fn test_local_validator() {
+        let Ok(url) = std::env::var("ATOMSI_TEST_SOLANA_URL") else {
+            return;
+        };
+        let mut config = crate::config::default().blockchain;
+        config.rpc_url = url;
+        let adapter = SolanaAdapter::new(&config).await.unwrap();
+
+        let keypair = Keypair::new();
+        let address = keypair.pubkey().to_string();
+        assert_eq!(adapter.get_balance(&address).await.unwrap(), "0");
+
+        // Fund the account from the faucet and wait for it to land
+        let airdrop = adapter.client.request_airdrop(&keypair.pubkey(), 1_000_000_000).await.unwrap().to_string();
+        assert!(adapter
+            .wait_for_confirmation(&airdrop, 1, std::time::Duration::from_secs(30))
+            .await
+            .unwrap());
+        assert_eq!(adapter.get_balance(&address).await.unwrap(), "1000000000");
+
+        assert!(adapter.get_block_number().await.unwrap() > 0);
+        let genesis_hash = adapter.client.get_genesis_hash().await.unwrap();
+        assert_eq!(adapter.get_chain_id().await.unwrap(), chain_id_from_genesis(&genesis_hash));
+    }
+}
@@ -8,8 +8,15 @@ use std::fs::File;
 use std::io::{Read, Write};
 use std::path::Path;
 use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use notify::{RecursiveMode, Watcher};
+use tokio::sync::{mpsc, watch};
+use tracing::{info, warn};
 
 use crate::error::{Error, Result};
+use crate::identity::MemberRole;
+use crate::security::AdminAction;
 
 /// Configuration settings for the AtomSi DAO
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -29,11 +36,249 @@ pub struct Config {
     /// Security configuration
     pub security: SecurityConfig,
     
+    /// Governance configuration
+    #[serde(default)]
+    pub governance: GovernanceConfig,
+    
+    /// Identity and reputation configuration
+    #[serde(default)]
+    pub identity: IdentityConfig,
+    
+    /// Treasury configuration
+    #[serde(default)]
+    pub treasury: TreasuryConfig,
+    
+    /// Price oracle configuration
+    #[serde(default)]
+    pub oracle: OracleConfig,
+    
+    /// Optional feature modules
+    #[serde(default)]
+    pub features: FeaturesConfig,
+    
+    /// Log output configuration
+    #[serde(default)]
+    pub logging: LoggingConfig,
+    
     /// Custom configuration values
     #[serde(default)]
     pub custom: HashMap<String, serde_json::Value>,
 }
 
+impl Config {
+    /// Check the configuration for values that would fail at runtime
+    ///
+    /// All problems are reported together in a single error.
+    pub fn validate(&self) -> Result<()> {
+        let mut problems = Vec::new();
+        
+        if self.dao.name.trim().is_empty() {
+            problems.push("dao.name must not be empty".to_string());
+        }
+        
+        if let Some(website_url) = &self.dao.website_url {
+            check_url(&mut problems, "dao.website_url", website_url, WEB_SCHEMES);
+        }
+        
+        match self.database.db_type.as_str() {
+            "sqlite" if self.database.sqlite_path.as_deref().map_or(true, |path| path.trim().is_empty()) => {
+                problems.push("database.sqlite_path is required for sqlite".to_string());
+            }
+            "sqlite" => {}
+            "postgres" => {
+                if self.database.host.trim().is_empty() || self.database.name.trim().is_empty() {
+                    problems.push("database.host and database.name are required for postgres".to_string());
+                }
+                if self.database.port == 0 {
+                    problems.push("database.port must not be 0 for postgres".to_string());
+                }
+            }
+            other => problems.push(format!("database.db_type '{}' is not supported", other)),
+        }
+        
+        if self.database.pool_size == 0 {
+            problems.push("database.pool_size must be at least 1".to_string());
+        }
+        
+        if let Some(replica) = &self.database.read_replica {
+            check_url(&mut problems, "database.read_replica.url", &replica.url, &["postgres", "postgresql"]);
+        }
+        
+        if self.blockchain.rpc_url.trim().is_empty() {
+            problems.push("blockchain.rpc_url must not be empty".to_string());
+        } else {
+            check_url(&mut problems, "blockchain.rpc_url", &self.blockchain.rpc_url, RPC_SCHEMES);
+        }
+        for url in &self.blockchain.rpc_urls {
+            check_url(&mut problems, "blockchain.rpc_urls", url, RPC_SCHEMES);
+        }
+        if !self.blockchain.explorer_url.trim().is_empty() {
+            check_url(&mut problems, "blockchain.explorer_url", &self.blockchain.explorer_url, WEB_SCHEMES);
+        }
+        
+        let mut chain_names: Vec<&String> = self.blockchain.supported_chains.keys().collect();
+        chain_names.sort();
+        for name in chain_names {
+            let chain = &self.blockchain.supported_chains[name];
+            let field = format!("blockchain.supported_chains.{}.rpc_url", name);
+            if chain.rpc_url.trim().is_empty() {
+                problems.push(format!("{} must not be empty", field));
+            } else {
+                check_url(&mut problems, &field, &chain.rpc_url, RPC_SCHEMES);
+            }
+            for url in &chain.rpc_urls {
+                check_url(&mut problems, &format!("blockchain.supported_chains.{}.rpc_urls", name), url, RPC_SCHEMES);
+            }
+        }
+        
+        match self.blockchain.gas_price_strategy.as_str() {
+            "static" if self.blockchain.static_gas_price_gwei.is_none() => {
+                problems.push("blockchain.static_gas_price_gwei is required for the static gas_price_strategy".to_string())
+            }
+            "static" | "standard" | "fast" => {}
+            other => problems.push(format!("blockchain.gas_price_strategy '{}' is not supported", other)),
+        }
+        
+        let log_scan = &self.blockchain.log_scan;
+        if log_scan.min_block_range > log_scan.max_block_range {
+            problems.push("blockchain.log_scan.min_block_range exceeds max_block_range".to_string());
+        }
+        
+        for (key, value) in [
+            ("quorum_percentage", self.governance.quorum_percentage),
+            ("majority_percentage", self.governance.majority_percentage),
+            ("veto_threshold_percent", self.governance.veto_threshold_percent),
+        ] {
+            if value > 100 {
+                problems.push(format!("governance.{} must be at most 100", key));
+            }
+        }
+        
+        if let Some(strategy) = &self.governance.voting_strategy {
+            if !crate::governance::VOTING_STRATEGIES.contains(&strategy.as_str()) {
+                problems.push(format!(
+                    "governance.voting_strategy must be one of {}",
+                    crate::governance::VOTING_STRATEGIES.join(", ")
+                ));
+            }
+        }
+        
+        let weighting = &self.governance.reputation_weighting;
+        let growth = match weighting.curve {
+            ReputationCurve::Linear { slope } => slope,
+            ReputationCurve::Logarithmic { scale } => scale,
+        };
+        if !growth.is_finite() || growth < 0.0 {
+            problems.push("governance.reputation_weighting.curve must not shrink the multiplier".to_string());
+        }
+        if !weighting.max_multiplier.is_finite() || weighting.max_multiplier < 1.0 {
+            problems.push("governance.reputation_weighting.max_multiplier must be at least 1".to_string());
+        }
+        
+        let conviction = &self.governance.conviction;
+        if conviction.period_seconds == 0 {
+            problems.push("governance.conviction.period_seconds must be at least 1".to_string());
+        }
+        if !(conviction.decay > 0.0 && conviction.decay < 1.0) {
+            problems.push("governance.conviction.decay must be between 0 and 1".to_string());
+        }
+        if !(conviction.max_ratio > 0.0 && conviction.max_ratio <= 1.0) {
+            problems.push("governance.conviction.max_ratio must be above 0 and at most 1".to_string());
+        }
+        if !conviction.weight.is_finite() || conviction.weight <= 0.0 {
+            problems.push("governance.conviction.weight must be positive".to_string());
+        }
+        if self.governance.quadratic.epoch_days == 0 {
+            problems.push("governance.quadratic.epoch_days must be at least 1".to_string());
+        }
+        
+        let gateway_url = &self.governance.attachments.ipfs_gateway_url;
+        if !gateway_url.trim().is_empty() {
+            check_url(&mut problems, "governance.attachments.ipfs_gateway_url", gateway_url, WEB_SCHEMES);
+        }
+        
+        let jwt_secret = self.security.jwt_secret.trim();
+        if jwt_secret == DEFAULT_JWT_SECRET || jwt_secret.len() < MIN_JWT_SECRET_BYTES {
+            problems.push(format!(
+                "security.jwt_secret must be set to a random secret of at least {} bytes",
+                MIN_JWT_SECRET_BYTES
+            ));
+        }
+        if self.security.min_password_length == 0 {
+            problems.push("security.min_password_length must be at least 1".to_string());
+        }
+        
+        if self.treasury.rebalance_tolerance_percent > 100 {
+            problems.push("treasury.rebalance_tolerance_percent must be at most 100".to_string());
+        }
+        
+        // Without signers the treasury refuses to operate; the threshold is
+        // checked once signers are configured
+        let signer_count = self.treasury.signers.len();
+        if self.treasury.threshold == 0 || (signer_count > 0 && self.treasury.threshold as usize > signer_count) {
+            problems.push("treasury.threshold must be between 1 and the number of treasury.signers".to_string());
+        }
+        
+        if self.treasury.transaction_expiry_hours == 0 {
+            problems.push("treasury.transaction_expiry_hours must be at least 1".to_string());
+        }
+        
+        if self.treasury.confirmations > 0 && self.treasury.confirmation_timeout_seconds == 0 {
+            problems.push("treasury.confirmation_timeout_seconds must be at least 1 when confirmations are awaited".to_string());
+        }
+        
+        for (symbol, limits) in &self.treasury.limits {
+            if let (Some(per_tx_limit), Some(daily_limit)) = (limits.per_tx_limit, limits.daily_limit) {
+                if per_tx_limit > daily_limit {
+                    problems.push(format!(
+                        "treasury.limits.{}.per_tx_limit exceeds its daily_limit",
+                        symbol
+                    ));
+                }
+            }
+        }
+        
+        let audit_shipping = &self.security.audit_shipping;
+        if audit_shipping.enabled
+            && (audit_shipping.endpoint_url.trim().is_empty() || audit_shipping.hmac_secret.is_empty())
+        {
+            problems.push("security.audit_shipping requires endpoint_url and hmac_secret when enabled".to_string());
+        } else if audit_shipping.enabled {
+            check_url(&mut problems, "security.audit_shipping.endpoint_url", &audit_shipping.endpoint_url, WEB_SCHEMES);
+        }
+        
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::ConfigError(problems.join("; ")))
+        }
+    }
+}
+
+/// Placeholder JWT secret written to new configurations, refused by
+/// [`Config::validate`] so it can't reach production
+const DEFAULT_JWT_SECRET: &str = "change_this_to_a_secure_random_string";
+
+/// Shortest JWT secret accepted, matching the HS256 key size
+const MIN_JWT_SECRET_BYTES: usize = 32;
+
+/// URL schemes accepted for RPC endpoints
+const RPC_SCHEMES: &[&str] = &["http", "https", "ws", "wss"];
+
+/// URL schemes accepted for web URLs
+const WEB_SCHEMES: &[&str] = &["http", "https"];
+
+/// Record a problem unless `url` parses with one of `schemes` and has a host
+fn check_url(problems: &mut Vec<String>, field: &str, url: &str, schemes: &[&str]) {
+    let well_formed = reqwest::Url::parse(url.trim()).map_or(false, |parsed| {
+        schemes.contains(&parsed.scheme()) && parsed.host_str().map_or(false, |host| !host.is_empty())
+    });
+    
+    if !well_formed {
+        problems.push(format!("{} '{}' is not a valid {} URL", field, url, schemes.join("/")));
+    }
+}
+
 /// Configuration for the DAO itself
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DaoConfig {
@@ -60,6 +305,21 @@ pub struct DaoConfig {
     
     /// List of admin addresses
     pub admin_addresses: Vec<String>,
+    
+    /// Deployment environment (development, staging, production)
+    #[serde(default = "default_environment")]
+    pub environment: String,
+}
+
+fn default_environment() -> String {
+    "development".to_string()
+}
+
+impl DaoConfig {
+    /// Whether this configuration targets a production deployment
+    pub fn is_production(&self) -> bool {
+        self.environment.eq_ignore_ascii_case("production")
+    }
 }
 
 /// Configuration for database connections
@@ -88,6 +348,21 @@ pub struct DatabaseConfig {
     
     /// SQLite file path (only used when db_type is sqlite)
     pub sqlite_path: Option<String>,
+    
+    /// Optional read-only replica used for read queries
+    #[serde(default)]
+    pub read_replica: Option<ReadReplicaConfig>,
+}
+
+/// Configuration for a read-only database replica
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadReplicaConfig {
+    /// Connection URL of the replica
+    pub url: String,
+    
+    /// Maximum number of replica connections (defaults to the primary pool size)
+    #[serde(default)]
+    pub max_connections: Option<u32>,
 }
 
 /// Configuration for blockchain connections
@@ -96,6 +371,10 @@ pub struct BlockchainConfig {
     /// Default RPC URL
     pub rpc_url: String,
     
+    /// Fallback RPC URLs, tried in order when the default endpoint fails
+    #[serde(default)]
+    pub rpc_urls: Vec<String>,
+    
     /// Chain ID
     pub chain_id: u64,
     
@@ -110,6 +389,81 @@ pub struct BlockchainConfig {
     
     /// List of supported chain configurations
     pub supported_chains: HashMap<String, ChainConfig>,
+    
+    /// Maximum number of on-chain executions sent in parallel
+    #[serde(default = "default_max_concurrent_executions")]
+    pub max_concurrent_executions: usize,
+    
+    /// Chunking and concurrency limits for `eth_getLogs` scans
+    #[serde(default)]
+    pub log_scan: LogScanConfig,
+    
+    /// Retry policy for RPC reads
+    #[serde(default)]
+    pub retry: RpcRetryConfig,
+}
+
+fn default_max_concurrent_executions() -> usize {
+    4
+}
+
+/// Configuration for chunked log scans
+///
+/// Windows shrink when a provider rejects a range and grow back on success,
+/// staying within the minimum and maximum.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogScanConfig {
+    /// Blocks per request when a scan starts
+    pub initial_block_range: u64,
+    
+    /// Smallest window before a scan gives up
+    pub min_block_range: u64,
+    
+    /// Largest window a scan grows to
+    pub max_block_range: u64,
+    
+    /// Maximum number of log requests in flight
+    pub max_concurrent_requests: usize,
+}
+
+impl Default for LogScanConfig {
+    fn default() -> Self {
+        Self {
+            initial_block_range: 2000,
+            min_block_range: 10,
+            max_block_range: 10000,
+            max_concurrent_requests: 4,
+        }
+    }
+}
+
+/// Configuration for retrying RPC reads
+///
+/// A read fails over across every endpoint first; only when all of them
+/// fail with a transient error is it retried, after a delay that doubles
+/// with each retry up to a maximum.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RpcRetryConfig {
+    /// Retries after the first pass over the endpoints
+    pub max_retries: u32,
+    
+    /// Delay before the first retry in milliseconds
+    pub base_delay_ms: u64,
+    
+    /// Longest delay between retries in milliseconds
+    pub max_delay_ms: u64,
+}
+
+impl Default for RpcRetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay_ms: 250,
+            max_delay_ms: 5_000,
+        }
+    }
 }
 
 /// Configuration for a specific blockchain
@@ -124,143 +478,1202 @@ pub struct ChainConfig {
     /// RPC URL
     pub rpc_url: String,
     
+    /// Fallback RPC URLs, tried in order when the RPC URL fails
+    #[serde(default)]
+    pub rpc_urls: Vec<String>,
+    
     /// Explorer URL
     pub explorer_url: String,
     
-    /// Native currency symbol
-    pub currency_symbol: String,
+    /// Native currency symbol
+    pub currency_symbol: String,
+    
+    /// Block time in seconds
+    pub block_time_seconds: u64,
+    
+    /// Contract addresses for this chain
+    pub contract_addresses: HashMap<String, String>,
+}
+
+/// Configuration for the API server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiConfig {
+    /// Host to bind the API server to
+    pub host: String,
+    
+    /// Port to bind the API server to
+    pub port: u16,
+    
+    /// Base URL for the API
+    pub base_url: String,
+    
+    /// Enable CORS
+    pub enable_cors: bool,
+    
+    /// CORS allowed origins
+    pub cors_allowed_origins: Vec<String>,
+    
+    /// Enable API key authentication
+    pub enable_api_key_auth: bool,
+    
+    /// API key (only used when enable_api_key_auth is true)
+    pub api_key: Option<String>,
+    
+    /// Enable rate limiting
+    pub enable_rate_limiting: bool,
+    
+    /// Rate limit requests per minute
+    pub rate_limit_per_minute: Option<u32>,
+    
+    /// IP allow/deny lists for sensitive route groups
+    #[serde(default)]
+    pub ip_filter: Option<IpFilterConfig>,
+    
+    /// Maximum request body size in bytes
+    #[serde(default = "default_max_body_bytes")]
+    pub max_body_bytes: usize,
+    
+    /// Maximum nesting depth of JSON request bodies
+    #[serde(default = "default_max_json_depth")]
+    pub max_json_depth: usize,
+    
+    /// Maximum number of values (objects, arrays and scalars) in a JSON request body
+    #[serde(default = "default_max_json_nodes")]
+    pub max_json_nodes: usize,
+    
+    /// Close WebSocket connections with no inbound traffic (including pongs)
+    /// for this many seconds (0 disables reaping)
+    #[serde(default = "default_websocket_idle_timeout_seconds")]
+    pub websocket_idle_timeout_seconds: u64,
+    
+    /// Interval between pings sent to WebSocket clients, which keeps live
+    /// connections active; should be well below the idle timeout
+    #[serde(default = "default_websocket_ping_interval_seconds")]
+    pub websocket_ping_interval_seconds: u64,
+    
+    /// ETag and `Cache-Control` settings for GET routes
+    #[serde(default)]
+    pub http_cache: HttpCacheConfig,
+}
+
+fn default_max_body_bytes() -> usize {
+    1024 * 1024 // 1 MiB
+}
+
+fn default_max_json_depth() -> usize {
+    32
+}
+
+fn default_max_json_nodes() -> usize {
+    10_000
+}
+
+fn default_websocket_idle_timeout_seconds() -> u64 {
+    90
+}
+
+fn default_websocket_ping_interval_seconds() -> u64 {
+    30
+}
+
+/// Configuration for restricting sensitive API routes by source IP
+///
+/// Entries are CIDR blocks (`10.0.0.0/8`, `2001:db8::/32`) or bare addresses.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IpFilterConfig {
+    /// Allowed source networks; an empty list allows every address not denied
+    #[serde(default)]
+    pub allow: Vec<String>,
+    
+    /// Denied source networks; deny entries take precedence over allow entries
+    #[serde(default)]
+    pub deny: Vec<String>,
+    
+    /// Proxies whose `X-Forwarded-For` header is trusted
+    #[serde(default)]
+    pub trusted_proxies: Vec<String>,
+}
+
+/// Configuration for HTTP caching of GET routes
+///
+/// Responses carry a strong ETag; a matching `If-None-Match` gets 304.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpCacheConfig {
+    /// Add ETags and `Cache-Control` to GET responses
+    pub enabled: bool,
+    
+    /// `Cache-Control` max-age for governance routes (proposals change while voting is open)
+    pub governance_max_age_seconds: u64,
+    
+    /// `Cache-Control` max-age for token routes
+    pub token_max_age_seconds: u64,
+    
+    /// `Cache-Control` max-age for treasury routes (approvals land at any time)
+    pub treasury_max_age_seconds: u64,
+    
+    /// `Cache-Control` max-age for identity routes
+    pub identity_max_age_seconds: u64,
+}
+
+impl Default for HttpCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            governance_max_age_seconds: 0,
+            token_max_age_seconds: 300,
+            treasury_max_age_seconds: 0,
+            identity_max_age_seconds: 60,
+        }
+    }
+}
+
+/// Configuration for security settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecurityConfig {
+    /// Secret key for signing JWTs
+    pub jwt_secret: String,
+    
+    /// JWT expiration time in seconds
+    pub jwt_expiration_seconds: u64,
+    
+    /// Session timeout in seconds
+    pub session_timeout_seconds: u64,
+    
+    /// Enable two-factor authentication
+    pub enable_2fa: bool,
+    
+    /// Minimum password length
+    pub min_password_length: u8,
+    
+    /// Number of required password character classes (lowercase, uppercase, numbers, symbols)
+    pub required_password_character_classes: u8,
+    
+    /// Maximum login attempts before lockout
+    pub max_login_attempts: u8,
+    
+    /// Login lockout duration in seconds
+    pub login_lockout_seconds: u64,
+    
+    /// Maximum age in seconds of the issued-at timestamp in signed messages
+    #[serde(default = "default_signature_max_age_seconds")]
+    pub signature_max_age_seconds: u64,
+    
+    /// Shipping of the admin action log to an external collector
+    #[serde(default)]
+    pub audit_shipping: AuditShippingConfig,
+}
+
+fn default_signature_max_age_seconds() -> u64 {
+    300
+}
+
+/// Configuration for shipping admin action log entries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AuditShippingConfig {
+    /// Ship entries to `endpoint_url`
+    pub enabled: bool,
+    
+    /// Collector endpoint entries are POSTed to
+    pub endpoint_url: String,
+    
+    /// Key for the HMAC-SHA256 signature sent with each entry
+    pub hmac_secret: String,
+    
+    /// Actions whose entries are shipped
+    pub actions: Vec<AdminAction>,
+    
+    /// Seconds between shipping rounds
+    pub ship_interval_seconds: u64,
+    
+    /// Maximum entries shipped per round
+    pub max_entries_per_interval: usize,
+    
+    /// Maximum unshipped entries kept; the oldest are dropped beyond this
+    pub buffer_size: usize,
+    
+    /// Timeout for each request to the collector
+    pub request_timeout_seconds: u64,
+}
+
+impl Default for AuditShippingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            endpoint_url: String::new(),
+            hmac_secret: String::new(),
+            actions: vec![
+                AdminAction::Pause,
+                AdminAction::Unpause,
+                AdminAction::RoleChange,
+                AdminAction::PermissionGrant,
+                AdminAction::PermissionRevoke,
+                AdminAction::SecretRotation,
+                AdminAction::Veto,
+            ],
+            ship_interval_seconds: 5,
+            max_entries_per_interval: 100,
+            buffer_size: 10_000,
+            request_timeout_seconds: 10,
+        }
+    }
+}
+
+/// Configuration for proposal governance rules
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GovernanceConfig {
+    /// Minimum token balance required to submit a proposal
+    pub proposal_threshold: u64,
+    
+    /// Length of the voting period in days
+    pub voting_period_days: u32,
+    
+    /// Percentage of the eligible supply that must vote for quorum
+    pub quorum_percentage: u8,
+    
+    /// Which votes count toward quorum and what supply it is measured against
+    pub quorum_basis: QuorumBasis,
+    
+    /// Supply that quorum and veto thresholds are measured against
+    pub quorum_supply_source: QuorumSupplySource,
+    
+    /// Addresses whose governance token balance is not circulating, such as
+    /// the treasury and staking or vesting contracts
+    pub quorum_excluded_addresses: Vec<String>,
+    
+    /// Percentage of yes votes required to pass
+    pub majority_percentage: u8,
+    
+    /// How the winner of a multiple-choice proposal is picked
+    pub multi_choice_tally: MultiChoiceTally,
+    
+    /// Number of distinct sponsors required before voting can start (0 disables sponsorship)
+    pub required_sponsors: u32,
+    
+    /// Minimum voting weight a member needs to sponsor a proposal
+    pub min_sponsor_weight: u64,
+    
+    /// Number of finalized proposals kept in memory (0 disables the cache)
+    pub proposal_cache_size: usize,
+    
+    /// Prefix of proposal display ids, e.g. "AIP" for `AIP-42`
+    pub proposal_id_prefix: String,
+    
+    /// Percentage of the eligible supply that vetoes a proposal regardless of
+    /// the yes/no tally (0 disables veto blocking)
+    pub veto_threshold_percent: u8,
+    
+    /// Extend voting by this many seconds when a vote that flips the outcome
+    /// arrives this close to the deadline (0 disables quiet endings)
+    pub quiet_ending_window_seconds: u64,
+    
+    /// Maximum number of quiet-ending extensions per proposal
+    pub max_quiet_ending_extensions: u32,
+    
+    /// Seconds an approved proposal stays queued before it can be executed,
+    /// giving members time to react (0 makes it executable immediately)
+    pub timelock_seconds: u64,
+    
+    /// Voting strategy ("TokenWeighted", "Quadratic", "Conviction" or
+    /// "Reputation"); unset selects it from `dao.governance_token`
+    pub voting_strategy: Option<String>,
+    
+    /// How fixed per-member vote weights combine with token-derived weight
+    pub vote_weight_override_mode: VoteWeightOverrideMode,
+    
+    /// Reputation multiplier used when `dao.governance_token` selects
+    /// reputation-weighted voting
+    pub reputation_weighting: ReputationWeightingConfig,
+    
+    /// Accrual and pass threshold used when `dao.governance_token` selects
+    /// conviction voting
+    pub conviction: ConvictionConfig,
+    
+    /// Voice credit budget used when `dao.governance_token` selects
+    /// quadratic voting
+    pub quadratic: QuadraticConfig,
+    
+    /// Let delegators vote directly, taking their delegated power back from
+    /// the delegate for that proposal only (otherwise their vote is rejected)
+    pub delegator_override_enabled: bool,
+    
+    /// Limits and verification for proposal attachments
+    pub attachments: AttachmentConfig,
+    
+    /// Require proposals to be committed (signed content hash) before they
+    /// are revealed, instead of being submitted directly
+    pub proposal_commit_reveal: bool,
+    
+    /// Hours a proposal commitment can be revealed for
+    pub proposal_reveal_window_hours: u64,
+    
+    /// Refuse votes that aren't signed by the voter; signed votes have a
+    /// receipt proving how the voter voted
+    pub require_signed_votes: bool,
+    
+    /// Contracts that `ContractCall` proposals may call, checked on
+    /// submission and again before execution
+    pub callable_targets: Vec<CallableTarget>,
+    
+    /// Whether an empty `callable_targets` list allows or denies every call
+    pub empty_callable_targets: EmptyAllowlistPolicy,
+    
+    /// Checks re-run immediately before a proposal is executed
+    pub execution_guard: ExecutionGuardConfig,
+}
+
+/// Basis for the quorum check
+///
+/// Abstentions never count toward the majority, which is always yes against
+/// no and veto.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QuorumBasis {
+    /// Every cast vote, abstentions included, counts toward quorum, measured
+    /// against the supply selected by `quorum_supply_source`
+    CastVotes,
+    /// Every cast vote counts toward quorum, measured against the governance
+    /// token's circulating supply as reported by the token manager
+    CirculatingSupply,
+    /// Only yes, no and veto votes count toward quorum; abstentions count
+    /// toward neither quorum nor majority
+    YesNoOnly,
+}
+
+/// How the winner of a multiple-choice proposal is picked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MultiChoiceTally {
+    /// The option with the most first-preference weight wins
+    Plurality,
+    /// Weakest options are eliminated and their ballots transferred to the
+    /// next preference until one option holds a majority
+    InstantRunoff,
+}
+
+/// Supply that quorum and veto thresholds are measured against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QuorumSupplySource {
+    /// The governance token's total supply
+    Total,
+    /// Total supply minus the balances of `quorum_excluded_addresses`
+    Circulating,
+}
+
+/// Contract that `ContractCall` proposals may call
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CallableTarget {
+    /// Contract address
+    pub address: String,
+    
+    /// Allowed functions as names, signatures or 4-byte selectors (empty allows all)
+    #[serde(default)]
+    pub functions: Vec<String>,
+}
+
+/// Meaning of an empty allowlist
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmptyAllowlistPolicy {
+    /// Everything is allowed
+    Allow,
+    /// Nothing is allowed
+    Deny,
+}
+
+/// How a member's fixed vote weight combines with their token-derived weight
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VoteWeightOverrideMode {
+    /// The fixed weight is used instead of the token-derived weight
+    Replace,
+    /// The fixed weight is added to the token-derived weight
+    Add,
+}
+
+/// How member reputation scales token balance in reputation-weighted voting
+///
+/// A member's weight is `balance * multiplier`, where the multiplier starts
+/// at 1 for zero reputation, grows along `curve` and is capped at
+/// `max_multiplier`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReputationWeightingConfig {
+    /// How the multiplier grows with reputation
+    pub curve: ReputationCurve,
+    
+    /// Largest multiplier any member can reach
+    pub max_multiplier: f64,
+}
+
+impl Default for ReputationWeightingConfig {
+    fn default() -> Self {
+        Self {
+            curve: ReputationCurve::Logarithmic { scale: 0.25 },
+            max_multiplier: 3.0,
+        }
+    }
+}
+
+/// Conviction voting parameters
+///
+/// Each period a stake's conviction decays by `decay` and the stake is added,
+/// so conviction approaches `stake / (1 - decay)` while the stake is held. A
+/// proposal passes once its conviction reaches
+/// `weight * staked supply / (max_ratio - requested / funds)^2`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConvictionConfig {
+    /// Length of an accrual period in seconds
+    pub period_seconds: u64,
+    
+    /// Share of conviction kept each period, between 0 and 1
+    pub decay: f64,
+    
+    /// Largest share of the treasury a single proposal can request
+    pub max_ratio: f64,
+    
+    /// Scale of the pass threshold
+    pub weight: f64,
+}
+
+impl Default for ConvictionConfig {
+    fn default() -> Self {
+        Self {
+            period_seconds: 3600,
+            decay: 0.9,
+            max_ratio: 0.2,
+            weight: 0.005,
+        }
+    }
+}
+
+/// Quadratic voting parameters
+///
+/// Each member gets `voice_credits` per epoch of `epoch_days` days, and a
+/// vote of weight `w` costs `w^2` credits. Credits spent on proposals still
+/// being voted on count against the budget; the budget resets each epoch.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QuadraticConfig {
+    /// Voice credits each member may spend per epoch
+    pub voice_credits: u64,
+    
+    /// Length of a governance epoch in days
+    pub epoch_days: u32,
+}
+
+impl Default for QuadraticConfig {
+    fn default() -> Self {
+        Self {
+            voice_credits: 100,
+            epoch_days: 30,
+        }
+    }
+}
+
+/// Growth of the reputation multiplier before the cap is applied
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum ReputationCurve {
+    /// `1 + slope * reputation`
+    Linear { slope: f64 },
+    /// `1 + scale * ln(1 + reputation)`, favouring early reputation
+    Logarithmic { scale: f64 },
+}
+
+/// Configuration for pre-execution proposal checks
+///
+/// Guards the gap between approval and execution, during which a target
+/// contract may be upgraded or a recipient may become flagged.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExecutionGuardConfig {
+    /// Enable the pre-execution checks
+    pub enabled: bool,
+    
+    /// Addresses that must never receive transfers or contract calls
+    pub denylist: Vec<String>,
+    
+    /// Account whose balance of the transferred token must still cover
+    /// transfers (check skipped when unset)
+    pub executor_address: Option<String>,
+}
+
+/// Configuration for proposal attachments
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AttachmentConfig {
+    /// Maximum number of attachments per proposal
+    pub max_attachments: usize,
+    
+    /// Fetch `ipfs://` attachments through the gateway on submission and
+    /// reject proposals whose content does not match the declared hash
+    pub verify_ipfs_content: bool,
+    
+    /// IPFS gateway used to fetch attachment content
+    pub ipfs_gateway_url: String,
+    
+    /// Seconds to wait for the gateway before failing the submission
+    pub fetch_timeout_seconds: u64,
+    
+    /// Largest attachment the gateway may return, in bytes
+    pub max_content_bytes: u64,
+}
+
+impl Default for AttachmentConfig {
+    fn default() -> Self {
+        Self {
+            max_attachments: 10,
+            verify_ipfs_content: false,
+            ipfs_gateway_url: "https://ipfs.io/ipfs/".to_string(),
+            fetch_timeout_seconds: 10,
+            max_content_bytes: 10 * 1024 * 1024,
+        }
+    }
+}
+
+impl Default for GovernanceConfig {
+    fn default() -> Self {
+        Self {
+            proposal_threshold: 1000,
+            voting_period_days: 7,
+            quorum_percentage: 40,
+            quorum_basis: QuorumBasis::CastVotes,
+            quorum_supply_source: QuorumSupplySource::Total,
+            quorum_excluded_addresses: Vec::new(),
+            majority_percentage: 51,
+            multi_choice_tally: MultiChoiceTally::Plurality,
+            required_sponsors: 0,
+            min_sponsor_weight: 1,
+            proposal_cache_size: 1000,
+            proposal_id_prefix: "AIP".to_string(),
+            veto_threshold_percent: 0,
+            quiet_ending_window_seconds: 0,
+            max_quiet_ending_extensions: 3,
+            timelock_seconds: 172_800, // 2 days
+            voting_strategy: None,
+            vote_weight_override_mode: VoteWeightOverrideMode::Replace,
+            reputation_weighting: ReputationWeightingConfig::default(),
+            conviction: ConvictionConfig::default(),
+            quadratic: QuadraticConfig::default(),
+            delegator_override_enabled: true,
+            attachments: AttachmentConfig::default(),
+            proposal_commit_reveal: false,
+            proposal_reveal_window_hours: 72,
+            require_signed_votes: false,
+            callable_targets: Vec::new(),
+            empty_callable_targets: EmptyAllowlistPolicy::Allow,
+            execution_guard: ExecutionGuardConfig::default(),
+        }
+    }
+}
+
+/// Configuration for the DAO treasury
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TreasuryConfig {
+    /// Multisig wallet holding the treasury funds
+    pub treasury_address: String,
+    
+    /// Addresses authorized to approve treasury transactions
+    pub signers: TreasurySigners,
+    
+    /// Number of signer approvals required for a transaction
+    pub threshold: u32,
+    
+    /// Maximum amount a single transaction may move
+    pub spending_limit: u64,
+    
+    /// Spending limits per token symbol; tokens without an entry are unlimited
+    pub limits: HashMap<String, TokenSpendingLimits>,
+    
+    /// Hours a transaction may wait for approvals before it expires
+    pub transaction_expiry_hours: u64,
+    
+    /// Hours an idempotency key stays bound to the transaction it created;
+    /// a retry after that creates a new transaction
+    pub idempotency_window_hours: u64,
+    
+    /// Confirmations to wait for after submitting an on-chain transfer; 0
+    /// settles from the receipt if the transfer is already mined and leaves
+    /// it to reconciliation otherwise
+    pub confirmations: u64,
+    
+    /// Seconds to wait for those confirmations before leaving the transfer
+    /// to reconciliation
+    pub confirmation_timeout_seconds: u64,
+    
+    /// Seconds between runs of receipt reconciliation and transaction
+    /// expiry in the background; 0 disables them
+    pub maintenance_interval_seconds: u64,
+    
+    /// Allowed drift in percentage points before a rebalance is proposed
+    pub rebalance_tolerance_percent: u8,
+}
+
+impl Default for TreasuryConfig {
+    fn default() -> Self {
+        Self {
+            treasury_address: String::new(),
+            signers: TreasurySigners::default(),
+            threshold: 2,
+            spending_limit: 10000,
+            limits: HashMap::new(),
+            transaction_expiry_hours: 168,
+            idempotency_window_hours: 24,
+            confirmations: 0,
+            confirmation_timeout_seconds: 300,
+            maintenance_interval_seconds: 60,
+            rebalance_tolerance_percent: 5,
+        }
+    }
+}
+
+/// Treasury signers, as addresses or, in configs written before signer
+/// addresses were configured, as a count
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TreasurySigners {
+    /// Addresses authorized to approve treasury transactions
+    Addresses(Vec<String>),
+    /// Number of signers; the treasury refuses to operate until it is
+    /// replaced with their addresses
+    Count(u32),
+}
+
+impl TreasurySigners {
+    /// Configured signer addresses; none for a count
+    pub fn addresses(&self) -> &[String] {
+        match self {
+            TreasurySigners::Addresses(addresses) => addresses,
+            TreasurySigners::Count(_) => &[],
+        }
+    }
+    
+    /// Number of signers
+    pub fn len(&self) -> usize {
+        match self {
+            TreasurySigners::Addresses(addresses) => addresses.len(),
+            TreasurySigners::Count(count) => *count as usize,
+        }
+    }
     
-    /// Block time in seconds
-    pub block_time_seconds: u64,
+    /// Whether no signers are configured
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for TreasurySigners {
+    fn default() -> Self {
+        TreasurySigners::Addresses(Vec::new())
+    }
+}
+
+/// Treasury spending limits for one token, in base units
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TokenSpendingLimits {
+    /// Largest amount a single transaction may move
+    pub per_tx_limit: Option<u64>,
     
-    /// Contract addresses for this chain
-    pub contract_addresses: HashMap<String, String>,
+    /// Most that executed and approved transactions may move in any
+    /// 24-hour window
+    pub daily_limit: Option<u64>,
 }
 
-/// Configuration for the API server
+/// Configuration for the price oracle used for USD valuations
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ApiConfig {
-    /// Host to bind the API server to
-    pub host: String,
-    
-    /// Port to bind the API server to
-    pub port: u16,
+#[serde(default)]
+pub struct OracleConfig {
+    /// How long a cached price may be served, marked stale, while the
+    /// oracle is unavailable (zero never serves stale prices)
+    pub max_stale_seconds: u64,
+}
+
+impl Default for OracleConfig {
+    fn default() -> Self {
+        Self {
+            max_stale_seconds: 3600,
+        }
+    }
+}
+
+/// Configuration for optional feature modules
+///
+/// Disabled modules are not initialized, their API routes are not mounted,
+/// and calls into them fail with a "not supported" error.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeaturesConfig {
+    /// Multisig treasury and its transaction routes
+    pub treasury_enabled: bool,
     
-    /// Base URL for the API
-    pub base_url: String,
+    /// Delegation of voting power
+    pub delegation_enabled: bool,
     
-    /// Enable CORS
-    pub enable_cors: bool,
+    /// WebSocket event streaming
+    pub websockets_enabled: bool,
+}
+
+impl Default for FeaturesConfig {
+    fn default() -> Self {
+        Self {
+            treasury_enabled: true,
+            delegation_enabled: true,
+            websockets_enabled: true,
+        }
+    }
+}
+
+/// Format of log lines
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human-readable lines, for local development
+    Text,
+    /// One JSON object per line, with span fields such as the request ID,
+    /// for log aggregation
+    Json,
+}
+
+/// Configuration for log output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    /// Line format
+    pub format: LogFormat,
     
-    /// CORS allowed origins
-    pub cors_allowed_origins: Vec<String>,
+    /// Filter directives such as `info` or `atomsi_dao=debug,info`;
+    /// `RUST_LOG` takes precedence when set
+    pub level: String,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            format: LogFormat::Text,
+            level: "info".to_string(),
+        }
+    }
+}
+
+/// Configuration for member identity and reputation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IdentityConfig {
+    /// Automatic role promotion by reputation
+    pub promotion: PromotionConfig,
     
-    /// Enable API key authentication
-    pub enable_api_key_auth: bool,
+    /// Batching of reputation changes from recorded activities
+    pub reputation_batching: ReputationBatchingConfig,
     
-    /// API key (only used when enable_api_key_auth is true)
-    pub api_key: Option<String>,
+    /// Let addresses without a member record submit proposals, vote and act
+    /// on treasury transactions (suspended and inactive members never can)
+    pub allow_unregistered_voters: bool,
+}
+
+impl Default for IdentityConfig {
+    fn default() -> Self {
+        Self {
+            promotion: PromotionConfig::default(),
+            reputation_batching: ReputationBatchingConfig::default(),
+            allow_unregistered_voters: true,
+        }
+    }
+}
+
+/// Configuration for batching reputation updates
+///
+/// Pending changes are held in memory, so changes not yet flushed are lost
+/// if the process stops without flushing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReputationBatchingConfig {
+    /// Hold reputation changes in memory instead of writing each one
+    pub enabled: bool,
     
-    /// Enable rate limiting
-    pub enable_rate_limiting: bool,
+    /// Seconds between periodic flushes
+    pub flush_interval_seconds: u64,
     
-    /// Rate limit requests per minute
-    pub rate_limit_per_minute: Option<u32>,
+    /// Flush as soon as this many members have pending changes
+    pub max_pending_members: usize,
 }
 
-/// Configuration for security settings
+impl Default for ReputationBatchingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            flush_interval_seconds: 30,
+            max_pending_members: 1000,
+        }
+    }
+}
+
+/// Configuration for automatic member role promotion
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SecurityConfig {
-    /// Secret key for signing JWTs
-    pub jwt_secret: String,
-    
-    /// JWT expiration time in seconds
-    pub jwt_expiration_seconds: u64,
+#[serde(default)]
+pub struct PromotionConfig {
+    /// Enable automatic promotion sweeps
+    pub enabled: bool,
     
-    /// Session timeout in seconds
-    pub session_timeout_seconds: u64,
+    /// Reputation required to be promoted from Member to Delegate
+    pub delegate_reputation: u64,
     
-    /// Enable two-factor authentication
-    pub enable_2fa: bool,
+    /// Reputation required to be promoted from Delegate to Council
+    pub council_reputation: u64,
     
-    /// Minimum password length
-    pub min_password_length: u8,
+    /// Highest role that can be granted automatically (Admin is never granted)
+    pub max_auto_role: MemberRole,
     
-    /// Number of required password character classes (lowercase, uppercase, numbers, symbols)
-    pub required_password_character_classes: u8,
+    /// Minimum hours between role changes for a member
+    pub cooldown_hours: u64,
+}
+
+impl Default for PromotionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            delegate_reputation: 100,
+            council_reputation: 500,
+            max_auto_role: MemberRole::Delegate,
+            cooldown_hours: 168, // 7 days
+        }
+    }
+}
+
+/// Prefix of environment variables that override configuration fields
+pub const ENV_PREFIX: &str = "ATOMSI_";
+
+/// Format of a configuration file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    /// JSON, the default for unrecognized extensions
+    Json,
+    /// TOML (`.toml`)
+    Toml,
+    /// YAML (`.yaml` or `.yml`)
+    Yaml,
+}
+
+impl ConfigFormat {
+    /// Detect the format from a file's extension
+    ///
+    /// `.toml` is TOML and `.yaml`/`.yml` are YAML; anything else, including
+    /// a path without an extension, is JSON.
+    pub fn from_path(path: &str) -> Self {
+        let extension = Path::new(path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase);
+
+        match extension.as_deref() {
+            Some("toml") => ConfigFormat::Toml,
+            Some("yaml") | Some("yml") => ConfigFormat::Yaml,
+            _ => ConfigFormat::Json,
+        }
+    }
+
+    /// Parse a file's contents
+    fn parse(self, contents: &str) -> Result<serde_json::Value> {
+        let parsed = match self {
+            ConfigFormat::Json => serde_json::from_str(contents).map_err(|e| e.to_string()),
+            ConfigFormat::Toml => toml::from_str(contents).map_err(|e| e.to_string()),
+            ConfigFormat::Yaml => serde_yaml::from_str(contents).map_err(|e| e.to_string()),
+        };
+        parsed.map_err(|e| Error::ConfigError(format!("Failed to parse config: {}", e)))
+    }
+
+    /// Render a configuration
+    fn render(self, value: &serde_json::Value) -> Result<String> {
+        let rendered = match self {
+            ConfigFormat::Json => serde_json::to_string_pretty(value).map_err(|e| e.to_string()),
+            // TOML has no null, so unset optional fields are left out
+            ConfigFormat::Toml => toml::to_string_pretty(&without_nulls(value.clone())).map_err(|e| e.to_string()),
+            ConfigFormat::Yaml => serde_yaml::to_string(value).map_err(|e| e.to_string()),
+        };
+        rendered.map_err(|e| Error::ConfigError(format!("Failed to serialize config: {}", e)))
+    }
+}
+
+/// Remove null object members and array elements, recursively
+fn without_nulls(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => map
+            .into_iter()
+            .filter(|(_, value)| !value.is_null())
+            .map(|(key, value)| (key, without_nulls(value)))
+            .collect(),
+        serde_json::Value::Array(values) => values
+            .into_iter()
+            .filter(|value| !value.is_null())
+            .map(without_nulls)
+            .collect(),
+        value => value,
+    }
+}
+
+/// A field set from the environment, with the value it has in the file
+#[derive(Debug, Clone)]
+struct EnvOverride {
+    section: String,
+    field: String,
+    file_value: serde_json::Value,
+}
+
+/// Apply `ATOMSI_<SECTION>_<FIELD>` overrides to a configuration
+///
+/// Section and field are the upper-cased config names, so
+/// `ATOMSI_SECURITY_JWT_SECRET` sets `security.jwt_secret` and
+/// `ATOMSI_BLOCKCHAIN_RPC_URL` sets `blockchain.rpc_url`. Only strings,
+/// numbers and booleans directly inside a section can be overridden; the
+/// value is parsed as the field's type. Variables that don't name a field
+/// are ignored.
+fn apply_env_overrides(
+    config: Config,
+    vars: impl IntoIterator<Item = (String, String)>,
+) -> Result<(Config, Vec<EnvOverride>)> {
+    let mut value = serde_json::to_value(&config)
+        .map_err(|e| Error::ConfigError(format!("Failed to serialize config: {}", e)))?;
+    let sections = value
+        .as_object_mut()
+        .ok_or_else(|| Error::ConfigError("Config must serialize to an object".to_string()))?;
+    let mut overrides = Vec::new();
     
-    /// Maximum login attempts before lockout
-    pub max_login_attempts: u8,
+    for (name, raw) in vars {
+        let Some(rest) = name.strip_prefix(ENV_PREFIX) else {
+            continue;
+        };
+        
+        let target = sections.iter().find_map(|(section, fields)| {
+            let field_name = rest.strip_prefix(section.to_uppercase().as_str())?.strip_prefix('_')?;
+            let field = fields.as_object()?.keys().find(|field| field.to_uppercase() == field_name)?;
+            Some((section.clone(), field.clone()))
+        });
+        let Some((section, field)) = target else {
+            continue;
+        };
+        
+        let slot = &mut sections[&section][&field];
+        let parsed = match slot {
+            serde_json::Value::Bool(_) => raw.trim().parse().ok().map(serde_json::Value::Bool),
+            serde_json::Value::Number(_) => raw.trim().parse().ok().map(serde_json::Value::Number),
+            serde_json::Value::String(_) => Some(serde_json::Value::String(raw.clone())),
+            // Unset optional field: a number or boolean if it parses as one
+            serde_json::Value::Null => Some(
+                serde_json::from_str(raw.trim())
+                    .ok()
+                    .filter(|parsed: &serde_json::Value| parsed.is_number() || parsed.is_boolean())
+                    .unwrap_or_else(|| serde_json::Value::String(raw.clone())),
+            ),
+            serde_json::Value::Array(_) | serde_json::Value::Object(_) => None,
+        };
+        let parsed = parsed.ok_or_else(|| {
+            Error::ConfigError(format!("{} is not a valid value for {}.{}", name, section, field))
+        })?;
+        
+        overrides.push(EnvOverride {
+            section,
+            field,
+            file_value: std::mem::replace(slot, parsed),
+        });
+    }
     
-    /// Login lockout duration in seconds
-    pub login_lockout_seconds: u64,
+    let config = serde_json::from_value(value)
+        .map_err(|e| Error::ConfigError(format!("Invalid environment override: {}", e)))?;
+    Ok((config, overrides))
 }
 
 /// Configuration manager for handling configuration
+///
+/// Clones share the same configuration.
+#[derive(Clone)]
 pub struct ConfigManager {
-    config: Arc<RwLock<Config>>,
+    config: Arc<RwLock<Arc<Config>>>,
     config_path: String,
+    /// Fields set from the environment, written back with their file values
+    env_overrides: Arc<RwLock<Vec<EnvOverride>>>,
+    /// Publishes every configuration that replaces the current one
+    changes: Arc<watch::Sender<Config>>,
 }
 
+/// How long to wait after a file change before reloading, so an editor's
+/// several writes are picked up as one change
+const RELOAD_DEBOUNCE: Duration = Duration::from_millis(100);
+
 impl ConfigManager {
     /// Create a new configuration manager
+    ///
+    /// Environment variables named `ATOMSI_<SECTION>_<FIELD>` take
+    /// precedence over the file, so secrets such as `jwt_secret` need not be
+    /// stored in it. Fails if the file is missing, can't be parsed, or the
+    /// result doesn't pass [`Config::validate`].
     pub fn new(config_path: &str) -> Result<Self> {
-        let config = if Path::new(config_path).exists() {
-            Self::load_from_file(config_path)?
-        } else {
-            return Err(Error::ConfigError(format!(
-                "Configuration file not found: {}",
-                config_path
-            )));
-        };
+        let (config, env_overrides) = Self::load(config_path)?;
         
-        Ok(Self {
-            config: Arc::new(RwLock::new(config)),
-            config_path: config_path.to_string(),
-        })
+        Ok(Self::from_parts(config, config_path, env_overrides))
     }
     
     /// Create a new configuration manager with default configuration
     pub fn with_defaults(config_path: &str) -> Self {
         let config = Self::create_default_config();
         
+        Self::from_parts(config, config_path, Vec::new())
+    }
+    
+    fn from_parts(config: Config, config_path: &str, env_overrides: Vec<EnvOverride>) -> Self {
+        let (changes, _) = watch::channel(config.clone());
+        
         Self {
-            config: Arc::new(RwLock::new(config)),
+            config: Arc::new(RwLock::new(Arc::new(config))),
             config_path: config_path.to_string(),
+            env_overrides: Arc::new(RwLock::new(env_overrides)),
+            changes: Arc::new(changes),
         }
     }
     
+    /// Read, override from the environment and validate the file
+    fn load(config_path: &str) -> Result<(Config, Vec<EnvOverride>)> {
+        if !Path::new(config_path).exists() {
+            return Err(Error::ConfigError(format!(
+                "Configuration file not found: {}",
+                config_path
+            )));
+        }
+        let config = Self::load_from_file(config_path)?;
+        let (config, env_overrides) = apply_env_overrides(config, std::env::vars())?;
+        config.validate()?;
+        
+        Ok((config, env_overrides))
+    }
+    
+    /// Reload the configuration from its file
+    ///
+    /// Environment overrides are applied again. An invalid file is rejected
+    /// and the current configuration kept.
+    pub fn reload(&self) -> Result<()> {
+        let (config, env_overrides) = Self::load(&self.config_path)?;
+        
+        let mut config_lock = self.config.write().unwrap();
+        *config_lock = Arc::new(config.clone());
+        *self.env_overrides.write().unwrap() = env_overrides;
+        drop(config_lock);
+        
+        self.changes.send_replace(config);
+        
+        Ok(())
+    }
+    
+    /// Receive every configuration that replaces the current one, whether
+    /// from [`update_config`](Self::update_config) or a reload
+    pub fn subscribe(&self) -> watch::Receiver<Config> {
+        self.changes.subscribe()
+    }
+    
+    /// Reload the configuration whenever its file changes
+    ///
+    /// Spawns a task on the current Tokio runtime that watches the file until
+    /// it is aborted. Changes that fail to load or validate are logged and
+    /// ignored, keeping the current configuration.
+    pub fn watch(&self) -> Result<tokio::task::JoinHandle<()>> {
+        let config_path = Path::new(&self.config_path);
+        let file_name = config_path
+            .file_name()
+            .ok_or_else(|| Error::ConfigError(format!("Invalid config path: {}", self.config_path)))?
+            .to_os_string();
+        // Watch the directory, as editors often replace the file rather than
+        // write to it
+        let directory = match config_path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => Path::new(".").to_path_buf(),
+        };
+        
+        let (events_tx, mut events) = mpsc::unbounded_channel();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            if let Ok(event) = event {
+                let touches_config = event.paths.iter().any(|path| path.file_name() == Some(file_name.as_os_str()));
+                if touches_config && (event.kind.is_create() || event.kind.is_modify()) {
+                    let _ = events_tx.send(());
+                }
+            }
+        })
+        .map_err(|e| Error::ConfigError(format!("Failed to watch config file: {}", e)))?;
+        watcher
+            .watch(&directory, RecursiveMode::NonRecursive)
+            .map_err(|e| Error::ConfigError(format!("Failed to watch config file: {}", e)))?;
+        
+        let manager = self.clone();
+        Ok(tokio::spawn(async move {
+            // Dropping the watcher stops the events
+            let _watcher = watcher;
+            while events.recv().await.is_some() {
+                tokio::time::sleep(RELOAD_DEBOUNCE).await;
+                while events.try_recv().is_ok() {}
+                
+                match manager.reload() {
+                    Ok(()) => info!("Reloaded configuration from {}", manager.config_path),
+                    Err(e) => warn!("Ignoring invalid configuration in {}: {}", manager.config_path, e),
+                }
+            }
+        }))
+    }
+    
     /// Get the current configuration
     pub fn get_config(&self) -> Config {
+        Config::clone(&self.config.read().unwrap())
+    }
+    
+    /// Get the current configuration without copying it
+    ///
+    /// The returned snapshot is not affected by later updates or reloads.
+    pub fn current(&self) -> Arc<Config> {
         self.config.read().unwrap().clone()
     }
     
     /// Update the configuration
+    ///
+    /// An invalid configuration is rejected and the current one kept.
     pub fn update_config(&self, config: Config) -> Result<()> {
+        config.validate()?;
+        
         let mut config_lock = self.config.write().unwrap();
-        *config_lock = config;
+        *config_lock = Arc::new(config.clone());
+        drop(config_lock);
         
         self.save_to_file(&self.config_path)?;
+        self.changes.send_replace(config);
         
         Ok(())
     }
     
     /// Save the configuration to a file
+    ///
+    /// The format follows the file's extension (see
+    /// [`ConfigFormat::from_path`]). Fields set from the environment keep
+    /// their file values, so secrets are never written to disk.
     pub fn save_to_file(&self, path: &str) -> Result<()> {
         let config = self.config.read().unwrap();
-        let config_json = serde_json::to_string_pretty(&*config)
+        let mut value = serde_json::to_value(&**config)
             .map_err(|e| Error::ConfigError(format!("Failed to serialize config: {}", e)))?;
+        for env_override in self.env_overrides.read().unwrap().iter() {
+            value[&env_override.section][&env_override.field] = env_override.file_value.clone();
+        }
+        let contents = ConfigFormat::from_path(path).render(&value)?;
         
         let mut file = File::create(path)
             .map_err(|e| Error::ConfigError(format!("Failed to create config file: {}", e)))?;
         
-        file.write_all(config_json.as_bytes())
+        file.write_all(contents.as_bytes())
             .map_err(|e| Error::ConfigError(format!("Failed to write config file: {}", e)))?;
         
         Ok(())
     }
     
-    /// Load the configuration from a file
+    /// Load the configuration from a file in the format its extension names
     fn load_from_file(path: &str) -> Result<Config> {
         let mut file = File::open(path)
             .map_err(|e| Error::ConfigError(format!("Failed to open config file: {}", e)))?;
@@ -269,7 +1682,8 @@ impl ConfigManager {
         file.read_to_string(&mut contents)
             .map_err(|e| Error::ConfigError(format!("Failed to read config file: {}", e)))?;
         
-        let config: Config = serde_json::from_str(&contents)
+        let value = ConfigFormat::from_path(path).parse(&contents)?;
+        let config: Config = serde_json::from_value(value)
             .map_err(|e| Error::ConfigError(format!("Failed to parse config: {}", e)))?;
         
         Ok(config)
@@ -287,6 +1701,7 @@ impl ConfigManager {
                 default_token_symbol: "ATOM".to_string(),
                 token_contract_address: "0x0000000000000000000000000000000000000000".to_string(),
                 admin_addresses: vec!["0x0000000000000000000000000000000000000000".to_string()],
+                environment: default_environment(),
             },
             database: DatabaseConfig {
                 db_type: "sqlite".to_string(),
@@ -297,9 +1712,11 @@ impl ConfigManager {
                 password: "password".to_string(),
                 pool_size: 10,
                 sqlite_path: Some("./data/atomsi_dao.db".to_string()),
+                read_replica: None,
             },
             blockchain: BlockchainConfig {
                 rpc_url: "https://mainnet.infura.io/v3/your-api-key".to_string(),
+                rpc_urls: Vec::new(),
                 chain_id: 1,
                 explorer_url: "https://etherscan.io".to_string(),
                 gas_price_strategy: "standard".to_string(),
@@ -312,6 +1729,7 @@ impl ConfigManager {
                             name: "Ethereum Mainnet".to_string(),
                             chain_id: 1,
                             rpc_url: "https://mainnet.infura.io/v3/your-api-key".to_string(),
+                            rpc_urls: Vec::new(),
                             explorer_url: "https://etherscan.io".to_string(),
                             currency_symbol: "ETH".to_string(),
                             block_time_seconds: 15,
@@ -327,6 +1745,9 @@ impl ConfigManager {
                     );
                     chains
                 },
+                max_concurrent_executions: default_max_concurrent_executions(),
+                log_scan: LogScanConfig::default(),
+                retry: RpcRetryConfig::default(),
             },
             api: ApiConfig {
                 host: "127.0.0.1".to_string(),
@@ -338,9 +1759,16 @@ impl ConfigManager {
                 api_key: None,
                 enable_rate_limiting: true,
                 rate_limit_per_minute: Some(60),
+                ip_filter: None,
+                max_body_bytes: default_max_body_bytes(),
+                max_json_depth: default_max_json_depth(),
+                max_json_nodes: default_max_json_nodes(),
+                websocket_idle_timeout_seconds: default_websocket_idle_timeout_seconds(),
+                websocket_ping_interval_seconds: default_websocket_ping_interval_seconds(),
+                http_cache: HttpCacheConfig::default(),
             },
             security: SecurityConfig {
-                jwt_secret: "change_this_to_a_secure_random_string".to_string(),
+                jwt_secret: DEFAULT_JWT_SECRET.to_string(),
                 jwt_expiration_seconds: 86400, // 24 hours
                 session_timeout_seconds: 3600, // 1 hour
                 enable_2fa: false,
@@ -348,7 +1776,15 @@ impl ConfigManager {
                 required_password_character_classes: 3,
                 max_login_attempts: 5,
                 login_lockout_seconds: 300, // 5 minutes
+                signature_max_age_seconds: default_signature_max_age_seconds(),
+                audit_shipping: AuditShippingConfig::default(),
             },
+            governance: GovernanceConfig::default(),
+            identity: IdentityConfig::default(),
+            treasury: TreasuryConfig::default(),
+            oracle: OracleConfig::default(),
+            features: FeaturesConfig::default(),
+            logging: LoggingConfig::default(),
             custom: HashMap::new(),
         }
     }
@@ -363,6 +1799,11 @@ impl ConfigManager {
             "blockchain" => serde_json::to_value(&config.blockchain),
             "api" => serde_json::to_value(&config.api),
             "security" => serde_json::to_value(&config.security),
+            "governance" => serde_json::to_value(&config.governance),
+            "identity" => serde_json::to_value(&config.identity),
+            "treasury" => serde_json::to_value(&config.treasury),
+            "oracle" => serde_json::to_value(&config.oracle),
+            "features" => serde_json::to_value(&config.features),
             _ => {
                 if let Some(value) = config.custom.get(section) {
                     Ok(value.clone())
@@ -382,7 +1823,7 @@ impl ConfigManager {
     /// Set a custom configuration value
     pub fn set_custom_value(&self, key: &str, value: serde_json::Value) -> Result<()> {
         let mut config = self.config.write().unwrap();
-        config.custom.insert(key.to_string(), value);
+        Arc::make_mut(&mut config).custom.insert(key.to_string(), value);
         
         drop(config);
         self.save_to_file(&self.config_path)?;
@@ -402,6 +1843,15 @@ mod tests {
     use super::*;
     use tempfile::tempdir;
     
+    const TEST_JWT_SECRET: &str = "0123456789abcdef0123456789abcdef";
+    
+    /// Default configuration with a secret that passes validation
+    fn valid_config() -> Config {
+        let mut config = ConfigManager::create_default_config();
+        config.security.jwt_secret = TEST_JWT_SECRET.to_string();
+        config
+    }
+    
     #[test]
     fn test_create_default_config() {
         let config = ConfigManager::create_default_config();
@@ -422,10 +1872,7 @@ mod tests {
         let mut config = ConfigManager::create_default_config();
         config.dao.name = "Test DAO".to_string();
         
-        let manager = ConfigManager {
-            config: Arc::new(RwLock::new(config.clone())),
-            config_path: config_path_str.to_string(),
-        };
+        let manager = ConfigManager::from_parts(config.clone(), config_path_str, Vec::new());
         
         manager.save_to_file(config_path_str).unwrap();
         
@@ -436,14 +1883,51 @@ mod tests {
         assert_eq!(loaded_config.api.port, 8080);
     }
     
+    #[test]
+    fn test_round_trip_each_format() {
+        let dir = tempdir().unwrap();
+        
+        let mut config = ConfigManager::create_default_config();
+        config.dao.name = "Round Trip DAO".to_string();
+        config.dao.website_url = None;
+        config.api.rate_limit_per_minute = Some(90);
+        config.blockchain.rpc_urls = vec!["https://rpc-a.example.com".to_string(), "https://rpc-b.example.com".to_string()];
+        config.custom.insert("quorum_note".to_string(), serde_json::json!({ "text": "Majority", "percent": 51 }));
+        let expected = serde_json::to_value(&config).unwrap();
+        
+        for (file_name, format, marker) in [
+            ("config.json", ConfigFormat::Json, "\"dao\": {"),
+            ("config.toml", ConfigFormat::Toml, "[dao]"),
+            ("config.yaml", ConfigFormat::Yaml, "dao:"),
+            ("config.yml", ConfigFormat::Yaml, "dao:"),
+        ] {
+            let path = dir.path().join(file_name);
+            let path_str = path.to_str().unwrap();
+            assert_eq!(ConfigFormat::from_path(path_str), format);
+            
+            ConfigManager::from_parts(config.clone(), path_str, Vec::new()).save_to_file(path_str).unwrap();
+            assert!(std::fs::read_to_string(&path).unwrap().contains(marker), "{} not written as {:?}", file_name, format);
+            
+            // Load the file and save it again without losing anything
+            let loaded = ConfigManager::load_from_file(path_str).unwrap();
+            assert_eq!(serde_json::to_value(&loaded).unwrap(), expected, "{}", file_name);
+            ConfigManager::from_parts(loaded, path_str, Vec::new()).save_to_file(path_str).unwrap();
+            let reloaded = ConfigManager::load_from_file(path_str).unwrap();
+            assert_eq!(serde_json::to_value(reloaded).unwrap(), expected, "{}", file_name);
+        }
+        
+        assert_eq!(ConfigFormat::from_path("config"), ConfigFormat::Json);
+        assert_eq!(ConfigFormat::from_path("/etc/atomsi/CONFIG.TOML"), ConfigFormat::Toml);
+    }
+    
     #[test]
     fn test_update_config() {
         let dir = tempdir().unwrap();
         let config_path = dir.path().join("config.json");
         let config_path_str = config_path.to_str().unwrap();
         
-        // Create a config manager with default config
-        let manager = ConfigManager::with_defaults(config_path_str);
+        // Create a config manager with a valid default config
+        let manager = ConfigManager::from_parts(valid_config(), config_path_str, Vec::new());
         
         // Update the config
         let mut config = manager.get_config();
@@ -458,6 +1942,227 @@ mod tests {
         assert_eq!(updated_config.api.port, 9090);
     }
     
+    #[test]
+    fn test_validate() {
+        let mut config = valid_config();
+        assert!(config.validate().is_ok());
+        
+        config.governance.quorum_percentage = 140;
+        config.blockchain.gas_price_strategy = "cheapest".to_string();
+        config.treasury.signers = TreasurySigners::Addresses(vec![
+            "0x0000000000000000000000000000000000000001".to_string(),
+            "0x0000000000000000000000000000000000000002".to_string(),
+            "0x0000000000000000000000000000000000000003".to_string(),
+        ]);
+        config.treasury.threshold = 5;
+        config.treasury.limits.insert(
+            "ATOM".to_string(),
+            TokenSpendingLimits {
+                per_tx_limit: Some(2_000),
+                daily_limit: Some(1_000),
+            },
+        );
+        
+        let error = config.validate().unwrap_err().to_string();
+        assert!(error.contains("governance.quorum_percentage"));
+        assert!(error.contains("blockchain.gas_price_strategy"));
+        assert!(error.contains("treasury.threshold"));
+        assert!(error.contains("treasury.limits.ATOM.per_tx_limit"));
+    }
+    
+    #[test]
+    fn test_treasury_signers_accept_addresses_or_count() {
+        let listed: TreasuryConfig = toml::from_str(
+            "signers = [\"0x0000000000000000000000000000000000000001\"]\nthreshold = 1",
+        )
+        .unwrap();
+        assert_eq!(listed.signers.addresses(), ["0x0000000000000000000000000000000000000001"]);
+        
+        // Configs written before signer addresses still load
+        let counted: TreasuryConfig = toml::from_str("signers = 3\nthreshold = 2").unwrap();
+        assert_eq!(counted.signers, TreasurySigners::Count(3));
+        assert_eq!(counted.signers.len(), 3);
+        assert!(counted.signers.addresses().is_empty());
+    }
+    
+    #[test]
+    fn test_validate_rejects_weak_jwt_secrets() {
+        // The shipped placeholder must be replaced before the DAO starts
+        let error = ConfigManager::create_default_config().validate().unwrap_err().to_string();
+        assert!(error.contains("security.jwt_secret"), "{}", error);
+        
+        for secret in ["", "   ", "hunter2", &TEST_JWT_SECRET[1..]] {
+            let mut config = valid_config();
+            config.security.jwt_secret = secret.to_string();
+            let error = config.validate().unwrap_err().to_string();
+            assert!(error.contains("security.jwt_secret"), "{:?} accepted", secret);
+        }
+    }
+    
+    #[test]
+    fn test_validate_reports_every_problem() {
+        let mut config = ConfigManager::create_default_config();
+        config.governance.majority_percentage = 150;
+        config.blockchain.rpc_url = String::new();
+        config.blockchain.rpc_urls = vec!["not a url".to_string(), "ftp://node.example.com".to_string()];
+        config.database.pool_size = 0;
+        config.security.min_password_length = 0;
+        config.governance.reputation_weighting.max_multiplier = 0.5;
+        config.governance.conviction.decay = 1.0;
+        config.governance.quadratic.epoch_days = 0;
+        
+        let error = config.validate().unwrap_err().to_string();
+        for field in [
+            "governance.majority_percentage",
+            "blockchain.rpc_url must not be empty",
+            "blockchain.rpc_urls 'not a url'",
+            "blockchain.rpc_urls 'ftp://node.example.com'",
+            "database.pool_size",
+            "security.min_password_length",
+            "governance.reputation_weighting.max_multiplier",
+            "governance.conviction.decay",
+            "governance.quadratic.epoch_days",
+        ] {
+            assert!(error.contains(field), "{} missing from: {}", field, error);
+        }
+    }
+    
+    #[test]
+    fn test_validate_database_and_urls() {
+        let mut config = valid_config();
+        config.database.db_type = "sqlite".to_string();
+        config.database.sqlite_path = None;
+        assert!(config.validate().unwrap_err().to_string().contains("database.sqlite_path"));
+        
+        config.database.sqlite_path = Some("./data/dao.db".to_string());
+        assert!(config.validate().is_ok());
+        
+        // Local nodes and websocket endpoints are fine
+        config.blockchain.rpc_url = "http://127.0.0.1:8545".to_string();
+        config.blockchain.rpc_urls = vec!["wss://mainnet.example.com/ws".to_string()];
+        assert!(config.validate().is_ok());
+        
+        config.dao.website_url = Some("atomsi-dao".to_string());
+        config.blockchain.supported_chains.get_mut("ethereum").unwrap().rpc_url = String::new();
+        let error = config.validate().unwrap_err().to_string();
+        assert!(error.contains("dao.website_url"));
+        assert!(error.contains("blockchain.supported_chains.ethereum.rpc_url"));
+    }
+    
+    #[test]
+    fn test_invalid_config_rejected_on_load_and_update() {
+        let dir = tempdir().unwrap();
+        let config_path = dir.path().join("config.json");
+        let config_path_str = config_path.to_str().unwrap();
+        
+        let mut invalid = valid_config();
+        invalid.governance.quorum_percentage = 150;
+        std::fs::write(&config_path, serde_json::to_string(&invalid).unwrap()).unwrap();
+        
+        let error = ConfigManager::new(config_path_str).err().unwrap();
+        assert!(matches!(error, Error::ConfigError(_)));
+        assert!(error.to_string().contains("governance.quorum_percentage"));
+        
+        let manager = ConfigManager::from_parts(valid_config(), config_path_str, Vec::new());
+        assert!(manager.update_config(invalid).is_err());
+        assert_eq!(manager.get_config().governance.quorum_percentage, 40);
+    }
+    
+    fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+    
+    #[test]
+    fn test_env_overrides_parse_field_types() {
+        let config = ConfigManager::create_default_config();
+        let (config, overrides) = apply_env_overrides(
+            config,
+            vars(&[
+                ("ATOMSI_API_PORT", "9443"),
+                ("ATOMSI_API_ENABLE_API_KEY_AUTH", "true"),
+                ("ATOMSI_API_API_KEY", "k-123"),
+                ("ATOMSI_BLOCKCHAIN_RPC_URL", "https://rpc.example.com"),
+                // Not config fields: ignored
+                ("ATOMSI_TEST_DATABASE_URL", "postgres://localhost/test"),
+                ("PATH", "/usr/bin"),
+            ]),
+        )
+        .unwrap();
+        
+        assert_eq!(config.api.port, 9443);
+        assert!(config.api.enable_api_key_auth);
+        assert_eq!(config.api.api_key.as_deref(), Some("k-123"));
+        assert_eq!(config.blockchain.rpc_url, "https://rpc.example.com");
+        assert_eq!(overrides.len(), 4);
+        
+        for bad in [("ATOMSI_API_PORT", "eighty"), ("ATOMSI_API_PORT", "70000"), ("ATOMSI_BLOCKCHAIN_RPC_URLS", "x")] {
+            assert!(apply_env_overrides(ConfigManager::create_default_config(), vars(&[bad])).is_err());
+        }
+    }
+    
+    #[test]
+    fn test_env_overrides_take_precedence_over_file() {
+        let dir = tempdir().unwrap();
+        let config_path = dir.path().join("config.json");
+        let config_path_str = config_path.to_str().unwrap();
+        
+        let mut file_config = ConfigManager::create_default_config();
+        file_config.security.jwt_secret = "secret-from-file-0123456789abcdef".to_string();
+        file_config.database.password = "password-from-file".to_string();
+        std::fs::write(&config_path, serde_json::to_string(&file_config).unwrap()).unwrap();
+        
+        std::env::set_var("ATOMSI_SECURITY_JWT_SECRET", "secret-from-env-0123456789abcdef");
+        let manager = ConfigManager::new(config_path_str);
+        std::env::remove_var("ATOMSI_SECURITY_JWT_SECRET");
+        let manager = manager.unwrap();
+        
+        let config = manager.get_config();
+        assert_eq!(config.security.jwt_secret, "secret-from-env-0123456789abcdef");
+        assert_eq!(config.database.password, "password-from-file");
+        assert_eq!(config.dao.name, file_config.dao.name);
+        
+        // Saving keeps the secret out of the file
+        let mut updated = config.clone();
+        updated.dao.name = "Renamed DAO".to_string();
+        manager.update_config(updated).unwrap();
+        let saved = ConfigManager::load_from_file(config_path_str).unwrap();
+        assert_eq!(saved.dao.name, "Renamed DAO");
+        assert_eq!(saved.security.jwt_secret, "secret-from-file-0123456789abcdef");
+        assert_eq!(manager.get_config().security.jwt_secret, "secret-from-env-0123456789abcdef");
+    }
+    
+    #[tokio::test]
+    async fn test_watch_reloads_changed_file() {
+        let dir = tempdir().unwrap();
+        let config_path = dir.path().join("config.json");
+        let config_path_str = config_path.to_str().unwrap();
+        
+        let mut config = valid_config();
+        config.api.rate_limit_per_minute = Some(60);
+        std::fs::write(&config_path, serde_json::to_string(&config).unwrap()).unwrap();
+        
+        let manager = ConfigManager::new(config_path_str).unwrap();
+        let mut changes = manager.subscribe();
+        let watcher = manager.watch().unwrap();
+        
+        config.api.rate_limit_per_minute = Some(120);
+        std::fs::write(&config_path, serde_json::to_string(&config).unwrap()).unwrap();
+        
+        tokio::time::timeout(Duration::from_secs(10), changes.changed())
+            .await
+            .expect("watcher did not pick up the change")
+            .unwrap();
+        assert_eq!(changes.borrow_and_update().api.rate_limit_per_minute, Some(120));
+        assert_eq!(manager.get_config().api.rate_limit_per_minute, Some(120));
+        
+        // An invalid file is ignored and the last good configuration kept
+        std::fs::write(&config_path, "{ not json").unwrap();
+        assert!(manager.reload().is_err());
+        assert_eq!(manager.get_config().api.rate_limit_per_minute, Some(120));
+        
+        watcher.abort();
+    }
+    
     #[test]
     fn test_get_section() {
         let manager = ConfigManager::with_defaults("test_config.json");
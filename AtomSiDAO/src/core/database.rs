@@ -1,27 +1,296 @@
 //! Database module for AtomSi DAO
 //!
 //! This module provides database connectivity and operations for AtomSi DAO.
+//!
+//! [`Database`] has two sets of query methods:
+//!
+//! - `execute`, `query`, `query_opt`, `query_one` and `transaction` take
+//!   tokio-postgres parameters and rows, and run on PostgreSQL only.
+//! - `execute_any`, `query_any`, `query_opt_any` and `query_one_any` take
+//!   [`SqlValue`] parameters and return [`DbRow`]s, and run on PostgreSQL and
+//!   SQLite alike. Statements use `$1`-style placeholders, which are
+//!   rewritten for SQLite, and must otherwise stick to SQL both accept (no
+//!   `::` casts or JSONB operators).
 
 use crate::config::DatabaseConfig;
 use crate::core::error::{DaoError, Result};
+use crate::database::{DatabaseManager, DatabaseType};
+use bytes::BytesMut;
+use chrono::{DateTime, Utc};
 use deadpool_postgres::{Config, Pool, PoolConfig, Runtime};
+use serde::de::DeserializeOwned;
+use sqlx::sqlite::{SqliteArguments, SqliteRow};
+use sqlx::Row as _;
 use std::sync::Arc;
+use tokio_postgres::types::{to_sql_checked, IsNull, ToSql, Type};
 use tokio_postgres::NoTls;
 
+/// Which pool a statement should run against
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryRoute {
+    /// Read-only statements, served by the replica when one is configured
+    Read,
+    /// Statements that write, or reads that must observe the latest writes
+    Write,
+}
+
+/// A parameter for the portable query methods
+#[derive(Debug, Clone, PartialEq)]
+pub enum SqlValue {
+    Null,
+    Text(String),
+    Int(i64),
+    /// JSONB on PostgreSQL, text on SQLite
+    Json(serde_json::Value),
+    /// TIMESTAMPTZ on PostgreSQL, RFC 3339 text on SQLite
+    Timestamp(DateTime<Utc>),
+}
+
+impl From<&str> for SqlValue {
+    fn from(value: &str) -> Self {
+        SqlValue::Text(value.to_string())
+    }
+}
+
+impl From<String> for SqlValue {
+    fn from(value: String) -> Self {
+        SqlValue::Text(value)
+    }
+}
+
+impl From<i64> for SqlValue {
+    fn from(value: i64) -> Self {
+        SqlValue::Int(value)
+    }
+}
+
+impl From<serde_json::Value> for SqlValue {
+    fn from(value: serde_json::Value) -> Self {
+        SqlValue::Json(value)
+    }
+}
+
+impl From<DateTime<Utc>> for SqlValue {
+    fn from(value: DateTime<Utc>) -> Self {
+        SqlValue::Timestamp(value)
+    }
+}
+
+impl<T: Into<SqlValue>> From<Option<T>> for SqlValue {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(SqlValue::Null, Into::into)
+    }
+}
+
+impl ToSql for SqlValue {
+    fn to_sql(&self, ty: &Type, out: &mut BytesMut) -> std::result::Result<IsNull, Box<dyn std::error::Error + Sync + Send>> {
+        match self {
+            SqlValue::Null => Ok(IsNull::Yes),
+            SqlValue::Text(value) => value.to_sql(ty, out),
+            SqlValue::Int(value) if *ty == Type::INT4 => i32::try_from(*value)?.to_sql(ty, out),
+            SqlValue::Int(value) => value.to_sql(ty, out),
+            SqlValue::Json(value) => value.to_sql(ty, out),
+            SqlValue::Timestamp(value) => value.to_sql(ty, out),
+        }
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        <String as ToSql>::accepts(ty)
+            || <i64 as ToSql>::accepts(ty)
+            || *ty == Type::INT4
+            || <serde_json::Value as ToSql>::accepts(ty)
+            || <DateTime<Utc> as ToSql>::accepts(ty)
+    }
+
+    to_sql_checked!();
+}
+
+/// A row returned by the portable query methods
+pub enum DbRow {
+    Postgres(tokio_postgres::Row),
+    Sqlite(SqliteRow),
+}
+
+impl DbRow {
+    /// Get a text column
+    pub fn text(&self, column: &str) -> Result<String> {
+        match self {
+            DbRow::Postgres(row) => row.try_get(column).map_err(|e| column_error(column, e)),
+            DbRow::Sqlite(row) => row.try_get(column).map_err(|e| column_error(column, e)),
+        }
+    }
+
+    /// Get an integer column
+    pub fn int(&self, column: &str) -> Result<i64> {
+        match self {
+            DbRow::Postgres(row) => row.try_get(column).map_err(|e| column_error(column, e)),
+            DbRow::Sqlite(row) => row.try_get(column).map_err(|e| column_error(column, e)),
+        }
+    }
+
+    /// Get a JSON column, stored as JSONB on PostgreSQL and text on SQLite
+    pub fn json<T: DeserializeOwned>(&self, column: &str) -> Result<T> {
+        let value: serde_json::Value = match self {
+            DbRow::Postgres(row) => row.try_get(column).map_err(|e| column_error(column, e))?,
+            DbRow::Sqlite(row) => {
+                let text: String = row.try_get(column).map_err(|e| column_error(column, e))?;
+                serde_json::from_str(&text).map_err(|e| column_error(column, e))?
+            }
+        };
+
+        serde_json::from_value(value).map_err(|e| column_error(column, e))
+    }
+}
+
+fn column_error(column: &str, error: impl std::fmt::Display) -> DaoError {
+    DaoError::DatabaseError(format!("Failed to read column {}: {}", column, error))
+}
+
+/// Escape `%`, `_` and `\` for use in a `LIKE ... ESCAPE '\'` pattern
+pub fn escape_like(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Rewrite `$1`-style placeholders as SQLite's `?1`, leaving string
+/// literals alone
+fn sqlite_placeholders(query: &str) -> String {
+    let mut translated = String::with_capacity(query.len());
+    let mut in_string = false;
+    let mut chars = query.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => in_string = !in_string,
+            '$' if !in_string && chars.peek().map_or(false, char::is_ascii_digit) => {
+                translated.push('?');
+                continue;
+            }
+            _ => {}
+        }
+        translated.push(c);
+    }
+
+    translated
+}
+
+/// Bind portable parameters to a SQLite statement
+fn bind_sqlite<'q>(
+    mut query: sqlx::query::Query<'q, sqlx::Sqlite, SqliteArguments<'q>>,
+    params: &[SqlValue],
+) -> sqlx::query::Query<'q, sqlx::Sqlite, SqliteArguments<'q>> {
+    for param in params {
+        query = match param {
+            SqlValue::Null => query.bind(None::<String>),
+            SqlValue::Text(value) => query.bind(value.clone()),
+            SqlValue::Int(value) => query.bind(*value),
+            SqlValue::Json(value) => query.bind(value.to_string()),
+            SqlValue::Timestamp(value) => query.bind(*value),
+        };
+    }
+    query
+}
+
+/// Where statements run
+#[derive(Clone)]
+enum Backend {
+    /// tokio-postgres pools, serving both sets of methods
+    Postgres {
+        /// Connection pool for the primary
+        pool: Arc<Pool>,
+        /// Connection pool for the read replica, if configured
+        read_pool: Option<Arc<Pool>>,
+    },
+    /// A SQLite [`DatabaseManager`], serving the portable methods only
+    Sqlite(DatabaseManager),
+}
+
 /// Database connection pool
+///
+/// Writes (`execute`, `transaction`) always go to the primary. Read-only
+/// statements issued through `query` and `query_one` are routed to the read
+/// replica when `database.read_replica` is configured, and fall back to the
+/// primary otherwise. Replica reads may lag the primary by the replication
+/// delay; `query_opt` always reads from the primary so that existence checks
+/// made right before a write see the latest state. The portable `*_any`
+/// methods route the same way.
 #[derive(Clone)]
 pub struct Database {
-    /// Connection pool
-    pool: Arc<Pool>,
+    backend: Backend,
 }
 
 impl Database {
     /// Connect to the database
     pub async fn connect(config: &DatabaseConfig) -> Result<Self> {
+        let pool = Self::create_pool(&config.url, config.max_connections).await?;
+        
+        let read_pool = match &config.read_replica {
+            Some(replica) => {
+                let max_connections = replica.max_connections.unwrap_or(config.max_connections);
+                Some(Arc::new(Self::create_pool(&replica.url, max_connections).await?))
+            }
+            None => None,
+        };
+        
+        Ok(Self::postgres(Arc::new(pool), read_pool))
+    }
+    
+    /// Use the database of a [`DatabaseManager`]
+    ///
+    /// On SQLite, statements run on the manager's pool, so a reconnect by the
+    /// manager is picked up, and only the portable methods are available. On
+    /// PostgreSQL a tokio-postgres pool is opened on the same database.
+    pub async fn from_manager(manager: &DatabaseManager) -> Result<Self> {
+        match manager.db_type() {
+            DatabaseType::SQLite => Ok(Self {
+                backend: Backend::Sqlite(manager.clone()),
+            }),
+            DatabaseType::Postgres => {
+                let pool = Self::create_pool(&manager.postgres_url(), manager.pool_size()).await?;
+                Ok(Self::postgres(Arc::new(pool), None))
+            }
+        }
+    }
+    
+    /// Open an in-memory SQLite database with every migration applied
+    #[cfg(test)]
+    pub(crate) async fn migrated_sqlite() -> Self {
+        let mut db_config = crate::config::default().database;
+        db_config.db_type = "sqlite".to_string();
+        db_config.sqlite_path = Some("sqlite::memory:".to_string());
+        // Every connection to `sqlite::memory:` opens a separate database
+        db_config.pool_size = 1;
+        let manager = DatabaseManager::new(&db_config).await.unwrap();
+        manager.migrate().await.unwrap();
+        
+        Self::from_manager(&manager).await.unwrap()
+    }
+    
+    fn postgres(pool: Arc<Pool>, read_pool: Option<Arc<Pool>>) -> Self {
+        Self {
+            backend: Backend::Postgres { pool, read_pool },
+        }
+    }
+    
+    /// Get the kind of database statements run on
+    pub fn db_type(&self) -> DatabaseType {
+        match self.backend {
+            Backend::Postgres { .. } => DatabaseType::Postgres,
+            Backend::Sqlite(_) => DatabaseType::SQLite,
+        }
+    }
+    
+    /// Create a connection pool for the given URL and verify it can be reached
+    async fn create_pool(db_url: &str, max_connections: u32) -> Result<Pool> {
         let mut pg_config = Config::new();
         
         // Parse database URL and set connection parameters
-        let db_url = &config.url;
         let parsed_url = url::Url::parse(db_url)
             .map_err(|e| DaoError::DatabaseError(format!("Invalid database URL: {}", e)))?;
         
@@ -47,7 +316,7 @@ impl Database {
         pg_config.password = Some(password.to_string());
         
         // Configure connection pool
-        let pool_config = PoolConfig::new(config.max_connections as usize);
+        let pool_config = PoolConfig::new(max_connections as usize);
         pg_config.pool = pool_config;
         
         // Create connection pool
@@ -66,20 +335,183 @@ impl Database {
             .await
             .map_err(|e| DaoError::DatabaseError(format!("Failed to query database: {}", e)))?;
         
-        Ok(Self {
-            pool: Arc::new(pool),
-        })
+        Ok(pool)
     }
     
-    /// Get a connection from the pool
+    /// Get a handle that sends every statement to the primary
+    ///
+    /// Use this for read-modify-write sequences, where a lagging replica
+    /// could otherwise hand back a stale row that is then written over the
+    /// newer one.
+    pub fn primary(&self) -> Self {
+        match &self.backend {
+            Backend::Postgres { pool, .. } => Self::postgres(pool.clone(), None),
+            Backend::Sqlite(_) => self.clone(),
+        }
+    }
+    
+    /// Check whether read-only queries are served by a replica
+    pub fn has_read_replica(&self) -> bool {
+        matches!(&self.backend, Backend::Postgres { read_pool: Some(_), .. })
+    }
+    
+    /// Get the PostgreSQL pool a statement with the given route runs against
+    fn pool_for(&self, route: QueryRoute) -> Result<&Arc<Pool>> {
+        match (&self.backend, route) {
+            (Backend::Postgres { read_pool: Some(read_pool), .. }, QueryRoute::Read) => Ok(read_pool),
+            (Backend::Postgres { pool, .. }, _) => Ok(pool),
+            (Backend::Sqlite(_), _) => Err(DaoError::DatabaseError(
+                "This operation requires PostgreSQL; SQLite supports only the portable query methods".to_string(),
+            )),
+        }
+    }
+    
+    /// Get a connection from the primary pool (PostgreSQL only)
     pub async fn get_client(&self) -> Result<deadpool_postgres::Client> {
-        self.pool
+        self.get_routed_client(QueryRoute::Write).await
+    }
+    
+    /// Get a connection for read-only queries (replica if configured, primary
+    /// otherwise; PostgreSQL only)
+    pub async fn get_read_client(&self) -> Result<deadpool_postgres::Client> {
+        self.get_routed_client(QueryRoute::Read).await
+    }
+    
+    /// Get a connection from the pool for the given route
+    async fn get_routed_client(&self, route: QueryRoute) -> Result<deadpool_postgres::Client> {
+        self.pool_for(route)?
             .get()
             .await
             .map_err(|e| DaoError::DatabaseError(format!("Failed to get database connection: {}", e)))
     }
     
-    /// Execute a query and return the number of rows affected
+    /// Get the SQLite pool, if statements run on SQLite
+    fn sqlite_pool(&self) -> Result<Option<sqlx::SqlitePool>> {
+        match &self.backend {
+            Backend::Postgres { .. } => Ok(None),
+            Backend::Sqlite(manager) => manager
+                .sqlite_pool()
+                .map(Some)
+                .map_err(|e| DaoError::DatabaseError(e.to_string())),
+        }
+    }
+    
+    /// Execute a portable statement and return the number of rows affected
+    pub async fn execute_any(&self, query: &str, params: &[SqlValue]) -> Result<u64> {
+        match self.sqlite_pool()? {
+            Some(pool) => {
+                let query = sqlite_placeholders(query);
+                bind_sqlite(sqlx::query(&query), params)
+                    .execute(&pool)
+                    .await
+                    .map(|result| result.rows_affected())
+                    .map_err(|e| DaoError::DatabaseError(format!("Failed to execute query: {}", e)))
+            }
+            None => self.execute(query, &postgres_params(params)).await,
+        }
+    }
+    
+    /// Execute a portable read-only query and return the rows
+    pub async fn query_any(&self, query: &str, params: &[SqlValue]) -> Result<Vec<DbRow>> {
+        match self.sqlite_pool()? {
+            Some(pool) => {
+                let query = sqlite_placeholders(query);
+                let rows = bind_sqlite(sqlx::query(&query), params)
+                    .fetch_all(&pool)
+                    .await
+                    .map_err(|e| DaoError::DatabaseError(format!("Failed to execute query: {}", e)))?;
+                Ok(rows.into_iter().map(DbRow::Sqlite).collect())
+            }
+            None => {
+                let rows = self.query(query, &postgres_params(params)).await?;
+                Ok(rows.into_iter().map(DbRow::Postgres).collect())
+            }
+        }
+    }
+    
+    /// Execute a portable query against the primary and return the first
+    /// row, if any
+    pub async fn query_opt_any(&self, query: &str, params: &[SqlValue]) -> Result<Option<DbRow>> {
+        match self.sqlite_pool()? {
+            Some(pool) => {
+                let query = sqlite_placeholders(query);
+                let row = bind_sqlite(sqlx::query(&query), params)
+                    .fetch_optional(&pool)
+                    .await
+                    .map_err(|e| DaoError::DatabaseError(format!("Failed to execute query: {}", e)))?;
+                Ok(row.map(DbRow::Sqlite))
+            }
+            None => Ok(self.query_opt(query, &postgres_params(params)).await?.map(DbRow::Postgres)),
+        }
+    }
+    
+    /// Execute a portable read-only query and return the first row
+    pub async fn query_one_any(&self, query: &str, params: &[SqlValue]) -> Result<DbRow> {
+        match self.sqlite_pool()? {
+            Some(pool) => {
+                let query = sqlite_placeholders(query);
+                let row = bind_sqlite(sqlx::query(&query), params)
+                    .fetch_one(&pool)
+                    .await
+                    .map_err(|e| DaoError::DatabaseError(format!("Failed to execute query: {}", e)))?;
+                Ok(DbRow::Sqlite(row))
+            }
+            None => Ok(DbRow::Postgres(self.query_one(query, &postgres_params(params)).await?)),
+        }
+    }
+    
+    /// Execute portable statements in a single transaction
+    ///
+    /// Either every statement is applied or, if one fails, none are. Returns
+    /// the number of rows each statement affected.
+    pub async fn execute_batch_any(&self, statements: &[(&str, Vec<SqlValue>)]) -> Result<Vec<u64>> {
+        let mut affected = Vec::with_capacity(statements.len());
+        
+        match self.sqlite_pool()? {
+            Some(pool) => {
+                let mut tx = pool
+                    .begin()
+                    .await
+                    .map_err(|e| DaoError::DatabaseError(format!("Failed to start transaction: {}", e)))?;
+                
+                for (query, params) in statements {
+                    let query = sqlite_placeholders(query);
+                    let result = bind_sqlite(sqlx::query(&query), params)
+                        .execute(&mut *tx)
+                        .await
+                        .map_err(|e| DaoError::DatabaseError(format!("Failed to execute query: {}", e)))?;
+                    affected.push(result.rows_affected());
+                }
+                
+                tx.commit()
+                    .await
+                    .map_err(|e| DaoError::DatabaseError(format!("Failed to commit transaction: {}", e)))?;
+            }
+            None => {
+                let mut client = self.get_client().await?;
+                let tx = client
+                    .transaction()
+                    .await
+                    .map_err(|e| DaoError::DatabaseError(format!("Failed to start transaction: {}", e)))?;
+                
+                for (query, params) in statements {
+                    let rows = tx
+                        .execute(*query, &postgres_params(params))
+                        .await
+                        .map_err(|e| DaoError::DatabaseError(format!("Failed to execute query: {}", e)))?;
+                    affected.push(rows);
+                }
+                
+                tx.commit()
+                    .await
+                    .map_err(|e| DaoError::DatabaseError(format!("Failed to commit transaction: {}", e)))?;
+            }
+        }
+        
+        Ok(affected)
+    }
+    
+    /// Execute a query and return the number of rows affected (PostgreSQL only)
     pub async fn execute(&self, query: &str, params: &[&(dyn tokio_postgres::types::ToSql + Sync)]) -> Result<u64> {
         let client = self.get_client().await?;
         
@@ -89,9 +521,9 @@ impl Database {
             .map_err(|e| DaoError::DatabaseError(format!("Failed to execute query: {}", e)))
     }
     
-    /// Execute a query and return the rows
+    /// Execute a read-only query and return the rows (PostgreSQL only)
     pub async fn query(&self, query: &str, params: &[&(dyn tokio_postgres::types::ToSql + Sync)]) -> Result<Vec<tokio_postgres::Row>> {
-        let client = self.get_client().await?;
+        let client = self.get_read_client().await?;
         
         client
             .query(query, params)
@@ -99,7 +531,8 @@ impl Database {
             .map_err(|e| DaoError::DatabaseError(format!("Failed to execute query: {}", e)))
     }
     
-    /// Execute a query and return the first row, if any
+    /// Execute a query against the primary and return the first row, if any
+    /// (PostgreSQL only)
     pub async fn query_opt(&self, query: &str, params: &[&(dyn tokio_postgres::types::ToSql + Sync)]) -> Result<Option<tokio_postgres::Row>> {
         let client = self.get_client().await?;
         
@@ -109,9 +542,9 @@ impl Database {
             .map_err(|e| DaoError::DatabaseError(format!("Failed to execute query: {}", e)))
     }
     
-    /// Execute a query and return the first row
+    /// Execute a read-only query and return the first row (PostgreSQL only)
     pub async fn query_one(&self, query: &str, params: &[&(dyn tokio_postgres::types::ToSql + Sync)]) -> Result<tokio_postgres::Row> {
-        let client = self.get_client().await?;
+        let client = self.get_read_client().await?;
         
         client
             .query_one(query, params)
@@ -119,7 +552,7 @@ impl Database {
             .map_err(|e| DaoError::DatabaseError(format!("Failed to execute query: {}", e)))
     }
     
-    /// Execute a transaction
+    /// Execute a transaction (PostgreSQL only)
     pub async fn transaction<F, R>(&self, f: F) -> Result<R>
     where
         F: for<'a> FnOnce(&'a mut deadpool_postgres::Transaction<'_>) -> futures_util::future::BoxFuture<'a, Result<R>>,
@@ -141,6 +574,11 @@ impl Database {
     }
 }
 
+/// Borrow portable parameters as tokio-postgres parameters
+fn postgres_params(params: &[SqlValue]) -> Vec<&(dyn ToSql + Sync)> {
+    params.iter().map(|param| param as &(dyn ToSql + Sync)).collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -173,4 +611,67 @@ mod tests {
             _ => panic!("Expected DatabaseError for invalid URL"),
         }
     }
+    
+    fn lazy_pool(db_name: &str) -> Pool {
+        let mut pg_config = Config::new();
+        pg_config.host = Some("localhost".to_string());
+        pg_config.dbname = Some(db_name.to_string());
+        pg_config
+            .create_pool(Some(Runtime::Tokio1), NoTls)
+            .unwrap()
+    }
+    
+    #[test]
+    fn test_reads_target_replica_when_configured() {
+        let primary = Arc::new(lazy_pool("primary"));
+        let replica = Arc::new(lazy_pool("replica"));
+        
+        let database = Database::postgres(primary.clone(), Some(replica.clone()));
+        
+        assert!(database.has_read_replica());
+        assert!(Arc::ptr_eq(database.pool_for(QueryRoute::Read).unwrap(), &replica));
+        assert!(Arc::ptr_eq(database.pool_for(QueryRoute::Write).unwrap(), &primary));
+        
+        let primary_only = database.primary();
+        assert!(!primary_only.has_read_replica());
+        assert!(Arc::ptr_eq(primary_only.pool_for(QueryRoute::Read).unwrap(), &primary));
+    }
+    
+    #[test]
+    fn test_reads_fall_back_to_primary_without_replica() {
+        let primary = Arc::new(lazy_pool("primary"));
+        
+        let database = Database::postgres(primary.clone(), None);
+        
+        assert!(!database.has_read_replica());
+        assert!(Arc::ptr_eq(database.pool_for(QueryRoute::Read).unwrap(), &primary));
+        assert!(Arc::ptr_eq(database.pool_for(QueryRoute::Write).unwrap(), &primary));
+    }
+    
+    #[test]
+    fn test_sqlite_placeholders() {
+        assert_eq!(
+            sqlite_placeholders("UPDATE proposals SET data = $1 WHERE id = $12 AND note = 'costs $5'"),
+            "UPDATE proposals SET data = ?1 WHERE id = ?12 AND note = 'costs $5'"
+        );
+        assert_eq!(sqlite_placeholders("SELECT 'it''s $1', $2"), "SELECT 'it''s $1', ?2");
+        assert_eq!(escape_like("50%_off\\"), "50\\%\\_off\\\\");
+    }
+    
+    #[tokio::test]
+    async fn test_sqlite_database_rejects_postgres_only_methods() {
+        let mut db_config = config::default().database;
+        db_config.db_type = "sqlite".to_string();
+        db_config.sqlite_path = Some("sqlite::memory:".to_string());
+        db_config.pool_size = 1;
+        let manager = DatabaseManager::new(&db_config).await.unwrap();
+        let database = Database::from_manager(&manager).await.unwrap();
+        
+        assert_eq!(database.db_type(), DatabaseType::SQLite);
+        assert!(matches!(database.execute("SELECT 1", &[]).await, Err(DaoError::DatabaseError(_))));
+        
+        let row = database.query_one_any("SELECT $1 AS name, $2 AS n", &["atom".into(), 7i64.into()]).await.unwrap();
+        assert_eq!(row.text("name").unwrap(), "atom");
+        assert_eq!(row.int("n").unwrap(), 7);
+    }
 } 
\ No newline at end of file
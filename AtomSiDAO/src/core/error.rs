@@ -45,6 +45,15 @@ pub enum DaoError {
     #[error("Proposal error: {0}")]
     ProposalError(String),
     
+    /// Proposal state change not allowed by the proposal state machine
+    #[error("Invalid proposal transition from {from:?} to {to:?}")]
+    InvalidTransition {
+        /// Current state
+        from: crate::proposals::ProposalState,
+        /// Requested state
+        to: crate::proposals::ProposalState,
+    },
+    
     /// Identity error
     #[error("Identity error: {0}")]
     IdentityError(String),
@@ -61,6 +70,13 @@ pub enum DaoError {
     #[error("Operation not supported: {0}")]
     NotSupported(String),
     
+    /// Too many failed attempts; retry once the lockout ends
+    #[error("Too many failed attempts, retry after {retry_after_seconds} seconds")]
+    RateLimitError {
+        /// Seconds until the lockout ends
+        retry_after_seconds: u64,
+    },
+    
     /// External service error
     #[error("External service error: {0}")]
     ExternalServiceError(String),
@@ -70,6 +86,16 @@ pub enum DaoError {
     InternalError(String),
 }
 
+impl DaoError {
+    /// Error for a call into a feature module disabled in the `features` config
+    pub fn feature_disabled(feature: &str) -> Self {
+        Self::NotSupported(format!(
+            "The {} module is disabled (set features.{}_enabled = true to enable it)",
+            feature, feature
+        ))
+    }
+}
+
 impl From<sqlx::Error> for DaoError {
     fn from(error: sqlx::Error) -> Self {
         Self::DatabaseError(error.to_string())
@@ -83,4 +109,21 @@ impl<E: std::error::Error> From<deadpool_postgres::PoolError<E>> for DaoError {
 }
 
 /// Result type for DAO operations
-pub type Result<T> = std::result::Result<T, DaoError>; 
\ No newline at end of file
+pub type Result<T> = std::result::Result<T, DaoError>; 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_feature_disabled_names_the_setting() {
+        let error = DaoError::feature_disabled("treasury");
+        
+        assert!(matches!(error, DaoError::NotSupported(_)));
+        assert_eq!(
+            error.to_string(),
+            "Operation not supported: The treasury module is disabled \
+             (set features.treasury_enabled = true to enable it)"
+        );
+    }
+}
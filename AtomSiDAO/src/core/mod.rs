@@ -4,19 +4,30 @@
 
 mod database;
 mod error;
+mod pagination;
+mod seed;
+mod types;
 
-pub use database::Database;
+pub use database::{escape_like, Database, DbRow, SqlValue};
 pub use error::{DaoError, Result};
+pub use pagination::{Cursor, CursorDirection, Page, PageRequest};
+pub(crate) use pagination::fetch_page;
+pub use seed::SeedSummary;
+pub use types::{Address, Symbol, SYSTEM_ACTOR};
 
 use crate::{
-    blockchain::BlockchainAdapter,
-    config::Config,
+    blockchain::{BlockchainAdapter, ExecutionLimiter},
+    config::{Config, ConfigManager},
     governance::GovernanceEngine,
     identity::IdentityManager,
     proposals::ProposalManager,
+    security::AuditLog,
     token::TokenManager,
     treasury::TreasuryManager,
 };
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tracing::info;
 
 /// Main DAO structure
 pub struct Dao {
@@ -29,14 +40,23 @@ pub struct Dao {
     /// Token manager
     token_manager: TokenManager,
     
-    /// Treasury manager
-    treasury_manager: TreasuryManager,
+    /// Treasury manager (absent when the treasury feature is disabled)
+    treasury_manager: Option<TreasuryManager>,
     
     /// Proposal manager
     proposal_manager: ProposalManager,
     
     /// Identity manager
     identity_manager: IdentityManager,
+    
+    /// Limiter shared by all on-chain executions
+    execution_limiter: ExecutionLimiter,
+    
+    /// Hash-chained log of admin actions
+    audit_log: Arc<AuditLog>,
+    
+    /// Database handle
+    database: Database,
 }
 
 impl Dao {
@@ -46,18 +66,35 @@ impl Dao {
         blockchain: impl BlockchainAdapter + 'static,
         database: Database,
     ) -> Result<Self> {
+        // Share one execution limiter so the concurrency cap is global
+        let execution_limiter = ExecutionLimiter::from_config(&config.blockchain);
+        // and one audit log so admin actions form a single chain
+        let audit_log = Arc::new(AuditLog::new(database.clone(), &config.security.audit_shipping));
+        
         // Initialize managers
         let token_manager = TokenManager::new(&config, blockchain.clone(), database.clone())?;
-        let identity_manager = IdentityManager::new(&config, blockchain.clone(), database.clone())?;
-        let treasury_manager = TreasuryManager::new(&config, blockchain.clone(), database.clone())?;
-        let proposal_manager = ProposalManager::new(&config, blockchain.clone(), database.clone())?;
+        let identity_manager = IdentityManager::new(&config, blockchain.clone(), database.clone())?
+            .with_audit_log(audit_log.clone());
+        let treasury_manager = if config.features.treasury_enabled {
+            Some(
+                TreasuryManager::new(&config, blockchain.clone(), database.clone())?
+                    .with_execution_limiter(execution_limiter.clone())
+                    .with_audit_log(audit_log.clone()),
+            )
+        } else {
+            None
+        };
+        let proposal_manager = ProposalManager::new(&config, blockchain.clone(), database.clone())?
+            .with_execution_limiter(execution_limiter.clone())
+            .with_token_manager(token_manager.clone());
         let governance = GovernanceEngine::new(
             &config,
             blockchain,
-            database,
+            database.clone(),
             &token_manager,
             &proposal_manager,
-        )?;
+        )?
+        .with_identity_manager(identity_manager.clone());
         
         Ok(Self {
             config,
@@ -66,9 +103,20 @@ impl Dao {
             treasury_manager,
             proposal_manager,
             identity_manager,
+            execution_limiter,
+            audit_log,
+            database,
         })
     }
     
+    /// Apply executed `ParameterChange` proposals through a config manager
+    pub fn with_config_manager(self, config_manager: ConfigManager) -> Self {
+        Self {
+            proposal_manager: self.proposal_manager.with_config_manager(config_manager),
+            ..self
+        }
+    }
+    
     /// Get the DAO configuration
     pub fn config(&self) -> &Config {
         &self.config
@@ -85,8 +133,12 @@ impl Dao {
     }
     
     /// Get the treasury manager
-    pub fn treasury_manager(&self) -> &TreasuryManager {
-        &self.treasury_manager
+    ///
+    /// Fails with `NotSupported` when the treasury feature is disabled.
+    pub fn treasury_manager(&self) -> Result<&TreasuryManager> {
+        self.treasury_manager
+            .as_ref()
+            .ok_or_else(|| DaoError::feature_disabled("treasury"))
     }
     
     /// Get the proposal manager
@@ -98,6 +150,98 @@ impl Dao {
     pub fn identity_manager(&self) -> &IdentityManager {
         &self.identity_manager
     }
+    
+    /// Get the admin action log
+    pub fn audit_log(&self) -> &AuditLog {
+        &self.audit_log
+    }
+    
+    /// Start shipping admin action log entries, if enabled
+    ///
+    /// Must be called from within a Tokio runtime.
+    pub fn spawn_audit_shipper(&self) -> Option<JoinHandle<()>> {
+        self.audit_log.shipper()?.spawn_shipper()
+    }
+    
+    /// Start flushing batched reputation changes, if batching is enabled
+    ///
+    /// Must be called from within a Tokio runtime.
+    pub fn spawn_reputation_flusher(&self) -> Option<JoinHandle<()>> {
+        self.identity_manager.spawn_reputation_flusher()
+    }
+    
+    /// Write state still held in memory before the process exits
+    ///
+    /// Flushes reputation changes that are batched but not yet written.
+    pub async fn shutdown(&self) -> Result<()> {
+        let flushed = self.identity_manager.flush_reputation().await?;
+        info!("Flushed pending reputation for {} members", flushed);
+        
+        Ok(())
+    }
+    
+    /// Start reconciling on-chain executions and expiring stale treasury
+    /// transactions, unless the treasury is disabled or the interval is 0
+    ///
+    /// Must be called from within a Tokio runtime.
+    pub fn spawn_treasury_maintenance(&self) -> Option<JoinHandle<()>> {
+        self.treasury_manager.as_ref()?.spawn_maintenance()
+    }
+    
+    /// Number of on-chain executions currently in flight
+    pub fn executions_in_flight(&self) -> usize {
+        self.execution_limiter.in_flight()
+    }
+    
+    /// Populate the database with sample members, a token, balances,
+    /// proposals, and treasury transactions for local development
+    ///
+    /// Refuses to run against a production or non-empty database unless
+    /// `force` is set.
+    pub async fn seed_sample_data(&self, force: bool) -> Result<SeedSummary> {
+        let is_empty = self
+            .database
+            .primary()
+            .query_opt("SELECT 1 FROM members LIMIT 1", &[])
+            .await?
+            .is_none();
+        seed::check_seed_allowed(self.config.dao.is_production(), is_empty, force)?;
+        
+        let treasury_manager = self.treasury_manager()?;
+        let treasury = treasury_manager.get_treasury_address().await?;
+        let data = seed::sample_data(&self.config.dao.governance_token, &treasury)?;
+        let mut summary = SeedSummary::default();
+        
+        for member in &data.members {
+            self.identity_manager.save_member(member, SYSTEM_ACTOR).await?;
+            summary.members += 1;
+        }
+        
+        // Start from zero supply so the total matches the minted balances
+        if !self.token_manager.token_exists(&data.token_symbol).await? {
+            self.token_manager
+                .create_token(&data.token_name, &data.token_symbol, 0)
+                .await?;
+            summary.tokens += 1;
+        }
+        
+        for (address, amount) in &data.balances {
+            self.token_manager.mint(&data.token_symbol, address, *amount).await?;
+            summary.balances += 1;
+        }
+        
+        for proposal in &data.proposals {
+            self.proposal_manager.save_proposal(proposal, SYSTEM_ACTOR).await?;
+            summary.proposals += 1;
+        }
+        
+        for transaction in &data.transactions {
+            treasury_manager.save_transaction(transaction, SYSTEM_ACTOR).await?;
+            summary.transactions += 1;
+        }
+        
+        Ok(summary)
+    }
 }
 
 /// Builder for DAO instances
@@ -105,6 +249,7 @@ pub struct DaoBuilder {
     config: Option<Config>,
     blockchain: Option<Box<dyn BlockchainAdapter>>,
     database: Option<Database>,
+    config_manager: Option<ConfigManager>,
 }
 
 impl DaoBuilder {
@@ -114,6 +259,7 @@ impl DaoBuilder {
             config: None,
             blockchain: None,
             database: None,
+            config_manager: None,
         }
     }
     
@@ -135,13 +281,26 @@ impl DaoBuilder {
         self
     }
     
+    /// Take the configuration from a config manager, which also applies
+    /// executed `ParameterChange` proposals
+    pub fn with_config_manager(mut self, config_manager: ConfigManager) -> Self {
+        self.config = Some(config_manager.get_config());
+        self.config_manager = Some(config_manager);
+        self
+    }
+    
     /// Build the DAO instance
     pub fn build(self) -> Result<Dao> {
         let config = self.config.ok_or(DaoError::MissingConfig)?;
         let blockchain = self.blockchain.ok_or(DaoError::MissingBlockchainAdapter)?;
         let database = self.database.ok_or(DaoError::MissingDatabase)?;
         
-        Dao::new(config, blockchain, database)
+        let dao = Dao::new(config, blockchain, database)?;
+        
+        Ok(match self.config_manager {
+            Some(config_manager) => dao.with_config_manager(config_manager),
+            None => dao,
+        })
     }
 }
 
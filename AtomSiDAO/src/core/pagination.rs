@@ -0,0 +1,280 @@
+//! Cursor pagination for AtomSi DAO
+//!
+//! Listings are ordered newest first by `(created_at, id)`. Offset pages
+//! drift when rows are inserted between requests, repeating or skipping
+//! rows; a [`Cursor`] instead names the row a page ended at, and the next
+//! page continues strictly past it, so concurrent inserts never shift what
+//! a client has already seen.
+//!
+//! Cursors are opaque to clients: [`Cursor::encode`] produces the token
+//! returned as `next_cursor`/`prev_cursor`, and [`Cursor::decode`] reads it
+//! back from the `cursor` query parameter.
+
+use crate::core::{Database, DaoError, Result, SqlValue};
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+
+/// Which way a cursor continues from the row it names
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CursorDirection {
+    /// Older rows (the next page)
+    After,
+    /// Newer rows (the previous page)
+    Before,
+}
+
+/// Position in a `(created_at, id)` ordered listing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cursor {
+    /// Creation time of the row the cursor names
+    pub created_at: DateTime<Utc>,
+    /// ID of the row the cursor names
+    pub id: String,
+    /// Which way to continue from it
+    pub direction: CursorDirection,
+}
+
+impl Cursor {
+    /// Create a cursor at a row
+    ///
+    /// `created_at` must be the value the row was stored with; PostgreSQL
+    /// rounds it to microseconds the same way on insert and in comparisons.
+    pub fn new(created_at: DateTime<Utc>, id: impl Into<String>, direction: CursorDirection) -> Self {
+        Self {
+            created_at,
+            id: id.into(),
+            direction,
+        }
+    }
+
+    /// Encode the cursor as an opaque token
+    pub fn encode(&self) -> String {
+        let direction = match self.direction {
+            CursorDirection::After => 'a',
+            CursorDirection::Before => 'b',
+        };
+        let raw = format!("{}|{}|{}", direction, self.created_at.to_rfc3339(), self.id);
+        hex::encode(raw)
+    }
+
+    /// Decode a token produced by [`Cursor::encode`]
+    pub fn decode(token: &str) -> Result<Self> {
+        let invalid = || DaoError::InvalidParameter(format!("Invalid cursor: {}", token));
+
+        let raw = hex::decode(token).map_err(|_| invalid())?;
+        let raw = String::from_utf8(raw).map_err(|_| invalid())?;
+        let mut parts = raw.splitn(3, '|');
+        let direction = match parts.next() {
+            Some("a") => CursorDirection::After,
+            Some("b") => CursorDirection::Before,
+            _ => return Err(invalid()),
+        };
+        let created_at = parts
+            .next()
+            .and_then(|value| DateTime::parse_from_rfc3339(value).ok())
+            .ok_or_else(invalid)?
+            .with_timezone(&Utc);
+        let id = parts.next().filter(|id| !id.is_empty()).ok_or_else(invalid)?;
+
+        Ok(Self::new(created_at, id, direction))
+    }
+}
+
+/// A cursor page request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageRequest {
+    /// Where to continue from (the newest rows when unset)
+    pub cursor: Option<Cursor>,
+    /// Maximum rows in the page
+    pub limit: usize,
+}
+
+/// A page of a cursor listing
+#[derive(Debug, Clone)]
+pub struct Page<T> {
+    /// Rows, newest first
+    pub items: Vec<T>,
+    /// Cursor to the following (older) rows, if there are any
+    pub next_cursor: Option<Cursor>,
+    /// Cursor to the preceding (newer) rows, if there are any
+    pub prev_cursor: Option<Cursor>,
+}
+
+/// Load one page of the `data` column of `table`, newest first
+///
+/// `conditions` and `params` filter the rows, with placeholders numbered
+/// from `$1`; `key` returns a row's `(created_at, id)`, which must match the
+/// table's columns of the same name.
+///
+/// Portable: runs on PostgreSQL and SQLite.
+pub(crate) async fn fetch_page<T, F>(
+    database: &Database,
+    table: &str,
+    mut conditions: Vec<String>,
+    mut params: Vec<SqlValue>,
+    request: &PageRequest,
+    key: F,
+) -> Result<Page<T>>
+where
+    T: DeserializeOwned,
+    F: Fn(&T) -> (DateTime<Utc>, String),
+{
+    let limit = request.limit.max(1);
+    let backwards = matches!(&request.cursor, Some(cursor) if cursor.direction == CursorDirection::Before);
+
+    // Continue strictly past the cursor row; each placeholder is bound once
+    if let Some(cursor) = &request.cursor {
+        let op = if backwards { ">" } else { "<" };
+        let n = params.len();
+        conditions.push(format!(
+            "(created_at {op} ${} OR (created_at = ${} AND id {op} ${}))",
+            n + 1,
+            n + 2,
+            n + 3,
+            op = op,
+        ));
+        params.push(cursor.created_at.into());
+        params.push(cursor.created_at.into());
+        params.push(cursor.id.as_str().into());
+    }
+
+    let filter = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!(" WHERE {}", conditions.join(" AND "))
+    };
+    let order = if backwards { "ASC" } else { "DESC" };
+    // One extra row tells whether there is more past this page
+    let query = format!(
+        "SELECT data FROM {}{} ORDER BY created_at {order}, id {order} LIMIT {}",
+        table,
+        filter,
+        limit + 1,
+        order = order,
+    );
+
+    let rows = database.query_any(&query, &params).await?;
+    let mut items = rows
+        .iter()
+        .map(|row| row.json("data"))
+        .collect::<Result<Vec<T>>>()?;
+
+    let more = items.len() > limit;
+    items.truncate(limit);
+    if backwards {
+        items.reverse();
+    }
+
+    let cursor_at = |item: &T, direction| {
+        let (created_at, id) = key(item);
+        Cursor::new(created_at, id, direction)
+    };
+
+    // The extra row says whether there is more in the direction we read;
+    // there is always more the other way if we came from a cursor
+    let (more_newer, more_older) = if backwards {
+        (more, true)
+    } else {
+        (request.cursor.is_some(), more)
+    };
+
+    Ok(Page {
+        next_cursor: items
+            .last()
+            .filter(|_| more_older)
+            .map(|item| cursor_at(item, CursorDirection::After)),
+        prev_cursor: items
+            .first()
+            .filter(|_| more_newer)
+            .map(|item| cursor_at(item, CursorDirection::Before)),
+        items,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Item {
+        id: String,
+        created_at: DateTime<Utc>,
+    }
+
+    async fn insert(database: &Database, id: &str, created_at: DateTime<Utc>) {
+        let item = Item {
+            id: id.to_string(),
+            created_at,
+        };
+        database
+            .execute_any(
+                "INSERT INTO treasury_transactions (id, data, status, created_at) VALUES ($1, $2, 'Pending', $3)",
+                &[
+                    id.into(),
+                    serde_json::to_value(&item).unwrap().into(),
+                    created_at.into(),
+                ],
+            )
+            .await
+            .unwrap();
+    }
+
+    async fn page(database: &Database, cursor: Option<Cursor>, limit: usize) -> Page<Item> {
+        let request = PageRequest { cursor, limit };
+        fetch_page(database, "treasury_transactions", Vec::new(), Vec::new(), &request, |item: &Item| {
+            (item.created_at, item.id.clone())
+        })
+        .await
+        .unwrap()
+    }
+
+    fn ids(page: &Page<Item>) -> Vec<&str> {
+        page.items.iter().map(|item| item.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = Cursor::new(Utc::now(), "AIP|7", CursorDirection::Before);
+        assert_eq!(Cursor::decode(&cursor.encode()).unwrap(), cursor);
+
+        assert!(matches!(Cursor::decode("zz"), Err(DaoError::InvalidParameter(_))));
+        assert!(Cursor::decode(&hex::encode("c|2024-01-01T00:00:00Z|x")).is_err());
+        assert!(Cursor::decode(&hex::encode("a|yesterday|x")).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_inserts_mid_iteration_neither_repeat_nor_skip() {
+        let database = Database::migrated_sqlite().await;
+        let start = Utc::now() - Duration::hours(1);
+        // Two rows share a timestamp, so the ID breaks the tie
+        for (id, minutes) in [("a", 0), ("b", 1), ("c", 2), ("d", 2), ("e", 3)] {
+            insert(&database, id, start + Duration::minutes(minutes)).await;
+        }
+
+        let first = page(&database, None, 2).await;
+        assert_eq!(ids(&first), ["e", "d"]);
+        assert!(first.prev_cursor.is_none());
+
+        // New rows arrive while the client is reading; offset paging would
+        // now show "d" again
+        insert(&database, "f", start + Duration::minutes(4)).await;
+        insert(&database, "g", start + Duration::minutes(5)).await;
+
+        let second = page(&database, first.next_cursor.clone(), 2).await;
+        assert_eq!(ids(&second), ["c", "b"]);
+        let third = page(&database, second.next_cursor.clone(), 2).await;
+        assert_eq!(ids(&third), ["a"]);
+        assert!(third.next_cursor.is_none());
+
+        // Going back from the second page returns the first page, and then
+        // reaches the rows inserted meanwhile
+        let back = page(&database, second.prev_cursor.clone(), 2).await;
+        assert_eq!(ids(&back), ["e", "d"]);
+        let newest = page(&database, back.prev_cursor.clone(), 2).await;
+        assert_eq!(ids(&newest), ["g", "f"]);
+        assert!(newest.prev_cursor.is_none());
+        assert_eq!(newest.next_cursor.as_ref().map(|cursor| cursor.id.as_str()), Some("f"));
+    }
+}
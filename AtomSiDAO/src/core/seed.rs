@@ -0,0 +1,323 @@
+//! Sample data for local development and demos
+//!
+//! The fixtures cover every member role, proposal state, and the common
+//! treasury transaction states. They are generated up front so their
+//! consistency can be checked without a database.
+
+use super::error::{DaoError, Result};
+use super::types::{Address, Symbol};
+use crate::identity::{Member, MemberRole, MemberStatus};
+use crate::proposals::{Proposal, ProposalBuilder, ProposalState, ProposalType, ProposalVote, Sponsor, Vote};
+use crate::token::TokenAmount;
+use crate::treasury::{Transaction, TransactionBuilder, TransactionStatus};
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+/// Number of records inserted by a seed run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SeedSummary {
+    /// Members registered
+    pub members: usize,
+    /// Tokens created
+    pub tokens: usize,
+    /// Token balances minted
+    pub balances: usize,
+    /// Proposals saved
+    pub proposals: usize,
+    /// Treasury transactions saved
+    pub transactions: usize,
+}
+
+/// Generated fixture set
+pub(crate) struct SampleData {
+    pub members: Vec<Member>,
+    pub token_name: String,
+    pub token_symbol: Symbol,
+    pub balances: Vec<(Address, TokenAmount)>,
+    pub proposals: Vec<Proposal>,
+    pub transactions: Vec<Transaction>,
+}
+
+/// Refuse to seed production or non-empty databases unless forced
+pub(crate) fn check_seed_allowed(is_production: bool, is_empty: bool, force: bool) -> Result<()> {
+    if force {
+        return Ok(());
+    }
+
+    if is_production {
+        return Err(DaoError::NotSupported(
+            "Refusing to seed a production database without force".to_string(),
+        ));
+    }
+
+    if !is_empty {
+        return Err(DaoError::NotSupported(
+            "Refusing to seed a non-empty database without force".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Build the fixture set for the given governance token and treasury
+pub(crate) fn sample_data(token_symbol: &str, treasury: &Address) -> Result<SampleData> {
+    let token_symbol = Symbol::parse(token_symbol)?;
+    let now = Utc::now();
+
+    let roles = [
+        ("Alice", MemberRole::Admin, 800, 50_000),
+        ("Bob", MemberRole::Council, 600, 30_000),
+        ("Carol", MemberRole::Delegate, 250, 15_000),
+        ("Dave", MemberRole::Member, 40, 5_000),
+        ("Erin", MemberRole::Member, 10, 1_000),
+    ];
+
+    let mut members = Vec::new();
+    let mut balances = Vec::new();
+
+    for (i, (name, role, reputation, balance)) in roles.iter().enumerate() {
+        let address = Address::parse(&format!("0x{:040x}", 0xa70000 + i))?;
+
+        members.push(Member {
+            id: Uuid::new_v4().to_string(),
+            address: address.to_string(),
+            name: Some(name.to_string()),
+            role: *role,
+            status: MemberStatus::Active,
+            reputation: *reputation,
+            joined_at: now - Duration::days(90),
+            last_active_at: now,
+            role_changed_at: None,
+            metadata: serde_json::json!({ "seeded": true }),
+        });
+        balances.push((address, *balance));
+    }
+
+    balances.push((treasury.clone(), 100_000));
+
+    let voter = |i: usize| (members[i].address.clone(), balances[i].1);
+
+    let mut proposals = Vec::new();
+
+    let states = [
+        (ProposalState::Draft, "Adopt a code of conduct"),
+        (ProposalState::Voting, "Fund the community grants round"),
+        (ProposalState::Queued, "Upgrade the treasury multisig"),
+        (ProposalState::Approved, "Hire a security auditor"),
+        (ProposalState::Rejected, "Double the proposal threshold"),
+        (ProposalState::Executed, "Pay the Q1 contributor rewards"),
+        (ProposalState::Cancelled, "Rename the governance token"),
+    ];
+
+    for (i, (state, title)) in states.iter().enumerate() {
+        let proposer = &members[i % 2 + 1];
+
+        let mut proposal = ProposalBuilder::new()
+            .title(*title)
+            .description(format!("Sample proposal: {}", title))
+            .proposal_type(ProposalType::TextProposal {
+                metadata: serde_json::json!({ "seeded": true }),
+            })
+            .proposer(proposer.address.clone())
+            .build()?;
+
+        proposal.state = *state;
+
+        if *state != ProposalState::Draft && *state != ProposalState::Cancelled {
+            proposal.voting_starts_at = Some(now - Duration::days(10));
+            proposal.voting_ends_at = Some(match state {
+                ProposalState::Voting => now + Duration::days(3),
+                _ => now - Duration::days(3),
+            });
+        }
+
+        let ballots: &[(usize, ProposalVote)] = match state {
+            ProposalState::Voting => &[(0, ProposalVote::Yes), (3, ProposalVote::No)],
+            ProposalState::Queued | ProposalState::Approved | ProposalState::Executed => &[
+                (0, ProposalVote::Yes),
+                (2, ProposalVote::Yes),
+                (3, ProposalVote::No),
+                (4, ProposalVote::Abstain),
+            ],
+            ProposalState::Rejected => &[
+                (0, ProposalVote::No),
+                (2, ProposalVote::Veto),
+                (3, ProposalVote::Yes),
+            ],
+            _ => &[],
+        };
+
+        for (member, vote) in ballots {
+            let (address, power) = voter(*member);
+            record_vote(&mut proposal, address, *vote, power);
+        }
+
+        if *state == ProposalState::Draft {
+            proposal.sponsors.push(Sponsor {
+                member: members[2].address.clone(),
+                weight: balances[2].1,
+                timestamp: now,
+            });
+        }
+
+        // Passed, but the timelock has another day to run
+        if *state == ProposalState::Queued {
+            proposal.execution_available_at = Some(now + Duration::days(1));
+        }
+
+        if *state == ProposalState::Executed {
+            proposal.execution_date = Some(now - Duration::days(1));
+        }
+
+        proposals.push(proposal);
+    }
+
+    let mut transactions = Vec::new();
+
+    let payments = [
+        (TransactionStatus::Pending, "Design contractor retainer", 3, 2_000),
+        (TransactionStatus::Approved, "Security audit deposit", 2, 10_000),
+        (TransactionStatus::Executed, "Q1 contributor rewards", 4, 5_000),
+    ];
+
+    for (status, description, recipient, amount) in payments {
+        let mut transaction = TransactionBuilder::new()
+            .description(description)
+            .to(members[recipient].address.clone())
+            .token(token_symbol.to_string())
+            .amount(amount)
+            .required_approvals(2)
+            .creator(members[1].address.clone())
+            .metadata(serde_json::json!({ "seeded": true }))
+            .build()?;
+
+        transaction.status = status;
+
+        if status != TransactionStatus::Pending {
+            transaction.approvers = vec![members[0].address.clone(), members[1].address.clone()];
+            transaction.current_approvals = 2;
+        }
+
+        if status == TransactionStatus::Executed {
+            transaction.executed_at = Some(now - Duration::days(1));
+        }
+
+        transactions.push(transaction);
+    }
+
+    Ok(SampleData {
+        members,
+        token_name: "AtomSi Governance Token".to_string(),
+        token_symbol,
+        balances,
+        proposals,
+        transactions,
+    })
+}
+
+/// Record a ballot and keep the weighted tallies in step
+fn record_vote(proposal: &mut Proposal, voter: String, vote: ProposalVote, voting_power: u64) {
+    match vote {
+        ProposalVote::Yes => proposal.yes_votes += voting_power,
+        ProposalVote::No => proposal.no_votes += voting_power,
+        ProposalVote::Abstain => proposal.abstain_votes += voting_power,
+        ProposalVote::Veto => proposal.veto_votes += voting_power,
+    }
+
+    proposal.votes.push(Vote {
+        voter,
+        vote,
+        voting_power,
+        timestamp: Utc::now(),
+        delegated: Vec::new(),
+        ranking: Vec::new(),
+        signature: None,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::{HashMap, HashSet};
+
+    fn treasury() -> Address {
+        Address::parse("0x0000000000000000000000000000000000000000").unwrap()
+    }
+
+    #[test]
+    fn test_sample_data_is_consistent() {
+        let data = sample_data("ATOM", &treasury()).unwrap();
+
+        let members: HashSet<&str> = data.members.iter().map(|m| m.address.as_str()).collect();
+        let balances: HashMap<String, TokenAmount> = data
+            .balances
+            .iter()
+            .map(|(address, amount)| (address.to_string(), *amount))
+            .collect();
+
+        // Every role is represented
+        for role in [MemberRole::Member, MemberRole::Delegate, MemberRole::Council, MemberRole::Admin] {
+            assert!(data.members.iter().any(|m| m.role == role));
+        }
+
+        // Every proposal state is represented, with references to known members
+        for state in [
+            ProposalState::Draft,
+            ProposalState::Voting,
+            ProposalState::Queued,
+            ProposalState::Approved,
+            ProposalState::Rejected,
+            ProposalState::Executed,
+            ProposalState::Cancelled,
+        ] {
+            assert!(data.proposals.iter().any(|p| p.state == state));
+        }
+
+        for proposal in &data.proposals {
+            assert!(members.contains(proposal.proposer.as_str()));
+            assert_eq!(
+                proposal.execution_available_at.is_some(),
+                proposal.state == ProposalState::Queued
+            );
+            assert!(proposal.sponsors.iter().all(|s| members.contains(s.member.as_str())));
+
+            let mut tallies = [0u64; 4];
+            for vote in &proposal.votes {
+                assert!(members.contains(vote.voter.as_str()));
+                assert_eq!(balances[&vote.voter], vote.voting_power);
+
+                let slot = match vote.vote {
+                    ProposalVote::Yes => 0,
+                    ProposalVote::No => 1,
+                    ProposalVote::Abstain => 2,
+                    ProposalVote::Veto => 3,
+                };
+                tallies[slot] += vote.voting_power;
+            }
+
+            assert_eq!(
+                tallies,
+                [proposal.yes_votes, proposal.no_votes, proposal.abstain_votes, proposal.veto_votes]
+            );
+        }
+
+        // Treasury payments go to members and are covered by the treasury balance
+        let mut committed = 0;
+        for transaction in &data.transactions {
+            assert!(members.contains(transaction.to.as_str()));
+            assert!(transaction.approvers.iter().all(|a| members.contains(a.as_str())));
+            assert_eq!(transaction.approvers.len() as u32, transaction.current_approvals);
+            assert_eq!(transaction.token, data.token_symbol.as_str());
+            committed += transaction.amount;
+        }
+        assert!(committed <= balances[treasury().as_str()]);
+    }
+
+    #[test]
+    fn test_seed_guard() {
+        assert!(check_seed_allowed(false, true, false).is_ok());
+        assert!(check_seed_allowed(false, false, false).is_err());
+        assert!(check_seed_allowed(true, true, false).is_err());
+        assert!(check_seed_allowed(true, false, true).is_ok());
+    }
+}
@@ -0,0 +1,196 @@
+//! Typed values shared by the managers
+//!
+//! Addresses and token symbols are validated once at the boundary, so a
+//! symbol can't be passed where an address is expected (or vice versa).
+
+use super::error::{DaoError, Result};
+use crate::utils::validation::{to_checksum_address, validate_token_symbol};
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+/// Actor recorded as `updated_by` for changes made by background jobs
+/// rather than a member
+pub const SYSTEM_ACTOR: &str = "system";
+
+/// An account address, normalized to its EIP-55 checksummed form
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Address(String);
+
+impl Address {
+    /// Parse and normalize an address
+    ///
+    /// All-lowercase and all-uppercase hex are accepted; mixed-case input
+    /// must carry a valid EIP-55 checksum.
+    pub fn parse(value: &str) -> Result<Self> {
+        let hex = value
+            .strip_prefix("0x")
+            .ok_or_else(|| invalid_address(value, "missing 0x prefix"))?;
+
+        let checksummed = to_checksum_address(value)
+            .ok_or_else(|| invalid_address(value, "expected 40 hex characters"))?;
+
+        let mixed_case = hex.chars().any(|c| c.is_ascii_lowercase())
+            && hex.chars().any(|c| c.is_ascii_uppercase());
+        if mixed_case && checksummed[2..] != *hex {
+            return Err(invalid_address(value, "checksum mismatch"));
+        }
+
+        Ok(Self(checksummed))
+    }
+
+    /// Get the checksummed address
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for Address {
+    type Err = DaoError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+impl TryFrom<String> for Address {
+    type Error = DaoError;
+
+    fn try_from(value: String) -> Result<Self> {
+        Self::parse(&value)
+    }
+}
+
+impl From<Address> for String {
+    fn from(address: Address) -> Self {
+        address.0
+    }
+}
+
+impl AsRef<str> for Address {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+/// A token symbol
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Symbol(String);
+
+impl Symbol {
+    /// Parse a token symbol (1-10 alphanumeric characters)
+    pub fn parse(value: &str) -> Result<Self> {
+        validate_token_symbol(value).map_err(|errors| {
+            let messages: Vec<String> = errors.into_iter().map(|e| e.message).collect();
+            DaoError::InvalidParameter(format!(
+                "Invalid token symbol '{}': {}",
+                value,
+                messages.join(", ")
+            ))
+        })?;
+
+        Ok(Self(value.to_string()))
+    }
+
+    /// Get the symbol
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for Symbol {
+    type Err = DaoError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+impl TryFrom<String> for Symbol {
+    type Error = DaoError;
+
+    fn try_from(value: String) -> Result<Self> {
+        Self::parse(&value)
+    }
+}
+
+impl From<Symbol> for String {
+    fn from(symbol: Symbol) -> Self {
+        symbol.0
+    }
+}
+
+impl AsRef<str> for Symbol {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+fn invalid_address(value: &str, reason: &str) -> DaoError {
+    DaoError::InvalidParameter(format!("Invalid address '{}': {}", value, reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHECKSUMMED: &str = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+
+    #[test]
+    fn test_address_is_normalized_to_checksum() {
+        let lower = Address::parse(&CHECKSUMMED.to_lowercase()).unwrap();
+        assert_eq!(lower.as_str(), CHECKSUMMED);
+
+        let upper = Address::parse(&format!("0x{}", CHECKSUMMED[2..].to_uppercase())).unwrap();
+        assert_eq!(upper, lower);
+
+        assert_eq!(Address::parse(CHECKSUMMED).unwrap().as_str(), CHECKSUMMED);
+    }
+
+    #[test]
+    fn test_address_rejects_bad_checksum() {
+        // Flip the case of one letter
+        let bad = CHECKSUMMED.replacen("aA", "Aa", 1);
+        assert!(Address::parse(&bad).is_err());
+    }
+
+    #[test]
+    fn test_address_rejects_non_addresses() {
+        assert!(Address::parse("ETH").is_err());
+        assert!(Address::parse("").is_err());
+        assert!(Address::parse("5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed").is_err());
+        assert!(Address::parse("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeA").is_err());
+        assert!(Address::parse("0xzzzeb6053F3E94C9b9A09f33669435E7Ef1BeAed").is_err());
+        assert!("not-an-address".parse::<Address>().is_err());
+    }
+
+    #[test]
+    fn test_address_deserialization_validates() {
+        let address: Address = serde_json::from_str(&format!("\"{}\"", CHECKSUMMED.to_lowercase())).unwrap();
+        assert_eq!(address.as_str(), CHECKSUMMED);
+        assert!(serde_json::from_str::<Address>("\"ATOM\"").is_err());
+    }
+
+    #[test]
+    fn test_symbol_parse() {
+        assert_eq!(Symbol::parse("ATOM").unwrap().as_str(), "ATOM");
+        assert!(Symbol::parse("").is_err());
+        assert!(Symbol::parse("A-B").is_err());
+        assert!(Symbol::parse(CHECKSUMMED).is_err());
+    }
+}
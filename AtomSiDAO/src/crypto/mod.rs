@@ -3,7 +3,10 @@
 //! This module provides cryptographic functionality for the DAO,
 //! including signature verification, encryption, and hashing.
 
-use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use aes_gcm::{Aes256Gcm, Nonce};
+use chrono::{DateTime, Duration, Utc};
+use ethers::types::Signature as EthSignature;
+use ethers::utils::to_checksum;
 use sha2::{Digest, Sha256};
 use std::error::Error;
 use std::fmt;
@@ -55,50 +58,82 @@ impl From<hex::FromHexError> for CryptoError {
 /// Result type for cryptographic operations
 pub type Result<T> = std::result::Result<T, CryptoError>;
 
-/// Verify a signature
+/// Prefix of the line carrying the issued-at timestamp in signed messages
+pub const ISSUED_AT_PREFIX: &str = "Issued At:";
+
+/// Allowed clock skew for issued-at timestamps in the future (seconds)
+pub const SIGNATURE_CLOCK_SKEW_SECONDS: i64 = 30;
+
+/// Verify an Ethereum `personal_sign` signature
 ///
-/// This function verifies that the provided signature is valid for the
-/// message, using the public key derived from the address.
+/// Recovers the signer from an EIP-191 signature over `message` and compares
+/// it with `address`, ignoring checksum case. Returns `Ok(false)` if the
+/// signature is well-formed but was made by a different address.
 pub fn verify_signature(address: &str, message: &str, signature: &str) -> Result<bool> {
-    // In a real implementation, we would:
-    // 1. Convert the address to a public key
-    // 2. Parse the signature
-    // 3. Verify the signature against the message using the public key
-    
-    // For Ethereum signatures, we would use secp256k1 and ecrecover
-    // For this example, we'll use ed25519
-    
-    // Extract public key from address (example implementation)
-    let public_key_bytes = match extract_public_key_from_address(address) {
-        Ok(pk) => pk,
-        Err(_) => {
-            // For development/testing purposes, accept a special test address and signature
-            if address == "0xTestAddress" && signature == "0xTestSignature" {
-                return Ok(true);
-            }
-            return Err(CryptoError::InvalidKey(format!("Could not extract public key from address: {}", address)));
-        }
-    };
-    
-    // Convert the public key bytes to a VerifyingKey
-    let verifying_key = VerifyingKey::from_bytes(&public_key_bytes)
-        .map_err(|e| CryptoError::InvalidKey(e.to_string()))?;
-    
-    // Decode the signature from hex
     let signature_bytes = hex::decode(signature.trim_start_matches("0x"))
         .map_err(|e| CryptoError::DecodingError(format!("Invalid signature format: {}", e)))?;
     
-    let signature = Signature::from_bytes(&signature_bytes)
-        .map_err(|e| CryptoError::InvalidSignature)?;
+    let signature = EthSignature::try_from(signature_bytes.as_slice())
+        .map_err(|_| CryptoError::InvalidSignature)?;
     
-    // Hash the message
-    let message_hash = hash_message(message);
+    let recovered = signature
+        .recover(message)
+        .map_err(|e| CryptoError::VerificationError(e.to_string()))?;
+    
+    Ok(to_checksum(&recovered, None).eq_ignore_ascii_case(address.trim()))
+}
+
+/// Verify a signature over a timestamped message
+///
+/// The message must contain an `Issued At: <RFC 3339 timestamp>` line. The
+/// signature is rejected if that timestamp is older than `max_age_seconds`
+/// or further in the future than the allowed clock skew, which bounds how
+/// long a captured signature can be replayed.
+pub fn verify_timestamped_signature(
+    address: &str,
+    message: &str,
+    signature: &str,
+    max_age_seconds: u64,
+) -> Result<bool> {
+    check_signature_timestamp(message, max_age_seconds, Utc::now())?;
+    verify_signature(address, message, signature)
+}
+
+/// Check the issued-at timestamp embedded in a signed message
+pub fn check_signature_timestamp(
+    message: &str,
+    max_age_seconds: u64,
+    now: DateTime<Utc>,
+) -> Result<DateTime<Utc>> {
+    let issued_at = extract_issued_at(message)?;
     
-    // Verify the signature
-    match verifying_key.verify(&message_hash, &signature) {
-        Ok(_) => Ok(true),
-        Err(e) => Err(CryptoError::VerificationError(e.to_string())),
+    if issued_at > now + Duration::seconds(SIGNATURE_CLOCK_SKEW_SECONDS) {
+        return Err(CryptoError::VerificationError(
+            "Signed message is issued in the future".to_string(),
+        ));
     }
+    
+    if now - issued_at > Duration::seconds(max_age_seconds as i64) {
+        return Err(CryptoError::VerificationError(
+            "Signed message has expired".to_string(),
+        ));
+    }
+    
+    Ok(issued_at)
+}
+
+/// Extract the issued-at timestamp from a signed message
+fn extract_issued_at(message: &str) -> Result<DateTime<Utc>> {
+    let value = message
+        .lines()
+        .find_map(|line| line.trim().strip_prefix(ISSUED_AT_PREFIX))
+        .ok_or_else(|| {
+            CryptoError::VerificationError("Signed message has no issued-at timestamp".to_string())
+        })?;
+    
+    DateTime::parse_from_rfc3339(value.trim())
+        .map(|timestamp| timestamp.with_timezone(&Utc))
+        .map_err(|e| CryptoError::DecodingError(format!("Invalid issued-at timestamp: {}", e)))
 }
 
 /// Hash a message using SHA-256
@@ -108,14 +143,15 @@ pub fn hash_message(message: &str) -> Vec<u8> {
     hasher.finalize().to_vec()
 }
 
-/// Extract a public key from an address
-fn extract_public_key_from_address(address: &str) -> Result<[u8; 32]> {
-    // This is a placeholder implementation
-    // In a real implementation, this would depend on the blockchain we're using
-    
-    // For Ethereum, addresses are derived from the public key through keccak256
-    // For simplicity, we'll return a dummy public key
-    Err(CryptoError::Other("Not implemented".to_string()))
+/// Hash structured data using SHA-256 over its canonical JSON form
+///
+/// Use this instead of hashing `serde_json::to_string` output, whose key
+/// order is not guaranteed to be stable.
+pub fn hash_json<T: serde::Serialize>(value: &T) -> Result<Vec<u8>> {
+    let canonical = crate::utils::to_canonical_json(value).map_err(CryptoError::EncodingError)?;
+    let mut hasher = Sha256::new();
+    hasher.update(canonical.as_bytes());
+    Ok(hasher.finalize().to_vec())
 }
 
 /// Generate a random key pair
@@ -153,27 +189,137 @@ pub fn sign_message(secret_key: &[u8; 64], message: &str) -> Result<Vec<u8>> {
     Ok(signature.to_bytes().to_vec())
 }
 
-/// Encrypt data
+/// Argon2id cost parameters for password hashing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PasswordHashParams {
+    /// Memory cost in KiB
+    pub memory_kib: u32,
+    /// Number of passes over memory
+    pub iterations: u32,
+    /// Degree of parallelism
+    pub parallelism: u32,
+}
+
+impl Default for PasswordHashParams {
+    /// OWASP's recommended minimum for Argon2id: 19 MiB, two passes
+    fn default() -> Self {
+        Self {
+            memory_kib: 19 * 1024,
+            iterations: 2,
+            parallelism: 1,
+        }
+    }
+}
+
+/// Hash a password with Argon2id and the default cost parameters
+///
+/// Returns a PHC string (`$argon2id$v=19$m=...,t=...,p=...$<salt>$<hash>`)
+/// that embeds the random salt and the parameters used.
+pub fn hash_password(password: &str) -> Result<String> {
+    hash_password_with(password, &PasswordHashParams::default())
+}
+
+/// Hash a password with Argon2id and the given cost parameters
+pub fn hash_password_with(password: &str, params: &PasswordHashParams) -> Result<String> {
+    use argon2::password_hash::{rand_core::OsRng, PasswordHasher, SaltString};
+    use argon2::{Algorithm, Argon2, Params, Version};
+    
+    let params = Params::new(params.memory_kib, params.iterations, params.parallelism, None)
+        .map_err(|e| CryptoError::Other(format!("Invalid password hash parameters: {}", e)))?;
+    let salt = SaltString::generate(&mut OsRng);
+    
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| CryptoError::EncodingError(format!("Failed to hash password: {}", e)))
+}
+
+/// Check a password against a PHC string from [`hash_password`]
+///
+/// The cost parameters are read from the hash, so hashes made with older
+/// parameters keep verifying. Malformed hashes never verify.
+pub fn verify_password(password: &str, hash: &str) -> bool {
+    use argon2::password_hash::{PasswordHash, PasswordVerifier};
+    use argon2::Argon2;
+    
+    match PasswordHash::new(hash) {
+        Ok(parsed) => Argon2::default().verify_password(password.as_bytes(), &parsed).is_ok(),
+        Err(_) => false,
+    }
+}
+
+/// Length of the AES-GCM nonce prepended to ciphertexts
+pub const NONCE_LENGTH: usize = 12;
+
+/// Encrypt data with AES-256-GCM
+///
+/// `key` must be 32 bytes. A random 96-bit nonce is generated for each call
+/// and prepended to the ciphertext, which ends with the authentication tag.
 pub fn encrypt(data: &[u8], key: &[u8]) -> Result<Vec<u8>> {
-    // This is a placeholder implementation
-    // In a real implementation, we would use a proper encryption algorithm
-    // such as AES-GCM or ChaCha20-Poly1305
+    use aes_gcm::aead::{Aead, AeadCore, OsRng};
+    
+    let cipher = aes_cipher(key)?;
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    
+    let ciphertext = cipher
+        .encrypt(&nonce, data)
+        .map_err(|_| CryptoError::EncodingError("Encryption failed".to_string()))?;
     
-    Err(CryptoError::Other("Encryption not implemented".to_string()))
+    let mut output = Vec::with_capacity(NONCE_LENGTH + ciphertext.len());
+    output.extend_from_slice(&nonce);
+    output.extend_from_slice(&ciphertext);
+    
+    Ok(output)
 }
 
-/// Decrypt data
+/// Decrypt data produced by [`encrypt`]
+///
+/// Fails with `DecodingError` if the input is too short to hold a nonce and
+/// tag, and with `VerificationError` if the key is wrong or the data was
+/// tampered with.
 pub fn decrypt(data: &[u8], key: &[u8]) -> Result<Vec<u8>> {
-    // This is a placeholder implementation
-    // In a real implementation, we would use a proper encryption algorithm
-    // such as AES-GCM or ChaCha20-Poly1305
+    use aes_gcm::aead::Aead;
+    
+    let cipher = aes_cipher(key)?;
+    
+    // Nonce followed by at least the 16-byte tag
+    if data.len() < NONCE_LENGTH + 16 {
+        return Err(CryptoError::DecodingError(format!(
+            "Encrypted data is {} bytes, too short for a nonce and tag",
+            data.len()
+        )));
+    }
+    
+    let (nonce, ciphertext) = data.split_at(NONCE_LENGTH);
     
-    Err(CryptoError::Other("Decryption not implemented".to_string()))
+    cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| CryptoError::VerificationError("Authentication tag mismatch".to_string()))
+}
+
+fn aes_cipher(key: &[u8]) -> Result<Aes256Gcm> {
+    use aes_gcm::KeyInit;
+    
+    Aes256Gcm::new_from_slice(key)
+        .map_err(|_| CryptoError::InvalidKey(format!("Expected a 32-byte key, got {} bytes", key.len())))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+    use ethers::signers::{LocalWallet, Signer};
+    
+    /// Well-known development key (the first Anvil/Hardhat account)
+    const TEST_KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+    const TEST_ADDRESS: &str = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266";
+    
+    /// Sign a message the way `personal_sign` does
+    fn personal_sign(message: &str) -> String {
+        let wallet: LocalWallet = TEST_KEY.parse().unwrap();
+        let signature = wallet.sign_hash(ethers::utils::hash_message(message)).unwrap();
+        format!("0x{}", signature)
+    }
     
     #[test]
     fn test_hash_message() {
@@ -187,6 +333,15 @@ mod tests {
         assert_eq!(hash, expected);
     }
     
+    #[test]
+    fn test_hash_json_is_order_independent() {
+        let a = serde_json::json!({ "amount": 10, "to": "0xabc" });
+        let b: serde_json::Value = serde_json::from_str(r#"{"to":"0xabc","amount":10}"#).unwrap();
+        
+        assert_eq!(hash_json(&a).unwrap(), hash_json(&b).unwrap());
+        assert_eq!(hash_json(&a).unwrap(), hash_message(r#"{"amount":10,"to":"0xabc"}"#));
+    }
+    
     #[test]
     fn test_generate_and_sign() {
         // Generate a key pair
@@ -205,10 +360,110 @@ mod tests {
         assert!(result.is_ok());
     }
     
+    /// Cheap parameters so the tests stay fast
+    fn test_params() -> PasswordHashParams {
+        PasswordHashParams {
+            memory_kib: 1024,
+            iterations: 1,
+            parallelism: 1,
+        }
+    }
+    
+    #[test]
+    fn test_password_hash_and_verify() {
+        let hash = hash_password_with("correct horse battery staple", &test_params()).unwrap();
+        
+        assert!(hash.starts_with("$argon2id$v=19$m=1024,t=1,p=1$"));
+        assert!(verify_password("correct horse battery staple", &hash));
+        assert!(!verify_password("correct horse battery stapler", &hash));
+        assert!(!verify_password("correct horse battery staple", "not a hash"));
+    }
+    
+    #[test]
+    fn test_password_hashes_are_salted() {
+        let first = hash_password_with("hunter2", &test_params()).unwrap();
+        let second = hash_password_with("hunter2", &test_params()).unwrap();
+        
+        assert_ne!(first, second);
+        assert!(verify_password("hunter2", &first));
+        assert!(verify_password("hunter2", &second));
+        
+        let defaults = hash_password("hunter2").unwrap();
+        assert!(defaults.contains("m=19456,t=2,p=1"));
+    }
+    
+    #[test]
+    fn test_encrypt_decrypt_round_trip() {
+        let key = [7u8; 32];
+        let secret = b"JBSWY3DPEHPK3PXP";
+        
+        let encrypted = encrypt(secret, &key).unwrap();
+        assert_eq!(encrypted.len(), NONCE_LENGTH + secret.len() + 16);
+        assert_eq!(decrypt(&encrypted, &key).unwrap(), secret);
+        
+        // A fresh nonce each time
+        assert_ne!(encrypt(secret, &key).unwrap(), encrypted);
+        assert_eq!(decrypt(&encrypt(b"", &key).unwrap(), &key).unwrap(), b"");
+    }
+    
+    #[test]
+    fn test_tampered_ciphertext_fails_verification() {
+        let key = [7u8; 32];
+        let mut encrypted = encrypt(b"api-key-123", &key).unwrap();
+        encrypted[NONCE_LENGTH] ^= 0x01;
+        
+        assert!(matches!(decrypt(&encrypted, &key), Err(CryptoError::VerificationError(_))));
+        
+        let encrypted = encrypt(b"api-key-123", &key).unwrap();
+        assert!(matches!(decrypt(&encrypted, &[8u8; 32]), Err(CryptoError::VerificationError(_))));
+        assert!(matches!(decrypt(&encrypted[..20], &key), Err(CryptoError::DecodingError(_))));
+        assert!(matches!(encrypt(b"data", &[0u8; 16]), Err(CryptoError::InvalidKey(_))));
+    }
+    
     #[test]
-    fn test_test_signature() {
-        // Test the special test address and signature
-        let result = verify_signature("0xTestAddress", "Any message", "0xTestSignature");
+    fn test_expired_timestamped_signature_rejected() {
+        let issued_at = Utc::now() - Duration::seconds(600);
+        let message = format!("Sign in to AtomSi DAO\n{} {}", ISSUED_AT_PREFIX, issued_at.to_rfc3339());
+        
+        let result = verify_timestamped_signature("0xTestAddress", &message, "0xTestSignature", 300);
+        assert!(matches!(result, Err(CryptoError::VerificationError(_))));
+    }
+    
+    #[test]
+    fn test_fresh_timestamped_signature_accepted() {
+        let message = format!("Sign in to AtomSi DAO\n{} {}", ISSUED_AT_PREFIX, Utc::now().to_rfc3339());
+        
+        let result = verify_timestamped_signature(TEST_ADDRESS, &message, &personal_sign(&message), 300);
         assert!(result.unwrap());
     }
+    
+    #[test]
+    fn test_signature_timestamp_bounds() {
+        let now = Utc::now();
+        let message = |at: DateTime<Utc>| format!("{} {}", ISSUED_AT_PREFIX, at.to_rfc3339());
+        
+        assert!(check_signature_timestamp(&message(now + Duration::seconds(10)), 300, now).is_ok());
+        assert!(check_signature_timestamp(&message(now + Duration::seconds(120)), 300, now).is_err());
+        assert!(check_signature_timestamp("No timestamp here", 300, now).is_err());
+        assert!(check_signature_timestamp("Issued At: yesterday", 300, now).is_err());
+    }
+    
+    #[test]
+    fn test_personal_sign_signature_verified() {
+        let signature = personal_sign("Vote yes on AIP-7");
+        
+        assert!(verify_signature(TEST_ADDRESS, "Vote yes on AIP-7", &signature).unwrap());
+        assert!(verify_signature(&TEST_ADDRESS.to_lowercase(), "Vote yes on AIP-7", &signature).unwrap());
+    }
+    
+    #[test]
+    fn test_signature_from_other_address_rejected() {
+        let signature = personal_sign("Vote yes on AIP-7");
+        
+        assert!(!verify_signature("0x70997970C51812dc3A010C7d01b50e0d17dc79C8", "Vote yes on AIP-7", &signature).unwrap());
+        assert!(!verify_signature(TEST_ADDRESS, "Vote no on AIP-7", &signature).unwrap());
+        
+        // The old development backdoor is gone
+        assert!(verify_signature("0xTestAddress", "Any message", "0xTestSignature").is_err());
+    }
 } 
\ No newline at end of file
@@ -4,12 +4,15 @@
 
 use async_trait::async_trait;
 use sqlx::{
+    migrate::Migrator,
     postgres::{PgPool, PgPoolOptions},
     sqlite::{SqlitePool, SqlitePoolOptions},
     Pool, Postgres, Sqlite,
 };
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
 use tokio::sync::Mutex;
+use tracing::{info, warn};
 
 use crate::config::DatabaseConfig;
 use crate::error::{Error, Result};
@@ -28,14 +31,16 @@ pub enum DatabaseType {
 pub struct DatabaseManager {
     /// Database type
     db_type: DatabaseType,
-    /// SQLite pool
-    sqlite_pool: Option<SqlitePool>,
-    /// PostgreSQL pool
-    pg_pool: Option<PgPool>,
+    /// SQLite pool, shared by clones and replaced on reconnect
+    sqlite_pool: Arc<RwLock<Option<SqlitePool>>>,
+    /// PostgreSQL pool, shared by clones and replaced on reconnect
+    pg_pool: Arc<RwLock<Option<PgPool>>>,
     /// Configuration
     config: Arc<DatabaseConfig>,
     /// Mutex for database initialization
     init_mutex: Arc<Mutex<()>>,
+    /// Mutex so only one caller rebuilds the pool at a time
+    reconnect_mutex: Arc<Mutex<()>>,
 }
 
 impl DatabaseManager {
@@ -52,12 +57,13 @@ impl DatabaseManager {
             }
         };
 
-        let mut manager = Self {
+        let manager = Self {
             db_type,
-            sqlite_pool: None,
-            pg_pool: None,
+            sqlite_pool: Arc::new(RwLock::new(None)),
+            pg_pool: Arc::new(RwLock::new(None)),
             config: Arc::new(config.clone()),
             init_mutex: Arc::new(Mutex::new(())),
+            reconnect_mutex: Arc::new(Mutex::new(())),
         };
 
         // Connect to the database
@@ -67,7 +73,9 @@ impl DatabaseManager {
     }
 
     /// Connect to the database
-    async fn connect(&mut self) -> Result<()> {
+    ///
+    /// Replaces the current pool, if any, and closes it.
+    async fn connect(&self) -> Result<()> {
         match self.db_type {
             DatabaseType::SQLite => {
                 let sqlite_path = self
@@ -82,25 +90,22 @@ impl DatabaseManager {
                     .await
                     .map_err(|e| Error::DatabaseError(format!("Failed to connect to SQLite: {}", e)))?;
 
-                self.sqlite_pool = Some(pool);
+                let old = self.sqlite_pool.write().unwrap().replace(pool);
+                if let Some(old) = old {
+                    old.close().await;
+                }
             }
             DatabaseType::Postgres => {
-                let connection_string = format!(
-                    "postgres://{}:{}@{}:{}/{}",
-                    self.config.username,
-                    self.config.password,
-                    self.config.host,
-                    self.config.port,
-                    self.config.name
-                );
-
                 let pool = PgPoolOptions::new()
                     .max_connections(self.config.pool_size)
-                    .connect(&connection_string)
+                    .connect(&self.postgres_url())
                     .await
                     .map_err(|e| Error::DatabaseError(format!("Failed to connect to PostgreSQL: {}", e)))?;
 
-                self.pg_pool = Some(pool);
+                let old = self.pg_pool.write().unwrap().replace(pool);
+                if let Some(old) = old {
+                    old.close().await;
+                }
             }
         }
 
@@ -112,20 +117,94 @@ impl DatabaseManager {
         self.db_type
     }
 
+    /// Get the PostgreSQL connection URL built from the configuration
+    pub(crate) fn postgres_url(&self) -> String {
+        format!(
+            "postgres://{}:{}@{}:{}/{}",
+            self.config.username, self.config.password, self.config.host, self.config.port, self.config.name
+        )
+    }
+
+    /// Get the configured maximum number of connections
+    pub fn pool_size(&self) -> u32 {
+        self.config.pool_size
+    }
+
     /// Get the SQLite pool
-    pub fn sqlite_pool(&self) -> Result<&SqlitePool> {
+    ///
+    /// The pool is a cheap handle; fetch it again after a reconnect.
+    pub fn sqlite_pool(&self) -> Result<SqlitePool> {
         self.sqlite_pool
-            .as_ref()
+            .read()
+            .unwrap()
+            .clone()
             .ok_or_else(|| Error::DatabaseError("SQLite pool not initialized".to_string()))
     }
 
     /// Get the PostgreSQL pool
-    pub fn pg_pool(&self) -> Result<&PgPool> {
+    ///
+    /// The pool is a cheap handle; fetch it again after a reconnect.
+    pub fn pg_pool(&self) -> Result<PgPool> {
         self.pg_pool
-            .as_ref()
+            .read()
+            .unwrap()
+            .clone()
             .ok_or_else(|| Error::DatabaseError("PostgreSQL pool not initialized".to_string()))
     }
 
+    /// Check that the database answers a trivial query
+    pub async fn health_check(&self) -> Result<()> {
+        let result = match self.db_type {
+            DatabaseType::SQLite => sqlx::query_scalar::<_, i32>("SELECT 1").fetch_one(&self.sqlite_pool()?).await,
+            DatabaseType::Postgres => sqlx::query_scalar::<_, i32>("SELECT 1").fetch_one(&self.pg_pool()?).await,
+        };
+
+        result
+            .map(|_| ())
+            .map_err(|e| Error::DatabaseError(format!("Database health check failed: {}", e)))
+    }
+
+    /// Rebuild the pool if the health check fails
+    ///
+    /// Stale connections, e.g. after the database restarted, are dropped
+    /// with the old pool. Fails if the database still can't be reached.
+    pub async fn ensure_connected(&self) -> Result<()> {
+        if self.health_check().await.is_ok() {
+            return Ok(());
+        }
+
+        let _lock = self.reconnect_mutex.lock().await;
+        // Another caller may have reconnected while we waited
+        if let Err(e) = self.health_check().await {
+            warn!("{}; reconnecting", e);
+            self.connect().await?;
+            self.health_check().await?;
+            info!("Reconnected to the {:?} database", self.db_type);
+        }
+
+        Ok(())
+    }
+
+    /// Run [`ensure_connected`](Self::ensure_connected) every `interval` in
+    /// the background until the task is aborted
+    pub fn start_health_monitor(&self, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let manager = self.clone();
+
+        tokio::spawn(async move {
+            let mut ticks = tokio::time::interval(interval);
+            ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            // The first tick completes immediately, just after connecting
+            ticks.tick().await;
+
+            loop {
+                ticks.tick().await;
+                if let Err(e) = manager.ensure_connected().await {
+                    warn!("Database is unavailable: {}", e);
+                }
+            }
+        })
+    }
+
     /// Initialize the database
     pub async fn init_db(&self) -> Result<()> {
         // Use a mutex to prevent multiple initialization attempts
@@ -134,11 +213,11 @@ impl DatabaseManager {
         match self.db_type {
             DatabaseType::SQLite => {
                 let pool = self.sqlite_pool()?;
-                init_sqlite_db(pool).await?;
+                init_sqlite_db(&pool).await?;
             }
             DatabaseType::Postgres => {
                 let pool = self.pg_pool()?;
-                init_postgres_db(pool).await?;
+                init_postgres_db(&pool).await?;
             }
         }
 
@@ -149,12 +228,14 @@ impl DatabaseManager {
     pub async fn close(&self) -> Result<()> {
         match self.db_type {
             DatabaseType::SQLite => {
-                if let Some(pool) = &self.sqlite_pool {
+                let pool = self.sqlite_pool.read().unwrap().clone();
+                if let Some(pool) = pool {
                     pool.close().await;
                 }
             }
             DatabaseType::Postgres => {
-                if let Some(pool) = &self.pg_pool {
+                let pool = self.pg_pool.read().unwrap().clone();
+                if let Some(pool) = pool {
                     pool.close().await;
                 }
             }
@@ -163,29 +244,148 @@ impl DatabaseManager {
         Ok(())
     }
 
-    /// Execute a database migration
+    /// Apply every migration not applied yet
     pub async fn migrate(&self) -> Result<()> {
+        self.migrate_to(self.latest_version()).await
+    }
+
+    /// Bring the schema to `version` by applying up migrations newer than
+    /// the current version, or down migrations for versions above it
+    ///
+    /// Version 0 is the empty schema. Each step runs in its own transaction
+    /// together with its `schema_migrations` record, so a failed step leaves
+    /// the schema at the previous version.
+    pub async fn migrate_to(&self, version: i64) -> Result<()> {
+        let migrator = self.migrator();
+        if version != 0 && !migrator.iter().any(|migration| migration.version == version) {
+            return Err(Error::DatabaseError(format!("Unknown migration version: {}", version)));
+        }
+
+        self.execute_script(&[MIGRATIONS_TABLE]).await?;
+        let applied = self.applied_versions().await?;
+
+        let ups = migrator
+            .iter()
+            .filter(|migration| !migration.migration_type.is_down_migration())
+            .filter(|migration| migration.version <= version && !applied.contains(&migration.version));
+        for migration in ups {
+            let record = format!(
+                "INSERT INTO schema_migrations (version, description, applied_at) VALUES ({}, '{}', {})",
+                migration.version,
+                migration.description.replace('\'', "''"),
+                chrono::Utc::now().timestamp()
+            );
+            self.execute_script(&[&*migration.sql, &*record]).await?;
+            info!("Applied migration {} ({})", migration.version, migration.description);
+        }
+
+        for applied_version in applied.iter().rev().filter(|applied_version| **applied_version > version) {
+            let down = migrator
+                .iter()
+                .find(|migration| migration.version == *applied_version && migration.migration_type.is_down_migration())
+                .ok_or_else(|| {
+                    Error::DatabaseError(format!("Migration {} has no down migration", applied_version))
+                })?;
+            let record = format!("DELETE FROM schema_migrations WHERE version = {}", applied_version);
+            self.execute_script(&[&*down.sql, &*record]).await?;
+            info!("Reverted migration {} ({})", down.version, down.description);
+        }
+
+        Ok(())
+    }
+
+    /// Get the newest applied migration version, or 0 if none is applied
+    pub async fn current_version(&self) -> Result<i64> {
+        Ok(self.applied_versions().await?.last().copied().unwrap_or(0))
+    }
+
+    /// Get the newest migration version available
+    pub fn latest_version(&self) -> i64 {
+        self.migrator().iter().map(|migration| migration.version).max().unwrap_or(0)
+    }
+
+    /// Count migrations that have not been applied yet, without applying them
+    pub async fn pending_migrations(&self) -> Result<usize> {
+        let applied = self.applied_versions().await?;
+
+        Ok(self
+            .migrator()
+            .iter()
+            .filter(|migration| !migration.migration_type.is_down_migration())
+            .filter(|migration| !applied.contains(&migration.version))
+            .count())
+    }
+
+    /// Get the migrations for this database type
+    fn migrator(&self) -> Migrator {
         match self.db_type {
+            DatabaseType::SQLite => sqlx::migrate!("./migrations/sqlite"),
+            DatabaseType::Postgres => sqlx::migrate!("./migrations/postgres"),
+        }
+    }
+
+    /// Get the applied migration versions, oldest first, without writing
+    ///
+    /// A missing migrations table means nothing has been applied; any other
+    /// failure to read it is an error.
+    async fn applied_versions(&self) -> Result<Vec<i64>> {
+        let query = "SELECT version FROM schema_migrations ORDER BY version";
+        let exists = match self.db_type {
+            DatabaseType::SQLite => "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'schema_migrations'",
+            DatabaseType::Postgres => {
+                "SELECT COUNT(*) FROM information_schema.tables \
+                 WHERE table_schema = current_schema() AND table_name = 'schema_migrations'"
+            }
+        };
+
+        let applied = match self.db_type {
             DatabaseType::SQLite => {
                 let pool = self.sqlite_pool()?;
-                sqlx::migrate!("./migrations/sqlite")
-                    .run(pool)
-                    .await
-                    .map_err(|e| Error::DatabaseError(format!("Failed to run SQLite migrations: {}", e)))?;
+                match sqlx::query_scalar::<_, i64>(exists).fetch_one(&pool).await {
+                    Ok(0) => Ok(Vec::new()),
+                    Ok(_) => sqlx::query_scalar(query).fetch_all(&pool).await,
+                    Err(e) => Err(e),
+                }
             }
             DatabaseType::Postgres => {
                 let pool = self.pg_pool()?;
-                sqlx::migrate!("./migrations/postgres")
-                    .run(pool)
-                    .await
-                    .map_err(|e| Error::DatabaseError(format!("Failed to run PostgreSQL migrations: {}", e)))?;
+                match sqlx::query_scalar::<_, i64>(exists).fetch_one(&pool).await {
+                    Ok(0) => Ok(Vec::new()),
+                    Ok(_) => sqlx::query_scalar(query).fetch_all(&pool).await,
+                    Err(e) => Err(e),
+                }
             }
-        }
+        };
 
-        Ok(())
+        applied.map_err(|e| Error::DatabaseError(format!("Failed to read applied migrations: {}", e)))
+    }
+
+    /// Run SQL scripts, which may hold several statements, in one transaction
+    async fn execute_script(&self, scripts: &[&str]) -> Result<()> {
+        let result = match self.db_type {
+            DatabaseType::SQLite => execute_in_transaction(&self.sqlite_pool()?, scripts).await,
+            DatabaseType::Postgres => execute_in_transaction(&self.pg_pool()?, scripts).await,
+        };
+
+        result.map_err(|e| Error::DatabaseError(format!("Failed to run migration: {}", e)))
     }
 }
 
+/// Table recording applied migration versions
+const MIGRATIONS_TABLE: &str = "CREATE TABLE IF NOT EXISTS schema_migrations (\
+    version BIGINT PRIMARY KEY, description TEXT NOT NULL, applied_at BIGINT NOT NULL)";
+
+async fn execute_in_transaction<DB: sqlx::Database>(pool: &Pool<DB>, scripts: &[&str]) -> std::result::Result<(), sqlx::Error>
+where
+    for<'c> &'c mut DB::Connection: sqlx::Executor<'c, Database = DB>,
+{
+    let mut tx = pool.begin().await?;
+    for script in scripts {
+        sqlx::Executor::execute(&mut *tx, *script).await?;
+    }
+    tx.commit().await
+}
+
 /// Initialize SQLite database
 async fn init_sqlite_db(pool: &SqlitePool) -> Result<()> {
     // Create tables if they don't exist
@@ -459,6 +659,7 @@ mod tests {
             password: "".to_string(),
             pool_size: 5,
             sqlite_path: Some("file::memory:".to_string()),
+            read_replica: None,
         };
 
         let db_manager = DatabaseManager::new(&config).await.unwrap();
@@ -470,4 +671,106 @@ mod tests {
         // Close the connection
         db_manager.close().await.unwrap();
     }
+
+    /// Versions of the SQLite migrations, read from the migrations directory
+    fn sqlite_migration_versions() -> Vec<i64> {
+        let directory = concat!(env!("CARGO_MANIFEST_DIR"), "/migrations/sqlite");
+        let mut versions: Vec<i64> = std::fs::read_dir(directory)
+            .unwrap()
+            .filter_map(|entry| {
+                let name = entry.unwrap().file_name().into_string().unwrap();
+                let (version, _) = name.strip_suffix(".up.sql")?.split_once('_')?;
+                Some(version.parse().unwrap())
+            })
+            .collect();
+        versions.sort_unstable();
+        versions
+    }
+
+    async fn sqlite_tables(db_manager: &DatabaseManager) -> Vec<String> {
+        sqlx::query_scalar("SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name")
+            .fetch_all(&db_manager.sqlite_pool().unwrap())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_migrate_up_then_down_one_version() {
+        let mut config = crate::config::default().database;
+        config.db_type = "sqlite".to_string();
+        config.sqlite_path = Some("sqlite::memory:".to_string());
+        config.pool_size = 1;
+        let db_manager = DatabaseManager::new(&config).await.unwrap();
+        let versions = sqlite_migration_versions();
+
+        assert_eq!(db_manager.current_version().await.unwrap(), 0);
+        assert_eq!(db_manager.pending_migrations().await.unwrap(), versions.len());
+
+        db_manager.migrate().await.unwrap();
+        assert_eq!(db_manager.current_version().await.unwrap(), db_manager.latest_version());
+        assert_eq!(Some(&db_manager.latest_version()), versions.last());
+        assert_eq!(db_manager.pending_migrations().await.unwrap(), 0);
+        let tables = sqlite_tables(&db_manager).await;
+        assert!(tables.contains(&"proposal_comments".to_string()));
+        assert!(tables.contains(&"treasury_execution_intents".to_string()));
+        assert!(tables.contains(&"login_attempts".to_string()));
+        assert!(tables.contains(&"event_checkpoints".to_string()));
+        assert!(tables.contains(&"proposal_templates".to_string()));
+        assert!(tables.contains(&"treasury_idempotency_keys".to_string()));
+        assert!(tables.contains(&"conviction".to_string()));
+        assert!(tables.contains(&"voice_credit_usage".to_string()));
+        assert!(tables.contains(&"voice_credit_budgets".to_string()));
+
+        // Down to version 2 reverts only the migrations after it
+        db_manager.migrate_to(2).await.unwrap();
+        assert_eq!(db_manager.current_version().await.unwrap(), 2);
+        assert_eq!(
+            db_manager.pending_migrations().await.unwrap(),
+            versions.iter().filter(|&&version| version > 2).count()
+        );
+        let tables = sqlite_tables(&db_manager).await;
+        assert!(tables.contains(&"proposal_comments".to_string()));
+        assert!(tables.contains(&"members".to_string()));
+        assert!(!tables.contains(&"treasury_execution_intents".to_string()));
+        assert!(!tables.contains(&"invitations".to_string()));
+
+        // Back to the empty schema, keeping only the version table
+        db_manager.migrate_to(0).await.unwrap();
+        assert_eq!(sqlite_tables(&db_manager).await, ["schema_migrations"]);
+
+        assert!(db_manager.migrate_to(42).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_ensure_connected_restores_closed_pool() {
+        let mut config = crate::config::default().database;
+        config.db_type = "sqlite".to_string();
+        config.sqlite_path = Some("sqlite::memory:".to_string());
+        config.pool_size = 1;
+
+        let db_manager = DatabaseManager::new(&config).await.unwrap();
+        let clone = db_manager.clone();
+        db_manager.health_check().await.unwrap();
+
+        // A closed pool behaves like one whose connections went stale
+        db_manager.close().await.unwrap();
+        assert!(db_manager.health_check().await.is_err());
+
+        db_manager.ensure_connected().await.unwrap();
+        db_manager.health_check().await.unwrap();
+        // Clones share the rebuilt pool
+        clone.health_check().await.unwrap();
+
+        // The monitor reconnects on its own
+        db_manager.close().await.unwrap();
+        let monitor = db_manager.start_health_monitor(Duration::from_millis(20));
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while db_manager.health_check().await.is_err() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("health monitor did not reconnect");
+        monitor.abort();
+    }
 } 
\ No newline at end of file
@@ -1,6 +1,10 @@
 //! Database schema module for AtomSi DAO
 //!
 //! This module contains schema definitions for the database tables.
+//!
+//! These scripts create the full current schema in one go. Databases are
+//! versioned by the paired up/down migrations under `migrations/`, so a
+//! schema change needs a new migration as well as an edit here.
 
 /// Create script for PostgreSQL database
 pub const POSTGRES_SCHEMA: &str = include_str!("postgres.sql");
@@ -7,14 +7,24 @@ CREATE EXTENSION IF NOT EXISTS "uuid-ossp";
 CREATE TABLE IF NOT EXISTS members (
     id VARCHAR(100) PRIMARY KEY,
     address VARCHAR(42) NOT NULL,
-    name VARCHAR(100) NOT NULL,
-    role VARCHAR(20) NOT NULL, -- 'Member', 'Delegate', 'Council', 'Admin'
-    status VARCHAR(20) NOT NULL, -- 'Active', 'Inactive', 'Suspended'
-    reputation INTEGER NOT NULL DEFAULT 0,
-    joined_at BIGINT NOT NULL,
-    last_active_at BIGINT NOT NULL,
-    metadata JSONB,
-    UNIQUE(address)
+    data JSONB NOT NULL, -- the serialized member
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_by VARCHAR(100), -- last writer
+    deleted_at BIGINT -- set when soft-deleted
+);
+
+-- Soft-deleted members don't hold on to their address
+CREATE UNIQUE INDEX IF NOT EXISTS idx_members_address ON members(address) WHERE deleted_at IS NULL;
+
+-- Single-use member invitations
+CREATE TABLE IF NOT EXISTS invitations (
+    id VARCHAR(100) PRIMARY KEY,
+    role VARCHAR(20) NOT NULL, -- invited role
+    expires_at BIGINT NOT NULL,
+    created_at BIGINT NOT NULL,
+    accepted_by VARCHAR(42), -- set once used
+    accepted_at BIGINT
 );
 
 -- Member activities
@@ -36,20 +46,37 @@ CREATE TABLE IF NOT EXISTS member_activities (
 -- Proposals table
 CREATE TABLE IF NOT EXISTS proposals (
     id VARCHAR(100) PRIMARY KEY,
+    number BIGINT UNIQUE, -- sequential display number, assigned on submission
+    data JSONB NOT NULL, -- the serialized proposal
+    state VARCHAR(20) NOT NULL,
     title VARCHAR(200) NOT NULL,
     description TEXT NOT NULL,
-    proposer_id VARCHAR(100) NOT NULL REFERENCES members(id),
-    proposal_type VARCHAR(50) NOT NULL, -- 'Governance', 'Treasury', 'Membership', 'Other'
-    status VARCHAR(20) NOT NULL, -- 'Draft', 'Active', 'Passed', 'Failed', 'Executed', 'Cancelled'
-    created_at BIGINT NOT NULL,
-    voting_starts_at BIGINT,
-    voting_ends_at BIGINT,
-    executed_at BIGINT,
-    execution_data JSONB,
-    metadata JSONB,
-    CONSTRAINT fk_proposer
-        FOREIGN KEY(proposer_id)
-        REFERENCES members(id)
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_by VARCHAR(100), -- last writer
+    deleted_at BIGINT, -- set when soft-deleted
+    -- Full-text search document; the title weighs more than the description
+    search_vector tsvector GENERATED ALWAYS AS (
+        setweight(to_tsvector('english', title), 'A') || setweight(to_tsvector('english', description), 'B')
+    ) STORED
+);
+
+CREATE INDEX IF NOT EXISTS idx_proposals_search ON proposals USING GIN (search_vector);
+
+-- Last assigned proposal number (single row, locked by each submission)
+CREATE TABLE IF NOT EXISTS proposal_sequence (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    last_number BIGINT NOT NULL
+);
+
+INSERT INTO proposal_sequence (id, last_number) VALUES (1, 0) ON CONFLICT (id) DO NOTHING;
+
+-- Proposal content commitments for commit-reveal submission
+CREATE TABLE IF NOT EXISTS proposal_commitments (
+    content_hash VARCHAR(64) PRIMARY KEY,
+    proposer VARCHAR(100) NOT NULL,
+    committed_at BIGINT NOT NULL,
+    revealed_proposal_id VARCHAR(100) REFERENCES proposals(id)
 );
 
 -- Votes table
@@ -74,20 +101,12 @@ CREATE TABLE IF NOT EXISTS votes (
 -- Treasury transactions
 CREATE TABLE IF NOT EXISTS treasury_transactions (
     id VARCHAR(100) PRIMARY KEY,
-    description TEXT NOT NULL,
-    recipient_address VARCHAR(42) NOT NULL,
-    token_symbol VARCHAR(10) NOT NULL,
-    amount BIGINT NOT NULL,
+    data JSONB NOT NULL, -- the serialized transaction
     status VARCHAR(20) NOT NULL, -- 'Pending', 'Approved', 'Executed', 'Rejected', 'Failed'
-    created_at BIGINT NOT NULL,
-    executed_at BIGINT,
-    required_approvals INTEGER NOT NULL,
-    current_approvals INTEGER NOT NULL DEFAULT 0,
-    related_proposal_id VARCHAR(100),
-    metadata JSONB,
-    CONSTRAINT fk_related_proposal
-        FOREIGN KEY(related_proposal_id)
-        REFERENCES proposals(id)
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_by VARCHAR(100), -- last writer
+    deleted_at BIGINT -- set when soft-deleted
 );
 
 -- Treasury transaction approvals
@@ -178,6 +197,32 @@ CREATE TABLE IF NOT EXISTS sessions (
         ON DELETE CASCADE
 );
 
+-- Failed authentication attempts, for login lockout
+CREATE TABLE IF NOT EXISTS login_attempts (
+    address VARCHAR(42) PRIMARY KEY,
+    failed_attempts BIGINT NOT NULL, -- failures since the last success or lockout
+    locked_until BIGINT, -- set while locked out
+    updated_at BIGINT NOT NULL
+);
+
+-- Reusable proposal presets
+CREATE TABLE IF NOT EXISTS proposal_templates (
+    id VARCHAR(100) PRIMARY KEY,
+    name VARCHAR(100) NOT NULL UNIQUE,
+    data JSONB NOT NULL, -- title and description patterns, type and metadata
+    created_at BIGINT NOT NULL,
+    updated_at BIGINT NOT NULL,
+    updated_by VARCHAR(100) NOT NULL -- last writer
+);
+
+-- Progress of contract event pollers
+CREATE TABLE IF NOT EXISTS event_checkpoints (
+    poller TEXT PRIMARY KEY,
+    next_block BIGINT NOT NULL, -- first block not yet delivered
+    block_range BIGINT NOT NULL, -- log scan window for the next poll
+    updated_at BIGINT NOT NULL
+);
+
 -- Settings table
 CREATE TABLE IF NOT EXISTS settings (
     key VARCHAR(100) PRIMARY KEY,
@@ -242,4 +287,88 @@ CREATE TABLE IF NOT EXISTS delegations (
         FOREIGN KEY(token_id)
         REFERENCES tokens(id),
     UNIQUE(delegator_id, delegate_id, token_id)
-); 
\ No newline at end of file
+);
+
+-- Fixed voting weight overrides (append-only; the latest row per address is in effect)
+CREATE TABLE IF NOT EXISTS vote_weight_overrides (
+    id BIGSERIAL PRIMARY KEY,
+    address VARCHAR(42) NOT NULL,
+    weight BIGINT, -- NULL removes the override
+    set_by VARCHAR(42),
+    proposal_id VARCHAR(100),
+    created_at BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_vote_weight_overrides_address ON vote_weight_overrides(LOWER(address));
+
+-- Conviction voting stakes, one per proposal and voter
+CREATE TABLE IF NOT EXISTS conviction (
+    proposal_id VARCHAR(100) NOT NULL,
+    voter VARCHAR(42) NOT NULL,
+    stake BIGINT NOT NULL, -- 0 once withdrawn, while conviction decays
+    conviction BIGINT NOT NULL,
+    updated_at BIGINT NOT NULL, -- start of the period not yet accrued
+    PRIMARY KEY (proposal_id, voter)
+);
+
+CREATE INDEX IF NOT EXISTS idx_conviction_voter ON conviction(voter);
+
+-- Voice credits spent on quadratic votes, one per voter, epoch and proposal
+CREATE TABLE IF NOT EXISTS voice_credit_usage (
+    voter VARCHAR(42) NOT NULL,
+    epoch BIGINT NOT NULL, -- governance.quadratic.epoch_days long
+    proposal_id VARCHAR(100) NOT NULL,
+    credits BIGINT NOT NULL,
+    created_at BIGINT NOT NULL,
+    PRIMARY KEY (voter, epoch, proposal_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_voice_credit_usage_epoch ON voice_credit_usage(epoch);
+
+-- Each voter's voice credit allocation for an epoch, written with every spend
+CREATE TABLE IF NOT EXISTS voice_credit_budgets (
+    voter VARCHAR(42) NOT NULL,
+    epoch BIGINT NOT NULL,
+    credits BIGINT NOT NULL,
+    PRIMARY KEY (voter, epoch)
+);
+
+-- Hash-chained admin action log (append-only)
+CREATE TABLE IF NOT EXISTS admin_audit_log (
+    sequence BIGINT PRIMARY KEY,
+    hash VARCHAR(64) NOT NULL,
+    data JSONB NOT NULL,
+    created_at BIGINT NOT NULL
+);
+
+-- Proposal discussion comments; deleted comments keep their row with an empty body
+CREATE TABLE IF NOT EXISTS proposal_comments (
+    id VARCHAR(100) PRIMARY KEY,
+    proposal_id VARCHAR(100) NOT NULL REFERENCES proposals(id) ON DELETE CASCADE,
+    parent_comment_id VARCHAR(100) REFERENCES proposal_comments(id),
+    author VARCHAR(100) NOT NULL,
+    body TEXT NOT NULL,
+    created_at BIGINT NOT NULL,
+    deleted_at BIGINT
+);
+
+CREATE INDEX IF NOT EXISTS idx_proposal_comments_proposal ON proposal_comments(proposal_id);
+
+-- On-chain treasury executions submitted but not yet settled
+CREATE TABLE IF NOT EXISTS treasury_execution_intents (
+    transaction_id VARCHAR(100) PRIMARY KEY REFERENCES treasury_transactions(id),
+    tx_hash VARCHAR(66),
+    created_at BIGINT NOT NULL
+);
+
+-- Idempotency keys of created treasury transactions
+CREATE TABLE IF NOT EXISTS treasury_idempotency_keys (
+    scope VARCHAR(100) NOT NULL, -- creator, or the system actor
+    idempotency_key VARCHAR(255) NOT NULL,
+    transaction_id VARCHAR(100) NOT NULL,
+    request_hash VARCHAR(64) NOT NULL DEFAULT '', -- SHA-256 of the request the key was used for
+    created_at BIGINT NOT NULL, -- keys expire after treasury.idempotency_window_hours
+    PRIMARY KEY (scope, idempotency_key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_treasury_idempotency_keys_created_at ON treasury_idempotency_keys(created_at);
\ No newline at end of file
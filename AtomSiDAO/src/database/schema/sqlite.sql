@@ -4,14 +4,24 @@
 CREATE TABLE IF NOT EXISTS members (
     id TEXT PRIMARY KEY,
     address TEXT NOT NULL,
-    name TEXT NOT NULL,
-    role TEXT NOT NULL, -- 'Member', 'Delegate', 'Council', 'Admin'
-    status TEXT NOT NULL, -- 'Active', 'Inactive', 'Suspended'
-    reputation INTEGER NOT NULL DEFAULT 0,
-    joined_at INTEGER NOT NULL,
-    last_active_at INTEGER NOT NULL,
-    metadata TEXT,
-    UNIQUE(address)
+    data TEXT NOT NULL, -- the serialized member
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_by TEXT, -- last writer
+    deleted_at INTEGER -- set when soft-deleted
+);
+
+-- Soft-deleted members don't hold on to their address
+CREATE UNIQUE INDEX IF NOT EXISTS idx_members_address ON members(address) WHERE deleted_at IS NULL;
+
+-- Single-use member invitations
+CREATE TABLE IF NOT EXISTS invitations (
+    id TEXT PRIMARY KEY,
+    role TEXT NOT NULL, -- invited role
+    expires_at INTEGER NOT NULL,
+    created_at INTEGER NOT NULL,
+    accepted_by TEXT, -- set once used
+    accepted_at INTEGER
 );
 
 -- Member activities
@@ -30,18 +40,31 @@ CREATE TABLE IF NOT EXISTS member_activities (
 -- Proposals table
 CREATE TABLE IF NOT EXISTS proposals (
     id TEXT PRIMARY KEY,
+    number INTEGER UNIQUE, -- sequential display number, assigned on submission
+    data TEXT NOT NULL, -- the serialized proposal
+    state TEXT NOT NULL,
     title TEXT NOT NULL,
     description TEXT NOT NULL,
-    proposer_id TEXT NOT NULL,
-    proposal_type TEXT NOT NULL, -- 'Governance', 'Treasury', 'Membership', 'Other'
-    status TEXT NOT NULL, -- 'Draft', 'Active', 'Passed', 'Failed', 'Executed', 'Cancelled'
-    created_at INTEGER NOT NULL,
-    voting_starts_at INTEGER,
-    voting_ends_at INTEGER,
-    executed_at INTEGER,
-    execution_data TEXT,
-    metadata TEXT,
-    FOREIGN KEY(proposer_id) REFERENCES members(id)
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_by TEXT, -- last writer
+    deleted_at INTEGER -- set when soft-deleted
+);
+
+-- Last assigned proposal number (single row, locked by each submission)
+CREATE TABLE IF NOT EXISTS proposal_sequence (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    last_number INTEGER NOT NULL
+);
+
+INSERT OR IGNORE INTO proposal_sequence (id, last_number) VALUES (1, 0);
+
+-- Proposal content commitments for commit-reveal submission
+CREATE TABLE IF NOT EXISTS proposal_commitments (
+    content_hash TEXT PRIMARY KEY,
+    proposer TEXT NOT NULL,
+    committed_at INTEGER NOT NULL,
+    revealed_proposal_id TEXT REFERENCES proposals(id)
 );
 
 -- Votes table
@@ -61,18 +84,12 @@ CREATE TABLE IF NOT EXISTS votes (
 -- Treasury transactions
 CREATE TABLE IF NOT EXISTS treasury_transactions (
     id TEXT PRIMARY KEY,
-    description TEXT NOT NULL,
-    recipient_address TEXT NOT NULL,
-    token_symbol TEXT NOT NULL,
-    amount INTEGER NOT NULL,
+    data TEXT NOT NULL, -- the serialized transaction
     status TEXT NOT NULL, -- 'Pending', 'Approved', 'Executed', 'Rejected', 'Failed'
-    created_at INTEGER NOT NULL,
-    executed_at INTEGER,
-    required_approvals INTEGER NOT NULL,
-    current_approvals INTEGER NOT NULL DEFAULT 0,
-    related_proposal_id TEXT,
-    metadata TEXT,
-    FOREIGN KEY(related_proposal_id) REFERENCES proposals(id)
+    created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_by TEXT, -- last writer
+    deleted_at INTEGER -- set when soft-deleted
 );
 
 -- Treasury transaction approvals
@@ -142,6 +159,32 @@ CREATE TABLE IF NOT EXISTS sessions (
     FOREIGN KEY(member_id) REFERENCES members(id) ON DELETE CASCADE
 );
 
+-- Failed authentication attempts, for login lockout
+CREATE TABLE IF NOT EXISTS login_attempts (
+    address TEXT PRIMARY KEY,
+    failed_attempts INTEGER NOT NULL, -- failures since the last success or lockout
+    locked_until INTEGER, -- set while locked out
+    updated_at INTEGER NOT NULL
+);
+
+-- Reusable proposal presets
+CREATE TABLE IF NOT EXISTS proposal_templates (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    data TEXT NOT NULL, -- title and description patterns, type and metadata
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    updated_by TEXT NOT NULL -- last writer
+);
+
+-- Progress of contract event pollers
+CREATE TABLE IF NOT EXISTS event_checkpoints (
+    poller TEXT PRIMARY KEY,
+    next_block INTEGER NOT NULL, -- first block not yet delivered
+    block_range INTEGER NOT NULL, -- log scan window for the next poll
+    updated_at INTEGER NOT NULL
+);
+
 -- Settings table
 CREATE TABLE IF NOT EXISTS settings (
     key TEXT PRIMARY KEY,
@@ -197,4 +240,91 @@ CREATE TABLE IF NOT EXISTS delegations (
     FOREIGN KEY(delegate_id) REFERENCES members(id),
     FOREIGN KEY(token_id) REFERENCES tokens(id),
     UNIQUE(delegator_id, delegate_id, token_id)
-); 
\ No newline at end of file
+);
+
+-- Fixed voting weight overrides (append-only; the latest row per address is in effect)
+CREATE TABLE IF NOT EXISTS vote_weight_overrides (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    address TEXT NOT NULL,
+    weight INTEGER, -- NULL removes the override
+    set_by TEXT,
+    proposal_id TEXT,
+    created_at INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_vote_weight_overrides_address ON vote_weight_overrides(address);
+
+-- Conviction voting stakes, one per proposal and voter
+CREATE TABLE IF NOT EXISTS conviction (
+    proposal_id TEXT NOT NULL,
+    voter TEXT NOT NULL,
+    stake INTEGER NOT NULL, -- 0 once withdrawn, while conviction decays
+    conviction INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL, -- start of the period not yet accrued
+    PRIMARY KEY (proposal_id, voter)
+);
+
+CREATE INDEX IF NOT EXISTS idx_conviction_voter ON conviction(voter);
+
+-- Voice credits spent on quadratic votes, one per voter, epoch and proposal
+CREATE TABLE IF NOT EXISTS voice_credit_usage (
+    voter TEXT NOT NULL,
+    epoch INTEGER NOT NULL, -- governance.quadratic.epoch_days long
+    proposal_id TEXT NOT NULL,
+    credits INTEGER NOT NULL,
+    created_at INTEGER NOT NULL,
+    PRIMARY KEY (voter, epoch, proposal_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_voice_credit_usage_epoch ON voice_credit_usage(epoch);
+
+-- Each voter's voice credit allocation for an epoch, written with every spend
+CREATE TABLE IF NOT EXISTS voice_credit_budgets (
+    voter TEXT NOT NULL,
+    epoch INTEGER NOT NULL,
+    credits INTEGER NOT NULL,
+    PRIMARY KEY (voter, epoch)
+);
+
+-- Hash-chained admin action log (append-only)
+CREATE TABLE IF NOT EXISTS admin_audit_log (
+    sequence INTEGER PRIMARY KEY,
+    hash TEXT NOT NULL,
+    data TEXT NOT NULL,
+    created_at INTEGER NOT NULL
+);
+
+-- Proposal discussion comments; deleted comments keep their row with an empty body
+CREATE TABLE IF NOT EXISTS proposal_comments (
+    id TEXT PRIMARY KEY,
+    proposal_id TEXT NOT NULL,
+    parent_comment_id TEXT,
+    author TEXT NOT NULL,
+    body TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    deleted_at INTEGER,
+    FOREIGN KEY(proposal_id) REFERENCES proposals(id) ON DELETE CASCADE,
+    FOREIGN KEY(parent_comment_id) REFERENCES proposal_comments(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_proposal_comments_proposal ON proposal_comments(proposal_id);
+
+-- On-chain treasury executions submitted but not yet settled
+CREATE TABLE IF NOT EXISTS treasury_execution_intents (
+    transaction_id TEXT PRIMARY KEY,
+    tx_hash TEXT,
+    created_at INTEGER NOT NULL,
+    FOREIGN KEY(transaction_id) REFERENCES treasury_transactions(id)
+);
+
+-- Idempotency keys of created treasury transactions
+CREATE TABLE IF NOT EXISTS treasury_idempotency_keys (
+    scope TEXT NOT NULL, -- creator, or the system actor
+    idempotency_key TEXT NOT NULL,
+    transaction_id TEXT NOT NULL,
+    request_hash TEXT NOT NULL DEFAULT '', -- SHA-256 of the request the key was used for
+    created_at INTEGER NOT NULL, -- keys expire after treasury.idempotency_window_hours
+    PRIMARY KEY (scope, idempotency_key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_treasury_idempotency_keys_created_at ON treasury_idempotency_keys(created_at);
\ No newline at end of file
@@ -0,0 +1,209 @@
+//! Pre-flight checks for AtomSi DAO deployments
+//!
+//! Verifies the configuration, database, and RPC endpoints without starting
+//! the server. Every check is read-only: nothing is migrated, written, or sent.
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::blockchain::BlockchainInterface;
+use crate::config::Config;
+use crate::database::DatabaseManager;
+use crate::error::Result;
+
+/// Outcome of a single check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    /// The check passed
+    Pass,
+    /// Something looks wrong but the DAO can still run
+    Warn,
+    /// The DAO cannot run until this is fixed
+    Fail,
+}
+
+/// Result of a single check
+#[derive(Debug, Clone)]
+pub struct CheckResult {
+    /// What was checked
+    pub name: String,
+    /// Outcome
+    pub status: CheckStatus,
+    /// What was found
+    pub detail: String,
+}
+
+/// Results of all checks, in the order they ran
+#[derive(Debug, Clone, Default)]
+pub struct DoctorReport {
+    checks: Vec<CheckResult>,
+}
+
+impl DoctorReport {
+    /// Get the individual check results
+    pub fn checks(&self) -> &[CheckResult] {
+        &self.checks
+    }
+
+    /// Whether no check failed (warnings are allowed)
+    pub fn is_healthy(&self) -> bool {
+        self.checks.iter().all(|check| check.status != CheckStatus::Fail)
+    }
+
+    fn push(&mut self, name: impl Into<String>, status: CheckStatus, detail: impl Into<String>) {
+        self.checks.push(CheckResult {
+            name: name.into(),
+            status,
+            detail: detail.into(),
+        });
+    }
+}
+
+impl fmt::Display for DoctorReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for check in &self.checks {
+            let status = match check.status {
+                CheckStatus::Pass => "PASS",
+                CheckStatus::Warn => "WARN",
+                CheckStatus::Fail => "FAIL",
+            };
+            writeln!(f, "[{}] {}: {}", status, check.name, check.detail)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Run every check against an already-loaded configuration
+///
+/// `database` and `chains` are the results of connecting, so connection
+/// failures are reported rather than aborting the run. `chains` pairs each
+/// configured chain ID with its adapter.
+pub async fn diagnose(
+    config: &Config,
+    database: &Result<DatabaseManager>,
+    chains: &Result<Vec<(u64, Arc<dyn BlockchainInterface>)>>,
+) -> DoctorReport {
+    let mut report = DoctorReport::default();
+
+    match config.validate() {
+        Ok(()) => report.push("config", CheckStatus::Pass, "configuration is valid"),
+        Err(e) => report.push("config", CheckStatus::Fail, e.to_string()),
+    }
+
+    match database {
+        Ok(database) => {
+            report.push("database", CheckStatus::Pass, format!("connected to {}", config.database.db_type));
+
+            match database.pending_migrations().await {
+                Ok(0) => report.push("migrations", CheckStatus::Pass, "schema is up to date"),
+                Ok(pending) => report.push(
+                    "migrations",
+                    CheckStatus::Warn,
+                    format!("{} pending migration(s)", pending),
+                ),
+                Err(e) => report.push("migrations", CheckStatus::Fail, e.to_string()),
+            }
+        }
+        Err(e) => report.push("database", CheckStatus::Fail, e.to_string()),
+    }
+
+    let chains = match chains {
+        Ok(chains) => chains,
+        Err(e) => {
+            report.push("rpc", CheckStatus::Fail, e.to_string());
+            return report;
+        }
+    };
+
+    for (chain_id, adapter) in chains {
+        check_chain(&mut report, *chain_id, adapter.as_ref()).await;
+    }
+
+    if let Some(executor) = &config.governance.execution_guard.executor_address {
+        match chains.iter().find(|(chain_id, _)| *chain_id == config.blockchain.chain_id) {
+            Some((_, adapter)) => check_executor(&mut report, executor, adapter.as_ref()).await,
+            None => report.push("executor", CheckStatus::Fail, "no adapter for the default chain"),
+        }
+    }
+
+    report
+}
+
+/// Check that an endpoint answers and serves the expected chain
+async fn check_chain(report: &mut DoctorReport, chain_id: u64, adapter: &dyn BlockchainInterface) {
+    let name = format!("rpc (chain {})", chain_id);
+
+    let reported = match adapter.get_chain_id().await {
+        Ok(reported) => reported,
+        Err(e) => return report.push(name, CheckStatus::Fail, e.to_string()),
+    };
+
+    if reported != chain_id {
+        return report.push(
+            name,
+            CheckStatus::Fail,
+            format!("endpoint reports chain {}", reported),
+        );
+    }
+
+    match adapter.get_block_number().await {
+        Ok(block) => report.push(name, CheckStatus::Pass, format!("latest block {}", block)),
+        Err(e) => report.push(name, CheckStatus::Fail, e.to_string()),
+    }
+}
+
+/// Check that the executor account can pay for executions
+async fn check_executor(report: &mut DoctorReport, executor: &str, adapter: &dyn BlockchainInterface) {
+    match adapter.get_balance(executor).await {
+        Ok(balance) if balance.trim() == "0" => report.push(
+            "executor",
+            CheckStatus::Warn,
+            format!("{} has a zero balance", executor),
+        ),
+        Ok(balance) => report.push("executor", CheckStatus::Pass, format!("{} holds {} wei", executor, balance)),
+        Err(e) => report.push("executor", CheckStatus::Fail, e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::MockBlockchain;
+    use crate::config::ConfigManager;
+
+    #[tokio::test]
+    async fn test_clean_report() {
+        let mut config = ConfigManager::with_defaults("doctor_test.json").get_config();
+        config.security.jwt_secret = "0123456789abcdef0123456789abcdef".to_string();
+        config.database.db_type = "sqlite".to_string();
+        config.database.sqlite_path = Some("sqlite::memory:".to_string());
+        // A single connection keeps the in-memory database shared
+        config.database.pool_size = 1;
+        let executor = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+        config.governance.execution_guard.executor_address = Some(executor.to_string());
+
+        let database = DatabaseManager::new(&config.database).await;
+        database.as_ref().unwrap().migrate().await.unwrap();
+
+        let chain = MockBlockchain::new();
+        chain.set_balance(executor, 1_000_000_000_000_000_000);
+        let adapter: Arc<dyn BlockchainInterface> = Arc::new(chain);
+        let chains = Ok(vec![(1, adapter)]);
+
+        let report = diagnose(&config, &database, &chains).await;
+
+        assert!(report.is_healthy(), "{}", report);
+        assert!(report.checks().iter().all(|check| check.status == CheckStatus::Pass), "{}", report);
+        assert_eq!(report.checks().len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_wrong_chain_fails() {
+        let mut report = DoctorReport::default();
+        check_chain(&mut report, 1, &MockBlockchain::new().with_chain_id(5)).await;
+
+        assert!(!report.is_healthy());
+        assert!(report.checks()[0].detail.contains("chain 5"));
+    }
+}
@@ -22,6 +22,10 @@ pub enum Error {
     #[error("Blockchain error: {0}")]
     BlockchainError(String),
     
+    /// Calls the node executed and rejected, such as reverts
+    #[error("Execution error: {0}")]
+    ExecutionError(String),
+    
     /// Proposal errors
     #[error("Proposal error: {0}")]
     ProposalError(String),
@@ -58,6 +62,10 @@ pub enum Error {
     #[error("Validation error: {0}")]
     ValidationError(String),
     
+    /// Operations the target chain or backend does not support
+    #[error("Not supported: {0}")]
+    NotSupported(String),
+    
     /// IO errors
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
@@ -0,0 +1,494 @@
+//! Conviction voting accrual and decay
+//!
+//! Under conviction voting, voters stake tokens on proposals instead of
+//! casting one-off votes. Each period a stake's conviction keeps
+//! `governance.conviction.decay` of itself and adds the stake, so it grows
+//! toward `stake / (1 - decay)` while the stake is held and decays back
+//! toward zero once it is withdrawn.
+//!
+//! A proposal passes once the conviction staked on it reaches a threshold
+//! that grows with the share of the treasury it requests. Stakes are kept in
+//! the `conviction` table, one row per proposal and voter, and advanced by
+//! `GovernanceEngine::process`, which also caps them at each voter's current
+//! balance so tokens moved away after staking stop counting.
+
+use crate::config::ConvictionConfig;
+use crate::core::{Database, DaoError, DbRow, Result, SqlValue};
+use crate::proposals::{Proposal, ProposalId, ProposalType};
+use crate::utils::time::timestamp_to_datetime;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A voter's stake on a proposal and the conviction it has built
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Conviction {
+    /// Proposal staked on
+    pub proposal_id: ProposalId,
+    /// Staking voter
+    pub voter: String,
+    /// Tokens currently staked; 0 once withdrawn
+    pub stake: u64,
+    /// Conviction accrued up to `updated_at`
+    pub conviction: u64,
+    /// Start of the period conviction has not yet accrued for
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Conviction {
+    /// A new, empty stake starting at `now`
+    pub fn new(proposal_id: &str, voter: &str, now: DateTime<Utc>) -> Self {
+        Self {
+            proposal_id: proposal_id.to_string(),
+            voter: voter.to_string(),
+            stake: 0,
+            conviction: 0,
+            // Only whole seconds are stored
+            updated_at: timestamp_to_datetime(now.timestamp().max(0) as u64),
+        }
+    }
+
+    /// Accrue conviction for every whole period elapsed by `now`
+    ///
+    /// A partial period carries over to the next call.
+    pub fn advance(&mut self, now: DateTime<Utc>, config: &ConvictionConfig) {
+        let period = config.period_seconds.max(1) as i64;
+        let periods = (now - self.updated_at).num_seconds() / period;
+        if periods <= 0 {
+            return;
+        }
+
+        self.conviction = accrue(self.conviction, self.stake, periods as u64, config.decay);
+        self.updated_at = self.updated_at + Duration::seconds(periods * period);
+    }
+
+    /// Whether the row no longer holds a stake or any conviction
+    pub fn is_spent(&self) -> bool {
+        self.stake == 0 && self.conviction == 0
+    }
+}
+
+/// Conviction after holding `stake` for `periods` periods
+///
+/// Closed form of applying `conviction = conviction * decay + stake` once
+/// per period.
+pub fn accrue(conviction: u64, stake: u64, periods: u64, decay: f64) -> u64 {
+    if periods == 0 {
+        return conviction;
+    }
+
+    let kept = decay.powf(periods as f64);
+    let accrued = conviction as f64 * kept + stake as f64 * (1.0 - kept) / (1.0 - decay);
+
+    // Float to integer casts saturate
+    accrued.round() as u64
+}
+
+/// Conviction a stake approaches when held indefinitely
+pub fn max_conviction(stake: u64, decay: f64) -> u64 {
+    (stake as f64 / (1.0 - decay)).round() as u64
+}
+
+/// Conviction a proposal needs to pass
+///
+/// `weight * staked_supply / (max_ratio - requested / funds)^2`, divided by
+/// `1 - decay` to put it in conviction units. Returns `None` for proposals requesting `max_ratio` of
+/// the funds or more, which can never pass.
+pub fn threshold(requested: u64, funds: u64, staked_supply: u64, config: &ConvictionConfig) -> Option<u64> {
+    let share = match (requested, funds) {
+        (0, _) => 0.0,
+        (_, 0) => return None,
+        (requested, funds) => requested as f64 / funds as f64,
+    };
+    if share >= config.max_ratio {
+        return None;
+    }
+
+    let threshold = config.weight * staked_supply as f64 / (config.max_ratio - share).powi(2) / (1.0 - config.decay);
+
+    Some(threshold.ceil() as u64)
+}
+
+/// Funds a proposal requests from the treasury; 0 for proposals that move none
+pub fn requested_funds(proposal: &Proposal) -> u64 {
+    match &proposal.proposal_type {
+        ProposalType::Transfer { amount, .. } => *amount,
+        _ => 0,
+    }
+}
+
+/// Tokens staked across all proposals, against which thresholds scale
+pub(crate) fn staked_supply(stakes: &[Conviction]) -> u64 {
+    stakes.iter().map(|stake| stake.stake).fold(0, u64::saturating_add)
+}
+
+/// Total conviction per proposal
+pub(crate) fn totals_by_proposal(stakes: &[Conviction]) -> BTreeMap<ProposalId, u64> {
+    let mut totals = BTreeMap::new();
+    for stake in stakes {
+        let total = totals.entry(stake.proposal_id.clone()).or_insert(0u64);
+        *total = total.saturating_add(stake.conviction);
+    }
+
+    totals
+}
+
+/// Stake `amount` of a voter's `balance` on a proposal; 0 withdraws
+///
+/// Conviction built by the previous stake is accrued first. A voter's stakes
+/// across all proposals can't exceed their balance.
+///
+/// Portable: runs on PostgreSQL and SQLite.
+pub(crate) async fn set_stake(
+    database: &Database,
+    proposal_id: &str,
+    voter: &str,
+    amount: u64,
+    balance: u64,
+    now: DateTime<Utc>,
+    config: &ConvictionConfig,
+) -> Result<Conviction> {
+    let stakes = load_stakes(database, "WHERE voter = $1", &[voter.into()]).await?;
+
+    let staked_elsewhere = stakes
+        .iter()
+        .filter(|stake| stake.proposal_id != proposal_id)
+        .map(|stake| stake.stake)
+        .fold(0, u64::saturating_add);
+    if staked_elsewhere.saturating_add(amount) > balance {
+        return Err(DaoError::InvalidParameter(format!(
+            "Stake of {} exceeds the {} not already staked on other proposals",
+            amount,
+            balance.saturating_sub(staked_elsewhere)
+        )));
+    }
+
+    let mut stake = stakes
+        .into_iter()
+        .find(|stake| stake.proposal_id == proposal_id)
+        .unwrap_or_else(|| Conviction::new(proposal_id, voter, now));
+    stake.advance(now, config);
+    stake.stake = amount;
+
+    save_stake(database, &stake).await?;
+
+    Ok(stake)
+}
+
+/// Scale each voter's stakes down to fit their balance
+///
+/// Voters holding less than they have staked keep the same split across
+/// proposals. Voters missing from `balances` are left as they are.
+pub(crate) fn cap_stakes(stakes: &mut [Conviction], balances: &BTreeMap<String, u64>) {
+    let mut totals: BTreeMap<String, u64> = BTreeMap::new();
+    for stake in stakes.iter() {
+        let total = totals.entry(stake.voter.clone()).or_insert(0);
+        *total = total.saturating_add(stake.stake);
+    }
+
+    for stake in stakes.iter_mut() {
+        let (Some(&balance), Some(&total)) = (balances.get(&stake.voter), totals.get(&stake.voter)) else {
+            continue;
+        };
+        if total > balance {
+            stake.stake = (u128::from(stake.stake) * u128::from(balance) / u128::from(total)) as u64;
+        }
+    }
+}
+
+/// Voters with a stake on any proposal
+///
+/// Portable: runs on PostgreSQL and SQLite.
+pub(crate) async fn stakers(database: &Database) -> Result<Vec<String>> {
+    database
+        .query_any("SELECT DISTINCT voter FROM conviction WHERE stake > 0 ORDER BY voter", &[])
+        .await?
+        .iter()
+        .map(|row| row.text("voter"))
+        .collect()
+}
+
+/// Advance every stake to `now`, dropping those with nothing left
+///
+/// Stakes are first capped at the voters' `balances`, so conviction only
+/// accrues on tokens still held.
+///
+/// Portable: runs on PostgreSQL and SQLite.
+pub(crate) async fn advance_all(
+    database: &Database,
+    now: DateTime<Utc>,
+    balances: &BTreeMap<String, u64>,
+    config: &ConvictionConfig,
+) -> Result<Vec<Conviction>> {
+    let mut stakes = load_stakes(database, "", &[]).await?;
+    cap_stakes(&mut stakes, balances);
+
+    let mut kept = Vec::new();
+
+    for mut stake in stakes {
+        stake.advance(now, config);
+        if stake.is_spent() {
+            delete_stake(database, &stake).await?;
+        } else {
+            save_stake(database, &stake).await?;
+            kept.push(stake);
+        }
+    }
+
+    Ok(kept)
+}
+
+/// Stakes on a proposal, advanced to `now` without saving
+///
+/// Portable: runs on PostgreSQL and SQLite.
+pub(crate) async fn proposal_stakes(
+    database: &Database,
+    proposal_id: &str,
+    now: DateTime<Utc>,
+    config: &ConvictionConfig,
+) -> Result<Vec<Conviction>> {
+    let mut stakes = load_stakes(database, "WHERE proposal_id = $1", &[proposal_id.into()]).await?;
+    for stake in &mut stakes {
+        stake.advance(now, config);
+    }
+
+    Ok(stakes)
+}
+
+/// Return the stakes on a proposal that passed or closed to their voters
+///
+/// Portable: runs on PostgreSQL and SQLite.
+pub(crate) async fn release_proposal(database: &Database, proposal_id: &str) -> Result<()> {
+    database
+        .execute_any("DELETE FROM conviction WHERE proposal_id = $1", &[proposal_id.into()])
+        .await?;
+
+    Ok(())
+}
+
+async fn load_stakes(database: &Database, filter: &str, params: &[SqlValue]) -> Result<Vec<Conviction>> {
+    database
+        .query_any(
+            &format!(
+                "SELECT proposal_id, voter, stake, conviction, updated_at FROM conviction {} ORDER BY proposal_id, voter",
+                filter
+            ),
+            params,
+        )
+        .await?
+        .iter()
+        .map(conviction_from_row)
+        .collect()
+}
+
+async fn save_stake(database: &Database, stake: &Conviction) -> Result<()> {
+    database
+        .execute_any(
+            "INSERT INTO conviction (proposal_id, voter, stake, conviction, updated_at) VALUES ($1, $2, $3, $4, $5) \
+             ON CONFLICT (proposal_id, voter) DO UPDATE SET stake = excluded.stake, \
+             conviction = excluded.conviction, updated_at = excluded.updated_at",
+            &[
+                stake.proposal_id.as_str().into(),
+                stake.voter.as_str().into(),
+                (stake.stake as i64).into(),
+                (stake.conviction.min(i64::MAX as u64) as i64).into(),
+                stake.updated_at.timestamp().into(),
+            ],
+        )
+        .await?;
+
+    Ok(())
+}
+
+async fn delete_stake(database: &Database, stake: &Conviction) -> Result<()> {
+    database
+        .execute_any(
+            "DELETE FROM conviction WHERE proposal_id = $1 AND voter = $2",
+            &[stake.proposal_id.as_str().into(), stake.voter.as_str().into()],
+        )
+        .await?;
+
+    Ok(())
+}
+
+fn conviction_from_row(row: &DbRow) -> Result<Conviction> {
+    Ok(Conviction {
+        proposal_id: row.text("proposal_id")?,
+        voter: row.text("voter")?,
+        stake: row.int("stake")? as u64,
+        conviction: row.int("conviction")? as u64,
+        updated_at: timestamp_to_datetime(row.int("updated_at")? as u64),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROPOSAL: &str = "AIP-1";
+    const VOTER: &str = "0x0000000000000000000000000000000000000001";
+
+    fn config() -> ConvictionConfig {
+        ConvictionConfig {
+            period_seconds: 3600,
+            decay: 0.9,
+            max_ratio: 0.2,
+            weight: 0.005,
+        }
+    }
+
+    fn start() -> DateTime<Utc> {
+        timestamp_to_datetime(1_700_000_000)
+    }
+
+    fn hours(n: i64) -> DateTime<Utc> {
+        start() + Duration::hours(n)
+    }
+
+    #[test]
+    fn test_conviction_grows_toward_cap() {
+        let config = config();
+        let mut stake = Conviction::new(PROPOSAL, VOTER, start());
+        stake.stake = 1_000;
+
+        // Nothing accrues within the first period
+        stake.advance(start() + Duration::minutes(59), &config);
+        assert_eq!(stake.conviction, 0);
+
+        // 90 minutes is one whole period; the other half carries over
+        stake.advance(start() + Duration::minutes(90), &config);
+        assert_eq!(stake.conviction, 1_000);
+        assert_eq!(stake.updated_at, hours(1));
+
+        stake.advance(hours(2), &config);
+        assert_eq!(stake.conviction, 1_900);
+
+        // Advancing in one step matches advancing period by period
+        let mut stepped = stake.clone();
+        for hour in 3..=10 {
+            stepped.advance(hours(hour), &config);
+        }
+        stake.advance(hours(10), &config);
+        assert!(stake.conviction.abs_diff(stepped.conviction) <= 1);
+        assert_eq!(stake.conviction, 6_513); // 10,000 * (1 - 0.9^10)
+
+        // Growth slows and never passes the cap
+        stake.advance(hours(500), &config);
+        assert!(stake.conviction <= max_conviction(1_000, config.decay));
+        assert!(stake.conviction >= 9_990);
+    }
+
+    #[test]
+    fn test_conviction_decays_after_withdrawal() {
+        let config = config();
+        let mut stake = Conviction::new(PROPOSAL, VOTER, start());
+        stake.stake = 1_000;
+        stake.advance(hours(10), &config);
+        let built = stake.conviction;
+
+        stake.stake = 0;
+        stake.advance(hours(11), &config);
+        assert_eq!(stake.conviction, (built as f64 * 0.9).round() as u64);
+
+        stake.advance(hours(20), &config);
+        assert!(stake.conviction < built / 2);
+
+        // Eventually nothing is left
+        stake.advance(hours(1_000), &config);
+        assert!(stake.is_spent());
+    }
+
+    #[test]
+    fn test_threshold_scales_with_requested_funds() {
+        let config = config();
+
+        let signal = threshold(0, 10_000, 1_000, &config).unwrap();
+        let small = threshold(500, 10_000, 1_000, &config).unwrap();
+        let large = threshold(1_500, 10_000, 1_000, &config).unwrap();
+        assert!(signal < small && small < large);
+
+        // Twice the stake raises the bar twice as high
+        assert!(threshold(500, 10_000, 2_000, &config).unwrap().abs_diff(2 * small) <= 1);
+
+        // Requests of max_ratio or more, or from an empty treasury, never pass
+        assert_eq!(threshold(2_000, 10_000, 1_000, &config), None);
+        assert_eq!(threshold(1, 0, 1_000, &config), None);
+    }
+
+    #[test]
+    fn test_proposal_crosses_threshold() {
+        let config = config();
+        let mut stake = Conviction::new(PROPOSAL, VOTER, start());
+        stake.stake = 1_000;
+
+        // Requesting 10% of the funds needs half the conviction the whole
+        // staked supply can reach
+        let threshold = threshold(100, 1_000, staked_supply(&[stake.clone()]), &config).unwrap();
+        assert!(threshold.abs_diff(5_000) <= 1);
+
+        let mut crossed_at = None;
+        for hour in 1..=24 {
+            stake.advance(hours(hour), &config);
+            if stake.conviction >= threshold {
+                crossed_at = Some(hour);
+                break;
+            }
+        }
+
+        // 0.9^6 > 0.5 > 0.9^7
+        assert_eq!(crossed_at, Some(7));
+    }
+
+    #[tokio::test]
+    async fn test_stakes_persist_and_advance() {
+        let database = Database::migrated_sqlite().await;
+        let config = config();
+
+        set_stake(&database, PROPOSAL, VOTER, 600, 1_000, start(), &config).await.unwrap();
+        set_stake(&database, "AIP-2", VOTER, 400, 1_000, start(), &config).await.unwrap();
+
+        // The voter's balance is fully staked
+        assert!(set_stake(&database, "AIP-3", VOTER, 1, 1_000, start(), &config).await.is_err());
+
+        let balances = BTreeMap::from([(VOTER.to_string(), 1_000)]);
+        let stakes = advance_all(&database, hours(2), &balances, &config).await.unwrap();
+        assert_eq!(staked_supply(&stakes), 1_000);
+        let totals = totals_by_proposal(&stakes);
+        assert_eq!(totals[PROPOSAL], 1_140);
+        assert_eq!(totals["AIP-2"], 760);
+
+        // Withdrawing keeps the conviction built so far, which then decays
+        let withdrawn = set_stake(&database, PROPOSAL, VOTER, 0, 1_000, hours(3), &config).await.unwrap();
+        assert_eq!(withdrawn.conviction, 1_626);
+        let stakes = proposal_stakes(&database, PROPOSAL, hours(4), &config).await.unwrap();
+        assert_eq!(stakes[0].conviction, 1_463);
+
+        release_proposal(&database, "AIP-2").await.unwrap();
+        let stakes = advance_all(&database, hours(4), &balances, &config).await.unwrap();
+        assert_eq!(stakes.len(), 1);
+        assert_eq!(stakes[0].proposal_id, PROPOSAL);
+    }
+
+    #[tokio::test]
+    async fn test_stakes_are_capped_at_the_current_balance() {
+        let database = Database::migrated_sqlite().await;
+        let config = config();
+
+        set_stake(&database, PROPOSAL, VOTER, 600, 1_000, start(), &config).await.unwrap();
+        set_stake(&database, "AIP-2", VOTER, 400, 1_000, start(), &config).await.unwrap();
+        assert_eq!(stakers(&database).await.unwrap(), vec![VOTER.to_string()]);
+
+        // Half the tokens were moved away after staking
+        let balances = BTreeMap::from([(VOTER.to_string(), 500)]);
+        let stakes = advance_all(&database, hours(1), &balances, &config).await.unwrap();
+        assert_eq!(staked_supply(&stakes), 500);
+        let totals = totals_by_proposal(&stakes);
+        assert_eq!(totals[PROPOSAL], 300);
+        assert_eq!(totals["AIP-2"], 200);
+
+        // The capped stakes are saved, so the freed tokens can't be staked twice
+        let stakes = proposal_stakes(&database, PROPOSAL, hours(1), &config).await.unwrap();
+        assert_eq!(stakes[0].stake, 300);
+        assert!(set_stake(&database, "AIP-3", VOTER, 1, 500, hours(1), &config).await.is_err());
+    }
+}
@@ -0,0 +1,288 @@
+//! Voice credit budgets for quadratic voting
+//!
+//! Under quadratic voting, voters choose how much weight to put behind each
+//! vote and pay `weight^2` voice credits for it, so doubling a vote's weight
+//! costs four times as much. Every member gets
+//! `governance.quadratic.voice_credits` per epoch of
+//! `governance.quadratic.epoch_days` days; credits spent on proposals still
+//! being voted on count against it, and the budget starts over each epoch.
+//!
+//! Spending is kept in the `voice_credit_usage` table, one row per voter,
+//! epoch and proposal, and each voter's allocation for an epoch in
+//! `voice_credit_budgets`.
+
+use crate::config::QuadraticConfig;
+use crate::core::{Database, DaoError, Result, SqlValue};
+use crate::proposals::ProposalId;
+use chrono::{DateTime, Utc};
+
+/// Voice credits a vote of `weight` costs, or `None` if it overflows
+pub fn vote_cost(weight: u64) -> Option<u64> {
+    weight.checked_mul(weight)
+}
+
+/// Governance epoch `now` falls in
+pub fn epoch_at(now: DateTime<Utc>, config: &QuadraticConfig) -> u64 {
+    let epoch_seconds = u64::from(config.epoch_days.max(1)) * 86_400;
+    now.timestamp().max(0) as u64 / epoch_seconds
+}
+
+/// Credits a voter spent in `epoch` on the proposals in `active`
+///
+/// Portable: runs on PostgreSQL and SQLite.
+pub(crate) async fn spent(database: &Database, voter: &str, epoch: u64, active: &[ProposalId]) -> Result<u64> {
+    let rows = database
+        .query_any(
+            "SELECT proposal_id, credits FROM voice_credit_usage WHERE voter = $1 AND epoch = $2",
+            &[voter.into(), (epoch as i64).into()],
+        )
+        .await?;
+
+    let mut total = 0u64;
+    for row in &rows {
+        if active.contains(&row.text("proposal_id")?) {
+            total = total.saturating_add(row.int("credits")? as u64);
+        }
+    }
+
+    Ok(total)
+}
+
+/// Credits a voter has left to spend in `epoch`
+///
+/// Portable: runs on PostgreSQL and SQLite.
+pub(crate) async fn remaining(
+    database: &Database,
+    voter: &str,
+    epoch: u64,
+    active: &[ProposalId],
+    config: &QuadraticConfig,
+) -> Result<u64> {
+    let spent = spent(database, voter, epoch, active).await?;
+    Ok(config.voice_credits.saturating_sub(spent))
+}
+
+/// Charge a voter for a vote of `weight` on a proposal
+///
+/// Refused when the cost exceeds what the voter has left across `active`,
+/// or when they already spent credits on the proposal this epoch. Returns
+/// the credits left afterwards.
+///
+/// The budget check and the charge happen in one statement, in a database
+/// transaction that first writes the voter's budget row. Concurrent spends
+/// by the same voter in the epoch wait on that row, so they can't both pass
+/// the check and overspend.
+///
+/// Portable: runs on PostgreSQL and SQLite.
+pub(crate) async fn spend(
+    database: &Database,
+    voter: &str,
+    proposal_id: &str,
+    weight: u64,
+    epoch: u64,
+    active: &[ProposalId],
+    config: &QuadraticConfig,
+) -> Result<u64> {
+    if weight == 0 {
+        return Err(DaoError::InvalidParameter(
+            "Vote weight must be at least 1".to_string(),
+        ));
+    }
+
+    let available = remaining(database, voter, epoch, active, config).await?;
+    let cost = match vote_cost(weight) {
+        Some(cost) if cost <= available => cost,
+        cost => return Err(over_budget(weight, cost, available)),
+    };
+
+    let budget = config.voice_credits.min(i64::MAX as u64) as i64;
+    let mut params: Vec<SqlValue> = vec![
+        voter.into(),
+        (epoch as i64).into(),
+        proposal_id.into(),
+        (cost as i64).into(),
+        Utc::now().timestamp().into(),
+    ];
+    let active_filter = if active.is_empty() {
+        "1 = 0".to_string()
+    } else {
+        let placeholders: Vec<String> = (0..active.len()).map(|i| format!("${}", params.len() + i + 1)).collect();
+        format!("proposal_id IN ({})", placeholders.join(", "))
+    };
+    params.extend(active.iter().map(|id| SqlValue::from(id.as_str())));
+
+    let charge = format!(
+        "INSERT INTO voice_credit_usage (voter, epoch, proposal_id, credits, created_at) \
+         SELECT CAST($1 AS TEXT), CAST($2 AS BIGINT), CAST($3 AS TEXT), CAST($4 AS BIGINT), CAST($5 AS BIGINT) \
+         WHERE CAST($4 AS BIGINT) + (SELECT COALESCE(SUM(credits), 0) FROM voice_credit_usage \
+         WHERE voter = $1 AND epoch = $2 AND {}) \
+         <= (SELECT credits FROM voice_credit_budgets WHERE voter = $1 AND epoch = $2) \
+         ON CONFLICT (voter, epoch, proposal_id) DO NOTHING",
+        active_filter
+    );
+    let affected = database
+        .execute_batch_any(&[
+            (
+                "INSERT INTO voice_credit_budgets (voter, epoch, credits) VALUES ($1, $2, $3) \
+                 ON CONFLICT (voter, epoch) DO UPDATE SET credits = excluded.credits",
+                vec![voter.into(), (epoch as i64).into(), budget.into()],
+            ),
+            (charge.as_str(), params),
+        ])
+        .await?;
+
+    if affected.get(1).copied().unwrap_or(0) == 0 {
+        let already_spent = database
+            .query_opt_any(
+                "SELECT 1 AS spent FROM voice_credit_usage WHERE voter = $1 AND epoch = $2 AND proposal_id = $3",
+                &[voter.into(), (epoch as i64).into(), proposal_id.into()],
+            )
+            .await?
+            .is_some();
+        if already_spent {
+            return Err(DaoError::InvalidParameter(format!(
+                "Voice credits already spent on proposal {} this epoch",
+                proposal_id
+            )));
+        }
+
+        // A concurrent vote spent the credits first
+        let available = remaining(database, voter, epoch, active, config).await?;
+        return Err(over_budget(weight, Some(cost), available));
+    }
+
+    Ok(available - cost)
+}
+
+fn over_budget(weight: u64, cost: Option<u64>, available: u64) -> DaoError {
+    DaoError::InvalidParameter(format!(
+        "Vote of weight {} costs {} voice credits but only {} remain this epoch",
+        weight,
+        cost.map_or_else(|| "more".to_string(), |cost| cost.to_string()),
+        available
+    ))
+}
+
+/// Give back the credits charged for a vote that was not recorded
+///
+/// Portable: runs on PostgreSQL and SQLite.
+pub(crate) async fn refund(database: &Database, voter: &str, epoch: u64, proposal_id: &str) -> Result<()> {
+    database
+        .execute_any(
+            "DELETE FROM voice_credit_usage WHERE voter = $1 AND epoch = $2 AND proposal_id = $3",
+            &[voter.into(), (epoch as i64).into(), proposal_id.into()],
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Delete credit usage and budgets from epochs before `epoch`
+///
+/// Returns the number of usage rows deleted. Portable: runs on PostgreSQL
+/// and SQLite.
+pub(crate) async fn purge_before(database: &Database, epoch: u64) -> Result<u64> {
+    let affected = database
+        .execute_batch_any(&[
+            ("DELETE FROM voice_credit_usage WHERE epoch < $1", vec![(epoch as i64).into()]),
+            ("DELETE FROM voice_credit_budgets WHERE epoch < $1", vec![(epoch as i64).into()]),
+        ])
+        .await?;
+
+    Ok(affected[0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::time::timestamp_to_datetime;
+
+    const VOTER: &str = "0x0000000000000000000000000000000000000001";
+
+    fn config() -> QuadraticConfig {
+        QuadraticConfig {
+            voice_credits: 100,
+            epoch_days: 30,
+        }
+    }
+
+    fn active(ids: &[&str]) -> Vec<ProposalId> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn test_vote_cost_is_weight_squared() {
+        assert_eq!(vote_cost(1), Some(1));
+        assert_eq!(vote_cost(10), Some(100));
+        assert_eq!(vote_cost(u64::MAX), None);
+    }
+
+    #[test]
+    fn test_epoch_boundaries() {
+        let config = config();
+        let epoch_seconds = 30 * 86_400;
+
+        assert_eq!(epoch_at(timestamp_to_datetime(epoch_seconds - 1), &config), 0);
+        assert_eq!(epoch_at(timestamp_to_datetime(epoch_seconds), &config), 1);
+    }
+
+    #[tokio::test]
+    async fn test_credits_spent_across_proposals_until_budget_runs_out() {
+        let database = Database::migrated_sqlite().await;
+        let config = config();
+        let active = active(&["AIP-1", "AIP-2", "AIP-3"]);
+
+        // Weight 6 costs 36 and weight 8 costs 64, using the whole budget
+        assert_eq!(spend(&database, VOTER, "AIP-1", 6, 0, &active, &config).await.unwrap(), 64);
+        assert_eq!(spend(&database, VOTER, "AIP-2", 8, 0, &active, &config).await.unwrap(), 0);
+
+        let result = spend(&database, VOTER, "AIP-3", 1, 0, &active, &config).await;
+        assert!(matches!(result, Err(DaoError::InvalidParameter(_))));
+        assert_eq!(spent(&database, VOTER, 0, &active).await.unwrap(), 100);
+
+        // Other voters have their own budget
+        let other = "0x0000000000000000000000000000000000000002";
+        assert_eq!(spend(&database, other, "AIP-3", 10, 0, &active, &config).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_over_budget_vote_rejected() {
+        let database = Database::migrated_sqlite().await;
+        let config = config();
+        let active = active(&["AIP-1", "AIP-2"]);
+
+        // 11^2 = 121 never fits a budget of 100
+        assert!(spend(&database, VOTER, "AIP-1", 11, 0, &active, &config).await.is_err());
+
+        // 7^2 = 49 leaves 51, which 8^2 = 64 exceeds
+        spend(&database, VOTER, "AIP-1", 7, 0, &active, &config).await.unwrap();
+        let error = spend(&database, VOTER, "AIP-2", 8, 0, &active, &config).await.unwrap_err();
+        assert!(error.to_string().contains("only 51 remain"));
+
+        // Nothing was charged for the rejected votes, nor twice for one proposal
+        assert!(spend(&database, VOTER, "AIP-1", 1, 0, &active, &config).await.is_err());
+        assert_eq!(remaining(&database, VOTER, 0, &active, &config).await.unwrap(), 51);
+        assert!(spend(&database, VOTER, "AIP-1", 0, 0, &active, &config).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_closed_proposals_and_new_epochs_free_credits() {
+        let database = Database::migrated_sqlite().await;
+        let config = config();
+
+        spend(&database, VOTER, "AIP-1", 10, 0, &active(&["AIP-1", "AIP-2"]), &config).await.unwrap();
+
+        // Credits spent on a proposal no longer being voted on don't count
+        assert_eq!(remaining(&database, VOTER, 0, &active(&["AIP-2"]), &config).await.unwrap(), 100);
+
+        // A new epoch starts with the full budget
+        assert_eq!(remaining(&database, VOTER, 1, &active(&["AIP-1"]), &config).await.unwrap(), 100);
+        spend(&database, VOTER, "AIP-1", 10, 1, &active(&["AIP-1"]), &config).await.unwrap();
+
+        // A refunded vote gives its credits back
+        refund(&database, VOTER, 1, "AIP-1").await.unwrap();
+        assert_eq!(remaining(&database, VOTER, 1, &active(&["AIP-1"]), &config).await.unwrap(), 100);
+
+        assert_eq!(purge_before(&database, 1).await.unwrap(), 1);
+    }
+}
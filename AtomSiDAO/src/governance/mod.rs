@@ -3,17 +3,36 @@
 //! This module provides functionality for governance operations
 //! including voting strategies, delegation, and vote counting.
 
+pub mod conviction;
+pub mod credits;
+mod overrides;
+mod parameters;
+mod records;
 mod strategies;
 
+pub use conviction::Conviction;
+pub use overrides::{
+    OverrideAuthority, VoteWeightOverride, VoteWeightOverrideChange, VOTE_WEIGHT_OVERRIDE_PARAMETER,
+};
+pub(crate) use overrides::{apply_override, current_override, parse_override_change, record_override};
+pub use parameters::{GovernanceParameter, ParameterSource, AMENDABLE_PARAMETERS};
+pub(crate) use parameters::{
+    amend, apply_parameter, default_parameters, effective_parameters, load_amendments,
+};
+pub use records::{VoteOutcome, VotingRecord, VotingRecordEntry, VotingRecordQuery, VotingRecordSummary};
 pub use strategies::{VoteWeight, VotingStrategy};
 
 use crate::{
     blockchain::BlockchainAdapter,
     config::Config,
-    core::{Database, DaoError, Result},
-    proposals::{ProposalManager, ProposalVote},
+    core::{Address, Database, DaoError, Result},
+    identity::IdentityManager,
+    proposals::{parse_balance, ProposalId, ProposalManager, ProposalState, ProposalVote, VoteReceipt, VoteSignature},
 };
+use chrono::Utc;
+use std::collections::BTreeMap;
 use std::sync::Arc;
+use tracing::{info, warn};
 
 /// Re-export proposal vote type for convenience
 pub use crate::proposals::ProposalVote as Vote;
@@ -30,6 +49,8 @@ pub struct GovernanceEngine {
     token_manager: Arc<crate::token::TokenManager>,
     /// Proposal manager reference
     proposal_manager: Arc<ProposalManager>,
+    /// Identity manager, for strategies weighted by reputation
+    identity_manager: Option<Arc<IdentityManager>>,
     /// Voting strategy
     voting_strategy: Box<dyn VotingStrategy>,
 }
@@ -44,14 +65,7 @@ impl GovernanceEngine {
         proposal_manager: &ProposalManager,
     ) -> Result<Self> {
         // Create the default voting strategy based on configuration
-        let voting_strategy: Box<dyn VotingStrategy> = match config.dao.governance_token.as_str() {
-            // If the governance token is set to "Quadratic", use quadratic voting
-            "Quadratic" => Box::new(strategies::QuadraticVoting::new()),
-            // If the governance token is set to "Conviction", use conviction voting
-            "Conviction" => Box::new(strategies::ConvictionVoting::new()),
-            // Otherwise, use token-weighted voting
-            _ => Box::new(strategies::TokenWeightedVoting::new()),
-        };
+        let voting_strategy = configured_strategy(config);
         
         Ok(Self {
             config: Arc::new(config.clone()),
@@ -59,30 +73,90 @@ impl GovernanceEngine {
             database,
             token_manager: Arc::new(token_manager.clone()),
             proposal_manager: Arc::new(proposal_manager.clone()),
+            identity_manager: None,
             voting_strategy,
         })
     }
     
+    /// Use an identity manager to read member reputation
+    ///
+    /// Required when the voting strategy weighs reputation.
+    pub fn with_identity_manager(mut self, identity_manager: IdentityManager) -> Self {
+        self.identity_manager = Some(Arc::new(identity_manager));
+        self
+    }
+    
     /// Set the voting strategy
     pub fn set_voting_strategy(&mut self, strategy: Box<dyn VotingStrategy>) {
         self.voting_strategy = strategy;
     }
     
     /// Get the voting weight for an address
-    pub async fn get_voting_weight(&self, address: &str) -> Result<VoteWeight> {
+    ///
+    /// A fixed weight override for the address replaces or adds to the
+    /// token-derived weight, per `governance.vote_weight_override_mode`.
+    pub async fn get_voting_weight(&self, address: &Address) -> Result<VoteWeight> {
         // Get the token balance for the address
         let balance = self
             .blockchain
-            .balance(address)
+            .balance(address.as_str())
             .await
             .map_err(|e| DaoError::BlockchainError(e))?;
         
         // Calculate the voting weight using the strategy
-        self.voting_strategy.calculate_weight(address, balance)
+        let mut weight = if self.voting_strategy.uses_reputation() {
+            let reputation = self.member_reputation(address).await?;
+            self.voting_strategy
+                .calculate_member_weight(address.as_str(), balance, reputation)?
+        } else {
+            self.voting_strategy.calculate_weight(address.as_str(), balance)?
+        };
+        
+        if let Some(fixed) = current_override(&self.database, address.as_str()).await? {
+            weight.value = apply_override(weight.value, Some(fixed), self.config.governance.vote_weight_override_mode);
+            weight.metadata.insert("fixed_weight".to_string(), fixed.to_string());
+        }
+        
+        Ok(weight)
+    }
+    
+    /// Reputation of the member at `address`, or 0 for non-members
+    async fn member_reputation(&self, address: &Address) -> Result<u64> {
+        let identity_manager = self.identity_manager.as_ref().ok_or_else(|| {
+            DaoError::InternalError(format!(
+                "{} requires an identity manager",
+                self.voting_strategy.name()
+            ))
+        })?;
+        
+        if !identity_manager.member_exists(address).await? {
+            return Ok(0);
+        }
+        
+        Ok(identity_manager.get_member_by_address(address).await?.reputation)
+    }
+    
+    /// Set or remove (`None`) a fixed voting weight for an address
+    ///
+    /// Only active Council members and above, going by their stored role, or
+    /// executed proposals may change overrides. Every change is kept for
+    /// auditing.
+    pub async fn set_vote_weight_override(
+        &self,
+        address: &Address,
+        weight: Option<u64>,
+        authority: &OverrideAuthority,
+    ) -> Result<()> {
+        record_override(&self.database, address.as_str(), weight, authority).await
+    }
+    
+    /// Get the history of fixed weight changes for an address, oldest first
+    pub async fn vote_weight_override_history(&self, address: &Address) -> Result<Vec<VoteWeightOverride>> {
+        overrides::override_history(&self.database, address.as_str()).await
     }
     
     /// Get the voting power for an address
-    pub async fn get_voting_power(&self, address: &str) -> Result<u64> {
+    pub async fn get_voting_power(&self, address: &Address) -> Result<u64> {
         // Get the voting weight
         let weight = self.get_voting_weight(address).await?;
         
@@ -90,23 +164,122 @@ impl GovernanceEngine {
         Ok(weight.value)
     }
     
-    /// Submit a vote
+    /// Submit a vote, optionally signed by the voter
     pub async fn submit_vote(
         &self,
         proposal_id: &str,
-        voter: &str,
+        voter: &Address,
         vote: Vote,
+        signature: Option<VoteSignature>,
     ) -> Result<()> {
         // Submit the vote through the proposal manager
-        self.proposal_manager.vote(proposal_id, voter, vote).await
+        self.proposal_manager.vote(&proposal_id.to_string(), voter, vote, signature).await
+    }
+    
+    /// Submit a quadratic vote of the given weight, optionally signed
+    ///
+    /// The vote costs `weight^2` of the voter's voice credits for the
+    /// current epoch and is refused if that exceeds what they have left
+    /// across proposals still being voted on.
+    pub async fn submit_quadratic_vote(
+        &self,
+        proposal_id: &str,
+        voter: &Address,
+        vote: Vote,
+        weight: u64,
+        signature: Option<VoteSignature>,
+    ) -> Result<()> {
+        if !self.voting_strategy.uses_voice_credits() {
+            return Err(DaoError::NotSupported(format!(
+                "{} does not use voice credits",
+                self.voting_strategy.name()
+            )));
+        }
+        
+        // Voice credits are for members holding governance tokens
+        let balance = self
+            .blockchain
+            .balance(voter.as_str())
+            .await
+            .map_err(|e| DaoError::BlockchainError(e))?;
+        if balance == 0 {
+            return Err(DaoError::InvalidParameter(format!(
+                "{} holds no governance tokens",
+                voter
+            )));
+        }
+        
+        let config = &self.config.governance.quadratic;
+        let epoch = credits::epoch_at(Utc::now(), config);
+        let active = self.active_proposal_ids().await?;
+        if !active.iter().any(|id| id == proposal_id) {
+            return Err(DaoError::InvalidParameter(
+                "Proposal is not in the voting state".to_string(),
+            ));
+        }
+        
+        let remaining = credits::spend(&self.database, voter.as_str(), proposal_id, weight, epoch, &active, config).await?;
+        
+        let proposal_id = proposal_id.to_string();
+        if let Err(e) = self.proposal_manager.vote_with_weight(&proposal_id, voter, vote, weight, signature).await {
+            // The vote was not recorded, so it costs nothing
+            if let Err(refund_error) = credits::refund(&self.database, voter.as_str(), epoch, &proposal_id).await {
+                warn!("Could not refund voice credits of {} on {}: {}", voter, proposal_id, refund_error);
+            }
+            return Err(e);
+        }
+        
+        info!("{} spent {} voice credits on {}, {} left", voter, weight * weight, proposal_id, remaining);
+        Ok(())
+    }
+    
+    /// Get the voice credits a voter has left this epoch
+    pub async fn remaining_voice_credits(&self, voter: &Address) -> Result<u64> {
+        let config = &self.config.governance.quadratic;
+        let active = self.active_proposal_ids().await?;
+        
+        credits::remaining(
+            &self.database,
+            voter.as_str(),
+            credits::epoch_at(Utc::now(), config),
+            &active,
+            config,
+        )
+        .await
+    }
+    
+    /// Ids of the proposals being voted on
+    async fn active_proposal_ids(&self) -> Result<Vec<ProposalId>> {
+        let proposals = self.proposal_manager.get_proposals(Some(ProposalState::Voting), false).await?;
+        Ok(proposals.into_iter().map(|proposal| proposal.id).collect())
+    }
+    
+    /// Change a vote while voting is still open
+    pub async fn change_vote(
+        &self,
+        proposal_id: &str,
+        voter: &Address,
+        vote: Vote,
+        signature: Option<VoteSignature>,
+    ) -> Result<()> {
+        self.proposal_manager.change_vote(&proposal_id.to_string(), voter, vote, signature).await
+    }
+    
+    /// Get the signed receipt for a voter's vote
+    pub async fn get_vote_receipt(&self, proposal_id: &str, voter: &str) -> Result<VoteReceipt> {
+        self.proposal_manager.get_vote_receipt(&proposal_id.to_string(), voter).await
     }
     
     /// Delegate voting power
-    pub async fn delegate_voting_power(&self, delegator: &str, delegate: &str) -> Result<()> {
+    pub async fn delegate_voting_power(&self, delegator: &Address, delegate: &Address) -> Result<()> {
+        if !self.config.features.delegation_enabled {
+            return Err(DaoError::feature_disabled("delegation"));
+        }
+        
         // Check if the delegator has enough tokens
         let delegator_balance = self
             .blockchain
-            .balance(delegator)
+            .balance(delegator.as_str())
             .await
             .map_err(|e| DaoError::BlockchainError(e))?;
         
@@ -119,8 +292,8 @@ impl GovernanceEngine {
             .execute(
                 "INSERT INTO delegations (delegator, delegate, amount, created_at) VALUES ($1, $2, $3, $4)",
                 &[
-                    &delegator,
-                    &delegate,
+                    &delegator.as_str(),
+                    &delegate.as_str(),
                     &delegator_balance,
                     &chrono::Utc::now(),
                 ],
@@ -131,12 +304,12 @@ impl GovernanceEngine {
     }
     
     /// Revoke delegation
-    pub async fn revoke_delegation(&self, delegator: &str, delegate: &str) -> Result<()> {
+    pub async fn revoke_delegation(&self, delegator: &Address, delegate: &Address) -> Result<()> {
         // Remove the delegation from the database
         self.database
             .execute(
                 "DELETE FROM delegations WHERE delegator = $1 AND delegate = $2",
-                &[&delegator, &delegate],
+                &[&delegator.as_str(), &delegate.as_str()],
             )
             .await?;
         
@@ -144,13 +317,13 @@ impl GovernanceEngine {
     }
     
     /// Get delegated voting power
-    pub async fn get_delegated_voting_power(&self, delegate: &str) -> Result<u64> {
+    pub async fn get_delegated_voting_power(&self, delegate: &Address) -> Result<u64> {
         // Query delegations for the delegate
         let rows = self
             .database
             .query(
                 "SELECT SUM(amount) as total FROM delegations WHERE delegate = $1",
-                &[&delegate],
+                &[&delegate.as_str()],
             )
             .await?;
         
@@ -164,11 +337,255 @@ impl GovernanceEngine {
         Ok(total)
     }
     
+    /// Get the effective governance parameters
+    ///
+    /// Values amended by executed `ParameterChange` proposals take precedence
+    /// over the file config and report the proposal that set them.
+    pub async fn parameters(&self) -> Result<Vec<GovernanceParameter>> {
+        let defaults = default_parameters(&self.config);
+        let amendments = load_amendments(&self.database).await?;
+        
+        Ok(effective_parameters(defaults, &amendments))
+    }
+    
+    /// Get the voting record of an address
+    ///
+    /// Lists every proposal the address voted on within the query's period,
+    /// most recent first, with participation and with-majority rates.
+    /// Served by the read replica when one is configured.
+    pub async fn voting_record(&self, address: &Address, query: &VotingRecordQuery) -> Result<VotingRecord> {
+        records::voting_record(&self.database, address.as_str(), query).await
+    }
+    
+    /// Stake tokens on a proposal under conviction voting; 0 withdraws
+    ///
+    /// Conviction keeps building while the stake is held and decays once it
+    /// is withdrawn. A voter's stakes across proposals can't exceed their
+    /// balance.
+    pub async fn stake_conviction(&self, proposal_id: &str, voter: &Address, amount: u64) -> Result<Conviction> {
+        if !self.voting_strategy.accrues_conviction() {
+            return Err(DaoError::NotSupported(format!(
+                "{} does not use conviction",
+                self.voting_strategy.name()
+            )));
+        }
+        
+        let proposal = self.proposal_manager.get_proposal(&proposal_id.to_string()).await?;
+        if amount > 0 && proposal.state != ProposalState::Voting {
+            return Err(DaoError::InvalidParameter(
+                "Proposal is not in the voting state".to_string(),
+            ));
+        }
+        
+        let balance = self
+            .blockchain
+            .balance(voter.as_str())
+            .await
+            .map_err(|e| DaoError::BlockchainError(e))?;
+        
+        conviction::set_stake(
+            &self.database,
+            proposal_id,
+            voter.as_str(),
+            amount,
+            balance,
+            Utc::now(),
+            &self.config.governance.conviction,
+        )
+        .await
+    }
+    
+    /// Get the conviction currently staked on a proposal
+    pub async fn proposal_conviction(&self, proposal_id: &str) -> Result<u64> {
+        let stakes = conviction::proposal_stakes(
+            &self.database,
+            proposal_id,
+            Utc::now(),
+            &self.config.governance.conviction,
+        )
+        .await?;
+        
+        Ok(stakes.iter().map(|stake| stake.conviction).fold(0, u64::saturating_add))
+    }
+    
     /// Process governance operations (e.g., update voting power, check proposal states)
     pub async fn process(&self) -> Result<()> {
+        // Accrue conviction and pass proposals that crossed their threshold
+        if self.voting_strategy.accrues_conviction() {
+            self.process_conviction().await?;
+        }
+        
+        // Voice credits from past epochs no longer count
+        if self.voting_strategy.uses_voice_credits() {
+            let epoch = credits::epoch_at(Utc::now(), &self.config.governance.quadratic);
+            credits::purge_before(&self.database, epoch).await?;
+        }
+        
         // Process proposals
         self.proposal_manager.process_proposals().await?;
         
         Ok(())
     }
-} 
\ No newline at end of file
+    
+    /// Advance every stake and approve proposals whose conviction crossed
+    /// the threshold for the funds they request
+    async fn process_conviction(&self) -> Result<()> {
+        let config = &self.config.governance.conviction;
+        
+        // Stakes can't outlast the tokens backing them; a voter whose balance
+        // can't be read keeps their stakes as they are until the next tick
+        let mut balances = BTreeMap::new();
+        for voter in conviction::stakers(&self.database).await? {
+            match self.balance(&voter).await {
+                Ok(balance) => {
+                    balances.insert(voter, balance);
+                }
+                Err(e) => warn!("Could not read the balance of staker {}: {}", voter, e),
+            }
+        }
+        
+        let stakes = conviction::advance_all(&self.database, Utc::now(), &balances, config).await?;
+        let staked_supply = conviction::staked_supply(&stakes);
+        
+        // One proposal that can't be checked, such as a deleted one, must
+        // not hold up the others
+        for (proposal_id, total) in conviction::totals_by_proposal(&stakes) {
+            let proposal = match self.proposal_manager.get_proposal(&proposal_id).await {
+                Ok(proposal) => proposal,
+                Err(e) => {
+                    warn!("Skipping conviction for proposal {}: {}", proposal_id, e);
+                    continue;
+                }
+            };
+            
+            // Stakes on proposals that closed go back to their voters
+            if proposal.state != ProposalState::Voting {
+                conviction::release_proposal(&self.database, &proposal_id).await?;
+                continue;
+            }
+            
+            let requested = conviction::requested_funds(&proposal);
+            let funds = if requested > 0 {
+                match self.balance(&self.config.treasury.treasury_address).await {
+                    Ok(funds) => funds,
+                    Err(e) => {
+                        warn!("Skipping conviction for proposal {}: no treasury balance: {}", proposal_id, e);
+                        continue;
+                    }
+                }
+            } else {
+                0
+            };
+            
+            let passed = conviction::threshold(requested, funds, staked_supply, config)
+                .map_or(false, |threshold| total >= threshold);
+            if passed {
+                info!("Proposal {} passed with conviction {}", proposal_id, total);
+                self.proposal_manager.pass_by_conviction(&proposal_id).await?;
+                conviction::release_proposal(&self.database, &proposal_id).await?;
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Get the current balance of an address on the default chain
+    async fn balance(&self, address: &str) -> Result<u64> {
+        let balance = self
+            .blockchain
+            .get_balance(address, None)
+            .await
+            .map_err(|e| DaoError::BlockchainError(e.to_string()))?;
+        
+        parse_balance(&balance)
+    }
+} 
+
+/// Names `governance.voting_strategy` accepts
+pub const VOTING_STRATEGIES: &[&str] = &["TokenWeighted", "Quadratic", "Conviction", "Reputation"];
+
+/// Name of the voting strategy selected by the configuration
+///
+/// `governance.voting_strategy` takes precedence; when it is unset the
+/// strategy is selected by `dao.governance_token`, as before the setting
+/// existed.
+pub(crate) fn strategy_selector(config: &Config) -> &str {
+    let selector = config
+        .governance
+        .voting_strategy
+        .as_deref()
+        .unwrap_or(&config.dao.governance_token);
+    
+    match selector {
+        "Quadratic" | "Conviction" | "Reputation" => selector,
+        _ => "TokenWeighted",
+    }
+}
+
+/// Create the voting strategy selected by the configuration
+pub(crate) fn configured_strategy(config: &Config) -> Box<dyn VotingStrategy> {
+    match strategy_selector(config) {
+        "Quadratic" => Box::new(strategies::QuadraticVoting::new()),
+        "Conviction" => Box::new(strategies::ConvictionVoting::new()),
+        // Weigh balances by reputation
+        "Reputation" => Box::new(strategies::ReputationWeightedVoting::new(
+            config.governance.reputation_weighting.clone(),
+        )),
+        // Otherwise, use token-weighted voting
+        _ => Box::new(strategies::TokenWeightedVoting::new()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::MockBlockchain;
+    use crate::proposals::{ProposalBuilder, ProposalType};
+    use crate::token::TokenManager;
+    
+    const VOTER: &str = "0x0000000000000000000000000000000000000001";
+    
+    async fn voting_proposal(proposal_manager: &ProposalManager, title: &str) -> ProposalId {
+        let mut proposal = ProposalBuilder::new()
+            .title(title)
+            .description("Signal support")
+            .proposal_type(ProposalType::TextProposal {
+                metadata: serde_json::Value::Null,
+            })
+            .proposer(VOTER)
+            .build()
+            .unwrap();
+        proposal.state = ProposalState::Voting;
+        proposal_manager.save_proposal(&proposal, VOTER).await.unwrap();
+        
+        proposal.id
+    }
+    
+    #[tokio::test]
+    async fn test_deleted_staked_proposal_does_not_stall_conviction() {
+        let mut config = crate::config::default();
+        config.dao.governance_token = "Conviction".to_string();
+        config.governance.timelock_seconds = 0;
+        let chain = Arc::new(MockBlockchain::new());
+        let blockchain = BlockchainAdapter::with_mock(&config.blockchain, chain.clone());
+        let database = Database::migrated_sqlite().await;
+        let token_manager = TokenManager::new(&config, blockchain.clone(), database.clone()).unwrap();
+        let proposal_manager = ProposalManager::new(&config, blockchain.clone(), database.clone()).unwrap();
+        let engine = GovernanceEngine::new(&config, blockchain, database.clone(), &token_manager, &proposal_manager).unwrap();
+        
+        let deleted = voting_proposal(&proposal_manager, "Withdrawn idea").await;
+        let live = voting_proposal(&proposal_manager, "Adopt the charter").await;
+        
+        // Both stakes have been building conviction for a day
+        let conviction = &config.governance.conviction;
+        let staked_at = Utc::now() - chrono::Duration::hours(24);
+        chain.set_balance(VOTER, 1_100);
+        conviction::set_stake(&database, &deleted, VOTER, 100, 1_100, staked_at, conviction).await.unwrap();
+        conviction::set_stake(&database, &live, VOTER, 1_000, 1_100, staked_at, conviction).await.unwrap();
+        proposal_manager.soft_delete_proposal(&deleted, VOTER).await.unwrap();
+        
+        engine.process().await.unwrap();
+        
+        assert_eq!(proposal_manager.get_proposal(&live).await.unwrap().state, ProposalState::Approved);
+    }
+}
@@ -0,0 +1,257 @@
+//! Fixed voting weights for named stakeholders
+//!
+//! Hybrid DAOs can give members a fixed voting weight regardless of their
+//! token holdings. Overrides are kept in the append-only
+//! `vote_weight_overrides` table, so every change records who made it and
+//! the latest row for an address is the one in effect.
+
+use crate::config::VoteWeightOverrideMode;
+use crate::core::{Database, DaoError, Result};
+use crate::identity::{Member, MemberRole, MemberStatus};
+use crate::proposals::ProposalId;
+use crate::utils::time::{current_timestamp, rfc3339, timestamp_to_datetime};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// `ParameterChange` name for proposals that set or clear an override
+///
+/// The value is a [`VoteWeightOverrideChange`].
+pub const VOTE_WEIGHT_OVERRIDE_PARAMETER: &str = "vote_weight_override";
+
+/// Who is changing an override
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OverrideAuthority {
+    /// A member acting directly; must be an active Council member or above
+    Member {
+        /// Member address; the role is looked up from the members table
+        address: String,
+    },
+    /// An executed `ParameterChange` proposal
+    Proposal {
+        /// Proposal that made the change
+        proposal_id: ProposalId,
+    },
+}
+
+/// Value of a `vote_weight_override` parameter change
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VoteWeightOverrideChange {
+    /// Address whose weight is overridden
+    pub address: String,
+    /// Fixed weight, or `None` to remove the override
+    pub weight: Option<u64>,
+}
+
+/// A recorded override change
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VoteWeightOverride {
+    /// Address whose weight is overridden
+    pub address: String,
+    /// Fixed weight (`None` when the override was removed)
+    pub weight: Option<u64>,
+    /// Member that made the change (`None` when made by a proposal)
+    pub set_by: Option<String>,
+    /// Proposal that made the change (`None` when made by a member)
+    pub proposal_id: Option<ProposalId>,
+    /// When the change was made
+    #[serde(with = "rfc3339")]
+    pub changed_at: DateTime<Utc>,
+}
+
+/// Check that an authority may change overrides
+///
+/// A member's role and status are read from the members table rather than
+/// trusted from the caller. Portable: runs on PostgreSQL and SQLite.
+pub(crate) async fn check_override_authority(database: &Database, authority: &OverrideAuthority) -> Result<()> {
+    let address = match authority {
+        OverrideAuthority::Member { address } => address,
+        OverrideAuthority::Proposal { .. } => return Ok(()),
+    };
+
+    let row = database
+        .query_opt_any(
+            "SELECT data FROM members WHERE LOWER(address) = LOWER($1) AND deleted_at IS NULL",
+            &[address.as_str().into()],
+        )
+        .await?;
+    let member: Option<Member> = row
+        .map(|row| {
+            row.json("data")
+                .map_err(|e| DaoError::DatabaseError(format!("Failed to parse member: {}", e)))
+        })
+        .transpose()?;
+
+    match member {
+        Some(member) if member.status == MemberStatus::Active && member.role.is_at_least(MemberRole::Council) => Ok(()),
+        _ => Err(DaoError::Unauthorized),
+    }
+}
+
+/// Combine a token-derived weight with an address's override
+pub(crate) fn apply_override(token_weight: u64, fixed_weight: Option<u64>, mode: VoteWeightOverrideMode) -> u64 {
+    match (fixed_weight, mode) {
+        (None, _) => token_weight,
+        (Some(fixed), VoteWeightOverrideMode::Replace) => fixed,
+        (Some(fixed), VoteWeightOverrideMode::Add) => token_weight.saturating_add(fixed),
+    }
+}
+
+/// Get the override currently in effect for an address
+pub(crate) async fn current_override(database: &Database, address: &str) -> Result<Option<u64>> {
+    let row = database
+        .query_opt(
+            "SELECT weight FROM vote_weight_overrides WHERE LOWER(address) = LOWER($1) ORDER BY id DESC LIMIT 1",
+            &[&address],
+        )
+        .await?;
+
+    Ok(row.and_then(|row| row.get::<_, Option<i64>>("weight")).map(|weight| weight as u64))
+}
+
+/// Record an override change, after checking the authority
+pub(crate) async fn record_override(
+    database: &Database,
+    address: &str,
+    weight: Option<u64>,
+    authority: &OverrideAuthority,
+) -> Result<()> {
+    check_override_authority(database, authority).await?;
+
+    let (set_by, proposal_id) = match authority {
+        OverrideAuthority::Member { address } => (Some(address.clone()), None),
+        OverrideAuthority::Proposal { proposal_id } => (None, Some(proposal_id.clone())),
+    };
+    let weight = weight.map(|weight| weight as i64);
+    let now = current_timestamp() as i64;
+
+    database
+        .execute_any(
+            "INSERT INTO vote_weight_overrides (address, weight, set_by, proposal_id, created_at) VALUES ($1, $2, $3, $4, $5)",
+            &[address.into(), weight.into(), set_by.into(), proposal_id.into(), now.into()],
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Get every override change for an address, oldest first
+pub(crate) async fn override_history(database: &Database, address: &str) -> Result<Vec<VoteWeightOverride>> {
+    let rows = database
+        .query(
+            "SELECT address, weight, set_by, proposal_id, created_at FROM vote_weight_overrides WHERE LOWER(address) = LOWER($1) ORDER BY id",
+            &[&address],
+        )
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| VoteWeightOverride {
+            address: row.get("address"),
+            weight: row.get::<_, Option<i64>>("weight").map(|weight| weight as u64),
+            set_by: row.get("set_by"),
+            proposal_id: row.get("proposal_id"),
+            changed_at: timestamp_to_datetime(row.get::<_, i64>("created_at") as u64),
+        })
+        .collect())
+}
+
+/// Parse the value of a `vote_weight_override` parameter change
+pub(crate) fn parse_override_change(value: &serde_json::Value) -> Result<VoteWeightOverrideChange> {
+    let change: VoteWeightOverrideChange = serde_json::from_value(value.clone()).map_err(|e| {
+        DaoError::InvalidParameter(format!("Invalid vote weight override: {}", e))
+    })?;
+
+    if change.address.is_empty() {
+        return Err(DaoError::InvalidParameter(
+            "Vote weight override address cannot be empty".to_string(),
+        ));
+    }
+
+    Ok(change)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_override_replaces_token_weight() {
+        let founder = Some(1_000);
+
+        // The founder votes with 1,000 whatever they hold
+        for balance in [0, 5, 1_000_000] {
+            assert_eq!(apply_override(balance, founder, VoteWeightOverrideMode::Replace), 1_000);
+        }
+
+        assert_eq!(apply_override(5, founder, VoteWeightOverrideMode::Add), 1_005);
+        assert_eq!(apply_override(5, None, VoteWeightOverrideMode::Replace), 5);
+    }
+
+    async fn insert_member(database: &Database, address: &str, role: MemberRole, status: MemberStatus) {
+        let member = Member {
+            id: uuid::Uuid::new_v4().to_string(),
+            address: address.to_string(),
+            name: None,
+            role,
+            status,
+            reputation: 0,
+            joined_at: Utc::now(),
+            last_active_at: Utc::now(),
+            role_changed_at: None,
+            metadata: serde_json::Value::Null,
+        };
+        database
+            .execute_any(
+                "INSERT INTO members (id, address, data) VALUES ($1, $2, $3)",
+                &[member.id.as_str().into(), address.into(), serde_json::to_value(&member).unwrap().into()],
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_only_council_or_proposal_may_change_overrides() {
+        let database = Database::migrated_sqlite().await;
+        insert_member(&database, "0xdelegate", MemberRole::Delegate, MemberStatus::Active).await;
+        insert_member(&database, "0xcouncil", MemberRole::Council, MemberStatus::Active).await;
+        insert_member(&database, "0xadmin", MemberRole::Admin, MemberStatus::Active).await;
+        insert_member(&database, "0xsuspended", MemberRole::Council, MemberStatus::Suspended).await;
+
+        let member = |address: &str| OverrideAuthority::Member {
+            address: address.to_string(),
+        };
+
+        // The role comes from the members table, whatever the caller claims
+        for refused in ["0xDelegate", "0xSuspended", "0xUnregistered"] {
+            assert!(
+                matches!(check_override_authority(&database, &member(refused)).await, Err(DaoError::Unauthorized)),
+                "{} allowed",
+                refused
+            );
+        }
+        assert!(check_override_authority(&database, &member("0xCouncil")).await.is_ok());
+        assert!(check_override_authority(&database, &member("0xADMIN")).await.is_ok());
+        assert!(check_override_authority(
+            &database,
+            &OverrideAuthority::Proposal {
+                proposal_id: "proposal-7".to_string()
+            }
+        )
+        .await
+        .is_ok());
+
+        assert!(record_override(&database, "0xFounder", Some(1_000), &member("0xDelegate")).await.is_err());
+        record_override(&database, "0xFounder", Some(1_000), &member("0xCouncil")).await.unwrap();
+    }
+
+    #[test]
+    fn test_parse_override_change() {
+        let change = parse_override_change(&serde_json::json!({ "address": "0xFounder", "weight": 1000 })).unwrap();
+        assert_eq!(change.weight, Some(1000));
+
+        let removal = parse_override_change(&serde_json::json!({ "address": "0xFounder", "weight": null })).unwrap();
+        assert_eq!(removal.weight, None);
+
+        assert!(parse_override_change(&serde_json::json!(1000)).is_err());
+    }
+}
@@ -0,0 +1,357 @@
+//! Effective governance parameters
+//!
+//! Parameters start from the file config and can be amended by executed
+//! `ParameterChange` proposals. An amendment is written to the config through
+//! the [`ConfigManager`], and also kept in the `settings` table under a
+//! `governance.` key prefix together with the proposal that made it.
+
+use crate::config::{Config, ConfigManager};
+use crate::core::{Database, DaoError, Result};
+use crate::proposals::ProposalId;
+use crate::utils::time::{current_timestamp, rfc3339, timestamp_to_datetime};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tracing::warn;
+
+/// Key prefix for governance amendments in the settings table
+const SETTINGS_PREFIX: &str = "governance.";
+
+/// Parameters that `ParameterChange` proposals may amend
+pub const AMENDABLE_PARAMETERS: &[&str] = &[
+    "proposal_threshold",
+    "voting_period_days",
+    "quorum_percentage",
+    "majority_percentage",
+    "veto_threshold_percent",
+    "voting_strategy",
+];
+
+/// Where the effective value of a parameter comes from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ParameterSource {
+    /// The file config
+    Default,
+    /// An executed `ParameterChange` proposal
+    Proposal {
+        /// Proposal that set the value
+        proposal_id: ProposalId,
+    },
+}
+
+/// The effective value of a governance parameter
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GovernanceParameter {
+    /// Parameter name
+    pub name: String,
+    /// Effective value
+    pub value: serde_json::Value,
+    /// Where the value comes from
+    pub source: ParameterSource,
+    /// When the value was last amended (`None` for defaults)
+    #[serde(default, with = "rfc3339::option")]
+    pub changed_at: Option<DateTime<Utc>>,
+}
+
+/// An amendment as stored in the settings table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct ParameterAmendment {
+    pub value: serde_json::Value,
+    pub proposal_id: ProposalId,
+    #[serde(skip)]
+    pub changed_at: Option<DateTime<Utc>>,
+}
+
+/// Config values for every amendable parameter, followed by the execution
+/// timelock, which is reported but can't be amended
+pub(crate) fn default_parameters(config: &Config) -> Vec<(&'static str, serde_json::Value)> {
+    let governance = &config.governance;
+    vec![
+        ("proposal_threshold", governance.proposal_threshold.into()),
+        ("voting_period_days", governance.voting_period_days.into()),
+        ("quorum_percentage", governance.quorum_percentage.into()),
+        ("majority_percentage", governance.majority_percentage.into()),
+        ("veto_threshold_percent", governance.veto_threshold_percent.into()),
+        ("voting_strategy", super::strategy_selector(config).into()),
+        ("timelock_seconds", governance.timelock_seconds.into()),
+    ]
+}
+
+/// Set an amendable parameter on a config
+///
+/// Numeric values may be a JSON integer or a string holding one, and the
+/// voting strategy one of [`VOTING_STRATEGIES`](super::VOTING_STRATEGIES).
+/// Unknown parameters and out-of-range values are rejected.
+pub(crate) fn apply_parameter(config: &mut Config, name: &str, value: &serde_json::Value) -> Result<()> {
+    let governance = &mut config.governance;
+    match name {
+        "proposal_threshold" => governance.proposal_threshold = coerce_integer(name, value, 0, u64::MAX)?,
+        "voting_period_days" => governance.voting_period_days = coerce_integer(name, value, 1, 365)? as u32,
+        "quorum_percentage" => governance.quorum_percentage = coerce_integer(name, value, 0, 100)? as u8,
+        "majority_percentage" => governance.majority_percentage = coerce_integer(name, value, 1, 100)? as u8,
+        "veto_threshold_percent" => governance.veto_threshold_percent = coerce_integer(name, value, 0, 100)? as u8,
+        "voting_strategy" => governance.voting_strategy = Some(coerce_strategy(value)?),
+        _ => {
+            return Err(DaoError::InvalidParameter(format!(
+                "Parameter '{}' cannot be amended by proposal",
+                name
+            )))
+        }
+    }
+
+    Ok(())
+}
+
+/// Amend a parameter for an executed proposal
+///
+/// The config is amended and the amendment recorded together: if it can't
+/// be recorded, the config is put back as it was.
+pub(crate) async fn amend(
+    config_manager: &ConfigManager,
+    database: &Database,
+    name: &str,
+    value: &serde_json::Value,
+    proposal_id: &ProposalId,
+) -> Result<()> {
+    let previous = config_manager.get_config();
+    apply_amendment(config_manager, name, value)?;
+
+    if let Err(e) = save_amendment(database, name, value, proposal_id).await {
+        if let Err(rollback_error) = config_manager.update_config(previous) {
+            warn!("Could not roll back amendment of {}: {}", name, rollback_error);
+        }
+        return Err(e);
+    }
+
+    Ok(())
+}
+
+/// Apply an amendment to the config and save it
+fn apply_amendment(config_manager: &ConfigManager, name: &str, value: &serde_json::Value) -> Result<()> {
+    let mut config = config_manager.get_config();
+    apply_parameter(&mut config, name, value)?;
+
+    config_manager
+        .update_config(config)
+        .map_err(|e| DaoError::InternalError(format!("Failed to save amended config: {}", e)))
+}
+
+fn coerce_strategy(value: &serde_json::Value) -> Result<String> {
+    match value.as_str().map(str::trim) {
+        Some(strategy) if super::VOTING_STRATEGIES.contains(&strategy) => Ok(strategy.to_string()),
+        _ => Err(DaoError::InvalidParameter(format!(
+            "voting_strategy must be one of {}, got {}",
+            super::VOTING_STRATEGIES.join(", "),
+            value
+        ))),
+    }
+}
+
+fn coerce_integer(name: &str, value: &serde_json::Value, min: u64, max: u64) -> Result<u64> {
+    let parsed = match value {
+        serde_json::Value::Number(number) => number.as_u64(),
+        serde_json::Value::String(text) => text.trim().parse().ok(),
+        _ => None,
+    };
+
+    match parsed {
+        Some(parsed) if (min..=max).contains(&parsed) => Ok(parsed),
+        Some(_) => Err(DaoError::InvalidParameter(format!(
+            "{} must be between {} and {}",
+            name, min, max
+        ))),
+        None => Err(DaoError::InvalidParameter(format!(
+            "{} must be a non-negative integer, got {}",
+            name, value
+        ))),
+    }
+}
+
+/// Overlay amendments on the defaults
+pub(crate) fn effective_parameters(
+    defaults: Vec<(&'static str, serde_json::Value)>,
+    amendments: &HashMap<String, ParameterAmendment>,
+) -> Vec<GovernanceParameter> {
+    defaults
+        .into_iter()
+        .map(|(name, default)| match amendments.get(name) {
+            Some(amendment) => GovernanceParameter {
+                name: name.to_string(),
+                value: amendment.value.clone(),
+                source: ParameterSource::Proposal {
+                    proposal_id: amendment.proposal_id.clone(),
+                },
+                changed_at: amendment.changed_at,
+            },
+            None => GovernanceParameter {
+                name: name.to_string(),
+                value: default,
+                source: ParameterSource::Default,
+                changed_at: None,
+            },
+        })
+        .collect()
+}
+
+/// Load all governance amendments, keyed by parameter name
+pub(crate) async fn load_amendments(database: &Database) -> Result<HashMap<String, ParameterAmendment>> {
+    let pattern = format!("{}%", SETTINGS_PREFIX);
+    let rows = database
+        .query("SELECT key, value, updated_at FROM settings WHERE key LIKE $1", &[&pattern])
+        .await?;
+
+    let mut amendments = HashMap::new();
+
+    for row in rows {
+        let key: String = row.get("key");
+        let updated_at: i64 = row.get("updated_at");
+
+        let mut amendment: ParameterAmendment = serde_json::from_value(row.get("value"))
+            .map_err(|e| DaoError::DatabaseError(format!("Failed to parse parameter amendment: {}", e)))?;
+        amendment.changed_at = Some(timestamp_to_datetime(updated_at as u64));
+
+        amendments.insert(key[SETTINGS_PREFIX.len()..].to_string(), amendment);
+    }
+
+    Ok(amendments)
+}
+
+/// Record the value set by an executed proposal
+async fn save_amendment(
+    database: &Database,
+    name: &str,
+    value: &serde_json::Value,
+    proposal_id: &ProposalId,
+) -> Result<()> {
+    let key = format!("{}{}", SETTINGS_PREFIX, name);
+    let data = serde_json::to_value(ParameterAmendment {
+        value: value.clone(),
+        proposal_id: proposal_id.clone(),
+        changed_at: None,
+    })
+    .map_err(|e| DaoError::DatabaseError(format!("Failed to serialize parameter amendment: {}", e)))?;
+    let now = current_timestamp() as i64;
+
+    // Check if the parameter was amended before
+    let exists = database
+        .query_opt("SELECT 1 FROM settings WHERE key = $1", &[&key])
+        .await?
+        .is_some();
+
+    if exists {
+        database
+            .execute(
+                "UPDATE settings SET value = $1, updated_at = $2 WHERE key = $3",
+                &[&data, &now, &key],
+            )
+            .await?;
+    } else {
+        database
+            .execute(
+                "INSERT INTO settings (key, value, updated_at) VALUES ($1, $2, $3)",
+                &[&key, &data, &now],
+            )
+            .await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_amended_parameter_reports_provenance() {
+        let config = crate::config::default();
+        let changed_at = Utc::now();
+
+        let mut amendments = HashMap::new();
+        amendments.insert(
+            "quorum_percentage".to_string(),
+            ParameterAmendment {
+                value: 25.into(),
+                proposal_id: "proposal-42".to_string(),
+                changed_at: Some(changed_at),
+            },
+        );
+
+        let parameters = effective_parameters(default_parameters(&config), &amendments);
+        let find = |name: &str| parameters.iter().find(|p| p.name == name).unwrap();
+
+        let quorum = find("quorum_percentage");
+        assert_eq!(quorum.value, serde_json::json!(25));
+        assert_eq!(
+            quorum.source,
+            ParameterSource::Proposal {
+                proposal_id: "proposal-42".to_string()
+            }
+        );
+        assert_eq!(quorum.changed_at, Some(changed_at));
+
+        let majority = find("majority_percentage");
+        assert_eq!(majority.value, serde_json::json!(config.governance.majority_percentage));
+        assert_eq!(majority.source, ParameterSource::Default);
+        assert_eq!(majority.changed_at, None);
+
+        assert_eq!(find("voting_strategy").value, serde_json::json!("TokenWeighted"));
+        assert_eq!(find("timelock_seconds").value, serde_json::json!(config.governance.timelock_seconds));
+        assert_eq!(parameters.len(), AMENDABLE_PARAMETERS.len() + 1);
+    }
+
+    #[test]
+    fn test_executed_parameter_change_updates_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        let mut config = ConfigManager::with_defaults(path.to_str().unwrap()).get_config();
+        config.security.jwt_secret = "0123456789abcdef0123456789abcdef".to_string();
+        std::fs::write(&path, serde_json::to_string(&config).unwrap()).unwrap();
+        let config_manager = ConfigManager::new(path.to_str().unwrap()).unwrap();
+
+        apply_amendment(&config_manager, "quorum_percentage", &serde_json::json!(25)).unwrap();
+        apply_amendment(&config_manager, "voting_period_days", &serde_json::json!("14")).unwrap();
+        apply_amendment(&config_manager, "veto_threshold_percent", &serde_json::json!(33)).unwrap();
+        apply_amendment(&config_manager, "voting_strategy", &serde_json::json!("Quadratic")).unwrap();
+
+        let config = config_manager.get_config();
+        assert_eq!(config.governance.quorum_percentage, 25);
+        assert_eq!(config.governance.voting_period_days, 14);
+        assert_eq!(config.governance.veto_threshold_percent, 33);
+        assert_eq!(super::super::configured_strategy(&config).name(), "Quadratic Voting");
+
+        // The change is persisted, not just held in memory
+        let saved = std::fs::read_to_string(&path).unwrap();
+        assert!(saved.contains("\"quorum_percentage\": 25"), "{}", saved);
+    }
+
+    #[test]
+    fn test_invalid_parameter_change_rejected() {
+        let mut config = crate::config::default();
+        let before = config.governance.clone();
+
+        for (name, value) in [
+            ("jwt_secret", serde_json::json!("hunter2")),
+            ("quorum_percentage", serde_json::json!(101)),
+            ("majority_percentage", serde_json::json!(0)),
+            ("voting_period_days", serde_json::json!(-3)),
+            ("proposal_threshold", serde_json::json!(1.5)),
+            ("proposal_threshold", serde_json::json!(true)),
+            ("veto_threshold_percent", serde_json::json!(150)),
+            ("voting_strategy", serde_json::json!("Plutocracy")),
+            ("voting_strategy", serde_json::json!(2)),
+            ("timelock_seconds", serde_json::json!(0)),
+        ] {
+            assert!(
+                matches!(apply_parameter(&mut config, name, &value), Err(DaoError::InvalidParameter(_))),
+                "{} = {} should be rejected",
+                name,
+                value
+            );
+        }
+
+        assert_eq!(config.governance.quorum_percentage, before.quorum_percentage);
+        assert_eq!(config.governance.proposal_threshold, before.proposal_threshold);
+        assert_eq!(config.governance.voting_strategy, None);
+    }
+}
@@ -0,0 +1,465 @@
+//! Member voting records
+//!
+//! A voting record lists every proposal an address voted on, with its
+//! choice, weight and the proposal's outcome, plus summary stats over the
+//! same period. Records are read from the votes stored on each proposal,
+//! filtered and paged in the database.
+
+use crate::core::{Database, DaoError, Result, SqlValue};
+use crate::database::DatabaseType;
+use crate::proposals::{ProposalState, ProposalVote, Vote};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Filter and page for a voting record
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VotingRecordQuery {
+    /// Only include votes cast at or after this time
+    pub from: Option<DateTime<Utc>>,
+    /// Only include votes cast at or before this time
+    pub to: Option<DateTime<Utc>>,
+    /// Page number (1-based)
+    pub page: usize,
+    /// Entries per page
+    pub limit: usize,
+}
+
+impl Default for VotingRecordQuery {
+    fn default() -> Self {
+        Self {
+            from: None,
+            to: None,
+            page: 1,
+            limit: 20,
+        }
+    }
+}
+
+/// Outcome of a proposal as seen from a voting record
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VoteOutcome {
+    /// Voting has not finished
+    Pending,
+    /// The proposal was approved (and possibly executed)
+    Approved,
+    /// The proposal was rejected
+    Rejected,
+    /// The proposal was cancelled
+    Cancelled,
+}
+
+impl From<ProposalState> for VoteOutcome {
+    fn from(state: ProposalState) -> Self {
+        match state {
+            ProposalState::Draft | ProposalState::Voting => VoteOutcome::Pending,
+            ProposalState::Queued | ProposalState::Approved | ProposalState::Executed => VoteOutcome::Approved,
+            ProposalState::Rejected => VoteOutcome::Rejected,
+            ProposalState::Cancelled => VoteOutcome::Cancelled,
+        }
+    }
+}
+
+/// One proposal in a voting record
+#[derive(Debug, Clone, PartialEq)]
+pub struct VotingRecordEntry {
+    /// Proposal ID
+    pub proposal_id: String,
+    /// Sequential proposal number, if assigned
+    pub proposal_number: Option<u64>,
+    /// Proposal title
+    pub title: String,
+    /// Choice the address voted
+    pub choice: ProposalVote,
+    /// Voting power the vote carried
+    pub weight: u64,
+    /// When the vote was cast
+    pub voted_at: DateTime<Utc>,
+    /// Proposal outcome
+    pub outcome: VoteOutcome,
+    /// Whether the vote sided with the outcome; `None` while pending, for
+    /// cancelled proposals and for abstentions
+    pub with_majority: Option<bool>,
+}
+
+/// Summary stats for a voting record
+#[derive(Debug, Clone, PartialEq)]
+pub struct VotingRecordSummary {
+    /// Votes cast in the period
+    pub votes_cast: usize,
+    /// Proposals open for voting during the period
+    pub eligible_proposals: usize,
+    /// Fraction of eligible proposals voted on; `None` if there were none
+    pub participation_rate: Option<f64>,
+    /// Fraction of decided, non-abstain votes that sided with the outcome;
+    /// `None` if there were none
+    pub with_majority_rate: Option<f64>,
+}
+
+/// A member's voting record
+#[derive(Debug, Clone, PartialEq)]
+pub struct VotingRecord {
+    /// Voter address
+    pub address: String,
+    /// Requested page of entries, most recent vote first
+    pub entries: Vec<VotingRecordEntry>,
+    /// Entries across all pages
+    pub total: usize,
+    /// Stats over all entries, not just the page
+    pub summary: VotingRecordSummary,
+}
+
+/// Build the voting record of `address` from the votes stored on each proposal
+///
+/// The date filter, ordering and paging run in the database, so only the
+/// requested page of votes is loaded. Soft-deleted proposals are left out.
+/// Portable: runs on PostgreSQL and SQLite.
+pub(crate) async fn voting_record(
+    database: &Database,
+    address: &str,
+    query: &VotingRecordQuery,
+) -> Result<VotingRecord> {
+    if query.page == 0 || query.limit == 0 {
+        return Err(DaoError::InvalidParameter(
+            "Page and limit must be at least 1".to_string(),
+        ));
+    }
+
+    let dialect = Dialect::of(database);
+    let (votes, params) = votes_in_period(&dialect, address, query);
+
+    // Stats over every vote in the period, by proposal state and choice
+    let groups = database
+        .query_any(
+            &format!(
+                "SELECT p.state, {} AS choice, COUNT(*) AS votes {} GROUP BY p.state, {}",
+                dialect.choice, votes, dialect.choice
+            ),
+            &params,
+        )
+        .await?;
+
+    let mut total = 0;
+    let mut decided = 0;
+    let mut sided = 0;
+    for row in &groups {
+        let count = usize::try_from(row.int("votes")?).unwrap_or(0);
+        total += count;
+
+        match with_majority(parse_choice(&row.text("choice")?)?, parse_state(&row.text("state")?)?.into()) {
+            Some(true) => {
+                decided += count;
+                sided += count;
+            }
+            Some(false) => decided += count,
+            None => {}
+        }
+    }
+
+    let eligible_proposals = eligible_proposals(database, &dialect, query).await?;
+
+    // The requested page, most recent vote first
+    let limit = i64::try_from(query.limit).unwrap_or(i64::MAX);
+    let offset = i64::try_from((query.page - 1).saturating_mul(query.limit)).unwrap_or(i64::MAX);
+    let mut page_params = params;
+    page_params.push(limit.into());
+    page_params.push(offset.into());
+
+    let rows = database
+        .query_any(
+            &format!(
+                "SELECT p.id, COALESCE(p.number, 0) AS number, p.title, p.state, v.value AS vote {} \
+                 ORDER BY {} DESC, p.id LIMIT ${} OFFSET ${}",
+                votes,
+                dialect.voted_at,
+                page_params.len() - 1,
+                page_params.len()
+            ),
+            &page_params,
+        )
+        .await?;
+
+    let entries = rows
+        .iter()
+        .map(|row| {
+            let vote: Vote = row.json("vote")?;
+            let outcome = VoteOutcome::from(parse_state(&row.text("state")?)?);
+            // Numbers start at 1; 0 stands for an unnumbered proposal
+            let number = u64::try_from(row.int("number")?).ok().filter(|&number| number > 0);
+
+            Ok(VotingRecordEntry {
+                proposal_id: row.text("id")?,
+                proposal_number: number,
+                title: row.text("title")?,
+                choice: vote.vote,
+                weight: vote.voting_power,
+                voted_at: vote.timestamp,
+                outcome,
+                with_majority: with_majority(vote.vote, outcome),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(VotingRecord {
+        address: address.to_string(),
+        entries,
+        total,
+        summary: VotingRecordSummary {
+            votes_cast: total,
+            eligible_proposals,
+            participation_rate: rate(total, eligible_proposals),
+            with_majority_rate: rate(sided, decided),
+        },
+    })
+}
+
+/// SQL for reading votes out of the proposal documents
+struct Dialect {
+    /// Table expression yielding one row `v` per vote of proposal `p`
+    votes: &'static str,
+    /// Lower-cased voter address of `v`
+    voter: &'static str,
+    /// Choice of `v`, as text
+    choice: &'static str,
+    /// Comparable time `v` was cast
+    voted_at: &'static str,
+    /// Comparable start of `p`'s voting window
+    starts_at: &'static str,
+    /// Comparable end of `p`'s voting window
+    ends_at: &'static str,
+    /// Wraps a timestamp parameter to compare with the times above
+    time_param: fn(usize) -> String,
+}
+
+impl Dialect {
+    fn of(database: &Database) -> Self {
+        match database.db_type() {
+            DatabaseType::Postgres => Dialect {
+                votes: "jsonb_array_elements(p.data->'votes') AS v(value)",
+                voter: "LOWER(v.value->>'voter')",
+                choice: "v.value->>'vote'",
+                voted_at: "CAST(v.value->>'timestamp' AS TIMESTAMPTZ)",
+                starts_at: "CAST(p.data->>'voting_starts_at' AS TIMESTAMPTZ)",
+                ends_at: "CAST(p.data->>'voting_ends_at' AS TIMESTAMPTZ)",
+                time_param: |index| format!("${}", index),
+            },
+            DatabaseType::SQLite => Dialect {
+                votes: "json_each(p.data, '$.votes') AS v",
+                voter: "LOWER(json_extract(v.value, '$.voter'))",
+                choice: "json_extract(v.value, '$.vote')",
+                voted_at: "julianday(json_extract(v.value, '$.timestamp'))",
+                starts_at: "julianday(json_extract(p.data, '$.voting_starts_at'))",
+                ends_at: "julianday(json_extract(p.data, '$.voting_ends_at'))",
+                time_param: |index| format!("julianday(${})", index),
+            },
+        }
+    }
+}
+
+/// `FROM ... WHERE ...` selecting the votes of `address` in the query's
+/// period, with its parameters
+fn votes_in_period(dialect: &Dialect, address: &str, query: &VotingRecordQuery) -> (String, Vec<SqlValue>) {
+    let mut params: Vec<SqlValue> = vec![address.to_lowercase().into()];
+    let mut sql = format!(
+        "FROM proposals p, {} WHERE p.deleted_at IS NULL AND {} = $1",
+        dialect.votes, dialect.voter
+    );
+
+    if let Some(from) = query.from {
+        params.push(from.into());
+        sql.push_str(&format!(" AND {} >= {}", dialect.voted_at, (dialect.time_param)(params.len())));
+    }
+    if let Some(to) = query.to {
+        params.push(to.into());
+        sql.push_str(&format!(" AND {} <= {}", dialect.voted_at, (dialect.time_param)(params.len())));
+    }
+
+    (sql, params)
+}
+
+/// Count the proposals whose voting window overlaps the query's period
+async fn eligible_proposals(database: &Database, dialect: &Dialect, query: &VotingRecordQuery) -> Result<usize> {
+    let mut params: Vec<SqlValue> = Vec::new();
+    let mut sql = format!(
+        "SELECT COUNT(*) AS eligible FROM proposals p WHERE p.deleted_at IS NULL AND {} IS NOT NULL",
+        dialect.starts_at
+    );
+
+    if let Some(to) = query.to {
+        params.push(to.into());
+        sql.push_str(&format!(" AND {} <= {}", dialect.starts_at, (dialect.time_param)(params.len())));
+    }
+    if let Some(from) = query.from {
+        params.push(from.into());
+        sql.push_str(&format!(
+            " AND ({} IS NULL OR {} >= {})",
+            dialect.ends_at,
+            dialect.ends_at,
+            (dialect.time_param)(params.len())
+        ));
+    }
+
+    let row = database.query_one_any(&sql, &params).await?;
+    Ok(usize::try_from(row.int("eligible")?).unwrap_or(0))
+}
+
+fn parse_state(state: &str) -> Result<ProposalState> {
+    serde_json::from_str(state)
+        .map_err(|e| DaoError::DatabaseError(format!("Failed to parse proposal state: {}", e)))
+}
+
+fn parse_choice(choice: &str) -> Result<ProposalVote> {
+    serde_json::from_value(serde_json::Value::String(choice.to_string()))
+        .map_err(|e| DaoError::DatabaseError(format!("Failed to parse vote: {}", e)))
+}
+
+/// Whether a vote sided with a decided outcome
+fn with_majority(choice: ProposalVote, outcome: VoteOutcome) -> Option<bool> {
+    match (choice, outcome) {
+        (ProposalVote::Abstain, _) | (_, VoteOutcome::Pending) | (_, VoteOutcome::Cancelled) => None,
+        (ProposalVote::Yes, outcome) => Some(outcome == VoteOutcome::Approved),
+        (ProposalVote::No, outcome) | (ProposalVote::Veto, outcome) => Some(outcome == VoteOutcome::Rejected),
+    }
+}
+
+fn rate(count: usize, of: usize) -> Option<f64> {
+    (of > 0).then(|| count as f64 / of as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proposals::{Proposal, ProposalBuilder, ProposalType};
+    use chrono::Duration;
+
+    fn proposal(title: &str, state: ProposalState, starts_at: DateTime<Utc>, votes: &[(&str, ProposalVote, u64)]) -> Proposal {
+        let mut proposal = ProposalBuilder::new()
+            .title(title)
+            .description("Test proposal")
+            .proposal_type(ProposalType::TextProposal {
+                metadata: serde_json::Value::Null,
+            })
+            .proposer("0xProposer")
+            .build()
+            .unwrap();
+
+        proposal.state = state;
+        proposal.voting_starts_at = Some(starts_at);
+        proposal.voting_ends_at = Some(starts_at + Duration::days(3));
+        proposal.votes = votes
+            .iter()
+            .map(|&(voter, vote, voting_power)| Vote {
+                voter: voter.to_string(),
+                vote,
+                voting_power,
+                timestamp: starts_at + Duration::hours(1),
+                delegated: Vec::new(),
+                ranking: Vec::new(),
+                signature: None,
+            })
+            .collect();
+        proposal
+    }
+
+    async fn store(database: &Database, proposals: &[Proposal]) {
+        for proposal in proposals {
+            database
+                .execute_any(
+                    "INSERT INTO proposals (id, data, state, title, description) VALUES ($1, $2, $3, $4, $5)",
+                    &[
+                        proposal.id.as_str().into(),
+                        serde_json::to_value(proposal).unwrap().into(),
+                        serde_json::to_string(&proposal.state).unwrap().into(),
+                        proposal.title.as_str().into(),
+                        proposal.description.as_str().into(),
+                    ],
+                )
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_record_reflects_votes_and_outcomes() {
+        let database = Database::migrated_sqlite().await;
+        let start = Utc::now() - Duration::days(30);
+        let proposals = vec![
+            proposal("Grants", ProposalState::Executed, start, &[("0xAlice", ProposalVote::Yes, 100), ("0xBob", ProposalVote::No, 50)]),
+            proposal("Fee change", ProposalState::Rejected, start + Duration::days(5), &[("0xalice", ProposalVote::Yes, 120)]),
+            proposal("Treasury swap", ProposalState::Rejected, start + Duration::days(10), &[("0xAlice", ProposalVote::Veto, 80)]),
+            proposal("Logo", ProposalState::Voting, start + Duration::days(20), &[("0xAlice", ProposalVote::Abstain, 90)]),
+            proposal("Hiring", ProposalState::Approved, start + Duration::days(25), &[("0xBob", ProposalVote::Yes, 50)]),
+        ];
+        store(&database, &proposals).await;
+
+        let record = voting_record(&database, "0xALICE", &VotingRecordQuery::default()).await.unwrap();
+        assert_eq!(record.total, 4);
+        assert_eq!(record.summary.eligible_proposals, 5);
+        assert_eq!(record.summary.participation_rate, Some(0.8));
+        // Sided with the outcome on Grants and Treasury swap, not on Fee change
+        assert_eq!(record.summary.with_majority_rate, Some(2.0 / 3.0));
+
+        let titles: Vec<&str> = record.entries.iter().map(|entry| entry.title.as_str()).collect();
+        assert_eq!(titles, ["Logo", "Treasury swap", "Fee change", "Grants"]);
+
+        let grants = &record.entries[3];
+        assert_eq!(grants.proposal_id, proposals[0].id);
+        assert_eq!(grants.proposal_number, None);
+        assert_eq!(grants.choice, ProposalVote::Yes);
+        assert_eq!(grants.weight, 100);
+        assert_eq!(grants.outcome, VoteOutcome::Approved);
+        assert_eq!(grants.with_majority, Some(true));
+        assert_eq!(record.entries[0].outcome, VoteOutcome::Pending);
+        assert_eq!(record.entries[0].with_majority, None);
+
+        // Soft-deleted proposals drop out of the record and the stats
+        database
+            .execute_any(
+                "UPDATE proposals SET deleted_at = $1 WHERE id = $2",
+                &[Utc::now().timestamp().into(), proposals[3].id.as_str().into()],
+            )
+            .await
+            .unwrap();
+        let record = voting_record(&database, "0xAlice", &VotingRecordQuery::default()).await.unwrap();
+        assert_eq!(record.total, 3);
+        assert_eq!(record.summary.eligible_proposals, 4);
+    }
+
+    #[tokio::test]
+    async fn test_record_date_filter_and_pagination() {
+        let database = Database::migrated_sqlite().await;
+        let start = Utc::now() - Duration::days(30);
+        let proposals: Vec<Proposal> = (0..5)
+            .map(|i| {
+                proposal(
+                    &format!("Proposal {}", i),
+                    ProposalState::Approved,
+                    start + Duration::days(i * 5),
+                    &[("0xAlice", ProposalVote::Yes, 10)],
+                )
+            })
+            .collect();
+        store(&database, &proposals).await;
+
+        let query = VotingRecordQuery {
+            from: Some(start + Duration::days(4)),
+            to: Some(start + Duration::days(16)),
+            page: 2,
+            limit: 2,
+        };
+        let record = voting_record(&database, "0xAlice", &query).await.unwrap();
+
+        // Proposals 1, 2 and 3 fall in the period; page 2 holds the oldest
+        assert_eq!(record.total, 3);
+        assert_eq!(record.summary.votes_cast, 3);
+        assert_eq!(record.summary.eligible_proposals, 3);
+        assert_eq!(record.entries.len(), 1);
+        assert_eq!(record.entries[0].title, "Proposal 1");
+
+        let invalid = VotingRecordQuery {
+            limit: 0,
+            ..Default::default()
+        };
+        assert!(voting_record(&database, "0xAlice", &invalid).await.is_err());
+    }
+}
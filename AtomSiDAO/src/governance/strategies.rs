@@ -1,8 +1,10 @@
 //! Voting strategies for governance
 //!
 //! This module provides different voting strategy implementations
-//! for calculating vote weight based on token holdings.
+//! for calculating vote weight based on token holdings and, for
+//! reputation-weighted voting, member reputation.
 
+use crate::config::{ReputationCurve, ReputationWeightingConfig};
 use crate::core::{DaoError, Result};
 use async_trait::async_trait;
 use std::collections::HashMap;
@@ -21,6 +23,30 @@ pub trait VotingStrategy: Send + Sync {
     /// Calculate the voting weight for an address
     fn calculate_weight(&self, address: &str, balance: u64) -> Result<VoteWeight>;
     
+    /// Calculate the voting weight for a member with a known reputation
+    ///
+    /// Strategies that ignore reputation use `calculate_weight`.
+    fn calculate_member_weight(&self, address: &str, balance: u64, _reputation: u64) -> Result<VoteWeight> {
+        self.calculate_weight(address, balance)
+    }
+    
+    /// Whether the weight depends on the voter's reputation
+    fn uses_reputation(&self) -> bool {
+        false
+    }
+    
+    /// Whether proposals pass on conviction accrued from stakes rather than
+    /// on the tally
+    fn accrues_conviction(&self) -> bool {
+        false
+    }
+    
+    /// Whether voters choose their vote's weight and pay for it from a
+    /// per-epoch voice credit budget
+    fn uses_voice_credits(&self) -> bool {
+        false
+    }
+    
     /// Get the strategy name
     fn name(&self) -> &str;
     
@@ -89,6 +115,10 @@ impl VotingStrategy for QuadraticVoting {
         })
     }
     
+    fn uses_voice_credits(&self) -> bool {
+        true
+    }
+    
     fn name(&self) -> &str {
         "Quadratic Voting"
     }
@@ -148,6 +178,10 @@ impl VotingStrategy for ConvictionVoting {
         })
     }
     
+    fn accrues_conviction(&self) -> bool {
+        true
+    }
+    
     fn name(&self) -> &str {
         "Conviction Voting"
     }
@@ -157,6 +191,69 @@ impl VotingStrategy for ConvictionVoting {
     }
 }
 
+/// Reputation-weighted voting strategy
+///
+/// In this strategy, voting power is the token balance scaled by a
+/// multiplier that grows with the member's reputation.
+pub struct ReputationWeightedVoting {
+    /// Multiplier curve and cap
+    weighting: ReputationWeightingConfig,
+}
+
+impl ReputationWeightedVoting {
+    /// Create a new reputation-weighted voting strategy
+    pub fn new(weighting: ReputationWeightingConfig) -> Self {
+        Self { weighting }
+    }
+    
+    /// Multiplier for a reputation, between 1 and the configured cap
+    fn multiplier(&self, reputation: u64) -> f64 {
+        let reputation = reputation as f64;
+        let multiplier = match self.weighting.curve {
+            ReputationCurve::Linear { slope } => 1.0 + slope * reputation,
+            ReputationCurve::Logarithmic { scale } => 1.0 + scale * reputation.ln_1p(),
+        };
+        
+        multiplier.clamp(1.0, self.weighting.max_multiplier.max(1.0))
+    }
+}
+
+impl VotingStrategy for ReputationWeightedVoting {
+    fn calculate_weight(&self, address: &str, balance: u64) -> Result<VoteWeight> {
+        // Addresses without a member record have no reputation
+        self.calculate_member_weight(address, balance, 0)
+    }
+    
+    fn calculate_member_weight(&self, _address: &str, balance: u64, reputation: u64) -> Result<VoteWeight> {
+        // Voting power = token balance * f(reputation), saturating at u64::MAX
+        let multiplier = self.multiplier(reputation);
+        let weight = (balance as f64 * multiplier) as u64;
+        
+        let mut metadata = HashMap::new();
+        metadata.insert("formula".to_string(), "balance * f(reputation)".to_string());
+        metadata.insert("original_balance".to_string(), balance.to_string());
+        metadata.insert("reputation".to_string(), reputation.to_string());
+        metadata.insert("multiplier".to_string(), multiplier.to_string());
+        
+        Ok(VoteWeight {
+            value: weight,
+            metadata,
+        })
+    }
+    
+    fn uses_reputation(&self) -> bool {
+        true
+    }
+    
+    fn name(&self) -> &str {
+        "Reputation Weighted Voting"
+    }
+    
+    fn description(&self) -> &str {
+        "Voting power is token holdings scaled by member reputation"
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -189,6 +286,9 @@ mod tests {
         // Test with large balance
         let weight = strategy.calculate_weight("0x1", 10000).unwrap();
         assert_eq!(weight.value, 100); // sqrt(10000) = 100
+        
+        assert!(strategy.uses_voice_credits());
+        assert!(!TokenWeightedVoting::new().uses_voice_credits());
     }
     
     #[test]
@@ -208,4 +308,43 @@ mod tests {
         let weight = strategy.calculate_weight("0x1", 100).unwrap();
         assert_eq!(weight.value, 300); // 100 * 3 = 300
     }
+    
+    #[test]
+    fn test_reputation_weighted_voting() {
+        let strategy = ReputationWeightedVoting::new(ReputationWeightingConfig {
+            curve: ReputationCurve::Linear { slope: 0.01 },
+            max_multiplier: 2.0,
+        });
+        
+        // Equal balances, different reputations
+        let newcomer = strategy.calculate_member_weight("0x1", 1_000, 0).unwrap();
+        let veteran = strategy.calculate_member_weight("0x2", 1_000, 50).unwrap();
+        assert_eq!(newcomer.value, 1_000); // 1000 * 1.0
+        assert_eq!(veteran.value, 1_500); // 1000 * (1 + 0.01 * 50)
+        assert_eq!(veteran.metadata["reputation"], "50");
+        
+        // The multiplier is capped
+        let elder = strategy.calculate_member_weight("0x3", 1_000, 500).unwrap();
+        assert_eq!(elder.value, 2_000);
+        
+        // Without a reputation the balance counts as is
+        assert_eq!(strategy.calculate_weight("0x4", 1_000).unwrap().value, 1_000);
+        assert!(strategy.uses_reputation());
+        assert!(!TokenWeightedVoting::new().uses_reputation());
+    }
+    
+    #[test]
+    fn test_logarithmic_reputation_curve() {
+        let strategy = ReputationWeightedVoting::new(ReputationWeightingConfig {
+            curve: ReputationCurve::Logarithmic { scale: 0.5 },
+            max_multiplier: 10.0,
+        });
+        
+        let low = strategy.calculate_member_weight("0x1", 1_000, 10).unwrap().value;
+        let high = strategy.calculate_member_weight("0x2", 1_000, 100).unwrap().value;
+        assert!(1_000 < low && low < high);
+        
+        // Each step of reputation counts for less than the one before
+        assert!(high - low < low - 1_000);
+    }
 } 
\ No newline at end of file
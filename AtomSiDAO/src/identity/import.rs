@@ -0,0 +1,418 @@
+//! Bulk member import
+//!
+//! Members can be imported in bulk from CSV (`address,name,role` header) or
+//! JSON (an array of objects with the same fields). Every entry is validated
+//! before anything is written: the address must parse, the role must be one
+//! of the member roles (blank means `Member`), and an address may appear once
+//! in the batch and must not already belong to a member, in any letter case.
+//! Addresses of soft-deleted members may be imported again. Valid entries are
+//! then inserted in a single transaction; in a lenient import, an entry whose
+//! address was registered in the meantime is reported as rejected instead of
+//! failing the batch.
+
+use super::{Member, MemberId, MemberRole, MemberStatus};
+use crate::core::{Address, Database, DaoError, Result, SqlValue};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+/// One member to import
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemberImport {
+    /// Member address
+    pub address: String,
+    /// Member name
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Role name, case-insensitive; blank imports as `Member`
+    #[serde(default)]
+    pub role: Option<String>,
+}
+
+/// How an import handles invalid entries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ImportMode {
+    /// Import nothing if any entry is invalid
+    Strict,
+    /// Import the valid entries and skip the invalid ones
+    Lenient,
+}
+
+/// What happened to one entry of an import
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ImportOutcome {
+    /// The member was created with this ID
+    Imported(MemberId),
+    /// The entry is invalid and was not imported
+    Rejected(String),
+    /// The entry is valid, but a strict import was aborted by another entry
+    Aborted,
+}
+
+/// Result for one entry of an import
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportRowResult {
+    /// Position of the entry in the batch, starting at 1
+    pub row: usize,
+    /// Address as given
+    pub address: String,
+    /// What happened to the entry
+    pub outcome: ImportOutcome,
+}
+
+/// Per-entry report of an import
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportReport {
+    /// One result per entry, in batch order
+    pub rows: Vec<ImportRowResult>,
+}
+
+impl ImportReport {
+    /// Number of members created
+    pub fn imported(&self) -> usize {
+        self.rows
+            .iter()
+            .filter(|row| matches!(row.outcome, ImportOutcome::Imported(_)))
+            .count()
+    }
+
+    /// Number of invalid entries
+    pub fn rejected(&self) -> usize {
+        self.rows
+            .iter()
+            .filter(|row| matches!(row.outcome, ImportOutcome::Rejected(_)))
+            .count()
+    }
+}
+
+/// Parse members from CSV with an `address,name,role` header
+///
+/// The `name` and `role` columns may be left empty.
+pub fn members_from_csv(text: &str) -> Result<Vec<MemberImport>> {
+    csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(text.as_bytes())
+        .deserialize()
+        .enumerate()
+        .map(|(index, record)| {
+            let record: MemberImport = record
+                .map_err(|e| DaoError::InvalidParameter(format!("Invalid CSV record {}: {}", index + 1, e)))?;
+            Ok(MemberImport {
+                name: record.name.filter(|name| !name.is_empty()),
+                ..record
+            })
+        })
+        .collect()
+}
+
+/// Parse members from a JSON array
+pub fn members_from_json(text: &str) -> Result<Vec<MemberImport>> {
+    serde_json::from_str(text)
+        .map_err(|e| DaoError::InvalidParameter(format!("Invalid member import JSON: {}", e)))
+}
+
+/// Parse a role name, case-insensitively
+fn parse_role(role: Option<&str>) -> std::result::Result<MemberRole, String> {
+    match role.map(str::trim).unwrap_or_default().to_ascii_lowercase().as_str() {
+        "" | "member" => Ok(MemberRole::Member),
+        "delegate" => Ok(MemberRole::Delegate),
+        "council" => Ok(MemberRole::Council),
+        "admin" => Ok(MemberRole::Admin),
+        other => Err(format!("Unknown role: {}", other)),
+    }
+}
+
+/// Validate and import a batch of members
+///
+/// `is_valid_address` applies the connected chain's address rules on top of
+/// the format check. Members are written with `actor` as their last writer.
+/// Portable: runs on PostgreSQL and SQLite.
+pub(crate) async fn import_members(
+    database: &Database,
+    imports: Vec<MemberImport>,
+    mode: ImportMode,
+    actor: &str,
+    is_valid_address: impl Fn(&str) -> bool,
+) -> Result<ImportReport> {
+    let now = Utc::now();
+    let mut seen = HashSet::new();
+    let mut report = ImportReport::default();
+    let mut members = Vec::new();
+
+    for (index, import) in imports.into_iter().enumerate() {
+        let validated = validate(database, &import, &mut seen, &is_valid_address).await?;
+        let outcome = match validated {
+            Ok((address, role)) => {
+                let member = Member {
+                    id: Uuid::new_v4().to_string(),
+                    address: address.to_string(),
+                    name: import.name.clone(),
+                    role,
+                    status: MemberStatus::Active,
+                    reputation: 0,
+                    joined_at: now,
+                    last_active_at: now,
+                    role_changed_at: None,
+                    metadata: serde_json::Value::Null,
+                };
+                let outcome = ImportOutcome::Imported(member.id.clone());
+                members.push(member);
+                outcome
+            }
+            Err(reason) => ImportOutcome::Rejected(reason),
+        };
+
+        report.rows.push(ImportRowResult {
+            row: index + 1,
+            address: import.address,
+            outcome,
+        });
+    }
+
+    if mode == ImportMode::Strict && report.rejected() > 0 {
+        for row in &mut report.rows {
+            if matches!(row.outcome, ImportOutcome::Imported(_)) {
+                row.outcome = ImportOutcome::Aborted;
+            }
+        }
+        return Ok(report);
+    }
+
+    // A strict import fails as a whole if an address was taken since it was
+    // checked; a lenient one skips that entry
+    let insert = match mode {
+        ImportMode::Strict => {
+            "INSERT INTO members (id, address, data, created_at, updated_at, updated_by) VALUES ($1, $2, $3, $4, $5, $6)"
+        }
+        ImportMode::Lenient => {
+            "INSERT INTO members (id, address, data, created_at, updated_at, updated_by) VALUES ($1, $2, $3, $4, $5, $6) \
+             ON CONFLICT DO NOTHING"
+        }
+    };
+    let mut statements = Vec::with_capacity(members.len());
+    for member in &members {
+        let data = serde_json::to_value(member)
+            .map_err(|e| DaoError::DatabaseError(format!("Failed to serialize member: {}", e)))?;
+        let params: Vec<SqlValue> = vec![
+            member.id.as_str().into(),
+            member.address.as_str().into(),
+            data.into(),
+            now.into(),
+            now.into(),
+            actor.into(),
+        ];
+        statements.push((insert, params));
+    }
+    let inserted = database.execute_batch_any(&statements).await?;
+
+    for (member, inserted) in members.iter().zip(inserted) {
+        if inserted == 0 {
+            if let Some(row) = report
+                .rows
+                .iter_mut()
+                .find(|row| row.outcome == ImportOutcome::Imported(member.id.clone()))
+            {
+                row.outcome = ImportOutcome::Rejected("Member with this address already exists".to_string());
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Validate one entry, returning its normalized address and role or the
+/// reason it is rejected
+async fn validate(
+    database: &Database,
+    import: &MemberImport,
+    seen: &mut HashSet<String>,
+    is_valid_address: &impl Fn(&str) -> bool,
+) -> Result<std::result::Result<(Address, MemberRole), String>> {
+    let address = match Address::parse(import.address.trim()) {
+        Ok(address) if is_valid_address(address.as_str()) => address,
+        Ok(_) => return Ok(Err("Invalid address".to_string())),
+        Err(e) => return Ok(Err(e.to_string())),
+    };
+
+    let role = match parse_role(import.role.as_deref()) {
+        Ok(role) => role,
+        Err(reason) => return Ok(Err(reason)),
+    };
+
+    if !seen.insert(address.to_string()) {
+        return Ok(Err("Duplicate address in batch".to_string()));
+    }
+
+    let exists = database
+        .query_opt_any(
+            "SELECT 1 FROM members WHERE LOWER(address) = LOWER($1) AND deleted_at IS NULL",
+            &[address.as_str().into()],
+        )
+        .await?
+        .is_some();
+    if exists {
+        return Ok(Err("Member with this address already exists".to_string()));
+    }
+
+    Ok(Ok((address, role)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXISTING: &str = "0x0000000000000000000000000000000000000001";
+    const NEW_A: &str = "0x00000000000000000000000000000000000000a1";
+    const NEW_B: &str = "0x00000000000000000000000000000000000000b2";
+
+    /// A migrated database where `EXISTING` is already a member
+    async fn database_with_member() -> Database {
+        let database = Database::migrated_sqlite().await;
+        database
+            .execute_any(
+                "INSERT INTO members (id, address, data) VALUES ('existing', $1, '{}')",
+                &[EXISTING.into()],
+            )
+            .await
+            .unwrap();
+
+        database
+    }
+
+    /// Two valid entries, a duplicate of an existing member and a bad address
+    fn batch() -> Vec<MemberImport> {
+        members_from_csv(&format!(
+            "address,name,role\n{},Alice,delegate\n{},,\n{},Mallory,member\nnot-an-address,Bob,council\n",
+            NEW_A, NEW_B, EXISTING
+        ))
+        .unwrap()
+    }
+
+    async fn member_count(database: &Database) -> i64 {
+        database
+            .query_one_any("SELECT COUNT(*) AS count FROM members", &[])
+            .await
+            .unwrap()
+            .int("count")
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_strict_import_aborts_on_invalid_rows() {
+        let database = database_with_member().await;
+
+        let report = import_members(&database, batch(), ImportMode::Strict, "0xAdmin", |_| true)
+            .await
+            .unwrap();
+
+        assert_eq!(report.rows.len(), 4);
+        assert_eq!(report.imported(), 0);
+        assert_eq!(report.rejected(), 2);
+        assert_eq!(report.rows[0].outcome, ImportOutcome::Aborted);
+        assert_eq!(report.rows[1].outcome, ImportOutcome::Aborted);
+        assert!(matches!(&report.rows[2].outcome, ImportOutcome::Rejected(reason) if reason.contains("already exists")));
+        assert!(matches!(report.rows[3].outcome, ImportOutcome::Rejected(_)));
+        assert_eq!(member_count(&database).await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_lenient_import_skips_invalid_rows() {
+        let database = database_with_member().await;
+
+        let report = import_members(&database, batch(), ImportMode::Lenient, "0xAdmin", |_| true)
+            .await
+            .unwrap();
+
+        assert_eq!(report.imported(), 2);
+        assert_eq!(report.rejected(), 2);
+        assert_eq!(member_count(&database).await, 3);
+
+        let ImportOutcome::Imported(id) = &report.rows[0].outcome else {
+            panic!("expected the first row to be imported");
+        };
+        let row = database
+            .query_one_any("SELECT data, updated_by FROM members WHERE id = $1", &[id.as_str().into()])
+            .await
+            .unwrap();
+        let member: Member = row.json("data").unwrap();
+        assert_eq!(member.name.as_deref(), Some("Alice"));
+        assert_eq!(member.role, MemberRole::Delegate);
+        assert_eq!(row.text("updated_by").unwrap(), "0xAdmin");
+
+        // Blank name and role import as an unnamed `Member`
+        let ImportOutcome::Imported(id) = &report.rows[1].outcome else {
+            panic!("expected the second row to be imported");
+        };
+        let row = database
+            .query_one_any("SELECT data FROM members WHERE id = $1", &[id.as_str().into()])
+            .await
+            .unwrap();
+        let member: Member = row.json("data").unwrap();
+        assert_eq!((member.name, member.role), (None, MemberRole::Member));
+    }
+
+    #[tokio::test]
+    async fn test_duplicates_within_batch_rejected() {
+        let database = database_with_member().await;
+        let imports = members_from_json(&format!(
+            r#"[{{"address": "{}"}}, {{"address": "{}", "role": "Admin"}}]"#,
+            NEW_A,
+            NEW_A.to_uppercase().replacen("0X", "0x", 1)
+        ))
+        .unwrap();
+
+        let report = import_members(&database, imports, ImportMode::Lenient, "0xAdmin", |_| true)
+            .await
+            .unwrap();
+
+        assert_eq!(report.imported(), 1);
+        assert_eq!(
+            report.rows[1].outcome,
+            ImportOutcome::Rejected("Duplicate address in batch".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_existing_address_checked_across_case_and_deletion() {
+        let database = Database::migrated_sqlite().await;
+        let live = Address::parse(NEW_A).unwrap();
+        database
+            .execute_any(
+                "INSERT INTO members (id, address, data) VALUES ('live', $1, '{}')",
+                &[live.as_str().to_lowercase().into()],
+            )
+            .await
+            .unwrap();
+        database
+            .execute_any(
+                "INSERT INTO members (id, address, data, deleted_at) VALUES ('removed', $1, '{}', $2)",
+                &[NEW_B.into(), Utc::now().timestamp().into()],
+            )
+            .await
+            .unwrap();
+
+        let imports = members_from_json(&format!(
+            r#"[{{"address": "{}"}}, {{"address": "{}"}}]"#,
+            live.as_str(),
+            NEW_B
+        ))
+        .unwrap();
+        let report = import_members(&database, imports, ImportMode::Lenient, "0xAdmin", |_| true)
+            .await
+            .unwrap();
+
+        // A live member under another spelling is a duplicate; a removed
+        // member's address is free again
+        assert!(matches!(&report.rows[0].outcome, ImportOutcome::Rejected(reason) if reason.contains("already exists")));
+        assert!(matches!(report.rows[1].outcome, ImportOutcome::Imported(_)));
+        assert_eq!(member_count(&database).await, 3);
+    }
+
+    #[test]
+    fn test_parse_role() {
+        assert_eq!(parse_role(Some(" Council ")), Ok(MemberRole::Council));
+        assert_eq!(parse_role(None), Ok(MemberRole::Member));
+        assert!(parse_role(Some("owner")).is_err());
+    }
+}
@@ -0,0 +1,319 @@
+//! Member invitations
+//!
+//! Members join by invitation rather than registering themselves. An
+//! invitation token is `<id>.<signature>`, where the signature is an HMAC of
+//! the invitation's id, role and expiry keyed with `security.jwt_secret`, so
+//! a token can't be forged or altered to grant another role. The invitee
+//! accepts by signing a timestamped [`acceptance_message`] with the address
+//! to register, and each invitation can be accepted once.
+
+use super::{Member, MemberRole};
+use crate::core::{Database, DaoError, Result};
+use crate::crypto;
+use crate::utils::time::timestamp_to_datetime;
+use chrono::{DateTime, SecondsFormat, Utc};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use sha2::Sha256;
+
+/// A stored invitation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Invitation {
+    /// Random hex id, the first part of the token
+    pub id: String,
+    /// Role the invitee is registered with
+    pub role: MemberRole,
+    /// Time after which the invitation can no longer be accepted
+    pub expires_at: DateTime<Utc>,
+    /// Address that accepted the invitation, once used
+    pub accepted_by: Option<String>,
+}
+
+/// Message an invitee signs with their address to accept an invitation
+///
+/// The timestamp is given to the second, so clients can rebuild the exact
+/// message; signatures older than `security.signature_max_age_seconds` are
+/// refused.
+pub fn acceptance_message(token: &str, signed_at: DateTime<Utc>) -> String {
+    format!(
+        "AtomSi DAO invitation\nToken: {}\n{} {}",
+        token.trim(),
+        crypto::ISSUED_AT_PREFIX,
+        signed_at.to_rfc3339_opts(SecondsFormat::Secs, true)
+    )
+}
+
+/// Create an invitation and its token
+pub(crate) fn new_invitation(role: MemberRole, expires_at: DateTime<Utc>, secret: &[u8]) -> Result<(Invitation, String)> {
+    let mut id = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut id);
+
+    // Only whole seconds are stored, so only whole seconds are signed
+    let invitation = Invitation {
+        id: hex::encode(id),
+        role,
+        expires_at: timestamp_to_datetime(expires_at.timestamp().max(0) as u64),
+        accepted_by: None,
+    };
+    let signature = signing_mac(&invitation, secret)?.finalize().into_bytes();
+    let token = format!("{}.{}", invitation.id, hex::encode(signature));
+
+    Ok((invitation, token))
+}
+
+/// Split a token into its invitation id and signature
+pub(crate) fn parse_token(token: &str) -> Result<(&str, Vec<u8>)> {
+    let (id, signature) = token.trim().split_once('.').ok_or_else(invalid_token)?;
+    let signature = hex::decode(signature).map_err(|_| invalid_token())?;
+
+    Ok((id, signature))
+}
+
+/// Check a token's signature and that its invitation can still be accepted
+pub(crate) fn check_invitation(
+    invitation: &Invitation,
+    signature: &[u8],
+    secret: &[u8],
+    now: DateTime<Utc>,
+) -> Result<()> {
+    signing_mac(invitation, secret)?
+        .verify_slice(signature)
+        .map_err(|_| invalid_token())?;
+
+    if invitation.accepted_by.is_some() {
+        return Err(DaoError::IdentityError("Invitation has already been used".to_string()));
+    }
+
+    if now >= invitation.expires_at {
+        return Err(DaoError::IdentityError("Invitation has expired".to_string()));
+    }
+
+    Ok(())
+}
+
+/// HMAC over the parts of an invitation a token vouches for
+fn signing_mac(invitation: &Invitation, secret: &[u8]) -> Result<Hmac<Sha256>> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret)
+        .map_err(|e| DaoError::InternalError(format!("Invalid invitation signing key: {}", e)))?;
+    mac.update(format!("{}:{:?}:{}", invitation.id, invitation.role, invitation.expires_at.timestamp()).as_bytes());
+
+    Ok(mac)
+}
+
+fn invalid_token() -> DaoError {
+    DaoError::IdentityError("Invalid invitation token".to_string())
+}
+
+/// Store a new invitation
+pub(crate) async fn save_invitation(database: &Database, invitation: &Invitation) -> Result<()> {
+    let role = serde_json::to_string(&invitation.role)
+        .map_err(|e| DaoError::DatabaseError(format!("Failed to serialize role: {}", e)))?;
+
+    database
+        .execute_any(
+            "INSERT INTO invitations (id, role, expires_at, created_at) VALUES ($1, $2, $3, $4)",
+            &[
+                invitation.id.as_str().into(),
+                role.into(),
+                invitation.expires_at.timestamp().into(),
+                Utc::now().timestamp().into(),
+            ],
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Load an invitation by id
+pub(crate) async fn load_invitation(database: &Database, id: &str) -> Result<Option<Invitation>> {
+    let row = database
+        .query_opt_any(
+            "SELECT id, role, expires_at, COALESCE(accepted_by, '') AS accepted_by FROM invitations WHERE id = $1",
+            &[id.into()],
+        )
+        .await?;
+
+    let Some(row) = row else {
+        return Ok(None);
+    };
+
+    let role = serde_json::from_str(&row.text("role")?)
+        .map_err(|e| DaoError::DatabaseError(format!("Failed to parse role: {}", e)))?;
+    let accepted_by = row.text("accepted_by")?;
+
+    Ok(Some(Invitation {
+        id: row.text("id")?,
+        role,
+        expires_at: timestamp_to_datetime(row.int("expires_at")?.max(0) as u64),
+        accepted_by: (!accepted_by.is_empty()).then_some(accepted_by),
+    }))
+}
+
+/// Mark an invitation accepted by `member` and register the member
+///
+/// Both happen in one database transaction, so a failed registration leaves
+/// the invitation unused. Only one of two racing acceptances succeeds, and
+/// an invitation that expired since it was checked is not accepted.
+pub(crate) async fn claim_invitation(database: &Database, id: &str, member: &Member, now: DateTime<Utc>) -> Result<()> {
+    let data = serde_json::to_value(member)
+        .map_err(|e| DaoError::DatabaseError(format!("Failed to serialize member: {}", e)))?;
+
+    let affected = database
+        .execute_batch_any(&[
+            (
+                "UPDATE invitations SET accepted_by = $1, accepted_at = $2 \
+                 WHERE id = $3 AND accepted_by IS NULL AND expires_at > $2",
+                vec![member.address.as_str().into(), now.timestamp().into(), id.into()],
+            ),
+            (
+                "INSERT INTO members (id, address, data, created_at, updated_at, updated_by) \
+                 SELECT $1, $2, $3, $4, $5, $2 WHERE EXISTS \
+                 (SELECT 1 FROM invitations WHERE id = $6 AND accepted_by = $2 AND accepted_at = $7)",
+                vec![
+                    member.id.as_str().into(),
+                    member.address.as_str().into(),
+                    data.into(),
+                    member.joined_at.into(),
+                    now.into(),
+                    id.into(),
+                    now.timestamp().into(),
+                ],
+            ),
+        ])
+        .await?;
+
+    if affected[0] == 0 {
+        return Err(DaoError::IdentityError(
+            "Invitation has expired or has already been used".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::{BlockchainAdapter, MockBlockchain};
+    use crate::core::Address;
+    use crate::identity::IdentityManager;
+    use chrono::Duration;
+    use ethers::signers::LocalWallet;
+    use std::sync::Arc;
+
+    /// Well-known development key (the first Anvil/Hardhat account)
+    const TEST_KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+    const TEST_ADDRESS: &str = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266";
+
+    fn manager(database: &Database) -> IdentityManager {
+        let config = crate::config::default();
+        IdentityManager::new(
+            &config,
+            BlockchainAdapter::with_mock(&config.blockchain, Arc::new(MockBlockchain::new())),
+            database.clone(),
+        )
+        .unwrap()
+    }
+
+    /// The test account's signature accepting `token`, made at `signed_at`
+    fn sign(token: &str, signed_at: DateTime<Utc>) -> String {
+        let wallet: LocalWallet = TEST_KEY.parse().unwrap();
+        let message = acceptance_message(token, signed_at);
+        format!("0x{}", wallet.sign_hash(ethers::utils::hash_message(message)).unwrap())
+    }
+
+    async fn accept(manager: &IdentityManager, token: &str) -> Result<String> {
+        let now = Utc::now();
+        manager
+            .accept_invitation(token, &Address::parse(TEST_ADDRESS).unwrap(), &sign(token, now), now)
+            .await
+    }
+
+    async fn accepted_by(database: &Database, token: &str) -> Option<String> {
+        let (id, _) = parse_token(token).unwrap();
+        load_invitation(database, id).await.unwrap().unwrap().accepted_by
+    }
+
+    #[tokio::test]
+    async fn test_accept_before_expiry() {
+        let database = Database::migrated_sqlite().await;
+        let manager = manager(&database);
+        let token = manager.create_invitation(MemberRole::Delegate, Duration::days(7)).await.unwrap();
+
+        let member_id = accept(&manager, &token).await.unwrap();
+
+        let member = manager.get_member_by_id(&member_id).await.unwrap();
+        assert_eq!(member.role, MemberRole::Delegate);
+        assert!(member.address.eq_ignore_ascii_case(TEST_ADDRESS));
+        assert!(accepted_by(&database, &token).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_accept_after_expiry_rejected() {
+        let database = Database::migrated_sqlite().await;
+        let manager = manager(&database);
+        let secret = crate::config::default().security.jwt_secret;
+        let (invitation, token) =
+            new_invitation(MemberRole::Delegate, Utc::now() - Duration::hours(1), secret.as_bytes()).unwrap();
+        save_invitation(&database, &invitation).await.unwrap();
+
+        assert!(matches!(accept(&manager, &token).await, Err(DaoError::IdentityError(_))));
+        assert_eq!(accepted_by(&database, &token).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_double_accept_rejected() {
+        let database = Database::migrated_sqlite().await;
+        let manager = manager(&database);
+        let token = manager.create_invitation(MemberRole::Delegate, Duration::days(1)).await.unwrap();
+
+        accept(&manager, &token).await.unwrap();
+        assert!(matches!(accept(&manager, &token).await, Err(DaoError::IdentityError(_))));
+
+        // A racing acceptance that passed the checks still loses the claim
+        let (id, _) = parse_token(&token).unwrap();
+        let rival = super::super::new_member(&Address::parse(TEST_ADDRESS).unwrap(), None, MemberRole::Admin);
+        assert!(claim_invitation(&database, id, &rival, Utc::now()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_failed_registration_keeps_invitation() {
+        let database = Database::migrated_sqlite().await;
+        let manager = manager(&database);
+        let token = manager.create_invitation(MemberRole::Delegate, Duration::days(1)).await.unwrap();
+        let (id, _) = parse_token(&token).unwrap();
+
+        // The member row can't be written, so the claim is rolled back
+        let address = Address::parse(TEST_ADDRESS).unwrap();
+        let member = super::super::new_member(&address, None, MemberRole::Delegate);
+        manager.save_member(&member, TEST_ADDRESS).await.unwrap();
+        manager.soft_delete_member(&member.id, TEST_ADDRESS).await.unwrap();
+        assert!(claim_invitation(&database, id, &member, Utc::now()).await.is_err());
+        assert_eq!(accepted_by(&database, &token).await, None);
+
+        accept(&manager, &token).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_stale_or_tampered_acceptance_rejected() {
+        let database = Database::migrated_sqlite().await;
+        let manager = manager(&database);
+        let token = manager.create_invitation(MemberRole::Delegate, Duration::days(1)).await.unwrap();
+        let address = Address::parse(TEST_ADDRESS).unwrap();
+
+        // A captured signature can't be replayed later
+        let signed_at = Utc::now() - Duration::hours(1);
+        let stale = manager.accept_invitation(&token, &address, &sign(&token, signed_at), signed_at).await;
+        assert!(matches!(stale, Err(DaoError::Unauthorized)));
+
+        let (id, _) = parse_token(&token).unwrap();
+        let forged = format!("{}.{}", id, hex::encode([0u8; 32]));
+        assert!(accept(&manager, &forged).await.is_err());
+        assert!(accept(&manager, "not-a-token").await.is_err());
+
+        // The genuine token is still usable
+        assert!(accept(&manager, &token).await.is_ok());
+    }
+}
+
+
@@ -3,19 +3,40 @@
 //! This module provides functionality for managing identities and
 //! reputation within the DAO.
 
+mod import;
+mod invitations;
+mod reputation;
+mod standing;
+
+pub use import::{
+    members_from_csv, members_from_json, ImportMode, ImportOutcome, ImportReport, ImportRowResult, MemberImport,
+};
+pub use invitations::acceptance_message;
+use reputation::ReputationBuffer;
+pub(crate) use standing::require_standing;
+
 use crate::{
+    api::websocket::{EventType, WebSocketEvent, WebSocketManager},
     blockchain::BlockchainAdapter,
-    config::Config,
-    core::{Database, DaoError, Result},
+    config::{Config, PromotionConfig},
+    core::{escape_like, fetch_page, Address, Database, DaoError, Page, PageRequest, Result, SYSTEM_ACTOR},
+    database::DatabaseType,
+    security::{record_admin_action, AdminAction, AuditLog},
 };
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tracing::warn;
 use uuid::Uuid;
 
 /// Member ID type
 pub type MemberId = String;
 
+/// Maximum number of members returned by a search
+pub const MAX_MEMBER_SEARCH_RESULTS: usize = 50;
+
 /// Member role enum
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum MemberRole {
@@ -29,6 +50,33 @@ pub enum MemberRole {
     Admin,
 }
 
+impl MemberRole {
+    /// Get the next role up the promotion ladder
+    pub fn next(&self) -> Option<MemberRole> {
+        match self {
+            MemberRole::Member => Some(MemberRole::Delegate),
+            MemberRole::Delegate => Some(MemberRole::Council),
+            MemberRole::Council => Some(MemberRole::Admin),
+            MemberRole::Admin => None,
+        }
+    }
+    
+    /// Whether the role is at least as privileged as `role`
+    pub fn is_at_least(&self, role: MemberRole) -> bool {
+        self.rank() >= role.rank()
+    }
+    
+    /// Rank of the role, higher is more privileged
+    fn rank(&self) -> u8 {
+        match self {
+            MemberRole::Member => 0,
+            MemberRole::Delegate => 1,
+            MemberRole::Council => 2,
+            MemberRole::Admin => 3,
+        }
+    }
+}
+
 /// Member status enum
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum MemberStatus {
@@ -59,6 +107,9 @@ pub struct Member {
     pub joined_at: DateTime<Utc>,
     /// Last activity timestamp
     pub last_active_at: DateTime<Utc>,
+    /// Last role change timestamp
+    #[serde(default)]
+    pub role_changed_at: Option<DateTime<Utc>>,
     /// Additional metadata
     pub metadata: serde_json::Value,
 }
@@ -76,6 +127,8 @@ pub enum ActivityType {
     Delegation,
     /// Treasury transaction
     TreasuryTransaction,
+    /// Role change
+    RoleChange,
     /// Other activity
     Other,
 }
@@ -102,6 +155,7 @@ pub struct Activity {
 }
 
 /// Identity manager
+#[derive(Clone)]
 pub struct IdentityManager {
     /// Configuration
     config: Arc<Config>,
@@ -109,6 +163,12 @@ pub struct IdentityManager {
     blockchain: Arc<dyn BlockchainAdapter>,
     /// Database
     database: Database,
+    /// Event sender for real-time updates
+    event_sender: Option<broadcast::Sender<WebSocketEvent>>,
+    /// Reputation changes waiting to be flushed (when batching is enabled)
+    reputation_buffer: Arc<ReputationBuffer>,
+    /// Log of admin actions such as role changes
+    audit_log: Option<Arc<AuditLog>>,
 }
 
 impl IdentityManager {
@@ -122,18 +182,33 @@ impl IdentityManager {
             config: Arc::new(config.clone()),
             blockchain: Arc::new(blockchain),
             database,
+            event_sender: None,
+            reputation_buffer: Arc::new(ReputationBuffer::default()),
+            audit_log: None,
         })
     }
     
+    /// Broadcast member events on the given channel
+    pub fn with_event_sender(mut self, sender: broadcast::Sender<WebSocketEvent>) -> Self {
+        self.event_sender = Some(sender);
+        self
+    }
+    
+    /// Record role changes in the given admin action log
+    pub fn with_audit_log(mut self, audit_log: Arc<AuditLog>) -> Self {
+        self.audit_log = Some(audit_log);
+        self
+    }
+    
     /// Register a new member
     pub async fn register_member(
         &self,
-        address: &str,
+        address: &Address,
         name: Option<String>,
         role: MemberRole,
     ) -> Result<MemberId> {
-        // Check if the address is valid
-        if !self.blockchain.is_valid_address(address) {
+        // Check if the address is valid on the connected chain
+        if !self.blockchain.is_valid_address(address.as_str()) {
             return Err(DaoError::InvalidParameter("Invalid address".to_string()));
         }
         
@@ -145,65 +220,223 @@ impl IdentityManager {
         }
         
         // Create a new member
-        let member = Member {
-            id: Uuid::new_v4().to_string(),
-            address: address.to_string(),
-            name,
-            role,
-            status: MemberStatus::Active,
-            reputation: 0,
-            joined_at: Utc::now(),
-            last_active_at: Utc::now(),
-            metadata: serde_json::Value::Null,
-        };
+        let member = new_member(address, name, role);
         
         // Save the member to the database
-        self.save_member(&member).await?;
+        self.save_member(&member, address.as_str()).await?;
+        
+        Ok(member.id)
+    }
+    
+    /// Import members in bulk
+    ///
+    /// Every entry is validated first; valid entries are inserted in one
+    /// transaction. In strict mode nothing is imported if any entry is
+    /// invalid, in lenient mode invalid entries are skipped. The report has
+    /// one result per entry. `actor` is recorded as the members' last writer.
+    pub async fn import_members(
+        &self,
+        imports: Vec<MemberImport>,
+        mode: ImportMode,
+        actor: &str,
+    ) -> Result<ImportReport> {
+        import::import_members(&self.database.primary(), imports, mode, actor, |address| {
+            self.blockchain.is_valid_address(address)
+        })
+        .await
+    }
+    
+    /// Create a single-use invitation to join with `role`
+    ///
+    /// Returns the token to send to the invitee, valid for `expires_in`.
+    pub async fn create_invitation(&self, role: MemberRole, expires_in: Duration) -> Result<String> {
+        if expires_in <= Duration::zero() {
+            return Err(DaoError::InvalidParameter(
+                "Invitation expiry must be in the future".to_string(),
+            ));
+        }
+        
+        let (invitation, token) = invitations::new_invitation(
+            role,
+            Utc::now() + expires_in,
+            self.config.security.jwt_secret.as_bytes(),
+        )?;
+        invitations::save_invitation(&self.database.primary(), &invitation).await?;
+        
+        Ok(token)
+    }
+    
+    /// Accept an invitation, registering `address` with the invited role
+    ///
+    /// `signature` is the address's `personal_sign` signature over
+    /// [`acceptance_message`] for the token and `signed_at`; signatures
+    /// older than `security.signature_max_age_seconds` are refused. Expired
+    /// and already used invitations are rejected. The invitation is used up
+    /// only if the member is registered.
+    pub async fn accept_invitation(
+        &self,
+        token: &str,
+        address: &Address,
+        signature: &str,
+        signed_at: DateTime<Utc>,
+    ) -> Result<MemberId> {
+        let database = self.database.primary();
+        let now = Utc::now();
+        
+        // Check the token before anything about the invitee
+        let (id, token_signature) = invitations::parse_token(token)?;
+        let invitation = invitations::load_invitation(&database, id)
+            .await?
+            .ok_or_else(|| DaoError::IdentityError("Invalid invitation token".to_string()))?;
+        invitations::check_invitation(&invitation, &token_signature, self.config.security.jwt_secret.as_bytes(), now)?;
+        
+        // The invitee must control the address they register
+        let signed = crate::crypto::verify_timestamped_signature(
+            address.as_str(),
+            &acceptance_message(token, signed_at),
+            signature,
+            self.config.security.signature_max_age_seconds,
+        )
+        .unwrap_or(false);
+        if !signed {
+            return Err(DaoError::Unauthorized);
+        }
+        
+        // Refuse before using up the invitation if registration would fail
+        if !self.blockchain.is_valid_address(address.as_str()) {
+            return Err(DaoError::InvalidParameter("Invalid address".to_string()));
+        }
+        if self.member_exists(address).await? {
+            return Err(DaoError::InvalidParameter(
+                "Member with this address already exists".to_string(),
+            ));
+        }
+        
+        let member = new_member(address, None, invitation.role);
+        invitations::claim_invitation(&database, id, &member, now).await?;
         
         Ok(member.id)
     }
     
     /// Get a member by ID
+    ///
+    /// Served by the read replica when one is configured.
     pub async fn get_member_by_id(&self, id: &MemberId) -> Result<Member> {
+        let mut member = Self::load_member(&self.database, id).await?;
+        self.reputation_buffer.apply_pending(&mut member);
+        Ok(member)
+    }
+    
+    /// Get a member by ID from the primary, for read-modify-write paths
+    ///
+    /// Pending reputation changes are not applied, so saving the member
+    /// does not write them twice.
+    async fn get_member_for_update(&self, id: &MemberId) -> Result<Member> {
+        Self::load_member(&self.database.primary(), id).await
+    }
+    
+    /// Load a member using the given database handle
+    ///
+    /// Soft-deleted members are not found. Portable: runs on PostgreSQL and
+    /// SQLite.
+    async fn load_member(database: &Database, id: &MemberId) -> Result<Member> {
         // Load the member from the database
-        let query = "SELECT * FROM members WHERE id = $1";
-        let row = self.database.query_one(query, &[&id]).await?;
+        let query = "SELECT data FROM members WHERE id = $1 AND deleted_at IS NULL";
+        let row = database
+            .query_opt_any(query, &[id.as_str().into()])
+            .await?
+            .ok_or_else(|| DaoError::IdentityError(format!("Member not found: {}", id)))?;
         
         // Parse the member from the row
-        let member: Member = serde_json::from_value(row.get("data"))
-            .map_err(|e| DaoError::DatabaseError(format!("Failed to parse member: {}", e)))?;
-        
-        Ok(member)
+        row.json("data")
+            .map_err(|e| DaoError::DatabaseError(format!("Failed to parse member: {}", e)))
     }
     
     /// Get a member by address
-    pub async fn get_member_by_address(&self, address: &str) -> Result<Member> {
+    pub async fn get_member_by_address(&self, address: &Address) -> Result<Member> {
         // Load the member from the database
-        let query = "SELECT * FROM members WHERE address = $1";
-        let row = self.database.query_one(query, &[&address]).await?;
+        let query = "SELECT data FROM members WHERE address = $1 AND deleted_at IS NULL";
+        let row = self.database.query_one_any(query, &[address.as_str().into()]).await?;
         
         // Parse the member from the row
-        let member: Member = serde_json::from_value(row.get("data"))
+        let mut member: Member = row
+            .json("data")
             .map_err(|e| DaoError::DatabaseError(format!("Failed to parse member: {}", e)))?;
+        self.reputation_buffer.apply_pending(&mut member);
         
         Ok(member)
     }
     
+    /// Find members whose name or address contains `query`, ignoring case
+    ///
+    /// An exact address or name match comes first, then names starting with
+    /// the query, then other matches. Returns at most
+    /// [`MAX_MEMBER_SEARCH_RESULTS`] members, without pending reputation
+    /// changes.
+    pub async fn search_members(&self, query: &str) -> Result<Vec<Member>> {
+        Self::find_members(&self.database, query, MAX_MEMBER_SEARCH_RESULTS).await
+    }
+    
+    /// Search members using the given database handle
+    async fn find_members(database: &Database, query: &str, limit: usize) -> Result<Vec<Member>> {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return Ok(Vec::new());
+        }
+        
+        let name = match database.db_type() {
+            DatabaseType::Postgres => "LOWER(COALESCE(data->>'name', ''))",
+            DatabaseType::SQLite => "LOWER(COALESCE(json_extract(data, '$.name'), ''))",
+        };
+        let sql = format!(
+            "SELECT data FROM members WHERE deleted_at IS NULL \
+             AND ({name} LIKE $1 ESCAPE '\\' OR LOWER(address) LIKE $1 ESCAPE '\\') \
+             ORDER BY CASE WHEN LOWER(address) = $2 OR {name} = $2 THEN 0 WHEN {name} LIKE $3 ESCAPE '\\' THEN 1 ELSE 2 END, \
+             address LIMIT $4",
+            name = name
+        );
+        let pattern = escape_like(&query);
+        let rows = database
+            .query_any(
+                &sql,
+                &[
+                    format!("%{}%", pattern).into(),
+                    query.as_str().into(),
+                    format!("{}%", pattern).into(),
+                    i64::try_from(limit).unwrap_or(i64::MAX).into(),
+                ],
+            )
+            .await?;
+        
+        rows.iter()
+            .map(|row| {
+                row.json("data")
+                    .map_err(|e| DaoError::DatabaseError(format!("Failed to parse member: {}", e)))
+            })
+            .collect()
+    }
+    
     /// Check if a member exists
-    pub async fn member_exists(&self, address: &str) -> Result<bool> {
+    ///
+    /// Soft-deleted members don't count, so their address can register again.
+    pub async fn member_exists(&self, address: &Address) -> Result<bool> {
         // Query the database for the member
-        let query = "SELECT 1 FROM members WHERE address = $1";
-        let result = self.database.query_opt(query, &[&address]).await?;
+        let query = "SELECT 1 FROM members WHERE address = $1 AND deleted_at IS NULL";
+        let result = self.database.query_opt_any(query, &[address.as_str().into()]).await?;
         
         Ok(result.is_some())
     }
     
     /// Update a member
-    pub async fn update_member(&self, member: &Member) -> Result<()> {
+    ///
+    /// Members read through this manager include pending reputation changes,
+    /// so this write persists them and they are dropped from the batch.
+    /// `actor` is recorded as the member's last writer.
+    pub async fn update_member(&self, member: &Member, actor: &str) -> Result<()> {
         // Check if the member exists
         let exists = self
             .database
-            .query_opt("SELECT 1 FROM members WHERE id = $1", &[&member.id])
+            .query_opt("SELECT 1 FROM members WHERE id = $1 AND deleted_at IS NULL", &[&member.id])
             .await?
             .is_some();
         
@@ -212,7 +445,8 @@ impl IdentityManager {
         }
         
         // Save the member to the database
-        self.save_member(member).await?;
+        self.save_member(member, actor).await?;
+        self.reputation_buffer.discard(&member.id);
         
         Ok(())
     }
@@ -222,37 +456,122 @@ impl IdentityManager {
         &self,
         id: &MemberId,
         status: MemberStatus,
+        actor: &str,
     ) -> Result<()> {
         // Load the member
-        let mut member = self.get_member_by_id(id).await?;
+        let mut member = self.get_member_for_update(id).await?;
         
         // Update the status
         member.status = status;
         member.last_active_at = Utc::now();
         
         // Save the member
-        self.save_member(&member).await?;
+        self.save_member(&member, actor).await?;
         
         Ok(())
     }
     
     /// Update member role
+    ///
+    /// The change is recorded in the admin action log.
     pub async fn update_member_role(
         &self,
         id: &MemberId,
         role: MemberRole,
+        actor: &str,
     ) -> Result<()> {
         // Load the member
-        let mut member = self.get_member_by_id(id).await?;
+        let mut member = self.get_member_for_update(id).await?;
         
         // Update the role
+        let old_role = member.role;
         member.role = role;
         member.last_active_at = Utc::now();
+        member.role_changed_at = Some(Utc::now());
         
         // Save the member
-        self.save_member(&member).await?;
+        self.save_member(&member, actor).await?;
         
-        Ok(())
+        record_admin_action(
+            self.audit_log.as_ref(),
+            AdminAction::RoleChange,
+            actor,
+            serde_json::json!({ "member_id": member.id, "old_role": old_role, "role": role }),
+        )
+        .await
+    }
+    
+    /// Promote members whose reputation has crossed the configured thresholds
+    ///
+    /// Each eligible member moves up at most one role per sweep. Promotions
+    /// are recorded as `RoleChange` activities and broadcast as
+    /// `MemberUpdated` events. Returns the IDs of the promoted members.
+    pub async fn run_promotion_sweep(&self) -> Result<Vec<MemberId>> {
+        let promotion = &self.config.identity.promotion;
+        if !promotion.enabled {
+            return Ok(Vec::new());
+        }
+        
+        // Promotions are decided on stored reputation, so write pending changes first
+        self.flush_reputation().await?;
+        
+        let now = Utc::now();
+        let mut promoted = Vec::new();
+        
+        for candidate in self.get_active_members().await? {
+            if promotion_target(&candidate, promotion, now).is_none() {
+                continue;
+            }
+            
+            // Re-check against the primary before writing
+            let mut member = self.get_member_for_update(&candidate.id).await?;
+            let new_role = match promotion_target(&member, promotion, now) {
+                Some(role) => role,
+                None => continue,
+            };
+            
+            let old_role = member.role;
+            member.role = new_role;
+            member.role_changed_at = Some(now);
+            self.save_member(&member, SYSTEM_ACTOR).await?;
+            
+            self.record_activity(
+                &member.id,
+                ActivityType::RoleChange,
+                None,
+                &format!("Automatically promoted from {:?} to {:?}", old_role, new_role),
+                0,
+                serde_json::json!({
+                    "old_role": old_role,
+                    "new_role": new_role,
+                    "reputation": member.reputation,
+                    "automatic": true,
+                }),
+            )
+            .await?;
+            
+            record_admin_action(
+                self.audit_log.as_ref(),
+                AdminAction::RoleChange,
+                SYSTEM_ACTOR,
+                serde_json::json!({ "member_id": member.id, "old_role": old_role, "role": new_role }),
+            )
+            .await?;
+            
+            self.emit_event(
+                EventType::MemberUpdated,
+                serde_json::json!({
+                    "member_id": member.id,
+                    "old_role": old_role,
+                    "role": new_role,
+                    "reason": "auto_promotion",
+                }),
+            );
+            
+            promoted.push(member.id);
+        }
+        
+        Ok(promoted)
     }
     
     /// Record an activity
@@ -281,23 +600,83 @@ impl IdentityManager {
         self.save_activity(&activity).await?;
         
         // Update the member's reputation
-        if reputation_change != 0 {
-            let mut member = self.get_member_by_id(member_id).await?;
+        let batching = &self.config.identity.reputation_batching;
+        if reputation_change != 0 && batching.enabled {
+            let pending_members = self.reputation_buffer.add(member_id, reputation_change, activity.timestamp);
+            if pending_members >= batching.max_pending_members {
+                self.flush_reputation().await?;
+            }
+        } else if reputation_change != 0 {
+            let mut member = self.get_member_for_update(member_id).await?;
             
             // Apply reputation change, ensuring it doesn't go below 0
-            if reputation_change < 0 && member.reputation < (-reputation_change) as u64 {
-                member.reputation = 0;
-            } else {
-                member.reputation = (member.reputation as i64 + reputation_change) as u64;
-            }
+            member.reputation = reputation::apply_reputation_delta(member.reputation, reputation_change);
             
             member.last_active_at = Utc::now();
-            self.save_member(&member).await?;
+            self.save_member(&member, SYSTEM_ACTOR).await?;
         }
         
         Ok(activity.id)
     }
     
+    /// Write pending reputation changes, one member update each
+    ///
+    /// Changes that fail to write are kept for the next flush. Returns the
+    /// number of members updated.
+    pub async fn flush_reputation(&self) -> Result<usize> {
+        let pending = self.reputation_buffer.take();
+        let mut flushed = 0;
+        let mut first_error = None;
+        
+        for (member_id, entry) in pending {
+            let result = async {
+                let mut member = self.get_member_for_update(&member_id).await?;
+                reputation::apply_pending(&mut member, &entry);
+                self.save_member(&member, SYSTEM_ACTOR).await
+            }
+            .await;
+            
+            match result {
+                Ok(()) => flushed += 1,
+                Err(e) => {
+                    warn!("Failed to flush reputation for member {}: {}", member_id, e);
+                    self.reputation_buffer.restore(&member_id, entry);
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+        
+        match first_error {
+            Some(e) => Err(e),
+            None => Ok(flushed),
+        }
+    }
+    
+    /// Flush pending reputation changes periodically
+    ///
+    /// Returns `None` when batching is disabled. The task shares this
+    /// manager's buffer, so a final [`flush_reputation`](Self::flush_reputation)
+    /// on shutdown writes what the last tick missed. Must be called from
+    /// within a Tokio runtime.
+    pub fn spawn_reputation_flusher(&self) -> Option<JoinHandle<()>> {
+        let batching = &self.config.identity.reputation_batching;
+        if !batching.enabled || batching.flush_interval_seconds == 0 {
+            return None;
+        }
+        
+        let manager = self.clone();
+        let period = std::time::Duration::from_secs(batching.flush_interval_seconds);
+        Some(tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                
+                // Failed members stay pending and are retried on the next tick
+                let _ = manager.flush_reputation().await;
+            }
+        }))
+    }
+    
     /// Get member activities
     pub async fn get_member_activities(
         &self,
@@ -323,14 +702,20 @@ impl IdentityManager {
         Ok(activities)
     }
     
-    /// Get members by role
-    pub async fn get_members_by_role(&self, role: MemberRole) -> Result<Vec<Member>> {
+    /// Get active members by role
+    ///
+    /// Soft-deleted members are left out unless `include_deleted` is set.
+    pub async fn get_members_by_role(&self, role: MemberRole, include_deleted: bool) -> Result<Vec<Member>> {
         // Convert role to string for the query
         let role_str = serde_json::to_string(&role)
             .map_err(|e| DaoError::DatabaseError(format!("Failed to serialize role: {}", e)))?;
         
         // Construct the query
-        let query = "SELECT * FROM members WHERE data->>'role' = $1 AND data->>'status' = $2";
+        let query = if include_deleted {
+            "SELECT * FROM members WHERE data->>'role' = $1 AND data->>'status' = $2"
+        } else {
+            "SELECT * FROM members WHERE data->>'role' = $1 AND data->>'status' = $2 AND deleted_at IS NULL"
+        };
         let status_str = serde_json::to_string(&MemberStatus::Active)
             .map_err(|e| DaoError::DatabaseError(format!("Failed to serialize status: {}", e)))?;
         
@@ -338,45 +723,113 @@ impl IdentityManager {
         let rows = self.database.query(query, &[&role_str, &status_str]).await?;
         
         // Parse the members from the rows
-        let members = rows
+        let mut members = rows
             .into_iter()
             .map(|row| {
                 serde_json::from_value(row.get("data"))
                     .map_err(|e| DaoError::DatabaseError(format!("Failed to parse member: {}", e)))
             })
             .collect::<Result<Vec<Member>>>()?;
+        members.iter_mut().for_each(|member| self.reputation_buffer.apply_pending(member));
         
         Ok(members)
     }
     
-    /// Get active members
+    /// Get active members, leaving out soft-deleted ones
     pub async fn get_active_members(&self) -> Result<Vec<Member>> {
         // Convert status to string for the query
         let status_str = serde_json::to_string(&MemberStatus::Active)
             .map_err(|e| DaoError::DatabaseError(format!("Failed to serialize status: {}", e)))?;
         
         // Construct the query
-        let query = "SELECT * FROM members WHERE data->>'status' = $1";
+        let query = "SELECT * FROM members WHERE data->>'status' = $1 AND deleted_at IS NULL";
         
         // Load the members from the database
         let rows = self.database.query(query, &[&status_str]).await?;
         
         // Parse the members from the rows
-        let members = rows
+        let mut members = rows
             .into_iter()
             .map(|row| {
                 serde_json::from_value(row.get("data"))
                     .map_err(|e| DaoError::DatabaseError(format!("Failed to parse member: {}", e)))
             })
             .collect::<Result<Vec<Member>>>()?;
+        members.iter_mut().for_each(|member| self.reputation_buffer.apply_pending(member));
         
         Ok(members)
     }
     
+    /// Get a page of members, newest first
+    ///
+    /// Members joining while a client pages through the listing don't shift
+    /// later pages. Soft-deleted members are left out unless
+    /// `include_deleted` is set.
+    pub async fn get_members_page(&self, include_deleted: bool, request: &PageRequest) -> Result<Page<Member>> {
+        let mut page = Self::list_members_page(&self.database, include_deleted, request).await?;
+        page.items.iter_mut().for_each(|member| self.reputation_buffer.apply_pending(member));
+        
+        Ok(page)
+    }
+    
+    /// List a page of members using the given database handle
+    ///
+    /// A member's `created_at` column holds their `joined_at`. Portable: runs
+    /// on PostgreSQL and SQLite.
+    async fn list_members_page(database: &Database, include_deleted: bool, request: &PageRequest) -> Result<Page<Member>> {
+        let conditions = if include_deleted {
+            Vec::new()
+        } else {
+            vec!["deleted_at IS NULL".to_string()]
+        };
+        
+        fetch_page(database, "members", conditions, Vec::new(), request, |member: &Member| {
+            (member.joined_at, member.id.clone())
+        })
+        .await
+    }
+    
+    /// Soft-delete a member, hiding them from lookups, listings and search
+    ///
+    /// The row and its data are kept, with `actor` recorded as the last
+    /// writer. Pending reputation changes for the member are dropped.
+    pub async fn soft_delete_member(&self, id: &MemberId, actor: &str) -> Result<()> {
+        Self::soft_delete(&self.database.primary(), id, actor).await?;
+        self.reputation_buffer.discard(id);
+        
+        Ok(())
+    }
+    
+    /// Soft-delete a member using the given database handle
+    ///
+    /// Portable: runs on PostgreSQL and SQLite.
+    async fn soft_delete(database: &Database, id: &MemberId, actor: &str) -> Result<()> {
+        let deleted = database
+            .execute_any(
+                "UPDATE members SET deleted_at = $1, updated_by = $2 WHERE id = $3 AND deleted_at IS NULL",
+                &[Utc::now().timestamp().into(), actor.into(), id.as_str().into()],
+            )
+            .await?;
+        
+        if deleted == 0 {
+            return Err(DaoError::IdentityError(format!("Member not found: {}", id)));
+        }
+        
+        Ok(())
+    }
+    
     // Private methods
     
-    /// Save a member to the database
-    async fn save_member(&self, member: &Member) -> Result<()> {
+    /// Broadcast an event if an event sender is configured
+    fn emit_event(&self, event_type: EventType, data: serde_json::Value) {
+        if let Some(sender) = &self.event_sender {
+            // Sending only fails when there are no subscribers
+            let _ = sender.send(WebSocketManager::create_event(event_type, data));
+        }
+    }
+    
+    /// Save a member to the database, recording `actor` as its last writer
+    pub(crate) async fn save_member(&self, member: &Member, actor: &str) -> Result<()> {
         // Serialize the member
         let data = serde_json::to_value(member)
             .map_err(|e| DaoError::DatabaseError(format!("Failed to serialize member: {}", e)))?;
@@ -384,29 +837,30 @@ impl IdentityManager {
         // Check if the member already exists
         let exists = self
             .database
-            .query_opt("SELECT 1 FROM members WHERE id = $1", &[&member.id])
+            .query_opt_any("SELECT 1 FROM members WHERE id = $1", &[member.id.as_str().into()])
             .await?
             .is_some();
         
         if exists {
             // Update the member
             self.database
-                .execute(
-                    "UPDATE members SET data = $1, address = $2, updated_at = $3 WHERE id = $4",
-                    &[&data, &member.address, &Utc::now(), &member.id],
+                .execute_any(
+                    "UPDATE members SET data = $1, address = $2, updated_at = $3, updated_by = $4 WHERE id = $5",
+                    &[data.into(), member.address.as_str().into(), Utc::now().into(), actor.into(), member.id.as_str().into()],
                 )
                 .await?;
         } else {
             // Insert the member
             self.database
-                .execute(
-                    "INSERT INTO members (id, address, data, created_at, updated_at) VALUES ($1, $2, $3, $4, $5)",
+                .execute_any(
+                    "INSERT INTO members (id, address, data, created_at, updated_at, updated_by) VALUES ($1, $2, $3, $4, $5, $6)",
                     &[
-                        &member.id,
-                        &member.address,
-                        &data,
-                        &member.joined_at,
-                        &Utc::now(),
+                        member.id.as_str().into(),
+                        member.address.as_str().into(),
+                        data.into(),
+                        member.joined_at.into(),
+                        Utc::now().into(),
+                        actor.into(),
                     ],
                 )
                 .await?;
@@ -437,4 +891,244 @@ impl IdentityManager {
         
         Ok(())
     }
-} 
\ No newline at end of file
+} 
+
+/// A newly registered, active member
+fn new_member(address: &Address, name: Option<String>, role: MemberRole) -> Member {
+    Member {
+        id: Uuid::new_v4().to_string(),
+        address: address.to_string(),
+        name,
+        role,
+        status: MemberStatus::Active,
+        reputation: 0,
+        joined_at: Utc::now(),
+        last_active_at: Utc::now(),
+        role_changed_at: None,
+        metadata: serde_json::Value::Null,
+    }
+}
+
+/// Determine the role a member should be auto-promoted to, if any
+fn promotion_target(
+    member: &Member,
+    config: &PromotionConfig,
+    now: DateTime<Utc>,
+) -> Option<MemberRole> {
+    if !config.enabled || member.status != MemberStatus::Active {
+        return None;
+    }
+    
+    // Don't change roles again while the cooldown is running
+    if let Some(changed_at) = member.role_changed_at {
+        if now - changed_at < Duration::hours(config.cooldown_hours as i64) {
+            return None;
+        }
+    }
+    
+    let target = member.role.next()?;
+    
+    // Admin is never granted automatically
+    let max_role = if config.max_auto_role.rank() >= MemberRole::Admin.rank() {
+        MemberRole::Council
+    } else {
+        config.max_auto_role
+    };
+    if target.rank() > max_role.rank() {
+        return None;
+    }
+    
+    let threshold = match target {
+        MemberRole::Delegate => config.delegate_reputation,
+        MemberRole::Council => config.council_reputation,
+        MemberRole::Member | MemberRole::Admin => return None,
+    };
+    
+    if member.reputation >= threshold {
+        Some(target)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn member(role: MemberRole, reputation: u64) -> Member {
+        Member {
+            id: Uuid::new_v4().to_string(),
+            address: "0x0000000000000000000000000000000000000001".to_string(),
+            name: None,
+            role,
+            status: MemberStatus::Active,
+            reputation,
+            joined_at: Utc::now(),
+            last_active_at: Utc::now(),
+            role_changed_at: None,
+            metadata: serde_json::Value::Null,
+        }
+    }
+    
+    async fn insert_member(database: &Database, member: &Member) {
+        database
+            .execute_any(
+                "INSERT INTO members (id, address, data) VALUES ($1, $2, $3)",
+                &[
+                    member.id.as_str().into(),
+                    member.address.as_str().into(),
+                    serde_json::to_value(member).unwrap().into(),
+                ],
+            )
+            .await
+            .unwrap();
+    }
+    
+    #[tokio::test]
+    async fn test_role_changes_are_chained_in_audit_log() {
+        use crate::blockchain::MockBlockchain;
+        use crate::security::first_broken_link;
+        
+        let config = crate::config::default();
+        let database = Database::migrated_sqlite().await;
+        let audit_log = Arc::new(AuditLog::new(database.clone(), &config.security.audit_shipping));
+        let manager = IdentityManager::new(
+            &config,
+            BlockchainAdapter::with_mock(&config.blockchain, Arc::new(MockBlockchain::new())),
+            database.clone(),
+        )
+        .unwrap()
+        .with_audit_log(audit_log.clone());
+        
+        let target = member(MemberRole::Member, 0);
+        insert_member(&database, &target).await;
+        
+        manager.update_member_role(&target.id, MemberRole::Council, "0xAdmin").await.unwrap();
+        manager.update_member_role(&target.id, MemberRole::Member, "0xAdmin").await.unwrap();
+        
+        let entries = audit_log.entries(1).await.unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(entries.iter().all(|entry| entry.action == AdminAction::RoleChange && entry.actor == "0xAdmin"));
+        assert_eq!(entries[0].details["role"], serde_json::json!(MemberRole::Council));
+        assert_eq!(first_broken_link(&entries), None);
+    }
+    
+    #[tokio::test]
+    async fn test_search_members_by_name_and_address() {
+        let database = Database::migrated_sqlite().await;
+        
+        for (address, name) in [
+            ("0x00000000000000000000000000000000000000a3", Some("Malice")),
+            ("0x00000000000000000000000000000000000000a2", Some("Alicia")),
+            ("0x00000000000000000000000000000000000000a1", Some("Alice")),
+            ("0x00000000000000000000000000000000000000b1", None),
+        ] {
+            let mut member = member(MemberRole::Member, 0);
+            member.address = address.to_string();
+            member.name = name.map(str::to_string);
+            insert_member(&database, &member).await;
+        }
+        
+        let names = |members: Vec<Member>| -> Vec<Option<String>> { members.into_iter().map(|member| member.name).collect() };
+        
+        // Exact name, then prefix matches, then other matches
+        let found = IdentityManager::find_members(&database, "ALICE", 10).await.unwrap();
+        assert_eq!(names(found), [Some("Alice".to_string()), Some("Malice".to_string())]);
+        let found = IdentityManager::find_members(&database, "ali", 10).await.unwrap();
+        assert_eq!(
+            names(found),
+            [Some("Alice".to_string()), Some("Alicia".to_string()), Some("Malice".to_string())]
+        );
+        
+        // Addresses match too, and an exact address comes first
+        let found = IdentityManager::find_members(&database, "0x00000000000000000000000000000000000000B1", 10)
+            .await
+            .unwrap();
+        assert_eq!(names(found), [None]);
+        assert_eq!(IdentityManager::find_members(&database, "00000000a", 2).await.unwrap().len(), 2);
+        
+        assert!(IdentityManager::find_members(&database, "bob", 10).await.unwrap().is_empty());
+        assert!(IdentityManager::find_members(&database, "_", 10).await.unwrap().is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_soft_deleted_member_hidden() {
+        let database = Database::migrated_sqlite().await;
+        let mut alice = member(MemberRole::Member, 0);
+        alice.name = Some("Alice".to_string());
+        insert_member(&database, &alice).await;
+        
+        IdentityManager::soft_delete(&database, &alice.id, "0xAdmin").await.unwrap();
+        
+        assert!(matches!(
+            IdentityManager::load_member(&database, &alice.id).await,
+            Err(DaoError::IdentityError(_))
+        ));
+        assert!(IdentityManager::find_members(&database, "alice", 10).await.unwrap().is_empty());
+        
+        // The row is kept, with the deleting actor recorded
+        let row = database
+            .query_one_any("SELECT updated_by FROM members WHERE id = $1", &[alice.id.as_str().into()])
+            .await
+            .unwrap();
+        assert_eq!(row.text("updated_by").unwrap(), "0xAdmin");
+        assert!(IdentityManager::soft_delete(&database, &alice.id, "0xAdmin").await.is_err());
+        
+        // The deleted member's address can register again, but only once
+        let returning = member(MemberRole::Member, 0);
+        insert_member(&database, &returning).await;
+        let duplicate = member(MemberRole::Member, 0);
+        assert!(database
+            .execute_any(
+                "INSERT INTO members (id, address, data) VALUES ($1, $2, '{}')",
+                &[duplicate.id.as_str().into(), duplicate.address.as_str().into()],
+            )
+            .await
+            .is_err());
+    }
+    
+    fn promotion_config() -> PromotionConfig {
+        PromotionConfig {
+            enabled: true,
+            delegate_reputation: 100,
+            council_reputation: 500,
+            max_auto_role: MemberRole::Council,
+            cooldown_hours: 24,
+        }
+    }
+    
+    #[test]
+    fn test_member_crossing_delegate_threshold_is_promoted() {
+        let config = promotion_config();
+        let now = Utc::now();
+        
+        assert_eq!(
+            promotion_target(&member(MemberRole::Member, 100), &config, now),
+            Some(MemberRole::Delegate)
+        );
+        assert_eq!(promotion_target(&member(MemberRole::Member, 99), &config, now), None);
+    }
+    
+    #[test]
+    fn test_promotion_respects_cooldown_and_max_role() {
+        let mut config = promotion_config();
+        let now = Utc::now();
+        
+        let mut delegate = member(MemberRole::Delegate, 1_000);
+        delegate.role_changed_at = Some(now - Duration::hours(1));
+        assert_eq!(promotion_target(&delegate, &config, now), None);
+        
+        delegate.role_changed_at = Some(now - Duration::hours(48));
+        assert_eq!(promotion_target(&delegate, &config, now), Some(MemberRole::Council));
+        
+        config.max_auto_role = MemberRole::Delegate;
+        assert_eq!(promotion_target(&delegate, &config, now), None);
+        
+        // Admin is never granted, even if configured
+        config.max_auto_role = MemberRole::Admin;
+        assert_eq!(promotion_target(&member(MemberRole::Council, u64::MAX), &config, now), None);
+        
+        config.enabled = false;
+        assert_eq!(promotion_target(&member(MemberRole::Member, 1_000), &config, now), None);
+    }
+}
@@ -0,0 +1,141 @@
+//! Batched reputation updates
+//!
+//! With batching enabled, reputation changes from recorded activities are
+//! held in memory and written in one member update per flush instead of one
+//! per activity. Reads apply the pending deltas, so members always see their
+//! current reputation.
+
+use super::{Member, MemberId};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Reputation change not yet written to the database
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct PendingReputation {
+    /// Sum of the pending changes
+    pub delta: i64,
+    /// Time of the latest activity
+    pub last_active_at: DateTime<Utc>,
+}
+
+/// In-memory reputation deltas waiting to be flushed
+#[derive(Debug, Default)]
+pub(crate) struct ReputationBuffer {
+    pending: Mutex<HashMap<MemberId, PendingReputation>>,
+}
+
+impl ReputationBuffer {
+    /// Add a reputation change for a member
+    ///
+    /// Returns the number of members with pending changes.
+    pub fn add(&self, member_id: &MemberId, delta: i64, at: DateTime<Utc>) -> usize {
+        let mut pending = self.pending.lock().unwrap();
+        pending
+            .entry(member_id.clone())
+            .and_modify(|entry| {
+                entry.delta = entry.delta.saturating_add(delta);
+                entry.last_active_at = entry.last_active_at.max(at);
+            })
+            .or_insert(PendingReputation {
+                delta,
+                last_active_at: at,
+            });
+
+        pending.len()
+    }
+
+    /// Apply a member's pending changes to a copy read from the database
+    pub fn apply_pending(&self, member: &mut Member) {
+        if let Some(entry) = self.pending.lock().unwrap().get(&member.id) {
+            apply_pending(member, entry);
+        }
+    }
+
+    /// Take every pending change, leaving the buffer empty
+    pub fn take(&self) -> HashMap<MemberId, PendingReputation> {
+        std::mem::take(&mut *self.pending.lock().unwrap())
+    }
+
+    /// Drop a member's pending change once it has been written another way
+    pub fn discard(&self, member_id: &MemberId) {
+        self.pending.lock().unwrap().remove(member_id);
+    }
+
+    /// Put back a change that could not be flushed
+    pub fn restore(&self, member_id: &MemberId, entry: PendingReputation) {
+        self.add(member_id, entry.delta, entry.last_active_at);
+    }
+}
+
+/// Apply a pending change to a member
+pub(crate) fn apply_pending(member: &mut Member, entry: &PendingReputation) {
+    member.reputation = apply_reputation_delta(member.reputation, entry.delta);
+    member.last_active_at = member.last_active_at.max(entry.last_active_at);
+}
+
+/// Apply a reputation change, never going below zero
+///
+/// Batched changes are summed before this is applied, so a loss followed by
+/// a gain in the same batch is not clamped in between.
+pub(crate) fn apply_reputation_delta(reputation: u64, delta: i64) -> u64 {
+    if delta < 0 {
+        reputation.saturating_sub(delta.unsigned_abs())
+    } else {
+        reputation.saturating_add(delta as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::identity::{MemberRole, MemberStatus};
+
+    fn member(id: &str, reputation: u64) -> Member {
+        Member {
+            id: id.to_string(),
+            address: "0xMember".to_string(),
+            name: None,
+            role: MemberRole::Member,
+            status: MemberStatus::Active,
+            reputation,
+            joined_at: Utc::now(),
+            last_active_at: Utc::now(),
+            role_changed_at: None,
+            metadata: serde_json::Value::Null,
+        }
+    }
+
+    #[test]
+    fn test_batched_deltas_sum_and_reads_see_pending_total() {
+        let buffer = ReputationBuffer::default();
+        let now = Utc::now();
+        let alice = "alice".to_string();
+
+        buffer.add(&alice, 10, now);
+        buffer.add(&alice, 5, now);
+        assert_eq!(buffer.add(&alice, -3, now), 1);
+        assert_eq!(buffer.add(&"bob".to_string(), 7, now), 2);
+
+        // A read between flushes sees stored reputation plus the pending total
+        let mut stored = member("alice", 100);
+        buffer.apply_pending(&mut stored);
+        assert_eq!(stored.reputation, 112);
+
+        let flushed = buffer.take();
+        assert_eq!(flushed[&alice].delta, 12);
+        assert_eq!(flushed[&"bob".to_string()].delta, 7);
+
+        // After the flush nothing is pending
+        let mut stored = member("alice", 112);
+        buffer.apply_pending(&mut stored);
+        assert_eq!(stored.reputation, 112);
+    }
+
+    #[test]
+    fn test_reputation_never_negative() {
+        assert_eq!(apply_reputation_delta(5, -10), 0);
+        assert_eq!(apply_reputation_delta(5, 10), 15);
+        assert_eq!(apply_reputation_delta(u64::MAX, 1), u64::MAX);
+    }
+}
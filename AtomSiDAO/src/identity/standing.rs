@@ -0,0 +1,135 @@
+//! Member standing checks
+//!
+//! Suspended and inactive members may not submit proposals, vote, or create
+//! or approve treasury transactions. Addresses without a member record are
+//! allowed or refused per `identity.allow_unregistered_voters`; soft-deleted
+//! members count as unregistered.
+
+use super::{Member, MemberStatus};
+use crate::core::{Database, DaoError, Result};
+
+/// Check that an actor may take part in governance and treasury actions
+pub(crate) fn check_standing(member: Option<&Member>, allow_unregistered: bool) -> Result<()> {
+    match member {
+        Some(member) if member.status == MemberStatus::Active => Ok(()),
+        Some(_) => Err(DaoError::Unauthorized),
+        None if allow_unregistered => Ok(()),
+        None => Err(DaoError::Unauthorized),
+    }
+}
+
+/// Resolve the member record for `address` and check its standing
+///
+/// Addresses match regardless of case, so a member can't pass as
+/// unregistered by changing the case of their address.
+///
+/// Portable: runs on PostgreSQL and SQLite.
+pub(crate) async fn require_standing(database: &Database, address: &str, allow_unregistered: bool) -> Result<()> {
+    let row = database
+        .query_opt_any(
+            "SELECT data FROM members WHERE LOWER(address) = LOWER($1) AND deleted_at IS NULL",
+            &[address.into()],
+        )
+        .await?;
+
+    let member: Option<Member> = row
+        .map(|row| {
+            row.json("data")
+                .map_err(|e| DaoError::DatabaseError(format!("Failed to parse member: {}", e)))
+        })
+        .transpose()?;
+
+    check_standing(member.as_ref(), allow_unregistered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::identity::MemberRole;
+    use chrono::Utc;
+
+    const ADDRESS: &str = "0x0000000000000000000000000000000000000001";
+    const CHECKSUMMED: &str = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266";
+
+    async fn set_status(database: &Database, member: &mut Member, status: MemberStatus) {
+        member.status = status;
+        database
+            .execute_any(
+                "UPDATE members SET data = $1 WHERE id = $2",
+                &[serde_json::to_value(&*member).unwrap().into(), member.id.as_str().into()],
+            )
+            .await
+            .unwrap();
+    }
+
+    /// Register an active member at `address`
+    async fn insert_member(database: &Database, address: &str) -> Member {
+        let member = Member {
+            id: "member-1".to_string(),
+            address: address.to_string(),
+            name: None,
+            role: MemberRole::Member,
+            status: MemberStatus::Active,
+            reputation: 0,
+            joined_at: Utc::now(),
+            last_active_at: Utc::now(),
+            role_changed_at: None,
+            metadata: serde_json::Value::Null,
+        };
+        database
+            .execute_any(
+                "INSERT INTO members (id, address, data) VALUES ($1, $2, $3)",
+                &[
+                    member.id.as_str().into(),
+                    address.into(),
+                    serde_json::to_value(&member).unwrap().into(),
+                ],
+            )
+            .await
+            .unwrap();
+
+        member
+    }
+
+    #[tokio::test]
+    async fn test_suspended_member_refused() {
+        let database = Database::migrated_sqlite().await;
+        let mut member = insert_member(&database, ADDRESS).await;
+
+        assert!(require_standing(&database, ADDRESS, false).await.is_ok());
+
+        // Submitting, voting and treasury actions all go through this check
+        for status in [MemberStatus::Suspended, MemberStatus::Inactive] {
+            set_status(&database, &mut member, status).await;
+            assert!(matches!(
+                require_standing(&database, ADDRESS, true).await,
+                Err(DaoError::Unauthorized)
+            ));
+        }
+
+        set_status(&database, &mut member, MemberStatus::Active).await;
+        assert!(require_standing(&database, ADDRESS, false).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_suspended_member_refused_under_any_case() {
+        let database = Database::migrated_sqlite().await;
+        let mut member = insert_member(&database, CHECKSUMMED).await;
+        set_status(&database, &mut member, MemberStatus::Suspended).await;
+
+        // Not treated as an unregistered voter under another spelling
+        let uppercase = format!("0x{}", CHECKSUMMED[2..].to_uppercase());
+        for address in [CHECKSUMMED.to_string(), CHECKSUMMED.to_lowercase(), uppercase] {
+            assert!(matches!(
+                require_standing(&database, &address, true).await,
+                Err(DaoError::Unauthorized)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_unregistered_addresses_follow_config() {
+        assert!(check_standing(None, true).is_ok());
+        assert!(matches!(check_standing(None, false), Err(DaoError::Unauthorized)));
+    }
+}
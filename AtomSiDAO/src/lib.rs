@@ -10,6 +10,7 @@
 //! - `config`: Configuration management
 //! - `crypto`: Cryptographic utilities
 //! - `database`: Database connection and management
+//! - `doctor`: Pre-flight configuration and connectivity checks
 //! - `error`: Error types and handling
 //! - `governance`: Governance mechanisms and voting
 //! - `identity`: Member identity and reputation
@@ -56,6 +57,7 @@ pub mod blockchain;
 pub mod config;
 pub mod crypto;
 pub mod database;
+pub mod doctor;
 pub mod error;
 pub mod governance;
 pub mod identity;
@@ -85,11 +87,15 @@ pub async fn init(config_path: &str) -> Result<DAOContext> {
     // Initialize blockchain adapter
     let blockchain = blockchain::BlockchainAdapter::new(&config.blockchain)?;
     
+    // Channel the managers broadcast their events on
+    let (event_sender, _) = tokio::sync::broadcast::channel(api::websocket::MAX_BROADCAST_BUFFER);
+    
     // Create and return the DAO context
     Ok(DAOContext {
         config_manager,
         db_manager,
         blockchain,
+        event_sender,
     })
 }
 
@@ -98,6 +104,8 @@ pub struct DAOContext {
     pub config_manager: config::ConfigManager,
     pub db_manager: database::DatabaseManager,
     pub blockchain: blockchain::BlockchainAdapter,
+    /// Events broadcast by the managers, relayed to WebSocket clients
+    pub event_sender: tokio::sync::broadcast::Sender<api::websocket::WebSocketEvent>,
 }
 
 impl DAOContext {
@@ -115,6 +123,7 @@ impl DAOContext {
             self.db_manager.clone(),
             self.blockchain.clone(),
         )
+        .with_event_sender(self.event_sender.clone())
     }
     
     /// Create a new treasury manager
@@ -123,6 +132,7 @@ impl DAOContext {
             self.db_manager.clone(),
             self.blockchain.clone(),
         )
+        .with_event_sender(self.event_sender.clone())
     }
     
     /// Create a new identity manager
@@ -130,6 +140,7 @@ impl DAOContext {
         identity::IdentityManager::new(
             self.db_manager.clone(),
         )
+        .with_event_sender(self.event_sender.clone())
     }
     
     /// Create a new token manager
@@ -150,8 +161,20 @@ pub async fn shutdown(context: DAOContext) -> Result<()> {
 }
 
 /// Initialize logging for the AtomSi DAO framework
-pub fn init_logging() {
-    env_logger::init();
+///
+/// Lines are written in `config.format`, and `RUST_LOG` overrides
+/// `config.level` when set. Records from the `log` crate are included. In
+/// JSON, each line carries the fields of the spans it was logged in, such as
+/// the API's `request_id`.
+pub fn init_logging(config: &config::LoggingConfig) {
+    let filter = tracing_subscriber::EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new(&config.level));
+    let subscriber = tracing_subscriber::fmt().with_env_filter(filter);
+    
+    match config.format {
+        config::LogFormat::Text => subscriber.init(),
+        config::LogFormat::Json => subscriber.json().with_current_span(true).with_span_list(true).init(),
+    }
 }
 
 #[cfg(test)]
@@ -1,26 +1,34 @@
 use atomsi_dao::{config, core::Dao, init_logging};
 use log::{info, warn};
 use std::process;
+use tokio::sync::watch;
+
+/// Configuration file used when none is given on the command line
+const DEFAULT_CONFIG_PATH: &str = "config.toml";
 
 #[tokio::main]
 async fn main() {
+    // Load configuration, which selects the log format, so the outcome is
+    // only logged once logging is initialized
+    let config_path = std::env::args().nth(1).unwrap_or_else(|| DEFAULT_CONFIG_PATH.to_string());
+    let loaded = config::ConfigManager::new(&config_path);
+    let config_manager = loaded
+        .as_ref()
+        .cloned()
+        .unwrap_or_else(|_| config::ConfigManager::with_defaults(&config_path));
+    let config = config_manager.get_config();
+    
     // Initialize logging
-    init_logging();
+    init_logging(&config.logging);
     
     info!("Starting AtomSi DAO v{}", atomsi_dao::VERSION);
-    
-    // Load configuration
-    let config = match config::load() {
-        Ok(cfg) => {
-            info!("Configuration loaded successfully");
-            cfg
-        }
+    match &loaded {
+        Ok(_) => info!("Configuration loaded successfully"),
         Err(e) => {
             warn!("Failed to load configuration: {}", e);
             warn!("Using default configuration");
-            config::default()
         }
-    };
+    }
     
     // Initialize blockchain connection
     let blockchain = match init_blockchain(&config).await {
@@ -46,11 +54,22 @@ async fn main() {
         }
     };
     
+    // Reload the configuration when its file changes; proposals read
+    // governance settings from the manager, so they follow each reload
+    let _config_listener = spawn_config_listener(config_manager.subscribe(), config.clone());
+    let _config_watcher = match config_manager.watch() {
+        Ok(watcher) => Some(watcher),
+        Err(e) => {
+            warn!("Configuration changes will not be reloaded: {}", e);
+            None
+        }
+    };
+    
     // Initialize the DAO
     let dao = match Dao::new(config, blockchain, db) {
         Ok(dao) => {
             info!("DAO initialized successfully");
-            dao
+            dao.with_config_manager(config_manager)
         }
         Err(e) => {
             eprintln!("ERROR: Failed to initialize DAO: {}", e);
@@ -58,13 +77,48 @@ async fn main() {
         }
     };
     
-    // Start the API server
-    if let Err(e) = run_api_server(&dao).await {
+    // Settle on-chain treasury executions and expire stale transactions
+    let _treasury_maintenance = dao.spawn_treasury_maintenance();
+    
+    // Write batched reputation changes periodically
+    let _reputation_flusher = dao.spawn_reputation_flusher();
+    
+    // Run the API server until it fails or the process is interrupted
+    let result = tokio::select! {
+        result = run_api_server(&dao) => result,
+        _ = tokio::signal::ctrl_c() => {
+            info!("Shutting down");
+            Ok(())
+        }
+    };
+    
+    if let Err(e) = dao.shutdown().await {
+        warn!("Failed to shut down cleanly: {}", e);
+    }
+    
+    if let Err(e) = result {
         eprintln!("ERROR: API server failed: {}", e);
         process::exit(1);
     }
 }
 
+/// Log each reloaded configuration, warning when it changes settings that
+/// only take effect on restart
+fn spawn_config_listener(mut changes: watch::Receiver<config::Config>, started: config::Config) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        while changes.changed().await.is_ok() {
+            let config = changes.borrow_and_update().clone();
+            info!("Configuration reloaded");
+            
+            let api_changed = serde_json::to_value(&config.api).ok() != serde_json::to_value(&started.api).ok();
+            let database_changed = serde_json::to_value(&config.database).ok() != serde_json::to_value(&started.database).ok();
+            if api_changed || database_changed {
+                warn!("API and database settings changed; restart to apply them");
+            }
+        }
+    })
+}
+
 async fn init_blockchain(config: &config::Config) -> Result<impl atomsi_dao::blockchain::BlockchainAdapter, String> {
     // This is a placeholder - in a real implementation, we would initialize the
     // appropriate blockchain adapter based on the configuration
@@ -0,0 +1,288 @@
+//! Proposal attachments
+//!
+//! Attachments reference documents too large for the description. Each one
+//! carries the SHA-256 of its content, so the reference is tamper-evident;
+//! `ipfs://` content can optionally be re-hashed through a gateway on submit.
+
+use super::types::Attachment;
+use crate::config::AttachmentConfig;
+use crate::core::{DaoError, Result};
+use sha2::{Digest, Sha256};
+use std::future::Future;
+use std::time::Duration;
+
+/// Length of a hex-encoded SHA-256 digest
+const CONTENT_HASH_HEX_LEN: usize = 64;
+
+/// Check that attachments are well-formed and within the configured limit
+pub(crate) fn validate_attachments(attachments: &[Attachment], config: &AttachmentConfig) -> Result<()> {
+    if attachments.len() > config.max_attachments {
+        return Err(DaoError::InvalidParameter(format!(
+            "A proposal can have at most {} attachments",
+            config.max_attachments
+        )));
+    }
+
+    attachments.iter().try_for_each(validate_attachment)
+}
+
+/// Check a single attachment's URI, content hash and mime type
+fn validate_attachment(attachment: &Attachment) -> Result<()> {
+    let invalid = |reason: &str| {
+        DaoError::InvalidParameter(format!("Invalid attachment '{}': {}", attachment.uri, reason))
+    };
+
+    let (scheme, rest) = attachment
+        .uri
+        .split_once("://")
+        .ok_or_else(|| invalid("URI must start with http://, https:// or ipfs://"))?;
+
+    if rest.is_empty() || rest.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err(invalid("URI is malformed"));
+    }
+
+    match scheme {
+        "http" | "https" => {
+            let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+            if host.is_empty() {
+                return Err(invalid("URI has no host"));
+            }
+        }
+        "ipfs" => {
+            let cid = ipfs_cid(rest);
+            if cid.is_empty() || !cid.chars().all(|c| c.is_ascii_alphanumeric()) {
+                return Err(invalid("IPFS URI has no valid CID"));
+            }
+        }
+        _ => return Err(invalid("URI must start with http://, https:// or ipfs://")),
+    }
+
+    let hash = normalized_hash(&attachment.content_hash);
+    if hash.len() != CONTENT_HASH_HEX_LEN || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(invalid("content hash must be a hex-encoded SHA-256 digest"));
+    }
+
+    let valid_mime = attachment
+        .mime_type
+        .split_once('/')
+        .map_or(false, |(kind, subtype)| !kind.is_empty() && !subtype.is_empty() && !subtype.contains('/'));
+    if !valid_mime {
+        return Err(invalid("mime type must look like 'type/subtype'"));
+    }
+
+    Ok(())
+}
+
+/// Re-hash `ipfs://` attachments through the configured gateway
+///
+/// `fetch` downloads a gateway URL. Other attachments are not fetched.
+pub(crate) async fn verify_ipfs_attachments<F, Fut>(
+    attachments: &[Attachment],
+    config: &AttachmentConfig,
+    fetch: F,
+) -> Result<()>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<Vec<u8>>>,
+{
+    if !config.verify_ipfs_content {
+        return Ok(());
+    }
+
+    for attachment in attachments {
+        let Some(path) = attachment.uri.strip_prefix("ipfs://") else {
+            continue;
+        };
+
+        let url = format!("{}/{}", config.ipfs_gateway_url.trim_end_matches('/'), path);
+        let content = fetch(url).await?;
+        let actual = hex::encode(Sha256::digest(&content));
+
+        if actual != normalized_hash(&attachment.content_hash) {
+            return Err(DaoError::InvalidParameter(format!(
+                "Content of attachment '{}' does not match its hash (expected {}, got {})",
+                attachment.uri, attachment.content_hash, actual
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Download attachment content from an IPFS gateway
+///
+/// Fails after `fetch_timeout_seconds`, and as soon as the content is known
+/// to exceed `max_content_bytes`, so a slow or oversized response can't
+/// hold up or exhaust the node.
+pub(crate) async fn fetch_from_gateway(url: String, config: &AttachmentConfig) -> Result<Vec<u8>> {
+    let gateway_error = |e: reqwest::Error| {
+        DaoError::ExternalServiceError(format!("Failed to fetch attachment from {}: {}", url, e))
+    };
+    let too_large = || {
+        DaoError::InvalidParameter(format!(
+            "Attachment at {} is larger than {} bytes",
+            url, config.max_content_bytes
+        ))
+    };
+
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(config.fetch_timeout_seconds))
+        .build()
+        .map_err(gateway_error)?;
+    let mut response = client
+        .get(&url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(gateway_error)?;
+
+    if response.content_length().map_or(false, |length| length > config.max_content_bytes) {
+        return Err(too_large());
+    }
+
+    // The declared length may be missing or wrong, so count as we read
+    let mut content = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(gateway_error)? {
+        if (content.len() + chunk.len()) as u64 > config.max_content_bytes {
+            return Err(too_large());
+        }
+        content.extend_from_slice(&chunk);
+    }
+
+    Ok(content)
+}
+
+/// CID part of an IPFS URI path
+fn ipfs_cid(path: &str) -> &str {
+    path.split('/').next().unwrap_or_default()
+}
+
+/// Content hash without an optional `0x` prefix, lowercased
+fn normalized_hash(hash: &str) -> String {
+    hash.strip_prefix("0x").unwrap_or(hash).to_ascii_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOCUMENT: &[u8] = b"Grant program budget breakdown";
+
+    fn attachment(uri: &str, content: &[u8]) -> Attachment {
+        Attachment {
+            uri: uri.to_string(),
+            content_hash: hex::encode(Sha256::digest(content)),
+            mime_type: "application/pdf".to_string(),
+        }
+    }
+
+    fn verifying_config() -> AttachmentConfig {
+        AttachmentConfig {
+            verify_ipfs_content: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_malformed_attachments_rejected() {
+        let config = AttachmentConfig::default();
+        let valid = attachment("ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi/budget.pdf", DOCUMENT);
+        assert!(validate_attachments(&[valid.clone()], &config).is_ok());
+        assert!(validate_attachments(&[attachment("https://example.org/budget.pdf", DOCUMENT)], &config).is_ok());
+
+        let mut short_hash = valid.clone();
+        short_hash.content_hash = "abc123".to_string();
+        let mut bad_mime = valid.clone();
+        bad_mime.mime_type = "pdf".to_string();
+
+        for invalid in [
+            attachment("ftp://example.org/budget.pdf", DOCUMENT),
+            attachment("https:///budget.pdf", DOCUMENT),
+            attachment("ipfs://not a cid", DOCUMENT),
+            short_hash,
+            bad_mime,
+        ] {
+            assert!(validate_attachments(&[invalid], &config).is_err());
+        }
+
+        let too_many = vec![valid; config.max_attachments + 1];
+        assert!(validate_attachments(&too_many, &config).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_mismatched_ipfs_hash_rejected() {
+        let uri = "ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi";
+        let gateway = |url: String| async move {
+            assert!(url.ends_with("/bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi"));
+            Ok(DOCUMENT.to_vec())
+        };
+
+        let matching = [attachment(uri, DOCUMENT)];
+        assert!(verify_ipfs_attachments(&matching, &verifying_config(), gateway).await.is_ok());
+
+        let tampered = [attachment(uri, b"A different budget")];
+        let error = verify_ipfs_attachments(&tampered, &verifying_config(), gateway)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("does not match its hash"));
+
+        // Nothing is fetched unless verification is enabled
+        let disabled = AttachmentConfig::default();
+        assert!(verify_ipfs_attachments(&tampered, &disabled, |_| async { panic!("fetched") })
+            .await
+            .is_ok());
+    }
+
+    /// Serve one connection with `response`, or hold it open without
+    /// answering if there is none
+    async fn gateway(response: Option<Vec<u8>>) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/ipfs/cid", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = [0; 1024];
+            let _ = socket.read(&mut request).await;
+            match response {
+                Some(response) => socket.write_all(&response).await.unwrap(),
+                None => tokio::time::sleep(Duration::from_secs(30)).await,
+            }
+        });
+        url
+    }
+
+    #[tokio::test]
+    async fn test_gateway_fetch_limits_size_and_time() {
+        let config = AttachmentConfig {
+            fetch_timeout_seconds: 1,
+            max_content_bytes: 16,
+            ..Default::default()
+        };
+        let respond = |body: &[u8], declared_length: bool| {
+            let mut response = b"HTTP/1.1 200 OK\r\nConnection: close\r\n".to_vec();
+            if declared_length {
+                response.extend_from_slice(format!("Content-Length: {}\r\n", body.len()).as_bytes());
+            }
+            response.extend_from_slice(b"\r\n");
+            response.extend_from_slice(body);
+            Some(response)
+        };
+
+        let url = gateway(respond(b"small", true)).await;
+        assert_eq!(fetch_from_gateway(url, &config).await.unwrap(), b"small");
+
+        // Too large by its declared length, or once read without one
+        for declared_length in [true, false] {
+            let url = gateway(respond(&[b'x'; 64], declared_length)).await;
+            let error = fetch_from_gateway(url, &config).await.unwrap_err();
+            assert!(error.to_string().contains("larger than 16 bytes"), "{}", error);
+        }
+
+        let url = gateway(None).await;
+        assert!(matches!(
+            fetch_from_gateway(url, &config).await,
+            Err(DaoError::ExternalServiceError(_))
+        ));
+    }
+}
@@ -0,0 +1,217 @@
+//! In-memory cache for finalized proposals
+//!
+//! Proposals in a terminal state never change, so they can be served without
+//! re-reading and re-parsing the stored JSON. Active proposals are never
+//! cached; an entry is only dropped when the proposal is saved again.
+
+use super::types::{Proposal, ProposalId};
+use crate::core::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// Hit/miss counters for the proposal cache
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheStats {
+    /// Reads served from the cache
+    pub hits: u64,
+    /// Reads that went to the database
+    pub misses: u64,
+    /// Proposals currently cached
+    pub entries: usize,
+    /// Maximum number of cached proposals
+    pub capacity: usize,
+}
+
+/// LRU cache of proposals in a terminal state
+pub struct ProposalCache {
+    capacity: usize,
+    inner: Mutex<Lru>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+/// Entries plus their recency order
+#[derive(Default)]
+struct Lru {
+    entries: HashMap<ProposalId, (Proposal, u64)>,
+    order: BTreeMap<u64, ProposalId>,
+    tick: u64,
+}
+
+impl ProposalCache {
+    /// Create a cache holding up to `capacity` proposals (0 disables it)
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            inner: Mutex::new(Lru::default()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Return the cached proposal, or load it and cache it if it is finalized
+    pub async fn get_or_load<F, Fut>(&self, id: &ProposalId, load: F) -> Result<Proposal>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Proposal>>,
+    {
+        if let Some(proposal) = self.get(id) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(proposal);
+        }
+
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let proposal = load().await?;
+        self.insert(&proposal);
+
+        Ok(proposal)
+    }
+
+    /// Drop a proposal from the cache
+    pub fn invalidate(&self, id: &ProposalId) {
+        let mut lru = self.inner.lock().unwrap();
+        if let Some((_, tick)) = lru.entries.remove(id) {
+            lru.order.remove(&tick);
+        }
+    }
+
+    /// Get the cache counters
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.inner.lock().unwrap().entries.len(),
+            capacity: self.capacity,
+        }
+    }
+
+    /// Look up a proposal, marking it as recently used
+    fn get(&self, id: &ProposalId) -> Option<Proposal> {
+        let mut lru = self.inner.lock().unwrap();
+        lru.tick += 1;
+        let tick = lru.tick;
+
+        let (proposal, old_tick) = {
+            let entry = lru.entries.get_mut(id)?;
+            let old_tick = std::mem::replace(&mut entry.1, tick);
+            (entry.0.clone(), old_tick)
+        };
+
+        lru.order.remove(&old_tick);
+        lru.order.insert(tick, id.clone());
+
+        Some(proposal)
+    }
+
+    /// Cache a proposal if it is in a terminal state
+    fn insert(&self, proposal: &Proposal) {
+        if self.capacity == 0 || !proposal.state.is_terminal() {
+            return;
+        }
+
+        let mut lru = self.inner.lock().unwrap();
+        lru.tick += 1;
+        let tick = lru.tick;
+
+        if let Some((_, old_tick)) = lru.entries.insert(proposal.id.clone(), (proposal.clone(), tick)) {
+            lru.order.remove(&old_tick);
+        }
+        lru.order.insert(tick, proposal.id.clone());
+
+        // Evict the least recently used entries
+        while lru.entries.len() > self.capacity {
+            let Some((_, oldest)) = lru.order.pop_first() else {
+                break;
+            };
+            lru.entries.remove(&oldest);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proposals::{ProposalBuilder, ProposalState, ProposalType};
+    use std::sync::atomic::AtomicUsize;
+
+    fn proposal(state: ProposalState) -> Proposal {
+        let mut proposal = ProposalBuilder::new()
+            .title("Fund the grants program")
+            .description("Allocate funds for community grants")
+            .proposal_type(ProposalType::TextProposal {
+                metadata: serde_json::Value::Null,
+            })
+            .proposer("0xProposer")
+            .build()
+            .unwrap();
+        proposal.state = state;
+        proposal
+    }
+
+    async fn read(cache: &ProposalCache, proposal: &Proposal, loads: &AtomicUsize) -> Proposal {
+        cache
+            .get_or_load(&proposal.id, || async {
+                loads.fetch_add(1, Ordering::SeqCst);
+                Ok(proposal.clone())
+            })
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_terminal_proposal_served_from_cache() {
+        let cache = ProposalCache::new(10);
+        let executed = proposal(ProposalState::Executed);
+        let loads = AtomicUsize::new(0);
+
+        read(&cache, &executed, &loads).await;
+        let second = read(&cache, &executed, &loads).await;
+
+        assert_eq!(second.id, executed.id);
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+        assert_eq!(cache.stats().hits, 1);
+        assert_eq!(cache.stats().misses, 1);
+    }
+
+    #[tokio::test]
+    async fn test_active_proposal_not_cached() {
+        let cache = ProposalCache::new(10);
+        let voting = proposal(ProposalState::Voting);
+        let loads = AtomicUsize::new(0);
+
+        read(&cache, &voting, &loads).await;
+        read(&cache, &voting, &loads).await;
+
+        assert_eq!(loads.load(Ordering::SeqCst), 2);
+        assert_eq!(cache.stats().hits, 0);
+        assert_eq!(cache.stats().entries, 0);
+    }
+
+    #[tokio::test]
+    async fn test_invalidate_and_eviction() {
+        let cache = ProposalCache::new(2);
+        let first = proposal(ProposalState::Rejected);
+        let second = proposal(ProposalState::Cancelled);
+        let third = proposal(ProposalState::Executed);
+        let loads = AtomicUsize::new(0);
+
+        read(&cache, &first, &loads).await;
+        read(&cache, &second, &loads).await;
+        // Touch the first entry so the second becomes least recently used
+        read(&cache, &first, &loads).await;
+        read(&cache, &third, &loads).await;
+
+        assert_eq!(cache.stats().entries, 2);
+        assert_eq!(loads.load(Ordering::SeqCst), 3);
+
+        read(&cache, &second, &loads).await;
+        assert_eq!(loads.load(Ordering::SeqCst), 4);
+
+        cache.invalidate(&third.id);
+        read(&cache, &third, &loads).await;
+        assert_eq!(loads.load(Ordering::SeqCst), 5);
+    }
+}
@@ -0,0 +1,229 @@
+//! Proposal discussion threads
+//!
+//! Members comment on proposals before voting. A comment may reply to
+//! another comment on the same proposal, forming a thread. Deleted comments
+//! keep their place in the thread with the body removed, so replies to them
+//! stay in context.
+
+use super::types::ProposalId;
+use crate::core::{Database, DaoError, Result};
+use crate::identity::MemberRole;
+use crate::utils::time::{current_timestamp, rfc3339, timestamp_to_datetime};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Maximum length of a comment body in characters
+pub const MAX_COMMENT_LENGTH: usize = 10_000;
+
+/// A comment on a proposal
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProposalComment {
+    /// Comment ID
+    pub id: String,
+    /// Proposal commented on
+    pub proposal_id: ProposalId,
+    /// Comment this one replies to; `None` for top-level comments
+    pub parent_comment_id: Option<String>,
+    /// Author address
+    pub author: String,
+    /// Comment text (empty once deleted)
+    pub body: String,
+    /// When the comment was posted
+    #[serde(with = "rfc3339")]
+    pub created_at: DateTime<Utc>,
+    /// When the comment was deleted
+    #[serde(default, with = "rfc3339::option")]
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+impl ProposalComment {
+    /// Create a new comment
+    pub fn new(proposal_id: &ProposalId, author: &str, body: &str, parent_comment_id: Option<String>) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            proposal_id: proposal_id.clone(),
+            parent_comment_id,
+            author: author.to_string(),
+            body: body.trim().to_string(),
+            created_at: Utc::now(),
+            deleted_at: None,
+        }
+    }
+}
+
+/// Check a comment body
+pub(crate) fn validate_body(body: &str) -> Result<()> {
+    if body.trim().is_empty() {
+        return Err(DaoError::InvalidParameter("Comment cannot be empty".to_string()));
+    }
+
+    if body.chars().count() > MAX_COMMENT_LENGTH {
+        return Err(DaoError::InvalidParameter(format!(
+            "Comment exceeds {} characters",
+            MAX_COMMENT_LENGTH
+        )));
+    }
+
+    Ok(())
+}
+
+/// Whether `actor` may delete a comment: its author or an admin
+pub(crate) fn can_delete(comment: &ProposalComment, actor: &str, actor_role: Option<MemberRole>) -> bool {
+    comment.author.eq_ignore_ascii_case(actor) || actor_role == Some(MemberRole::Admin)
+}
+
+/// Order comments as threads
+///
+/// Top-level comments come oldest first, each followed depth-first by its
+/// replies, also oldest first. Replies whose parent is missing are treated
+/// as top-level.
+pub fn thread_order(comments: Vec<ProposalComment>) -> Vec<ProposalComment> {
+    let ids: Vec<String> = comments.iter().map(|comment| comment.id.clone()).collect();
+    let mut children: HashMap<Option<String>, Vec<ProposalComment>> = HashMap::new();
+
+    for comment in comments {
+        let parent = comment.parent_comment_id.clone().filter(|parent| ids.contains(parent));
+        children.entry(parent).or_default().push(comment);
+    }
+    for siblings in children.values_mut() {
+        siblings.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+    }
+
+    let mut ordered = Vec::with_capacity(ids.len());
+    let mut stack: Vec<ProposalComment> = children.remove(&None).unwrap_or_default().into_iter().rev().collect();
+
+    while let Some(comment) = stack.pop() {
+        if let Some(replies) = children.remove(&Some(comment.id.clone())) {
+            stack.extend(replies.into_iter().rev());
+        }
+        ordered.push(comment);
+    }
+
+    ordered
+}
+
+/// Store a new comment
+pub(crate) async fn insert_comment(database: &Database, comment: &ProposalComment) -> Result<()> {
+    database
+        .execute(
+            "INSERT INTO proposal_comments (id, proposal_id, parent_comment_id, author, body, created_at) VALUES ($1, $2, $3, $4, $5, $6)",
+            &[
+                &comment.id,
+                &comment.proposal_id,
+                &comment.parent_comment_id,
+                &comment.author,
+                &comment.body,
+                &comment.created_at.timestamp(),
+            ],
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Load a comment by ID
+pub(crate) async fn load_comment(database: &Database, id: &str) -> Result<Option<ProposalComment>> {
+    let row = database
+        .query_opt(
+            "SELECT id, proposal_id, parent_comment_id, author, body, created_at, deleted_at FROM proposal_comments WHERE id = $1",
+            &[&id],
+        )
+        .await?;
+
+    Ok(row.map(|row| parse_comment(&row)))
+}
+
+/// Load every comment on a proposal, unordered
+pub(crate) async fn load_comments(database: &Database, proposal_id: &ProposalId) -> Result<Vec<ProposalComment>> {
+    let rows = database
+        .query(
+            "SELECT id, proposal_id, parent_comment_id, author, body, created_at, deleted_at FROM proposal_comments WHERE proposal_id = $1",
+            &[&proposal_id],
+        )
+        .await?;
+
+    Ok(rows.iter().map(parse_comment).collect())
+}
+
+/// Remove a comment's body, keeping its place in the thread
+pub(crate) async fn mark_deleted(database: &Database, id: &str) -> Result<()> {
+    database
+        .execute(
+            "UPDATE proposal_comments SET body = '', deleted_at = $1 WHERE id = $2",
+            &[&(current_timestamp() as i64), &id],
+        )
+        .await?;
+
+    Ok(())
+}
+
+fn parse_comment(row: &tokio_postgres::Row) -> ProposalComment {
+    ProposalComment {
+        id: row.get("id"),
+        proposal_id: row.get("proposal_id"),
+        parent_comment_id: row.get("parent_comment_id"),
+        author: row.get("author"),
+        body: row.get("body"),
+        created_at: timestamp_to_datetime(row.get::<_, i64>("created_at") as u64),
+        deleted_at: row
+            .get::<_, Option<i64>>("deleted_at")
+            .map(|deleted_at| timestamp_to_datetime(deleted_at as u64)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn comment(id: &str, parent: Option<&str>, minutes: i64) -> ProposalComment {
+        ProposalComment {
+            id: id.to_string(),
+            proposal_id: "proposal-1".to_string(),
+            parent_comment_id: parent.map(str::to_string),
+            author: "0xAlice".to_string(),
+            body: format!("Comment {}", id),
+            created_at: Utc::now() + Duration::minutes(minutes),
+            deleted_at: None,
+        }
+    }
+
+    #[test]
+    fn test_nested_comments_come_back_in_thread_order() {
+        // Stored out of order: two threads, one with a reply to a reply
+        let comments = vec![
+            comment("b1", Some("b"), 6),
+            comment("a2", Some("a"), 5),
+            comment("b", None, 2),
+            comment("a1a", Some("a1"), 4),
+            comment("a", None, 0),
+            comment("a1", Some("a"), 1),
+        ];
+
+        let ordered: Vec<String> = thread_order(comments).into_iter().map(|comment| comment.id).collect();
+        assert_eq!(ordered, ["a", "a1", "a1a", "a2", "b", "b1"]);
+    }
+
+    #[test]
+    fn test_orphaned_reply_shown_at_top_level() {
+        let comments = vec![comment("reply", Some("missing"), 1), comment("root", None, 0)];
+
+        let ordered: Vec<String> = thread_order(comments).into_iter().map(|comment| comment.id).collect();
+        assert_eq!(ordered, ["root", "reply"]);
+    }
+
+    #[test]
+    fn test_only_author_or_admin_can_delete() {
+        let comment = comment("a", None, 0);
+
+        assert!(can_delete(&comment, "0xalice", Some(MemberRole::Member)));
+        assert!(can_delete(&comment, "0xAdmin", Some(MemberRole::Admin)));
+        assert!(!can_delete(&comment, "0xBob", Some(MemberRole::Council)));
+        assert!(!can_delete(&comment, "0xBob", None));
+
+        assert!(validate_body("  ").is_err());
+        assert!(validate_body(&"x".repeat(MAX_COMMENT_LENGTH + 1)).is_err());
+    }
+}
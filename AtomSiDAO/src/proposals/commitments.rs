@@ -0,0 +1,257 @@
+//! Commit-then-reveal proposal submission
+//!
+//! A proposer first commits a signed hash of the proposal content, then
+//! reveals the proposal. The first commitment to a hash owns that content,
+//! so copying a revealed proposal and racing it in gains nothing. Each
+//! commitment can be revealed once, and the revealed proposal records when
+//! it was committed.
+
+use super::types::Proposal;
+use crate::core::{Database, DaoError, Result};
+use crate::crypto;
+use crate::utils::time::{current_timestamp, timestamp_to_datetime};
+use chrono::{DateTime, Duration, Utc};
+
+/// Prefix of the content hash line in a signed commitment message
+pub const CONTENT_HASH_PREFIX: &str = "Content Hash:";
+
+/// A proposer's commitment to proposal content
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProposalCommitment {
+    /// Hex-encoded hash of the committed content
+    pub content_hash: String,
+    /// Committing proposer
+    pub proposer: String,
+    /// When the commitment was made
+    pub committed_at: DateTime<Utc>,
+    /// Proposal revealed from the commitment, once revealed
+    pub revealed_proposal_id: Option<String>,
+}
+
+/// Hash of the parts of a proposal a commitment covers
+///
+/// Covers the content but not the id or timestamps, which are only
+/// assigned when the proposal is built.
+pub fn content_hash(proposal: &Proposal) -> Result<String> {
+    let content = serde_json::json!({
+        "title": proposal.title,
+        "description": proposal.description,
+        "proposal_type": proposal.proposal_type,
+        "metadata": proposal.metadata,
+        "attachments": proposal.attachments,
+    });
+
+    crypto::hash_json(&content)
+        .map(hex::encode)
+        .map_err(|e| DaoError::InternalError(format!("Failed to hash proposal content: {}", e)))
+}
+
+/// Message a proposer signs to commit to content
+pub fn commit_message(proposer: &str, content_hash: &str, issued_at: DateTime<Utc>) -> String {
+    format!(
+        "AtomSi DAO proposal commitment\nProposer: {}\n{} {}\n{} {}",
+        proposer,
+        CONTENT_HASH_PREFIX,
+        content_hash,
+        crypto::ISSUED_AT_PREFIX,
+        issued_at.to_rfc3339()
+    )
+}
+
+/// Check that a signed commitment message commits to `content_hash`
+pub(crate) fn check_commit_message(message: &str, content_hash: &str) -> Result<()> {
+    let committed = message
+        .lines()
+        .find_map(|line| line.trim().strip_prefix(CONTENT_HASH_PREFIX))
+        .map(str::trim);
+
+    if committed != Some(content_hash) {
+        return Err(DaoError::InvalidParameter(
+            "Signed message does not commit to the given content hash".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Check that a proposal is a valid reveal of a commitment
+pub(crate) fn check_reveal(
+    commitment: &ProposalCommitment,
+    proposal: &Proposal,
+    reveal_window: Duration,
+    now: DateTime<Utc>,
+) -> Result<()> {
+    if content_hash(proposal)? != commitment.content_hash {
+        return Err(DaoError::InvalidParameter(
+            "Proposal content does not match the committed hash".to_string(),
+        ));
+    }
+
+    if !commitment.proposer.eq_ignore_ascii_case(&proposal.proposer) {
+        return Err(DaoError::Unauthorized);
+    }
+
+    if commitment.revealed_proposal_id.is_some() {
+        return Err(DaoError::ProposalError("Commitment has already been revealed".to_string()));
+    }
+
+    if now - commitment.committed_at > reveal_window {
+        return Err(DaoError::ProposalError("Commitment has expired".to_string()));
+    }
+
+    Ok(())
+}
+
+/// Load the commitment to a content hash
+pub(crate) async fn load_commitment(database: &Database, content_hash: &str) -> Result<Option<ProposalCommitment>> {
+    let row = database
+        .query_opt(
+            "SELECT content_hash, proposer, committed_at, revealed_proposal_id FROM proposal_commitments WHERE content_hash = $1",
+            &[&content_hash],
+        )
+        .await?;
+
+    Ok(row.map(|row| ProposalCommitment {
+        content_hash: row.get("content_hash"),
+        proposer: row.get("proposer"),
+        committed_at: timestamp_to_datetime(row.get::<_, i64>("committed_at") as u64),
+        revealed_proposal_id: row.get("revealed_proposal_id"),
+    }))
+}
+
+/// Record a commitment; the first commitment to a hash wins
+pub(crate) async fn save_commitment(database: &Database, proposer: &str, content_hash: &str) -> Result<()> {
+    let inserted = database
+        .execute(
+            "INSERT INTO proposal_commitments (content_hash, proposer, committed_at) VALUES ($1, $2, $3) ON CONFLICT (content_hash) DO NOTHING",
+            &[&content_hash, &proposer, &(current_timestamp() as i64)],
+        )
+        .await?;
+
+    if inserted == 0 {
+        return Err(DaoError::ProposalError(
+            "This content has already been committed".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Mark a commitment as revealed by `proposal_id`
+///
+/// Only one of two racing reveals of the same commitment succeeds.
+///
+/// Portable: runs on PostgreSQL and SQLite.
+pub(crate) async fn mark_revealed(database: &Database, content_hash: &str, proposal_id: &str) -> Result<()> {
+    let claimed = database
+        .execute_any(
+            "UPDATE proposal_commitments SET revealed_proposal_id = $1 \
+             WHERE content_hash = $2 AND revealed_proposal_id IS NULL",
+            &[proposal_id.into(), content_hash.into()],
+        )
+        .await?;
+
+    if claimed == 0 {
+        return Err(DaoError::ProposalError("Commitment has already been revealed".to_string()));
+    }
+
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proposals::{ProposalBuilder, ProposalType};
+
+    fn proposal(proposer: &str, description: &str) -> Proposal {
+        ProposalBuilder::new()
+            .title("Fund the grants program")
+            .description(description)
+            .proposal_type(ProposalType::TextProposal {
+                metadata: serde_json::Value::Null,
+            })
+            .proposer(proposer)
+            .build()
+            .unwrap()
+    }
+
+    fn commitment(proposal: &Proposal, committed_at: DateTime<Utc>) -> ProposalCommitment {
+        ProposalCommitment {
+            content_hash: content_hash(proposal).unwrap(),
+            proposer: proposal.proposer.clone(),
+            committed_at,
+            revealed_proposal_id: None,
+        }
+    }
+
+    #[test]
+    fn test_reveal_must_match_committed_hash() {
+        let now = Utc::now();
+        let window = Duration::hours(72);
+        let committed = proposal("0xAlice", "Allocate 10,000 ATOM to community grants");
+        let commitment = commitment(&committed, now);
+
+        // The same content built again (new id and timestamps) matches
+        let revealed = proposal("0xAlice", "Allocate 10,000 ATOM to community grants");
+        assert!(check_reveal(&commitment, &revealed, window, now).is_ok());
+
+        let altered = proposal("0xAlice", "Allocate 90,000 ATOM to community grants");
+        assert!(matches!(
+            check_reveal(&commitment, &altered, window, now),
+            Err(DaoError::InvalidParameter(_))
+        ));
+
+        // Someone else cannot reveal Alice's commitment
+        let copied = proposal("0xMallory", "Allocate 10,000 ATOM to community grants");
+        assert!(check_reveal(&commitment, &copied, window, now).is_err());
+
+        assert!(check_reveal(&commitment, &revealed, window, now + Duration::hours(73)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_commitment_revealed_once() {
+        let database = Database::migrated_sqlite().await;
+        for id in ["proposal-1", "proposal-2"] {
+            database
+                .execute_any(
+                    "INSERT INTO proposals (id, data, state, title, description) VALUES ($1, '{}', 'Draft', 'Grants', 'Grants')",
+                    &[id.into()],
+                )
+                .await
+                .unwrap();
+        }
+        database
+            .execute_any(
+                "INSERT INTO proposal_commitments (content_hash, proposer, committed_at) VALUES ($1, $2, $3)",
+                &["abc123".into(), "0xAlice".into(), Utc::now().timestamp().into()],
+            )
+            .await
+            .unwrap();
+
+        // The second of two racing reveals loses and the first stays recorded
+        mark_revealed(&database, "abc123", "proposal-1").await.unwrap();
+        assert!(matches!(
+            mark_revealed(&database, "abc123", "proposal-2").await,
+            Err(DaoError::ProposalError(_))
+        ));
+
+        let revealed = database
+            .query_one_any(
+                "SELECT revealed_proposal_id FROM proposal_commitments WHERE content_hash = $1",
+                &["abc123".into()],
+            )
+            .await
+            .unwrap()
+            .text("revealed_proposal_id")
+            .unwrap();
+        assert_eq!(revealed, "proposal-1");
+    }
+
+    #[test]
+    fn test_commit_message_names_the_hash() {
+        let message = commit_message("0xAlice", "abc123", Utc::now());
+        assert!(check_commit_message(&message, "abc123").is_ok());
+        assert!(check_commit_message(&message, "def456").is_err());
+    }
+}
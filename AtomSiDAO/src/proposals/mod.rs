@@ -3,20 +3,49 @@
 //! This module provides functionality for creating, tracking, and executing
 //! proposals within the DAO.
 
+mod attachments;
+mod cache;
+mod comments;
+pub mod commitments;
+mod multichoice;
+mod receipts;
+mod search;
+mod targets;
+mod templates;
 mod types;
 
-pub use types::{Proposal, ProposalId, ProposalState, ProposalType, ProposalVote};
+pub use cache::CacheStats;
+pub use comments::{thread_order, ProposalComment, MAX_COMMENT_LENGTH};
+pub use receipts::{vote_message, VoteReceipt, VoteSignature};
+pub use templates::{ProposalTemplate, MAX_TEMPLATE_NAME_LENGTH};
+pub use types::{
+    Attachment, DelegatedPower, Participation, Proposal, ProposalId, ProposalState, ProposalType, ProposalVote, Sponsor, SwapAction,
+    Vote, VotingExtension,
+};
+
+use cache::ProposalCache;
 
 use crate::{
-    blockchain::BlockchainAdapter,
-    config::Config,
-    core::{Database, DaoError, Result},
+    api::websocket::{EventType, WebSocketEvent, WebSocketManager},
+    blockchain::{BlockchainAdapter, ExecutionLimiter},
+    config::{Config, ConfigManager, ExecutionGuardConfig, GovernanceConfig, QuorumBasis, QuorumSupplySource},
+    core::{fetch_page, Address, Database, DaoError, Page, PageRequest, Result, SqlValue, Symbol, SYSTEM_ACTOR},
+    governance,
+    identity::{self, Member, MemberRole},
+    token::{Token, TokenManager},
 };
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
 use std::sync::Arc;
+use tokio::sync::broadcast;
+use tracing::warn;
 use uuid::Uuid;
 
+/// Times a submission retries when a concurrent one took its number
+const NUMBERING_ATTEMPTS: usize = 5;
+
 /// Proposal builder for creating new proposals
 pub struct ProposalBuilder {
     title: Option<String>,
@@ -24,6 +53,7 @@ pub struct ProposalBuilder {
     proposal_type: Option<ProposalType>,
     proposer: Option<String>,
     metadata: serde_json::Value,
+    attachments: Vec<Attachment>,
 }
 
 impl ProposalBuilder {
@@ -35,6 +65,7 @@ impl ProposalBuilder {
             proposal_type: None,
             proposer: None,
             metadata: serde_json::Value::Null,
+            attachments: Vec::new(),
         }
     }
     
@@ -68,6 +99,27 @@ impl ProposalBuilder {
         self
     }
     
+    /// Attach a document to the proposal
+    pub fn attachment(mut self, attachment: Attachment) -> Self {
+        self.attachments.push(attachment);
+        self
+    }
+    
+    /// Start a proposal from a template, filling its `{placeholders}` from
+    /// `vars`
+    ///
+    /// Sets the title, description, type and metadata; the proposer still
+    /// has to be set. Fails if a placeholder has no variable.
+    pub fn from_template(template: &ProposalTemplate, vars: &HashMap<String, String>) -> Result<Self> {
+        let rendered = template.render(vars)?;
+        
+        Ok(Self::new()
+            .title(rendered.title)
+            .description(rendered.description)
+            .proposal_type(rendered.proposal_type)
+            .metadata(rendered.metadata))
+    }
+    
     /// Build the proposal
     pub fn build(self) -> Result<Proposal> {
         let title = self.title.ok_or_else(|| {
@@ -88,6 +140,7 @@ impl ProposalBuilder {
         
         Ok(Proposal {
             id: Uuid::new_v4().to_string(),
+            number: None,
             title,
             description,
             proposal_type,
@@ -97,12 +150,23 @@ impl ProposalBuilder {
             updated_at: Utc::now(),
             voting_starts_at: None,
             voting_ends_at: None,
+            snapshot_block: None,
             execution_date: None,
+            execution_available_at: None,
             metadata: self.metadata,
             yes_votes: 0,
             no_votes: 0,
             abstain_votes: 0,
+            veto_votes: 0,
             votes: Vec::new(),
+            sponsors: Vec::new(),
+            target_code_hash: None,
+            extensions: Vec::new(),
+            attachments: self.attachments,
+            participation: None,
+            committed_at: None,
+            option_votes: HashMap::new(),
+            winning_option: None,
         })
     }
 }
@@ -118,6 +182,11 @@ pub struct ProposalManager {
     config: Arc<Config>,
     blockchain: Arc<dyn BlockchainAdapter>,
     database: Database,
+    execution_limiter: ExecutionLimiter,
+    cache: Arc<ProposalCache>,
+    token_manager: Option<TokenManager>,
+    config_manager: Option<ConfigManager>,
+    event_sender: Option<broadcast::Sender<WebSocketEvent>>,
 }
 
 impl ProposalManager {
@@ -131,84 +200,510 @@ impl ProposalManager {
             config: Arc::new(config.clone()),
             blockchain: Arc::new(blockchain),
             database,
+            execution_limiter: ExecutionLimiter::from_config(&config.blockchain),
+            cache: Arc::new(ProposalCache::new(config.governance.proposal_cache_size)),
+            token_manager: None,
+            config_manager: None,
+            event_sender: None,
         })
     }
     
+    /// Share an execution limiter with other executing components
+    pub fn with_execution_limiter(mut self, limiter: ExecutionLimiter) -> Self {
+        self.execution_limiter = limiter;
+        self
+    }
+    
+    /// Use a token manager to read the governance token's supply
+    ///
+    /// Required when `governance.quorum_basis` is `circulatingsupply`.
+    pub fn with_token_manager(mut self, token_manager: TokenManager) -> Self {
+        self.token_manager = Some(token_manager);
+        self
+    }
+    
+    /// Use a config manager to apply executed `ParameterChange` proposals
+    ///
+    /// The manager then reads its configuration from the config manager, so
+    /// amendments and reloads take effect without a restart.
+    pub fn with_config_manager(mut self, config_manager: ConfigManager) -> Self {
+        self.config_manager = Some(config_manager);
+        self
+    }
+    
+    /// Current configuration
+    fn config(&self) -> Arc<Config> {
+        match &self.config_manager {
+            Some(config_manager) => config_manager.current(),
+            None => self.config.clone(),
+        }
+    }
+    
+    /// Broadcast proposal events on the given channel
+    pub fn with_event_sender(mut self, sender: broadcast::Sender<WebSocketEvent>) -> Self {
+        self.event_sender = Some(sender);
+        self
+    }
+    
     /// Submit a proposal
+    ///
+    /// When `governance.proposal_commit_reveal` is on, proposals must be
+    /// committed with [`Self::commit_proposal`] and submitted with
+    /// [`Self::reveal_proposal`] instead.
     pub async fn submit_proposal(&self, proposal: Proposal) -> Result<ProposalId> {
+        if self.config().governance.proposal_commit_reveal {
+            return Err(DaoError::ProposalError(
+                "Proposals must be committed and then revealed".to_string(),
+            ));
+        }
+        
+        self.submit(proposal).await
+    }
+    
+    /// Commit to proposal content ahead of revealing it
+    ///
+    /// `signed_message` is a [`commitments::commit_message`] for the hash,
+    /// signed by the proposer.
+    pub async fn commit_proposal(
+        &self,
+        proposer: &str,
+        content_hash: &str,
+        signed_message: &str,
+        signature: &str,
+    ) -> Result<()> {
+        if !self.config().governance.proposal_commit_reveal {
+            return Err(DaoError::NotSupported(
+                "Proposal commit-reveal is disabled (set governance.proposal_commit_reveal = true)".to_string(),
+            ));
+        }
+        
+        commitments::check_commit_message(signed_message, content_hash)?;
+        let signed = crate::crypto::verify_timestamped_signature(
+            proposer,
+            signed_message,
+            signature,
+            self.config().security.signature_max_age_seconds,
+        )
+        .unwrap_or(false);
+        if !signed {
+            return Err(DaoError::Unauthorized);
+        }
+        
+        commitments::save_commitment(&self.database, proposer, content_hash).await
+    }
+    
+    /// Reveal a committed proposal, submitting it with its commit time
+    ///
+    /// Of two concurrent reveals of one commitment only one claims it; the
+    /// other's proposal is withdrawn again and an error returned.
+    pub async fn reveal_proposal(&self, mut proposal: Proposal) -> Result<ProposalId> {
+        let content_hash = commitments::content_hash(&proposal)?;
+        let commitment = commitments::load_commitment(&self.database, &content_hash)
+            .await?
+            .ok_or_else(|| {
+                DaoError::InvalidParameter("Proposal content does not match any commitment".to_string())
+            })?;
+        
+        let reveal_window = chrono::Duration::hours(self.config().governance.proposal_reveal_window_hours as i64);
+        commitments::check_reveal(&commitment, &proposal, reveal_window, Utc::now())?;
+        
+        proposal.committed_at = Some(commitment.committed_at);
+        let id = self.submit(proposal).await?;
+        
+        if let Err(e) = commitments::mark_revealed(&self.database, &content_hash, &id).await {
+            if let Err(delete_error) = self.soft_delete_proposal(&id, SYSTEM_ACTOR).await {
+                warn!("Could not withdraw duplicate reveal {}: {}", id, delete_error);
+            }
+            return Err(e);
+        }
+        
+        Ok(id)
+    }
+    
+    /// Check and store a new proposal
+    async fn submit(&self, proposal: Proposal) -> Result<ProposalId> {
+        // Suspended and inactive members can't propose
+        identity::require_standing(
+            &self.database.primary(),
+            &proposal.proposer,
+            self.config().identity.allow_unregistered_voters,
+        )
+        .await?;
+        
         // Check if the proposer has enough tokens
-        let proposer_balance = self
-            .blockchain
-            .balance(&proposal.proposer)
-            .await
-            .map_err(|e| DaoError::BlockchainError(e))?;
+        let proposer_balance = self.balance(&proposal.proposer).await?;
         
-        if proposer_balance < self.config.governance.proposal_threshold {
+        if proposer_balance < self.config().governance.proposal_threshold {
             return Err(DaoError::Unauthorized);
         }
         
         // Check if the proposal is valid
         self.validate_proposal(&proposal)?;
         
-        // Save the proposal to the database
-        self.save_proposal(&proposal).await?;
+        // Check IPFS attachment content against the declared hashes
+        let config = self.config();
+        let attachment_config = &config.governance.attachments;
+        attachments::verify_ipfs_attachments(&proposal.attachments, attachment_config, |url| {
+            attachments::fetch_from_gateway(url, attachment_config)
+        })
+        .await?;
+        
+        // Number and save the proposal
+        let number = self.insert_proposal(&proposal).await?;
+        
+        self.emit_event(
+            EventType::ProposalCreated,
+            serde_json::json!({
+                "proposal_id": proposal.id,
+                "number": number,
+                "title": proposal.title,
+                "proposer": proposal.proposer,
+                "state": proposal.state,
+            }),
+        );
         
         Ok(proposal.id)
     }
     
+    /// Get a proposal by UUID or display id (`AIP-42` or `42`)
+    pub async fn find_proposal(&self, reference: &str) -> Result<Proposal> {
+        let Some(number) = parse_proposal_number(reference, &self.config().governance.proposal_id_prefix) else {
+            return self.get_proposal(&reference.to_string()).await;
+        };
+        
+        let row = self
+            .database
+            .query_opt_any("SELECT id FROM proposals WHERE number = $1 AND deleted_at IS NULL", &[(number as i64).into()])
+            .await?
+            .ok_or_else(|| DaoError::ProposalError(format!("Proposal not found: {}", reference)))?;
+        
+        self.get_proposal(&row.text("id")?).await
+    }
+    
     /// Get a proposal by ID
+    ///
+    /// Served by the read replica when one is configured, so the result may
+    /// lag recent votes by the replication delay. Finalized proposals are
+    /// served from memory after the first read.
     pub async fn get_proposal(&self, id: &ProposalId) -> Result<Proposal> {
+        self.cache
+            .get_or_load(id, || Self::load_proposal(&self.database, id))
+            .await
+    }
+    
+    /// Get hit/miss counters for the finalized proposal cache
+    pub fn cache_stats(&self) -> CacheStats {
+        self.cache.stats()
+    }
+    
+    /// Get a proposal by ID from the primary, for read-modify-write paths
+    async fn get_proposal_for_update(&self, id: &ProposalId) -> Result<Proposal> {
+        Self::load_proposal(&self.database.primary(), id).await
+    }
+    
+    /// Load a proposal using the given database handle
+    ///
+    /// Soft-deleted proposals are not found. Portable: runs on PostgreSQL
+    /// and SQLite.
+    async fn load_proposal(database: &Database, id: &ProposalId) -> Result<Proposal> {
         // Load the proposal from the database
-        let query = "SELECT * FROM proposals WHERE id = $1";
-        let row = self.database.query_one(query, &[&id]).await?;
+        let query = "SELECT data FROM proposals WHERE id = $1 AND deleted_at IS NULL";
+        let row = database.query_one_any(query, &[id.as_str().into()]).await?;
         
         // Parse the proposal from the row
-        let proposal: Proposal = serde_json::from_value(row.get("data"))
-            .map_err(|e| DaoError::DatabaseError(format!("Failed to parse proposal: {}", e)))?;
-        
-        Ok(proposal)
+        row.json("data")
+            .map_err(|e| DaoError::DatabaseError(format!("Failed to parse proposal: {}", e)))
     }
     
-    /// Get all proposals
-    pub async fn get_proposals(&self, state: Option<ProposalState>) -> Result<Vec<Proposal>> {
-        // Construct the query based on the state filter
-        let (query, params) = match state {
-            Some(state) => {
-                let query = "SELECT * FROM proposals WHERE state = $1 ORDER BY created_at DESC";
-                let state_str = serde_json::to_string(&state)
-                    .map_err(|e| DaoError::DatabaseError(format!("Failed to serialize state: {}", e)))?;
-                (query, vec![&state_str as &(dyn tokio_postgres::types::ToSql + Sync)])
-            }
-            None => {
-                let query = "SELECT * FROM proposals ORDER BY created_at DESC";
-                (query, Vec::new())
-            }
+    /// Find up to `limit` proposals whose title or description match every
+    /// word of `query`, most relevant first
+    ///
+    /// Uses full-text search on PostgreSQL and substring matching on SQLite.
+    /// Served by the read replica when one is configured.
+    pub async fn search(&self, query: &str, limit: usize) -> Result<Vec<Proposal>> {
+        search::search_proposals(&self.database, query, limit).await
+    }
+    
+    /// Get all proposals, newest first
+    ///
+    /// Soft-deleted proposals are left out unless `include_deleted` is set.
+    /// Served by the read replica when one is configured.
+    pub async fn get_proposals(&self, state: Option<ProposalState>, include_deleted: bool) -> Result<Vec<Proposal>> {
+        Self::list_proposals(&self.database, state, include_deleted).await
+    }
+    
+    /// Get a page of proposals, newest first
+    ///
+    /// Proposals created while a client pages through the listing don't
+    /// shift later pages. Soft-deleted proposals are left out unless
+    /// `include_deleted` is set. Served by the read replica when one is
+    /// configured.
+    pub async fn get_proposals_page(
+        &self,
+        state: Option<ProposalState>,
+        include_deleted: bool,
+        request: &PageRequest,
+    ) -> Result<Page<Proposal>> {
+        Self::list_proposals_page(&self.database, state, include_deleted, request).await
+    }
+    
+    /// List proposals using the given database handle
+    ///
+    /// Portable: runs on PostgreSQL and SQLite.
+    async fn list_proposals(database: &Database, state: Option<ProposalState>, include_deleted: bool) -> Result<Vec<Proposal>> {
+        // Construct the query from the filters
+        let (conditions, params) = Self::list_filter(state, include_deleted)?;
+        let filter = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!(" WHERE {}", conditions.join(" AND "))
         };
+        let query = format!("SELECT data FROM proposals{} ORDER BY created_at DESC", filter);
         
         // Load the proposals from the database
-        let rows = self.database.query(query, &params).await?;
+        let rows = database.query_any(&query, &params).await?;
         
         // Parse the proposals from the rows
+        rows.iter()
+            .map(|row| {
+                row.json("data")
+                    .map_err(|e| DaoError::DatabaseError(format!("Failed to parse proposal: {}", e)))
+            })
+            .collect()
+    }
+    
+    /// List a page of proposals using the given database handle
+    ///
+    /// Portable: runs on PostgreSQL and SQLite.
+    async fn list_proposals_page(
+        database: &Database,
+        state: Option<ProposalState>,
+        include_deleted: bool,
+        request: &PageRequest,
+    ) -> Result<Page<Proposal>> {
+        let (conditions, params) = Self::list_filter(state, include_deleted)?;
+        fetch_page(database, "proposals", conditions, params, request, |proposal: &Proposal| {
+            (proposal.created_at, proposal.id.clone())
+        })
+        .await
+    }
+    
+    /// Conditions and parameters selecting the proposals to list
+    fn list_filter(state: Option<ProposalState>, include_deleted: bool) -> Result<(Vec<String>, Vec<SqlValue>)> {
+        let mut conditions = Vec::new();
+        let mut params = Vec::new();
+        if let Some(state) = state {
+            let state_str = serde_json::to_string(&state)
+                .map_err(|e| DaoError::DatabaseError(format!("Failed to serialize state: {}", e)))?;
+            params.push(SqlValue::Text(state_str));
+            conditions.push("state = $1".to_string());
+        }
+        if !include_deleted {
+            conditions.push("deleted_at IS NULL".to_string());
+        }
+        
+        Ok((conditions, params))
+    }
+    
+    /// Soft-delete a proposal, hiding it from lookups and listings
+    ///
+    /// The row and its data are kept, with `actor` recorded as the last
+    /// writer. Fails if the proposal doesn't exist or is already deleted.
+    pub async fn soft_delete_proposal(&self, id: &ProposalId, actor: &str) -> Result<()> {
+        Self::soft_delete(&self.database, id, actor).await?;
+        
+        // Any cached copy must no longer be served
+        self.cache.invalidate(id);
+        
+        Ok(())
+    }
+    
+    /// Soft-delete a proposal using the given database handle
+    ///
+    /// Portable: runs on PostgreSQL and SQLite.
+    async fn soft_delete(database: &Database, id: &ProposalId, actor: &str) -> Result<()> {
+        let deleted = database
+            .execute_any(
+                "UPDATE proposals SET deleted_at = $1, updated_by = $2 WHERE id = $3 AND deleted_at IS NULL",
+                &[Utc::now().timestamp().into(), actor.into(), id.as_str().into()],
+            )
+            .await?;
+        
+        if deleted == 0 {
+            return Err(DaoError::ProposalError(format!("Proposal not found: {}", id)));
+        }
+        
+        Ok(())
+    }
+    
+    /// Get the open proposals an address can still vote on
+    ///
+    /// Returns proposals in the voting state whose window is open, that the
+    /// address has not voted on and on which it has voting power at the
+    /// proposal's snapshot, ordered by soonest deadline.
+    pub async fn votable_by(&self, address: &Address) -> Result<Vec<Proposal>> {
+        // Votes are recorded under the checksummed address
+        let address = address.as_str();
+        
+        let query = "SELECT data FROM proposals WHERE state = $1 AND deleted_at IS NULL";
+        let state = serde_json::to_string(&ProposalState::Voting)
+            .map_err(|e| DaoError::DatabaseError(format!("Failed to serialize state: {}", e)))?;
+        let rows = self.database.query_any(query, &[state.into()]).await?;
+        
         let proposals = rows
-            .into_iter()
+            .iter()
             .map(|row| {
-                serde_json::from_value(row.get("data"))
+                row.json("data")
                     .map_err(|e| DaoError::DatabaseError(format!("Failed to parse proposal: {}", e)))
             })
             .collect::<Result<Vec<Proposal>>>()?;
         
-        Ok(proposals)
+        // Voting power is measured at each proposal's snapshot, as when
+        // voting; proposals the address already voted on need no lookup
+        let mut candidates = Vec::with_capacity(proposals.len());
+        for proposal in proposals {
+            if proposal.votes.iter().any(|v| v.voter.eq_ignore_ascii_case(address)) {
+                continue;
+            }
+            let (voting_power, _) = self.snapshot_voting_power(&proposal, address).await?;
+            candidates.push((proposal, voting_power));
+        }
+        
+        Ok(votable_proposals(candidates, address, Utc::now()))
     }
     
     /// Vote on a proposal
+    ///
+    /// Multiple-choice proposals only accept abstain and veto votes here;
+    /// choices are cast with [`Self::vote_for_options`].
+    ///
+    /// `signature` is the voter's signature over the [`vote_message`] for
+    /// the vote; it is required when `governance.require_signed_votes` is set.
     pub async fn vote(
         &self,
         proposal_id: &ProposalId,
-        voter: &str,
+        voter: &Address,
+        vote: ProposalVote,
+        signature: Option<VoteSignature>,
+    ) -> Result<()> {
+        self.cast_vote(proposal_id, voter, vote, Vec::new(), None, signature).await
+    }
+    
+    /// Vote on a proposal with a chosen weight instead of the voter's
+    /// holdings, for quadratic voting
+    ///
+    /// The caller charges the voter's voice credits for the weight first.
+    pub(crate) async fn vote_with_weight(
+        &self,
+        proposal_id: &ProposalId,
+        voter: &Address,
+        vote: ProposalVote,
+        weight: u64,
+        signature: Option<VoteSignature>,
+    ) -> Result<()> {
+        self.cast_vote(proposal_id, voter, vote, Vec::new(), Some(weight), signature).await
+    }
+    
+    /// Vote for options of a multiple-choice proposal, most preferred first
+    ///
+    /// The ballot counts as a yes vote in the proposal's tallies, so quorum
+    /// and veto apply as for other proposals; its first preference is added
+    /// to `option_votes`.
+    pub async fn vote_for_options(
+        &self,
+        proposal_id: &ProposalId,
+        voter: &Address,
+        ranking: Vec<usize>,
+        signature: Option<VoteSignature>,
+    ) -> Result<()> {
+        self.cast_vote(proposal_id, voter, ProposalVote::Yes, ranking, None, signature).await
+    }
+    
+    /// Change a recorded vote while voting is still open
+    ///
+    /// The vote keeps the voting power it was cast with; that power moves
+    /// from the old choice's tally to the new one. A signature for the old
+    /// choice no longer applies, so it is replaced by `signature`.
+    pub async fn change_vote(
+        &self,
+        proposal_id: &ProposalId,
+        voter: &Address,
         vote: ProposalVote,
+        signature: Option<VoteSignature>,
     ) -> Result<()> {
+        let voter = voter.as_str();
+        identity::require_standing(
+            &self.database.primary(),
+            voter,
+            self.config().identity.allow_unregistered_voters,
+        )
+        .await?;
+        
+        let mut proposal = self.get_proposal_for_update(proposal_id).await?;
+        let now = Utc::now();
+        
+        let passing_before = majority_reached(&proposal, &self.config().governance);
+        change_recorded_vote(&mut proposal, voter, vote, now)?;
+        
+        if let Some(recorded) = proposal.votes.iter_mut().find(|v| v.voter.eq_ignore_ascii_case(voter)) {
+            recorded.signature = receipts::check_vote_signature(
+                &self.config().dao.name,
+                self.config().blockchain.chain_id,
+                proposal_id,
+                recorded,
+                signature,
+                self.config().governance.require_signed_votes,
+                self.config().security.signature_max_age_seconds,
+            )?;
+        }
+        
+        // A changed vote can flip the outcome as much as a new one
+        extend_quiet_ending(&mut proposal, passing_before, voter, now, &self.config().governance);
+        
+        proposal.updated_at = now;
+        self.save_proposal(&proposal, voter).await?;
+        
+        self.emit_event(EventType::ProposalVoted, vote_event_data(&proposal, voter));
+        
+        Ok(())
+    }
+    
+    /// Record a vote, with a ranking of options for multiple-choice ballots
+    ///
+    /// `weight` replaces the voter's power at the snapshot when set.
+    async fn cast_vote(
+        &self,
+        proposal_id: &ProposalId,
+        voter: &Address,
+        vote: ProposalVote,
+        ranking: Vec<usize>,
+        weight: Option<u64>,
+        signature: Option<VoteSignature>,
+    ) -> Result<()> {
+        // Votes are recorded under the checksummed address
+        let voter = voter.as_str();
+        
+        // Quadratic votes must say how much weight they buy
+        if weight.is_none() {
+            let strategy = governance::configured_strategy(&self.config());
+            if strategy.uses_voice_credits() {
+                return Err(DaoError::NotSupported(format!(
+                    "{} votes carry a weight paid for in voice credits; use GovernanceEngine::submit_quadratic_vote",
+                    strategy.name()
+                )));
+            }
+        }
+        
+        // Suspended and inactive members can't vote
+        identity::require_standing(
+            &self.database.primary(),
+            voter,
+            self.config().identity.allow_unregistered_voters,
+        )
+        .await?;
+        
         // Load the proposal
-        let mut proposal = self.get_proposal(proposal_id).await?;
+        let mut proposal = self.get_proposal_for_update(proposal_id).await?;
         
         // Check if the proposal is in the voting state
         if proposal.state != ProposalState::Voting {
@@ -237,69 +732,117 @@ impl ProposalManager {
         }
         
         // Check if the voter has already voted
-        if proposal.votes.iter().any(|v| v.voter == voter) {
+        if proposal.votes.iter().any(|v| v.voter.eq_ignore_ascii_case(voter)) {
             return Err(DaoError::InvalidParameter(
-                "Voter has already voted".to_string(),
+                "Voter has already voted; use change_vote to change the vote".to_string(),
             ));
         }
         
-        // Get the voter's voting power
-        let voting_power = self
-            .blockchain
-            .balance(voter)
-            .await
-            .map_err(|e| DaoError::BlockchainError(e))?;
+        check_ballot(&proposal, vote, &ranking)?;
+        
+        // A quadratic vote counts with the weight its voice credits bought
+        let (voting_power, delegated) = match weight {
+            Some(weight) => (weight, Vec::new()),
+            None => self.snapshot_voting_power(&proposal, voter).await?,
+        };
         
         if voting_power == 0 {
             return Err(DaoError::Unauthorized);
         }
         
-        // Update the vote counts
-        match vote {
-            ProposalVote::Yes => {
-                proposal.yes_votes += voting_power;
-            }
-            ProposalVote::No => {
-                proposal.no_votes += voting_power;
-            }
-            ProposalVote::Abstain => {
-                proposal.abstain_votes += voting_power;
+        if !self.config().governance.delegator_override_enabled {
+            if let Some(delegate) = self.delegate_of(voter).await? {
+                return Err(DaoError::InvalidParameter(format!(
+                    "Voting power is delegated to {}",
+                    delegate
+                )));
             }
         }
         
-        // Record the vote
-        proposal.votes.push(types::Vote {
+        let mut record = types::Vote {
             voter: voter.to_string(),
             vote,
             voting_power,
             timestamp: Utc::now(),
-        });
+            delegated,
+            ranking,
+            signature: None,
+        };
+        
+        // A signature must cover the choice and power the vote counts with
+        record.signature = receipts::check_vote_signature(
+            &self.config().dao.name,
+            self.config().blockchain.chain_id,
+            proposal_id,
+            &record,
+            signature,
+            self.config().governance.require_signed_votes,
+            self.config().security.signature_max_age_seconds,
+        )?;
+        
+        let passing_before = majority_reached(&proposal, &self.config().governance);
+        
+        // A delegator voting directly takes their power back from the delegate
+        reclaim_delegated_power(&mut proposal, voter);
+        
+        // Update the vote counts
+        *vote_count_mut(&mut proposal, vote) += voting_power;
+        if let Some(&first) = record.ranking.first() {
+            *proposal.option_votes.entry(first).or_insert(0) += voting_power;
+        }
+        
+        // Record the vote
+        proposal.votes.push(record);
+        
+        // Give others a chance to respond to a last-minute swing
+        extend_quiet_ending(&mut proposal, passing_before, voter, now, &self.config().governance);
         
         // Update the proposal in the database
         proposal.updated_at = Utc::now();
-        self.save_proposal(&proposal).await?;
+        self.save_proposal(&proposal, voter).await?;
+        
+        self.emit_event(EventType::ProposalVoted, vote_event_data(&proposal, voter));
         
         Ok(())
     }
     
+    /// Get the signed receipt for a voter's vote on a proposal
+    ///
+    /// Anyone can check the receipt's signature against its message without
+    /// trusting the DAO. Unsigned votes have no receipt.
+    pub async fn get_vote_receipt(&self, proposal_id: &ProposalId, voter: &str) -> Result<VoteReceipt> {
+        let proposal = self.get_proposal(proposal_id).await?;
+        let vote = proposal
+            .votes
+            .iter()
+            .find(|v| v.voter.eq_ignore_ascii_case(voter))
+            .ok_or_else(|| DaoError::InvalidParameter("Voter has not voted".to_string()))?;
+        
+        receipts::receipt(proposal_id, vote)
+    }
+    
     /// Execute a proposal
-    pub async fn execute_proposal(&self, proposal_id: &ProposalId) -> Result<()> {
+    pub async fn execute_proposal(&self, proposal_id: &ProposalId, executor: &str) -> Result<()> {
         // Load the proposal
-        let mut proposal = self.get_proposal(proposal_id).await?;
+        let mut proposal = self.get_proposal_for_update(proposal_id).await?;
         
-        // Check if the proposal is in the approved state
-        if proposal.state != ProposalState::Approved {
-            return Err(DaoError::InvalidParameter(
-                "Proposal is not in the approved state".to_string(),
-            ));
+        // Queued proposals become executable once their timelock has passed
+        release_timelock(&mut proposal, Utc::now())?;
+        
+        // Only approved proposals can be executed
+        check_transition(&proposal, ProposalState::Executed)?;
+        
+        // Re-check on-chain preconditions that may have changed since approval
+        if self.config().governance.execution_guard.enabled {
+            self.check_execution_guard(&proposal).await?;
         }
         
         // Execute the proposal based on its type
         match proposal.proposal_type {
             ProposalType::Transfer { to, amount, token } => {
                 // Execute the transfer
-                self.blockchain
-                    .send_transaction(&to, amount)
+                self.execution_limiter
+                    .run(self.blockchain.send_transaction(&to, amount))
                     .await
                     .map_err(|e| DaoError::BlockchainError(e))?;
             }
@@ -308,31 +851,177 @@ impl ProposalManager {
                 function,
                 args,
             } => {
+                // The allowlist may have been tightened since submission
+                targets::check_callable_target(&contract, &function, &self.config().governance)?;
+                
                 // Call the contract function
-                self.blockchain
-                    .call_contract(&contract, &function, &args)
+                self.execution_limiter
+                    .run(self.blockchain.call_contract(&contract, &function, &args, None))
                     .await
-                    .map_err(|e| DaoError::BlockchainError(e))?;
+                    .map_err(|e| DaoError::BlockchainError(e.to_string()))?;
+            }
+            ProposalType::ParameterChange { parameter, value } if parameter == governance::VOTE_WEIGHT_OVERRIDE_PARAMETER => {
+                // Set or clear a member's fixed voting weight
+                let change = governance::parse_override_change(&value)?;
+                let authority = governance::OverrideAuthority::Proposal {
+                    proposal_id: proposal_id.clone(),
+                };
+                governance::record_override(&self.database, &change.address, change.weight, &authority).await?;
             }
             ProposalType::ParameterChange { parameter, value } => {
-                // Update the parameter
-                // This would typically involve a governance contract call
+                // Write the amended value to the config and record which
+                // proposal set it; later reads of the config see it
+                let config_manager = self.config_manager.as_ref().ok_or_else(|| {
+                    DaoError::NotSupported("Parameter changes require a config manager".to_string())
+                })?;
+                governance::amend(config_manager, &self.database, &parameter, &value, proposal_id).await?;
+            }
+            ProposalType::Swap { .. } => {
+                // Swaps are routed through an exchange integration
                 return Err(DaoError::NotSupported(
-                    "Parameter changes not yet implemented".to_string(),
+                    "Swap execution not yet implemented".to_string(),
                 ));
             }
+            ProposalType::MultiChoice { .. } => {
+                // The chosen option is recorded in `winning_option`; acting on it happens off-chain
+            }
             ProposalType::TextProposal { .. } => {
                 // Text proposals don't require execution
             }
         }
         
         // Update the proposal state
+        let previous_state = proposal.state;
         proposal.state = ProposalState::Executed;
         proposal.execution_date = Some(Utc::now());
         proposal.updated_at = Utc::now();
         
         // Save the updated proposal
-        self.save_proposal(&proposal).await?;
+        self.save_proposal(&proposal, executor).await?;
+        
+        self.emit_event(EventType::ProposalUpdated, state_event_data(&proposal, previous_state));
+        
+        Ok(())
+    }
+    
+    /// Comment on a proposal, optionally replying to another comment
+    pub async fn add_comment(
+        &self,
+        proposal_id: &ProposalId,
+        author: &str,
+        body: &str,
+        parent_comment_id: Option<String>,
+    ) -> Result<ProposalComment> {
+        comments::validate_body(body)?;
+        
+        // Only members in good standing take part in the discussion
+        identity::require_standing(&self.database.primary(), author, false).await?;
+        
+        // The proposal must exist
+        self.get_proposal(proposal_id).await?;
+        
+        // A reply must be to a comment on the same proposal
+        if let Some(parent_id) = &parent_comment_id {
+            match comments::load_comment(&self.database, parent_id).await? {
+                Some(parent) if &parent.proposal_id == proposal_id => {}
+                _ => {
+                    return Err(DaoError::InvalidParameter(format!(
+                        "Comment {} not found on this proposal",
+                        parent_id
+                    )))
+                }
+            }
+        }
+        
+        let comment = ProposalComment::new(proposal_id, author, body, parent_comment_id);
+        comments::insert_comment(&self.database, &comment).await?;
+        
+        Ok(comment)
+    }
+    
+    /// Get the comments on a proposal in thread order
+    ///
+    /// See [`thread_order`]. Deleted comments are included with an empty
+    /// body so their replies keep their context.
+    pub async fn get_comments(&self, proposal_id: &ProposalId) -> Result<Vec<ProposalComment>> {
+        let comments = comments::load_comments(&self.database, proposal_id).await?;
+        Ok(thread_order(comments))
+    }
+    
+    /// Delete a comment; only its author or an admin may do so
+    pub async fn delete_comment(&self, comment_id: &str, actor: &str) -> Result<()> {
+        let comment = comments::load_comment(&self.database, comment_id)
+            .await?
+            .ok_or_else(|| DaoError::InvalidParameter(format!("Comment {} not found", comment_id)))?;
+        
+        if !comments::can_delete(&comment, actor, self.member_role(actor).await?) {
+            return Err(DaoError::Unauthorized);
+        }
+        
+        comments::mark_deleted(&self.database, comment_id).await
+    }
+    
+    /// Store a new proposal template; only council members and admins may
+    /// do so
+    pub async fn create_template(&self, mut template: ProposalTemplate, actor: &str) -> Result<ProposalTemplate> {
+        self.require_template_manager(actor).await?;
+        templates::validate(&template)?;
+        
+        let now = Utc::now();
+        template.name = template.name.trim().to_string();
+        template.created_by = actor.to_string();
+        template.created_at = now;
+        template.updated_at = now;
+        templates::insert_template(&self.database, &template, actor).await?;
+        
+        Ok(template)
+    }
+    
+    /// Get every proposal template, by name
+    pub async fn list_templates(&self) -> Result<Vec<ProposalTemplate>> {
+        templates::load_templates(&self.database).await
+    }
+    
+    /// Get a proposal template
+    pub async fn get_template(&self, id: &str) -> Result<ProposalTemplate> {
+        templates::load_template(&self.database, id).await
+    }
+    
+    /// Replace a proposal template's contents; only council members and
+    /// admins may do so
+    ///
+    /// The template's creator and creation time are kept.
+    pub async fn update_template(&self, template: ProposalTemplate, actor: &str) -> Result<ProposalTemplate> {
+        self.require_template_manager(actor).await?;
+        templates::validate(&template)?;
+        
+        let existing = templates::load_template(&self.database, &template.id).await?;
+        let template = ProposalTemplate {
+            name: template.name.trim().to_string(),
+            created_by: existing.created_by,
+            created_at: existing.created_at,
+            updated_at: Utc::now(),
+            ..template
+        };
+        templates::update_template(&self.database, &template, actor).await?;
+        
+        Ok(template)
+    }
+    
+    /// Delete a proposal template; only council members and admins may do
+    /// so
+    ///
+    /// Proposals already created from it are unaffected.
+    pub async fn delete_template(&self, id: &str, actor: &str) -> Result<()> {
+        self.require_template_manager(actor).await?;
+        templates::delete_template(&self.database, id).await
+    }
+    
+    /// Fail unless `actor` may manage proposal templates
+    async fn require_template_manager(&self, actor: &str) -> Result<()> {
+        if !templates::can_manage(self.member_role(actor).await?) {
+            return Err(DaoError::Unauthorized);
+        }
         
         Ok(())
     }
@@ -340,7 +1029,7 @@ impl ProposalManager {
     /// Process proposals (check voting periods, finalize votes, etc.)
     pub async fn process_proposals(&self) -> Result<()> {
         // Get all active proposals
-        let mut proposals = self.get_proposals(Some(ProposalState::Voting)).await?;
+        let mut proposals = self.get_proposals(Some(ProposalState::Voting), false).await?;
         
         let now = Utc::now();
         
@@ -354,59 +1043,120 @@ impl ProposalManager {
             }
         }
         
+        // Mark queued proposals whose timelock has passed as ready
+        for mut proposal in self.get_proposals(Some(ProposalState::Queued), false).await? {
+            if release_timelock(&mut proposal, now).is_ok() {
+                proposal.updated_at = now;
+                self.save_proposal(&proposal, SYSTEM_ACTOR).await?;
+                self.emit_event(EventType::ProposalUpdated, state_event_data(&proposal, ProposalState::Queued));
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Sponsor a draft proposal
+    ///
+    /// Each member may sponsor a proposal once, and proposers cannot sponsor
+    /// their own proposals. The sponsor's current voting weight is recorded.
+    pub async fn sponsor(&self, proposal_id: &ProposalId, member: &Address) -> Result<()> {
+        // Suspended and inactive members can't sponsor
+        identity::require_standing(
+            &self.database.primary(),
+            member.as_str(),
+            self.config().identity.allow_unregistered_voters,
+        )
+        .await?;
+        
+        // Load the proposal
+        let mut proposal = self.get_proposal_for_update(proposal_id).await?;
+        
+        // Get the sponsor's voting weight
+        let weight = self
+            .blockchain
+            .balance(member.as_str())
+            .await
+            .map_err(|e| DaoError::BlockchainError(e))?;
+        
+        // Record the sponsorship
+        add_sponsor(&mut proposal, member, weight, self.config().governance.min_sponsor_weight)?;
+        
+        // Save the updated proposal
+        proposal.updated_at = Utc::now();
+        self.save_proposal(&proposal, member.as_str()).await?;
+        
         Ok(())
     }
     
     /// Start the voting period for a proposal
-    pub async fn start_voting(&self, proposal_id: &ProposalId) -> Result<()> {
+    pub async fn start_voting(&self, proposal_id: &ProposalId, actor: &str) -> Result<()> {
         // Load the proposal
-        let mut proposal = self.get_proposal(proposal_id).await?;
+        let mut proposal = self.get_proposal_for_update(proposal_id).await?;
         
-        // Check if the proposal is in the draft state
-        if proposal.state != ProposalState::Draft {
-            return Err(DaoError::InvalidParameter(
-                "Proposal is not in the draft state".to_string(),
-            ));
-        }
+        // Only drafts can move to voting
+        check_transition(&proposal, ProposalState::Voting)?;
+        
+        // Check if the proposal has gathered enough sponsors
+        check_sponsorship(&proposal, self.config().governance.required_sponsors)?;
+        
+        // Voting power is measured at the current block for the whole vote
+        let snapshot_block = self
+            .blockchain
+            .get_block_number(None)
+            .await
+            .map_err(|e| DaoError::BlockchainError(e.to_string()))?;
         
         // Set the voting period
         let now = Utc::now();
-        let voting_period = chrono::Duration::days(self.config.governance.voting_period_days as i64);
+        let voting_period = chrono::Duration::days(self.config().governance.voting_period_days as i64);
         
+        let previous_state = proposal.state;
         proposal.state = ProposalState::Voting;
         proposal.voting_starts_at = Some(now);
         proposal.voting_ends_at = Some(now + voting_period);
+        proposal.snapshot_block = Some(snapshot_block);
         proposal.updated_at = now;
         
         // Save the updated proposal
-        self.save_proposal(&proposal).await?;
+        self.save_proposal(&proposal, actor).await?;
+        
+        self.emit_event(EventType::ProposalUpdated, state_event_data(&proposal, previous_state));
         
         Ok(())
     }
     
     /// Cancel a proposal
+    ///
+    /// The proposer can cancel it until it is approved. A Council member in
+    /// good standing can also cancel it while it is queued behind the
+    /// execution timelock.
     pub async fn cancel_proposal(&self, proposal_id: &ProposalId, canceller: &str) -> Result<()> {
         // Load the proposal
-        let mut proposal = self.get_proposal(proposal_id).await?;
+        let mut proposal = self.get_proposal_for_update(proposal_id).await?;
         
         // Check if the proposal is in a cancellable state
-        if proposal.state != ProposalState::Draft && proposal.state != ProposalState::Voting {
-            return Err(DaoError::InvalidParameter(
-                "Proposal cannot be cancelled in its current state".to_string(),
-            ));
-        }
+        check_transition(&proposal, ProposalState::Cancelled)?;
         
-        // Check if the canceller is the proposer
+        // The proposer can cancel, and the Council can stop a queued
+        // proposal while its timelock runs
         if proposal.proposer != canceller {
-            return Err(DaoError::Unauthorized);
+            let council_veto = proposal.state == ProposalState::Queued
+                && self.member_role(canceller).await? == Some(MemberRole::Council);
+            if !council_veto {
+                return Err(DaoError::Unauthorized);
+            }
+            identity::require_standing(&self.database.primary(), canceller, false).await?;
         }
         
         // Update the proposal state
+        let previous_state = proposal.state;
         proposal.state = ProposalState::Cancelled;
         proposal.updated_at = Utc::now();
         
         // Save the updated proposal
-        self.save_proposal(&proposal).await?;
+        self.save_proposal(&proposal, canceller).await?;
+        
+        self.emit_event(EventType::ProposalUpdated, state_event_data(&proposal, previous_state));
         
         Ok(())
     }
@@ -429,6 +1179,9 @@ impl ProposalManager {
             ));
         }
         
+        // Check the attachments
+        attachments::validate_attachments(&proposal.attachments, &self.config().governance.attachments)?;
+        
         // Validate based on proposal type
         match &proposal.proposal_type {
             ProposalType::Transfer { to, amount, token } => {
@@ -471,6 +1224,9 @@ impl ProposalManager {
                         "Function name cannot be empty".to_string(),
                     ));
                 }
+                
+                // Check the target against the allowlist
+                targets::check_callable_target(contract, function, &self.config().governance)?;
             }
             ProposalType::ParameterChange { parameter, value } => {
                 // Check if the parameter is valid
@@ -480,6 +1236,13 @@ impl ProposalManager {
                     ));
                 }
                 
+                if parameter == governance::VOTE_WEIGHT_OVERRIDE_PARAMETER {
+                    governance::parse_override_change(value)?;
+                } else {
+                    // Reject unknown parameters and bad values before voting
+                    governance::apply_parameter(&mut Config::clone(&self.config()), parameter, value)?;
+                }
+                
                 // Check if the value is valid
                 if value.is_null() {
                     return Err(DaoError::InvalidParameter(
@@ -487,6 +1250,27 @@ impl ProposalManager {
                     ));
                 }
             }
+            ProposalType::Swap { swaps } => {
+                // Check that there is something to swap
+                if swaps.is_empty() {
+                    return Err(DaoError::InvalidParameter(
+                        "Swap proposal must contain at least one swap".to_string(),
+                    ));
+                }
+                
+                // Check each swap
+                for swap in swaps {
+                    if swap.amount == 0 || swap.from_token == swap.to_token {
+                        return Err(DaoError::InvalidParameter(format!(
+                            "Invalid swap of {} {} to {}",
+                            swap.amount, swap.from_token, swap.to_token
+                        )));
+                    }
+                }
+            }
+            ProposalType::MultiChoice { options } => {
+                multichoice::validate_options(options)?;
+            }
             ProposalType::TextProposal { .. } => {
                 // Text proposals don't require additional validation
             }
@@ -495,43 +1279,67 @@ impl ProposalManager {
         Ok(())
     }
     
-    /// Save a proposal to the database
-    async fn save_proposal(&self, proposal: &Proposal) -> Result<()> {
+    /// Save a proposal to the database, recording `actor` as its last writer
+    pub(crate) async fn save_proposal(&self, proposal: &Proposal, actor: &str) -> Result<()> {
+        Self::store_proposal(&self.database, proposal, actor).await?;
+        
+        // Any cached copy is now stale
+        self.cache.invalidate(&proposal.id);
+        
+        Ok(())
+    }
+    
+    /// Insert or update a proposal using the given database handle
+    ///
+    /// Portable: runs on PostgreSQL and SQLite.
+    async fn store_proposal(database: &Database, proposal: &Proposal, actor: &str) -> Result<()> {
         // Serialize the proposal
         let data = serde_json::to_value(proposal)
             .map_err(|e| DaoError::DatabaseError(format!("Failed to serialize proposal: {}", e)))?;
+        let state = serde_json::to_string(&proposal.state).unwrap();
         
         // Check if the proposal already exists
-        let exists = self
-            .database
-            .query_opt("SELECT 1 FROM proposals WHERE id = $1", &[&proposal.id])
+        let exists = database
+            .query_opt_any("SELECT 1 FROM proposals WHERE id = $1", &[proposal.id.as_str().into()])
             .await?
             .is_some();
         
         if exists {
-            // Update the proposal
-            self.database
-                .execute(
-                    "UPDATE proposals SET data = $1, state = $2, updated_at = $3 WHERE id = $4",
+            // Update the proposal; a deleted proposal stays as it was
+            let updated = database
+                .execute_any(
+                    "UPDATE proposals SET data = $1, state = $2, title = $3, description = $4, updated_at = $5, \
+                     updated_by = $6 WHERE id = $7 AND deleted_at IS NULL",
                     &[
-                        &data,
-                        &serde_json::to_string(&proposal.state).unwrap(),
-                        &proposal.updated_at,
-                        &proposal.id,
+                        data.into(),
+                        state.into(),
+                        proposal.title.as_str().into(),
+                        proposal.description.as_str().into(),
+                        proposal.updated_at.into(),
+                        actor.into(),
+                        proposal.id.as_str().into(),
                     ],
                 )
                 .await?;
+            
+            if updated == 0 {
+                return Err(DaoError::ProposalError(format!("Proposal not found: {}", proposal.id)));
+            }
         } else {
             // Insert the proposal
-            self.database
-                .execute(
-                    "INSERT INTO proposals (id, data, state, created_at, updated_at) VALUES ($1, $2, $3, $4, $5)",
+            database
+                .execute_any(
+                    "INSERT INTO proposals (id, data, state, title, description, created_at, updated_at, updated_by) \
+                     VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
                     &[
-                        &proposal.id,
-                        &data,
-                        &serde_json::to_string(&proposal.state).unwrap(),
-                        &proposal.created_at,
-                        &proposal.updated_at,
+                        proposal.id.as_str().into(),
+                        data.into(),
+                        state.into(),
+                        proposal.title.as_str().into(),
+                        proposal.description.as_str().into(),
+                        proposal.created_at.into(),
+                        proposal.updated_at.into(),
+                        actor.into(),
                     ],
                 )
                 .await?;
@@ -540,33 +1348,2089 @@ impl ProposalManager {
         Ok(())
     }
     
+    /// Insert a new proposal with the next proposal number
+    ///
+    /// The insert and the sequence bump are applied together, so a failed
+    /// insert leaves no gap. A submission that loses the race for a number
+    /// to a concurrent one retries with the next number. Portable: runs on
+    /// PostgreSQL and SQLite.
+    async fn insert_proposal(&self, proposal: &Proposal) -> Result<u64> {
+        let database = self.database.primary();
+        let mut proposal = proposal.clone();
+        
+        for _ in 0..NUMBERING_ATTEMPTS {
+            let number = next_proposal_number(&database).await?;
+            proposal.number = Some(number);
+            
+            let data = serde_json::to_value(&proposal)
+                .map_err(|e| DaoError::DatabaseError(format!("Failed to serialize proposal: {}", e)))?;
+            
+            let affected = database
+                .execute_batch_any(&[
+                    (
+                        "INSERT INTO proposals (id, number, data, state, title, description, created_at, updated_at, updated_by) \
+                         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9) ON CONFLICT (number) DO NOTHING",
+                        vec![
+                            proposal.id.as_str().into(),
+                            (number as i64).into(),
+                            data.into(),
+                            serde_json::to_string(&proposal.state).unwrap().into(),
+                            proposal.title.as_str().into(),
+                            proposal.description.as_str().into(),
+                            proposal.created_at.into(),
+                            proposal.updated_at.into(),
+                            proposal.proposer.as_str().into(),
+                        ],
+                    ),
+                    (
+                        "UPDATE proposal_sequence SET last_number = $1 WHERE id = 1 AND last_number < $1",
+                        vec![(number as i64).into()],
+                    ),
+                ])
+                .await?;
+            
+            if affected[0] == 1 {
+                return Ok(number);
+            }
+        }
+        
+        Err(DaoError::ProposalError(
+            "Could not allocate a proposal number, please retry".to_string(),
+        ))
+    }
+    
     /// Finalize the vote for a proposal
     async fn finalize_vote(&self, proposal: &mut Proposal) -> Result<()> {
-        // Calculate the total votes
-        let total_votes = proposal.yes_votes + proposal.no_votes + proposal.abstain_votes;
+        // Quorum and veto are measured against the supply, not just the votes cast
+        let eligible_supply = self.eligible_supply(proposal).await?;
+        
+        let mut outcome = tally_outcome(proposal, eligible_supply, &self.config().governance);
+        
+        // A multiple-choice proposal passes only if one option wins
+        if let ProposalType::MultiChoice { options } = &proposal.proposal_type {
+            let winner = multichoice::winning_option(proposal, options.len(), self.config().governance.multi_choice_tally);
+            if winner.is_none() {
+                outcome = ProposalState::Rejected;
+            }
+            proposal.winning_option = winner.filter(|_| outcome == ProposalState::Approved);
+        }
         
-        // Calculate the quorum
-        let quorum_threshold = (self.config.governance.quorum_percentage as u64 * total_votes) / 100;
+        // An approval is queued behind the execution timelock
+        let outcome = schedule_execution(proposal, outcome, self.config().governance.timelock_seconds, Utc::now());
         
-        // Calculate the majority
-        let majority_threshold =
-            (self.config.governance.majority_percentage as u64 * (proposal.yes_votes + proposal.no_votes)) / 100;
+        check_transition(proposal, outcome)?;
+        let previous_state = proposal.state;
+        proposal.state = outcome;
+        proposal.participation = Some(participation(proposal, eligible_supply));
         
-        // Check if the proposal has reached quorum
-        if total_votes < quorum_threshold {
-            proposal.state = ProposalState::Rejected;
-            proposal.updated_at = Utc::now();
-            return self.save_proposal(proposal).await;
+        // Snapshot the target contract so an upgrade before execution is detected
+        if matches!(proposal.state, ProposalState::Queued | ProposalState::Approved)
+            && self.config().governance.execution_guard.enabled
+        {
+            if let ProposalType::ContractCall { contract, .. } = &proposal.proposal_type {
+                proposal.target_code_hash = Some(self.code_hash(contract).await?);
+            }
         }
         
-        // Check if the proposal has reached majority
-        if proposal.yes_votes >= majority_threshold {
-            proposal.state = ProposalState::Approved;
-        } else {
-            proposal.state = ProposalState::Rejected;
+        proposal.updated_at = Utc::now();
+        self.save_proposal(proposal, SYSTEM_ACTOR).await?;
+        
+        self.emit_event(EventType::ProposalUpdated, state_event_data(proposal, previous_state));
+        
+        Ok(())
+    }
+    
+    /// Approve a proposal whose conviction crossed its threshold
+    ///
+    /// Conviction replaces the tally, so the proposal passes while voting is
+    /// still open; the approval is queued behind the timelock as usual.
+    pub(crate) async fn pass_by_conviction(&self, proposal_id: &ProposalId) -> Result<()> {
+        let mut proposal = self.get_proposal_for_update(proposal_id).await?;
+        
+        if proposal.state != ProposalState::Voting {
+            return Err(DaoError::InvalidParameter(
+                "Proposal is not in the voting state".to_string(),
+            ));
         }
         
+        let outcome = schedule_execution(
+            &mut proposal,
+            ProposalState::Approved,
+            self.config().governance.timelock_seconds,
+            Utc::now(),
+        );
+        check_transition(&proposal, outcome)?;
+        let previous_state = proposal.state;
+        proposal.state = outcome;
+        
         proposal.updated_at = Utc::now();
-        self.save_proposal(proposal).await
+        self.save_proposal(&proposal, SYSTEM_ACTOR).await?;
+        
+        self.emit_event(EventType::ProposalUpdated, state_event_data(&proposal, previous_state));
+        
+        Ok(())
+    }
+    
+    /// Broadcast an event if an event sender is configured
+    fn emit_event(&self, event_type: EventType, data: serde_json::Value) {
+        send_event(self.event_sender.as_ref(), event_type, data);
+    }
+    
+    /// Re-check a proposal's on-chain preconditions before execution
+    ///
+    /// A failed check blocks execution with the reason and raises an alert.
+    async fn check_execution_guard(&self, proposal: &Proposal) -> Result<()> {
+        let config = self.config();
+        let guard = &config.governance.execution_guard;
+        
+        let current_code_hash = match &proposal.proposal_type {
+            ProposalType::ContractCall { contract, .. } => Some(self.code_hash(contract).await?),
+            _ => None,
+        };
+        
+        let available_balance = match (&proposal.proposal_type, &guard.executor_address) {
+            (ProposalType::Transfer { token, .. }, Some(executor)) => Some(self.token_balance(token, executor).await?),
+            _ => None,
+        };
+        
+        if let Err(violation) =
+            check_execution_preconditions(proposal, guard, current_code_hash.as_deref(), available_balance)
+        {
+            warn!("ALERT: execution of proposal {} blocked: {}", proposal.id, violation);
+            return Err(DaoError::ProposalError(format!("Execution blocked: {}", violation)));
+        }
+        
+        Ok(())
+    }
+    
+    /// Get the current balance of an address on the default chain
+    async fn balance(&self, address: &str) -> Result<u64> {
+        let balance = self
+            .blockchain
+            .get_balance(address, None)
+            .await
+            .map_err(|e| DaoError::BlockchainError(e.to_string()))?;
+        
+        parse_balance(&balance)
+    }
+    
+    /// Get an address's balance of the token a transfer spends
+    ///
+    /// Transfers can move any token, so the native balance says nothing
+    /// about whether the executor can cover them.
+    async fn token_balance(&self, token: &str, address: &str) -> Result<u64> {
+        let token_manager = self.token_manager.as_ref().ok_or_else(|| {
+            DaoError::InternalError(
+                "governance.execution_guard.executor_address requires a token manager".to_string(),
+            )
+        })?;
+        
+        token_manager
+            .get_balance(&Symbol::parse(token)?, &Address::parse(address)?)
+            .await
+    }
+    
+    /// Get the current code hash of a contract
+    async fn code_hash(&self, contract: &str) -> Result<String> {
+        self.blockchain
+            .get_code_hash(contract, None)
+            .await
+            .map_err(|e| DaoError::BlockchainError(e.to_string()))
+    }
+    
+    /// Get the power delegated to an address, by delegator
+    async fn delegations_to(&self, delegate: &str) -> Result<Vec<DelegatedPower>> {
+        if !self.config().features.delegation_enabled {
+            return Ok(Vec::new());
+        }
+        
+        let rows = self
+            .database
+            .query_any(
+                "SELECT delegator, amount FROM delegations WHERE delegate = $1",
+                &[delegate.into()],
+            )
+            .await?;
+        
+        rows.iter()
+            .map(|row| {
+                Ok(DelegatedPower {
+                    delegator: row.text("delegator")?,
+                    amount: row.int("amount")? as u64,
+                })
+            })
+            .collect()
+    }
+    
+    /// Voting power of an address at a proposal's snapshot, with the power
+    /// delegated to it by delegator
+    async fn snapshot_voting_power(&self, proposal: &Proposal, voter: &str) -> Result<(u64, Vec<DelegatedPower>)> {
+        // Get the voter's voting power at the snapshot, weighted by their
+        // reputation under reputation-weighted voting, including any fixed
+        // weight override
+        let balance = self.snapshot_balance(proposal, voter).await?;
+        let strategy = governance::configured_strategy(&self.config());
+        let weight = if strategy.uses_reputation() {
+            let reputation = self.member_reputation(voter).await?;
+            strategy.calculate_member_weight(voter, balance, reputation)?.value
+        } else {
+            balance
+        };
+        let own_power = governance::apply_override(
+            weight,
+            governance::current_override(&self.database, voter).await?,
+            self.config().governance.vote_weight_override_mode,
+        );
+        
+        // Delegators who already voted on this proposal keep their own power,
+        // and each delegation counts no more than its delegator held at the
+        // snapshot, so coins moved after delegating are not counted twice
+        let delegations = unclaimed_delegations(proposal, self.delegations_to(voter).await?);
+        let mut held = Vec::with_capacity(delegations.len());
+        for delegation in &delegations {
+            held.push(self.snapshot_balance(proposal, &delegation.delegator).await?);
+        }
+        let delegated = cap_delegations(delegations, &held);
+        let voting_power = own_power + delegated.iter().map(|d| d.amount).sum::<u64>();
+        
+        Ok((voting_power, delegated))
+    }
+    
+    /// Balance of an address at a proposal's snapshot block
+    ///
+    /// Measuring at the snapshot stops the same tokens voting twice after a
+    /// transfer. Proposals opened before snapshots were recorded fall back to
+    /// the live balance.
+    async fn snapshot_balance(&self, proposal: &Proposal, address: &str) -> Result<u64> {
+        let Some(block) = proposal.snapshot_block else {
+            return self.balance(address).await;
+        };
+        
+        let balance = self
+            .blockchain
+            .balance_at_block(address, block, None)
+            .await
+            .map_err(|e| DaoError::BlockchainError(e.to_string()))?;
+        
+        parse_balance(&balance)
+    }
+    
+    /// Reputation of the member with the given address, or 0 for non-members
+    async fn member_reputation(&self, address: &str) -> Result<u64> {
+        let row = self
+            .database
+            .query_opt_any(
+                "SELECT data FROM members WHERE LOWER(address) = LOWER($1) AND deleted_at IS NULL",
+                &[address.into()],
+            )
+            .await?;
+        
+        match row {
+            Some(row) => Ok(row.json::<Member>("data")?.reputation),
+            None => Ok(0),
+        }
+    }
+    
+    /// Get the role of the member with the given address, if registered
+    ///
+    /// Addresses match regardless of case; soft-deleted members have no role.
+    async fn member_role(&self, address: &str) -> Result<Option<MemberRole>> {
+        let row = self
+            .database
+            .query_opt_any(
+                "SELECT data FROM members WHERE LOWER(address) = LOWER($1) AND deleted_at IS NULL",
+                &[address.into()],
+            )
+            .await?;
+        
+        row.map(|row| {
+            row.json::<Member>("data")
+                .map(|member| member.role)
+                .map_err(|e| DaoError::DatabaseError(format!("Failed to parse member: {}", e)))
+        })
+        .transpose()
+    }
+    
+    /// Get the delegate an address has delegated to, if any
+    async fn delegate_of(&self, delegator: &str) -> Result<Option<String>> {
+        if !self.config().features.delegation_enabled {
+            return Ok(None);
+        }
+        
+        let row = self
+            .database
+            .query_opt_any("SELECT delegate FROM delegations WHERE delegator = $1", &[delegator.into()])
+            .await?;
+        
+        row.map(|row| row.text("delegate")).transpose()
+    }
+    
+    /// Get the voting supply quorum and veto thresholds are measured against
+    ///
+    /// Uses the governance token's total or circulating supply, depending on
+    /// `quorum_basis` and `quorum_supply_source`. Under `castvotes` and
+    /// `yesnoonly` it falls back to the weight cast on the proposal when the
+    /// token isn't tracked locally; `circulatingsupply` requires the token.
+    async fn eligible_supply(&self, proposal: &Proposal) -> Result<u64> {
+        let config = self.config();
+        let governance = &config.governance;
+        
+        let total_supply = if governance.quorum_basis == QuorumBasis::CirculatingSupply {
+            let token_manager = self.token_manager.as_ref().ok_or_else(|| {
+                DaoError::InternalError(
+                    "governance.quorum_basis = \"circulatingsupply\" requires a token manager".to_string(),
+                )
+            })?;
+            let symbol = Symbol::parse(&config.dao.governance_token)?;
+            
+            token_manager.get_token(&symbol).await?.total_supply
+        } else {
+            let row = self
+                .database
+                .query_opt_any(
+                    "SELECT data FROM tokens WHERE symbol = $1",
+                    &[config.dao.governance_token.as_str().into()],
+                )
+                .await?;
+            
+            match row {
+                Some(row) => {
+                    let token: Token = row
+                        .json("data")
+                        .map_err(|e| DaoError::DatabaseError(format!("Failed to parse token: {}", e)))?;
+                    token.total_supply
+                }
+                None => return Ok(total_votes(proposal)),
+            }
+        };
+        
+        let source = supply_source(governance);
+        let mut excluded_balances = Vec::new();
+        
+        if source == QuorumSupplySource::Circulating {
+            for address in &governance.quorum_excluded_addresses {
+                excluded_balances.push(self.balance(address).await?);
+            }
+        }
+        
+        Ok(quorum_supply(total_supply, &excluded_balances, source))
     }
-} 
\ No newline at end of file
+} 
+
+/// Reason a proposal's on-chain preconditions no longer hold
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PreconditionViolation {
+    /// Recipient or target is on the denylist
+    DeniedAddress(String),
+    /// No code hash was recorded when the proposal was approved
+    MissingCodeHash(String),
+    /// Target contract code changed since approval
+    CodeHashChanged {
+        contract: String,
+        approved: String,
+        current: String,
+    },
+    /// Executor can no longer cover the transfer
+    InsufficientBalance { required: u64, available: u64 },
+}
+
+impl fmt::Display for PreconditionViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DeniedAddress(address) => write!(f, "address {} is on the denylist", address),
+            Self::MissingCodeHash(contract) => {
+                write!(f, "no code hash was recorded for {} at approval", contract)
+            }
+            Self::CodeHashChanged { contract, approved, current } => write!(
+                f,
+                "code at {} changed since approval ({} -> {})",
+                contract, approved, current
+            ),
+            Self::InsufficientBalance { required, available } => write!(
+                f,
+                "executor token balance {} is below the transfer amount {}",
+                available, required
+            ),
+        }
+    }
+}
+
+/// Check a proposal against the current on-chain state
+///
+/// `current_code_hash` is required for contract calls; `available_balance`
+/// is only checked for transfers when provided.
+fn check_execution_preconditions(
+    proposal: &Proposal,
+    guard: &ExecutionGuardConfig,
+    current_code_hash: Option<&str>,
+    available_balance: Option<u64>,
+) -> std::result::Result<(), PreconditionViolation> {
+    let is_denied = |address: &str| guard.denylist.iter().any(|d| d.eq_ignore_ascii_case(address));
+    
+    match &proposal.proposal_type {
+        ProposalType::Transfer { to, amount, .. } => {
+            if is_denied(to) {
+                return Err(PreconditionViolation::DeniedAddress(to.clone()));
+            }
+            
+            if let Some(available) = available_balance {
+                if available < *amount {
+                    return Err(PreconditionViolation::InsufficientBalance {
+                        required: *amount,
+                        available,
+                    });
+                }
+            }
+        }
+        ProposalType::ContractCall { contract, .. } => {
+            if is_denied(contract) {
+                return Err(PreconditionViolation::DeniedAddress(contract.clone()));
+            }
+            
+            let approved = proposal
+                .target_code_hash
+                .as_deref()
+                .ok_or_else(|| PreconditionViolation::MissingCodeHash(contract.clone()))?;
+            let current = current_code_hash.unwrap_or_default();
+            
+            if !approved.eq_ignore_ascii_case(current) {
+                return Err(PreconditionViolation::CodeHashChanged {
+                    contract: contract.clone(),
+                    approved: approved.to_string(),
+                    current: current.to_string(),
+                });
+            }
+        }
+        ProposalType::ParameterChange { .. }
+        | ProposalType::Swap { .. }
+        | ProposalType::MultiChoice { .. }
+        | ProposalType::TextProposal { .. } => {}
+    }
+    
+    Ok(())
+}
+
+/// Filter proposals down to those a voter can act on, soonest deadline first
+fn votable_proposals(
+    candidates: Vec<(Proposal, u64)>,
+    voter: &str,
+    now: DateTime<Utc>,
+) -> Vec<Proposal> {
+    let mut votable: Vec<Proposal> = candidates
+        .into_iter()
+        .filter(|(_, voting_power)| *voting_power > 0)
+        .map(|(proposal, _)| proposal)
+        .filter(|proposal| {
+            let window_open = match (proposal.voting_starts_at, proposal.voting_ends_at) {
+                (Some(starts), Some(ends)) => starts <= now && now <= ends,
+                _ => false,
+            };
+            
+            proposal.state == ProposalState::Voting
+                && window_open
+                && !proposal.votes.iter().any(|v| v.voter.eq_ignore_ascii_case(voter))
+        })
+        .collect();
+    
+    votable.sort_by_key(|proposal| proposal.voting_ends_at);
+    votable
+}
+
+/// Tally for a vote choice
+fn vote_count_mut(proposal: &mut Proposal, vote: ProposalVote) -> &mut u64 {
+    match vote {
+        ProposalVote::Yes => &mut proposal.yes_votes,
+        ProposalVote::No => &mut proposal.no_votes,
+        ProposalVote::Abstain => &mut proposal.abstain_votes,
+        ProposalVote::Veto => &mut proposal.veto_votes,
+    }
+}
+
+/// Check that a ballot fits the proposal: option rankings are only for,
+/// and yes/no votes not for, multiple-choice proposals
+fn check_ballot(proposal: &Proposal, vote: ProposalVote, ranking: &[usize]) -> Result<()> {
+    match &proposal.proposal_type {
+        ProposalType::MultiChoice { options } if !ranking.is_empty() => {
+            multichoice::validate_ranking(ranking, options.len())
+        }
+        ProposalType::MultiChoice { .. } if matches!(vote, ProposalVote::Yes | ProposalVote::No) => {
+            Err(DaoError::InvalidParameter(
+                "Multiple-choice proposals take a ranking of options instead of yes or no".to_string(),
+            ))
+        }
+        ProposalType::MultiChoice { .. } => Ok(()),
+        _ if !ranking.is_empty() => Err(DaoError::InvalidParameter(
+            "Only multiple-choice proposals take a ranking of options".to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Move a voter's recorded vote to a new choice
+///
+/// The vote's power is taken off the old tally (and the old first option of
+/// a multiple-choice ballot) and added to the new one, so the totals stay
+/// consistent. Rejected once voting has ended.
+fn change_recorded_vote(proposal: &mut Proposal, voter: &str, vote: ProposalVote, now: DateTime<Utc>) -> Result<()> {
+    if proposal.state != ProposalState::Voting {
+        return Err(DaoError::InvalidParameter(
+            "Proposal is not in the voting state".to_string(),
+        ));
+    }
+    
+    let voting_ends_at = proposal
+        .voting_ends_at
+        .ok_or_else(|| DaoError::InternalError("Voting end time not set".to_string()))?;
+    if now > voting_ends_at {
+        return Err(DaoError::InvalidParameter("Voting has ended".to_string()));
+    }
+    
+    check_ballot(proposal, vote, &[])?;
+    
+    let index = proposal
+        .votes
+        .iter()
+        .position(|v| v.voter.eq_ignore_ascii_case(voter))
+        .ok_or_else(|| DaoError::InvalidParameter("Voter has not voted".to_string()))?;
+    
+    let previous = proposal.votes[index].clone();
+    if previous.vote == vote && previous.ranking.is_empty() {
+        return Ok(());
+    }
+    
+    let old_count = vote_count_mut(proposal, previous.vote);
+    *old_count = old_count.saturating_sub(previous.voting_power);
+    if let Some(count) = previous.ranking.first().and_then(|option| proposal.option_votes.get_mut(option)) {
+        *count = count.saturating_sub(previous.voting_power);
+    }
+    *vote_count_mut(proposal, vote) += previous.voting_power;
+    
+    let recorded = &mut proposal.votes[index];
+    recorded.vote = vote;
+    recorded.ranking.clear();
+    recorded.timestamp = now;
+    
+    Ok(())
+}
+
+/// Drop delegations whose delegator already voted on the proposal directly
+fn unclaimed_delegations(proposal: &Proposal, delegations: Vec<DelegatedPower>) -> Vec<DelegatedPower> {
+    delegations
+        .into_iter()
+        .filter(|d| !proposal.votes.iter().any(|v| v.voter.eq_ignore_ascii_case(&d.delegator)))
+        .collect()
+}
+
+/// Limit each delegation to what its delegator held; `held` is in the same order
+///
+/// Delegations left with no power are dropped.
+fn cap_delegations(delegations: Vec<DelegatedPower>, held: &[u64]) -> Vec<DelegatedPower> {
+    delegations
+        .into_iter()
+        .zip(held)
+        .map(|(delegation, &held)| DelegatedPower {
+            amount: delegation.amount.min(held),
+            ..delegation
+        })
+        .filter(|delegation| delegation.amount > 0)
+        .collect()
+}
+
+/// Take a delegator's power back from a delegate who already voted
+///
+/// Only this proposal is affected: the delegate's vote and the tally it went
+/// into are reduced by the delegated amount. Returns the reclaimed amount.
+fn reclaim_delegated_power(proposal: &mut Proposal, delegator: &str) -> u64 {
+    let mut reclaimed = Vec::new();
+    
+    for vote in &mut proposal.votes {
+        if let Some(index) = vote.delegated.iter().position(|d| d.delegator.eq_ignore_ascii_case(delegator)) {
+            let delegation = vote.delegated.remove(index);
+            vote.voting_power = vote.voting_power.saturating_sub(delegation.amount);
+            reclaimed.push((vote.vote, vote.ranking.first().copied(), delegation.amount));
+        }
+    }
+    
+    for (vote, first_option, amount) in &reclaimed {
+        let count = vote_count_mut(proposal, *vote);
+        *count = count.saturating_sub(*amount);
+        
+        if let Some(count) = first_option.and_then(|option| proposal.option_votes.get_mut(&option)) {
+            *count = count.saturating_sub(*amount);
+        }
+    }
+    
+    reclaimed.iter().map(|(_, _, amount)| amount).sum()
+}
+
+/// Total weight cast on a proposal, including abstentions and vetoes
+fn total_votes(proposal: &Proposal) -> u64 {
+    proposal.yes_votes + proposal.no_votes + proposal.abstain_votes + proposal.veto_votes
+}
+
+/// Votes that count toward quorum under the configured basis
+fn quorum_votes(proposal: &Proposal, governance: &GovernanceConfig) -> u64 {
+    match governance.quorum_basis {
+        QuorumBasis::CastVotes | QuorumBasis::CirculatingSupply => total_votes(proposal),
+        QuorumBasis::YesNoOnly => total_votes(proposal) - proposal.abstain_votes,
+    }
+}
+
+/// Supply source under the configured basis
+///
+/// `circulatingsupply` always measures against the circulating supply;
+/// the other bases follow `quorum_supply_source`.
+fn supply_source(governance: &GovernanceConfig) -> QuorumSupplySource {
+    match governance.quorum_basis {
+        QuorumBasis::CirculatingSupply => QuorumSupplySource::Circulating,
+        QuorumBasis::CastVotes | QuorumBasis::YesNoOnly => governance.quorum_supply_source,
+    }
+}
+
+/// Turnout of a vote against the eligible supply
+///
+/// Abstentions are counted as participation whether or not they count
+/// toward quorum, and are reported separately.
+fn participation(proposal: &Proposal, eligible_supply: u64) -> Participation {
+    let rate = |votes: u64| {
+        if eligible_supply == 0 {
+            0.0
+        } else {
+            votes as f64 / eligible_supply as f64
+        }
+    };
+    
+    Participation {
+        eligible_supply,
+        participation_rate: rate(total_votes(proposal)),
+        abstain_rate: rate(proposal.abstain_votes),
+    }
+}
+
+/// Supply eligible to vote under the configured source
+///
+/// Circulating supply is the total minus the excluded balances, and never
+/// goes below zero if those balances are out of date.
+fn quorum_supply(total_supply: u64, excluded_balances: &[u64], source: QuorumSupplySource) -> u64 {
+    match source {
+        QuorumSupplySource::Total => total_supply,
+        QuorumSupplySource::Circulating => excluded_balances
+            .iter()
+            .fold(total_supply, |supply, balance| supply.saturating_sub(*balance)),
+    }
+}
+
+/// Decide the outcome of a finished vote
+///
+/// Veto is evaluated first: if veto weight exceeds the configured share of
+/// the eligible supply, the proposal is rejected whatever the yes/no split.
+/// Otherwise veto votes count as no votes. Quorum is the share of the
+/// eligible supply that voted, abstentions included unless `quorum_basis`
+/// is `yesnoonly`.
+fn tally_outcome(proposal: &Proposal, eligible_supply: u64, governance: &GovernanceConfig) -> ProposalState {
+    let veto_threshold = governance.veto_threshold_percent as u128;
+    if veto_threshold > 0 && proposal.veto_votes as u128 * 100 > veto_threshold * eligible_supply as u128 {
+        return ProposalState::Rejected;
+    }
+    
+    let quorum_votes = quorum_votes(proposal, governance);
+    
+    // Check if the proposal has reached quorum
+    if (quorum_votes as u128) * 100 < governance.quorum_percentage as u128 * eligible_supply as u128 {
+        return ProposalState::Rejected;
+    }
+    
+    // Check if the proposal has reached majority
+    if majority_reached(proposal, governance) {
+        ProposalState::Approved
+    } else {
+        ProposalState::Rejected
+    }
+}
+
+/// Whether yes votes currently meet the majority line (veto counts as no)
+fn majority_reached(proposal: &Proposal, governance: &GovernanceConfig) -> bool {
+    let against = proposal.no_votes + proposal.veto_votes;
+    let majority_threshold = (governance.majority_percentage as u64 * (proposal.yes_votes + against)) / 100;
+    
+    proposal.yes_votes >= majority_threshold
+}
+
+/// Extend voting when a vote inside the quiet-ending window flips the outcome
+///
+/// Returns whether the deadline was extended. Extensions stop once the
+/// configured maximum is reached.
+fn extend_quiet_ending(
+    proposal: &mut Proposal,
+    passing_before: bool,
+    voter: &str,
+    now: DateTime<Utc>,
+    governance: &GovernanceConfig,
+) -> bool {
+    let window = chrono::Duration::seconds(governance.quiet_ending_window_seconds as i64);
+    let Some(ends_at) = proposal.voting_ends_at else {
+        return false;
+    };
+    
+    if window.is_zero() || ends_at - now > window {
+        return false;
+    }
+    
+    let passing_after = majority_reached(proposal, governance);
+    if passing_after == passing_before {
+        return false;
+    }
+    
+    if proposal.extensions.len() as u32 >= governance.max_quiet_ending_extensions {
+        return false;
+    }
+    
+    let new_ends_at = ends_at + window;
+    proposal.extensions.push(VotingExtension {
+        previous_ends_at: ends_at,
+        new_ends_at,
+        triggered_by: voter.to_string(),
+        reason: format!(
+            "Vote flipped the outcome to {} within {}s of the deadline",
+            if passing_after { "passing" } else { "failing" },
+            governance.quiet_ending_window_seconds
+        ),
+    });
+    proposal.voting_ends_at = Some(new_ends_at);
+    
+    true
+}
+
+/// Check that the proposal state machine allows moving a proposal to `to`
+fn check_transition(proposal: &Proposal, to: ProposalState) -> Result<()> {
+    if !proposal.state.can_transition_to(to) {
+        return Err(DaoError::InvalidTransition {
+            from: proposal.state,
+            to,
+        });
+    }
+    
+    Ok(())
+}
+
+/// Broadcast an event on a channel, if there is one
+fn send_event(sender: Option<&broadcast::Sender<WebSocketEvent>>, event_type: EventType, data: serde_json::Value) {
+    if let Some(sender) = sender {
+        // Sending only fails when there are no subscribers
+        let _ = sender.send(WebSocketManager::create_event(event_type, data));
+    }
+}
+
+/// Event data for a vote cast or changed on a proposal
+fn vote_event_data(proposal: &Proposal, voter: &str) -> serde_json::Value {
+    let vote = proposal.votes.iter().find(|vote| vote.voter.eq_ignore_ascii_case(voter));
+    
+    serde_json::json!({
+        "proposal_id": proposal.id,
+        "state": proposal.state,
+        "voter": voter,
+        "vote": vote.map(|vote| vote.vote),
+        "voting_power": vote.map(|vote| vote.voting_power),
+        "yes_votes": proposal.yes_votes,
+        "no_votes": proposal.no_votes,
+        "abstain_votes": proposal.abstain_votes,
+        "veto_votes": proposal.veto_votes,
+    })
+}
+
+/// Event data for a proposal state change
+fn state_event_data(proposal: &Proposal, previous_state: ProposalState) -> serde_json::Value {
+    serde_json::json!({
+        "proposal_id": proposal.id,
+        "state": proposal.state,
+        "previous_state": previous_state,
+    })
+}
+
+/// State a finished vote moves to, given the execution timelock
+///
+/// An approval sets when the proposal becomes executable and, with a
+/// nonzero timelock, is queued until then.
+fn schedule_execution(proposal: &mut Proposal, outcome: ProposalState, timelock_seconds: u64, now: DateTime<Utc>) -> ProposalState {
+    if outcome != ProposalState::Approved {
+        return outcome;
+    }
+    
+    proposal.execution_available_at = Some(now + chrono::Duration::seconds(timelock_seconds as i64));
+    
+    if timelock_seconds > 0 {
+        ProposalState::Queued
+    } else {
+        ProposalState::Approved
+    }
+}
+
+/// Move a queued proposal to approved once its timelock has passed
+///
+/// Fails while the timelock is still running.
+fn release_timelock(proposal: &mut Proposal, now: DateTime<Utc>) -> Result<()> {
+    let timelocked = matches!(proposal.state, ProposalState::Queued | ProposalState::Approved);
+    
+    if let Some(available_at) = proposal.execution_available_at.filter(|_| timelocked) {
+        if now < available_at {
+            return Err(DaoError::ProposalError(format!(
+                "Proposal is timelocked and cannot be executed until {}",
+                available_at.to_rfc3339()
+            )));
+        }
+    }
+    
+    if proposal.state == ProposalState::Queued {
+        check_transition(proposal, ProposalState::Approved)?;
+        proposal.state = ProposalState::Approved;
+    }
+    
+    Ok(())
+}
+
+/// Record a sponsor endorsement on a draft proposal
+fn add_sponsor(proposal: &mut Proposal, member: &Address, weight: u64, min_weight: u64) -> Result<()> {
+    if proposal.state != ProposalState::Draft {
+        return Err(DaoError::InvalidParameter(
+            "Only draft proposals can be sponsored".to_string(),
+        ));
+    }
+    
+    // Stored addresses may predate checksumming, so compare normalized forms
+    let is_member = |stored: &str| Address::parse(stored).map_or(false, |stored| stored == *member);
+    
+    if is_member(&proposal.proposer) {
+        return Err(DaoError::InvalidParameter(
+            "Proposers cannot sponsor their own proposal".to_string(),
+        ));
+    }
+    
+    if proposal.sponsors.iter().any(|s| is_member(&s.member)) {
+        return Err(DaoError::InvalidParameter(
+            "Member has already sponsored this proposal".to_string(),
+        ));
+    }
+    
+    if weight == 0 || weight < min_weight {
+        return Err(DaoError::Unauthorized);
+    }
+    
+    proposal.sponsors.push(Sponsor {
+        member: member.as_str().to_string(),
+        weight,
+        timestamp: Utc::now(),
+    });
+    
+    Ok(())
+}
+
+/// Parse a balance reported by the chain
+///
+/// Balances beyond `u64::MAX` saturate; thresholds and tallies are `u64`.
+pub(crate) fn parse_balance(balance: &str) -> Result<u64> {
+    let balance: u128 = balance
+        .parse()
+        .map_err(|e| DaoError::BlockchainError(format!("Invalid balance {:?}: {}", balance, e)))?;
+    
+    Ok(u64::try_from(balance).unwrap_or(u64::MAX))
+}
+
+/// The number the next proposal would take
+async fn next_proposal_number(database: &Database) -> Result<u64> {
+    let row = database
+        .query_one_any("SELECT last_number FROM proposal_sequence WHERE id = 1", &[])
+        .await
+        .map_err(|e| DaoError::DatabaseError(format!("Failed to allocate proposal number: {}", e)))?;
+    
+    Ok(row.int("last_number")? as u64 + 1)
+}
+
+/// Parse a display id (`AIP-42`, case-insensitive) or bare number
+///
+/// Returns `None` for anything else, such as a UUID.
+fn parse_proposal_number(reference: &str, prefix: &str) -> Option<u64> {
+    let number = match reference.split_once('-') {
+        Some((reference_prefix, number)) if reference_prefix.eq_ignore_ascii_case(prefix) => number,
+        _ => reference,
+    };
+    
+    if number.is_empty() || !number.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    
+    number.parse().ok()
+}
+
+/// Check that a proposal has gathered the required number of sponsors
+fn check_sponsorship(proposal: &Proposal, required_sponsors: u32) -> Result<()> {
+    if (proposal.sponsors.len() as u32) < required_sponsors {
+        return Err(DaoError::ProposalError(format!(
+            "Proposal has {} of {} required sponsors",
+            proposal.sponsors.len(),
+            required_sponsors
+        )));
+    }
+    
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::MockBlockchain;
+    
+    const PROPOSER: &str = "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266";
+    const SPONSOR_1: &str = "0x70997970c51812dc3a010c7d01b50e0d17dc79c8";
+    const SPONSOR_2: &str = "0x3c44cdddb6a900fa2b585dd299e03d12fa4293bc";
+    
+    fn address(value: &str) -> Address {
+        Address::parse(value).unwrap()
+    }
+    
+    fn draft_proposal() -> Proposal {
+        ProposalBuilder::new()
+            .title("Fund the grants program")
+            .description("Allocate funds for community grants")
+            .proposal_type(ProposalType::TextProposal {
+                metadata: serde_json::Value::Null,
+            })
+            .proposer(PROPOSER)
+            .build()
+            .unwrap()
+    }
+    
+    #[test]
+    fn test_insufficient_sponsors_block_voting() {
+        let mut proposal = draft_proposal();
+        add_sponsor(&mut proposal, &address(SPONSOR_1), 500, 1).unwrap();
+        
+        assert!(matches!(
+            check_sponsorship(&proposal, 2),
+            Err(DaoError::ProposalError(_))
+        ));
+        
+        add_sponsor(&mut proposal, &address(SPONSOR_2), 300, 1).unwrap();
+        assert!(check_sponsorship(&proposal, 2).is_ok());
+        assert_eq!(proposal.sponsors[0].weight, 500);
+    }
+    
+    #[test]
+    fn test_duplicate_sponsorship_rejected() {
+        let mut proposal = draft_proposal();
+        add_sponsor(&mut proposal, &address(SPONSOR_1), 500, 1).unwrap();
+        
+        assert!(add_sponsor(&mut proposal, &address(SPONSOR_1), 500, 1).is_err());
+        
+        // A sponsorship recorded in another case is still the same member
+        proposal.sponsors[0].member = SPONSOR_1.to_uppercase().replacen("0X", "0x", 1);
+        assert!(add_sponsor(&mut proposal, &address(SPONSOR_1), 500, 1).is_err());
+        assert_eq!(proposal.sponsors.len(), 1);
+    }
+    
+    #[test]
+    fn test_proposer_cannot_sponsor_own_proposal() {
+        let mut proposal = draft_proposal();
+        
+        // The proposer was recorded in lowercase; the sponsor is checksummed
+        assert!(add_sponsor(&mut proposal, &address(PROPOSER), 500, 1).is_err());
+        assert!(matches!(
+            add_sponsor(&mut proposal, &address(SPONSOR_1), 5, 10),
+            Err(DaoError::Unauthorized)
+        ));
+        assert!(proposal.sponsors.is_empty());
+    }
+    
+    #[test]
+    fn test_veto_overrides_passing_majority() {
+        let mut proposal = draft_proposal();
+        proposal.yes_votes = 600;
+        proposal.no_votes = 100;
+        proposal.veto_votes = 150;
+        
+        let mut governance = GovernanceConfig::default();
+        assert_eq!(tally_outcome(&proposal, 1_000, &governance), ProposalState::Approved);
+        
+        // 150 of 1000 eligible exceeds a 10% veto threshold
+        governance.veto_threshold_percent = 10;
+        assert_eq!(tally_outcome(&proposal, 1_000, &governance), ProposalState::Rejected);
+    }
+    
+    #[test]
+    fn test_veto_below_threshold_counts_as_no() {
+        let mut proposal = draft_proposal();
+        proposal.yes_votes = 500;
+        proposal.no_votes = 200;
+        proposal.veto_votes = 300;
+        
+        let mut governance = GovernanceConfig::default();
+        governance.veto_threshold_percent = 40;
+        governance.quorum_percentage = 10;
+        
+        // 300 of 10_000 is under the threshold, but 500 yes is short of 51% of 1000
+        assert_eq!(tally_outcome(&proposal, 10_000, &governance), ProposalState::Rejected);
+        
+        proposal.yes_votes = 600;
+        assert_eq!(tally_outcome(&proposal, 10_000, &governance), ProposalState::Approved);
+    }
+    
+    #[test]
+    fn test_change_vote_moves_power_between_tallies() {
+        let now = Utc::now();
+        let mut proposal = open_proposal(1);
+        proposal.yes_votes = 300;
+        proposal.no_votes = 100;
+        let ballots = [
+            ("0xAlice", ProposalVote::Yes, 200),
+            ("0xBob", ProposalVote::Yes, 100),
+            ("0xCarol", ProposalVote::No, 100),
+        ];
+        for (voter, vote, voting_power) in ballots {
+            proposal.votes.push(types::Vote {
+                voter: voter.to_string(),
+                vote,
+                voting_power,
+                timestamp: now,
+                delegated: Vec::new(),
+                ranking: Vec::new(),
+                signature: None,
+            });
+        }
+        
+        change_recorded_vote(&mut proposal, "0xAlice", ProposalVote::No, now).unwrap();
+        assert_eq!(proposal.yes_votes, 100);
+        assert_eq!(proposal.no_votes, 300);
+        assert_eq!(total_votes(&proposal), 400);
+        assert_eq!(proposal.votes[0].vote, ProposalVote::No);
+        assert_eq!(proposal.votes.len(), 3);
+        
+        // Someone who never voted cannot change a vote
+        assert!(change_recorded_vote(&mut proposal, "0xDave", ProposalVote::No, now).is_err());
+        
+        // Nor can anyone once voting has ended
+        let after_deadline = proposal.voting_ends_at.unwrap() + chrono::Duration::seconds(1);
+        assert!(change_recorded_vote(&mut proposal, "0xBob", ProposalVote::No, after_deadline).is_err());
+        assert_eq!(proposal.yes_votes, 100);
+        assert_eq!(proposal.no_votes, 300);
+    }
+    
+    #[test]
+    fn test_ballots_must_fit_the_proposal_type() {
+        let text = draft_proposal();
+        let mut multi_choice = draft_proposal();
+        multi_choice.proposal_type = ProposalType::MultiChoice {
+            options: vec!["Option A".to_string(), "Option B".to_string(), "Option C".to_string()],
+        };
+        
+        assert!(check_ballot(&multi_choice, ProposalVote::Yes, &[2, 0]).is_ok());
+        assert!(check_ballot(&multi_choice, ProposalVote::Yes, &[3]).is_err());
+        assert!(check_ballot(&multi_choice, ProposalVote::Abstain, &[]).is_ok());
+        assert!(check_ballot(&multi_choice, ProposalVote::No, &[]).is_err());
+        
+        assert!(check_ballot(&text, ProposalVote::No, &[]).is_ok());
+        assert!(check_ballot(&text, ProposalVote::Yes, &[0]).is_err());
+    }
+    
+    #[test]
+    fn test_delegator_vote_reclaims_power_from_delegate() {
+        let mut proposal = open_proposal(1);
+        let delegations = vec![
+            DelegatedPower {
+                delegator: "0xDelegator".to_string(),
+                amount: 50,
+            },
+            DelegatedPower {
+                delegator: "0xOther".to_string(),
+                amount: 30,
+            },
+        ];
+        
+        // The delegate votes yes with 100 of their own plus 80 delegated
+        let delegated = unclaimed_delegations(&proposal, delegations.clone());
+        proposal.yes_votes += 180;
+        proposal.votes.push(types::Vote {
+            voter: "0xDelegate".to_string(),
+            vote: ProposalVote::Yes,
+            voting_power: 180,
+            timestamp: Utc::now(),
+            delegated,
+            ranking: Vec::new(),
+            signature: None,
+        });
+        
+        // The delegator disagrees and votes no directly
+        assert_eq!(reclaim_delegated_power(&mut proposal, "0xDelegator"), 50);
+        proposal.no_votes += 50;
+        proposal.votes.push(types::Vote {
+            voter: "0xDelegator".to_string(),
+            vote: ProposalVote::No,
+            voting_power: 50,
+            timestamp: Utc::now(),
+            delegated: Vec::new(),
+            ranking: Vec::new(),
+            signature: None,
+        });
+        
+        assert_eq!(proposal.yes_votes, 130);
+        assert_eq!(proposal.no_votes, 50);
+        assert_eq!(proposal.votes[0].voting_power, 130);
+        assert_eq!(reclaim_delegated_power(&mut proposal, "0xDelegator"), 0);
+        
+        // Had the delegator voted first, the delegate would not count their power
+        let remaining = unclaimed_delegations(&proposal, delegations);
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].delegator, "0xOther");
+    }
+    
+    #[test]
+    fn test_delegate_votes_with_power_of_two_delegators() {
+        let mut proposal = open_proposal(1);
+        let delegations = vec![
+            DelegatedPower {
+                delegator: "0xCarol".to_string(),
+                amount: 40,
+            },
+            DelegatedPower {
+                delegator: "0xDave".to_string(),
+                amount: 60,
+            },
+        ];
+        
+        // Dave moved 25 of his 60 delegated coins away before the snapshot
+        let delegated = cap_delegations(unclaimed_delegations(&proposal, delegations.clone()), &[40, 35]);
+        assert_eq!(delegated.iter().map(|d| d.amount).sum::<u64>(), 75);
+        
+        let own_power = 100;
+        let voting_power = own_power + delegated.iter().map(|d| d.amount).sum::<u64>();
+        proposal.yes_votes += voting_power;
+        proposal.votes.push(types::Vote {
+            voter: "0xDelegate".to_string(),
+            vote: ProposalVote::Yes,
+            voting_power,
+            timestamp: Utc::now(),
+            delegated,
+            ranking: Vec::new(),
+            signature: None,
+        });
+        assert_eq!(proposal.yes_votes, 175);
+        
+        // Carol overrides her delegation by voting directly
+        assert_eq!(reclaim_delegated_power(&mut proposal, "0xCarol"), 40);
+        assert_eq!(proposal.yes_votes, 135);
+        assert_eq!(proposal.votes[0].delegated.len(), 1);
+        
+        // Delegators holding nothing at the snapshot lend nothing
+        assert!(cap_delegations(delegations, &[0, 0]).is_empty());
+    }
+    
+    #[test]
+    fn test_quorum_against_total_vs_circulating_supply() {
+        let mut proposal = draft_proposal();
+        proposal.yes_votes = 2_000;
+        proposal.no_votes = 500;
+        
+        let governance = GovernanceConfig::default();
+        
+        // 6,000 of 10,000 tokens sit in the treasury and a vesting contract
+        let total = quorum_supply(10_000, &[5_000, 1_000], QuorumSupplySource::Total);
+        let circulating = quorum_supply(10_000, &[5_000, 1_000], QuorumSupplySource::Circulating);
+        assert_eq!(circulating, 4_000);
+        
+        // 2,500 votes are 25% of the total supply but 62.5% of circulating
+        assert_eq!(tally_outcome(&proposal, total, &governance), ProposalState::Rejected);
+        assert_eq!(tally_outcome(&proposal, circulating, &governance), ProposalState::Approved);
+        
+        assert_eq!(quorum_supply(100, &[80, 80], QuorumSupplySource::Circulating), 0);
+    }
+    
+    #[test]
+    fn test_participation_and_abstain_rates() {
+        let mut proposal = draft_proposal();
+        proposal.yes_votes = 2_000;
+        proposal.no_votes = 1_000;
+        proposal.abstain_votes = 1_500;
+        proposal.veto_votes = 500;
+        
+        // 5,000 of 10,000 eligible tokens voted; 1,500 of them abstained
+        let turnout = participation(&proposal, 10_000);
+        assert_eq!(turnout.eligible_supply, 10_000);
+        assert_eq!(turnout.participation_rate, 0.5);
+        assert_eq!(turnout.abstain_rate, 0.15);
+        
+        assert_eq!(participation(&proposal, 0).participation_rate, 0.0);
+        
+        // With a 40% quorum, abstentions decide whether it is met
+        let mut governance = GovernanceConfig::default();
+        assert_eq!(tally_outcome(&proposal, 10_000, &governance), ProposalState::Approved);
+        
+        governance.quorum_basis = QuorumBasis::YesNoOnly;
+        assert_eq!(tally_outcome(&proposal, 10_000, &governance), ProposalState::Rejected);
+    }
+    
+    #[test]
+    fn test_quorum_basis_changes_outcome() {
+        let mut proposal = draft_proposal();
+        proposal.yes_votes = 3_000;
+        proposal.no_votes = 900;
+        proposal.abstain_votes = 1_000;
+        
+        // 20,000 tokens exist, 10,000 of them held by the treasury
+        let supply = |governance: &GovernanceConfig| quorum_supply(20_000, &[10_000], supply_source(governance));
+        let outcome = |basis: QuorumBasis| {
+            let governance = GovernanceConfig {
+                quorum_percentage: 40,
+                quorum_basis: basis,
+                ..Default::default()
+            };
+            tally_outcome(&proposal, supply(&governance), &governance)
+        };
+        
+        // 4,900 votes miss a 40% quorum of the total supply (8,000)...
+        assert_eq!(outcome(QuorumBasis::CastVotes), ProposalState::Rejected);
+        // ...but meet it against the circulating supply (4,000)
+        assert_eq!(outcome(QuorumBasis::CirculatingSupply), ProposalState::Approved);
+        
+        // Against the same supply, dropping abstentions loses quorum
+        let governance = GovernanceConfig {
+            quorum_percentage: 40,
+            quorum_supply_source: QuorumSupplySource::Circulating,
+            ..Default::default()
+        };
+        assert_eq!(tally_outcome(&proposal, supply(&governance), &governance), ProposalState::Approved);
+        
+        let governance = GovernanceConfig {
+            quorum_basis: QuorumBasis::YesNoOnly,
+            ..governance
+        };
+        assert_eq!(tally_outcome(&proposal, supply(&governance), &governance), ProposalState::Rejected);
+    }
+    
+    #[test]
+    fn test_flipping_vote_near_deadline_extends_voting() {
+        let now = Utc::now();
+        let ends_at = now + chrono::Duration::seconds(60);
+        
+        let mut proposal = draft_proposal();
+        proposal.voting_ends_at = Some(ends_at);
+        proposal.yes_votes = 600;
+        proposal.no_votes = 400;
+        
+        let mut governance = GovernanceConfig::default();
+        governance.quiet_ending_window_seconds = 600;
+        
+        // A late whale vote that keeps the proposal passing changes nothing
+        let passing_before = majority_reached(&proposal, &governance);
+        proposal.yes_votes += 1_000;
+        assert!(!extend_quiet_ending(&mut proposal, passing_before, "0xWhale", now, &governance));
+        assert_eq!(proposal.voting_ends_at, Some(ends_at));
+        assert!(proposal.extensions.is_empty());
+        
+        // A late vote that flips it to failing extends by the window
+        let passing_before = majority_reached(&proposal, &governance);
+        proposal.no_votes += 5_000;
+        assert!(extend_quiet_ending(&mut proposal, passing_before, "0xSniper", now, &governance));
+        assert_eq!(proposal.voting_ends_at, Some(ends_at + chrono::Duration::seconds(600)));
+        assert_eq!(proposal.extensions.len(), 1);
+        assert_eq!(proposal.extensions[0].previous_ends_at, ends_at);
+        assert_eq!(proposal.extensions[0].triggered_by, "0xSniper");
+    }
+    
+    #[test]
+    fn test_quiet_ending_outside_window_or_at_limit() {
+        let now = Utc::now();
+        let mut governance = GovernanceConfig::default();
+        governance.quiet_ending_window_seconds = 600;
+        governance.max_quiet_ending_extensions = 1;
+        
+        let mut proposal = draft_proposal();
+        proposal.voting_ends_at = Some(now + chrono::Duration::hours(2));
+        proposal.no_votes = 100;
+        
+        // Flipping, but well before the window
+        assert!(!extend_quiet_ending(&mut proposal, true, "0xEarly", now, &governance));
+        
+        proposal.voting_ends_at = Some(now + chrono::Duration::seconds(30));
+        assert!(extend_quiet_ending(&mut proposal, true, "0xLate", now, &governance));
+        
+        // The maximum number of extensions has been used up
+        proposal.voting_ends_at = Some(now + chrono::Duration::seconds(30));
+        assert!(!extend_quiet_ending(&mut proposal, true, "0xLater", now, &governance));
+    }
+    
+    fn open_proposal(ends_in_days: i64) -> Proposal {
+        let mut proposal = draft_proposal();
+        let now = Utc::now();
+        proposal.state = ProposalState::Voting;
+        proposal.voting_starts_at = Some(now - chrono::Duration::days(1));
+        proposal.voting_ends_at = Some(now + chrono::Duration::days(ends_in_days));
+        proposal
+    }
+    
+    #[test]
+    fn test_votable_excludes_voted_and_zero_power() {
+        let voter = "0xVoter";
+        
+        let later = open_proposal(5);
+        let sooner = open_proposal(1);
+        let mut voted = open_proposal(2);
+        voted.votes.push(types::Vote {
+            voter: voter.to_string(),
+            vote: ProposalVote::Yes,
+            voting_power: 10,
+            timestamp: Utc::now(),
+            delegated: Vec::new(),
+            ranking: Vec::new(),
+            signature: None,
+        });
+        let mut closed = open_proposal(3);
+        closed.voting_ends_at = Some(Utc::now() - chrono::Duration::hours(1));
+        let draft = draft_proposal();
+        
+        let proposals = vec![later.clone(), voted, sooner.clone(), closed, draft];
+        
+        let with_power = |power: u64| -> Vec<(Proposal, u64)> {
+            proposals.iter().cloned().map(|proposal| (proposal, power)).collect()
+        };
+        
+        let votable = votable_proposals(with_power(10), voter, Utc::now());
+        let ids: Vec<&str> = votable.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(ids, vec![sooner.id.as_str(), later.id.as_str()]);
+        
+        assert!(votable_proposals(with_power(0), voter, Utc::now()).is_empty());
+        
+        // Power is per proposal: none at one proposal's snapshot excludes only it
+        let mut candidates = with_power(10);
+        candidates[0].1 = 0;
+        let votable = votable_proposals(candidates, &voter.to_uppercase(), Utc::now());
+        let ids: Vec<&str> = votable.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(ids, vec![sooner.id.as_str()]);
+    }
+    
+    fn approved_call(code_hash: &str) -> Proposal {
+        let mut proposal = draft_proposal();
+        proposal.proposal_type = ProposalType::ContractCall {
+            contract: "0x00000000000000000000000000000000000000c0".to_string(),
+            function: "upgradeTo".to_string(),
+            args: vec![],
+        };
+        proposal.state = ProposalState::Approved;
+        proposal.target_code_hash = Some(code_hash.to_string());
+        proposal
+    }
+    
+    #[test]
+    fn test_changed_code_hash_blocks_execution() {
+        let guard = ExecutionGuardConfig {
+            enabled: true,
+            ..Default::default()
+        };
+        let proposal = approved_call("0xaaaa");
+        
+        assert!(check_execution_preconditions(&proposal, &guard, Some("0xAAAA"), None).is_ok());
+        assert!(matches!(
+            check_execution_preconditions(&proposal, &guard, Some("0xbbbb"), None),
+            Err(PreconditionViolation::CodeHashChanged { .. })
+        ));
+        
+        let mut unsnapshotted = proposal.clone();
+        unsnapshotted.target_code_hash = None;
+        assert!(matches!(
+            check_execution_preconditions(&unsnapshotted, &guard, Some("0xaaaa"), None),
+            Err(PreconditionViolation::MissingCodeHash(_))
+        ));
+    }
+    
+    #[test]
+    fn test_denylist_and_balance_block_transfers() {
+        let guard = ExecutionGuardConfig {
+            enabled: true,
+            denylist: vec!["0x00000000000000000000000000000000000000BA".to_string()],
+            executor_address: None,
+        };
+        let mut proposal = draft_proposal();
+        proposal.proposal_type = ProposalType::Transfer {
+            to: "0x00000000000000000000000000000000000000ba".to_string(),
+            amount: 100,
+            token: "ATOM".to_string(),
+        };
+        
+        assert!(matches!(
+            check_execution_preconditions(&proposal, &guard, None, Some(1_000)),
+            Err(PreconditionViolation::DeniedAddress(_))
+        ));
+        
+        proposal.proposal_type = ProposalType::Transfer {
+            to: "0x0000000000000000000000000000000000000001".to_string(),
+            amount: 100,
+            token: "ATOM".to_string(),
+        };
+        assert!(check_execution_preconditions(&proposal, &guard, None, Some(100)).is_ok());
+        assert_eq!(
+            check_execution_preconditions(&proposal, &guard, None, Some(99)),
+            Err(PreconditionViolation::InsufficientBalance { required: 100, available: 99 })
+        );
+    }
+    
+    /// A manager over a migrated SQLite database and a mock chain
+    async fn mock_manager(config: &Config) -> (ProposalManager, Arc<MockBlockchain>, Database) {
+        let chain = Arc::new(MockBlockchain::new());
+        let database = Database::migrated_sqlite().await;
+        let manager = ProposalManager::new(
+            config,
+            BlockchainAdapter::with_mock(&config.blockchain, chain.clone()),
+            database.clone(),
+        )
+        .unwrap();
+        
+        (manager, chain, database)
+    }
+    
+    /// Register a member with the given role and status
+    async fn insert_member(database: &Database, address: &str, role: MemberRole, status: identity::MemberStatus) -> Member {
+        let member = Member {
+            id: Uuid::new_v4().to_string(),
+            address: address.to_string(),
+            name: None,
+            role,
+            status,
+            reputation: 0,
+            joined_at: Utc::now(),
+            last_active_at: Utc::now(),
+            role_changed_at: None,
+            metadata: serde_json::Value::Null,
+        };
+        database
+            .execute_any(
+                "INSERT INTO members (id, address, data) VALUES ($1, $2, $3)",
+                &[
+                    member.id.as_str().into(),
+                    address.into(),
+                    serde_json::to_value(&member).unwrap().into(),
+                ],
+            )
+            .await
+            .unwrap();
+        
+        member
+    }
+    
+    #[tokio::test]
+    async fn test_reputation_strategy_weighs_snapshot_power() {
+        let mut config = crate::config::default();
+        config.governance.voting_strategy = Some("Reputation".to_string());
+        config.governance.reputation_weighting = crate::config::ReputationWeightingConfig {
+            curve: crate::config::ReputationCurve::Linear { slope: 0.01 },
+            max_multiplier: 3.0,
+        };
+        let (manager, chain, database) = mock_manager(&config).await;
+        chain.set_balance(PROPOSER, 1_000);
+        chain.set_balance(SPONSOR_1, 1_000);
+        
+        let mut member = insert_member(&database, PROPOSER, MemberRole::Member, identity::MemberStatus::Active).await;
+        member.reputation = 100;
+        database
+            .execute_any(
+                "UPDATE members SET data = $1 WHERE id = $2",
+                &[serde_json::to_value(&member).unwrap().into(), member.id.as_str().into()],
+            )
+            .await
+            .unwrap();
+        insert_member(&database, SPONSOR_1, MemberRole::Member, identity::MemberStatus::Active).await;
+        
+        let mut proposal = draft_proposal();
+        proposal.state = ProposalState::Voting;
+        proposal.voting_starts_at = Some(Utc::now() - chrono::Duration::hours(1));
+        proposal.voting_ends_at = Some(Utc::now() + chrono::Duration::days(1));
+        manager.save_proposal(&proposal, PROPOSER).await.unwrap();
+        
+        manager.vote(&proposal.id, &address(PROPOSER), ProposalVote::Yes, None).await.unwrap();
+        manager.vote(&proposal.id, &address(SPONSOR_1), ProposalVote::No, None).await.unwrap();
+        
+        // Equal balances; reputation 100 doubles the power on a 0.01 slope
+        let proposal = manager.get_proposal(&proposal.id).await.unwrap();
+        let power = |voter: &str| proposal.votes.iter().find(|v| v.voter.eq_ignore_ascii_case(voter)).unwrap().voting_power;
+        assert_eq!(power(PROPOSER), 2_000);
+        assert_eq!(power(SPONSOR_1), 1_000);
+    }
+    
+    #[tokio::test]
+    async fn test_council_cancels_queued_proposals() {
+        let config = crate::config::default();
+        let (manager, _chain, database) = mock_manager(&config).await;
+        insert_member(&database, SPONSOR_1, MemberRole::Council, identity::MemberStatus::Active).await;
+        insert_member(&database, SPONSOR_2, MemberRole::Member, identity::MemberStatus::Active).await;
+        
+        let mut voting = draft_proposal();
+        voting.state = ProposalState::Voting;
+        manager.save_proposal(&voting, PROPOSER).await.unwrap();
+        let mut queued = draft_proposal();
+        queued.state = ProposalState::Queued;
+        queued.execution_available_at = Some(Utc::now() + chrono::Duration::days(2));
+        manager.save_proposal(&queued, PROPOSER).await.unwrap();
+        
+        // Only the proposer may cancel before the vote is decided
+        assert!(matches!(
+            manager.cancel_proposal(&voting.id, SPONSOR_1).await,
+            Err(DaoError::Unauthorized)
+        ));
+        assert!(matches!(
+            manager.cancel_proposal(&queued.id, SPONSOR_2).await,
+            Err(DaoError::Unauthorized)
+        ));
+        
+        manager.cancel_proposal(&queued.id, SPONSOR_1).await.unwrap();
+        assert_eq!(manager.get_proposal(&queued.id).await.unwrap().state, ProposalState::Cancelled);
+    }
+    
+    #[tokio::test]
+    async fn test_only_members_comment_and_admins_moderate() {
+        let config = crate::config::default();
+        let (manager, _chain, database) = mock_manager(&config).await;
+        insert_member(&database, PROPOSER, MemberRole::Member, identity::MemberStatus::Active).await;
+        let admin = insert_member(&database, SPONSOR_1, MemberRole::Admin, identity::MemberStatus::Active).await;
+        
+        let proposal = draft_proposal();
+        manager.save_proposal(&proposal, PROPOSER).await.unwrap();
+        
+        assert!(matches!(
+            manager.add_comment(&proposal.id, SPONSOR_2, "Drive-by", None).await,
+            Err(DaoError::Unauthorized)
+        ));
+        let comment = manager.add_comment(&proposal.id, PROPOSER, "Looks good", None).await.unwrap();
+        
+        // Role lookups ignore address case
+        manager.delete_comment(&comment.id, &SPONSOR_1.to_uppercase().replace("0X", "0x")).await.unwrap();
+        
+        // A deleted admin is no longer an admin
+        let comment = manager.add_comment(&proposal.id, PROPOSER, "Second thoughts", None).await.unwrap();
+        database
+            .execute_any(
+                "UPDATE members SET deleted_at = $1 WHERE id = $2",
+                &[Utc::now().timestamp().into(), admin.id.as_str().into()],
+            )
+            .await
+            .unwrap();
+        assert!(matches!(
+            manager.delete_comment(&comment.id, SPONSOR_1).await,
+            Err(DaoError::Unauthorized)
+        ));
+    }
+    
+    #[tokio::test]
+    async fn test_only_council_creates_templates() {
+        let config = crate::config::default();
+        let (manager, _chain, database) = mock_manager(&config).await;
+        insert_member(&database, PROPOSER, MemberRole::Member, identity::MemberStatus::Active).await;
+        insert_member(&database, SPONSOR_1, MemberRole::Council, identity::MemberStatus::Active).await;
+        
+        let template = || {
+            ProposalTemplate::new(
+                "  Quarterly report  ",
+                "Report for {quarter}",
+                "Summary of {quarter}",
+                ProposalType::TextProposal {
+                    metadata: serde_json::Value::Null,
+                },
+            )
+        };
+        
+        assert!(matches!(
+            manager.create_template(template(), PROPOSER).await,
+            Err(DaoError::Unauthorized)
+        ));
+        assert!(manager.list_templates().await.unwrap().is_empty());
+        
+        let created = manager.create_template(template(), SPONSOR_1).await.unwrap();
+        assert_eq!(created.name, "Quarterly report");
+    }
+    
+    #[tokio::test]
+    async fn test_suspended_members_cannot_propose_sponsor_or_vote() {
+        let mut config = crate::config::default();
+        config.identity.allow_unregistered_voters = true;
+        let (manager, chain, database) = mock_manager(&config).await;
+        chain.set_balance(PROPOSER, config.governance.proposal_threshold as u128);
+        chain.set_balance(SPONSOR_1, config.governance.proposal_threshold as u128);
+        
+        let proposal_id = manager.submit_proposal(draft_proposal()).await.unwrap();
+        insert_member(&database, SPONSOR_1, MemberRole::Council, identity::MemberStatus::Suspended).await;
+        
+        let mut proposal = draft_proposal();
+        proposal.proposer = SPONSOR_1.to_string();
+        assert!(matches!(manager.submit_proposal(proposal).await, Err(DaoError::Unauthorized)));
+        assert!(matches!(
+            manager.sponsor(&proposal_id, &address(SPONSOR_1)).await,
+            Err(DaoError::Unauthorized)
+        ));
+        assert!(matches!(
+            manager.vote(&proposal_id, &address(SPONSOR_1), ProposalVote::Yes, None).await,
+            Err(DaoError::Unauthorized)
+        ));
+        assert!(manager.get_proposal(&proposal_id).await.unwrap().votes.is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_sponsorships_are_stored_until_voting_can_start() {
+        let mut config = crate::config::default();
+        config.identity.allow_unregistered_voters = true;
+        config.governance.required_sponsors = 2;
+        let (manager, chain, _database) = mock_manager(&config).await;
+        chain.set_balance(PROPOSER, config.governance.proposal_threshold as u128);
+        chain.set_balance(SPONSOR_1, 500);
+        chain.set_balance(SPONSOR_2, 300);
+        
+        let proposal_id = manager.submit_proposal(draft_proposal()).await.unwrap();
+        manager.sponsor(&proposal_id, &address(SPONSOR_1)).await.unwrap();
+        
+        let sponsors = manager.get_proposal(&proposal_id).await.unwrap().sponsors;
+        assert_eq!(sponsors.len(), 1);
+        assert_eq!((sponsors[0].member.as_str(), sponsors[0].weight), (address(SPONSOR_1).as_str(), 500));
+        assert!(matches!(
+            manager.start_voting(&proposal_id, PROPOSER).await,
+            Err(DaoError::ProposalError(_))
+        ));
+        
+        manager.sponsor(&proposal_id, &address(SPONSOR_2)).await.unwrap();
+        manager.start_voting(&proposal_id, PROPOSER).await.unwrap();
+        assert_eq!(manager.get_proposal(&proposal_id).await.unwrap().sponsors.len(), 2);
+    }
+    
+    #[tokio::test]
+    async fn test_submission_numbers_proposals_on_sqlite() {
+        let mut config = crate::config::default();
+        config.identity.allow_unregistered_voters = true;
+        let (manager, chain, _database) = mock_manager(&config).await;
+        
+        // Below the proposal threshold nothing is stored or numbered
+        chain.set_balance(PROPOSER, 10);
+        assert!(matches!(
+            manager.submit_proposal(draft_proposal()).await,
+            Err(DaoError::Unauthorized)
+        ));
+        
+        chain.set_balance(PROPOSER, config.governance.proposal_threshold as u128);
+        let first = manager.submit_proposal(draft_proposal()).await.unwrap();
+        let second = manager.submit_proposal(draft_proposal()).await.unwrap();
+        
+        assert_eq!(manager.get_proposal(&first).await.unwrap().number, Some(1));
+        assert_eq!(manager.find_proposal("2").await.unwrap().id, second);
+    }
+    
+    #[tokio::test]
+    async fn test_guard_reads_preconditions_from_chain() {
+        let mut config = crate::config::default();
+        config.governance.execution_guard.enabled = true;
+        let (manager, chain, database) = mock_manager(&config).await;
+        
+        let contract = "0x00000000000000000000000000000000000000c0";
+        chain.set_code_hash(contract, "0xaaaa");
+        
+        // The contract is upgraded after the vote
+        let upgraded = approved_call("0xaaaa");
+        ProposalManager::store_proposal(&database, &upgraded, PROPOSER).await.unwrap();
+        chain.set_code_hash(contract, "0xbbbb");
+        let err = manager.execute_proposal(&upgraded.id, PROPOSER).await.unwrap_err();
+        assert!(matches!(err, DaoError::ProposalError(message) if message.starts_with("Execution blocked")));
+        assert!(chain.contract_calls().is_empty());
+        
+        // An unreachable node blocks execution rather than passing the check
+        let unchanged = approved_call("0xbbbb");
+        ProposalManager::store_proposal(&database, &unchanged, PROPOSER).await.unwrap();
+        chain.fail("get_code_hash", "connection refused");
+        assert!(matches!(
+            manager.execute_proposal(&unchanged.id, PROPOSER).await,
+            Err(DaoError::BlockchainError(_))
+        ));
+        chain.clear_failure("get_code_hash");
+        
+        manager.execute_proposal(&unchanged.id, PROPOSER).await.unwrap();
+        assert_eq!(chain.contract_calls().len(), 1);
+        assert_eq!(
+            manager.get_proposal(&unchanged.id).await.unwrap().state,
+            ProposalState::Executed
+        );
+    }
+    
+    const ALL_STATES: [ProposalState; 7] = [
+        ProposalState::Draft,
+        ProposalState::Voting,
+        ProposalState::Queued,
+        ProposalState::Approved,
+        ProposalState::Rejected,
+        ProposalState::Executed,
+        ProposalState::Cancelled,
+    ];
+    
+    #[test]
+    fn test_allowed_transitions() {
+        let allowed = [
+            (ProposalState::Draft, ProposalState::Voting),
+            (ProposalState::Draft, ProposalState::Cancelled),
+            (ProposalState::Voting, ProposalState::Queued),
+            (ProposalState::Voting, ProposalState::Approved),
+            (ProposalState::Voting, ProposalState::Rejected),
+            (ProposalState::Voting, ProposalState::Cancelled),
+            (ProposalState::Queued, ProposalState::Approved),
+            (ProposalState::Queued, ProposalState::Cancelled),
+            (ProposalState::Approved, ProposalState::Executed),
+        ];
+        
+        for from in ALL_STATES {
+            for to in ALL_STATES {
+                assert_eq!(
+                    from.can_transition_to(to),
+                    allowed.contains(&(from, to)),
+                    "{:?} -> {:?}",
+                    from,
+                    to
+                );
+            }
+        }
+        
+        // Terminal states have no way out
+        for state in ALL_STATES.into_iter().filter(ProposalState::is_terminal) {
+            assert!(ALL_STATES.iter().all(|next| !state.can_transition_to(*next)));
+        }
+    }
+    
+    #[test]
+    fn test_execution_waits_for_timelock() {
+        let mut proposal = draft_proposal();
+        proposal.state = ProposalState::Voting;
+        let approved_at = Utc::now();
+        
+        let outcome = schedule_execution(&mut proposal, ProposalState::Approved, 3600, approved_at);
+        assert_eq!(outcome, ProposalState::Queued);
+        proposal.state = outcome;
+        
+        // Executing early fails and leaves the proposal queued
+        let early = release_timelock(&mut proposal, approved_at + chrono::Duration::minutes(30));
+        assert!(matches!(early, Err(DaoError::ProposalError(message)) if message.contains("timelocked")));
+        assert_eq!(proposal.state, ProposalState::Queued);
+        assert!(check_transition(&proposal, ProposalState::Executed).is_err());
+        
+        // Once the clock passes the timelock the proposal can be executed
+        release_timelock(&mut proposal, approved_at + chrono::Duration::hours(1)).unwrap();
+        assert_eq!(proposal.state, ProposalState::Approved);
+        assert!(check_transition(&proposal, ProposalState::Executed).is_ok());
+    }
+    
+    #[test]
+    fn test_zero_timelock_approves_immediately() {
+        let mut proposal = draft_proposal();
+        let now = Utc::now();
+        
+        assert_eq!(schedule_execution(&mut proposal, ProposalState::Approved, 0, now), ProposalState::Approved);
+        proposal.state = ProposalState::Approved;
+        assert!(release_timelock(&mut proposal, now).is_ok());
+        
+        // Rejections never get an execution time
+        let mut rejected = draft_proposal();
+        assert_eq!(schedule_execution(&mut rejected, ProposalState::Rejected, 3600, now), ProposalState::Rejected);
+        assert_eq!(rejected.execution_available_at, None);
+    }
+    
+    #[tokio::test]
+    async fn test_vote_broadcasts_proposal_voted_event() {
+        let mut config = crate::config::default();
+        config.identity.allow_unregistered_voters = true;
+        let (manager, chain, _database) = mock_manager(&config).await;
+        let (sender, mut receiver) = broadcast::channel(16);
+        let manager = manager.with_event_sender(sender);
+        chain.set_balance(PROPOSER, 150);
+        
+        let mut proposal = draft_proposal();
+        proposal.state = ProposalState::Voting;
+        proposal.voting_starts_at = Some(Utc::now() - chrono::Duration::hours(1));
+        proposal.voting_ends_at = Some(Utc::now() + chrono::Duration::days(1));
+        manager.save_proposal(&proposal, PROPOSER).await.unwrap();
+        
+        manager.vote(&proposal.id, &address(PROPOSER), ProposalVote::Yes, None).await.unwrap();
+        
+        let event = receiver.try_recv().unwrap();
+        assert!(matches!(event.event_type, EventType::ProposalVoted));
+        assert_eq!(event.data["proposal_id"], serde_json::json!(proposal.id));
+        assert_eq!(event.data["state"], serde_json::json!(ProposalState::Voting));
+        assert_eq!(event.data["voter"], serde_json::json!(address(PROPOSER).as_str()));
+        assert_eq!(event.data["vote"], serde_json::json!(ProposalVote::Yes));
+        assert_eq!(event.data["voting_power"], 150);
+        assert_eq!(event.data["yes_votes"], 150);
+        assert!(receiver.try_recv().is_err());
+    }
+    
+    #[test]
+    fn test_illegal_transition_rejected() {
+        let mut proposal = draft_proposal();
+        proposal.state = ProposalState::Executed;
+        
+        assert!(matches!(
+            check_transition(&proposal, ProposalState::Voting),
+            Err(DaoError::InvalidTransition {
+                from: ProposalState::Executed,
+                to: ProposalState::Voting,
+            })
+        ));
+        
+        proposal.state = ProposalState::Approved;
+        assert!(check_transition(&proposal, ProposalState::Executed).is_ok());
+    }
+    
+    #[test]
+    fn test_display_id_lookup() {
+        let mut proposal = draft_proposal();
+        assert_eq!(proposal.display_id("AIP"), None);
+        
+        proposal.number = Some(42);
+        assert_eq!(proposal.display_id("AIP").as_deref(), Some("AIP-42"));
+        
+        assert_eq!(parse_proposal_number("AIP-42", "AIP"), Some(42));
+        assert_eq!(parse_proposal_number("aip-42", "AIP"), Some(42));
+        assert_eq!(parse_proposal_number("42", "AIP"), Some(42));
+        assert_eq!(parse_proposal_number("DIP-42", "AIP"), None);
+        assert_eq!(parse_proposal_number("AIP-", "AIP"), None);
+        assert_eq!(parse_proposal_number("0f8fad5b-d9cb-469f-a165-70867728950e", "AIP"), None);
+    }
+    
+    /// Save a proposal, update it, and load it back after each write
+    async fn check_save_and_load(database: &Database) {
+        let mut proposal = draft_proposal();
+        ProposalManager::store_proposal(database, &proposal, "0xProposer").await.unwrap();
+        let loaded = ProposalManager::load_proposal(database, &proposal.id).await.unwrap();
+        assert_eq!(serde_json::to_value(&loaded).unwrap(), serde_json::to_value(&proposal).unwrap());
+        
+        proposal.title = "Fund the grants program twice".to_string();
+        proposal.state = ProposalState::Voting;
+        proposal.updated_at = Utc::now();
+        ProposalManager::store_proposal(database, &proposal, "0xProposer").await.unwrap();
+        let loaded = ProposalManager::load_proposal(database, &proposal.id).await.unwrap();
+        assert_eq!(serde_json::to_value(&loaded).unwrap(), serde_json::to_value(&proposal).unwrap());
+        
+        let row = database
+            .query_one_any("SELECT state FROM proposals WHERE id = $1", &[proposal.id.as_str().into()])
+            .await
+            .unwrap();
+        assert_eq!(row.text("state").unwrap(), serde_json::to_string(&ProposalState::Voting).unwrap());
+        
+        assert!(ProposalManager::load_proposal(database, &"missing".to_string()).await.is_err());
+    }
+    
+    /// The Postgres database in `ATOMSI_TEST_DATABASE_URL`, with a
+    /// `proposals` table, or `None` when it is not set
+    async fn postgres_database() -> Option<Database> {
+        let url = std::env::var("ATOMSI_TEST_DATABASE_URL").ok()?;
+        let mut db_config = crate::config::default().database;
+        db_config.url = url;
+        let database = Database::connect(&db_config).await.unwrap();
+        
+        database
+            .execute_any(
+                "CREATE TABLE IF NOT EXISTS proposals (id TEXT PRIMARY KEY, data JSONB NOT NULL, state TEXT NOT NULL, \
+                 title TEXT NOT NULL, description TEXT NOT NULL, created_at TIMESTAMPTZ NOT NULL, \
+                 updated_at TIMESTAMPTZ NOT NULL, updated_by VARCHAR(100), deleted_at BIGINT, search_vector tsvector GENERATED ALWAYS AS \
+                 (setweight(to_tsvector('english', title), 'A') || setweight(to_tsvector('english', description), 'B')) STORED)",
+                &[],
+            )
+            .await
+            .unwrap();
+        
+        Some(database)
+    }
+    
+    #[tokio::test]
+    async fn test_save_and_load_on_sqlite() {
+        check_save_and_load(&Database::migrated_sqlite().await).await;
+    }
+    
+    /// Runs against the Postgres database in `ATOMSI_TEST_DATABASE_URL`
+    /// and is skipped when it is not set
+    #[tokio::test]
+    async fn test_save_and_load_on_postgres() {
+        let Some(database) = postgres_database().await else {
+            return;
+        };
+        
+        check_save_and_load(&database).await;
+    }
+    
+    #[tokio::test]
+    async fn test_soft_deleted_proposals_hidden_by_default() {
+        let database = Database::migrated_sqlite().await;
+        let kept = store_text_proposal(&database, "Fund the grants program", "Allocate funds for grants").await;
+        let deleted = store_text_proposal(&database, "Grants committee election", "Elect the committee").await;
+        let stale = ProposalManager::load_proposal(&database, &deleted).await.unwrap();
+        
+        ProposalManager::soft_delete(&database, &deleted, "0xAdmin").await.unwrap();
+        
+        // Hidden from lookups, listings and search
+        assert!(ProposalManager::load_proposal(&database, &deleted).await.is_err());
+        let listed = ProposalManager::list_proposals(&database, None, false).await.unwrap();
+        assert_eq!(listed.iter().map(|proposal| &proposal.id).collect::<Vec<_>>(), [&kept]);
+        assert_eq!(search_titles(&database, "grants", 10).await, ["Fund the grants program"]);
+        
+        // Still listed on request, with the deleting actor recorded
+        assert_eq!(ProposalManager::list_proposals(&database, None, true).await.unwrap().len(), 2);
+        let row = database
+            .query_one_any("SELECT updated_by FROM proposals WHERE id = $1", &[deleted.as_str().into()])
+            .await
+            .unwrap();
+        assert_eq!(row.text("updated_by").unwrap(), "0xAdmin");
+        
+        // Deleting twice fails
+        assert!(matches!(
+            ProposalManager::soft_delete(&database, &deleted, "0xAdmin").await,
+            Err(DaoError::ProposalError(_))
+        ));
+        
+        // Saving a copy loaded before the delete doesn't overwrite it
+        assert!(ProposalManager::store_proposal(&database, &stale, "0xVoter").await.is_err());
+        let row = database
+            .query_one_any("SELECT updated_by FROM proposals WHERE id = $1", &[deleted.as_str().into()])
+            .await
+            .unwrap();
+        assert_eq!(row.text("updated_by").unwrap(), "0xAdmin");
+    }
+    
+    #[tokio::test]
+    async fn test_proposal_pages_unaffected_by_new_proposals() {
+        let database = Database::migrated_sqlite().await;
+        for title in ["First", "Second", "Third"] {
+            store_text_proposal(&database, title, "Text").await;
+        }
+        let database = &database;
+        let page = |cursor| async move {
+            let request = PageRequest { cursor, limit: 2 };
+            ProposalManager::list_proposals_page(database, None, false, &request).await.unwrap()
+        };
+        let titles = |page: &Page<Proposal>| page.items.iter().map(|proposal| proposal.title.clone()).collect::<Vec<_>>();
+        
+        let first = page(None).await;
+        assert_eq!(titles(&first), ["Third", "Second"]);
+        
+        // A proposal submitted in between doesn't push "Second" onto the next page
+        store_text_proposal(database, "Fourth", "Text").await;
+        let second = page(first.next_cursor.clone()).await;
+        assert_eq!(titles(&second), ["First"]);
+        assert!(second.next_cursor.is_none());
+        
+        let back = page(second.prev_cursor.clone()).await;
+        assert_eq!(titles(&back), ["Third", "Second"]);
+        assert_eq!(titles(&page(back.prev_cursor.clone()).await), ["Fourth"]);
+    }
+    
+    /// Store a text proposal with the given title and description
+    async fn store_text_proposal(database: &Database, title: &str, description: &str) -> ProposalId {
+        let proposal = ProposalBuilder::new()
+            .title(title)
+            .description(description)
+            .proposal_type(ProposalType::TextProposal {
+                metadata: serde_json::Value::Null,
+            })
+            .proposer("0xProposer")
+            .build()
+            .unwrap();
+        ProposalManager::store_proposal(database, &proposal, "0xProposer").await.unwrap();
+        proposal.id
+    }
+    
+    async fn search_titles(database: &Database, query: &str, limit: usize) -> Vec<String> {
+        search::search_proposals(database, query, limit)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|proposal| proposal.title)
+            .collect()
+    }
+    
+    #[tokio::test]
+    async fn test_search_falls_back_to_like_on_sqlite() {
+        let database = Database::migrated_sqlite().await;
+        store_text_proposal(&database, "Rebalance the treasury", "Move idle funds into grants").await;
+        store_text_proposal(&database, "Fund the grants program", "Allocate funds for community grants").await;
+        store_text_proposal(&database, "Grants committee election", "Elect the committee").await;
+        store_text_proposal(&database, "Cut fees by 50%", "Lower the protocol fee").await;
+        
+        // Title and description hits outrank title-only hits, which outrank
+        // description-only hits; matching ignores case
+        assert_eq!(
+            search_titles(&database, "GRANTS", 10).await,
+            ["Fund the grants program", "Grants committee election", "Rebalance the treasury"]
+        );
+        assert_eq!(search_titles(&database, "grants", 2).await.len(), 2);
+        
+        // Every word must match
+        assert_eq!(search_titles(&database, "grants committee", 10).await, ["Grants committee election"]);
+        
+        // Wildcards are matched literally
+        assert_eq!(search_titles(&database, "50%", 10).await, ["Cut fees by 50%"]);
+        assert_eq!(search_titles(&database, "%", 10).await, ["Cut fees by 50%"]);
+        
+        assert!(search_titles(&database, "quorum", 10).await.is_empty());
+        assert!(search_titles(&database, "   ", 10).await.is_empty());
+    }
+    
+    /// Runs against the Postgres database in `ATOMSI_TEST_DATABASE_URL`
+    /// and is skipped when it is not set
+    #[tokio::test]
+    async fn test_search_ranks_full_text_matches_on_postgres() {
+        let Some(database) = postgres_database().await else {
+            return;
+        };
+        // A word no other test's proposals contain
+        let marker = format!("x{}", Uuid::new_v4().simple());
+        
+        store_text_proposal(&database, &format!("Rebalance {}", marker), "Move idle funds into grants").await;
+        store_text_proposal(&database, &format!("Grants program {}", marker), "Allocate funding for community grants").await;
+        store_text_proposal(&database, &format!("Election {}", marker), "Elect a new council").await;
+        
+        // Stemmed: "grant" finds "grants"; title matches rank first
+        let titles = search_titles(&database, &format!("grant {}", marker), 10).await;
+        assert_eq!(titles, [format!("Grants program {}", marker), format!("Rebalance {}", marker)]);
+        
+        assert_eq!(search_titles(&database, &format!("funded {}", marker), 1).await.len(), 1);
+    }
+    
+    /// Runs against the Postgres database in `ATOMSI_TEST_DATABASE_URL`
+    /// and is skipped when it is not set
+    #[tokio::test]
+    async fn test_concurrent_submissions_get_consecutive_numbers() {
+        let Ok(url) = std::env::var("ATOMSI_TEST_DATABASE_URL") else {
+            return;
+        };
+        let mut db_config = crate::config::default().database;
+        db_config.url = url;
+        let database = Database::connect(&db_config).await.unwrap();
+        
+        database
+            .execute(
+                "CREATE TABLE IF NOT EXISTS proposal_sequence (id INTEGER PRIMARY KEY CHECK (id = 1), last_number BIGINT NOT NULL)",
+                &[],
+            )
+            .await
+            .unwrap();
+        database
+            .execute(
+                "INSERT INTO proposal_sequence (id, last_number) VALUES (1, 0) ON CONFLICT (id) DO NOTHING",
+                &[],
+            )
+            .await
+            .unwrap();
+        
+        let submissions = (0..20).map(|_| {
+            let database = database.clone();
+            tokio::spawn(async move {
+                database
+                    .transaction(|tx| Box::pin(async move { next_proposal_number(tx).await }))
+                    .await
+            })
+        });
+        let mut numbers: Vec<u64> = futures::future::join_all(submissions)
+            .await
+            .into_iter()
+            .map(|submission| submission.unwrap().unwrap())
+            .collect();
+        numbers.sort_unstable();
+        
+        // Distinct and consecutive: no duplicates and no gaps
+        assert_eq!(numbers.len(), 20);
+        assert!(numbers.windows(2).all(|pair| pair[1] == pair[0] + 1));
+    }
+}
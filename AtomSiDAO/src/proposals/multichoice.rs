@@ -0,0 +1,198 @@
+//! Multiple-option proposals
+//!
+//! A `MultiChoice` proposal asks voters to pick between several options.
+//! Each ballot ranks one or more options, most preferred first. Plurality
+//! counts only first preferences; instant runoff repeatedly eliminates the
+//! weakest option and moves its ballots to their next surviving preference
+//! until one option holds a majority.
+
+use super::types::Proposal;
+use crate::config::MultiChoiceTally;
+use crate::core::{DaoError, Result};
+use std::collections::BTreeMap;
+
+/// Check the options of a `MultiChoice` proposal
+pub(crate) fn validate_options(options: &[String]) -> Result<()> {
+    if options.len() < 2 {
+        return Err(DaoError::InvalidParameter(
+            "Multiple-choice proposal must have at least two options".to_string(),
+        ));
+    }
+
+    for (i, option) in options.iter().enumerate() {
+        if option.trim().is_empty() {
+            return Err(DaoError::InvalidParameter("Option cannot be empty".to_string()));
+        }
+
+        if options[..i].iter().any(|other| other.trim() == option.trim()) {
+            return Err(DaoError::InvalidParameter(format!("Duplicate option '{}'", option)));
+        }
+    }
+
+    Ok(())
+}
+
+/// Check a ballot's ranking against the number of options
+pub(crate) fn validate_ranking(ranking: &[usize], option_count: usize) -> Result<()> {
+    if ranking.is_empty() {
+        return Err(DaoError::InvalidParameter(
+            "Ballot must choose at least one option".to_string(),
+        ));
+    }
+
+    for (i, &option) in ranking.iter().enumerate() {
+        if option >= option_count {
+            return Err(DaoError::InvalidParameter(format!(
+                "Option {} is out of range; the proposal has {} options",
+                option, option_count
+            )));
+        }
+
+        if ranking[..i].contains(&option) {
+            return Err(DaoError::InvalidParameter(format!("Option {} is ranked twice", option)));
+        }
+    }
+
+    Ok(())
+}
+
+/// Pick the winning option of a finished multiple-choice vote
+///
+/// Returns `None` if no ballots were cast or the lead is tied.
+pub(crate) fn winning_option(proposal: &Proposal, option_count: usize, tally: MultiChoiceTally) -> Option<usize> {
+    let ballots: Vec<(&[usize], u64)> = proposal
+        .votes
+        .iter()
+        .filter(|vote| !vote.ranking.is_empty())
+        .map(|vote| (vote.ranking.as_slice(), vote.voting_power))
+        .collect();
+
+    match tally {
+        MultiChoiceTally::Plurality => plurality(&ballots),
+        MultiChoiceTally::InstantRunoff => instant_runoff(&ballots, option_count),
+    }
+}
+
+/// Option with the most first-preference weight
+fn plurality(ballots: &[(&[usize], u64)]) -> Option<usize> {
+    let mut counts = BTreeMap::new();
+    for (ranking, weight) in ballots {
+        *counts.entry(ranking[0]).or_insert(0u64) += weight;
+    }
+
+    sole_leader(&counts)
+}
+
+/// Instant-runoff winner
+///
+/// Each round counts every ballot for its highest-ranked option still in the
+/// running. An option with more than half of the counted weight wins;
+/// otherwise the option with the least weight is eliminated (the higher
+/// index on a tie). Ballots whose options are all eliminated are exhausted.
+fn instant_runoff(ballots: &[(&[usize], u64)], option_count: usize) -> Option<usize> {
+    let mut remaining: Vec<usize> = (0..option_count).collect();
+
+    loop {
+        let mut counts: BTreeMap<usize, u64> = remaining.iter().map(|&option| (option, 0)).collect();
+        for (ranking, weight) in ballots {
+            if let Some(&option) = ranking.iter().find(|option| remaining.contains(*option)) {
+                *counts.get_mut(&option)? += weight;
+            }
+        }
+
+        let counted: u64 = counts.values().sum();
+        if counted == 0 {
+            return None;
+        }
+
+        if let Some((&option, _)) = counts.iter().find(|(_, weight)| **weight as u128 * 2 > counted as u128) {
+            return Some(option);
+        }
+
+        // Two options left without a majority means a tie
+        if remaining.len() <= 2 {
+            return None;
+        }
+
+        let weakest = counts
+            .iter()
+            .min_by(|(a, a_weight), (b, b_weight)| a_weight.cmp(b_weight).then(b.cmp(a)))
+            .map(|(&option, _)| option)?;
+        remaining.retain(|&option| option != weakest);
+    }
+}
+
+/// Option with strictly the most weight, if any
+fn sole_leader(counts: &BTreeMap<usize, u64>) -> Option<usize> {
+    let (&leader, &most) = counts.iter().max_by_key(|&(_, weight)| *weight)?;
+    let tied = counts.values().filter(|&&weight| weight == most).count() > 1;
+
+    (!tied && most > 0).then_some(leader)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proposals::{ProposalBuilder, ProposalType, ProposalVote, Vote};
+    use chrono::Utc;
+
+    fn proposal(ballots: Vec<(Vec<usize>, u64)>) -> Proposal {
+        let mut proposal = ProposalBuilder::new()
+            .title("Choose a grants provider")
+            .description("Pick one of three funding options")
+            .proposal_type(ProposalType::MultiChoice {
+                options: vec!["Gitcoin".to_string(), "Giveth".to_string(), "In-house".to_string()],
+            })
+            .proposer("0xProposer")
+            .build()
+            .unwrap();
+
+        for (i, (ranking, weight)) in ballots.into_iter().enumerate() {
+            proposal.votes.push(Vote {
+                voter: format!("0xVoter{}", i),
+                vote: ProposalVote::Yes,
+                voting_power: weight,
+                timestamp: Utc::now(),
+                delegated: Vec::new(),
+                ranking,
+                signature: None,
+            });
+        }
+        proposal
+    }
+
+    #[test]
+    fn test_instant_runoff_elimination_round() {
+        // First preferences: A 40, B 35, C 25. C is eliminated and its
+        // ballots move to B, which then wins 60 to 40.
+        let proposal = proposal(vec![(vec![0], 40), (vec![1, 0], 35), (vec![2, 1], 25)]);
+
+        assert_eq!(winning_option(&proposal, 3, MultiChoiceTally::Plurality), Some(0));
+        assert_eq!(winning_option(&proposal, 3, MultiChoiceTally::InstantRunoff), Some(1));
+    }
+
+    #[test]
+    fn test_exhausted_ballots_and_ties() {
+        // C's ballots rank nothing else, so they are exhausted after round one
+        // and A's 40 of the remaining 75 is a majority
+        let exhausted = proposal(vec![(vec![0], 40), (vec![1], 35), (vec![2], 25)]);
+        assert_eq!(winning_option(&exhausted, 3, MultiChoiceTally::InstantRunoff), Some(0));
+
+        let tied = proposal(vec![(vec![0], 50), (vec![1], 50)]);
+        assert_eq!(winning_option(&tied, 3, MultiChoiceTally::Plurality), None);
+        assert_eq!(winning_option(&tied, 3, MultiChoiceTally::InstantRunoff), None);
+
+        assert_eq!(winning_option(&proposal(Vec::new()), 3, MultiChoiceTally::Plurality), None);
+    }
+
+    #[test]
+    fn test_ballot_and_option_validation() {
+        assert!(validate_ranking(&[2, 0], 3).is_ok());
+        assert!(validate_ranking(&[3], 3).is_err());
+        assert!(validate_ranking(&[1, 1], 3).is_err());
+        assert!(validate_ranking(&[], 3).is_err());
+
+        assert!(validate_options(&["Yes".to_string()]).is_err());
+        assert!(validate_options(&["A".to_string(), " A ".to_string()]).is_err());
+    }
+}
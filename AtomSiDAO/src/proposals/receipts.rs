@@ -0,0 +1,262 @@
+//! Signed vote receipts
+//!
+//! A voter may sign a [`vote_message`] stating the DAO and chain, the
+//! proposal, their choice, their voting power and when they signed, plus
+//! their ranking on a multiple-choice ballot. The signature is checked when
+//! the vote is cast and stored with the exact message signed, so the vote's
+//! receipt proves how the voter voted to anyone auditing the result
+//! off-chain, without trusting the DAO's database.
+//!
+//! With `governance.require_signed_votes` set, unsigned votes are refused.
+
+use super::types::{ProposalId, ProposalVote, Vote};
+use crate::core::{DaoError, Result};
+use crate::crypto;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A voter's signature over the [`vote_message`] for their vote
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VoteSignature {
+    /// Time in the signed message; signed messages older than
+    /// `security.signature_max_age_seconds` are refused
+    pub signed_at: DateTime<Utc>,
+    /// `personal_sign` signature over the message
+    pub signature: String,
+    /// The message that was signed; filled in when the vote is recorded, as
+    /// the vote's power can drop afterwards when delegators vote directly
+    #[serde(default)]
+    pub message: String,
+}
+
+/// Signed payload proving how a voter voted
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VoteReceipt {
+    /// Proposal voted on
+    pub proposal_id: ProposalId,
+    /// Voter address
+    pub voter: String,
+    /// Vote choice
+    pub vote: ProposalVote,
+    /// Voting power the vote is counted with; lower than the signed power
+    /// once delegators take their power back by voting directly
+    pub voting_power: u64,
+    /// Option indices ranked on a multiple-choice ballot
+    pub ranking: Vec<usize>,
+    /// Message the voter signed
+    pub message: String,
+    /// The voter's signature over `message`
+    pub signature: String,
+}
+
+/// Message a voter signs to vote
+///
+/// `dao` is the DAO's configured name and `chain_id` its chain, so a
+/// signature can't be replayed on another deployment. The voting power must
+/// be the power the vote will be counted with, which includes power
+/// delegated to the voter. `ranking` is empty except on multiple-choice
+/// ballots. The timestamp is given to the second, so clients can rebuild
+/// the exact message.
+pub fn vote_message(
+    dao: &str,
+    chain_id: u64,
+    proposal_id: &str,
+    vote: ProposalVote,
+    voting_power: u64,
+    ranking: &[usize],
+    signed_at: DateTime<Utc>,
+) -> String {
+    let mut message = format!(
+        "AtomSi DAO vote\nDAO: {}\nChain ID: {}\nProposal: {}\nChoice: {:?}\nVoting Power: {}\n",
+        dao, chain_id, proposal_id, vote, voting_power
+    );
+    if !ranking.is_empty() {
+        let ranking: Vec<String> = ranking.iter().map(usize::to_string).collect();
+        message.push_str(&format!("Ranking: {}\n", ranking.join(", ")));
+    }
+    message.push_str(&format!(
+        "{} {}",
+        crypto::ISSUED_AT_PREFIX,
+        signed_at.to_rfc3339_opts(SecondsFormat::Secs, true)
+    ));
+
+    message
+}
+
+/// Check a vote's signature before the vote is recorded
+///
+/// Returns the signature to store with the vote, including the message it
+/// covers. An unsigned vote is accepted only when signed votes are not
+/// required; a signed vote must be signed by the voter for this DAO and
+/// chain over exactly this choice, voting power and ranking.
+pub(crate) fn check_vote_signature(
+    dao: &str,
+    chain_id: u64,
+    proposal_id: &str,
+    vote: &Vote,
+    signature: Option<VoteSignature>,
+    require_signed_votes: bool,
+    max_age_seconds: u64,
+) -> Result<Option<VoteSignature>> {
+    let mut signature = match signature {
+        Some(signature) => signature,
+        None if require_signed_votes => {
+            return Err(DaoError::InvalidParameter(
+                "Votes must be signed (governance.require_signed_votes is set)".to_string(),
+            ));
+        }
+        None => return Ok(None),
+    };
+
+    let message = vote_message(
+        dao,
+        chain_id,
+        proposal_id,
+        vote.vote,
+        vote.voting_power,
+        &vote.ranking,
+        signature.signed_at,
+    );
+    let signed = crypto::verify_timestamped_signature(&vote.voter, &message, &signature.signature, max_age_seconds)
+        .unwrap_or(false);
+    if !signed {
+        return Err(DaoError::Unauthorized);
+    }
+
+    signature.message = message;
+    Ok(Some(signature))
+}
+
+/// Receipt for a recorded vote; unsigned votes have none
+///
+/// The receipt carries the stored signed message rather than one rebuilt
+/// from the vote, which may since have lost delegated power.
+pub(crate) fn receipt(proposal_id: &str, vote: &Vote) -> Result<VoteReceipt> {
+    let signature = vote.signature.as_ref().ok_or_else(|| {
+        DaoError::InvalidParameter(format!("Vote by {} was not signed", vote.voter))
+    })?;
+    if signature.message.is_empty() {
+        return Err(DaoError::InvalidParameter(format!(
+            "Vote by {} was recorded without its signed message",
+            vote.voter
+        )));
+    }
+
+    Ok(VoteReceipt {
+        proposal_id: proposal_id.to_string(),
+        voter: vote.voter.clone(),
+        vote: vote.vote,
+        voting_power: vote.voting_power,
+        ranking: vote.ranking.clone(),
+        message: signature.message.clone(),
+        signature: signature.signature.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::signers::LocalWallet;
+
+    /// Well-known development key (the first Anvil/Hardhat account)
+    const TEST_KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+    const TEST_ADDRESS: &str = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266";
+    const MAX_AGE: u64 = 300;
+    const DAO: &str = "AtomSi DAO";
+    const CHAIN_ID: u64 = 1;
+
+    fn sign(vote: ProposalVote, voting_power: u64) -> VoteSignature {
+        sign_at(vote, voting_power, Utc::now())
+    }
+
+    fn sign_at(vote: ProposalVote, voting_power: u64, signed_at: DateTime<Utc>) -> VoteSignature {
+        let wallet: LocalWallet = TEST_KEY.parse().unwrap();
+        let message = vote_message(DAO, CHAIN_ID, "AIP-1", vote, voting_power, &[], signed_at);
+        let signature = wallet.sign_hash(ethers::utils::hash_message(message)).unwrap();
+
+        VoteSignature {
+            signed_at,
+            signature: format!("0x{}", signature),
+            message: String::new(),
+        }
+    }
+
+    fn check(signature: Option<VoteSignature>, require_signed_votes: bool) -> Result<Option<VoteSignature>> {
+        check_vote_signature(DAO, CHAIN_ID, "AIP-1", &recorded(None), signature, require_signed_votes, MAX_AGE)
+    }
+
+    fn recorded(signature: Option<VoteSignature>) -> Vote {
+        Vote {
+            voter: TEST_ADDRESS.to_string(),
+            vote: ProposalVote::Yes,
+            voting_power: 100,
+            timestamp: Utc::now(),
+            delegated: Vec::new(),
+            ranking: Vec::new(),
+            signature,
+        }
+    }
+
+    #[test]
+    fn test_signed_vote_accepted_under_both_settings() {
+        for require_signed_votes in [false, true] {
+            let signature = sign(ProposalVote::Yes, 100);
+            let stored = check(Some(signature.clone()), require_signed_votes).unwrap().unwrap();
+            assert_eq!(stored.signature, signature.signature);
+            assert!(stored.message.contains("Voting Power: 100"));
+        }
+    }
+
+    #[test]
+    fn test_unsigned_vote_rejected_only_when_required() {
+        assert_eq!(check(None, false).unwrap(), None);
+        assert!(matches!(check(None, true), Err(DaoError::InvalidParameter(_))));
+    }
+
+    #[test]
+    fn test_signature_must_match_vote() {
+        // Signed for another choice or power than the vote counts with
+        assert!(matches!(check(Some(sign(ProposalVote::No, 100)), false), Err(DaoError::Unauthorized)));
+        assert!(matches!(check(Some(sign(ProposalVote::Yes, 99)), false), Err(DaoError::Unauthorized)));
+
+        // Signed long ago
+        let stale = sign_at(ProposalVote::Yes, 100, Utc::now() - chrono::Duration::seconds(MAX_AGE as i64 + 60));
+        assert!(check(Some(stale), false).is_err());
+
+        // Signed by someone else
+        let mut other = recorded(None);
+        other.voter = "0x70997970C51812dc3A010C7d01b50e0d17dc79C8".to_string();
+        let result = check_vote_signature(DAO, CHAIN_ID, "AIP-1", &other, Some(sign(ProposalVote::Yes, 100)), false, MAX_AGE);
+        assert!(matches!(result, Err(DaoError::Unauthorized)));
+
+        // Signed for another deployment
+        let vote = recorded(None);
+        for (dao, chain_id) in [("Other DAO", CHAIN_ID), (DAO, 137)] {
+            let result = check_vote_signature(dao, chain_id, "AIP-1", &vote, Some(sign(ProposalVote::Yes, 100)), false, MAX_AGE);
+            assert!(matches!(result, Err(DaoError::Unauthorized)));
+        }
+
+        // A ranked ballot's signature covers the ranking
+        let mut ranked = recorded(None);
+        ranked.ranking = vec![1, 0];
+        let result = check_vote_signature(DAO, CHAIN_ID, "AIP-1", &ranked, Some(sign(ProposalVote::Yes, 100)), false, MAX_AGE);
+        assert!(matches!(result, Err(DaoError::Unauthorized)));
+    }
+
+    #[test]
+    fn test_receipt_verifies_offline() {
+        let mut vote = recorded(None);
+        vote.signature = check(Some(sign(ProposalVote::Yes, 100)), true).unwrap();
+
+        // A delegator voting directly lowers the power after signing
+        vote.voting_power = 60;
+        let receipt = receipt("AIP-1", &vote).unwrap();
+
+        assert_eq!(receipt.voting_power, 60);
+        assert!(receipt.message.contains("Choice: Yes"));
+        assert!(receipt.message.contains("Voting Power: 100"));
+        assert!(crypto::verify_signature(&receipt.voter, &receipt.message, &receipt.signature).unwrap());
+
+        assert!(super::receipt("AIP-1", &recorded(None)).is_err());
+    }
+}
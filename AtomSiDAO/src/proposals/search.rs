@@ -0,0 +1,91 @@
+//! Keyword search over proposals
+//!
+//! On PostgreSQL, proposals are matched with full-text search against the
+//! generated `search_vector` column, which weighs the title above the
+//! description, and ranked with `ts_rank`. Words are stemmed, so "funding"
+//! finds "fund". SQLite has no full-text search here, so every word must
+//! appear, case-insensitively, in the title or description; a title hit
+//! scores 2 and a description hit 1.
+
+use super::types::Proposal;
+use crate::core::{escape_like, Database, DaoError, Result, SqlValue};
+use crate::database::DatabaseType;
+
+/// Full-text query, ranked by relevance, then newest first
+const POSTGRES_SEARCH: &str = "SELECT data FROM proposals, plainto_tsquery('english', $1) AS query \
+     WHERE search_vector @@ query AND deleted_at IS NULL ORDER BY ts_rank(search_vector, query) DESC, created_at DESC LIMIT $2";
+
+/// Find proposals matching every word of `query`, most relevant first
+///
+/// Soft-deleted proposals are never returned.
+pub(crate) async fn search_proposals(database: &Database, query: &str, limit: usize) -> Result<Vec<Proposal>> {
+    let words: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    if words.is_empty() || limit == 0 {
+        return Ok(Vec::new());
+    }
+    let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+
+    let rows = match database.db_type() {
+        DatabaseType::Postgres => database.query_any(POSTGRES_SEARCH, &[query.into(), limit.into()]).await?,
+        DatabaseType::SQLite => {
+            let (sql, params) = like_search(&words, limit);
+            database.query_any(&sql, &params).await?
+        }
+    };
+
+    rows.iter()
+        .map(|row| {
+            row.json("data")
+                .map_err(|e| DaoError::DatabaseError(format!("Failed to parse proposal: {}", e)))
+        })
+        .collect()
+}
+
+/// Build the SQLite fallback query for lower-cased, non-empty `words`
+fn like_search(words: &[String], limit: i64) -> (String, Vec<SqlValue>) {
+    let mut conditions = Vec::with_capacity(words.len());
+    let mut scores = Vec::with_capacity(words.len());
+    let mut params = Vec::with_capacity(words.len() + 1);
+
+    for (index, word) in words.iter().enumerate() {
+        let title = format!("LOWER(title) LIKE ${} ESCAPE '\\'", index + 1);
+        let description = format!("LOWER(description) LIKE ${} ESCAPE '\\'", index + 1);
+
+        conditions.push(format!("({} OR {})", title, description));
+        scores.push(format!(
+            "(CASE WHEN {} THEN 2 ELSE 0 END) + (CASE WHEN {} THEN 1 ELSE 0 END)",
+            title, description
+        ));
+        params.push(SqlValue::Text(format!("%{}%", escape_like(word))));
+    }
+    params.push(SqlValue::Int(limit));
+
+    let sql = format!(
+        "SELECT data FROM proposals WHERE deleted_at IS NULL AND {} ORDER BY {} DESC, created_at DESC LIMIT ${}",
+        conditions.join(" AND "),
+        scores.join(" + "),
+        words.len() + 1
+    );
+
+    (sql, params)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_like_search_escapes_wildcards() {
+        let (sql, params) = like_search(&["50%".to_string(), "fee".to_string()], 10);
+
+        assert!(sql.contains("LIKE $1 ESCAPE") && sql.contains("LIKE $2 ESCAPE") && sql.ends_with("LIMIT $3"));
+        assert_eq!(
+            params,
+            [
+                SqlValue::Text("%50\\%%".to_string()),
+                SqlValue::Text("%fee%".to_string()),
+                SqlValue::Int(10)
+            ]
+        );
+    }
+}
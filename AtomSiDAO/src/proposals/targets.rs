@@ -0,0 +1,132 @@
+//! Allowlist of `ContractCall` targets
+//!
+//! Approved contract calls run with the DAO's authority, so a malicious
+//! proposal that slips through a vote could call anything. The allowlist in
+//! `governance.callable_targets` limits calls to pre-approved contracts and,
+//! optionally, to specific functions on them.
+
+use crate::config::{CallableTarget, EmptyAllowlistPolicy, GovernanceConfig};
+use crate::core::{DaoError, Result};
+use sha3::{Digest, Keccak256};
+
+/// Check that a contract call is allowed by the configured targets
+pub(crate) fn check_callable_target(contract: &str, function: &str, governance: &GovernanceConfig) -> Result<()> {
+    let targets = &governance.callable_targets;
+
+    let allowed = if targets.is_empty() {
+        governance.empty_callable_targets == EmptyAllowlistPolicy::Allow
+    } else {
+        targets
+            .iter()
+            .filter(|target| target.address.eq_ignore_ascii_case(contract))
+            .any(|target| allows_function(target, function))
+    };
+
+    if !allowed {
+        return Err(DaoError::InvalidParameter(format!(
+            "Contract call {}.{} is not in governance.callable_targets",
+            contract, function
+        )));
+    }
+
+    Ok(())
+}
+
+/// Whether a target entry allows a function
+///
+/// Entries can be names (`transfer`), signatures (`transfer(address,uint256)`)
+/// or 4-byte selectors (`0xa9059cbb`). A target without entries allows any
+/// function.
+fn allows_function(target: &CallableTarget, function: &str) -> bool {
+    if target.functions.is_empty() {
+        return true;
+    }
+
+    let function = strip_whitespace(function);
+    let name = function.split('(').next().unwrap_or_default();
+    let selector = if is_selector(&function) {
+        Some(function.to_ascii_lowercase())
+    } else if function.contains('(') {
+        Some(function_selector(&function))
+    } else {
+        None
+    };
+
+    target.functions.iter().map(|entry| strip_whitespace(entry)).any(|entry| {
+        if is_selector(&entry) {
+            selector.as_deref() == Some(entry.to_ascii_lowercase().as_str())
+        } else if entry.contains('(') {
+            entry == function
+        } else {
+            entry == name
+        }
+    })
+}
+
+/// 4-byte selector of a function signature, `0x`-prefixed
+fn function_selector(signature: &str) -> String {
+    format!("0x{}", hex::encode(&Keccak256::digest(signature.as_bytes())[..4]))
+}
+
+/// Whether a value is a `0x`-prefixed 4-byte selector
+fn is_selector(value: &str) -> bool {
+    value
+        .strip_prefix("0x")
+        .map_or(false, |hex| hex.len() == 8 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+fn strip_whitespace(value: &str) -> String {
+    value.chars().filter(|c| !c.is_whitespace()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKEN: &str = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+    const GRANTS: &str = "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359";
+
+    fn governance(targets: Vec<CallableTarget>) -> GovernanceConfig {
+        GovernanceConfig {
+            callable_targets: targets,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_function_selector() {
+        assert_eq!(function_selector("transfer(address,uint256)"), "0xa9059cbb");
+    }
+
+    #[test]
+    fn test_allowlisted_functions() {
+        let governance = governance(vec![
+            CallableTarget {
+                address: TOKEN.to_string(),
+                functions: vec!["0xa9059cbb".to_string(), "approve".to_string()],
+            },
+            CallableTarget {
+                address: GRANTS.to_string(),
+                functions: Vec::new(),
+            },
+        ]);
+
+        // Matched by selector, name or address alone; addresses ignore case
+        assert!(check_callable_target(TOKEN, "transfer(address, uint256)", &governance).is_ok());
+        assert!(check_callable_target(&TOKEN.to_lowercase(), "0xA9059CBB", &governance).is_ok());
+        assert!(check_callable_target(TOKEN, "approve(address,uint256)", &governance).is_ok());
+        assert!(check_callable_target(GRANTS, "payout", &governance).is_ok());
+
+        assert!(check_callable_target(TOKEN, "transferOwnership(address)", &governance).is_err());
+        assert!(check_callable_target(TOKEN, "transfer", &governance).is_err());
+    }
+
+    #[test]
+    fn test_empty_allowlist_policy() {
+        let mut governance = governance(Vec::new());
+        assert!(check_callable_target(TOKEN, "transfer", &governance).is_ok());
+
+        governance.empty_callable_targets = EmptyAllowlistPolicy::Deny;
+        assert!(check_callable_target(TOKEN, "transfer", &governance).is_err());
+    }
+}
@@ -0,0 +1,419 @@
+//! Proposal templates
+//!
+//! Recurring proposals, such as monthly grants, share most of their text. A
+//! template stores that boilerplate once: a title pattern, a markdown
+//! description, the proposal type and default metadata. Text in any of them
+//! may contain `{placeholders}`, filled from variables when a proposal is
+//! started with [`ProposalBuilder::from_template`](super::ProposalBuilder::from_template).
+//!
+//! Anyone may read templates; only council members and admins may create,
+//! change or delete them.
+
+use super::types::ProposalType;
+use crate::core::{Database, DaoError, Result};
+use crate::identity::MemberRole;
+use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use uuid::Uuid;
+
+/// Maximum length of a template name in characters
+pub const MAX_TEMPLATE_NAME_LENGTH: usize = 100;
+
+lazy_static! {
+    /// `{name}`; other braces, such as in code blocks, are left alone
+    static ref PLACEHOLDER_REGEX: Regex = Regex::new(r"\{([A-Za-z_][A-Za-z0-9_]*)\}").unwrap();
+}
+
+/// A reusable proposal preset
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProposalTemplate {
+    /// Template ID
+    pub id: String,
+    /// Unique name, e.g. "Monthly grant"
+    pub name: String,
+    /// Title pattern
+    pub title: String,
+    /// Markdown description pattern
+    pub description: String,
+    /// Proposal type; its text fields may contain placeholders too
+    pub proposal_type: ProposalType,
+    /// Default proposal metadata; its strings may contain placeholders too
+    pub metadata: serde_json::Value,
+    /// Address of the member who created the template
+    pub created_by: String,
+    /// When the template was created
+    pub created_at: DateTime<Utc>,
+    /// When the template was last changed
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ProposalTemplate {
+    /// Create a new template without default metadata
+    pub fn new(name: &str, title: &str, description: &str, proposal_type: ProposalType) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name: name.trim().to_string(),
+            title: title.to_string(),
+            description: description.to_string(),
+            proposal_type,
+            metadata: serde_json::Value::Null,
+            created_by: String::new(),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    /// Set the default metadata
+    pub fn with_metadata(mut self, metadata: serde_json::Value) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Names of the variables the template needs, sorted
+    pub fn placeholders(&self) -> Vec<String> {
+        let mut names = BTreeSet::new();
+        let mut collect = |text: &str| {
+            for captures in PLACEHOLDER_REGEX.captures_iter(text) {
+                names.insert(captures[1].to_string());
+            }
+        };
+
+        collect(&self.title);
+        collect(&self.description);
+        for_each_string(&serde_json::to_value(&self.proposal_type).unwrap_or_default(), &mut collect);
+        for_each_string(&self.metadata, &mut collect);
+
+        names.into_iter().collect()
+    }
+
+    /// Fill the placeholders from `vars`
+    ///
+    /// Returns the template with every placeholder replaced. Fails, naming
+    /// them all, if any placeholder has no variable; unused variables are
+    /// ignored.
+    pub fn render(&self, vars: &HashMap<String, String>) -> Result<ProposalTemplate> {
+        let missing: Vec<String> = self
+            .placeholders()
+            .into_iter()
+            .filter(|name| !vars.contains_key(name))
+            .collect();
+        if !missing.is_empty() {
+            return Err(DaoError::InvalidParameter(format!(
+                "Missing template variables: {}",
+                missing.join(", ")
+            )));
+        }
+
+        let proposal_type: ProposalType = serde_json::to_value(&self.proposal_type)
+            .map(|value| render_value(value, vars))
+            .and_then(serde_json::from_value)
+            .map_err(|e| DaoError::InvalidParameter(format!("Template variables don't fit the proposal type: {}", e)))?;
+
+        Ok(ProposalTemplate {
+            title: render_text(&self.title, vars),
+            description: render_text(&self.description, vars),
+            proposal_type,
+            metadata: render_value(self.metadata.clone(), vars),
+            ..self.clone()
+        })
+    }
+}
+
+/// Check a template before storing it
+pub(crate) fn validate(template: &ProposalTemplate) -> Result<()> {
+    let name_length = template.name.trim().chars().count();
+    if name_length == 0 || name_length > MAX_TEMPLATE_NAME_LENGTH {
+        return Err(DaoError::InvalidParameter(format!(
+            "Template name must be 1-{} characters",
+            MAX_TEMPLATE_NAME_LENGTH
+        )));
+    }
+
+    if template.title.trim().is_empty() {
+        return Err(DaoError::InvalidParameter("Template title cannot be empty".to_string()));
+    }
+
+    if template.description.trim().is_empty() {
+        return Err(DaoError::InvalidParameter("Template description cannot be empty".to_string()));
+    }
+
+    Ok(())
+}
+
+/// Whether a member with `role` may create, change or delete templates
+pub(crate) fn can_manage(role: Option<MemberRole>) -> bool {
+    role.map_or(false, |role| role.is_at_least(MemberRole::Council))
+}
+
+fn render_text(text: &str, vars: &HashMap<String, String>) -> String {
+    PLACEHOLDER_REGEX
+        .replace_all(text, |captures: &Captures| vars[&captures[1]].clone())
+        .into_owned()
+}
+
+/// Fill the placeholders in every string of a JSON value
+fn render_value(value: serde_json::Value, vars: &HashMap<String, String>) -> serde_json::Value {
+    match value {
+        serde_json::Value::String(text) => serde_json::Value::String(render_text(&text, vars)),
+        serde_json::Value::Array(values) => values.into_iter().map(|value| render_value(value, vars)).collect(),
+        serde_json::Value::Object(fields) => fields
+            .into_iter()
+            .map(|(key, value)| (key, render_value(value, vars)))
+            .collect(),
+        other => other,
+    }
+}
+
+/// Call `f` with every string in a JSON value
+fn for_each_string(value: &serde_json::Value, f: &mut impl FnMut(&str)) {
+    match value {
+        serde_json::Value::String(text) => f(text),
+        serde_json::Value::Array(values) => {
+            for value in values {
+                for_each_string(value, f);
+            }
+        }
+        serde_json::Value::Object(fields) => {
+            for value in fields.values() {
+                for_each_string(value, f);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Store a new template
+///
+/// Portable: runs on PostgreSQL and SQLite.
+pub(crate) async fn insert_template(database: &Database, template: &ProposalTemplate, actor: &str) -> Result<()> {
+    check_name_free(database, template).await?;
+
+    database
+        .execute_any(
+            "INSERT INTO proposal_templates (id, name, data, created_at, updated_at, updated_by) \
+             VALUES ($1, $2, $3, $4, $5, $6)",
+            &[
+                template.id.as_str().into(),
+                template.name.as_str().into(),
+                template_data(template)?.into(),
+                template.created_at.timestamp().into(),
+                template.updated_at.timestamp().into(),
+                actor.into(),
+            ],
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Replace a stored template
+///
+/// Portable: runs on PostgreSQL and SQLite.
+pub(crate) async fn update_template(database: &Database, template: &ProposalTemplate, actor: &str) -> Result<()> {
+    check_name_free(database, template).await?;
+
+    let updated = database
+        .execute_any(
+            "UPDATE proposal_templates SET name = $1, data = $2, updated_at = $3, updated_by = $4 WHERE id = $5",
+            &[
+                template.name.as_str().into(),
+                template_data(template)?.into(),
+                template.updated_at.timestamp().into(),
+                actor.into(),
+                template.id.as_str().into(),
+            ],
+        )
+        .await?;
+
+    if updated == 0 {
+        return Err(template_not_found(&template.id));
+    }
+
+    Ok(())
+}
+
+/// Load a template by ID
+///
+/// Portable: runs on PostgreSQL and SQLite.
+pub(crate) async fn load_template(database: &Database, id: &str) -> Result<ProposalTemplate> {
+    database
+        .query_opt_any("SELECT data FROM proposal_templates WHERE id = $1", &[id.into()])
+        .await?
+        .ok_or_else(|| template_not_found(id))?
+        .json("data")
+}
+
+/// Load every template, by name
+///
+/// Portable: runs on PostgreSQL and SQLite.
+pub(crate) async fn load_templates(database: &Database) -> Result<Vec<ProposalTemplate>> {
+    database
+        .query_any("SELECT data FROM proposal_templates ORDER BY name, id", &[])
+        .await?
+        .iter()
+        .map(|row| row.json("data"))
+        .collect()
+}
+
+/// Delete a template
+///
+/// Portable: runs on PostgreSQL and SQLite.
+pub(crate) async fn delete_template(database: &Database, id: &str) -> Result<()> {
+    let deleted = database
+        .execute_any("DELETE FROM proposal_templates WHERE id = $1", &[id.into()])
+        .await?;
+
+    if deleted == 0 {
+        return Err(template_not_found(id));
+    }
+
+    Ok(())
+}
+
+/// Fail if another template already has this template's name
+async fn check_name_free(database: &Database, template: &ProposalTemplate) -> Result<()> {
+    let taken = database
+        .query_opt_any(
+            "SELECT id FROM proposal_templates WHERE name = $1 AND id <> $2",
+            &[template.name.as_str().into(), template.id.as_str().into()],
+        )
+        .await?;
+
+    if taken.is_some() {
+        return Err(DaoError::InvalidParameter(format!(
+            "A template named '{}' already exists",
+            template.name
+        )));
+    }
+
+    Ok(())
+}
+
+fn template_data(template: &ProposalTemplate) -> Result<serde_json::Value> {
+    serde_json::to_value(template)
+        .map_err(|e| DaoError::DatabaseError(format!("Failed to serialize template: {}", e)))
+}
+
+fn template_not_found(id: &str) -> DaoError {
+    DaoError::InvalidParameter(format!("Template {} not found", id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proposals::ProposalBuilder;
+    use serde_json::json;
+
+    const COUNCIL: &str = "0x0000000000000000000000000000000000000001";
+
+    fn grant_template() -> ProposalTemplate {
+        ProposalTemplate::new(
+            "Monthly grant",
+            "Grant for {project}: {month}",
+            "## {project}\n\nFunds {project} for {month}.\n\n```json\n{ \"budget\": \"see above\" }\n```",
+            ProposalType::Transfer {
+                to: "{recipient}".to_string(),
+                amount: 5_000,
+                token: "USDC".to_string(),
+            },
+        )
+        .with_metadata(json!({ "category": "grants", "tags": ["{project}", "recurring"] }))
+    }
+
+    fn vars(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn test_proposal_from_template_substitutes_variables() {
+        let template = grant_template();
+        assert_eq!(template.placeholders(), ["month", "project", "recipient"]);
+
+        let vars = vars(&[
+            ("project", "Block explorer"),
+            ("month", "March"),
+            ("recipient", "0x0000000000000000000000000000000000000002"),
+            ("unused", "ignored"),
+        ]);
+        let proposal = ProposalBuilder::from_template(&template, &vars)
+            .unwrap()
+            .proposer(COUNCIL)
+            .build()
+            .unwrap();
+
+        assert_eq!(proposal.title, "Grant for Block explorer: March");
+        // Braces that aren't placeholders, as in the code block, are kept
+        assert_eq!(
+            proposal.description,
+            "## Block explorer\n\nFunds Block explorer for March.\n\n```json\n{ \"budget\": \"see above\" }\n```"
+        );
+        assert_eq!(
+            proposal.proposal_type,
+            ProposalType::Transfer {
+                to: "0x0000000000000000000000000000000000000002".to_string(),
+                amount: 5_000,
+                token: "USDC".to_string(),
+            }
+        );
+        assert_eq!(proposal.metadata, json!({ "category": "grants", "tags": ["Block explorer", "recurring"] }));
+        assert_eq!(proposal.proposer, COUNCIL);
+    }
+
+    #[test]
+    fn test_missing_variables_are_named() {
+        let result = ProposalBuilder::from_template(&grant_template(), &vars(&[("project", "Explorer")]));
+
+        match result {
+            Err(DaoError::InvalidParameter(message)) => {
+                assert_eq!(message, "Missing template variables: month, recipient")
+            }
+            _ => panic!("expected missing variables to be rejected"),
+        }
+    }
+
+    #[test]
+    fn test_only_council_and_admins_manage_templates() {
+        assert!(can_manage(Some(MemberRole::Council)));
+        assert!(can_manage(Some(MemberRole::Admin)));
+        assert!(!can_manage(Some(MemberRole::Delegate)));
+        assert!(!can_manage(Some(MemberRole::Member)));
+        assert!(!can_manage(None));
+
+        assert!(validate(&grant_template()).is_ok());
+        assert!(validate(&ProposalTemplate::new(" ", "Title", "Body", grant_template().proposal_type)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_template_crud() {
+        let database = Database::migrated_sqlite().await;
+        let mut template = grant_template();
+        insert_template(&database, &template, COUNCIL).await.unwrap();
+
+        // Names are unique
+        let duplicate = ProposalTemplate::new("Monthly grant", "Other", "Other", template.proposal_type.clone());
+        assert!(insert_template(&database, &duplicate, COUNCIL).await.is_err());
+
+        let other = ProposalTemplate::new("Audit retainer", "Audit {quarter}", "Audit", template.proposal_type.clone());
+        insert_template(&database, &other, COUNCIL).await.unwrap();
+
+        template.title = "Grant: {project}".to_string();
+        update_template(&database, &template, COUNCIL).await.unwrap();
+        assert_eq!(load_template(&database, &template.id).await.unwrap(), template);
+
+        let names: Vec<String> = load_templates(&database)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|template| template.name)
+            .collect();
+        assert_eq!(names, ["Audit retainer", "Monthly grant"]);
+
+        delete_template(&database, &other.id).await.unwrap();
+        assert!(load_template(&database, &other.id).await.is_err());
+        assert!(delete_template(&database, &other.id).await.is_err());
+    }
+}
@@ -1,7 +1,9 @@
 //! Types for the proposals module
 
+use super::receipts::VoteSignature;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// Proposal ID type
 pub type ProposalId = String;
@@ -13,7 +15,9 @@ pub enum ProposalState {
     Draft,
     /// Proposal is in voting state
     Voting,
-    /// Proposal has been approved
+    /// Proposal has been approved and is waiting out the execution timelock
+    Queued,
+    /// Proposal has been approved and can be executed
     Approved,
     /// Proposal has been rejected
     Rejected,
@@ -23,6 +27,42 @@ pub enum ProposalState {
     Cancelled,
 }
 
+impl ProposalState {
+    /// Whether the state machine allows moving from this state to `next`
+    ///
+    /// ```text
+    /// Draft ──> Voting ──> Queued ──> Approved ──> Executed
+    ///   │        │││         │           ^
+    ///   │        ││└─────────┼───────────┘  (no timelock)
+    ///   │        │└──> Rejected
+    ///   └────────┴───────────┴──> Cancelled
+    /// ```
+    pub fn can_transition_to(&self, next: ProposalState) -> bool {
+        use ProposalState::*;
+        
+        matches!(
+            (self, next),
+            (Draft, Voting)
+                | (Draft, Cancelled)
+                | (Voting, Queued)
+                | (Voting, Approved)
+                | (Voting, Rejected)
+                | (Voting, Cancelled)
+                | (Queued, Approved)
+                | (Queued, Cancelled)
+                | (Approved, Executed)
+        )
+    }
+    
+    /// Whether the proposal can no longer change
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            ProposalState::Rejected | ProposalState::Executed | ProposalState::Cancelled
+        )
+    }
+}
+
 /// Proposal type enum
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ProposalType {
@@ -51,6 +91,16 @@ pub enum ProposalType {
         /// Parameter value
         value: serde_json::Value,
     },
+    /// Bundle of treasury swaps, e.g. to rebalance allocations
+    Swap {
+        /// Swaps to perform, in order
+        swaps: Vec<SwapAction>,
+    },
+    /// Choose between several options, e.g. competing funding plans
+    MultiChoice {
+        /// Options, referenced by index in ballots
+        options: Vec<String>,
+    },
     /// Text proposal
     TextProposal {
         /// Additional metadata
@@ -58,6 +108,17 @@ pub enum ProposalType {
     },
 }
 
+/// A single token swap
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SwapAction {
+    /// Token symbol to sell
+    pub from_token: String,
+    /// Token symbol to buy
+    pub to_token: String,
+    /// Amount of `from_token` to sell, in base units
+    pub amount: u64,
+}
+
 /// Proposal vote enum
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ProposalVote {
@@ -67,6 +128,8 @@ pub enum ProposalVote {
     No,
     /// Abstain from voting
     Abstain,
+    /// Vote against and block the proposal if enough weight vetoes it
+    Veto,
 }
 
 /// Vote record
@@ -76,10 +139,80 @@ pub struct Vote {
     pub voter: String,
     /// Vote choice
     pub vote: ProposalVote,
-    /// Voting power
+    /// Voting power, including delegated power
     pub voting_power: u64,
     /// Timestamp of the vote
     pub timestamp: DateTime<Utc>,
+    /// Delegated power included in `voting_power`, by delegator
+    #[serde(default)]
+    pub delegated: Vec<DelegatedPower>,
+    /// Option indices chosen on a multiple-choice proposal, most preferred first
+    #[serde(default)]
+    pub ranking: Vec<usize>,
+    /// The voter's signature over the vote, if they signed it
+    #[serde(default)]
+    pub signature: Option<VoteSignature>,
+}
+
+/// Power a delegator lent to a delegate's vote
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DelegatedPower {
+    /// Delegator address
+    pub delegator: String,
+    /// Delegated amount
+    pub amount: u64,
+}
+
+/// Turnout of a finalized vote
+///
+/// Both rates are fractions of the eligible supply, so
+/// `participation_rate - abstain_rate` is the share that voted decisively.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Participation {
+    /// Supply eligible to vote when the vote was finalized
+    pub eligible_supply: u64,
+    /// Share of the eligible supply that cast any vote, abstentions included
+    pub participation_rate: f64,
+    /// Share of the eligible supply that was present but abstained
+    pub abstain_rate: f64,
+}
+
+/// Document referenced by a proposal
+///
+/// The content hash pins the document, so a changed file at the same URI
+/// is detectable.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attachment {
+    /// `https://` or `ipfs://` URI of the document
+    pub uri: String,
+    /// Hex-encoded SHA-256 of the document content
+    pub content_hash: String,
+    /// MIME type of the document
+    pub mime_type: String,
+}
+
+/// Sponsor endorsement record
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Sponsor {
+    /// Sponsor address
+    pub member: String,
+    /// Voting weight of the sponsor at the time of sponsorship
+    pub weight: u64,
+    /// Timestamp of the sponsorship
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Deadline extension granted by the quiet-ending rule
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VotingExtension {
+    /// Deadline before the extension
+    pub previous_ends_at: DateTime<Utc>,
+    /// Deadline after the extension
+    pub new_ends_at: DateTime<Utc>,
+    /// Voter whose ballot triggered the extension
+    pub triggered_by: String,
+    /// Why voting was extended
+    pub reason: String,
 }
 
 /// Proposal structure
@@ -87,6 +220,9 @@ pub struct Vote {
 pub struct Proposal {
     /// Proposal ID
     pub id: ProposalId,
+    /// Sequential proposal number, assigned on submission
+    #[serde(default)]
+    pub number: Option<u64>,
     /// Proposal title
     pub title: String,
     /// Proposal description
@@ -105,8 +241,14 @@ pub struct Proposal {
     pub voting_starts_at: Option<DateTime<Utc>>,
     /// Voting end timestamp
     pub voting_ends_at: Option<DateTime<Utc>>,
+    /// Block at which voting power is measured, recorded when voting starts
+    #[serde(default)]
+    pub snapshot_block: Option<u64>,
     /// Execution timestamp
     pub execution_date: Option<DateTime<Utc>>,
+    /// Earliest time an approved proposal can be executed
+    #[serde(default)]
+    pub execution_available_at: Option<DateTime<Utc>>,
     /// Additional metadata
     pub metadata: serde_json::Value,
     /// Yes votes count (weighted)
@@ -115,6 +257,40 @@ pub struct Proposal {
     pub no_votes: u64,
     /// Abstain votes count (weighted)
     pub abstain_votes: u64,
+    /// Veto votes count (weighted)
+    #[serde(default)]
+    pub veto_votes: u64,
     /// Individual votes
     pub votes: Vec<Vote>,
+    /// Sponsor endorsements gathered while in draft
+    #[serde(default)]
+    pub sponsors: Vec<Sponsor>,
+    /// Code hash of the target contract recorded at approval
+    #[serde(default)]
+    pub target_code_hash: Option<String>,
+    /// Quiet-ending extensions of the voting period
+    #[serde(default)]
+    pub extensions: Vec<VotingExtension>,
+    /// Documents referenced by the proposal
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
+    /// Turnout recorded when voting was finalized
+    #[serde(default)]
+    pub participation: Option<Participation>,
+    /// When the content was committed, for proposals submitted by commit-reveal
+    #[serde(default)]
+    pub committed_at: Option<DateTime<Utc>>,
+    /// First-preference voting power per option of a multiple-choice proposal
+    #[serde(default)]
+    pub option_votes: HashMap<usize, u64>,
+    /// Option chosen when a multiple-choice vote was finalized
+    #[serde(default)]
+    pub winning_option: Option<usize>,
+}
+
+impl Proposal {
+    /// Human-friendly id such as `AIP-42`, once the proposal is numbered
+    pub fn display_id(&self, prefix: &str) -> Option<String> {
+        self.number.map(|number| format!("{}-{}", prefix, number))
+    }
 } 
\ No newline at end of file
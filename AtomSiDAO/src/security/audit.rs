@@ -0,0 +1,437 @@
+//! Tamper-evident admin action log
+//!
+//! Admin actions are appended to a hash chain: each entry records the hash
+//! of the entry before it, so an edited or removed entry breaks every later
+//! link. Entries for the actions listed in `security.audit_shipping.actions`
+//! are also shipped, HMAC-signed, to an external collector such as a SIEM.
+//! Shipping runs from an in-memory buffer and retries on failure, so it
+//! never blocks the action being logged.
+
+use crate::config::AuditShippingConfig;
+use crate::core::{Database, DaoError, DbRow, Result};
+use crate::crypto;
+use crate::utils::time::{current_timestamp, rfc3339};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+/// `previous_hash` of the first entry in the chain
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Header carrying the HMAC-SHA256 of a shipped entry
+pub const SIGNATURE_HEADER: &str = "X-AtomSi-Signature";
+
+/// High-sensitivity admin actions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AdminAction {
+    /// The DAO or a module was paused
+    Pause,
+    /// The DAO or a module was unpaused
+    Unpause,
+    /// A member's role was changed
+    RoleChange,
+    /// A permission was granted
+    PermissionGrant,
+    /// A permission was revoked
+    PermissionRevoke,
+    /// A secret or key was rotated
+    SecretRotation,
+    /// A proposal or transaction was vetoed
+    Veto,
+}
+
+/// An entry in the admin action log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// Position in the chain, starting at 1
+    pub sequence: u64,
+    /// Action taken
+    pub action: AdminAction,
+    /// Who took the action
+    pub actor: String,
+    /// Action-specific details
+    pub details: serde_json::Value,
+    /// When the action was taken
+    #[serde(with = "rfc3339")]
+    pub recorded_at: DateTime<Utc>,
+    /// Hash of the previous entry, or [`GENESIS_HASH`] for the first
+    pub previous_hash: String,
+    /// Hash of this entry's fields, including `previous_hash`
+    pub hash: String,
+}
+
+/// Build the entry that follows `previous` in the chain
+pub(crate) fn chain_entry(
+    previous: Option<&AuditEntry>,
+    action: AdminAction,
+    actor: &str,
+    details: serde_json::Value,
+    recorded_at: DateTime<Utc>,
+) -> Result<AuditEntry> {
+    let mut entry = AuditEntry {
+        sequence: previous.map_or(1, |previous| previous.sequence + 1),
+        action,
+        actor: actor.to_string(),
+        details,
+        recorded_at,
+        previous_hash: previous.map_or_else(|| GENESIS_HASH.to_string(), |previous| previous.hash.clone()),
+        hash: String::new(),
+    };
+    entry.hash = entry_hash(&entry)?;
+
+    Ok(entry)
+}
+
+/// Sequence of the first entry whose hash or link to its predecessor is wrong
+///
+/// `entries` must be consecutive and in order; a missing entry shows up as a
+/// broken link. Returns `None` when the chain is intact.
+pub fn first_broken_link(entries: &[AuditEntry]) -> Option<u64> {
+    let mut previous: Option<&AuditEntry> = None;
+
+    for entry in entries {
+        let linked = match previous {
+            Some(previous) => entry.sequence == previous.sequence + 1 && entry.previous_hash == previous.hash,
+            None => entry.sequence != 1 || entry.previous_hash == GENESIS_HASH,
+        };
+
+        if !linked || entry_hash(entry).ok().as_deref() != Some(entry.hash.as_str()) {
+            return Some(entry.sequence);
+        }
+
+        previous = Some(entry);
+    }
+
+    None
+}
+
+/// HMAC-SHA256 of an entry's canonical JSON, hex-encoded
+pub fn sign_entry(entry: &AuditEntry, secret: &[u8]) -> Result<String> {
+    let canonical = crate::utils::to_canonical_json(entry).map_err(DaoError::InternalError)?;
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret)
+        .map_err(|e| DaoError::InternalError(format!("Invalid audit signing key: {}", e)))?;
+    mac.update(canonical.as_bytes());
+
+    Ok(hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Hash of every field but `hash` itself
+fn entry_hash(entry: &AuditEntry) -> Result<String> {
+    let content = serde_json::json!({
+        "sequence": entry.sequence,
+        "action": entry.action,
+        "actor": entry.actor,
+        "details": entry.details,
+        "recorded_at": entry.recorded_at.timestamp_micros(),
+        "previous_hash": entry.previous_hash,
+    });
+
+    crypto::hash_json(&content)
+        .map(hex::encode)
+        .map_err(|e| DaoError::InternalError(format!("Failed to hash audit entry: {}", e)))
+}
+
+/// Append-only admin action log
+pub struct AuditLog {
+    database: Database,
+    shipper: Option<Arc<AuditShipper>>,
+    /// Serializes appends so entries chain in order
+    append_lock: tokio::sync::Mutex<()>,
+}
+
+impl AuditLog {
+    /// Create an audit log, shipping entries when `shipping.enabled` is set
+    pub fn new(database: Database, shipping: &AuditShippingConfig) -> Self {
+        Self {
+            database,
+            shipper: shipping.enabled.then(|| Arc::new(AuditShipper::new(shipping.clone()))),
+            append_lock: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// The shipper, when shipping is enabled
+    pub fn shipper(&self) -> Option<&Arc<AuditShipper>> {
+        self.shipper.as_ref()
+    }
+
+    /// Record an admin action
+    ///
+    /// The entry is stored before it is queued for shipping; a shipping
+    /// failure never fails the action.
+    pub async fn record(&self, action: AdminAction, actor: &str, details: serde_json::Value) -> Result<AuditEntry> {
+        let _guard = self.append_lock.lock().await;
+
+        let previous = self.latest().await?;
+        let entry = chain_entry(previous.as_ref(), action, actor, details, Utc::now())?;
+
+        let data = serde_json::to_value(&entry)
+            .map_err(|e| DaoError::DatabaseError(format!("Failed to serialize audit entry: {}", e)))?;
+        self.database
+            .execute_any(
+                "INSERT INTO admin_audit_log (sequence, hash, data, created_at) VALUES ($1, $2, $3, $4)",
+                &[(entry.sequence as i64).into(), entry.hash.as_str().into(), data.into(), (current_timestamp() as i64).into()],
+            )
+            .await?;
+
+        if let Some(shipper) = &self.shipper {
+            if shipper.ships(action) {
+                shipper.enqueue(entry.clone());
+            }
+        }
+
+        Ok(entry)
+    }
+
+    /// Entries from `from_sequence` on, in order
+    pub async fn entries(&self, from_sequence: u64) -> Result<Vec<AuditEntry>> {
+        let rows = self
+            .database
+            .query_any(
+                "SELECT data FROM admin_audit_log WHERE sequence >= $1 ORDER BY sequence",
+                &[(from_sequence as i64).into()],
+            )
+            .await?;
+
+        rows.iter().map(parse_entry).collect()
+    }
+
+    /// The last entry in the chain
+    async fn latest(&self) -> Result<Option<AuditEntry>> {
+        let row = self
+            .database
+            .query_opt_any("SELECT data FROM admin_audit_log ORDER BY sequence DESC LIMIT 1", &[])
+            .await?;
+
+        row.as_ref().map(parse_entry).transpose()
+    }
+}
+
+fn parse_entry(row: &DbRow) -> Result<AuditEntry> {
+    row.json("data")
+        .map_err(|e| DaoError::DatabaseError(format!("Failed to parse audit entry: {}", e)))
+}
+
+/// Record an admin action in a manager's audit log, if it has one
+pub(crate) async fn record_admin_action(
+    audit_log: Option<&Arc<AuditLog>>,
+    action: AdminAction,
+    actor: &str,
+    details: serde_json::Value,
+) -> Result<()> {
+    if let Some(audit_log) = audit_log {
+        audit_log.record(action, actor, details).await?;
+    }
+
+    Ok(())
+}
+
+/// Ships audit entries to the configured endpoint
+///
+/// Entries wait in a bounded buffer and are sent in order, at most
+/// `max_entries_per_interval` per interval. A failed send stops the round and
+/// is retried next interval. When the buffer overflows the oldest entries are
+/// dropped and counted; the receiver sees the gap as a broken chain.
+pub struct AuditShipper {
+    config: AuditShippingConfig,
+    client: reqwest::Client,
+    buffer: Mutex<VecDeque<AuditEntry>>,
+    dropped: AtomicU64,
+}
+
+impl AuditShipper {
+    /// Create a shipper
+    pub fn new(config: AuditShippingConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.request_timeout_seconds))
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            config,
+            client,
+            buffer: Mutex::new(VecDeque::new()),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    /// Whether entries for an action are shipped
+    pub fn ships(&self, action: AdminAction) -> bool {
+        self.config.actions.contains(&action)
+    }
+
+    /// Queue an entry for shipping
+    pub fn enqueue(&self, entry: AuditEntry) {
+        let mut buffer = self.buffer.lock().unwrap();
+        buffer.push_back(entry);
+
+        while buffer.len() > self.config.buffer_size.max(1) {
+            if let Some(dropped) = buffer.pop_front() {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+                warn!("Audit shipping buffer full; dropped entry {}", dropped.sequence);
+            }
+        }
+    }
+
+    /// Entries waiting to be shipped
+    pub fn pending(&self) -> usize {
+        self.buffer.lock().unwrap().len()
+    }
+
+    /// Entries dropped because the buffer was full
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Ship up to one interval's worth of entries
+    ///
+    /// Returns the number shipped.
+    pub async fn ship_pending(&self) -> usize {
+        let mut shipped = 0;
+
+        while shipped < self.config.max_entries_per_interval {
+            let Some(entry) = self.buffer.lock().unwrap().front().cloned() else {
+                break;
+            };
+
+            if let Err(e) = self.send(&entry).await {
+                warn!("Failed to ship audit entry {}, will retry: {}", entry.sequence, e);
+                break;
+            }
+
+            // Only the shipping task pops, so the front is still this entry
+            // unless it was dropped on overflow meanwhile
+            {
+                let mut buffer = self.buffer.lock().unwrap();
+                if buffer.front().map(|front| front.sequence) == Some(entry.sequence) {
+                    buffer.pop_front();
+                }
+            }
+            shipped += 1;
+        }
+
+        shipped
+    }
+
+    /// Ship buffered entries every `ship_interval_seconds`
+    pub fn spawn_shipper(self: &Arc<Self>) -> Option<JoinHandle<()>> {
+        if !self.config.enabled || self.config.ship_interval_seconds == 0 {
+            return None;
+        }
+
+        let shipper = Arc::clone(self);
+        let period = Duration::from_secs(shipper.config.ship_interval_seconds);
+
+        Some(tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                shipper.ship_pending().await;
+            }
+        }))
+    }
+
+    /// Post a signed entry to the endpoint
+    async fn send(&self, entry: &AuditEntry) -> Result<()> {
+        let signature = sign_entry(entry, self.config.hmac_secret.as_bytes())?;
+
+        self.client
+            .post(&self.config.endpoint_url)
+            .header(SIGNATURE_HEADER, format!("sha256={}", signature))
+            .json(entry)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| DaoError::ExternalServiceError(format!("Audit shipping failed: {}", e)))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain(actions: &[(AdminAction, &str)]) -> Vec<AuditEntry> {
+        let mut entries: Vec<AuditEntry> = Vec::new();
+        for (i, &(action, actor)) in actions.iter().enumerate() {
+            let details = serde_json::json!({ "step": i });
+            let entry = chain_entry(entries.last(), action, actor, details, Utc::now()).unwrap();
+            entries.push(entry);
+        }
+        entries
+    }
+
+    #[test]
+    fn test_hash_chain_links_admin_actions() {
+        let entries = chain(&[
+            (AdminAction::Pause, "0xAdmin"),
+            (AdminAction::RoleChange, "0xAdmin"),
+            (AdminAction::PermissionGrant, "0xCouncil"),
+            (AdminAction::SecretRotation, "0xAdmin"),
+            (AdminAction::Veto, "0xGuardian"),
+        ]);
+
+        assert_eq!(entries[0].sequence, 1);
+        assert_eq!(entries[0].previous_hash, GENESIS_HASH);
+        for pair in entries.windows(2) {
+            assert_eq!(pair[1].previous_hash, pair[0].hash);
+            assert_eq!(pair[1].sequence, pair[0].sequence + 1);
+        }
+        assert_eq!(first_broken_link(&entries), None);
+
+        // Tampering with an entry breaks its own hash
+        let mut tampered = entries.clone();
+        tampered[2].actor = "0xMallory".to_string();
+        assert_eq!(first_broken_link(&tampered), Some(3));
+
+        // Removing an entry breaks the next link
+        let mut gapped = entries.clone();
+        gapped.remove(1);
+        assert_eq!(first_broken_link(&gapped), Some(3));
+
+        // Rehashing a tampered entry still breaks the link after it
+        let mut rehashed = entries;
+        rehashed[2].actor = "0xMallory".to_string();
+        rehashed[2].hash = entry_hash(&rehashed[2]).unwrap();
+        assert_eq!(first_broken_link(&rehashed), Some(4));
+    }
+
+    #[test]
+    fn test_signature_depends_on_secret_and_content() {
+        let entries = chain(&[(AdminAction::Pause, "0xAdmin"), (AdminAction::Unpause, "0xAdmin")]);
+
+        let signature = sign_entry(&entries[0], b"secret").unwrap();
+        assert_eq!(signature, sign_entry(&entries[0], b"secret").unwrap());
+        assert_ne!(signature, sign_entry(&entries[0], b"other secret").unwrap());
+        assert_ne!(signature, sign_entry(&entries[1], b"secret").unwrap());
+    }
+
+    #[test]
+    fn test_full_buffer_drops_oldest() {
+        let shipper = AuditShipper::new(AuditShippingConfig {
+            enabled: true,
+            buffer_size: 2,
+            ..Default::default()
+        });
+
+        for entry in chain(&[
+            (AdminAction::Pause, "0xAdmin"),
+            (AdminAction::RoleChange, "0xAdmin"),
+            (AdminAction::Veto, "0xAdmin"),
+        ]) {
+            shipper.enqueue(entry);
+        }
+
+        assert_eq!(shipper.pending(), 2);
+        assert_eq!(shipper.dropped(), 1);
+        assert_eq!(shipper.buffer.lock().unwrap().front().unwrap().sequence, 2);
+    }
+}
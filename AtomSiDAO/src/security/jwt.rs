@@ -0,0 +1,100 @@
+//! JSON Web Tokens for AtomSi DAO
+//!
+//! Signed-in members get an HS256 JWT keyed with `security.jwt_secret`
+//! alongside their session, so API calls can be authenticated without a
+//! session lookup. Tokens carry the member's address and role and expire
+//! after `security.jwt_expiration_seconds`.
+
+use crate::core::{DaoError, Result};
+use crate::identity::MemberRole;
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+
+/// Claims carried by a token
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Claims {
+    /// Member address
+    pub sub: String,
+    /// Member role when the token was issued
+    pub role: MemberRole,
+    /// Expiry, in seconds since the Unix epoch
+    pub exp: i64,
+    /// Issue time, in seconds since the Unix epoch
+    pub iat: i64,
+}
+
+/// Sign a token for `address` valid from `now` for `lifetime`
+pub(crate) fn encode(
+    address: &str,
+    role: MemberRole,
+    now: DateTime<Utc>,
+    lifetime: Duration,
+    secret: &[u8],
+) -> Result<String> {
+    let claims = Claims {
+        sub: address.to_string(),
+        role,
+        exp: (now + lifetime).timestamp(),
+        iat: now.timestamp(),
+    };
+
+    jsonwebtoken::encode(&Header::new(Algorithm::HS256), &claims, &EncodingKey::from_secret(secret))
+        .map_err(|e| DaoError::InternalError(format!("Failed to sign JWT: {}", e)))
+}
+
+/// Check a token's signature and expiry and return its claims
+pub(crate) fn decode(token: &str, secret: &[u8]) -> Result<Claims> {
+    let mut validation = Validation::new(Algorithm::HS256);
+    validation.leeway = 0;
+    validation.set_required_spec_claims(&["exp", "sub"]);
+
+    jsonwebtoken::decode::<Claims>(token, &DecodingKey::from_secret(secret), &validation)
+        .map(|data| data.claims)
+        .map_err(|_| DaoError::Unauthorized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &[u8] = b"jwt-test-secret";
+    const ADDRESS: &str = "0x0000000000000000000000000000000000000001";
+
+    #[test]
+    fn test_valid_token() {
+        let now = Utc::now();
+        let token = encode(ADDRESS, MemberRole::Council, now, Duration::hours(1), SECRET).unwrap();
+
+        let claims = decode(&token, SECRET).unwrap();
+        assert_eq!(claims.sub, ADDRESS);
+        assert_eq!(claims.role, MemberRole::Council);
+        assert_eq!(claims.iat, now.timestamp());
+        assert_eq!(claims.exp, now.timestamp() + 3600);
+    }
+
+    #[test]
+    fn test_expired_token_rejected() {
+        let issued = Utc::now() - Duration::hours(2);
+        let token = encode(ADDRESS, MemberRole::Member, issued, Duration::hours(1), SECRET).unwrap();
+
+        assert!(matches!(decode(&token, SECRET), Err(DaoError::Unauthorized)));
+    }
+
+    #[test]
+    fn test_tampered_token_rejected() {
+        let token = encode(ADDRESS, MemberRole::Member, Utc::now(), Duration::hours(1), SECRET).unwrap();
+
+        // Another key's signature doesn't verify
+        assert!(matches!(decode(&token, b"another-secret"), Err(DaoError::Unauthorized)));
+
+        // Neither does a payload swapped in from a token granting more
+        let admin = encode(ADDRESS, MemberRole::Admin, Utc::now(), Duration::hours(1), b"another-secret").unwrap();
+        let parts: Vec<&str> = token.split('.').collect();
+        let admin_payload = admin.split('.').nth(1).unwrap();
+        let forged = format!("{}.{}.{}", parts[0], admin_payload, parts[2]);
+        assert!(matches!(decode(&forged, SECRET), Err(DaoError::Unauthorized)));
+
+        assert!(decode(&token, SECRET).is_ok());
+    }
+}
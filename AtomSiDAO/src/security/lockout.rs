@@ -0,0 +1,172 @@
+//! Login lockout
+//!
+//! Failed authentications are counted per address in `login_attempts`. Once
+//! an address reaches `security.max_login_attempts` failures it is locked out
+//! for `security.login_lockout_seconds`, and authentication is refused with
+//! [`DaoError::RateLimitError`] until the lockout ends. A successful
+//! authentication clears the count. A `max_login_attempts` of 0 disables the
+//! lockout.
+//!
+//! Only new sign-ins are locked. Sessions and JWTs issued before the lockout
+//! keep working, so failures submitted by someone else for an address can't
+//! end its owner's existing sessions.
+
+use crate::core::{Database, DaoError, Result};
+use chrono::{DateTime, Duration, Utc};
+
+/// Lockout settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct LockoutPolicy {
+    /// Failures that trigger a lockout (0 disables it)
+    pub max_attempts: u32,
+    /// How long a lockout lasts
+    pub lockout: Duration,
+}
+
+/// Refuse authentication for an address that is locked out
+pub(crate) async fn check_lockout(database: &Database, address: &str, now: DateTime<Utc>) -> Result<()> {
+    match locked_until(database, address).await? {
+        Some(locked_until) if locked_until > now.timestamp() => Err(DaoError::RateLimitError {
+            retry_after_seconds: (locked_until - now.timestamp()) as u64,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Record a failed authentication for an address
+///
+/// Returns the lockout error when this failure locks the address out.
+pub(crate) async fn record_failure(
+    database: &Database,
+    address: &str,
+    policy: LockoutPolicy,
+    now: DateTime<Utc>,
+) -> Result<()> {
+    if policy.max_attempts == 0 {
+        return Ok(());
+    }
+
+    // Count the failure in one statement so concurrent failures all count;
+    // failures before an expired lockout don't count towards the next one
+    let expired = "login_attempts.locked_until IS NOT NULL AND login_attempts.locked_until <= $2";
+    let count_failure = format!(
+        "INSERT INTO login_attempts (address, failed_attempts, locked_until, updated_at) VALUES ($1, 1, NULL, $2) \
+         ON CONFLICT (address) DO UPDATE SET \
+         failed_attempts = CASE WHEN {expired} THEN 1 ELSE login_attempts.failed_attempts + 1 END, \
+         locked_until = CASE WHEN {expired} THEN NULL ELSE login_attempts.locked_until END, \
+         updated_at = $2"
+    );
+    let affected = database
+        .execute_batch_any(&[
+            (count_failure.as_str(), vec![address.into(), now.timestamp().into()]),
+            (
+                "UPDATE login_attempts SET locked_until = $2 \
+                 WHERE address = $1 AND locked_until IS NULL AND failed_attempts >= $3",
+                vec![
+                    address.into(),
+                    (now + policy.lockout).timestamp().into(),
+                    i64::from(policy.max_attempts).into(),
+                ],
+            ),
+        ])
+        .await?;
+
+    // Only the failure that reached the limit reports the lockout
+    if affected[1] == 1 {
+        return Err(DaoError::RateLimitError {
+            retry_after_seconds: policy.lockout.num_seconds().max(0) as u64,
+        });
+    }
+
+    Ok(())
+}
+
+/// Clear the failure count for an address after a successful authentication
+pub(crate) async fn reset(database: &Database, address: &str) -> Result<()> {
+    database
+        .execute_any("DELETE FROM login_attempts WHERE address = $1", &[address.into()])
+        .await?;
+
+    Ok(())
+}
+
+/// When the address's current or last lockout ends, if it was ever locked
+async fn locked_until(database: &Database, address: &str) -> Result<Option<i64>> {
+    let row = database
+        .query_opt_any(
+            "SELECT COALESCE(locked_until, 0) AS locked_until FROM login_attempts WHERE address = $1",
+            &[address.into()],
+        )
+        .await?;
+
+    let Some(row) = row else {
+        return Ok(None);
+    };
+
+    let locked_until = row.int("locked_until")?;
+    Ok((locked_until > 0).then_some(locked_until))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDRESS: &str = "0x0000000000000000000000000000000000000001";
+
+    fn policy() -> LockoutPolicy {
+        LockoutPolicy {
+            max_attempts: 3,
+            lockout: Duration::minutes(15),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_repeated_failures_lock_out_until_expiry() {
+        let database = Database::migrated_sqlite().await;
+        let now = Utc::now();
+
+        record_failure(&database, ADDRESS, policy(), now).await.unwrap();
+        record_failure(&database, ADDRESS, policy(), now).await.unwrap();
+        assert!(check_lockout(&database, ADDRESS, now).await.is_ok());
+
+        // The third failure locks the address out
+        assert!(matches!(
+            record_failure(&database, ADDRESS, policy(), now).await,
+            Err(DaoError::RateLimitError { retry_after_seconds: 900 })
+        ));
+        let later = now + Duration::minutes(5);
+        assert!(matches!(
+            check_lockout(&database, ADDRESS, later).await,
+            Err(DaoError::RateLimitError { retry_after_seconds: 600 })
+        ));
+
+        // Once the lockout ends the address may try again, with a fresh count
+        let expired = now + Duration::minutes(16);
+        assert!(check_lockout(&database, ADDRESS, expired).await.is_ok());
+        record_failure(&database, ADDRESS, policy(), expired).await.unwrap();
+        assert!(check_lockout(&database, ADDRESS, expired).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_success_resets_count() {
+        let database = Database::migrated_sqlite().await;
+        let now = Utc::now();
+
+        record_failure(&database, ADDRESS, policy(), now).await.unwrap();
+        record_failure(&database, ADDRESS, policy(), now).await.unwrap();
+        reset(&database, ADDRESS).await.unwrap();
+
+        record_failure(&database, ADDRESS, policy(), now).await.unwrap();
+        record_failure(&database, ADDRESS, policy(), now).await.unwrap();
+        assert!(check_lockout(&database, ADDRESS, now).await.is_ok());
+
+        // Without an attempt limit nothing is locked out
+        let disabled = LockoutPolicy { max_attempts: 0, ..policy() };
+        for _ in 0..5 {
+            record_failure(&database, "0x0000000000000000000000000000000000000002", disabled, now)
+                .await
+                .unwrap();
+        }
+        assert!(check_lockout(&database, "0x0000000000000000000000000000000000000002", now).await.is_ok());
+    }
+}
@@ -3,16 +3,22 @@
 //! This module provides functionality for authentication, authorization,
 //! and cryptographic operations for the DAO.
 
+mod audit;
+mod jwt;
+mod lockout;
 mod permissions;
 
+pub use audit::{first_broken_link, sign_entry, AdminAction, AuditEntry, AuditLog, AuditShipper, GENESIS_HASH, SIGNATURE_HEADER};
+pub(crate) use audit::record_admin_action;
+pub use jwt::Claims;
 pub use permissions::{Permission, PermissionManager, Resource};
 
 use crate::{
     blockchain::BlockchainAdapter,
     config::Config,
-    core::{Database, DaoError, Result},
+    core::{Address, Database, DaoError, Result},
     crypto,
-    identity::{IdentityManager, MemberRole},
+    identity::{IdentityManager, MemberRole, MemberStatus},
 };
 use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
@@ -43,6 +49,26 @@ pub struct Session {
     pub is_active: bool,
 }
 
+/// A signed-in member's session and JWT
+#[derive(Debug, Clone)]
+pub struct Authentication {
+    /// Session created for the sign-in
+    pub session: Session,
+    /// JWT for the member, see [`AuthManager::issue_jwt`]
+    pub token: String,
+}
+
+/// The member an API request was authenticated as
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthenticatedMember {
+    /// Member address
+    pub address: String,
+    /// Member's current role
+    pub role: MemberRole,
+    /// Session ID, when authenticated with a session
+    pub session_id: Option<SessionId>,
+}
+
 /// Authentication type enum
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum AuthenticationType {
@@ -88,40 +114,94 @@ impl AuthManager {
     }
     
     /// Authenticate a user with signature
+    ///
+    /// Returns both a session and a JWT for the member. Addresses locked out
+    /// after too many failed attempts get `RateLimitError`.
     pub async fn authenticate_with_signature(
         &self,
         address: &str,
         message: &str,
         signature: &str,
-    ) -> Result<Session> {
-        // Verify the signature
-        let is_valid = crypto::verify_signature(address, message, signature)
-            .map_err(|e| DaoError::SecurityError(e.to_string()))?;
+    ) -> Result<Authentication> {
+        // Normalize the address before it reaches the member records
+        let parsed = Address::parse(address)?;
+        let database = self.database.primary();
+        let now = Utc::now();
         
-        if !is_valid {
-            return Err(DaoError::Unauthorized);
+        lockout::check_lockout(&database, parsed.as_str(), now).await?;
+        
+        // Verify the signature and that the signed message is recent; a
+        // malformed signature or a stale message counts as a failed attempt
+        let verified = crypto::verify_timestamped_signature(
+            address,
+            message,
+            signature,
+            self.config.security.signature_max_age_seconds,
+        );
+        
+        if !matches!(verified, Ok(true)) {
+            lockout::record_failure(&database, parsed.as_str(), self.lockout_policy(), now).await?;
+            return match verified {
+                Err(e) => Err(DaoError::SecurityError(e.to_string())),
+                _ => Err(DaoError::Unauthorized),
+            };
         }
         
+        lockout::reset(&database, parsed.as_str()).await?;
+        let address = parsed;
+        
         // Check if the address is a member
-        let is_member = self.identity_manager.member_exists(address).await?;
+        let is_member = self.identity_manager.member_exists(&address).await?;
         
-        if !is_member {
+        let role = if is_member {
+            self.identity_manager.get_member_by_address(&address).await?.role
+        } else {
             // Register as a new member if not already registered
             self.identity_manager
-                .register_member(address, None, MemberRole::Member)
+                .register_member(&address, None, MemberRole::Member)
                 .await?;
-        }
+            MemberRole::Member
+        };
         
-        // Create a new session
-        self.create_session(address, None, None).await
+        // Create a new session and a token to go with it
+        let session = self.create_session(address.as_str(), None, None).await?;
+        let token = self.issue_jwt(&address, role)?;
+        
+        Ok(Authentication { session, token })
+    }
+    
+    /// Issue an HS256 JWT for `address`
+    ///
+    /// The token is signed with `security.jwt_secret` and expires after
+    /// `security.jwt_expiration_seconds`.
+    pub fn issue_jwt(&self, address: &Address, role: MemberRole) -> Result<String> {
+        let security = &self.config.security;
+        
+        jwt::encode(
+            address.as_str(),
+            role,
+            Utc::now(),
+            Duration::seconds(security.jwt_expiration_seconds as i64),
+            security.jwt_secret.as_bytes(),
+        )
+    }
+    
+    /// Check a JWT's signature and expiry and return its claims
+    ///
+    /// Returns `Unauthorized` for a token that is malformed, was not signed
+    /// with `security.jwt_secret`, or has expired.
+    pub fn validate_jwt(&self, token: &str) -> Result<Claims> {
+        jwt::decode(token, self.config.security.jwt_secret.as_bytes())
     }
     
     /// Authenticate a user with token
     pub async fn authenticate_with_token(&self, token: &str) -> Result<Session> {
-        // Load the session from the database
+        // Load the session from the primary; a freshly issued session may not
+        // have reached the read replica yet
         let query = "SELECT * FROM sessions WHERE id = $1 AND is_active = true";
         let row = self
             .database
+            .primary()
             .query_one(query, &[&token])
             .await
             .map_err(|_| DaoError::Unauthorized)?;
@@ -130,8 +210,11 @@ impl AuthManager {
         let mut session: Session = serde_json::from_value(row.get("data"))
             .map_err(|e| DaoError::DatabaseError(format!("Failed to parse session: {}", e)))?;
         
-        // Check if the session has expired
+        // Sessions outlive a later lockout, like JWTs; only new sign-ins
+        // are locked
         let now = Utc::now();
+        
+        // Check if the session has expired
         if now > session.expires_at {
             session.is_active = false;
             self.save_session(&session).await?;
@@ -145,11 +228,42 @@ impl AuthManager {
         Ok(session)
     }
     
+    /// Authenticate an API request credential
+    ///
+    /// Accepts either a JWT from [`issue_jwt`](Self::issue_jwt) or a session
+    /// ID; sessions must be active and unexpired. Either way the member's
+    /// current role is loaded, and members who are no longer active are
+    /// refused, so a demotion or suspension applies before the JWT expires.
+    pub async fn authenticate_request(&self, credential: &str) -> Result<AuthenticatedMember> {
+        // A JWT has three dot-separated parts; session IDs are UUIDs
+        let (address, session_id) = if credential.split('.').count() == 3 {
+            (self.validate_jwt(credential)?.sub, None)
+        } else {
+            let session = self.authenticate_with_token(credential).await?;
+            (session.address, Some(session.id))
+        };
+        
+        let member = self
+            .identity_manager
+            .get_member_by_address(&Address::parse(&address)?)
+            .await
+            .map_err(|_| DaoError::Unauthorized)?;
+        if member.status != MemberStatus::Active {
+            return Err(DaoError::Unauthorized);
+        }
+        
+        Ok(AuthenticatedMember {
+            address,
+            role: member.role,
+            session_id,
+        })
+    }
+    
     /// Logout a user
     pub async fn logout(&self, session_id: &SessionId) -> Result<()> {
         // Load the session
         let query = "SELECT * FROM sessions WHERE id = $1";
-        let row = self.database.query_one(query, &[&session_id]).await?;
+        let row = self.database.primary().query_one(query, &[&session_id]).await?;
         
         // Parse the session from the row
         let mut session: Session = serde_json::from_value(row.get("data"))
@@ -172,7 +286,8 @@ impl AuthManager {
         permission: &str,
     ) -> Result<bool> {
         // Get the member's role
-        let member = self.identity_manager.get_member_by_address(address).await?;
+        let address = Address::parse(address)?;
+        let member = self.identity_manager.get_member_by_address(&address).await?;
         let role = member.role;
         
         // Check permission
@@ -181,6 +296,14 @@ impl AuthManager {
             .has_permission(role, resource, permission))
     }
     
+    /// Lockout settings from the security configuration
+    fn lockout_policy(&self) -> lockout::LockoutPolicy {
+        lockout::LockoutPolicy {
+            max_attempts: self.config.security.max_login_attempts as u32,
+            lockout: Duration::seconds(self.config.security.login_lockout_seconds as i64),
+        }
+    }
+    
     /// Create a new session
     async fn create_session(
         &self,
@@ -250,4 +373,72 @@ impl AuthManager {
         
         Ok(())
     }
-} 
\ No newline at end of file
+} 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::MockBlockchain;
+    use crate::identity::Member;
+    
+    const ADDRESS: &str = "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266";
+    
+    async fn insert_member(database: &Database, role: MemberRole, status: MemberStatus) {
+        let member = Member {
+            id: Uuid::new_v4().to_string(),
+            address: ADDRESS.to_string(),
+            name: None,
+            role,
+            status,
+            reputation: 0,
+            joined_at: Utc::now(),
+            last_active_at: Utc::now(),
+            role_changed_at: None,
+            metadata: serde_json::Value::Null,
+        };
+        database
+            .execute_any(
+                "INSERT INTO members (id, address, data) VALUES ($1, $2, $3)",
+                &[
+                    member.id.as_str().into(),
+                    ADDRESS.into(),
+                    serde_json::to_value(&member).unwrap().into(),
+                ],
+            )
+            .await
+            .unwrap();
+    }
+    
+    async fn auth_manager(database: &Database) -> AuthManager {
+        let config = crate::config::default();
+        let blockchain = BlockchainAdapter::with_mock(&config.blockchain, Arc::new(MockBlockchain::new()));
+        let identity_manager = IdentityManager::new(&config, blockchain.clone(), database.clone()).unwrap();
+        AuthManager::new(&config, blockchain, database.clone(), &identity_manager).unwrap()
+    }
+    
+    #[tokio::test]
+    async fn test_jwt_callers_get_their_current_role() {
+        let database = Database::migrated_sqlite().await;
+        insert_member(&database, MemberRole::Member, MemberStatus::Active).await;
+        let manager = auth_manager(&database).await;
+        
+        // Issued while the member was on the council
+        let token = manager.issue_jwt(&Address::parse(ADDRESS).unwrap(), MemberRole::Council).unwrap();
+        let member = manager.authenticate_request(&token).await.unwrap();
+        assert_eq!(member.role, MemberRole::Member);
+        assert_eq!(member.session_id, None);
+    }
+    
+    #[tokio::test]
+    async fn test_jwt_of_suspended_member_refused() {
+        let database = Database::migrated_sqlite().await;
+        insert_member(&database, MemberRole::Council, MemberStatus::Suspended).await;
+        let manager = auth_manager(&database).await;
+        
+        let token = manager.issue_jwt(&Address::parse(ADDRESS).unwrap(), MemberRole::Council).unwrap();
+        assert!(matches!(
+            manager.authenticate_request(&token).await,
+            Err(DaoError::Unauthorized)
+        ));
+    }
+}
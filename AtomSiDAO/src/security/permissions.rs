@@ -142,7 +142,7 @@ impl PermissionManager {
             ("proposal", &["create", "read", "update", "delete"]),
             ("vote", &["create", "read"]),
             ("token", &["read", "create"]),
-            ("treasury", &["read", "create"]),
+            ("treasury", &["read", "create", "update"]),
             ("member", &["read", "update"]),
             ("settings", &["read", "update"]),
         ];
@@ -10,7 +10,7 @@ pub use types::{Token, TokenAmount, TokenId};
 use crate::{
     blockchain::BlockchainAdapter,
     config::Config,
-    core::{Database, DaoError, Result},
+    core::{Address, Database, DaoError, Result, Symbol},
 };
 use std::sync::Arc;
 
@@ -40,7 +40,7 @@ impl TokenManager {
     }
     
     /// Create a new token
-    pub async fn create_token(&self, name: &str, symbol: &str, initial_supply: u64) -> Result<Token> {
+    pub async fn create_token(&self, name: &str, symbol: &Symbol, initial_supply: u64) -> Result<Token> {
         // Check if the token already exists
         if self.token_exists(symbol).await? {
             return Err(DaoError::InvalidParameter(format!(
@@ -56,6 +56,7 @@ impl TokenManager {
             symbol: symbol.to_string(),
             total_supply: initial_supply,
             decimals: 18,
+            contract_address: None,
             created_at: chrono::Utc::now(),
         };
         
@@ -65,13 +66,45 @@ impl TokenManager {
         Ok(token)
     }
     
+    /// Register an ERC-20 token whose balances are read from its contract
+    ///
+    /// Supply is tracked by the contract, so `total_supply` is left at zero.
+    pub async fn register_erc20_token(
+        &self,
+        name: &str,
+        symbol: &Symbol,
+        contract_address: &Address,
+        decimals: u8,
+    ) -> Result<Token> {
+        if self.token_exists(symbol).await? {
+            return Err(DaoError::InvalidParameter(format!(
+                "Token with symbol {} already exists",
+                symbol
+            )));
+        }
+        
+        let token = Token {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: name.to_string(),
+            symbol: symbol.to_string(),
+            total_supply: 0,
+            decimals,
+            contract_address: Some(contract_address.to_string()),
+            created_at: chrono::Utc::now(),
+        };
+        
+        self.save_token(&token).await?;
+        
+        Ok(token)
+    }
+    
     /// Get a token by symbol
-    pub async fn get_token(&self, symbol: &str) -> Result<Token> {
+    pub async fn get_token(&self, symbol: &Symbol) -> Result<Token> {
         // Load the token from the database
         let query = "SELECT * FROM tokens WHERE symbol = $1";
         let row = self
             .database
-            .query_one(query, &[&symbol])
+            .query_one(query, &[&symbol.as_str()])
             .await
             .map_err(|e| match e {
                 DaoError::DatabaseError(msg) if msg.contains("no rows in result set") => {
@@ -88,21 +121,32 @@ impl TokenManager {
     }
     
     /// Check if a token exists
-    pub async fn token_exists(&self, symbol: &str) -> Result<bool> {
+    pub async fn token_exists(&self, symbol: &Symbol) -> Result<bool> {
         // Query the database for the token
         let query = "SELECT 1 FROM tokens WHERE symbol = $1";
-        let result = self.database.query_opt(query, &[&symbol]).await?;
+        let result = self.database.query_opt(query, &[&symbol.as_str()]).await?;
         
         Ok(result.is_some())
     }
     
     /// Get the balance of a token for an address
-    pub async fn get_balance(&self, symbol: &str, address: &str) -> Result<TokenAmount> {
+    pub async fn get_balance(&self, symbol: &Symbol, address: &Address) -> Result<TokenAmount> {
         // If the symbol matches the governance token, get the balance from the blockchain
-        if symbol == self.config.dao.governance_token {
+        if symbol.as_str() == self.config.dao.governance_token {
             let balance = self
                 .blockchain
-                .balance(address)
+                .balance(address.as_str())
+                .await
+                .map_err(|e| DaoError::BlockchainError(e))?;
+            
+            return Ok(balance);
+        }
+        
+        // ERC-20 tokens are read from their contract
+        if let Some(contract_address) = self.contract_address(symbol).await? {
+            let balance = self
+                .blockchain
+                .erc20_balance(&contract_address, address.as_str())
                 .await
                 .map_err(|e| DaoError::BlockchainError(e))?;
             
@@ -111,7 +155,7 @@ impl TokenManager {
         
         // Otherwise, query the database
         let query = "SELECT balance FROM token_balances WHERE symbol = $1 AND address = $2";
-        let result = self.database.query_opt(query, &[&symbol, &address]).await?;
+        let result = self.database.query_opt(query, &[&symbol.as_str(), &address.as_str()]).await?;
         
         let balance = match result {
             Some(row) => row.get::<_, i64>("balance") as u64,
@@ -124,11 +168,36 @@ impl TokenManager {
     /// Transfer tokens from one address to another
     pub async fn transfer(
         &self,
-        symbol: &str,
-        from: &str,
-        to: &str,
+        symbol: &Symbol,
+        from: &Address,
+        to: &Address,
         amount: TokenAmount,
     ) -> Result<()> {
+        self.check_transfer(symbol, from, amount).await?;
+        
+        // If the symbol matches the governance token, send a transaction on the blockchain
+        if self.is_on_chain(symbol) {
+            self.send_on_chain(to, amount).await?;
+            return Ok(());
+        }
+        
+        // Otherwise, update the balances in the database
+        let (symbol, from, to) = (symbol.as_str(), from.as_str(), to.as_str());
+        self.database
+            .transaction(|tx| Box::pin(async move { apply_transfer(tx, symbol, from, to, amount).await }))
+            .await?;
+        
+        Ok(())
+    }
+    
+    /// Whether transfers of a token happen on the blockchain rather than in
+    /// the database
+    pub(crate) fn is_on_chain(&self, symbol: &Symbol) -> bool {
+        symbol.as_str() == self.config.dao.governance_token
+    }
+    
+    /// Check that a token exists and the sender can cover a transfer
+    pub(crate) async fn check_transfer(&self, symbol: &Symbol, from: &Address, amount: TokenAmount) -> Result<()> {
         // Check if the token exists
         if !self.token_exists(symbol).await? {
             return Err(DaoError::InvalidParameter(format!(
@@ -145,68 +214,22 @@ impl TokenManager {
             ));
         }
         
-        // If the symbol matches the governance token, send a transaction on the blockchain
-        if symbol == self.config.dao.governance_token {
-            self.blockchain
-                .send_transaction(to, amount)
-                .await
-                .map_err(|e| DaoError::BlockchainError(e))?;
-            
-            return Ok(());
-        }
-        
-        // Otherwise, update the balances in the database
-        self.database
-            .transaction(|tx| {
-                Box::pin(async move {
-                    // Deduct from sender
-                    tx.execute(
-                        "UPDATE token_balances SET balance = balance - $1 WHERE symbol = $2 AND address = $3",
-                        &[&(amount as i64), &symbol, &from],
-                    )
-                    .await
-                    .map_err(|e| DaoError::DatabaseError(format!("Failed to update balance: {}", e)))?;
-                    
-                    // Add to recipient
-                    let updated = tx
-                        .execute(
-                            "UPDATE token_balances SET balance = balance + $1 WHERE symbol = $2 AND address = $3",
-                            &[&(amount as i64), &symbol, &to],
-                        )
-                        .await
-                        .map_err(|e| DaoError::DatabaseError(format!("Failed to update balance: {}", e)))?;
-                    
-                    // If recipient doesn't have a balance entry yet, create one
-                    if updated == 0 {
-                        tx.execute(
-                            "INSERT INTO token_balances (symbol, address, balance) VALUES ($1, $2, $3)",
-                            &[&symbol, &to, &(amount as i64)],
-                        )
-                        .await
-                        .map_err(|e| DaoError::DatabaseError(format!("Failed to insert balance: {}", e)))?;
-                    }
-                    
-                    // Record the transfer
-                    tx.execute(
-                        "INSERT INTO token_transfers (symbol, from_address, to_address, amount, timestamp) VALUES ($1, $2, $3, $4, $5)",
-                        &[&symbol, &from, &to, &(amount as i64), &chrono::Utc::now()],
-                    )
-                    .await
-                    .map_err(|e| DaoError::DatabaseError(format!("Failed to record transfer: {}", e)))?;
-                    
-                    Ok(())
-                })
-            })
-            .await?;
-        
         Ok(())
     }
     
+    /// Send an on-chain transfer, returning the blockchain transaction hash
+    pub(crate) async fn send_on_chain(&self, to: &Address, amount: TokenAmount) -> Result<String> {
+        self.blockchain
+            .send_transaction(to.as_str(), amount)
+            .await
+            .map_err(|e| DaoError::BlockchainError(e))
+    }
+    
     /// Mint new tokens
     pub async fn mint(
         &self,
-        symbol: &str,
-        to: &str,
+        symbol: &Symbol,
+        to: &Address,
         amount: TokenAmount,
     ) -> Result<()> {
         // Check if the token exists
@@ -219,6 +242,7 @@ impl TokenManager {
         self.save_token(&token).await?;
         
         // Add to recipient balance
+        let (symbol, to) = (symbol.as_str(), to.as_str());
         let updated = self
             .database
             .execute(
@@ -257,8 +281,8 @@ impl TokenManager {
     /// Burn tokens
     pub async fn burn(
         &self,
-        symbol: &str,
-        from: &str,
+        symbol: &Symbol,
+        from: &Address,
         amount: TokenAmount,
     ) -> Result<()> {
         // Check if the token exists
@@ -279,6 +303,7 @@ impl TokenManager {
         self.save_token(&token).await?;
         
         // Deduct from sender balance
+        let (symbol, from) = (symbol.as_str(), from.as_str());
         self.database
             .execute(
                 "UPDATE token_balances SET balance = balance - $1 WHERE symbol = $2 AND address = $3",
@@ -305,6 +330,23 @@ impl TokenManager {
     
     // Private methods
     
+    /// Contract address of an ERC-20 token, if the token is one
+    async fn contract_address(&self, symbol: &Symbol) -> Result<Option<String>> {
+        let row = self
+            .database
+            .query_opt("SELECT data FROM tokens WHERE symbol = $1", &[&symbol.as_str()])
+            .await?;
+        
+        match row {
+            Some(row) => {
+                let token: Token = serde_json::from_value(row.get("data"))
+                    .map_err(|e| DaoError::DatabaseError(format!("Failed to parse token: {}", e)))?;
+                Ok(token.contract_address)
+            }
+            None => Ok(None),
+        }
+    }
+    
     /// Save a token to the database
     async fn save_token(&self, token: &Token) -> Result<()> {
         // Serialize the token
@@ -347,4 +389,53 @@ impl TokenManager {
         
         Ok(())
     }
-} 
\ No newline at end of file
+}
+
+/// Move a database-held balance inside an open database transaction
+///
+/// Callers compose this with their own writes so the balance change commits
+/// or rolls back together with them.
+pub(crate) async fn apply_transfer(
+    tx: &deadpool_postgres::Transaction<'_>,
+    symbol: &str,
+    from: &str,
+    to: &str,
+    amount: TokenAmount,
+) -> Result<()> {
+    // Deduct from sender
+    tx.execute(
+        "UPDATE token_balances SET balance = balance - $1 WHERE symbol = $2 AND address = $3",
+        &[&(amount as i64), &symbol, &from],
+    )
+    .await
+    .map_err(|e| DaoError::DatabaseError(format!("Failed to update balance: {}", e)))?;
+    
+    // Add to recipient
+    let updated = tx
+        .execute(
+            "UPDATE token_balances SET balance = balance + $1 WHERE symbol = $2 AND address = $3",
+            &[&(amount as i64), &symbol, &to],
+        )
+        .await
+        .map_err(|e| DaoError::DatabaseError(format!("Failed to update balance: {}", e)))?;
+    
+    // If recipient doesn't have a balance entry yet, create one
+    if updated == 0 {
+        tx.execute(
+            "INSERT INTO token_balances (symbol, address, balance) VALUES ($1, $2, $3)",
+            &[&symbol, &to, &(amount as i64)],
+        )
+        .await
+        .map_err(|e| DaoError::DatabaseError(format!("Failed to insert balance: {}", e)))?;
+    }
+    
+    // Record the transfer
+    tx.execute(
+        "INSERT INTO token_transfers (symbol, from_address, to_address, amount, timestamp) VALUES ($1, $2, $3, $4, $5)",
+        &[&symbol, &from, &to, &(amount as i64), &chrono::Utc::now()],
+    )
+    .await
+    .map_err(|e| DaoError::DatabaseError(format!("Failed to record transfer: {}", e)))?;
+    
+    Ok(())
+}
\ No newline at end of file
@@ -22,6 +22,10 @@ pub struct Token {
     pub total_supply: TokenAmount,
     /// Decimals
     pub decimals: u8,
+    /// ERC-20 contract holding balances on-chain; `None` for tokens whose
+    /// balances are kept in the database
+    #[serde(default)]
+    pub contract_address: Option<String>,
     /// Creation timestamp
     pub created_at: DateTime<Utc>,
 }
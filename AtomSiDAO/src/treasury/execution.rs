@@ -0,0 +1,315 @@
+//! Treasury transaction execution
+//!
+//! Database-held balances move in the same database transaction that marks
+//! the treasury transaction executed, so either both changes commit or
+//! neither does. An on-chain transfer can't share a database transaction
+//! with the chain, so an execution intent is stored before the transfer is
+//! submitted. The intent records the blockchain hash once it is known and is
+//! settled, with the treasury transaction, when the receipt arrives.
+
+use super::{Transaction, TransactionId};
+use crate::core::{Database, DaoError, Result, SqlValue};
+use crate::token;
+use crate::utils::time::timestamp_to_datetime;
+use chrono::{DateTime, Utc};
+
+/// Update a treasury transaction only if its stored version still matches
+pub(super) const VERSIONED_UPDATE: &str = "UPDATE treasury_transactions SET data = $1, status = $2, updated_at = $3, \
+     updated_by = $4 WHERE id = $5 AND COALESCE(CAST(data->>'version' AS BIGINT), 0) = $6";
+
+/// An on-chain execution that has not been settled yet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecutionIntent {
+    /// Treasury transaction being executed
+    pub transaction_id: TransactionId,
+    /// Blockchain transaction hash, once the transfer was submitted
+    pub tx_hash: Option<String>,
+    /// When the intent was stored
+    pub created_at: DateTime<Utc>,
+}
+
+/// Move database-held balances for every line and save the executed
+/// transaction atomically
+///
+/// `executed` must already carry its executed status. On failure nothing is
+/// written and the stored transaction keeps its previous status.
+pub(super) async fn execute_in_database(
+    database: &Database,
+    executed: &mut Transaction,
+    from: &str,
+    actor: &str,
+) -> Result<()> {
+    let mut saved = executed.clone();
+    let from = from.to_string();
+    let actor = actor.to_string();
+
+    let saved = database
+        .transaction(move |tx| {
+            Box::pin(async move {
+                for line in saved.transfers() {
+                    token::apply_transfer(tx, &line.token, &from, &line.to, line.amount).await?;
+                }
+                update_versioned(tx, &mut saved, &actor).await?;
+                Ok(saved)
+            })
+        })
+        .await?;
+
+    *executed = saved;
+    Ok(())
+}
+
+/// Save a treasury transaction inside an open database transaction if its
+/// stored version still matches, bumping the version and recording `actor`
+/// as the last writer
+pub(super) async fn update_versioned(
+    tx: &deadpool_postgres::Transaction<'_>,
+    transaction: &mut Transaction,
+    actor: &str,
+) -> Result<()> {
+    let expected_version = transaction.version as i64;
+    transaction.version += 1;
+
+    let data = serde_json::to_value(&*transaction)
+        .map_err(|e| DaoError::DatabaseError(format!("Failed to serialize transaction: {}", e)))?;
+
+    let updated = tx
+        .execute(
+            VERSIONED_UPDATE,
+            &[
+                &data,
+                &serde_json::to_string(&transaction.status).unwrap(),
+                &transaction.updated_at,
+                &actor,
+                &transaction.id,
+                &expected_version,
+            ],
+        )
+        .await
+        .map_err(|e| DaoError::DatabaseError(format!("Failed to update transaction: {}", e)))?;
+
+    if updated == 0 {
+        transaction.version -= 1;
+        return Err(DaoError::TreasuryError(format!(
+            "Transaction {} was modified concurrently",
+            transaction.id
+        )));
+    }
+
+    Ok(())
+}
+
+/// Store an intent before submitting an on-chain transfer
+///
+/// Fails if the transaction already has an unsettled intent.
+pub(super) async fn insert_intent(database: &Database, transaction_id: &TransactionId) -> Result<()> {
+    let inserted = database
+        .execute_any(
+            "INSERT INTO treasury_execution_intents (transaction_id, created_at) VALUES ($1, $2) \
+             ON CONFLICT (transaction_id) DO NOTHING",
+            &[transaction_id.as_str().into(), Utc::now().timestamp().into()],
+        )
+        .await?;
+
+    if inserted == 0 {
+        return Err(DaoError::TreasuryError(format!(
+            "Transaction {} has already been submitted and is awaiting its receipt",
+            transaction_id
+        )));
+    }
+
+    Ok(())
+}
+
+/// Record the blockchain hash of a submitted transfer
+pub(super) async fn record_intent_hash(database: &Database, transaction_id: &TransactionId, tx_hash: &str) -> Result<()> {
+    database
+        .execute_any(
+            "UPDATE treasury_execution_intents SET tx_hash = $1 WHERE transaction_id = $2",
+            &[tx_hash.into(), transaction_id.as_str().into()],
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Remove an intent whose transfer was never submitted
+pub(super) async fn discard_intent(database: &Database, transaction_id: &TransactionId) -> Result<()> {
+    database
+        .execute_any(
+            "DELETE FROM treasury_execution_intents WHERE transaction_id = $1",
+            &[transaction_id.as_str().into()],
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Whether a transaction has an unsettled on-chain execution
+pub(super) async fn has_intent(database: &Database, transaction_id: &TransactionId) -> Result<bool> {
+    let row = database
+        .query_opt_any(
+            "SELECT 1 FROM treasury_execution_intents WHERE transaction_id = $1",
+            &[transaction_id.as_str().into()],
+        )
+        .await?;
+
+    Ok(row.is_some())
+}
+
+/// Unsettled intents, oldest first
+pub(super) async fn pending_intents(database: &Database) -> Result<Vec<ExecutionIntent>> {
+    let rows = database
+        .primary()
+        .query_any(
+            "SELECT transaction_id, COALESCE(tx_hash, '') AS tx_hash, created_at \
+             FROM treasury_execution_intents ORDER BY created_at",
+            &[],
+        )
+        .await?;
+
+    rows.iter()
+        .map(|row| {
+            let tx_hash = row.text("tx_hash")?;
+            Ok(ExecutionIntent {
+                transaction_id: row.text("transaction_id")?,
+                tx_hash: (!tx_hash.is_empty()).then_some(tx_hash),
+                created_at: timestamp_to_datetime(row.int("created_at")? as u64),
+            })
+        })
+        .collect()
+}
+
+/// Save the settled transaction and remove its intent atomically
+///
+/// The intent is only removed when the versioned update applied, so a
+/// concurrent modification leaves both rows untouched.
+pub(super) async fn settle_intent(database: &Database, mut settled: Transaction, actor: &str) -> Result<()> {
+    let expected_version = settled.version as i64;
+    settled.version += 1;
+
+    let data = serde_json::to_value(&settled)
+        .map_err(|e| DaoError::DatabaseError(format!("Failed to serialize transaction: {}", e)))?;
+    let id: SqlValue = settled.id.as_str().into();
+
+    let affected = database
+        .execute_batch_any(&[
+            (
+                VERSIONED_UPDATE,
+                vec![
+                    data.into(),
+                    serde_json::to_string(&settled.status).unwrap().into(),
+                    settled.updated_at.into(),
+                    actor.into(),
+                    id.clone(),
+                    expected_version.into(),
+                ],
+            ),
+            (
+                "DELETE FROM treasury_execution_intents WHERE transaction_id = $1 AND EXISTS \
+                 (SELECT 1 FROM treasury_transactions WHERE id = $1 AND CAST(data->>'version' AS BIGINT) = $2)",
+                vec![id, (expected_version + 1).into()],
+            ),
+        ])
+        .await?;
+
+    if affected[0] == 0 {
+        return Err(DaoError::TreasuryError(format!(
+            "Transaction {} was modified concurrently",
+            settled.id
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::treasury::{TransactionBuilder, TransactionStatus};
+
+    const TREASURY: &str = "0x0000000000000000000000000000000000000000";
+    const RECIPIENT: &str = "0x0000000000000000000000000000000000000002";
+
+    async fn balance(database: &Database, address: &str) -> i64 {
+        database
+            .query_opt(
+                "SELECT balance FROM token_balances WHERE symbol = 'RBK' AND address = $1",
+                &[&address],
+            )
+            .await
+            .unwrap()
+            .map_or(0, |row| row.get("balance"))
+    }
+
+    /// Runs against the Postgres database in `ATOMSI_TEST_DATABASE_URL`;
+    /// run with `cargo test -- --ignored`
+    #[tokio::test]
+    #[ignore = "needs a Postgres database in ATOMSI_TEST_DATABASE_URL"]
+    async fn test_failure_after_transfer_rolls_back_balance_and_status() {
+        let url = std::env::var("ATOMSI_TEST_DATABASE_URL").expect("ATOMSI_TEST_DATABASE_URL must be set");
+        let mut db_config = crate::config::default().database;
+        db_config.url = url;
+        let database = Database::connect(&db_config).await.unwrap();
+
+        for statement in [
+            "CREATE TABLE IF NOT EXISTS treasury_transactions (id TEXT PRIMARY KEY, data JSONB NOT NULL, status TEXT NOT NULL, \
+             created_at TIMESTAMPTZ NOT NULL, updated_at TIMESTAMPTZ NOT NULL, updated_by VARCHAR(100), deleted_at BIGINT)",
+            "CREATE TABLE IF NOT EXISTS token_balances (symbol TEXT NOT NULL, address TEXT NOT NULL, balance BIGINT NOT NULL, \
+             PRIMARY KEY (symbol, address))",
+            "CREATE TABLE IF NOT EXISTS token_transfers (symbol TEXT NOT NULL, from_address TEXT NOT NULL, to_address TEXT NOT NULL, \
+             amount BIGINT NOT NULL, timestamp TIMESTAMPTZ NOT NULL)",
+            "DELETE FROM token_balances WHERE symbol = 'RBK'",
+            "INSERT INTO token_balances (symbol, address, balance) VALUES ('RBK', '0x0000000000000000000000000000000000000000', 5000)",
+        ] {
+            database.execute(statement, &[]).await.unwrap();
+        }
+
+        let mut transaction = TransactionBuilder::new()
+            .description("Pay auditors")
+            .to(RECIPIENT)
+            .token("RBK")
+            .amount(1_000)
+            .build()
+            .unwrap();
+        transaction.status = TransactionStatus::Approved;
+        database
+            .execute(
+                "INSERT INTO treasury_transactions (id, data, status, created_at, updated_at) VALUES ($1, $2, $3, $4, $5)",
+                &[
+                    &transaction.id,
+                    &serde_json::to_value(&transaction).unwrap(),
+                    &serde_json::to_string(&transaction.status).unwrap(),
+                    &transaction.created_at,
+                    &transaction.updated_at,
+                ],
+            )
+            .await
+            .unwrap();
+
+        // Another writer bumps the stored version, so the status update that
+        // follows the balance change fails inside the database transaction
+        database
+            .execute(
+                "UPDATE treasury_transactions SET data = jsonb_set(data, '{version}', '1') WHERE id = $1",
+                &[&transaction.id],
+            )
+            .await
+            .unwrap();
+
+        let mut executed = transaction.clone();
+        executed.status = TransactionStatus::Executed;
+        executed.executed_at = Some(Utc::now());
+        let result = execute_in_database(&database, &mut executed, TREASURY, "0xSigner").await;
+        assert!(matches!(result, Err(DaoError::TreasuryError(_))));
+
+        // Neither the balance change nor the status change was kept
+        assert_eq!(balance(&database, TREASURY).await, 5000);
+        assert_eq!(balance(&database, RECIPIENT).await, 0);
+        let row = database
+            .query_one("SELECT status FROM treasury_transactions WHERE id = $1", &[&transaction.id])
+            .await
+            .unwrap();
+        assert_eq!(row.get::<_, String>("status"), serde_json::to_string(&TransactionStatus::Approved).unwrap());
+    }
+}
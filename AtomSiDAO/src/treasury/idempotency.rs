@@ -0,0 +1,245 @@
+//! Idempotent transaction creation
+//!
+//! A client whose create request times out can't tell whether the
+//! transaction was stored, so it retries. Tagging the request with an
+//! idempotency key makes the retry safe: the first create claims the key,
+//! and later creates with the same key return the transaction it was claimed
+//! for instead of storing a duplicate.
+//!
+//! The claim is stored in the same database transaction as the treasury
+//! transaction it names, so a key never points at a transaction that doesn't
+//! exist. Each claim records a hash of the request, and reusing a key for a
+//! different request is refused.
+//!
+//! Keys are scoped to the creator, so clients can't collide with each
+//! other's keys, and expire after `treasury.idempotency_window_hours`, after
+//! which the key may be used again.
+
+use super::{Transaction, TransactionId};
+use crate::core::{Database, DaoError, Result, SqlValue};
+use chrono::{Duration, Utc};
+use sha2::{Digest, Sha256};
+use tracing::info;
+
+/// Maximum length of an idempotency key in characters
+pub const MAX_IDEMPOTENCY_KEY_LENGTH: usize = 255;
+
+/// Check an idempotency key is usable
+pub(super) fn validate_key(key: &str) -> Result<()> {
+    if key.trim().is_empty() {
+        return Err(DaoError::InvalidParameter(
+            "Idempotency key must not be empty".to_string(),
+        ));
+    }
+
+    if key.chars().count() > MAX_IDEMPOTENCY_KEY_LENGTH {
+        return Err(DaoError::InvalidParameter(format!(
+            "Idempotency key must be at most {} characters",
+            MAX_IDEMPOTENCY_KEY_LENGTH
+        )));
+    }
+
+    Ok(())
+}
+
+/// Hash of the parts of a create request that a retry repeats
+///
+/// The ID and timestamps differ between attempts and are left out.
+pub(super) fn request_hash(transaction: &Transaction) -> String {
+    let request = serde_json::json!({
+        "description": transaction.description,
+        "transfers": transaction.transfers(),
+        "required_approvals": transaction.required_approvals,
+        "expires_at": transaction.expires_at,
+        "metadata": transaction.metadata,
+    });
+
+    hex::encode(Sha256::digest(request.to_string().as_bytes()))
+}
+
+/// The transaction an unexpired key was already used for
+///
+/// Returns `None` if the key is free. A key used for a different request is
+/// refused.
+///
+/// Portable: runs on PostgreSQL and SQLite.
+pub(super) async fn find_existing(
+    database: &Database,
+    scope: &str,
+    key: &str,
+    request_hash: &str,
+    window: Duration,
+) -> Result<Option<TransactionId>> {
+    let row = database
+        .query_opt_any(
+            "SELECT transaction_id, request_hash FROM treasury_idempotency_keys \
+             WHERE scope = $1 AND idempotency_key = $2 AND created_at > $3",
+            &[scope.into(), key.into(), (Utc::now().timestamp() - window.num_seconds()).into()],
+        )
+        .await?;
+
+    let Some(row) = row else {
+        return Ok(None);
+    };
+
+    if row.text("request_hash")? != request_hash {
+        return Err(DaoError::InvalidParameter(format!(
+            "Idempotency key {} was already used for a different request",
+            key
+        )));
+    }
+
+    let existing = row.text("transaction_id")?;
+    info!("Idempotency key {} already used for treasury transaction {}", key, existing);
+    Ok(Some(existing))
+}
+
+/// Store a new transaction together with its key claim
+///
+/// Both are written in one database transaction: an expired claim is
+/// replaced, and the transaction is only inserted if the claim is its own.
+/// When a concurrent request claimed the key first, nothing is stored and
+/// that request's transaction is returned instead.
+///
+/// Portable: runs on PostgreSQL and SQLite.
+pub(super) async fn insert_with_key(
+    database: &Database,
+    scope: &str,
+    key: &str,
+    request_hash: &str,
+    window: Duration,
+    transaction: &Transaction,
+    actor: &str,
+) -> Result<TransactionId> {
+    let now = Utc::now().timestamp();
+    let data = serde_json::to_value(transaction)
+        .map_err(|e| DaoError::DatabaseError(format!("Failed to serialize transaction: {}", e)))?;
+    let status = serde_json::to_string(&transaction.status).unwrap();
+
+    let statements: [(&str, Vec<SqlValue>); 3] = [
+        (
+            "DELETE FROM treasury_idempotency_keys WHERE scope = $1 AND idempotency_key = $2 AND created_at <= $3",
+            vec![scope.into(), key.into(), (now - window.num_seconds()).into()],
+        ),
+        (
+            "INSERT INTO treasury_idempotency_keys (scope, idempotency_key, transaction_id, request_hash, created_at) \
+             VALUES ($1, $2, $3, $4, $5) ON CONFLICT (scope, idempotency_key) DO NOTHING",
+            vec![scope.into(), key.into(), transaction.id.as_str().into(), request_hash.into(), now.into()],
+        ),
+        (
+            "INSERT INTO treasury_transactions (id, data, status, created_at, updated_at, updated_by) \
+             SELECT $1, $2, $3, $4, $5, $6 WHERE EXISTS (SELECT 1 FROM treasury_idempotency_keys \
+             WHERE scope = $7 AND idempotency_key = $8 AND transaction_id = $1)",
+            vec![
+                transaction.id.as_str().into(),
+                data.into(),
+                status.into(),
+                transaction.created_at.into(),
+                transaction.updated_at.into(),
+                actor.into(),
+                scope.into(),
+                key.into(),
+            ],
+        ),
+    ];
+    let affected = database.execute_batch_any(&statements).await?;
+
+    if affected[2] == 1 {
+        return Ok(transaction.id.clone());
+    }
+
+    // Lost the race to a request that committed its own transaction
+    find_existing(database, scope, key, request_hash, window)
+        .await?
+        .ok_or_else(|| DaoError::TreasuryError(format!("Idempotency key {} could not be claimed", key)))
+}
+
+/// Delete keys claimed longer ago than `window`
+///
+/// Portable: runs on PostgreSQL and SQLite.
+pub(super) async fn purge_expired_keys(database: &Database, window: Duration) -> Result<u64> {
+    database
+        .execute_any(
+            "DELETE FROM treasury_idempotency_keys WHERE created_at <= $1",
+            &[(Utc::now().timestamp() - window.num_seconds()).into()],
+        )
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::treasury::TransactionBuilder;
+
+    const CREATOR: &str = "0x0000000000000000000000000000000000000001";
+
+    fn transaction(amount: u64) -> Transaction {
+        TransactionBuilder::new()
+            .description("Pay auditors")
+            .to("0x0000000000000000000000000000000000000002")
+            .token("ATOM")
+            .amount(amount)
+            .creator(CREATOR)
+            .idempotency_key("retry-1")
+            .build()
+            .unwrap()
+    }
+
+    async fn insert(database: &Database, transaction: &Transaction, window: Duration) -> Result<TransactionId> {
+        let hash = request_hash(transaction);
+        insert_with_key(database, CREATOR, "retry-1", &hash, window, transaction, CREATOR).await
+    }
+
+    async fn transaction_count(database: &Database) -> i64 {
+        database
+            .query_opt_any("SELECT COUNT(*) AS count FROM treasury_transactions", &[])
+            .await
+            .unwrap()
+            .unwrap()
+            .int("count")
+            .unwrap()
+    }
+
+    #[test]
+    fn test_request_hash_ignores_attempt_details() {
+        let first = transaction(1_000);
+        let retry = transaction(1_000);
+
+        assert_ne!(first.id, retry.id);
+        assert_eq!(request_hash(&first), request_hash(&retry));
+        assert_ne!(request_hash(&first), request_hash(&transaction(2_000)));
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_insert_returns_first_transaction() {
+        let database = Database::migrated_sqlite().await;
+        let window = Duration::hours(24);
+
+        let first = transaction(1_000);
+        let retry = transaction(1_000);
+
+        assert_eq!(insert(&database, &first, window).await.unwrap(), first.id);
+        assert_eq!(insert(&database, &retry, window).await.unwrap(), first.id);
+        assert_eq!(transaction_count(&database).await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_expired_key_can_be_reused() {
+        let database = Database::migrated_sqlite().await;
+
+        let first = transaction(1_000);
+        let second = transaction(2_000);
+
+        insert(&database, &first, Duration::zero()).await.unwrap();
+        assert_eq!(insert(&database, &second, Duration::zero()).await.unwrap(), second.id);
+        assert_eq!(transaction_count(&database).await, 2);
+        assert_eq!(purge_expired_keys(&database, Duration::zero()).await.unwrap(), 1);
+    }
+
+    #[test]
+    fn test_validate_key() {
+        assert!(validate_key("retry-1").is_ok());
+        assert!(validate_key("  ").is_err());
+        assert!(validate_key(&"k".repeat(MAX_IDEMPOTENCY_KEY_LENGTH + 1)).is_err());
+    }
+}
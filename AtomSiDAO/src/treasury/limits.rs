@@ -0,0 +1,262 @@
+//! Treasury spending limits
+//!
+//! Each token may cap the amount of a single transaction and the amount
+//! moved over a rolling 24 hours, so a compromised signer set can't drain
+//! the treasury at once. The daily window counts transactions executed in the
+//! last 24 hours plus unexpired approved and pending ones, which may still
+//! execute. Limits are checked when a transaction is created and again when
+//! it is approved and executed.
+
+use super::{Transaction, TransactionStatus};
+use crate::config::TokenSpendingLimits;
+use crate::core::{Database, DaoError, Result};
+use crate::token::TokenAmount;
+use chrono::{DateTime, Duration, Utc};
+
+/// Length of the rolling spending window
+pub(crate) fn spending_window() -> Duration {
+    Duration::hours(24)
+}
+
+/// Amount of a token committed within the window ending at `now`
+///
+/// Executed transactions count if they executed within the window; unexpired
+/// approved and pending transactions count regardless of age, as they may
+/// execute at any time. The transaction with id `excluding`, the one being
+/// checked, is left out.
+pub(crate) fn committed_spend(
+    transactions: &[Transaction],
+    token: &str,
+    now: DateTime<Utc>,
+    excluding: &str,
+) -> TokenAmount {
+    let window_start = now - spending_window();
+
+    transactions
+        .iter()
+        .filter(|transaction| transaction.id != excluding)
+        .filter(|transaction| match transaction.status {
+            TransactionStatus::Approved | TransactionStatus::Pending => !transaction.is_expired(now),
+            TransactionStatus::Executed => transaction.executed_at.map_or(false, |at| at > window_start),
+            _ => false,
+        })
+        .flat_map(|transaction| transaction.transfers())
+        .filter(|line| line.token == token)
+        .map(|line| line.amount)
+        .fold(0, TokenAmount::saturating_add)
+}
+
+/// Check a new transaction's amount against its token's limits
+pub(crate) fn check_spending_limits(
+    token: &str,
+    amount: TokenAmount,
+    limits: &TokenSpendingLimits,
+    committed: TokenAmount,
+) -> Result<()> {
+    if let Some(per_tx_limit) = limits.per_tx_limit {
+        if amount > per_tx_limit {
+            return Err(DaoError::TreasuryError(format!(
+                "Amount {} exceeds the {} per-transaction limit of {}",
+                amount, token, per_tx_limit
+            )));
+        }
+    }
+
+    if let Some(daily_limit) = limits.daily_limit {
+        if committed.saturating_add(amount) > daily_limit {
+            return Err(DaoError::TreasuryError(format!(
+                "Amount {} would exceed the {} daily limit of {} ({} already committed in the last 24 hours)",
+                amount, token, daily_limit, committed
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Load the transactions that may count against a token's daily limit
+///
+/// Executed transactions last updated before the window are skipped in the
+/// query, as they executed no later than their last update; approved and
+/// pending transactions are loaded whatever their age. Only transactions
+/// with a line in `token` are returned. Soft-deleted transactions are
+/// skipped.
+///
+/// Portable: runs on PostgreSQL and SQLite.
+pub(crate) async fn load_window_transactions(
+    database: &Database,
+    token: &str,
+    now: DateTime<Utc>,
+) -> Result<Vec<Transaction>> {
+    let rows = database
+        .query_any(
+            "SELECT data FROM treasury_transactions \
+             WHERE deleted_at IS NULL \
+             AND (data->>'status' IN ('Approved', 'Pending') \
+             OR (data->>'status' = 'Executed' AND updated_at > $1))",
+            &[(now - spending_window()).into()],
+        )
+        .await?;
+
+    let mut transactions = Vec::new();
+    for row in rows {
+        let transaction: Transaction = row
+            .json("data")
+            .map_err(|e| DaoError::DatabaseError(format!("Failed to parse transaction: {}", e)))?;
+        if transaction.transfers().iter().any(|line| line.token == token) {
+            transactions.push(transaction);
+        }
+    }
+
+    Ok(transactions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::treasury::{TransactionBuilder, TransferLine};
+
+    fn transaction(amount: TokenAmount, status: TransactionStatus, executed_hours_ago: Option<i64>) -> Transaction {
+        token_transaction("ATOM", amount, status, executed_hours_ago)
+    }
+
+    fn token_transaction(
+        token: &str,
+        amount: TokenAmount,
+        status: TransactionStatus,
+        executed_hours_ago: Option<i64>,
+    ) -> Transaction {
+        let mut transaction = TransactionBuilder::new()
+            .description("Contributor payment")
+            .to("0x0000000000000000000000000000000000000002")
+            .token(token)
+            .amount(amount)
+            .build()
+            .unwrap();
+        transaction.status = status;
+        transaction.executed_at = executed_hours_ago.map(|hours| Utc::now() - Duration::hours(hours));
+        transaction
+    }
+
+    fn limits() -> TokenSpendingLimits {
+        TokenSpendingLimits {
+            per_tx_limit: Some(1_000),
+            daily_limit: Some(2_500),
+        }
+    }
+
+    #[test]
+    fn test_per_transaction_ceiling() {
+        assert!(check_spending_limits("ATOM", 1_000, &limits(), 0).is_ok());
+        assert!(matches!(
+            check_spending_limits("ATOM", 1_001, &limits(), 0),
+            Err(DaoError::TreasuryError(_))
+        ));
+
+        // Tokens without limits are unrestricted
+        assert!(check_spending_limits("USDC", u64::MAX, &TokenSpendingLimits::default(), u64::MAX).is_ok());
+    }
+
+    #[test]
+    fn test_daily_ceiling_over_rolling_window() {
+        let now = Utc::now();
+        let mut transactions = vec![
+            transaction(900, TransactionStatus::Executed, Some(2)),
+            transaction(800, TransactionStatus::Approved, None),
+            // Outside the window, rejected, or a different token: not counted
+            transaction(1_000, TransactionStatus::Executed, Some(30)),
+            transaction(1_000, TransactionStatus::Rejected, None),
+        ];
+        transactions.push(token_transaction("USDC", 1_000, TransactionStatus::Executed, Some(1)));
+
+        let committed = committed_spend(&transactions, "ATOM", now, "");
+        assert_eq!(committed, 1_700);
+
+        // Each transaction is within the per-transaction limit, but together
+        // they reach the daily ceiling
+        assert!(check_spending_limits("ATOM", 800, &limits(), committed).is_ok());
+        transactions.push(transaction(800, TransactionStatus::Approved, None));
+
+        let committed = committed_spend(&transactions, "ATOM", now, "");
+        assert_eq!(committed, 2_500);
+        assert!(matches!(
+            check_spending_limits("ATOM", 1, &limits(), committed),
+            Err(DaoError::TreasuryError(_))
+        ));
+
+        // Once the executed transaction leaves the window, only the approved
+        // ones still count
+        let tomorrow = now + Duration::hours(23);
+        assert_eq!(committed_spend(&transactions, "ATOM", tomorrow, ""), 1_600);
+    }
+
+    #[test]
+    fn test_pending_transactions_count_until_they_expire() {
+        let now = Utc::now();
+        let mut transactions: Vec<Transaction> = (0..3)
+            .map(|_| transaction(1_000, TransactionStatus::Pending, None))
+            .collect();
+
+        // Pending transactions each under the cap can't be approved together
+        // past it
+        let committed = committed_spend(&transactions, "ATOM", now, "");
+        assert_eq!(committed, 3_000);
+        assert!(check_spending_limits("ATOM", 1, &limits(), committed).is_err());
+
+        // Re-checked on approval, a transaction doesn't count against itself
+        let first = transactions[0].id.clone();
+        let committed = committed_spend(&transactions, "ATOM", now, &first);
+        assert_eq!(committed, 2_000);
+        assert!(check_spending_limits("ATOM", 1_000, &limits(), committed).is_err());
+
+        // Expired pending transactions can no longer execute
+        transactions[1].expires_at = Some(now - Duration::hours(1));
+        let committed = committed_spend(&transactions, "ATOM", now, &first);
+        assert_eq!(committed, 1_000);
+        assert!(check_spending_limits("ATOM", 1_000, &limits(), committed).is_ok());
+
+        // Nor can expired approved ones
+        transactions[2].status = TransactionStatus::Approved;
+        transactions[2].expires_at = Some(now - Duration::hours(1));
+        assert_eq!(committed_spend(&transactions, "ATOM", now, &first), 0);
+    }
+
+    #[tokio::test]
+    async fn test_window_transactions_load_on_sqlite() {
+        let database = Database::migrated_sqlite().await;
+        let now = Utc::now();
+
+        let counted = [
+            transaction(800, TransactionStatus::Approved, None),
+            transaction(900, TransactionStatus::Executed, Some(2)),
+        ];
+        let mut batch = token_transaction("USDC", 500, TransactionStatus::Pending, None);
+        batch.lines = vec![
+            TransferLine { to: batch.to.clone(), token: "USDC".to_string(), amount: 500 },
+            TransferLine { to: batch.to.clone(), token: "ATOM".to_string(), amount: 300 },
+        ];
+        let skipped = [
+            transaction(1_000, TransactionStatus::Rejected, None),
+            token_transaction("USDC", 1_000, TransactionStatus::Approved, None),
+        ];
+        for transaction in counted.iter().chain([&batch]).chain(&skipped) {
+            database
+                .execute_any(
+                    "INSERT INTO treasury_transactions (id, data, status, created_at, updated_at) VALUES ($1, $2, $3, $4, $5)",
+                    &[
+                        transaction.id.as_str().into(),
+                        serde_json::to_value(transaction).unwrap().into(),
+                        serde_json::to_string(&transaction.status).unwrap().into(),
+                        transaction.created_at.into(),
+                        transaction.executed_at.unwrap_or(transaction.updated_at).into(),
+                    ],
+                )
+                .await
+                .unwrap();
+        }
+
+        let window = load_window_transactions(&database, "ATOM", now).await.unwrap();
+        assert_eq!(window.len(), 3);
+        assert_eq!(committed_spend(&window, "ATOM", now, ""), 2_000);
+    }
+}
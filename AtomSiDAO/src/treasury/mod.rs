@@ -4,16 +4,42 @@
 //! including multi-signature control, spending limits, and asset tracking.
 
 use crate::{
-    blockchain::BlockchainAdapter,
-    config::Config,
-    core::{Database, DaoError, Result},
+    api::websocket::{EventType, WebSocketEvent, WebSocketManager},
+    blockchain::{confirmation_depth, BlockchainAdapter, ExecutionLimiter},
+    config::{Config, TreasuryConfig, TreasurySigners},
+    core::{fetch_page, Address, Database, DaoError, Page, PageRequest, Result, SqlValue, Symbol, SYSTEM_ACTOR},
+    identity::{self, Member, MemberRole},
+    security::{record_admin_action, AdminAction, AuditLog},
+    proposals::{Proposal, ProposalBuilder, ProposalType},
     token::{TokenAmount, TokenManager},
 };
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
 use uuid::Uuid;
 
+mod execution;
+mod idempotency;
+mod limits;
+mod rebalance;
+mod valuation;
+
+pub use execution::ExecutionIntent;
+pub use idempotency::MAX_IDEMPOTENCY_KEY_LENGTH;
+pub use rebalance::{AllocationTarget, PriceOracle};
+use rebalance::{plan_rebalance, Holding};
+pub use valuation::{overall_status, OracleStatus, ValuedBalance};
+use valuation::{value_balances, PriceCache};
+
 /// Treasury transaction ID type
 pub type TransactionId = String;
 
@@ -30,6 +56,21 @@ pub enum TransactionStatus {
     Rejected,
     /// Failed
     Failed,
+    /// Cancelled after approval, before execution
+    Cancelled,
+    /// Expired before collecting enough approvals
+    Expired,
+}
+
+/// One payment within a treasury transaction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransferLine {
+    /// Recipient address
+    pub to: String,
+    /// Token symbol
+    pub token: String,
+    /// Amount
+    pub amount: TokenAmount,
 }
 
 /// Treasury transaction
@@ -39,12 +80,16 @@ pub struct Transaction {
     pub id: TransactionId,
     /// Transaction description
     pub description: String,
-    /// Recipient address
+    /// Recipient address of the first transfer line
     pub to: String,
-    /// Token symbol
+    /// Token symbol of the first transfer line
     pub token: String,
-    /// Amount
+    /// Amount of the first transfer line
     pub amount: TokenAmount,
+    /// Every transfer line, executed together; empty for transactions
+    /// stored before batches, whose only line is `to`, `token` and `amount`
+    #[serde(default)]
+    pub lines: Vec<TransferLine>,
     /// Transaction status
     pub status: TransactionStatus,
     /// Required number of approvals
@@ -57,21 +102,62 @@ pub struct Transaction {
     pub updated_at: DateTime<Utc>,
     /// Execution timestamp
     pub executed_at: Option<DateTime<Utc>>,
+    /// Time after which the transaction can no longer be approved or
+    /// executed; `None` never expires
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
     /// Signers who have approved
     pub approvers: Vec<String>,
     /// Blockchain transaction hash (if available)
     pub transaction_hash: Option<String>,
+    /// Address that created the transaction
+    #[serde(default)]
+    pub creator: Option<String>,
+    /// Reason given when the transaction was cancelled
+    #[serde(default)]
+    pub cancel_reason: Option<String>,
+    /// Client-supplied key that makes retried creates return this
+    /// transaction instead of creating another
+    #[serde(default)]
+    pub idempotency_key: Option<String>,
+    /// Version number used for optimistic concurrency control
+    #[serde(default)]
+    pub version: u64,
     /// Additional metadata
     pub metadata: serde_json::Value,
 }
 
+impl Transaction {
+    /// The transfer lines this transaction executes
+    pub fn transfers(&self) -> Vec<TransferLine> {
+        if self.lines.is_empty() {
+            vec![TransferLine {
+                to: self.to.clone(),
+                token: self.token.clone(),
+                amount: self.amount,
+            }]
+        } else {
+            self.lines.clone()
+        }
+    }
+    
+    /// Whether the transaction's expiry time has passed
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.map_or(false, |expires_at| now >= expires_at)
+    }
+}
+
 /// Transaction builder
 pub struct TransactionBuilder {
     description: Option<String>,
     to: Option<String>,
     token: Option<String>,
     amount: Option<TokenAmount>,
+    transfers: Vec<(String, String, TokenAmount)>,
     required_approvals: Option<u32>,
+    creator: Option<String>,
+    expires_at: Option<DateTime<Utc>>,
+    idempotency_key: Option<String>,
     metadata: serde_json::Value,
 }
 
@@ -83,7 +169,11 @@ impl TransactionBuilder {
             to: None,
             token: None,
             amount: None,
+            transfers: Vec::new(),
             required_approvals: None,
+            creator: None,
+            expires_at: None,
+            idempotency_key: None,
             metadata: serde_json::Value::Null,
         }
     }
@@ -112,12 +202,42 @@ impl TransactionBuilder {
         self
     }
     
-    /// Set the required number of approvals
+    /// Set the required number of approvals; defaults to 1, and must be at
+    /// least `treasury.threshold` when the transaction is created
     pub fn required_approvals(mut self, required_approvals: u32) -> Self {
         self.required_approvals = Some(required_approvals);
         self
     }
     
+    /// Set the creator address
+    pub fn creator<S: Into<String>>(mut self, creator: S) -> Self {
+        self.creator = Some(creator.into());
+        self
+    }
+    
+    /// Add a transfer line, for transactions paying several recipients
+    ///
+    /// Lines set with `to`, `token` and `amount` come first.
+    pub fn add_transfer<S: Into<String>, T: Into<String>>(mut self, to: S, token: T, amount: TokenAmount) -> Self {
+        self.transfers.push((to.into(), token.into(), amount));
+        self
+    }
+    
+    /// Set the expiry time; defaults to `treasury.transaction_expiry_hours`
+    /// after creation
+    pub fn expires_at(mut self, expires_at: DateTime<Utc>) -> Self {
+        self.expires_at = Some(expires_at);
+        self
+    }
+    
+    /// Set an idempotency key; creating another transaction with the same
+    /// key and creator within `treasury.idempotency_window_hours` returns
+    /// this transaction's ID instead
+    pub fn idempotency_key<S: Into<String>>(mut self, idempotency_key: S) -> Self {
+        self.idempotency_key = Some(idempotency_key.into());
+        self
+    }
+    
     /// Set additional metadata
     pub fn metadata(mut self, metadata: serde_json::Value) -> Self {
         self.metadata = metadata;
@@ -130,17 +250,35 @@ impl TransactionBuilder {
             DaoError::InvalidParameter("Transaction description is required".to_string())
         })?;
         
-        let to = self.to.ok_or_else(|| {
-            DaoError::InvalidParameter("Recipient address is required".to_string())
-        })?;
+        let mut transfers = self.transfers;
+        if self.to.is_some() || self.token.is_some() || self.amount.is_some() || transfers.is_empty() {
+            let to = self.to.ok_or_else(|| {
+                DaoError::InvalidParameter("Recipient address is required".to_string())
+            })?;
+            let token = self.token.ok_or_else(|| {
+                DaoError::InvalidParameter("Token symbol is required".to_string())
+            })?;
+            let amount = self.amount.ok_or_else(|| {
+                DaoError::InvalidParameter("Transaction amount is required".to_string())
+            })?;
+            transfers.insert(0, (to, token, amount));
+        }
         
-        let token = self.token.ok_or_else(|| {
-            DaoError::InvalidParameter("Token symbol is required".to_string())
-        })?;
+        let lines = transfers
+            .into_iter()
+            .map(|(to, token, amount)| {
+                Ok(TransferLine {
+                    to: Address::parse(&to)?.to_string(),
+                    token: Symbol::parse(&token)?.to_string(),
+                    amount,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let TransferLine { to, token, amount } = lines[0].clone();
         
-        let amount = self.amount.ok_or_else(|| {
-            DaoError::InvalidParameter("Transaction amount is required".to_string())
-        })?;
+        if let Some(key) = &self.idempotency_key {
+            idempotency::validate_key(key)?;
+        }
         
         let required_approvals = self.required_approvals.unwrap_or(1);
         
@@ -158,14 +296,20 @@ impl TransactionBuilder {
             to,
             token,
             amount,
+            lines,
             status: TransactionStatus::Pending,
             required_approvals,
             current_approvals: 0,
             created_at: now,
             updated_at: now,
             executed_at: None,
+            expires_at: self.expires_at,
             approvers: Vec::new(),
             transaction_hash: None,
+            creator: self.creator,
+            cancel_reason: None,
+            idempotency_key: self.idempotency_key,
+            version: 0,
             metadata: self.metadata,
         })
     }
@@ -178,6 +322,7 @@ impl Default for TransactionBuilder {
 }
 
 /// Treasury manager
+#[derive(Clone)]
 pub struct TreasuryManager {
     /// Configuration
     config: Arc<Config>,
@@ -187,6 +332,18 @@ pub struct TreasuryManager {
     database: Database,
     /// Token manager reference
     token_manager: Arc<TokenManager>,
+    /// Limiter for concurrent on-chain executions
+    execution_limiter: ExecutionLimiter,
+    /// Event sender for real-time updates
+    event_sender: Option<broadcast::Sender<WebSocketEvent>>,
+    /// Price source for rebalancing and USD valuation
+    price_oracle: Option<Arc<dyn PriceOracle>>,
+    /// Last known prices, served while the oracle is down
+    price_cache: Arc<PriceCache>,
+    /// Whether the last valuation found the oracle down
+    oracle_down: Arc<AtomicBool>,
+    /// Log of admin actions such as vetoes
+    audit_log: Option<Arc<AuditLog>>,
 }
 
 impl TreasuryManager {
@@ -203,83 +360,280 @@ impl TreasuryManager {
             blockchain: Arc::new(blockchain),
             database,
             token_manager: Arc::new(token_manager),
+            execution_limiter: ExecutionLimiter::from_config(&config.blockchain),
+            event_sender: None,
+            price_oracle: None,
+            price_cache: Arc::new(PriceCache::default()),
+            oracle_down: Arc::new(AtomicBool::new(false)),
+            audit_log: None,
         })
     }
     
+    /// Broadcast treasury events on the given channel
+    pub fn with_event_sender(mut self, sender: broadcast::Sender<WebSocketEvent>) -> Self {
+        self.event_sender = Some(sender);
+        self
+    }
+    
+    /// Share an execution limiter with other executing components
+    pub fn with_execution_limiter(mut self, limiter: ExecutionLimiter) -> Self {
+        self.execution_limiter = limiter;
+        self
+    }
+    
+    /// Record vetoes in the given admin action log
+    pub fn with_audit_log(mut self, audit_log: Arc<AuditLog>) -> Self {
+        self.audit_log = Some(audit_log);
+        self
+    }
+    
+    /// Price holdings with the given oracle when rebalancing and valuing balances
+    pub fn with_price_oracle(mut self, oracle: Arc<dyn PriceOracle>) -> Self {
+        self.price_oracle = Some(oracle);
+        self
+    }
+    
+    /// Number of treasury and proposal executions currently in flight
+    pub fn executions_in_flight(&self) -> usize {
+        self.execution_limiter.in_flight()
+    }
+    
     /// Create a new transaction
+    ///
+    /// The transaction must name its creator, and a creator who is suspended
+    /// or inactive is refused. If the transaction has an idempotency key the
+    /// creator already used within the window, the earlier transaction's ID is
+    /// returned and nothing new is stored.
     pub async fn create_transaction(&self, transaction: Transaction) -> Result<TransactionId> {
-        // Check if the token exists
-        if !self.token_manager.token_exists(&transaction.token).await? {
-            return Err(DaoError::InvalidParameter(format!(
-                "Token with symbol {} not found",
-                transaction.token
-            )));
+        let actor = transaction
+            .creator
+            .clone()
+            .ok_or_else(|| DaoError::InvalidParameter("Transaction creator is required".to_string()))?;
+        identity::require_standing(
+            &self.database.primary(),
+            &actor,
+            self.config.identity.allow_unregistered_voters,
+        )
+        .await?;
+        
+        let Some(key) = transaction.idempotency_key.clone() else {
+            let transaction = self.prepare_new_transaction(transaction).await?;
+            self.save_transaction(&transaction, &actor).await?;
+            return Ok(transaction.id);
+        };
+        
+        // A retry with the same idempotency key returns the first transaction;
+        // keys are scoped to the creator
+        let window = Duration::hours(self.config.treasury.idempotency_window_hours as i64);
+        let request_hash = idempotency::request_hash(&transaction);
+        if let Some(existing) = idempotency::find_existing(&self.database, &actor, &key, &request_hash, window).await? {
+            return Ok(existing);
         }
         
-        // Check if the treasury has enough balance
-        let treasury_address = self.get_treasury_address().await?;
-        let treasury_balance = self
-            .token_manager
-            .get_balance(&transaction.token, &treasury_address)
-            .await?;
+        let transaction = self.prepare_new_transaction(transaction).await?;
+        idempotency::insert_with_key(&self.database, &actor, &key, &request_hash, window, &transaction, &actor).await
+    }
+    
+    /// Validate a new transaction and set its expiry, ready to be stored
+    async fn prepare_new_transaction(&self, mut transaction: Transaction) -> Result<Transaction> {
+        let lines = transaction.transfers();
+        let totals = totals_by_token(&lines);
         
-        if treasury_balance < transaction.amount {
-            return Err(DaoError::InvalidParameter(
-                "Insufficient treasury balance".to_string(),
-            ));
+        for token in totals.keys() {
+            // Check if the token exists
+            let token = Symbol::parse(token)?;
+            if !self.token_manager.token_exists(&token).await? {
+                return Err(DaoError::InvalidParameter(format!(
+                    "Token with symbol {} not found",
+                    token
+                )));
+            }
+            
+            // On-chain transfers can't be rolled back with the rest of a batch
+            if lines.len() > 1 && self.token_manager.is_on_chain(&token) {
+                return Err(DaoError::InvalidParameter(format!(
+                    "{} is transferred on-chain and can't be part of a batch transaction",
+                    token
+                )));
+            }
         }
         
-        // Save the transaction to the database
-        self.save_transaction(&transaction).await?;
+        let signers = self.get_signers().await?;
+        check_required_approvals(transaction.required_approvals, self.config.treasury.threshold, signers.len())?;
+        
+        self.check_spending_limits(&transaction).await?;
+        
+        // Check if the treasury has enough balance for every line
+        let treasury_address = self.get_treasury_address().await?;
+        let balances = self.treasury_balances(&totals, &treasury_address).await?;
+        check_batch_balances(&lines, &balances)?;
+        
+        // Expire after the configured period unless the builder set a time
+        let expiry = Duration::hours(self.config.treasury.transaction_expiry_hours as i64);
+        transaction.expires_at.get_or_insert(transaction.created_at + expiry);
         
-        Ok(transaction.id)
+        Ok(transaction)
+    }
+    
+    /// Enforce each token's per-transaction and daily spending limits
+    ///
+    /// The transaction itself is left out of the committed amount, so it can
+    /// be checked again when it is approved and executed.
+    async fn check_spending_limits(&self, transaction: &Transaction) -> Result<()> {
+        let now = Utc::now();
+        
+        for (token, total) in &totals_by_token(&transaction.transfers()) {
+            if let Some(limits) = self.config.treasury.limits.get(token) {
+                let window = limits::load_window_transactions(&self.database, token, now).await?;
+                let committed = limits::committed_spend(&window, token, now, &transaction.id);
+                limits::check_spending_limits(token, *total, limits, committed)?;
+            }
+        }
+        
+        Ok(())
     }
     
     /// Get a transaction by ID
+    ///
+    /// Served by the read replica when one is configured, so approvals made
+    /// moments ago may not be reflected yet.
     pub async fn get_transaction(&self, id: &TransactionId) -> Result<Transaction> {
+        Self::load_transaction(&self.database, id).await
+    }
+    
+    /// Get a transaction by ID from the primary, for read-modify-write paths
+    async fn get_transaction_for_update(&self, id: &TransactionId) -> Result<Transaction> {
+        Self::load_transaction(&self.database.primary(), id).await
+    }
+    
+    /// Load a transaction using the given database handle
+    ///
+    /// Soft-deleted transactions are not found. Portable: runs on
+    /// PostgreSQL and SQLite.
+    async fn load_transaction(database: &Database, id: &TransactionId) -> Result<Transaction> {
         // Load the transaction from the database
-        let query = "SELECT * FROM treasury_transactions WHERE id = $1";
-        let row = self.database.query_one(query, &[&id]).await?;
+        let query = "SELECT data FROM treasury_transactions WHERE id = $1 AND deleted_at IS NULL";
+        let row = database
+            .query_opt_any(query, &[id.as_str().into()])
+            .await?
+            .ok_or_else(|| DaoError::TreasuryError(format!("Transaction not found: {}", id)))?;
         
         // Parse the transaction from the row
-        let transaction: Transaction = serde_json::from_value(row.get("data"))
-            .map_err(|e| DaoError::DatabaseError(format!("Failed to parse transaction: {}", e)))?;
-        
-        Ok(transaction)
+        row.json("data")
+            .map_err(|e| DaoError::DatabaseError(format!("Failed to parse transaction: {}", e)))
     }
     
-    /// Get all transactions
+    /// Get all transactions, newest first
+    ///
+    /// Soft-deleted transactions are left out unless `include_deleted` is set.
     pub async fn get_transactions(
         &self,
         status: Option<TransactionStatus>,
+        include_deleted: bool,
     ) -> Result<Vec<Transaction>> {
-        // Construct the query based on the status filter
-        let (query, params) = match status {
-            Some(status) => {
-                let query = "SELECT * FROM treasury_transactions WHERE status = $1 ORDER BY created_at DESC";
-                let status_str = serde_json::to_string(&status)
-                    .map_err(|e| DaoError::DatabaseError(format!("Failed to serialize status: {}", e)))?;
-                (query, vec![&status_str as &(dyn tokio_postgres::types::ToSql + Sync)])
-            }
-            None => {
-                let query = "SELECT * FROM treasury_transactions ORDER BY created_at DESC";
-                (query, Vec::new())
-            }
+        Self::list_transactions(&self.database, status, include_deleted).await
+    }
+    
+    /// Get a page of transactions, newest first
+    ///
+    /// Transactions created while a client pages through the listing don't
+    /// shift later pages. Soft-deleted transactions are left out unless
+    /// `include_deleted` is set.
+    pub async fn get_transactions_page(
+        &self,
+        status: Option<TransactionStatus>,
+        include_deleted: bool,
+        request: &PageRequest,
+    ) -> Result<Page<Transaction>> {
+        Self::list_transactions_page(&self.database, status, include_deleted, request).await
+    }
+    
+    /// List transactions using the given database handle
+    ///
+    /// Portable: runs on PostgreSQL and SQLite.
+    async fn list_transactions(
+        database: &Database,
+        status: Option<TransactionStatus>,
+        include_deleted: bool,
+    ) -> Result<Vec<Transaction>> {
+        // Construct the query from the filters
+        let (conditions, params) = Self::list_filter(status, include_deleted)?;
+        let filter = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!(" WHERE {}", conditions.join(" AND "))
         };
+        let query = format!("SELECT data FROM treasury_transactions{} ORDER BY created_at DESC", filter);
         
         // Load the transactions from the database
-        let rows = self.database.query(query, &params).await?;
+        let rows = database.query_any(&query, &params).await?;
         
         // Parse the transactions from the rows
-        let transactions = rows
-            .into_iter()
+        rows.iter()
             .map(|row| {
-                serde_json::from_value(row.get("data"))
+                row.json("data")
                     .map_err(|e| DaoError::DatabaseError(format!("Failed to parse transaction: {}", e)))
             })
-            .collect::<Result<Vec<Transaction>>>()?;
+            .collect()
+    }
+    
+    /// List a page of transactions using the given database handle
+    ///
+    /// Portable: runs on PostgreSQL and SQLite.
+    async fn list_transactions_page(
+        database: &Database,
+        status: Option<TransactionStatus>,
+        include_deleted: bool,
+        request: &PageRequest,
+    ) -> Result<Page<Transaction>> {
+        let (conditions, params) = Self::list_filter(status, include_deleted)?;
+        fetch_page(database, "treasury_transactions", conditions, params, request, |transaction: &Transaction| {
+            (transaction.created_at, transaction.id.clone())
+        })
+        .await
+    }
+    
+    /// Conditions and parameters selecting the transactions to list
+    fn list_filter(status: Option<TransactionStatus>, include_deleted: bool) -> Result<(Vec<String>, Vec<SqlValue>)> {
+        let mut conditions = Vec::new();
+        let mut params = Vec::new();
+        if let Some(status) = status {
+            let status_str = serde_json::to_string(&status)
+                .map_err(|e| DaoError::DatabaseError(format!("Failed to serialize status: {}", e)))?;
+            params.push(SqlValue::Text(status_str));
+            conditions.push("status = $1".to_string());
+        }
+        if !include_deleted {
+            conditions.push("deleted_at IS NULL".to_string());
+        }
         
-        Ok(transactions)
+        Ok((conditions, params))
+    }
+    
+    /// Soft-delete a transaction, hiding it from lookups and listings
+    ///
+    /// The row and its data are kept, with `actor` recorded as the last
+    /// writer. Fails if the transaction doesn't exist or is already deleted.
+    pub async fn soft_delete_transaction(&self, id: &TransactionId, actor: &str) -> Result<()> {
+        Self::soft_delete(&self.database.primary(), id, actor).await
+    }
+    
+    /// Soft-delete a transaction using the given database handle
+    ///
+    /// Portable: runs on PostgreSQL and SQLite.
+    async fn soft_delete(database: &Database, id: &TransactionId, actor: &str) -> Result<()> {
+        let deleted = database
+            .execute_any(
+                "UPDATE treasury_transactions SET deleted_at = $1, updated_by = $2 WHERE id = $3 AND deleted_at IS NULL",
+                &[Utc::now().timestamp().into(), actor.into(), id.as_str().into()],
+            )
+            .await?;
+        
+        if deleted == 0 {
+            return Err(DaoError::TreasuryError(format!("Transaction not found: {}", id)));
+        }
+        
+        Ok(())
     }
     
     /// Approve a transaction
@@ -288,45 +642,34 @@ impl TreasuryManager {
         id: &TransactionId,
         approver: &str,
     ) -> Result<()> {
+        // Suspended and inactive signers can't approve
+        identity::require_standing(
+            &self.database.primary(),
+            approver,
+            self.config.identity.allow_unregistered_voters,
+        )
+        .await?;
+        
         // Load the transaction
-        let mut transaction = self.get_transaction(id).await?;
+        let mut transaction = self.get_transaction_for_update(id).await?;
         
-        // Check if the transaction is in a pending state
-        if transaction.status != TransactionStatus::Pending {
-            return Err(DaoError::InvalidParameter(
-                "Transaction is not in a pending state".to_string(),
-            ));
-        }
+        // Limits may have filled up since the transaction was created
+        self.check_spending_limits(&transaction).await?;
         
-        // Check if the approver is a valid signer
+        // The signer set may have shrunk or the threshold risen since the
+        // transaction was created; check before recording anything
         let signers = self.get_signers().await?;
-        if !signers.contains(&approver.to_string()) {
-            return Err(DaoError::Unauthorized);
-        }
+        check_required_approvals(transaction.required_approvals, self.config.treasury.threshold, signers.len())?;
         
-        // Check if the approver has already approved
-        if transaction.approvers.contains(&approver.to_string()) {
-            return Err(DaoError::InvalidParameter(
-                "Approver has already approved this transaction".to_string(),
-            ));
-        }
-        
-        // Add the approver
-        transaction.approvers.push(approver.to_string());
-        transaction.current_approvals += 1;
-        transaction.updated_at = Utc::now();
-        
-        // Check if the transaction has enough approvals
-        if transaction.current_approvals >= transaction.required_approvals {
-            transaction.status = TransactionStatus::Approved;
-        }
+        // Record the approval if the approver is a signer who hasn't approved yet
+        record_approval(&mut transaction, approver, &signers, Utc::now())?;
         
         // Save the updated transaction
-        self.save_transaction(&transaction).await?;
+        self.save_transaction(&transaction, approver).await?;
         
         // If the transaction is now approved, try to execute it
         if transaction.status == TransactionStatus::Approved {
-            self.execute_transaction(id).await?;
+            self.execute_transaction(id, approver).await?;
         }
         
         Ok(())
@@ -335,7 +678,7 @@ impl TreasuryManager {
     /// Reject a transaction
     pub async fn reject_transaction(&self, id: &TransactionId, rejector: &str) -> Result<()> {
         // Load the transaction
-        let mut transaction = self.get_transaction(id).await?;
+        let mut transaction = self.get_transaction_for_update(id).await?;
         
         // Check if the transaction is in a pending state
         if transaction.status != TransactionStatus::Pending {
@@ -346,7 +689,7 @@ impl TreasuryManager {
         
         // Check if the rejector is a valid signer
         let signers = self.get_signers().await?;
-        if !signers.contains(&rejector.to_string()) {
+        if !is_signer(&signers, rejector) {
             return Err(DaoError::Unauthorized);
         }
         
@@ -355,15 +698,86 @@ impl TreasuryManager {
         transaction.updated_at = Utc::now();
         
         // Save the updated transaction
-        self.save_transaction(&transaction).await?;
+        self.save_transaction(&transaction, rejector).await?;
+        
+        Ok(())
+    }
+    
+    /// Cancel an approved transaction before it is executed
+    ///
+    /// Permitted for the transaction's creator and for Council or Admin
+    /// members; a cancellation by anyone but the creator is a veto and is
+    /// recorded in the admin action log. Fails if an execution claimed the
+    /// transaction first.
+    pub async fn cancel_transaction(
+        &self,
+        id: &TransactionId,
+        canceller: &str,
+        reason: &str,
+    ) -> Result<()> {
+        if reason.trim().is_empty() {
+            return Err(DaoError::InvalidParameter(
+                "Cancellation reason cannot be empty".to_string(),
+            ));
+        }
+        
+        // Load the transaction
+        let mut transaction = self.get_transaction_for_update(id).await?;
+        
+        // Check the state and the canceller's rights
+        let canceller_role = self.get_member_role(canceller).await?;
+        check_cancellable(&transaction, canceller, canceller_role)?;
+        
+        // A submitted on-chain transfer can't be called back
+        if execution::has_intent(&self.database, &transaction.id).await? {
+            return Err(DaoError::InvalidParameter(
+                "Transaction has been submitted on-chain and can no longer be cancelled".to_string(),
+            ));
+        }
+        
+        // Update the transaction status
+        transaction.status = TransactionStatus::Cancelled;
+        transaction.cancel_reason = Some(reason.to_string());
+        transaction.updated_at = Utc::now();
+        
+        // Save only if nothing (e.g. an execution) changed it since we loaded it
+        self.save_transaction_versioned(&mut transaction, canceller).await?;
+        
+        if transaction.creator.as_deref() != Some(canceller) {
+            record_admin_action(
+                self.audit_log.as_ref(),
+                AdminAction::Veto,
+                canceller,
+                serde_json::json!({ "transaction_id": transaction.id, "reason": reason }),
+            )
+            .await?;
+        }
+        
+        self.emit_event(
+            EventType::TransactionCancelled,
+            serde_json::json!({
+                "transaction_id": transaction.id,
+                "status": transaction.status,
+                "cancelled_by": canceller,
+                "reason": reason,
+            }),
+        );
         
         Ok(())
     }
     
     /// Execute a transaction
-    pub async fn execute_transaction(&self, id: &TransactionId) -> Result<()> {
+    ///
+    /// Database-held tokens move in the same database transaction that marks
+    /// the treasury transaction executed; if either write fails, neither is
+    /// kept and the transaction stays approved. On-chain transfers are
+    /// submitted behind an execution intent and settled by
+    /// [`reconcile_executions`](Self::reconcile_executions) once their
+    /// receipt arrives. `executor` is recorded as the transaction's last
+    /// writer.
+    pub async fn execute_transaction(&self, id: &TransactionId, executor: &str) -> Result<()> {
         // Load the transaction
-        let mut transaction = self.get_transaction(id).await?;
+        let transaction = self.get_transaction_for_update(id).await?;
         
         // Check if the transaction is approved
         if transaction.status != TransactionStatus::Approved {
@@ -372,50 +786,316 @@ impl TreasuryManager {
             ));
         }
         
+        check_not_expired(&transaction, Utc::now())?;
+        
+        // Left approved, so it can execute once the daily window frees up
+        self.check_spending_limits(&transaction).await?;
+        
         // Get the treasury address
         let treasury_address = self.get_treasury_address().await?;
         
-        // Execute the transfer
+        // Every line is checked against the aggregate balance per token
+        // before anything moves; one short line fails the whole transaction
+        let lines = transaction.transfers();
+        let totals = totals_by_token(&lines);
+        let balances = self.treasury_balances(&totals, &treasury_address).await?;
+        if let Err(e) = check_batch_balances(&lines, &balances) {
+            return self.fail_transaction(transaction, e, executor).await;
+        }
+        
+        // Only single-line transactions move on-chain tokens
+        if let [line] = lines.as_slice() {
+            let token = Symbol::parse(&line.token)?;
+            if self.token_manager.is_on_chain(&token) {
+                let recipient = Address::parse(&line.to)?;
+                return self.execute_on_chain(transaction, &recipient, executor).await;
+            }
+        }
+        
+        let mut executed = transaction;
+        executed.status = TransactionStatus::Executed;
+        executed.executed_at = Some(Utc::now());
+        executed.updated_at = Utc::now();
+        
+        self.execution_limiter
+            .run(execution::execute_in_database(
+                &self.database,
+                &mut executed,
+                treasury_address.as_str(),
+                executor,
+            ))
+            .await
+    }
+    
+    /// Submit an on-chain transfer behind an execution intent
+    ///
+    /// The intent is stored before submission, so a crash after the transfer
+    /// was sent still leaves a record for reconciliation. The transaction
+    /// stays approved until the receipt is settled, either here or later by
+    /// `reconcile_executions`, which [`spawn_maintenance`](Self::spawn_maintenance)
+    /// runs periodically.
+    async fn execute_on_chain(&self, mut transaction: Transaction, recipient: &Address, executor: &str) -> Result<()> {
+        execution::insert_intent(&self.database, &transaction.id).await?;
+        
+        // Claim the transaction so a concurrent cancellation can't slip in
+        // between the state check and the transfer
+        transaction.updated_at = Utc::now();
+        if let Err(e) = self.save_transaction_versioned(&mut transaction, executor).await {
+            execution::discard_intent(&self.database, &transaction.id).await?;
+            return Err(e);
+        }
+        
         let result = self
-            .token_manager
-            .transfer(
-                &transaction.token,
-                &treasury_address,
-                &transaction.to,
-                transaction.amount,
-            )
+            .execution_limiter
+            .run(self.token_manager.send_on_chain(recipient, transaction.amount))
             .await;
         
         match result {
-            Ok(_) => {
-                // Update the transaction status
-                transaction.status = TransactionStatus::Executed;
-                transaction.executed_at = Some(Utc::now());
-                transaction.updated_at = Utc::now();
-                
-                // Save the updated transaction
-                self.save_transaction(&transaction).await?;
-                
-                Ok(())
+            Ok(tx_hash) => {
+                execution::record_intent_hash(&self.database, &transaction.id, &tx_hash).await?;
+                info!("Submitted treasury transaction {} as {}", transaction.id, tx_hash);
+                self.await_confirmation(&transaction.id, tx_hash, executor).await
             }
             Err(e) => {
-                // Update the transaction status
-                transaction.status = TransactionStatus::Failed;
-                transaction.updated_at = Utc::now();
-                transaction.metadata = serde_json::json!({
-                    "error": e.to_string(),
-                });
-                
-                // Save the updated transaction
-                self.save_transaction(&transaction).await?;
+                execution::discard_intent(&self.database, &transaction.id).await?;
+                self.fail_transaction(transaction, e, executor).await
+            }
+        }
+    }
+    
+    /// Wait for a submitted transfer's configured confirmations and settle
+    /// the transaction from its receipt
+    ///
+    /// With no confirmations configured, the receipt is checked once. A
+    /// transfer that isn't mined or confirmed in time keeps its intent and is
+    /// left for `reconcile_executions`.
+    async fn await_confirmation(&self, transaction_id: &TransactionId, tx_hash: String, executor: &str) -> Result<()> {
+        let treasury = &self.config.treasury;
+        
+        let status = if treasury.confirmations == 0 {
+            // Without confirmations to wait for, settle now if the receipt is in
+            match self.blockchain.get_transaction(&tx_hash, None).await {
+                Ok(receipt) => receipt.status,
+                Err(e) => {
+                    warn!(
+                        "No receipt for treasury transaction {} yet, leaving it for reconciliation: {}",
+                        transaction_id, e
+                    );
+                    return Ok(());
+                }
+            }
+        } else {
+            let confirmed = self
+                .blockchain
+                .wait_for_confirmation(
+                    &tx_hash,
+                    treasury.confirmations,
+                    std::time::Duration::from_secs(treasury.confirmation_timeout_seconds),
+                    None,
+                )
+                .await;
+            
+            match confirmed {
+                Ok(status) => Some(status),
+                Err(e) => {
+                    warn!(
+                        "Treasury transaction {} is not confirmed yet, leaving it for reconciliation: {}",
+                        transaction_id, e
+                    );
+                    return Ok(());
+                }
+            }
+        };
+        
+        // Settle the stored transaction rather than the copy from before the
+        // wait; reconciliation may have settled it in the meantime
+        if !execution::has_intent(&self.database, transaction_id).await? {
+            return Ok(());
+        }
+        let mut transaction = self.get_transaction_for_update(transaction_id).await?;
+        if !apply_receipt(&mut transaction, &tx_hash, status, Utc::now()) {
+            return Ok(());
+        }
+        
+        execution::settle_intent(&self.database, transaction, executor).await
+    }
+    
+    /// Settle on-chain executions whose receipts have arrived
+    ///
+    /// Returns the number of transactions settled. An intent without a hash
+    /// was interrupted between storing the intent and recording the hash, so
+    /// the transfer may or may not have been sent; it is left for an operator
+    /// to resolve.
+    pub async fn reconcile_executions(&self) -> Result<usize> {
+        let mut settled = 0;
+        
+        // Receipts only settle once they are `treasury.confirmations` deep
+        let confirmations = self.config.treasury.confirmations;
+        let current_block = if confirmations > 0 {
+            Some(
+                self.blockchain
+                    .get_block_number(None)
+                    .await
+                    .map_err(|e| DaoError::BlockchainError(e.to_string()))?,
+            )
+        } else {
+            None
+        };
+        
+        for intent in execution::pending_intents(&self.database).await? {
+            let Some(tx_hash) = intent.tx_hash else {
+                warn!(
+                    "Treasury transaction {} has an execution intent without a blockchain hash since {}",
+                    intent.transaction_id, intent.created_at
+                );
+                continue;
+            };
+            
+            // One failing intent must not hold up the rest
+            let receipt = match self.blockchain.get_transaction(&tx_hash, None).await {
+                Ok(receipt) => receipt,
+                Err(e) => {
+                    warn!(
+                        "Could not fetch the receipt for treasury transaction {}: {}",
+                        intent.transaction_id, e
+                    );
+                    continue;
+                }
+            };
+            
+            if let Some(current_block) = current_block {
+                let confirmed = receipt
+                    .block_number
+                    .map_or(false, |mined| confirmation_depth(mined, current_block) >= confirmations);
+                if !confirmed {
+                    continue;
+                }
+            }
+            
+            let mut transaction = match self.get_transaction_for_update(&intent.transaction_id).await {
+                Ok(transaction) => transaction,
+                Err(e) => {
+                    warn!("Could not load treasury transaction {}: {}", intent.transaction_id, e);
+                    continue;
+                }
+            };
+            if !apply_receipt(&mut transaction, &tx_hash, receipt.status, Utc::now()) {
+                continue;
+            }
+            
+            match execution::settle_intent(&self.database, transaction, SYSTEM_ACTOR).await {
+                Ok(()) => settled += 1,
+                Err(e) => warn!("Could not settle treasury transaction {}: {}", intent.transaction_id, e),
+            }
+        }
+        
+        Ok(settled)
+    }
+    
+    /// Reconcile on-chain executions and expire stale transactions every
+    /// `treasury.maintenance_interval_seconds`
+    ///
+    /// Returns `None` when the interval is 0. Must be called from within a
+    /// Tokio runtime.
+    pub fn spawn_maintenance(&self) -> Option<JoinHandle<()>> {
+        let interval_seconds = self.config.treasury.maintenance_interval_seconds;
+        if interval_seconds == 0 {
+            return None;
+        }
+        
+        let manager = self.clone();
+        let period = std::time::Duration::from_secs(interval_seconds);
+        Some(tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
                 
-                Err(e)
+                // Failures are retried on the next tick
+                if let Err(e) = manager.reconcile_executions().await {
+                    warn!("Treasury reconciliation failed: {}", e);
+                }
+                if let Err(e) = manager.process_expired_transactions().await {
+                    warn!("Expiring treasury transactions failed: {}", e);
+                }
             }
+        }))
+    }
+    
+    /// Treasury balance of each token in `totals`
+    async fn treasury_balances(
+        &self,
+        totals: &BTreeMap<String, TokenAmount>,
+        treasury_address: &Address,
+    ) -> Result<BTreeMap<String, TokenAmount>> {
+        let mut balances = BTreeMap::new();
+        
+        for token in totals.keys() {
+            let symbol = Symbol::parse(token)?;
+            let balance = self.token_manager.get_balance(&symbol, treasury_address).await?;
+            balances.insert(token.clone(), balance);
         }
+        
+        Ok(balances)
+    }
+    
+    /// Mark a transaction failed and return the error that failed it
+    async fn fail_transaction(&self, mut transaction: Transaction, error: DaoError, actor: &str) -> Result<()> {
+        transaction.status = TransactionStatus::Failed;
+        transaction.updated_at = Utc::now();
+        transaction.metadata = serde_json::json!({
+            "error": error.to_string(),
+        });
+        
+        self.save_transaction_versioned(&mut transaction, actor).await?;
+        
+        Err(error)
+    }
+    
+    /// Expire pending and approved transactions whose expiry time has passed
+    ///
+    /// Returns the number of transactions expired. A transaction changed
+    /// concurrently (e.g. approved), or whose execution is already in
+    /// flight, is skipped.
+    pub async fn process_expired_transactions(&self) -> Result<usize> {
+        let now = Utc::now();
+        let mut expired = 0;
+        
+        // Approved transactions can't execute past their expiry either
+        let mut open = self.get_transactions(Some(TransactionStatus::Pending), false).await?;
+        open.extend(self.get_transactions(Some(TransactionStatus::Approved), false).await?);
+        
+        for mut transaction in open {
+            if !transaction.is_expired(now) {
+                continue;
+            }
+            // A sent execution settles from its receipt instead
+            if execution::has_intent(&self.database, &transaction.id).await? {
+                continue;
+            }
+            
+            transaction.status = TransactionStatus::Expired;
+            transaction.updated_at = now;
+            
+            match self.save_transaction_versioned(&mut transaction, SYSTEM_ACTOR).await {
+                Ok(()) => {
+                    info!("Treasury transaction {} expired", transaction.id);
+                    expired += 1;
+                }
+                Err(e) => warn!("Could not expire treasury transaction {}: {}", transaction.id, e),
+            }
+        }
+        
+        // Idempotency keys past their window no longer block reuse
+        let window = Duration::hours(self.config.treasury.idempotency_window_hours as i64);
+        if let Err(e) = idempotency::purge_expired_keys(&self.database, window).await {
+            warn!("Could not purge expired idempotency keys: {}", e);
+        }
+        
+        Ok(expired)
     }
     
     /// Get the treasury balance
-    pub async fn get_balance(&self, token: &str) -> Result<TokenAmount> {
+    pub async fn get_balance(&self, token: &Symbol) -> Result<TokenAmount> {
         let treasury_address = self.get_treasury_address().await?;
         self.token_manager
             .get_balance(token, &treasury_address)
@@ -437,7 +1117,7 @@ impl TreasuryManager {
             let symbol: String = row.get("symbol");
             let balance = self
                 .token_manager
-                .get_balance(&symbol, &treasury_address)
+                .get_balance(&Symbol::parse(&symbol)?, &treasury_address)
                 .await?;
             
             balances.insert(symbol, balance);
@@ -446,34 +1126,189 @@ impl TreasuryManager {
         Ok(balances)
     }
     
+    /// Get all treasury balances with their USD values
+    ///
+    /// An oracle outage does not fail the request: cached prices no older
+    /// than `oracle.max_stale_seconds` are served marked stale, and beyond
+    /// that `usd_value` is `None`. A warning event is emitted when the oracle
+    /// goes down.
+    pub async fn get_valued_balances(&self) -> Result<Vec<ValuedBalance>> {
+        let mut balances = Vec::new();
+        for (symbol, balance) in self.get_balances().await? {
+            let symbol = Symbol::parse(&symbol)?;
+            let token = self.token_manager.get_token(&symbol).await?;
+            balances.push((symbol, balance, token.decimals));
+        }
+        
+        let max_stale = chrono::Duration::seconds(self.config.oracle.max_stale_seconds as i64);
+        let valued = value_balances(
+            balances,
+            self.price_oracle.as_deref(),
+            &self.price_cache,
+            max_stale,
+            Utc::now(),
+        )
+        .await;
+        
+        if self.price_oracle.is_some() {
+            self.track_oracle_status(overall_status(&valued));
+        }
+        
+        Ok(valued)
+    }
+    
+    /// Draft a swap proposal that restores the target allocations
+    ///
+    /// Returns `None` when every holding is within the configured tolerance.
+    /// The draft is not submitted; it goes through the normal proposal flow.
+    pub async fn propose_rebalance(
+        &self,
+        targets: &[AllocationTarget],
+        proposer: &Address,
+    ) -> Result<Option<Proposal>> {
+        let oracle = self.price_oracle.as_ref().ok_or_else(|| {
+            DaoError::NotSupported("Rebalancing requires a price oracle".to_string())
+        })?;
+        
+        let mut holdings = Vec::new();
+        for (symbol, balance) in self.get_balances().await? {
+            let symbol = Symbol::parse(&symbol)?;
+            let token = self.token_manager.get_token(&symbol).await?;
+            let price = oracle.price(&symbol).await?;
+            
+            holdings.push(Holding {
+                symbol,
+                balance,
+                decimals: token.decimals,
+                price,
+            });
+        }
+        
+        let tolerance = self.config.treasury.rebalance_tolerance_percent;
+        let Some(swaps) = plan_rebalance(&holdings, targets, tolerance)? else {
+            return Ok(None);
+        };
+        
+        let allocation: Vec<String> = targets
+            .iter()
+            .map(|t| format!("{} {}%", t.symbol, t.percent))
+            .collect();
+        
+        let proposal = ProposalBuilder::new()
+            .title("Rebalance treasury")
+            .description(format!(
+                "Swap treasury holdings back to the target allocation ({}), \
+                 with a tolerance of {}%.",
+                allocation.join(", "),
+                tolerance
+            ))
+            .proposal_type(ProposalType::Swap { swaps })
+            .proposer(proposer.to_string())
+            .build()?;
+        
+        Ok(Some(proposal))
+    }
+    
     // Private methods
     
-    /// Get the treasury address
-    async fn get_treasury_address(&self) -> Result<String> {
-        // In a real implementation, this would be a multi-sig wallet address
-        // controlled by the DAO signers
+    /// Warn once when the oracle goes down and log when it recovers
+    fn track_oracle_status(&self, status: OracleStatus) {
+        let down = status != OracleStatus::Available;
+        if self.oracle_down.swap(down, Ordering::SeqCst) == down {
+            return;
+        }
         
-        // For this example, we'll just return a placeholder address
-        // This would be derived from the governance parameters or stored in the database
-        Ok("0xTreasury".to_string())
+        if down {
+            warn!("Price oracle unavailable; serving {:?} USD values", status);
+            self.emit_event(
+                EventType::OracleUnavailable,
+                serde_json::json!({
+                    "oracle_status": status,
+                    "max_stale_seconds": self.config.oracle.max_stale_seconds,
+                }),
+            );
+        } else {
+            info!("Price oracle available again");
+        }
+    }
+    
+    /// Broadcast an event if an event sender is configured
+    fn emit_event(&self, event_type: EventType, data: serde_json::Value) {
+        if let Some(sender) = &self.event_sender {
+            // Sending only fails when there are no subscribers
+            let _ = sender.send(WebSocketManager::create_event(event_type, data));
+        }
+    }
+    
+    /// Get the role of the member with the given address, if registered
+    async fn get_member_role(&self, address: &str) -> Result<Option<MemberRole>> {
+        let row = self
+            .database
+            .query_opt("SELECT data FROM members WHERE LOWER(address) = LOWER($1)", &[&address])
+            .await?;
+        
+        match row {
+            Some(row) => {
+                let member: Member = serde_json::from_value(row.get("data"))
+                    .map_err(|e| DaoError::DatabaseError(format!("Failed to parse member: {}", e)))?;
+                Ok(Some(member.role))
+            }
+            None => Ok(None),
+        }
+    }
+    
+    /// Get the treasury multisig address from the treasury config
+    pub(crate) async fn get_treasury_address(&self) -> Result<Address> {
+        configured_treasury_address(&self.config.treasury, |address| self.blockchain.is_valid_address(address))
     }
     
-    /// Get authorized signers
+    /// Get the authorized signers from the treasury config
     async fn get_signers(&self) -> Result<Vec<String>> {
-        // In a real implementation, these would be loaded from a governance contract
-        // or from the database
+        configured_signers(&self.config.treasury, |address| self.blockchain.is_valid_address(address))
+    }
+    
+    /// Save an existing transaction if its stored version still matches
+    ///
+    /// Bumps the version on success and records `actor` as the last writer.
+    /// Returns an error if another writer updated the transaction since it
+    /// was loaded.
+    async fn save_transaction_versioned(&self, transaction: &mut Transaction, actor: &str) -> Result<()> {
+        let expected_version = transaction.version as i64;
+        transaction.version += 1;
         
-        // For this example, we'll just return a placeholder list
-        // This would be derived from the governance parameters
-        let signers = (0..self.config.treasury.signers)
-            .map(|i| format!("0xSigner{}", i))
-            .collect();
+        // Serialize the transaction
+        let data = serde_json::to_value(&*transaction)
+            .map_err(|e| DaoError::DatabaseError(format!("Failed to serialize transaction: {}", e)))?;
+        
+        let updated = self
+            .database
+            .execute_any(
+                execution::VERSIONED_UPDATE,
+                &[
+                    data.into(),
+                    serde_json::to_string(&transaction.status).unwrap().into(),
+                    transaction.updated_at.into(),
+                    actor.into(),
+                    transaction.id.as_str().into(),
+                    expected_version.into(),
+                ],
+            )
+            .await?;
         
-        Ok(signers)
+        if updated == 0 {
+            transaction.version -= 1;
+            return Err(DaoError::TreasuryError(format!(
+                "Transaction {} was modified concurrently",
+                transaction.id
+            )));
+        }
+        
+        Ok(())
     }
     
-    /// Save a transaction to the database
-    async fn save_transaction(&self, transaction: &Transaction) -> Result<()> {
+    /// Save a transaction to the database, recording `actor` as its last
+    /// writer
+    pub(crate) async fn save_transaction(&self, transaction: &Transaction, actor: &str) -> Result<()> {
         // Serialize the transaction
         let data = serde_json::to_value(transaction)
             .map_err(|e| DaoError::DatabaseError(format!("Failed to serialize transaction: {}", e)))?;
@@ -481,9 +1316,9 @@ impl TreasuryManager {
         // Check if the transaction already exists
         let exists = self
             .database
-            .query_opt(
+            .query_opt_any(
                 "SELECT 1 FROM treasury_transactions WHERE id = $1",
-                &[&transaction.id],
+                &[transaction.id.as_str().into()],
             )
             .await?
             .is_some();
@@ -491,27 +1326,30 @@ impl TreasuryManager {
         if exists {
             // Update the transaction
             self.database
-                .execute(
-                    "UPDATE treasury_transactions SET data = $1, status = $2, updated_at = $3 WHERE id = $4",
+                .execute_any(
+                    "UPDATE treasury_transactions SET data = $1, status = $2, updated_at = $3, updated_by = $4 WHERE id = $5",
                     &[
-                        &data,
-                        &serde_json::to_string(&transaction.status).unwrap(),
-                        &transaction.updated_at,
-                        &transaction.id,
+                        data.into(),
+                        serde_json::to_string(&transaction.status).unwrap().into(),
+                        transaction.updated_at.into(),
+                        actor.into(),
+                        transaction.id.as_str().into(),
                     ],
                 )
                 .await?;
         } else {
             // Insert the transaction
             self.database
-                .execute(
-                    "INSERT INTO treasury_transactions (id, data, status, created_at, updated_at) VALUES ($1, $2, $3, $4, $5)",
+                .execute_any(
+                    "INSERT INTO treasury_transactions (id, data, status, created_at, updated_at, updated_by) \
+                     VALUES ($1, $2, $3, $4, $5, $6)",
                     &[
-                        &transaction.id,
-                        &data,
-                        &serde_json::to_string(&transaction.status).unwrap(),
-                        &transaction.created_at,
-                        &transaction.updated_at,
+                        transaction.id.as_str().into(),
+                        data.into(),
+                        serde_json::to_string(&transaction.status).unwrap().into(),
+                        transaction.created_at.into(),
+                        transaction.updated_at.into(),
+                        actor.into(),
                     ],
                 )
                 .await?;
@@ -519,4 +1357,750 @@ impl TreasuryManager {
         
         Ok(())
     }
-} 
\ No newline at end of file
+} 
+
+/// Treasury multisig address from the config, checked with `is_valid_address`
+fn configured_treasury_address(config: &TreasuryConfig, is_valid_address: impl Fn(&str) -> bool) -> Result<Address> {
+    let address = config.treasury_address.trim();
+    if address.is_empty() {
+        return Err(DaoError::TreasuryError(
+            "No treasury address is configured (set treasury.treasury_address)".to_string(),
+        ));
+    }
+    
+    if !is_valid_address(address) {
+        return Err(DaoError::InvalidParameter(format!(
+            "Treasury address {} is not a valid address",
+            address
+        )));
+    }
+    
+    Address::parse(address)
+}
+
+/// Authorized signers from the config, each checked with `is_valid_address`
+fn configured_signers(config: &TreasuryConfig, is_valid_address: impl Fn(&str) -> bool) -> Result<Vec<String>> {
+    if let TreasurySigners::Count(_) = config.signers {
+        return Err(DaoError::TreasuryError(
+            "treasury.signers holds a signer count; set it to the signer addresses".to_string(),
+        ));
+    }
+    if config.signers.is_empty() {
+        return Err(DaoError::TreasuryError(
+            "No treasury signers are configured (set treasury.signers)".to_string(),
+        ));
+    }
+    
+    config
+        .signers
+        .addresses()
+        .iter()
+        .map(|signer| {
+            if !is_valid_address(signer) {
+                return Err(DaoError::InvalidParameter(format!(
+                    "Treasury signer {} is not a valid address",
+                    signer
+                )));
+            }
+            Ok(Address::parse(signer)?.to_string())
+        })
+        .collect()
+}
+
+/// Total amount per token across transfer lines
+fn totals_by_token(lines: &[TransferLine]) -> BTreeMap<String, TokenAmount> {
+    let mut totals = BTreeMap::new();
+    for line in lines {
+        let total = totals.entry(line.token.clone()).or_insert(0);
+        *total = total.saturating_add(line.amount);
+    }
+    totals
+}
+
+/// Check the treasury can pay every line of a transaction
+///
+/// Lines are summed per token in order; the first line that takes its
+/// token's running total past the balance is reported.
+fn check_batch_balances(lines: &[TransferLine], balances: &BTreeMap<String, TokenAmount>) -> Result<()> {
+    let mut running: BTreeMap<&str, TokenAmount> = BTreeMap::new();
+    
+    for (i, line) in lines.iter().enumerate() {
+        let total = running.entry(line.token.as_str()).or_insert(0);
+        *total = total.saturating_add(line.amount);
+        
+        let balance = balances.get(&line.token).copied().unwrap_or(0);
+        if *total > balance {
+            return Err(DaoError::InvalidParameter(format!(
+                "Insufficient treasury balance for line {}: {} {} needed, {} held",
+                i + 1,
+                total,
+                line.token,
+                balance
+            )));
+        }
+    }
+    
+    Ok(())
+}
+
+/// Reject a transaction whose expiry time has passed
+fn check_not_expired(transaction: &Transaction, now: DateTime<Utc>) -> Result<()> {
+    match transaction.expires_at {
+        Some(expires_at) if transaction.is_expired(now) => Err(DaoError::TreasuryError(format!(
+            "Transaction {} expired at {}",
+            transaction.id, expires_at
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Check a transaction's required approvals against the signer set
+///
+/// A transaction can't require fewer approvals than `treasury.threshold`,
+/// or one signer could move funds alone under a multi-signer setup, nor
+/// more approvals than there are signers.
+fn check_required_approvals(required_approvals: u32, threshold: u32, signers: usize) -> Result<()> {
+    if required_approvals < threshold {
+        return Err(DaoError::InvalidParameter(format!(
+            "Transaction requires {} approvals but the treasury threshold is {}",
+            required_approvals, threshold
+        )));
+    }
+    
+    if required_approvals as usize > signers {
+        return Err(DaoError::InvalidParameter(format!(
+            "Transaction requires {} approvals but only {} signers are configured",
+            required_approvals, signers
+        )));
+    }
+    
+    Ok(())
+}
+
+/// Whether an address is one of the signers, ignoring checksum case
+fn is_signer(signers: &[String], address: &str) -> bool {
+    signers.iter().any(|signer| signer.eq_ignore_ascii_case(address))
+}
+
+/// Record a signer's approval of a pending transaction
+///
+/// The transaction becomes approved once it reaches its required approvals.
+fn record_approval(
+    transaction: &mut Transaction,
+    approver: &str,
+    signers: &[String],
+    now: DateTime<Utc>,
+) -> Result<()> {
+    // Check if the transaction is in a pending state
+    if transaction.status != TransactionStatus::Pending {
+        return Err(DaoError::InvalidParameter(
+            "Transaction is not in a pending state".to_string(),
+        ));
+    }
+    
+    check_not_expired(transaction, now)?;
+    
+    // Check if the approver is a valid signer
+    if !is_signer(signers, approver) {
+        return Err(DaoError::Unauthorized);
+    }
+    
+    // Check if the approver has already approved
+    if transaction.approvers.iter().any(|existing| existing.eq_ignore_ascii_case(approver)) {
+        return Err(DaoError::InvalidParameter(
+            "Approver has already approved this transaction".to_string(),
+        ));
+    }
+    
+    // Add the approver
+    transaction.approvers.push(approver.to_string());
+    transaction.current_approvals += 1;
+    transaction.updated_at = Utc::now();
+    
+    // Check if the transaction has enough approvals
+    if transaction.current_approvals >= transaction.required_approvals {
+        transaction.status = TransactionStatus::Approved;
+    }
+    
+    Ok(())
+}
+
+/// Settle a transaction from its blockchain receipt status
+///
+/// Returns `false`, leaving the transaction unchanged, if the blockchain
+/// transaction has not been mined yet.
+fn apply_receipt(transaction: &mut Transaction, tx_hash: &str, status: Option<bool>, now: DateTime<Utc>) -> bool {
+    match status {
+        Some(true) => {
+            transaction.status = TransactionStatus::Executed;
+            transaction.executed_at = Some(now);
+        }
+        Some(false) => {
+            transaction.status = TransactionStatus::Failed;
+            transaction.metadata = serde_json::json!({
+                "error": "Blockchain transaction reverted",
+            });
+        }
+        None => return false,
+    }
+    
+    transaction.transaction_hash = Some(tx_hash.to_string());
+    transaction.updated_at = now;
+    true
+}
+
+/// Check whether a transaction can be cancelled by the given member
+fn check_cancellable(
+    transaction: &Transaction,
+    canceller: &str,
+    canceller_role: Option<MemberRole>,
+) -> Result<()> {
+    if transaction.status != TransactionStatus::Approved || transaction.executed_at.is_some() {
+        return Err(DaoError::InvalidParameter(
+            "Only approved transactions that have not been executed can be cancelled".to_string(),
+        ));
+    }
+    
+    let is_creator = transaction.creator.as_deref() == Some(canceller);
+    let is_council = matches!(canceller_role, Some(MemberRole::Council | MemberRole::Admin));
+    
+    if !is_creator && !is_council {
+        return Err(DaoError::Unauthorized);
+    }
+    
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn approved_transaction() -> Transaction {
+        let mut transaction = TransactionBuilder::new()
+            .description("Pay auditors")
+            .to("0x0000000000000000000000000000000000000002")
+            .token("ATOM")
+            .amount(1_000)
+            .creator("0xCreator")
+            .build()
+            .unwrap();
+        transaction.status = TransactionStatus::Approved;
+        transaction
+    }
+    
+    #[test]
+    fn test_cancel_after_execution_rejected() {
+        let mut transaction = approved_transaction();
+        transaction.status = TransactionStatus::Executed;
+        transaction.executed_at = Some(Utc::now());
+        
+        assert!(matches!(
+            check_cancellable(&transaction, "0xCreator", None),
+            Err(DaoError::InvalidParameter(_))
+        ));
+        assert!(check_cancellable(&transaction, "0xCouncil", Some(MemberRole::Council)).is_err());
+    }
+    
+    #[test]
+    fn test_cancel_permissions() {
+        let transaction = approved_transaction();
+        
+        assert!(check_cancellable(&transaction, "0xCreator", None).is_ok());
+        assert!(check_cancellable(&transaction, "0xCouncil", Some(MemberRole::Council)).is_ok());
+        assert!(check_cancellable(&transaction, "0xAdmin", Some(MemberRole::Admin)).is_ok());
+        assert!(matches!(
+            check_cancellable(&transaction, "0xDelegate", Some(MemberRole::Delegate)),
+            Err(DaoError::Unauthorized)
+        ));
+        
+        let mut pending = approved_transaction();
+        pending.status = TransactionStatus::Pending;
+        assert!(check_cancellable(&pending, "0xCreator", None).is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_on_chain_execution_settles_from_receipt() {
+        use crate::blockchain::{BlockchainInterface, MockBlockchain, RawTransaction};
+        
+        let mut config = crate::config::default();
+        config.treasury.confirmations = 2;
+        let chain = Arc::new(MockBlockchain::new());
+        let database = Database::migrated_sqlite().await;
+        let manager = TreasuryManager::new(
+            &config,
+            BlockchainAdapter::with_mock(&config.blockchain, chain.clone()),
+            database.clone(),
+        )
+        .unwrap();
+        
+        let treasury = "0x00000000000000000000000000000000000000aa";
+        chain.set_balance(treasury, 5_000);
+        
+        let transaction = approved_transaction();
+        manager.save_transaction(&transaction, "0xCreator").await.unwrap();
+        execution::insert_intent(&database, &transaction.id).await.unwrap();
+        
+        let tx_hash = chain
+            .send_transaction(&RawTransaction {
+                from: treasury.to_string(),
+                to: transaction.to.clone(),
+                value: transaction.amount.to_string(),
+                data: None,
+                gas_limit: None,
+                gas_price: None,
+                max_fee_per_gas: None,
+                max_priority_fee_per_gas: None,
+                nonce: None,
+            })
+            .await
+            .unwrap();
+        execution::record_intent_hash(&database, &transaction.id, &tx_hash).await.unwrap();
+        
+        // An intent whose receipt can't be fetched doesn't hold up the others
+        let unknown = approved_transaction();
+        manager.save_transaction(&unknown, "0xCreator").await.unwrap();
+        execution::insert_intent(&database, &unknown.id).await.unwrap();
+        execution::record_intent_hash(&database, &unknown.id, "0xunknown").await.unwrap();
+        
+        // Not mined yet: nothing to settle
+        chain.set_receipt_status(&tx_hash, None);
+        assert_eq!(manager.reconcile_executions().await.unwrap(), 0);
+        let stored = manager.get_transaction(&transaction.id).await.unwrap();
+        assert_eq!(stored.status, TransactionStatus::Approved);
+        assert!(execution::has_intent(&database, &transaction.id).await.unwrap());
+        
+        // Mined, but only one block deep
+        chain.set_block_number(1);
+        chain.set_receipt_status(&tx_hash, Some(true));
+        assert_eq!(manager.reconcile_executions().await.unwrap(), 0);
+        
+        chain.set_block_number(2);
+        assert_eq!(manager.reconcile_executions().await.unwrap(), 1);
+        let stored = manager.get_transaction(&transaction.id).await.unwrap();
+        assert_eq!(stored.status, TransactionStatus::Executed);
+        assert_eq!(stored.transaction_hash.as_deref(), Some(tx_hash.as_str()));
+        assert!(!execution::has_intent(&database, &transaction.id).await.unwrap());
+        assert!(execution::has_intent(&database, &unknown.id).await.unwrap());
+    }
+    
+    #[tokio::test]
+    async fn test_create_transaction_retry_returns_first_transaction() {
+        use crate::blockchain::MockBlockchain;
+        
+        let config = crate::config::default();
+        let database = Database::migrated_sqlite().await;
+        let manager = TreasuryManager::new(
+            &config,
+            BlockchainAdapter::with_mock(&config.blockchain, Arc::new(MockBlockchain::new())),
+            database.clone(),
+        )
+        .unwrap();
+        
+        let request = |amount: TokenAmount| {
+            TransactionBuilder::new()
+                .description("Pay auditors")
+                .to("0x0000000000000000000000000000000000000002")
+                .token("ATOM")
+                .amount(amount)
+                .creator("0x0000000000000000000000000000000000000001")
+                .idempotency_key("retry-1")
+                .build()
+                .unwrap()
+        };
+        
+        // The first create is stored as create_transaction stores it, without
+        // the token checks that need a token backend
+        let first = request(1_000);
+        let actor = first.creator.clone().unwrap();
+        let window = Duration::hours(config.treasury.idempotency_window_hours as i64);
+        idempotency::insert_with_key(&database, &actor, "retry-1", &idempotency::request_hash(&first), window, &first, &actor)
+            .await
+            .unwrap();
+        
+        // A retry returns the stored transaction without validating again
+        assert_eq!(manager.create_transaction(request(1_000)).await.unwrap(), first.id);
+        
+        // Reusing the key for a different request is refused
+        assert!(matches!(
+            manager.create_transaction(request(2_000)).await,
+            Err(DaoError::InvalidParameter(_))
+        ));
+        
+        let count = database
+            .query_one_any("SELECT COUNT(*) AS count FROM treasury_transactions", &[])
+            .await
+            .unwrap()
+            .int("count")
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+    
+    #[tokio::test]
+    async fn test_create_and_approve_require_standing() {
+        use crate::blockchain::MockBlockchain;
+        
+        const SUSPENDED: &str = "0x0000000000000000000000000000000000000003";
+        
+        let config = crate::config::default();
+        let database = Database::migrated_sqlite().await;
+        let manager = TreasuryManager::new(
+            &config,
+            BlockchainAdapter::with_mock(&config.blockchain, Arc::new(MockBlockchain::new())),
+            database.clone(),
+        )
+        .unwrap();
+        
+        let member = Member {
+            id: Uuid::new_v4().to_string(),
+            address: SUSPENDED.to_string(),
+            name: None,
+            role: MemberRole::Council,
+            status: identity::MemberStatus::Suspended,
+            reputation: 0,
+            joined_at: Utc::now(),
+            last_active_at: Utc::now(),
+            role_changed_at: None,
+            metadata: serde_json::Value::Null,
+        };
+        database
+            .execute_any(
+                "INSERT INTO members (id, address, data) VALUES ($1, $2, $3)",
+                &[
+                    member.id.as_str().into(),
+                    SUSPENDED.into(),
+                    serde_json::to_value(&member).unwrap().into(),
+                ],
+            )
+            .await
+            .unwrap();
+        
+        let request = |creator: Option<&str>| {
+            let builder = TransactionBuilder::new()
+                .description("Pay auditors")
+                .to("0x0000000000000000000000000000000000000002")
+                .token("ATOM")
+                .amount(1_000);
+            match creator {
+                Some(creator) => builder.creator(creator),
+                None => builder,
+            }
+            .build()
+            .unwrap()
+        };
+        
+        // A transaction without a creator can't skip the standing check
+        assert!(matches!(
+            manager.create_transaction(request(None)).await,
+            Err(DaoError::InvalidParameter(_))
+        ));
+        assert!(matches!(
+            manager.create_transaction(request(Some(SUSPENDED))).await,
+            Err(DaoError::Unauthorized)
+        ));
+        
+        let pending = request(Some("0x0000000000000000000000000000000000000001"));
+        manager.save_transaction(&pending, SYSTEM_ACTOR).await.unwrap();
+        assert!(matches!(
+            manager.approve_transaction(&pending.id, SUSPENDED).await,
+            Err(DaoError::Unauthorized)
+        ));
+        assert!(manager.get_transaction(&pending.id).await.unwrap().approvers.is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_approval_checks_threshold_before_recording() {
+        use crate::blockchain::MockBlockchain;
+        
+        // The threshold was raised after the transaction was created
+        let mut config = crate::config::default();
+        config.identity.allow_unregistered_voters = true;
+        config.treasury = TreasuryConfig {
+            threshold: 3,
+            ..treasury_config()
+        };
+        let database = Database::migrated_sqlite().await;
+        let manager = TreasuryManager::new(
+            &config,
+            BlockchainAdapter::with_mock(&config.blockchain, Arc::new(MockBlockchain::new())),
+            database,
+        )
+        .unwrap();
+        
+        let pending = TransactionBuilder::new()
+            .description("Pay auditors")
+            .to("0x0000000000000000000000000000000000000002")
+            .token("ATOM")
+            .amount(1_000)
+            .creator("0x0000000000000000000000000000000000000001")
+            .required_approvals(2)
+            .build()
+            .unwrap();
+        manager.save_transaction(&pending, SYSTEM_ACTOR).await.unwrap();
+        
+        assert!(matches!(
+            manager.approve_transaction(&pending.id, "0x0000000000000000000000000000000000000001").await,
+            Err(DaoError::InvalidParameter(_))
+        ));
+        assert!(manager.get_transaction(&pending.id).await.unwrap().approvers.is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_expired_approved_transactions_expire() {
+        use crate::blockchain::MockBlockchain;
+        
+        let config = crate::config::default();
+        let database = Database::migrated_sqlite().await;
+        let manager = TreasuryManager::new(
+            &config,
+            BlockchainAdapter::with_mock(&config.blockchain, Arc::new(MockBlockchain::new())),
+            database.clone(),
+        )
+        .unwrap();
+        
+        let mut expired = approved_transaction();
+        expired.expires_at = Some(Utc::now() - Duration::hours(1));
+        let mut in_flight = approved_transaction();
+        in_flight.expires_at = expired.expires_at;
+        let open = approved_transaction();
+        for transaction in [&expired, &in_flight, &open] {
+            manager.save_transaction(transaction, "0xCreator").await.unwrap();
+        }
+        execution::insert_intent(&database, &in_flight.id).await.unwrap();
+        
+        assert_eq!(manager.process_expired_transactions().await.unwrap(), 1);
+        assert_eq!(manager.get_transaction(&expired.id).await.unwrap().status, TransactionStatus::Expired);
+        assert_eq!(manager.get_transaction(&in_flight.id).await.unwrap().status, TransactionStatus::Approved);
+        assert_eq!(manager.get_transaction(&open.id).await.unwrap().status, TransactionStatus::Approved);
+    }
+    
+    #[tokio::test]
+    async fn test_soft_deleted_transactions_hidden_by_default() {
+        let database = Database::migrated_sqlite().await;
+        
+        let kept = approved_transaction();
+        let deleted = approved_transaction();
+        for transaction in [&kept, &deleted] {
+            database
+                .execute_any(
+                    "INSERT INTO treasury_transactions (id, data, status, created_at, updated_at) VALUES ($1, $2, $3, $4, $5)",
+                    &[
+                        transaction.id.as_str().into(),
+                        serde_json::to_value(transaction).unwrap().into(),
+                        serde_json::to_string(&transaction.status).unwrap().into(),
+                        transaction.created_at.into(),
+                        transaction.updated_at.into(),
+                    ],
+                )
+                .await
+                .unwrap();
+        }
+        
+        TreasuryManager::soft_delete(&database, &deleted.id, "0xAdmin").await.unwrap();
+        
+        assert!(TreasuryManager::load_transaction(&database, &deleted.id).await.is_err());
+        assert!(TreasuryManager::load_transaction(&database, &kept.id).await.is_ok());
+        let listed = TreasuryManager::list_transactions(&database, Some(TransactionStatus::Approved), false)
+            .await
+            .unwrap();
+        assert_eq!(listed.iter().map(|transaction| &transaction.id).collect::<Vec<_>>(), [&kept.id]);
+        assert_eq!(
+            TreasuryManager::list_transactions(&database, Some(TransactionStatus::Approved), true)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+        
+        let row = database
+            .query_one_any("SELECT updated_by FROM treasury_transactions WHERE id = $1", &[deleted.id.as_str().into()])
+            .await
+            .unwrap();
+        assert_eq!(row.text("updated_by").unwrap(), "0xAdmin");
+        assert!(TreasuryManager::soft_delete(&database, &deleted.id, "0xAdmin").await.is_err());
+    }
+    
+    fn treasury_config() -> TreasuryConfig {
+        TreasuryConfig {
+            treasury_address: "0x00000000000000000000000000000000000000aa".to_string(),
+            signers: TreasurySigners::Addresses(vec![
+                "0x0000000000000000000000000000000000000001".to_string(),
+                "0x0000000000000000000000000000000000000002".to_string(),
+                "0x0000000000000000000000000000000000000003".to_string(),
+            ]),
+            threshold: 2,
+            ..Default::default()
+        }
+    }
+    
+    #[test]
+    fn test_two_of_three_signers_approve() {
+        let config = treasury_config();
+        let signers = configured_signers(&config, |_| true).unwrap();
+        let mut transaction = TransactionBuilder::new()
+            .description("Pay auditors")
+            .to("0x0000000000000000000000000000000000000002")
+            .token("ATOM")
+            .amount(1_000)
+            .required_approvals(config.threshold)
+            .build()
+            .unwrap();
+        
+        assert!(matches!(
+            record_approval(&mut transaction, "0x0000000000000000000000000000000000000009", &signers, Utc::now()),
+            Err(DaoError::Unauthorized)
+        ));
+        
+        record_approval(&mut transaction, &config.signers.addresses()[0], &signers, Utc::now()).unwrap();
+        assert_eq!(transaction.status, TransactionStatus::Pending);
+        assert!(record_approval(&mut transaction, &config.signers.addresses()[0], &signers, Utc::now()).is_err());
+        
+        record_approval(&mut transaction, &config.signers.addresses()[2], &signers, Utc::now()).unwrap();
+        assert_eq!(transaction.status, TransactionStatus::Approved);
+        assert_eq!(transaction.current_approvals, 2);
+        
+        // Approved transactions take no further approvals
+        assert!(record_approval(&mut transaction, &config.signers.addresses()[1], &signers, Utc::now()).is_err());
+    }
+    
+    #[test]
+    fn test_required_approvals_respect_threshold() {
+        let config = treasury_config();
+        let signers = config.signers.len();
+        
+        // The builder's default of one approval is refused under 2-of-3
+        let transaction = TransactionBuilder::new()
+            .description("Pay auditors")
+            .to("0x0000000000000000000000000000000000000002")
+            .token("ATOM")
+            .amount(1_000)
+            .build()
+            .unwrap();
+        assert_eq!(transaction.required_approvals, 1);
+        assert!(matches!(
+            check_required_approvals(transaction.required_approvals, config.threshold, signers),
+            Err(DaoError::InvalidParameter(_))
+        ));
+        
+        assert!(check_required_approvals(2, config.threshold, signers).is_ok());
+        assert!(check_required_approvals(3, config.threshold, signers).is_ok());
+        assert!(check_required_approvals(4, config.threshold, signers).is_err());
+    }
+    
+    #[test]
+    fn test_transaction_created_in_the_past_expires() {
+        let config = treasury_config();
+        let signers = configured_signers(&config, |_| true).unwrap();
+        let created_at = Utc::now() - Duration::days(10);
+        let mut transaction = TransactionBuilder::new()
+            .description("Stale grant")
+            .to("0x0000000000000000000000000000000000000002")
+            .token("ATOM")
+            .amount(1_000)
+            .required_approvals(2)
+            .expires_at(created_at + Duration::hours(168))
+            .build()
+            .unwrap();
+        transaction.created_at = created_at;
+        
+        assert!(transaction.is_expired(Utc::now()));
+        assert!(!transaction.is_expired(created_at + Duration::days(6)));
+        
+        assert!(matches!(
+            record_approval(&mut transaction, &config.signers.addresses()[0], &signers, Utc::now()),
+            Err(DaoError::TreasuryError(_))
+        ));
+        assert!(transaction.approvers.is_empty());
+        
+        // An approved transaction past its expiry can't be executed either
+        transaction.status = TransactionStatus::Approved;
+        assert!(check_not_expired(&transaction, Utc::now()).is_err());
+        
+        // Transactions without an expiry never expire
+        transaction.expires_at = None;
+        assert!(check_not_expired(&transaction, Utc::now() + Duration::days(365)).is_ok());
+    }
+    
+    #[test]
+    fn test_missing_or_invalid_signers_rejected() {
+        let config = treasury_config();
+        assert!(configured_treasury_address(&config, |_| true)
+            .unwrap()
+            .as_str()
+            .eq_ignore_ascii_case(&config.treasury_address));
+        assert!(matches!(
+            configured_signers(&config, |address| !address.ends_with('3')),
+            Err(DaoError::InvalidParameter(_))
+        ));
+        
+        let unconfigured = TreasuryConfig::default();
+        assert!(matches!(configured_signers(&unconfigured, |_| true), Err(DaoError::TreasuryError(_))));
+        assert!(matches!(
+            configured_treasury_address(&unconfigured, |_| true),
+            Err(DaoError::TreasuryError(_))
+        ));
+    }
+    
+    #[test]
+    fn test_batch_with_short_second_line_is_rejected_whole() {
+        let transaction = TransactionBuilder::new()
+            .description("March payroll")
+            .add_transfer("0x0000000000000000000000000000000000000002", "ATOM", 600)
+            .add_transfer("0x0000000000000000000000000000000000000003", "USDC", 2_000)
+            .add_transfer("0x0000000000000000000000000000000000000004", "ATOM", 300)
+            .build()
+            .unwrap();
+        
+        let lines = transaction.transfers();
+        assert_eq!(lines.len(), 3);
+        assert_eq!((transaction.token.as_str(), transaction.amount), ("ATOM", 600));
+        
+        let totals = totals_by_token(&lines);
+        assert_eq!(totals["ATOM"], 900);
+        assert_eq!(totals["USDC"], 2_000);
+        
+        // Only the USDC line is short, but no line of the batch may proceed
+        let balances = BTreeMap::from([("ATOM".to_string(), 1_000), ("USDC".to_string(), 1_500)]);
+        let error = check_batch_balances(&lines, &balances).unwrap_err().to_string();
+        assert!(error.contains("line 2"), "{}", error);
+        
+        // ATOM lines are checked against their combined total
+        let balances = BTreeMap::from([("ATOM".to_string(), 800), ("USDC".to_string(), 5_000)]);
+        let error = check_batch_balances(&lines, &balances).unwrap_err().to_string();
+        assert!(error.contains("line 3"), "{}", error);
+        
+        let balances = BTreeMap::from([("ATOM".to_string(), 900), ("USDC".to_string(), 2_000)]);
+        assert!(check_batch_balances(&lines, &balances).is_ok());
+    }
+    
+    #[test]
+    fn test_transactions_stored_before_batches_have_one_line() {
+        let mut transaction = approved_transaction();
+        transaction.lines.clear();
+        
+        let data = serde_json::to_value(&transaction).unwrap();
+        let mut legacy = data.as_object().unwrap().clone();
+        legacy.remove("lines");
+        let restored: Transaction = serde_json::from_value(serde_json::Value::Object(legacy)).unwrap();
+        
+        assert_eq!(
+            restored.transfers(),
+            vec![TransferLine {
+                to: transaction.to.clone(),
+                token: "ATOM".to_string(),
+                amount: 1_000,
+            }]
+        );
+    }
+    
+    #[test]
+    fn test_builder_rejects_swapped_address_and_symbol() {
+        let result = TransactionBuilder::new()
+            .description("Pay auditors")
+            .to("ATOM")
+            .token("0x0000000000000000000000000000000000000002")
+            .amount(1_000)
+            .build();
+        
+        assert!(matches!(result, Err(DaoError::InvalidParameter(_))));
+    }
+}
@@ -0,0 +1,256 @@
+//! Treasury rebalancing
+//!
+//! Compares the treasury's holdings against target allocations and plans the
+//! swaps needed to bring them back in line. Planning is pure; pricing comes
+//! from a [`PriceOracle`] supplied by the caller.
+
+use crate::core::{DaoError, Result, Symbol};
+use crate::proposals::SwapAction;
+use crate::token::TokenAmount;
+use async_trait::async_trait;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// Source of token prices in a common quote currency
+#[async_trait]
+pub trait PriceOracle: Send + Sync {
+    /// Price of one whole token
+    async fn price(&self, symbol: &Symbol) -> Result<Decimal>;
+}
+
+/// Target share of the treasury's value for one token
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AllocationTarget {
+    /// Token symbol
+    pub symbol: Symbol,
+    /// Share of total value, in percent
+    pub percent: u8,
+}
+
+/// A priced treasury position
+#[derive(Debug, Clone)]
+pub(crate) struct Holding {
+    pub symbol: Symbol,
+    pub balance: TokenAmount,
+    pub decimals: u8,
+    pub price: Decimal,
+}
+
+impl Holding {
+    /// Value of the position in the quote currency
+    fn value(&self) -> Decimal {
+        Decimal::from(self.balance) / unit(self.decimals) * self.price
+    }
+}
+
+/// Check that targets name distinct tokens and add up to 100%
+pub(crate) fn validate_targets(targets: &[AllocationTarget]) -> Result<()> {
+    let total: u32 = targets.iter().map(|t| t.percent as u32).sum();
+    if total != 100 {
+        return Err(DaoError::InvalidParameter(format!(
+            "Allocation targets must sum to 100%, got {}%",
+            total
+        )));
+    }
+
+    for (i, target) in targets.iter().enumerate() {
+        if targets[..i].iter().any(|t| t.symbol == target.symbol) {
+            return Err(DaoError::InvalidParameter(format!(
+                "Duplicate allocation target for {}",
+                target.symbol
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Plan the swaps that restore the target allocations
+///
+/// Returns `None` when every token is within `tolerance_percent` of its
+/// target. Tokens held but not targeted have a target of 0%. Swap amounts
+/// are in base units of the token being sold.
+pub(crate) fn plan_rebalance(
+    holdings: &[Holding],
+    targets: &[AllocationTarget],
+    tolerance_percent: u8,
+) -> Result<Option<Vec<SwapAction>>> {
+    validate_targets(targets)?;
+
+    for target in targets {
+        if !holdings.iter().any(|h| h.symbol == target.symbol) {
+            return Err(DaoError::InvalidParameter(format!(
+                "No price or balance for allocation target {}",
+                target.symbol
+            )));
+        }
+    }
+
+    if let Some(holding) = holdings.iter().find(|h| h.price <= Decimal::ZERO) {
+        return Err(DaoError::InvalidParameter(format!(
+            "Invalid price for {}: {}",
+            holding.symbol, holding.price
+        )));
+    }
+
+    let total: Decimal = holdings.iter().map(Holding::value).sum();
+    if total.is_zero() {
+        return Ok(None);
+    }
+
+    let tolerance = Decimal::from(tolerance_percent);
+    let hundred = Decimal::from(100);
+
+    // Positive drift is value to sell, negative is value to buy
+    let mut in_band = true;
+    let mut sellers = Vec::new();
+    let mut buyers = Vec::new();
+
+    for holding in holdings {
+        let percent = targets
+            .iter()
+            .find(|t| t.symbol == holding.symbol)
+            .map_or(0, |t| t.percent);
+
+        let value = holding.value();
+        let drift = value - total * Decimal::from(percent) / hundred;
+
+        if (value / total * hundred - Decimal::from(percent)).abs() > tolerance {
+            in_band = false;
+        }
+
+        if drift > Decimal::ZERO {
+            sellers.push((holding, drift));
+        } else if drift < Decimal::ZERO {
+            buyers.push((holding, -drift));
+        }
+    }
+
+    if in_band {
+        return Ok(None);
+    }
+
+    // Pair the largest excesses with the largest deficits
+    sellers.sort_by(|a, b| b.1.cmp(&a.1));
+    buyers.sort_by(|a, b| b.1.cmp(&a.1));
+
+    let mut swaps = Vec::new();
+    let mut buyers = buyers.into_iter().peekable();
+
+    for (seller, mut excess) in sellers {
+        while excess > Decimal::ZERO {
+            let Some((buyer, deficit)) = buyers.peek_mut() else {
+                break;
+            };
+
+            let value = excess.min(*deficit);
+            excess -= value;
+            *deficit -= value;
+
+            let amount = (value / seller.price * unit(seller.decimals))
+                .trunc()
+                .to_u64()
+                .unwrap_or(0);
+
+            if amount > 0 {
+                swaps.push(SwapAction {
+                    from_token: seller.symbol.to_string(),
+                    to_token: buyer.symbol.to_string(),
+                    amount,
+                });
+            }
+
+            if deficit.is_zero() {
+                buyers.next();
+            }
+        }
+    }
+
+    Ok(Some(swaps).filter(|swaps| !swaps.is_empty()))
+}
+
+/// Base units per whole token
+pub(super) fn unit(decimals: u8) -> Decimal {
+    Decimal::from(10u64.pow(decimals as u32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn holding(symbol: &str, whole: u64, decimals: u8, price: Decimal) -> Holding {
+        Holding {
+            symbol: Symbol::parse(symbol).unwrap(),
+            balance: whole * 10u64.pow(decimals as u32),
+            decimals,
+            price,
+        }
+    }
+
+    fn target(symbol: &str, percent: u8) -> AllocationTarget {
+        AllocationTarget {
+            symbol: Symbol::parse(symbol).unwrap(),
+            percent,
+        }
+    }
+
+    #[test]
+    fn test_out_of_band_allocation_yields_sized_swaps() {
+        // 8,000 in ATOM and 2,000 in USDC against a 50/50 target
+        let holdings = [
+            holding("ATOM", 400, 9, dec!(20)),
+            holding("USDC", 2_000, 6, dec!(1)),
+        ];
+        let targets = [target("ATOM", 50), target("USDC", 50)];
+
+        let swaps = plan_rebalance(&holdings, &targets, 5).unwrap().unwrap();
+
+        // Sell 3,000 worth of ATOM, i.e. 150 ATOM in base units
+        assert_eq!(
+            swaps,
+            vec![SwapAction {
+                from_token: "ATOM".to_string(),
+                to_token: "USDC".to_string(),
+                amount: 150 * 10u64.pow(9),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_untargeted_holdings_are_sold() {
+        let holdings = [
+            holding("ATOM", 50, 9, dec!(20)),
+            holding("USDC", 1_000, 6, dec!(1)),
+            holding("DAI", 1_000, 6, dec!(1)),
+        ];
+        let targets = [target("ATOM", 50), target("USDC", 50)];
+
+        let swaps = plan_rebalance(&holdings, &targets, 5).unwrap().unwrap();
+
+        let sold: u64 = swaps.iter().filter(|s| s.from_token == "DAI").map(|s| s.amount).sum();
+        assert_eq!(sold, 1_000 * 10u64.pow(6));
+        assert!(swaps.iter().all(|s| s.to_token != "DAI"));
+    }
+
+    #[test]
+    fn test_within_band_yields_none() {
+        let holdings = [
+            holding("ATOM", 260, 9, dec!(20)),
+            holding("USDC", 4_800, 6, dec!(1)),
+        ];
+        let targets = [target("ATOM", 50), target("USDC", 50)];
+
+        assert_eq!(plan_rebalance(&holdings, &targets, 5).unwrap(), None);
+    }
+
+    #[test]
+    fn test_invalid_targets_rejected() {
+        let holdings = [holding("ATOM", 1, 9, dec!(20))];
+
+        assert!(plan_rebalance(&holdings, &[target("ATOM", 90)], 5).is_err());
+        assert!(plan_rebalance(&holdings, &[target("ATOM", 50), target("ATOM", 50)], 5).is_err());
+        assert!(plan_rebalance(&holdings, &[target("ATOM", 50), target("USDC", 50)], 5).is_err());
+    }
+}
@@ -0,0 +1,181 @@
+//! USD valuation of treasury balances
+//!
+//! Valuation depends on an external [`PriceOracle`], so an oracle outage
+//! degrades it instead of failing balance requests: the last known price is
+//! served, marked stale, for up to `oracle.max_stale_seconds`, and after that
+//! the USD value is reported as unknown.
+
+use super::rebalance::{unit, PriceOracle};
+use crate::core::Symbol;
+use crate::token::TokenAmount;
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Whether a USD value came from a live oracle price
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OracleStatus {
+    /// Priced with a fresh oracle price
+    Available,
+    /// The oracle failed; priced with a cached price within the staleness bound
+    Stale,
+    /// No usable price; the USD value is unknown
+    Unavailable,
+}
+
+/// A treasury balance with its USD value
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValuedBalance {
+    /// Token symbol
+    pub symbol: String,
+    /// Balance in base units
+    pub balance: TokenAmount,
+    /// Value in USD, if a usable price was found
+    pub usd_value: Option<Decimal>,
+    /// Source of the price
+    pub oracle_status: OracleStatus,
+    /// When the price was fetched from the oracle
+    pub priced_at: Option<DateTime<Utc>>,
+}
+
+/// Last known prices, used while the oracle is unavailable
+#[derive(Debug, Default)]
+pub(crate) struct PriceCache {
+    prices: Mutex<HashMap<Symbol, (Decimal, DateTime<Utc>)>>,
+}
+
+impl PriceCache {
+    /// Get a token's price, falling back to a cached price within `max_stale`
+    ///
+    /// Returns the price, when it was fetched and its status. Oracle errors
+    /// are not returned; they degrade the status instead.
+    pub async fn price(
+        &self,
+        oracle: &dyn PriceOracle,
+        symbol: &Symbol,
+        max_stale: Duration,
+        now: DateTime<Utc>,
+    ) -> (Option<(Decimal, DateTime<Utc>)>, OracleStatus) {
+        match oracle.price(symbol).await {
+            Ok(price) => {
+                self.prices.lock().unwrap().insert(symbol.clone(), (price, now));
+                (Some((price, now)), OracleStatus::Available)
+            }
+            Err(_) => match self.prices.lock().unwrap().get(symbol) {
+                Some(&(price, fetched_at)) if now - fetched_at <= max_stale => {
+                    (Some((price, fetched_at)), OracleStatus::Stale)
+                }
+                _ => (None, OracleStatus::Unavailable),
+            },
+        }
+    }
+}
+
+/// Value balances in USD, degrading to stale or unknown values on oracle failure
+pub(crate) async fn value_balances(
+    balances: Vec<(Symbol, TokenAmount, u8)>,
+    oracle: Option<&dyn PriceOracle>,
+    cache: &PriceCache,
+    max_stale: Duration,
+    now: DateTime<Utc>,
+) -> Vec<ValuedBalance> {
+    let mut valued = Vec::with_capacity(balances.len());
+
+    for (symbol, balance, decimals) in balances {
+        let (price, oracle_status) = match oracle {
+            Some(oracle) => cache.price(oracle, &symbol, max_stale, now).await,
+            None => (None, OracleStatus::Unavailable),
+        };
+
+        valued.push(ValuedBalance {
+            symbol: symbol.to_string(),
+            balance,
+            usd_value: price.map(|(price, _)| Decimal::from(balance) / unit(decimals) * price),
+            oracle_status,
+            priced_at: price.map(|(_, fetched_at)| fetched_at),
+        });
+    }
+
+    valued
+}
+
+/// Worst status across valued balances; `Available` when there are none
+pub fn overall_status(balances: &[ValuedBalance]) -> OracleStatus {
+    balances
+        .iter()
+        .map(|balance| balance.oracle_status)
+        .max()
+        .unwrap_or(OracleStatus::Available)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{DaoError, Result};
+    use async_trait::async_trait;
+    use rust_decimal_macros::dec;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    /// Oracle that can be switched off to simulate an outage
+    struct FlakyOracle {
+        down: AtomicBool,
+    }
+
+    #[async_trait]
+    impl PriceOracle for FlakyOracle {
+        async fn price(&self, symbol: &Symbol) -> Result<Decimal> {
+            if self.down.load(Ordering::SeqCst) {
+                return Err(DaoError::ExternalServiceError("oracle timed out".to_string()));
+            }
+            Ok(if symbol.as_str() == "USDC" { dec!(1) } else { dec!(2.5) })
+        }
+    }
+
+    fn balances() -> Vec<(Symbol, TokenAmount, u8)> {
+        vec![
+            (Symbol::parse("ATOM").unwrap(), 1_000_000_000_000, 9),
+            (Symbol::parse("USDC").unwrap(), 500_000_000, 6),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_oracle_failure_degrades_to_stale_then_null_values() {
+        let oracle = FlakyOracle {
+            down: AtomicBool::new(false),
+        };
+        let cache = PriceCache::default();
+        let max_stale = Duration::seconds(3600);
+        let now = Utc::now();
+
+        let live = value_balances(balances(), Some(&oracle as &dyn PriceOracle), &cache, max_stale, now).await;
+        assert_eq!(live[0].usd_value, Some(dec!(2500)));
+        assert_eq!(live[1].usd_value, Some(dec!(500)));
+        assert_eq!(overall_status(&live), OracleStatus::Available);
+
+        // The oracle goes down: cached prices are served, marked stale
+        oracle.down.store(true, Ordering::SeqCst);
+        let later = now + Duration::minutes(30);
+        let stale = value_balances(balances(), Some(&oracle as &dyn PriceOracle), &cache, max_stale, later).await;
+        assert_eq!(stale[0].usd_value, Some(dec!(2500)));
+        assert_eq!(stale[0].oracle_status, OracleStatus::Stale);
+        assert_eq!(stale[0].priced_at, Some(now));
+        assert_eq!(overall_status(&stale), OracleStatus::Stale);
+
+        // Past the staleness bound, balances still return with null USD values
+        let much_later = now + Duration::hours(2);
+        let unknown = value_balances(balances(), Some(&oracle as &dyn PriceOracle), &cache, max_stale, much_later).await;
+        assert_eq!(unknown.len(), 2);
+        assert_eq!(unknown[0].balance, 1_000_000_000_000);
+        assert!(unknown.iter().all(|balance| balance.usd_value.is_none()));
+        assert_eq!(overall_status(&unknown), OracleStatus::Unavailable);
+    }
+
+    #[tokio::test]
+    async fn test_no_oracle_reports_unavailable() {
+        let valued = value_balances(balances(), None, &PriceCache::default(), Duration::zero(), Utc::now()).await;
+        assert!(valued.iter().all(|balance| balance.oracle_status == OracleStatus::Unavailable));
+    }
+}
@@ -0,0 +1,151 @@
+//! Monetary amount helpers for the AtomSi DAO
+//!
+//! Amounts cross the API as decimal strings in base units, so values above
+//! 2^53 survive JSON parsers that read numbers as doubles.
+
+/// Largest amount the API accepts, in base units
+pub type ApiAmount = u128;
+
+/// Parse a human-entered amount in whole tokens into base units
+///
+/// The value is validated against the token's decimals: more fractional
+/// digits than the token supports are rejected rather than rounded.
+///
+/// # Examples
+///
+/// ```
+/// use atomsi_dao::utils::amount::parse_amount;
+///
+/// assert_eq!(parse_amount("1.5", 6), Ok(1_500_000));
+/// assert_eq!(parse_amount("2", 6), Ok(2_000_000));
+/// assert!(parse_amount("1.0000001", 6).is_err());
+/// ```
+pub fn parse_amount(value: &str, decimals: u8) -> Result<ApiAmount, String> {
+    let (whole, fractional) = match value.split_once('.') {
+        Some((whole, fractional)) => (whole, Some(fractional)),
+        None => (value, None),
+    };
+
+    let is_digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    if !is_digits(whole) || fractional.map_or(false, |f| !is_digits(f)) {
+        return Err(format!("Invalid amount '{}': expected a decimal string", value));
+    }
+
+    let overflow = || format!("Amount '{}' is too large", value);
+    let whole: ApiAmount = whole.parse().map_err(|_| overflow())?;
+    let fractional = fractional.unwrap_or("");
+
+    if fractional.len() > decimals as usize {
+        return Err(format!(
+            "Amount '{}' has more than {} decimal places",
+            value, decimals
+        ));
+    }
+
+    let factor = 10u128.checked_pow(decimals as u32).ok_or_else(overflow)?;
+    let fractional_factor = 10u128.pow((decimals as usize - fractional.len()) as u32);
+    let fractional_value: ApiAmount = if fractional.is_empty() {
+        0
+    } else {
+        fractional.parse().map_err(|_| overflow())?
+    };
+
+    // The fractional part is below `factor`, so only the sum can overflow
+    whole
+        .checked_mul(factor)
+        .and_then(|amount| amount.checked_add(fractional_value * fractional_factor))
+        .ok_or_else(overflow)
+}
+
+/// Serde helpers for amount fields
+///
+/// Use with `#[serde(with = "crate::utils::amount::base_units")]`, or the
+/// `option` submodule for optional fields. JSON numbers are rejected on input.
+pub mod base_units {
+    use super::ApiAmount;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    /// Serialize an amount as a decimal string
+    pub fn serialize<S: Serializer>(amount: &ApiAmount, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&amount.to_string())
+    }
+
+    /// Deserialize an amount from a decimal string
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<ApiAmount, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        super::parse_amount(&value, 0).map_err(serde::de::Error::custom)
+    }
+
+    /// Serde helpers for optional amount fields
+    pub mod option {
+        use super::super::ApiAmount;
+        use serde::{Deserialize, Deserializer, Serializer};
+
+        /// Serialize an optional amount as a decimal string or null
+        pub fn serialize<S: Serializer>(amount: &Option<ApiAmount>, serializer: S) -> Result<S::Ok, S::Error> {
+            match amount {
+                Some(amount) => super::serialize(amount, serializer),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        /// Deserialize an optional amount from a decimal string or null
+        pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<ApiAmount>, D::Error> {
+            match Option::<String>::deserialize(deserializer)? {
+                Some(value) => super::super::parse_amount(&value, 0)
+                    .map(Some)
+                    .map_err(serde::de::Error::custom),
+                None => Ok(None),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Payload {
+        #[serde(with = "base_units")]
+        amount: ApiAmount,
+        #[serde(default, with = "base_units::option")]
+        limit: Option<ApiAmount>,
+    }
+
+    #[test]
+    fn test_round_trip_above_2_pow_53() {
+        let amount = (1u128 << 53) + 1;
+        let payload = Payload {
+            amount,
+            limit: Some(u128::MAX),
+        };
+
+        let json = serde_json::to_string(&payload).unwrap();
+        assert_eq!(
+            json,
+            format!("{{\"amount\":\"9007199254740993\",\"limit\":\"{}\"}}", u128::MAX)
+        );
+        assert_eq!(serde_json::from_str::<Payload>(&json).unwrap(), payload);
+    }
+
+    #[test]
+    fn test_json_numbers_rejected() {
+        assert!(serde_json::from_str::<Payload>("{\"amount\":9007199254740993}").is_err());
+        assert!(serde_json::from_str::<Payload>("{\"amount\":\"-1\"}").is_err());
+    }
+
+    #[test]
+    fn test_parse_amount_against_decimals() {
+        assert_eq!(parse_amount("12", 0), Ok(12));
+        assert_eq!(parse_amount("12", 6), Ok(12_000_000));
+        assert_eq!(parse_amount("0.000001", 6), Ok(1));
+        assert_eq!(parse_amount("2.5", 18), Ok(2_500_000_000_000_000_000));
+        assert!(parse_amount("2.5", 0).is_err());
+        assert!(parse_amount("1.", 6).is_err());
+        assert!(parse_amount("1e6", 6).is_err());
+        assert!(parse_amount("", 6).is_err());
+        assert!(parse_amount(&format!("{}0", u128::MAX), 0).is_err());
+    }
+}
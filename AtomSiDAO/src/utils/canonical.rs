@@ -0,0 +1,177 @@
+//! Canonical JSON serialization
+//!
+//! This module produces a deterministic JSON encoding (following RFC 8785,
+//! the JSON Canonicalization Scheme) so that structured data hashes and
+//! signs identically regardless of field insertion order or serializer
+//! version.
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// Serialize a JSON value in canonical form
+///
+/// Object keys are sorted by their UTF-16 code units, no insignificant
+/// whitespace is emitted, and floating point numbers use the shortest
+/// ECMAScript representation. Integers are written exactly.
+///
+/// # Examples
+///
+/// ```
+/// use atomsi_dao::utils::canonical_json;
+/// use serde_json::json;
+///
+/// let value = json!({ "b": 2.0, "a": [true, null] });
+/// assert_eq!(canonical_json(&value), r#"{"a":[true,null],"b":2}"#);
+/// ```
+pub fn canonical_json(value: &Value) -> String {
+    let mut out = String::new();
+    write_value(&mut out, value);
+    out
+}
+
+/// Serialize any serializable value in canonical form
+pub fn to_canonical_json<T: Serialize>(value: &T) -> Result<String, String> {
+    let value = serde_json::to_value(value).map_err(|e| e.to_string())?;
+    Ok(canonical_json(&value))
+}
+
+/// Append the canonical form of a value to the output
+fn write_value(out: &mut String, value: &Value) {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => {
+            if n.is_i64() || n.is_u64() {
+                out.push_str(&n.to_string());
+            } else if let Some(f) = n.as_f64() {
+                out.push_str(&format_number(f));
+            }
+        }
+        Value::String(s) => write_string(out, s),
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_value(out, item);
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<(&String, &Value)> = map.iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+            
+            out.push('{');
+            for (i, (key, item)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_string(out, key);
+                out.push(':');
+                write_value(out, item);
+            }
+            out.push('}');
+        }
+    }
+}
+
+/// Append a JSON string literal using the minimal escaping RFC 8785 requires
+fn write_string(out: &mut String, s: &str) {
+    // serde_json escapes only quotes, backslashes and control characters,
+    // using lowercase \u00xx for controls without a short form
+    out.push_str(&serde_json::to_string(s).expect("string serialization cannot fail"));
+}
+
+/// Format a finite float the way ECMAScript `Number.prototype.toString` does
+fn format_number(value: f64) -> String {
+    if value == 0.0 {
+        return "0".to_string();
+    }
+    
+    // Shortest round-trip digits, e.g. "1.5e-7" or "123.45"
+    let repr = format!("{:e}", value.abs());
+    let (mantissa, exponent) = repr.split_once('e').unwrap_or((&repr, "0"));
+    let exponent: i32 = exponent.parse().unwrap_or(0);
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    
+    // value = 0.<digits> * 10^point
+    let k = digits.len() as i32;
+    let point = exponent + 1;
+    
+    let body = if k <= point && point <= 21 {
+        format!("{}{}", digits, "0".repeat((point - k) as usize))
+    } else if 0 < point && point <= 21 {
+        format!("{}.{}", &digits[..point as usize], &digits[point as usize..])
+    } else if -6 < point && point <= 0 {
+        format!("0.{}{}", "0".repeat((-point) as usize), digits)
+    } else {
+        let sign = if point - 1 < 0 { '-' } else { '+' };
+        let exp = (point - 1).abs();
+        if k == 1 {
+            format!("{}e{}{}", digits, sign, exp)
+        } else {
+            format!("{}.{}e{}{}", &digits[..1], &digits[1..], sign, exp)
+        }
+    };
+    
+    if value < 0.0 {
+        format!("-{}", body)
+    } else {
+        body
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Map};
+    use sha2::{Digest, Sha256};
+    
+    #[test]
+    fn test_insertion_order_does_not_matter() {
+        let mut first = Map::new();
+        first.insert("voter".to_string(), json!("0xabc"));
+        first.insert("proposal_id".to_string(), json!("p-1"));
+        first.insert("weight".to_string(), json!(42));
+        first.insert("meta".to_string(), json!({ "z": 1, "a": [1.0, 2.5] }));
+        
+        let mut second = Map::new();
+        second.insert("meta".to_string(), json!({ "a": [1, 2.5], "z": 1 }));
+        second.insert("weight".to_string(), json!(42));
+        second.insert("proposal_id".to_string(), json!("p-1"));
+        second.insert("voter".to_string(), json!("0xabc"));
+        
+        let first = canonical_json(&Value::Object(first));
+        let second = canonical_json(&Value::Object(second));
+        
+        assert_eq!(first, second);
+        assert_eq!(
+            first,
+            r#"{"meta":{"a":[1,2.5],"z":1},"proposal_id":"p-1","voter":"0xabc","weight":42}"#
+        );
+        assert_eq!(Sha256::digest(first.as_bytes()), Sha256::digest(second.as_bytes()));
+    }
+    
+    #[test]
+    fn test_number_formatting() {
+        assert_eq!(format_number(1.0), "1");
+        assert_eq!(format_number(-2.5), "-2.5");
+        assert_eq!(format_number(0.000001), "0.000001");
+        assert_eq!(format_number(1e-7), "1e-7");
+        assert_eq!(format_number(1e21), "1e+21");
+        assert_eq!(format_number(123456789012345680000.0), "123456789012345680000");
+        assert_eq!(format_number(4.5e-10), "4.5e-10");
+    }
+    
+    #[test]
+    fn test_string_escaping_and_key_order() {
+        let value = json!({ "\u{e000}": 1, "\u{1f600}": 2, "line\n": "tab\t\"q\"" });
+        
+        // U+1F600 sorts before U+E000 as a UTF-16 surrogate pair
+        assert_eq!(
+            canonical_json(&value),
+            "{\"line\\n\":\"tab\\t\\\"q\\\"\",\"\u{1f600}\":2,\"\u{e000}\":1}"
+        );
+    }
+}
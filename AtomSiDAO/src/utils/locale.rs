@@ -0,0 +1,163 @@
+//! Locale utilities for the AtomSi DAO
+//!
+//! This module provides locale-aware formatting for human-readable amounts.
+
+/// Number formatting conventions for a language
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Locale {
+    language: &'static str,
+    group_separator: &'static str,
+    decimal_separator: char,
+}
+
+/// Supported locales, keyed by primary language subtag
+const LOCALES: &[Locale] = &[
+    Locale::EN,
+    Locale::DE,
+    Locale { language: "es", group_separator: ".", decimal_separator: ',' },
+    Locale { language: "it", group_separator: ".", decimal_separator: ',' },
+    Locale { language: "nl", group_separator: ".", decimal_separator: ',' },
+    Locale { language: "pt", group_separator: ".", decimal_separator: ',' },
+    Locale::FR,
+];
+
+impl Locale {
+    /// English: `1,234.5`
+    pub const EN: Locale = Locale { language: "en", group_separator: ",", decimal_separator: '.' };
+
+    /// German: `1.234,5`
+    pub const DE: Locale = Locale { language: "de", group_separator: ".", decimal_separator: ',' };
+
+    /// French: `1 234,5` (narrow no-break space)
+    pub const FR: Locale = Locale { language: "fr", group_separator: "\u{202f}", decimal_separator: ',' };
+
+    /// Parse a language tag such as `de` or `de-AT`
+    ///
+    /// Returns `None` for unsupported languages.
+    pub fn parse(tag: &str) -> Option<Locale> {
+        let language = tag.trim().split(['-', '_']).next()?.to_ascii_lowercase();
+        LOCALES.iter().copied().find(|locale| locale.language == language)
+    }
+
+    /// Pick the preferred supported locale from an `Accept-Language` header
+    ///
+    /// Falls back to English when no listed language is supported.
+    pub fn from_accept_language(header: &str) -> Locale {
+        let mut ranges: Vec<(&str, f32)> = header
+            .split(',')
+            .filter_map(|range| {
+                let mut parts = range.split(';');
+                let tag = parts.next()?.trim();
+                let quality = parts
+                    .find_map(|param| param.trim().strip_prefix("q="))
+                    .map(|q| q.parse().unwrap_or(0.0))
+                    .unwrap_or(1.0);
+                (!tag.is_empty() && quality > 0.0).then_some((tag, quality))
+            })
+            .collect();
+
+        // Stable sort keeps header order for equal weights
+        ranges.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+
+        ranges
+            .into_iter()
+            .find_map(|(tag, _)| Locale::parse(tag))
+            .unwrap_or_default()
+    }
+
+    /// Primary language subtag
+    pub fn language(&self) -> &'static str {
+        self.language
+    }
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Locale::EN
+    }
+}
+
+/// Format an amount with token symbol for the given locale
+///
+/// Whole units are grouped in thousands and trailing fractional zeros are
+/// dropped.
+///
+/// # Examples
+///
+/// ```
+/// use atomsi_dao::utils::locale::{format_token_amount_localized, Locale};
+///
+/// let amount = 1_234_500_000_000_000_000_000;
+/// assert_eq!(format_token_amount_localized(amount, "ATOM", 18, Locale::EN), "1,234.5 ATOM");
+/// assert_eq!(format_token_amount_localized(amount, "ATOM", 18, Locale::DE), "1.234,5 ATOM");
+/// ```
+pub fn format_token_amount_localized(amount: u128, symbol: &str, decimals: u8, locale: Locale) -> String {
+    let factor = 10u128.pow(decimals as u32);
+    let whole = (amount / factor).to_string();
+    let fractional = amount % factor;
+
+    let grouped = group_thousands(&whole, locale.group_separator);
+
+    let fractional_str = format!("{:0width$}", fractional, width = decimals as usize);
+    let fractional_str = fractional_str.trim_end_matches('0');
+
+    if fractional_str.is_empty() {
+        format!("{} {}", grouped, symbol)
+    } else {
+        format!("{}{}{} {}", grouped, locale.decimal_separator, fractional_str, symbol)
+    }
+}
+
+/// Insert `separator` between groups of three digits, from the right
+pub(crate) fn group_thousands(digits: &str, separator: &str) -> String {
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3 * separator.len());
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            grouped.push_str(separator);
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ONE: u128 = 1_000_000_000_000_000_000;
+
+    #[test]
+    fn test_format_en() {
+        assert_eq!(format_token_amount_localized(1_234_567 * ONE + ONE / 4, "ATOM", 18, Locale::EN), "1,234,567.25 ATOM");
+        assert_eq!(format_token_amount_localized(999 * ONE, "ATOM", 18, Locale::EN), "999 ATOM");
+        assert_eq!(format_token_amount_localized(0, "ATOM", 18, Locale::EN), "0 ATOM");
+        assert_eq!(format_token_amount_localized(1, "ATOM", 18, Locale::EN), "0.000000000000000001 ATOM");
+    }
+
+    #[test]
+    fn test_format_de_and_fr() {
+        let amount = 1_234_567 * ONE + ONE / 4;
+        assert_eq!(format_token_amount_localized(amount, "ATOM", 18, Locale::DE), "1.234.567,25 ATOM");
+        assert_eq!(
+            format_token_amount_localized(amount, "ATOM", 18, Locale::FR),
+            "1\u{202f}234\u{202f}567,25 ATOM"
+        );
+        assert_eq!(format_token_amount_localized(1_500, "USDC", 2, Locale::DE), "15 USDC");
+    }
+
+    #[test]
+    fn test_parse_locale() {
+        assert_eq!(Locale::parse("de-AT"), Some(Locale::DE));
+        assert_eq!(Locale::parse("FR_ca"), Some(Locale::FR));
+        assert_eq!(Locale::parse("ja"), None);
+    }
+
+    #[test]
+    fn test_accept_language() {
+        assert_eq!(Locale::from_accept_language("fr-CH, fr;q=0.9, en;q=0.8"), Locale::FR);
+        assert_eq!(Locale::from_accept_language("ja, de;q=0.7, en;q=0.5"), Locale::DE);
+        assert_eq!(Locale::from_accept_language("en;q=0.4, de;q=0.9"), Locale::DE);
+        assert_eq!(Locale::from_accept_language("de;q=0, ja"), Locale::EN);
+        assert_eq!(Locale::from_accept_language(""), Locale::EN);
+    }
+}
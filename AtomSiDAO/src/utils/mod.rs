@@ -2,9 +2,32 @@
 //!
 //! This module provides utility functions and helpers for the DAO.
 
+pub mod amount;
+pub mod canonical;
+pub mod locale;
 pub mod time;
 pub mod validation;
 
+pub use amount::ApiAmount;
+pub use canonical::{canonical_json, to_canonical_json};
+
+/// Display options for [`format_token_amount_with`]
+///
+/// The decimal point is only shown when fractional digits follow it, so
+/// 0-decimal tokens and amounts trimmed to whole units have none.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FormatOptions {
+    /// Drop trailing zeros from the fractional part
+    pub trim_trailing_zeros: bool,
+    
+    /// Separator between groups of three whole digits, if any
+    pub thousands_separator: Option<char>,
+    
+    /// Most fractional digits to show; further digits are cut off, not
+    /// rounded, so the shown amount never exceeds the real one
+    pub max_decimals: Option<u8>,
+}
+
 /// Format an amount with token symbol
 ///
 /// # Examples
@@ -16,18 +39,63 @@ pub mod validation;
 /// let formatted = format_token_amount(amount, "ETH", 18);
 /// assert_eq!(formatted, "1.000000000000000000 ETH");
 /// ```
-pub fn format_token_amount(amount: u64, symbol: &str, decimals: u8) -> String {
-    let factor = 10u64.pow(decimals as u32);
-    let whole = amount / factor;
+pub fn format_token_amount(amount: ApiAmount, symbol: &str, decimals: u8) -> String {
+    format_token_amount_with(amount, symbol, decimals, FormatOptions::default())
+}
+
+/// Format an amount with token symbol using display options
+///
+/// # Examples
+///
+/// ```
+/// use atomsi_dao::utils::{format_token_amount_with, FormatOptions};
+///
+/// let options = FormatOptions {
+///     trim_trailing_zeros: true,
+///     thousands_separator: Some(','),
+///     max_decimals: None,
+/// };
+/// let formatted = format_token_amount_with(1_234_500_000_000_000_000, "ETH", 18, options);
+/// assert_eq!(formatted, "1.2345 ETH");
+/// ```
+pub fn format_token_amount_with(amount: ApiAmount, symbol: &str, decimals: u8, options: FormatOptions) -> String {
+    let factor = 10u128.pow(decimals as u32);
+    let whole = (amount / factor).to_string();
     let fractional = amount % factor;
     
-    let fractional_str = format!("{:0width$}", fractional, width = decimals as usize);
+    let mut formatted = match options.thousands_separator {
+        Some(separator) => locale::group_thousands(&whole, separator.encode_utf8(&mut [0; 4])),
+        None => whole,
+    };
+    
+    // A zero width still prints one digit, so 0 decimals needs no formatting
+    let fractional_str = if decimals == 0 {
+        String::new()
+    } else {
+        format!("{:0width$}", fractional, width = decimals as usize)
+    };
+    let mut fractional_str = fractional_str.as_str();
+    if let Some(max_decimals) = options.max_decimals {
+        fractional_str = &fractional_str[..fractional_str.len().min(max_decimals as usize)];
+    }
+    if options.trim_trailing_zeros {
+        fractional_str = fractional_str.trim_end_matches('0');
+    }
+    
+    if !fractional_str.is_empty() {
+        formatted.push('.');
+        formatted.push_str(fractional_str);
+    }
     
-    format!("{}.{} {}", whole, fractional_str, symbol)
+    format!("{} {}", formatted, symbol)
 }
 
 /// Parse a token amount from a string
 ///
+/// The first word is parsed with [`amount::parse_amount`], after accepting
+/// an optional leading `+` and a missing whole part (`.5`). Amounts that
+/// don't fit in an [`ApiAmount`] in base units are an error.
+///
 /// # Examples
 ///
 /// ```
@@ -37,47 +105,27 @@ pub fn format_token_amount(amount: u64, symbol: &str, decimals: u8) -> String {
 /// let parsed = parse_token_amount(amount_str, 18);
 /// assert_eq!(parsed, Ok(1500000000000000000));
 /// ```
-pub fn parse_token_amount(amount_str: &str, decimals: u8) -> Result<u64, String> {
+pub fn parse_token_amount(amount_str: &str, decimals: u8) -> Result<ApiAmount, String> {
     // Parse the string to extract the number
     let number_str = amount_str
         .split_whitespace()
         .next()
         .ok_or_else(|| "Invalid amount format".to_string())?;
+    let number_str = number_str.strip_prefix('+').unwrap_or(number_str);
     
-    // Split by decimal point
-    let parts: Vec<&str> = number_str.split('.').collect();
-    if parts.len() > 2 {
-        return Err("Invalid number format".to_string());
+    // `.5` has no whole part
+    if number_str.starts_with('.') {
+        return amount::parse_amount(&format!("0{}", number_str), decimals);
     }
     
-    let whole_part = parts[0].parse::<u64>().map_err(|e| e.to_string())?;
-    
-    let factor = 10u64.pow(decimals as u32);
-    let mut amount = whole_part * factor;
-    
-    if parts.len() == 2 {
-        let fractional_str = parts[1];
-        
-        // Ensure the fractional part is not too long
-        if fractional_str.len() > decimals as usize {
-            return Err(format!(
-                "Fractional part too long, maximum {} decimal places allowed",
-                decimals
-            ));
-        }
-        
-        // Parse the fractional part
-        let fractional_value = fractional_str.parse::<u64>().map_err(|e| e.to_string())?;
-        
-        // Add the fractional part
-        let fractional_factor = 10u64.pow((decimals - fractional_str.len() as u8) as u32);
-        amount += fractional_value * fractional_factor;
-    }
-    
-    Ok(amount)
+    amount::parse_amount(number_str, decimals)
 }
 
-/// Truncate a string to a maximum length with ellipsis
+/// Truncate a string to at most `max_length` characters, ending it with an
+/// ellipsis when it is cut
+///
+/// The ellipsis counts towards the length; below four characters there is
+/// no room for it and the text is simply cut.
 ///
 /// # Examples
 ///
@@ -85,15 +133,26 @@ pub fn parse_token_amount(amount_str: &str, decimals: u8) -> Result<u64, String>
 /// use atomsi_dao::utils::truncate_string;
 ///
 /// let text = "This is a very long text that should be truncated";
-/// let truncated = truncate_string(text, 20);
+/// let truncated = truncate_string(text, 22);
 /// assert_eq!(truncated, "This is a very long...");
 /// ```
 pub fn truncate_string(text: &str, max_length: usize) -> String {
-    if text.len() <= max_length {
-        text.to_string()
-    } else {
-        format!("{}...", &text[..max_length])
+    const ELLIPSIS: &str = "...";
+    
+    // Byte offset of the first character past the limit, if there is one
+    let Some((end, _)) = text.char_indices().nth(max_length) else {
+        return text.to_string();
+    };
+    
+    if max_length <= ELLIPSIS.len() {
+        return text[..end].to_string();
     }
+    
+    let (cut, _) = text
+        .char_indices()
+        .nth(max_length - ELLIPSIS.len())
+        .expect("text is longer than max_length");
+    format!("{}{}", &text[..cut], ELLIPSIS)
 }
 
 /// Check if a string is a valid hex string
@@ -165,7 +224,50 @@ mod tests {
         assert_eq!(format_token_amount(1000000, "USDC", 6), "1.000000 USDC");
         
         // Test with 0 decimals
-        assert_eq!(format_token_amount(100, "TOKEN", 0), "100. TOKEN");
+        assert_eq!(format_token_amount(100, "TOKEN", 0), "100 TOKEN");
+    }
+    
+    #[test]
+    fn test_format_token_amount_with_options() {
+        let trimmed = FormatOptions {
+            trim_trailing_zeros: true,
+            ..Default::default()
+        };
+        assert_eq!(format_token_amount_with(1_500_000_000_000_000_000, "ETH", 18, trimmed), "1.5 ETH");
+        assert_eq!(format_token_amount_with(2_000_000, "USDC", 6, trimmed), "2 USDC");
+        assert_eq!(format_token_amount_with(0, "ETH", 18, trimmed), "0 ETH");
+        assert_eq!(format_token_amount_with(100, "TOKEN", 0, trimmed), "100 TOKEN");
+        
+        // Thousands separators apply to whole units only
+        let grouped = FormatOptions {
+            thousands_separator: Some(','),
+            ..trimmed
+        };
+        assert_eq!(format_token_amount_with(1_234_567_250_000, "USDC", 6, grouped), "1,234,567.25 USDC");
+        assert_eq!(format_token_amount_with(999_000_000, "USDC", 6, grouped), "999 USDC");
+        assert_eq!(format_token_amount_with(1_000_000, "TOKEN", 0, grouped), "1,000,000 TOKEN");
+        
+        // Amounts past `u64::MAX` format as parsed
+        let large = parse_token_amount("1000000.25 ETH", 18).unwrap();
+        assert_eq!(format_token_amount_with(large, "ETH", 18, grouped), "1,000,000.25 ETH");
+        
+        // Extra decimals are cut off, and what remains is still trimmed
+        let short = FormatOptions {
+            max_decimals: Some(4),
+            ..grouped
+        };
+        assert_eq!(format_token_amount_with(1_000_099_999_999_999_999, "ETH", 18, short), "1 ETH");
+        assert_eq!(format_token_amount_with(1_234_567_890_000_000_000, "ETH", 18, short), "1.2345 ETH");
+        
+        let untrimmed = FormatOptions {
+            max_decimals: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(format_token_amount_with(1_500_000, "USDC", 6, untrimmed), "1.50 USDC");
+        assert_eq!(
+            format_token_amount_with(1_500_000, "USDC", 6, FormatOptions { max_decimals: Some(0), ..untrimmed }),
+            "1 USDC"
+        );
     }
     
     #[test]
@@ -184,10 +286,66 @@ mod tests {
         assert!(parse_token_amount("1.1.1", 18).is_err());
     }
     
+    #[test]
+    fn test_parse_large_token_amounts() {
+        // A million 18-decimal tokens is past `u64::MAX` in base units
+        assert_eq!(
+            parse_token_amount("1000000.25 ETH", 18),
+            Ok(1_000_000_250_000_000_000_000_000)
+        );
+        assert_eq!(
+            parse_token_amount("340282366920938463463.374607431768211455 ETH", 18),
+            Ok(u128::MAX)
+        );
+        
+        // One more base unit, or whole part, no longer fits
+        assert!(parse_token_amount("340282366920938463463.374607431768211456 ETH", 18)
+            .unwrap_err()
+            .contains("too large"));
+        assert!(parse_token_amount("340282366920938463464 ETH", 18)
+            .unwrap_err()
+            .contains("too large"));
+        assert!(parse_token_amount("1 ETH", 39).unwrap_err().contains("too large"));
+    }
+    
+    #[test]
+    fn test_parse_token_amount_signs_and_bare_fractions() {
+        assert_eq!(parse_token_amount(".5 ETH", 18), Ok(500000000000000000));
+        assert_eq!(parse_token_amount("+.5", 6), Ok(500000));
+        assert_eq!(parse_token_amount("  +2 USDC", 6), Ok(2000000));
+        
+        assert!(parse_token_amount(".", 6).is_err());
+        assert!(parse_token_amount("+", 6).is_err());
+        assert!(parse_token_amount("-1", 6).is_err());
+        assert!(parse_token_amount("++1", 6).is_err());
+        assert!(parse_token_amount("1.+5", 6).is_err());
+    }
+    
     #[test]
     fn test_truncate_string() {
         assert_eq!(truncate_string("short", 10), "short");
-        assert_eq!(truncate_string("this is a long string", 10), "this is a ...");
+        assert_eq!(truncate_string("this is a long string", 10), "this is...");
+        
+        // Exactly at the limit is kept whole; one past it is cut
+        assert_eq!(truncate_string("0123456789", 10), "0123456789");
+        assert_eq!(truncate_string("0123456789a", 10), "0123456...");
+        
+        // Too short for an ellipsis
+        assert_eq!(truncate_string("abcdef", 3), "abc");
+        assert_eq!(truncate_string("abcdef", 0), "");
+    }
+    
+    #[test]
+    fn test_truncate_string_multibyte() {
+        // Byte slicing would split these characters and panic
+        assert_eq!(truncate_string("Größenveränderung", 8), "Größe...");
+        assert_eq!(truncate_string("🚀🚀🚀🚀🚀🚀", 5), "🚀🚀...");
+        assert_eq!(truncate_string("日本語のテキスト", 2), "日本");
+        
+        // Length is counted in characters, not bytes
+        assert_eq!(truncate_string("café", 4), "café");
+        assert_eq!(truncate_string("🚀🚀🚀", 3), "🚀🚀🚀");
+        assert_eq!(truncate_string("🚀🚀🚀🚀", 3), "🚀🚀🚀");
     }
     
     #[test]
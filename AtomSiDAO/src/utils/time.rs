@@ -89,6 +89,62 @@ pub fn add_days(timestamp: u64, days: u64) -> u64 {
     timestamp + (days * 24 * 60 * 60)
 }
 
+/// Format a datetime as an RFC3339 string with second precision
+pub fn to_rfc3339(datetime: &DateTime<Utc>) -> String {
+    datetime.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+}
+
+/// Serde helpers for RFC3339 timestamp fields
+///
+/// Use with `#[serde(with = "crate::utils::time::rfc3339")]`, or the
+/// `option` submodule for `Option<DateTime<Utc>>` fields.
+pub mod rfc3339 {
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize, Deserializer, Serializer};
+    
+    /// Serialize a datetime as RFC3339
+    pub fn serialize<S: Serializer>(datetime: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&super::to_rfc3339(datetime))
+    }
+    
+    /// Deserialize a datetime from RFC3339
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DateTime<Utc>, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        DateTime::parse_from_rfc3339(&value)
+            .map(|datetime| datetime.with_timezone(&Utc))
+            .map_err(serde::de::Error::custom)
+    }
+    
+    /// Serde helpers for optional RFC3339 timestamp fields
+    pub mod option {
+        use chrono::{DateTime, Utc};
+        use serde::{Deserialize, Deserializer, Serializer};
+        
+        /// Serialize an optional datetime as RFC3339 or null
+        pub fn serialize<S: Serializer>(
+            datetime: &Option<DateTime<Utc>>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            match datetime {
+                Some(datetime) => super::serialize(datetime, serializer),
+                None => serializer.serialize_none(),
+            }
+        }
+        
+        /// Deserialize an optional datetime from RFC3339 or null
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<DateTime<Utc>>, D::Error> {
+            match Option::<String>::deserialize(deserializer)? {
+                Some(value) => DateTime::parse_from_rfc3339(&value)
+                    .map(|datetime| Some(datetime.with_timezone(&Utc)))
+                    .map_err(serde::de::Error::custom),
+                None => Ok(None),
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -165,4 +221,32 @@ mod tests {
         // 1 day = 86400 seconds
         assert_eq!(add_days(timestamp, 1), 87400);
     }
+    
+    #[derive(serde::Serialize, serde::Deserialize, PartialEq, Debug)]
+    struct Stamped {
+        #[serde(with = "rfc3339")]
+        at: DateTime<Utc>,
+        #[serde(with = "rfc3339::option")]
+        until: Option<DateTime<Utc>>,
+    }
+    
+    #[test]
+    fn test_rfc3339_round_trip() {
+        let stamped = Stamped {
+            at: timestamp_to_datetime(1609459200),
+            until: None,
+        };
+        
+        let json = serde_json::to_string(&stamped).unwrap();
+        assert_eq!(json, r#"{"at":"2021-01-01T00:00:00Z","until":null}"#);
+        assert_eq!(serde_json::from_str::<Stamped>(&json).unwrap(), stamped);
+        
+        // Offsets are normalized to UTC
+        let parsed: Stamped =
+            serde_json::from_str(r#"{"at":"2021-01-01T02:00:00+02:00","until":"2021-01-02T00:00:00Z"}"#).unwrap();
+        assert_eq!(parsed.at, stamped.at);
+        assert_eq!(parsed.until, Some(timestamp_to_datetime(1609545600)));
+        
+        assert!(serde_json::from_str::<Stamped>(r#"{"at":"1609459200","until":null}"#).is_err());
+    }
 } 
\ No newline at end of file
@@ -5,6 +5,8 @@
 use regex::Regex;
 use lazy_static::lazy_static;
 use crate::utils::is_valid_hex;
+use serde::Serialize;
+use sha3::{Digest, Keccak256};
 use std::collections::HashMap;
 
 lazy_static! {
@@ -22,7 +24,7 @@ lazy_static! {
 }
 
 /// Error type for validation
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ValidationError {
     /// Field that failed validation
     pub field: String,
@@ -59,16 +61,83 @@ pub fn is_valid_email(email: &str) -> bool {
 
 /// Validate an Ethereum address
 ///
+/// All-lowercase and all-uppercase hex are accepted; mixed-case addresses
+/// must carry a valid EIP-55 checksum.
+///
 /// # Examples
 ///
 /// ```
 /// use atomsi_dao::utils::validation::is_valid_eth_address;
 ///
 /// assert!(is_valid_eth_address("0x742d35Cc6634C0532925a3b844Bc454e4438f44e"));
+/// assert!(!is_valid_eth_address("0x742d35cC6634C0532925a3b844Bc454e4438f44e"));
 /// assert!(!is_valid_eth_address("invalid-address"));
 /// ```
 pub fn is_valid_eth_address(address: &str) -> bool {
-    ETH_ADDRESS_REGEX.is_match(address)
+    ETH_ADDRESS_REGEX.is_match(address) && (!is_mixed_case(&address[2..]) || is_checksummed(address))
+}
+
+/// Apply the EIP-55 checksum casing to an Ethereum address
+///
+/// Returns `None` unless the input is `0x` followed by 40 hex characters,
+/// in any case.
+///
+/// # Examples
+///
+/// ```
+/// use atomsi_dao::utils::validation::to_checksum_address;
+///
+/// assert_eq!(
+///     to_checksum_address("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed").as_deref(),
+///     Some("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed")
+/// );
+/// assert_eq!(to_checksum_address("0x1234"), None);
+/// ```
+pub fn to_checksum_address(address: &str) -> Option<String> {
+    if !ETH_ADDRESS_REGEX.is_match(address) {
+        return None;
+    }
+
+    let lower = address[2..].to_ascii_lowercase();
+    let hash = Keccak256::digest(lower.as_bytes());
+
+    let mut checksummed = String::with_capacity(42);
+    checksummed.push_str("0x");
+
+    // A letter is uppercase when the matching nibble of the hash is 8 or more
+    for (i, c) in lower.chars().enumerate() {
+        let nibble = if i % 2 == 0 { hash[i / 2] >> 4 } else { hash[i / 2] & 0x0f };
+        if c.is_ascii_alphabetic() && nibble >= 8 {
+            checksummed.push(c.to_ascii_uppercase());
+        } else {
+            checksummed.push(c);
+        }
+    }
+
+    Some(checksummed)
+}
+
+/// Check that an address is cased exactly as its EIP-55 checksum
+///
+/// Unlike [`is_valid_eth_address`], all-lowercase or all-uppercase input
+/// only passes if that happens to be its checksummed form.
+///
+/// # Examples
+///
+/// ```
+/// use atomsi_dao::utils::validation::is_checksummed;
+///
+/// assert!(is_checksummed("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"));
+/// assert!(!is_checksummed("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"));
+/// ```
+pub fn is_checksummed(address: &str) -> bool {
+    to_checksum_address(address).map_or(false, |checksummed| checksummed == address)
+}
+
+/// Whether hex digits mix lowercase and uppercase letters, which marks
+/// them as checksummed
+fn is_mixed_case(hex: &str) -> bool {
+    hex.chars().any(|c| c.is_ascii_lowercase()) && hex.chars().any(|c| c.is_ascii_uppercase())
 }
 
 /// Validate a URL
@@ -192,6 +261,8 @@ pub fn is_valid_password(password: &str) -> bool {
 
 /// Validate an address format (e.g., Ethereum address)
 ///
+/// Mixed-case addresses must carry a valid EIP-55 checksum.
+///
 /// # Examples
 ///
 /// ```
@@ -219,6 +290,14 @@ pub fn validate_address(address: &str) -> ValidationResult {
         )]);
     }
     
+    // All-lowercase and all-uppercase addresses carry no checksum
+    if is_mixed_case(addr) && !is_checksummed(address) {
+        return Err(vec![ValidationError::new(
+            "address",
+            "Address checksum is invalid (EIP-55)",
+        )]);
+    }
+    
     Ok(())
 }
 
@@ -414,6 +493,50 @@ mod tests {
         assert!(!is_valid_eth_address("0x742d35Cc6634C0532925a3b844Bc454e4438f44eX")); // too long
         assert!(!is_valid_eth_address("742d35Cc6634C0532925a3b844Bc454e4438f44e"));   // missing 0x
         assert!(!is_valid_eth_address("0xGHIJKLMNOPQRSTUVWXYZ0123456789abcdefghijkl")); // invalid chars
+        assert!(!is_valid_eth_address("0x742d35Cc6634C0532925a3b844Bc454e4438f44E"));  // bad checksum
+    }
+    
+    /// Test vectors from EIP-55
+    const EIP55_VECTORS: &[&str] = &[
+        // All caps
+        "0x52908400098527886E0F7030069857D2E4169EE7",
+        "0x8617E340B3D01FA5F11F306F4090FD50E238070D",
+        // All lower
+        "0xde709f2102306220921060314715629080e2fb77",
+        "0x27b1fdb04752bbc536007a920d24acb045561c26",
+        // Normal
+        "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+        "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+        "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+        "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+    ];
+    
+    #[test]
+    fn test_to_checksum_address() {
+        for vector in EIP55_VECTORS {
+            assert_eq!(to_checksum_address(&vector.to_lowercase()).as_deref(), Some(*vector));
+            assert_eq!(to_checksum_address(&format!("0x{}", vector[2..].to_uppercase())).as_deref(), Some(*vector));
+            assert!(is_checksummed(vector));
+        }
+        
+        assert_eq!(to_checksum_address("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAe"), None);
+        assert_eq!(to_checksum_address("5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed"), None);
+        assert!(!is_checksummed("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"));
+    }
+    
+    #[test]
+    fn test_validate_address_checksum() {
+        // Flip the case of one letter
+        let miscased = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD";
+        assert!(!is_checksummed(miscased));
+        let errors = validate_address(miscased).unwrap_err();
+        assert_eq!(errors[0].message, "Address checksum is invalid (EIP-55)");
+        assert!(!is_valid_eth_address(miscased));
+        
+        // Without mixed case there is no checksum to check
+        assert!(validate_address("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed").is_ok());
+        assert!(validate_address("0x5AAEB6053F3E94C9B9A09F33669435E7EF1BEAED").is_ok());
+        assert!(validate_address("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed").is_ok());
     }
     
     #[test]